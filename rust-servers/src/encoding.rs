@@ -0,0 +1,176 @@
+// WebSocket 消息编码 (JSON / MessagePack)
+// 握手时客户端以查询参数 `encoding=msgpack` 声明偏好，服务器在响应头 X-SW-Encoding 中确认。
+// MessagePack 连接的控制消息与 JSON 字段完全一致 (由同一 JSON 值直接序列化)，以二进制帧发送；
+// 为与 PTY 输出、音频旁路等原始二进制数据区分，该连接上所有二进制帧首字节为类型标记
+
+use thiserror::Error;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::outbound::Payload;
+
+/// 二进制帧类型标记：原始数据 (PTY 输入输出、音频)
+pub const DATA_TAG: u8 = 0x00;
+
+/// 二进制帧类型标记：MessagePack 编码的控制消息
+pub const CONTROL_TAG: u8 = 0x01;
+
+/// 确认编码的响应头
+pub const ENCODING_HEADER: &str = "X-SW-Encoding";
+
+/// 连接的消息编码
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// 控制消息为 JSON 文本帧，二进制帧为原始数据 (默认)
+    #[default]
+    Json,
+    /// 控制消息为带标记的 MessagePack 二进制帧
+    MessagePack,
+}
+
+/// 二进制帧解码错误
+#[derive(Debug, Error)]
+pub enum DecodeError {
+    #[error("二进制帧为空，缺少类型标记")]
+    Empty,
+
+    #[error("未知的二进制帧类型标记: {0:#04x}")]
+    UnknownTag(u8),
+
+    #[error("MessagePack 解码失败: {0}")]
+    MessagePack(#[from] rmp_serde::decode::Error),
+}
+
+/// 解码后的客户端二进制帧
+#[derive(Debug, PartialEq)]
+pub enum Incoming<'a> {
+    /// 控制消息 (已转换为 JSON 文本，与文本帧同样处理)
+    Control(String),
+    /// 原始数据 (已去掉类型标记)
+    Data(&'a [u8]),
+}
+
+impl Encoding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Json => "json",
+            Encoding::MessagePack => "msgpack",
+        }
+    }
+
+    /// 从握手请求的查询串 (如 `token=x&encoding=msgpack`) 读取编码偏好，未声明或无法识别时为 JSON
+    pub fn from_query(query: Option<&str>) -> Self {
+        query
+            .into_iter()
+            .flat_map(|query| query.split('&'))
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "encoding")
+            .map_or(Encoding::Json, |(_, value)| match value {
+                "msgpack" | "messagepack" => Encoding::MessagePack,
+                _ => Encoding::Json,
+            })
+    }
+
+    /// 按连接编码生成待发送的帧
+    ///
+    /// 控制消息在 JSON 连接上序列化为文本帧，在 MessagePack 连接上序列化为控制帧；
+    /// MessagePack 连接上的二进制帧加数据标记，其余帧原样发送
+    pub fn encode(&self, payload: Payload) -> Message {
+        match (self, payload) {
+            (Encoding::Json, Payload::Value(value)) => Message::Text(value.to_string().into()),
+            (Encoding::MessagePack, Payload::Value(value)) => match rmp_serde::to_vec_named(&value) {
+                Ok(payload) => Message::Binary(tagged(CONTROL_TAG, &payload).into()),
+                Err(e) => {
+                    eprintln!("[WARN] 消息无法编码为 MessagePack，按文本发送: {}", e);
+                    Message::Text(value.to_string().into())
+                }
+            },
+            (Encoding::MessagePack, Payload::Frame(Message::Binary(data))) => {
+                Message::Binary(tagged(DATA_TAG, &data).into())
+            }
+            (_, Payload::Frame(message)) => message,
+        }
+    }
+
+    /// 解码客户端发来的二进制帧：JSON 连接上整帧都是原始数据
+    pub fn decode_binary<'a>(&self, data: &'a [u8]) -> Result<Incoming<'a>, DecodeError> {
+        if *self == Encoding::Json {
+            return Ok(Incoming::Data(data));
+        }
+        match data.split_first() {
+            None => Err(DecodeError::Empty),
+            Some((&DATA_TAG, payload)) => Ok(Incoming::Data(payload)),
+            Some((&CONTROL_TAG, payload)) => {
+                let value: serde_json::Value = rmp_serde::from_slice(payload)?;
+                Ok(Incoming::Control(value.to_string()))
+            }
+            Some((&tag, _)) => Err(DecodeError::UnknownTag(tag)),
+        }
+    }
+}
+
+impl std::fmt::Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+fn tagged(tag: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 1);
+    frame.push(tag);
+    frame.extend_from_slice(payload);
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::{ModuleType, ServerResponse};
+
+    #[test]
+    fn test_negotiate_from_query() {
+        assert_eq!(Encoding::from_query(None), Encoding::Json);
+        assert_eq!(Encoding::from_query(Some("encoding=msgpack")), Encoding::MessagePack);
+        assert_eq!(Encoding::from_query(Some("token=abc&encoding=msgpack")), Encoding::MessagePack);
+        assert_eq!(Encoding::from_query(Some("encoding=cbor")), Encoding::Json);
+        assert_eq!(Encoding::from_query(Some("xencoding=msgpack")), Encoding::Json);
+    }
+
+    #[test]
+    fn test_msgpack_round_trip_matches_json() {
+        let response = ServerResponse::new(ModuleType::Voice, "waveform", serde_json::json!({
+            "peaks": [0.1, -0.25, 1.0],
+            "seq": 42,
+            "final": false,
+            "text": "你好",
+            "meta": { "rate": 16000, "label": null },
+        }));
+        let value = serde_json::to_value(&response).unwrap();
+        let json = value.to_string();
+
+        // 控制消息直接序列化为带标记的二进制帧，解码后与 JSON 的值一致
+        let Message::Binary(frame) = Encoding::MessagePack.encode(Payload::Value(value.clone())) else {
+            panic!("应编码为二进制帧");
+        };
+        assert_eq!(frame[0], CONTROL_TAG);
+        let Incoming::Control(decoded) = Encoding::MessagePack.decode_binary(&frame).unwrap() else {
+            panic!("应解码为控制消息");
+        };
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&decoded).unwrap(), value);
+        assert!(frame.len() < json.len());
+
+        // 原始二进制数据加数据标记，与控制帧区分
+        let Message::Binary(data) = Encoding::MessagePack.encode(Payload::Frame(Message::Binary(vec![1u8, 2, 3].into()))) else {
+            panic!("应保持二进制帧");
+        };
+        assert_eq!(&data[..], &[DATA_TAG, 1, 2, 3]);
+        assert_eq!(Encoding::MessagePack.decode_binary(&data).unwrap(), Incoming::Data(&[1, 2, 3]));
+        assert!(matches!(Encoding::MessagePack.decode_binary(&[0x7f]), Err(DecodeError::UnknownTag(0x7f))));
+        assert!(matches!(Encoding::MessagePack.decode_binary(&[]), Err(DecodeError::Empty)));
+
+        // JSON 连接上控制消息为文本帧，二进制帧不做转换
+        assert_eq!(Encoding::Json.encode(Payload::Value(value)), Message::Text(json.into()));
+        let pong = Message::Pong(vec![7u8].into());
+        assert_eq!(Encoding::MessagePack.encode(Payload::Frame(pong.clone())), pong);
+        assert_eq!(Encoding::Json.decode_binary(&[1, 2]).unwrap(), Incoming::Data(&[1, 2]));
+    }
+}
@@ -0,0 +1,266 @@
+// 二进制帧头与块序号 (sw-voice.v2)
+// v2 连接的二进制帧以 8 字节帧头开始：序号 (u32 大端) + 负载长度 (u32 大端)，之后为负载。
+// 服务器每收到一块回 chunk_ack，客户端据此重传未确认的块。序号跳变后的块先缓存在重排窗口里，
+// 缺失块重传补齐后按序写出；窗口放不下时放弃等待最旧的缺失块 (记为丢失)，之后再收到的丢弃且不确认。
+// 二进制帧只写入主会话，序号按主会话跟踪，主会话重建后重新以第一块为起点
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use thiserror::Error;
+
+/// 帧头长度 (字节)
+pub const HEADER_LEN: usize = 8;
+
+/// 重排窗口 (块数)：只缓存序号在 [期望序号, 期望序号 + 窗口) 内的块
+const REORDER_WINDOW: u32 = 256;
+
+/// 最多记住的已放弃序号数，更早的序号无法区分是否写入过，一律视为过期
+const MAX_EXPIRED_TRACKED: usize = 1024;
+
+/// 帧头解析错误
+#[derive(Debug, Error, PartialEq)]
+pub enum FrameError {
+    #[error("帧头不完整: 仅 {0} 字节 (需要 {HEADER_LEN} 字节)")]
+    TooShort(usize),
+
+    #[error("负载长度不符: 帧头声明 {declared} 字节，实际 {actual} 字节")]
+    LengthMismatch { declared: usize, actual: usize },
+}
+
+/// 解析二进制帧，返回序号与负载
+pub fn parse_chunk(data: &[u8]) -> Result<(u32, &[u8]), FrameError> {
+    if data.len() < HEADER_LEN {
+        return Err(FrameError::TooShort(data.len()));
+    }
+    let seq = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+    let declared = u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as usize;
+    let payload = &data[HEADER_LEN..];
+    if declared != payload.len() {
+        return Err(FrameError::LengthMismatch { declared, actual: payload.len() });
+    }
+    Ok((seq, payload))
+}
+
+/// 编码二进制帧 (客户端格式，供测试与调试工具使用)
+#[allow(dead_code)]
+pub fn encode_chunk(seq: u32, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(HEADER_LEN + payload.len());
+    frame.extend_from_slice(&seq.to_be_bytes());
+    frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// 收到的块相对于期望序号的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkOrder {
+    /// 正是期望的下一块 (含第一块)，没有等待中的缺失块
+    InOrder,
+    /// 序号超前，缓存到缺失块补齐；`missing` 为当前仍缺的块数
+    Buffered { missing: u32 },
+    /// 补到了缺失的块 (客户端重传)，与之后已缓存的连续块一起写出
+    Late,
+    /// 已写入或已缓存的块 (客户端未收到确认而重传)，负载丢弃，但可再次确认
+    Duplicate,
+    /// 已放弃等待或早于跟踪范围的块，负载丢弃且不确认
+    Expired,
+}
+
+/// 一块的处理结果
+#[derive(Debug, PartialEq)]
+pub struct Accepted<'a> {
+    pub order: ChunkOrder,
+    /// 现在可以按序写出的负载 (可能包含之前缓存的块)
+    pub ready: Vec<Cow<'a, [u8]>>,
+}
+
+/// 跟踪块序号并按序交付负载
+#[derive(Debug, Default)]
+pub struct ChunkSequencer {
+    /// 期望交付的下一个序号 (尚未收到任何块时为 None，以第一块的序号为起点)
+    next: Option<u32>,
+    /// 序号超前、等待缺失块的已收块
+    pending: BTreeMap<u32, Vec<u8>>,
+    /// 跟踪范围的起点，更早的序号视为过期
+    floor: u32,
+    /// 已放弃等待的序号 (最近 MAX_EXPIRED_TRACKED 个)
+    abandoned: BTreeSet<u32>,
+    /// 已放弃等待的块数
+    expired: u64,
+}
+
+impl ChunkSequencer {
+    /// 记录收到的块，返回其位置与现在可以写出的负载
+    pub fn accept<'a>(&mut self, seq: u32, payload: &'a [u8]) -> Accepted<'a> {
+        let mut ready = Vec::new();
+        let next = *self.next.get_or_insert_with(|| {
+            self.floor = seq;
+            seq
+        });
+
+        let order = if seq < next {
+            if seq < self.floor || self.abandoned.contains(&seq) {
+                ChunkOrder::Expired
+            } else {
+                ChunkOrder::Duplicate
+            }
+        } else if self.pending.contains_key(&seq) {
+            ChunkOrder::Duplicate
+        } else if seq == next {
+            let order = if self.pending.is_empty() { ChunkOrder::InOrder } else { ChunkOrder::Late };
+            ready.push(Cow::Borrowed(payload));
+            self.next = Some(seq.wrapping_add(1));
+            self.flush(&mut ready);
+            order
+        } else {
+            // 超出窗口时放弃最旧的缺失块，让窗口前移到能容纳这一块
+            if seq - next >= REORDER_WINDOW {
+                self.abandon_until(seq - (REORDER_WINDOW - 1), &mut ready);
+            }
+            self.pending.insert(seq, payload.to_vec());
+            self.flush(&mut ready);
+            if self.pending.is_empty() {
+                ChunkOrder::Late
+            } else {
+                ChunkOrder::Buffered { missing: self.missing() }
+            }
+        };
+        Accepted { order, ready }
+    }
+
+    /// 把从期望序号开始连续的已缓存块移入 ready
+    fn flush<'a>(&mut self, ready: &mut Vec<Cow<'a, [u8]>>) {
+        let Some(mut next) = self.next else {
+            return;
+        };
+        while let Some(payload) = self.pending.remove(&next) {
+            ready.push(Cow::Owned(payload));
+            next = next.wrapping_add(1);
+        }
+        self.next = Some(next);
+    }
+
+    /// 不再等待 until 之前的缺失块：已缓存的按序写出，其余记为丢失
+    fn abandon_until<'a>(&mut self, until: u32, ready: &mut Vec<Cow<'a, [u8]>>) {
+        let Some(next) = self.next else {
+            return;
+        };
+        let kept = self.pending.split_off(&until);
+        let released = std::mem::replace(&mut self.pending, kept);
+        let mut cursor = next;
+        for (seq, payload) in released {
+            self.mark_abandoned(cursor, seq);
+            ready.push(Cow::Owned(payload));
+            cursor = seq.wrapping_add(1);
+        }
+        self.mark_abandoned(cursor, until);
+        self.next = Some(until);
+    }
+
+    /// 把 [from, to) 记为已放弃，超出记录上限时最旧的移出跟踪范围
+    fn mark_abandoned(&mut self, from: u32, to: u32) {
+        if from >= to {
+            return;
+        }
+        self.expired += (to - from) as u64;
+        // 跳变本身超过上限时，只记住最近的部分
+        let tracked_from = to.saturating_sub(MAX_EXPIRED_TRACKED as u32).max(from);
+        if tracked_from > from {
+            self.floor = tracked_from;
+            self.abandoned.clear();
+        }
+        self.abandoned.extend(tracked_from..to);
+        while self.abandoned.len() > MAX_EXPIRED_TRACKED {
+            if let Some(oldest) = self.abandoned.pop_first() {
+                self.floor = oldest + 1;
+            }
+        }
+    }
+
+    /// 窗口内仍在等待重传的块数
+    fn missing(&self) -> u32 {
+        match (self.next, self.pending.last_key_value()) {
+            (Some(next), Some((&last, _))) => last - next + 1 - self.pending.len() as u32,
+            _ => 0,
+        }
+    }
+
+    /// 尚未补到的块数 (等待重传的缺失块与已放弃的块)
+    pub fn lost(&self) -> u64 {
+        self.missing() as u64 + self.expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 送入一块，返回位置与写出的负载
+    fn feed(sequencer: &mut ChunkSequencer, seq: u32, payload: &[u8]) -> (ChunkOrder, Vec<Vec<u8>>) {
+        let accepted = sequencer.accept(seq, payload);
+        (accepted.order, accepted.ready.into_iter().map(Cow::into_owned).collect())
+    }
+
+    #[test]
+    fn test_parse_chunk_and_track_sequence() {
+        let frame = encode_chunk(7, b"ls\n");
+        assert_eq!(parse_chunk(&frame), Ok((7, &b"ls\n"[..])));
+        assert_eq!(parse_chunk(&encode_chunk(0, b"")), Ok((0, &b""[..])));
+        assert_eq!(parse_chunk(b"\x00\x01"), Err(FrameError::TooShort(2)));
+        let mut truncated = encode_chunk(1, b"abcd");
+        truncated.pop();
+        assert_eq!(parse_chunk(&truncated), Err(FrameError::LengthMismatch { declared: 4, actual: 3 }));
+
+        // 以第一块为起点，跳变后的块先缓存，重传的旧块视为重复
+        let mut sequencer = ChunkSequencer::default();
+        assert_eq!(feed(&mut sequencer, 5, b"a"), (ChunkOrder::InOrder, vec![b"a".to_vec()]));
+        assert_eq!(feed(&mut sequencer, 6, b"b"), (ChunkOrder::InOrder, vec![b"b".to_vec()]));
+        assert_eq!(feed(&mut sequencer, 9, b"e"), (ChunkOrder::Buffered { missing: 2 }, vec![]));
+        assert_eq!(feed(&mut sequencer, 6, b"b").0, ChunkOrder::Duplicate);
+        assert_eq!(feed(&mut sequencer, 9, b"e").0, ChunkOrder::Duplicate);
+        assert_eq!(feed(&mut sequencer, 10, b"f"), (ChunkOrder::Buffered { missing: 2 }, vec![]));
+        assert_eq!(sequencer.lost(), 2);
+        assert_eq!(feed(&mut sequencer, 4, b"").0, ChunkOrder::Expired);
+    }
+
+    #[test]
+    fn test_late_retransmission_flushes_in_order() {
+        let mut sequencer = ChunkSequencer::default();
+        assert_eq!(feed(&mut sequencer, 0, b"l").0, ChunkOrder::InOrder);
+        assert_eq!(feed(&mut sequencer, 3, b"\n").0, ChunkOrder::Buffered { missing: 2 });
+
+        // 补到的块若不是期望的下一块，也先缓存
+        assert_eq!(feed(&mut sequencer, 2, b"s"), (ChunkOrder::Buffered { missing: 1 }, vec![]));
+        assert_eq!(sequencer.lost(), 1);
+        assert_eq!(
+            feed(&mut sequencer, 1, b" "),
+            (ChunkOrder::Late, vec![b" ".to_vec(), b"s".to_vec(), b"\n".to_vec()])
+        );
+        assert_eq!(sequencer.lost(), 0);
+        assert_eq!(feed(&mut sequencer, 2, b"s").0, ChunkOrder::Duplicate);
+        assert_eq!(feed(&mut sequencer, 4, b"x").0, ChunkOrder::InOrder);
+    }
+
+    #[test]
+    fn test_reorder_window_abandons_oldest_missing() {
+        let mut sequencer = ChunkSequencer::default();
+        assert_eq!(feed(&mut sequencer, 0, b"a").0, ChunkOrder::InOrder);
+        assert_eq!(feed(&mut sequencer, 2, b"c").0, ChunkOrder::Buffered { missing: 1 });
+
+        // 超出窗口的块迫使窗口前移：放弃 1，写出缓存的 2，之后的缺失块继续等待
+        let far = 2 + REORDER_WINDOW;
+        let (order, ready) = feed(&mut sequencer, far, b"z");
+        assert_eq!(ready, vec![b"c".to_vec()]);
+        assert_eq!(order, ChunkOrder::Buffered { missing: REORDER_WINDOW - 1 });
+        assert_eq!(sequencer.lost(), 1 + (REORDER_WINDOW - 1) as u64);
+        assert_eq!(feed(&mut sequencer, 1, b"b").0, ChunkOrder::Expired);
+        assert_eq!(feed(&mut sequencer, 2, b"c").0, ChunkOrder::Duplicate);
+
+        // 跳变远超记录上限时，只记住最近放弃的序号
+        let jump = far + 2 * MAX_EXPIRED_TRACKED as u32;
+        let (_, ready) = feed(&mut sequencer, jump, b"y");
+        assert_eq!(ready, vec![b"z".to_vec()]);
+        assert_eq!(feed(&mut sequencer, far - 1, b"").0, ChunkOrder::Expired);
+        assert_eq!(feed(&mut sequencer, jump - 1, b"x").0, ChunkOrder::Buffered { missing: REORDER_WINDOW - 2 });
+    }
+}
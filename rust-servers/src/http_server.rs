@@ -0,0 +1,372 @@
+// HTTP SSE 服务器
+// 为不便使用 WebSocket 的简单客户端提供转录接口：
+// POST 上传音频，以 Server-Sent Events 流返回 partial/final/error 事件；n-best 接口直接返回候选列表 JSON。
+// 另提供存活 (/healthz) 与就绪 (/readyz) 探针，供容器编排做健康检查
+
+use base64::Engine;
+use bytes::Bytes;
+use http_body_util::{combinators::UnsyncBoxBody, BodyExt, Full, Limited, StreamBody};
+use hyper::body::{Frame, Incoming};
+use hyper::header::{HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::convert::Infallible;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::server::ConnectionLimiter;
+use crate::voice::asr::{warmup, ASRError};
+use crate::voice::audio::decode_wav;
+use crate::voice::config::ASRConfig;
+use crate::voice::upload::{transcribe_nbest, transcribe_upload, UploadEvent};
+
+/// 日志宏
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        eprintln!("[INFO] [HTTP] {}", format!($($arg)*));
+    };
+}
+
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        eprintln!("[ERROR] [HTTP] {}", format!($($arg)*));
+    };
+}
+
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions) {
+            eprintln!("[DEBUG] [HTTP] {}", format!($($arg)*));
+        }
+    };
+}
+
+/// 转录接口路径
+pub const TRANSCRIBE_PATH: &str = "/v1/voice/transcribe";
+
+/// n-best 转录接口路径：返回按分数降序的多个候选结果
+pub const NBEST_PATH: &str = "/v1/voice/transcribe/nbest";
+
+/// n-best 接口默认候选数
+const DEFAULT_NBEST: usize = 5;
+
+/// 存活探针路径：进程能响应即返回 200
+pub const HEALTH_PATH: &str = "/healthz";
+
+/// 就绪探针路径：引擎预热完成且连接数未满时返回 200，否则 503
+pub const READY_PATH: &str = "/readyz";
+
+/// 心跳注释行间隔 (防止代理或客户端因空闲断开)
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// 请求体大小上限 (base64 编码后的音频)
+const MAX_BODY_BYTES: usize = 64 * 1024 * 1024;
+
+/// SSE 输出缓冲 (事件数)
+const SSE_BUFFER: usize = 32;
+
+type ResponseBody = UnsyncBoxBody<Bytes, Infallible>;
+
+/// 转录请求
+#[derive(Debug, serde::Deserialize)]
+struct TranscribeRequest {
+    /// base64 编码的 16 位 PCM WAV
+    audio: String,
+    asr_config: ASRConfig,
+    /// n-best 接口的候选数 (默认 5)
+    #[serde(default)]
+    n: Option<usize>,
+}
+
+/// 启动 HTTP 服务器，返回实际监听端口
+///
+/// `connections` 为 WebSocket 服务器的连接计数，供健康检查报告
+pub async fn start(port: u16, connections: ConnectionLimiter) -> Result<u16, Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(format!("127.0.0.1:{}", port)).await?;
+    let port = listener.local_addr()?.port();
+    started_at();
+    log_info!("HTTP SSE 接口: http://127.0.0.1:{}{}", port, TRANSCRIBE_PATH);
+    log_info!("健康检查: http://127.0.0.1:{}{} (存活) / {} (就绪)", port, HEALTH_PATH, READY_PATH);
+
+    tokio::spawn(async move {
+        while let Ok((stream, addr)) = listener.accept().await {
+            log_debug!("接受来自 {} 的 HTTP 连接", addr);
+            let connections = connections.clone();
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(move |request| handle_request(request, connections.clone()));
+                if let Err(e) = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await
+                {
+                    log_error!("HTTP 连接处理错误: {}", e);
+                }
+            });
+        }
+    });
+
+    Ok(port)
+}
+
+/// 服务启动时间 (首次调用时记录)
+fn started_at() -> Instant {
+    static STARTED_AT: OnceLock<Instant> = OnceLock::new();
+    *STARTED_AT.get_or_init(Instant::now)
+}
+
+async fn handle_request(
+    request: Request<Incoming>,
+    connections: ConnectionLimiter,
+) -> Result<Response<ResponseBody>, Infallible> {
+    match request.uri().path() {
+        HEALTH_PATH => return Ok(liveness(&connections)),
+        READY_PATH => return Ok(readiness(&connections)),
+        TRANSCRIBE_PATH | NBEST_PATH => {}
+        _ => return Ok(json_error(StatusCode::NOT_FOUND, "NOT_FOUND", "接口不存在".to_string())),
+    }
+    let nbest = request.uri().path() == NBEST_PATH;
+    if request.method() != Method::POST {
+        return Ok(json_error(StatusCode::METHOD_NOT_ALLOWED, "METHOD_NOT_ALLOWED", "仅支持 POST".to_string()));
+    }
+
+    let body = match Limited::new(request.into_body(), MAX_BODY_BYTES).collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(e) => {
+            let status = if e.is::<http_body_util::LengthLimitError>() {
+                StatusCode::PAYLOAD_TOO_LARGE
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+            return Ok(json_error(status, "INVALID_REQUEST", format!("读取请求体失败: {}", e)));
+        }
+    };
+
+    match parse_request(&body) {
+        Ok((audio, asr_config, n)) if nbest => {
+            let n = n.unwrap_or(DEFAULT_NBEST).max(1);
+            log_info!("收到 n-best 转录请求，时长: {}ms，候选数: {}", audio.duration_ms, n);
+            Ok(nbest_response(transcribe_nbest(audio, &asr_config, n).await))
+        }
+        Ok((audio, asr_config, _)) => {
+            log_info!("收到上传音频转录请求，时长: {}ms", audio.duration_ms);
+            Ok(sse_response(audio, asr_config))
+        }
+        Err(message) => Ok(json_error(StatusCode::BAD_REQUEST, "INVALID_REQUEST", message)),
+    }
+}
+
+/// 解析请求体为音频、ASR 配置与 n-best 候选数
+fn parse_request(body: &[u8]) -> Result<(crate::voice::audio::AudioData, ASRConfig, Option<usize>), String> {
+    let request: TranscribeRequest =
+        serde_json::from_slice(body).map_err(|e| format!("请求体不是合法的 JSON: {}", e))?;
+    let wav = base64::engine::general_purpose::STANDARD
+        .decode(request.audio.trim())
+        .map_err(|e| format!("audio 不是合法的 base64: {}", e))?;
+    let audio = decode_wav(&wav).map_err(|e| format!("无法解码 WAV 音频: {}", e))?;
+    Ok((audio, request.asr_config, request.n))
+}
+
+/// n-best 结果：引擎不支持时返回 501，配置错误 400，其余失败 502
+fn nbest_response(result: Result<Vec<(String, f32)>, ASRError>) -> Response<ResponseBody> {
+    match result {
+        Ok(candidates) => {
+            let alternatives: Vec<_> = candidates
+                .into_iter()
+                .map(|(text, score)| serde_json::json!({ "text": text, "score": score }))
+                .collect();
+            json_response(StatusCode::OK, serde_json::json!({ "alternatives": alternatives }))
+        }
+        Err(e @ ASRError::UnsupportedOperation(_)) => {
+            json_error(StatusCode::NOT_IMPLEMENTED, "NBEST_UNSUPPORTED", e.to_string())
+        }
+        Err(e @ ASRError::ConfigError(_)) => json_error(StatusCode::BAD_REQUEST, "INVALID_REQUEST", e.to_string()),
+        Err(e) => {
+            log_error!("n-best 转录失败: {}", e);
+            json_error(StatusCode::BAD_GATEWAY, "TRANSCRIPTION_FAILED", e.to_string())
+        }
+    }
+}
+
+/// 启动转录并以 SSE 流返回事件
+///
+/// 客户端断开后响应体被丢弃，转发任务随之结束并取消转录
+fn sse_response(audio: crate::voice::audio::AudioData, asr_config: ASRConfig) -> Response<ResponseBody> {
+    let (body_tx, body_rx) = mpsc::channel::<Bytes>(SSE_BUFFER);
+
+    tokio::spawn(async move {
+        let cancel_token = CancellationToken::new();
+        let _cancel_on_exit = cancel_token.clone().drop_guard();
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        tokio::spawn(transcribe_upload(audio, asr_config, event_tx, cancel_token.child_token()));
+
+        let mut heartbeat = tokio::time::interval_at(
+            tokio::time::Instant::now() + HEARTBEAT_INTERVAL,
+            HEARTBEAT_INTERVAL,
+        );
+        loop {
+            let chunk = tokio::select! {
+                event = event_rx.recv() => match event {
+                    Some(event) => sse_event(&event),
+                    // 转录结束，发送方已释放
+                    None => break,
+                },
+                _ = heartbeat.tick() => Bytes::from_static(b": heartbeat\n\n"),
+                _ = body_tx.closed() => {
+                    log_info!("客户端已断开，取消转录");
+                    break;
+                }
+            };
+            if body_tx.send(chunk).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let stream = futures_util::stream::unfold(body_rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (Ok(Frame::data(chunk)), rx))
+    });
+
+    let mut response = Response::new(StreamBody::new(stream).boxed_unsync());
+    let headers = response.headers_mut();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream; charset=utf-8"));
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    // 关闭反向代理 (如 nginx) 的响应缓冲，保证事件及时送达
+    headers.insert("X-Accel-Buffering", HeaderValue::from_static("no"));
+    response
+}
+
+/// 编码为一个 SSE 事件 (JSON 序列化结果不含换行，单行 data 即可)
+fn sse_event(event: &UploadEvent) -> Bytes {
+    Bytes::from(format!("event: {}\ndata: {}\n\n", event.name(), event.data()))
+}
+
+/// 存活探针：进程在运行
+fn liveness(connections: &ConnectionLimiter) -> Response<ResponseBody> {
+    json_response(StatusCode::OK, serde_json::json!({
+        "status": "ok",
+        "uptime_secs": started_at().elapsed().as_secs(),
+        "active_connections": connections.active(),
+    }))
+}
+
+/// 就绪探针：没有正在预热或全部失败的引擎，且连接数未达上限
+fn readiness(connections: &ConnectionLimiter) -> Response<ResponseBody> {
+    let engines = warmup::snapshot();
+    let ready = warmup::engines_ready(&engines) && !connections.is_full();
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    json_response(status, serde_json::json!({
+        "status": if ready { "ready" } else { "not_ready" },
+        "active_connections": connections.active(),
+        "max_connections": connections.max(),
+        "engines": engines,
+    }))
+}
+
+fn json_error(status: StatusCode, code: &str, message: String) -> Response<ResponseBody> {
+    json_response(status, serde_json::json!({ "code": code, "message": message }))
+}
+
+fn json_response(status: StatusCode, body: serde_json::Value) -> Response<ResponseBody> {
+    let mut response = Response::new(Full::new(Bytes::from(body.to_string())).boxed_unsync());
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voice::audio::encode_i16_to_wav;
+
+    #[test]
+    fn test_sse_event_format() {
+        let event = UploadEvent::Partial(serde_json::json!({ "partial_text": "你好\n世界" }));
+        assert_eq!(
+            sse_event(&event),
+            Bytes::from("event: partial\ndata: {\"partial_text\":\"你好\\n世界\"}\n\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_endpoint_streams_events() {
+        let port = start(0, ConnectionLimiter::new(None)).await.unwrap();
+        let url = format!("http://127.0.0.1:{}{}", port, TRANSCRIBE_PATH);
+        let client = reqwest::Client::new();
+
+        assert_eq!(client.get(&url).send().await.unwrap().status(), 405);
+        let response = client.post(&url).body("not json").send().await.unwrap();
+        assert_eq!(response.status(), 400);
+
+        // 缺少 API Key，引擎创建失败，以 error 事件返回
+        let wav = encode_i16_to_wav(&vec![0i16; 1600], 16000, 1).unwrap();
+        let body = serde_json::json!({
+            "audio": base64::engine::general_purpose::STANDARD.encode(wav),
+            "asr_config": {
+                "primary": { "provider": "qwen", "mode": "http", "dashscope_api_key": "" },
+                "enable_fallback": false,
+            },
+        });
+        let response = client.post(&url).json(&body).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()[CONTENT_TYPE.as_str()], "text/event-stream; charset=utf-8");
+
+        let text = response.text().await.unwrap();
+        assert!(text.starts_with("event: error\ndata: {"), "{}", text);
+        assert!(text.ends_with("}\n\n"));
+    }
+
+    #[tokio::test]
+    async fn test_nbest_endpoint() {
+        let port = start(0, ConnectionLimiter::new(None)).await.unwrap();
+        let url = format!("http://127.0.0.1:{}{}", port, NBEST_PATH);
+        let client = reqwest::Client::new();
+        let tone: Vec<i16> = (0..8000).map(|i| ((i as f32 * 0.17).sin() * 8000.0) as i16).collect();
+        let audio = base64::engine::general_purpose::STANDARD.encode(encode_i16_to_wav(&tone, 16000, 1).unwrap());
+
+        // 缺少 API Key，配置错误
+        let body = serde_json::json!({
+            "audio": audio,
+            "asr_config": { "primary": { "provider": "qwen", "mode": "http", "dashscope_api_key": "" }, "enable_fallback": false },
+        });
+        let response = client.post(&url).json(&body).send().await.unwrap();
+        assert_eq!(response.status(), 400);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert!(body["message"].as_str().unwrap().contains("API"), "{}", body);
+
+        // 通义千问 HTTP 引擎不提供候选结果
+        let body = serde_json::json!({
+            "audio": audio,
+            "asr_config": { "primary": { "provider": "qwen", "mode": "http", "dashscope_api_key": "sk-test" }, "enable_fallback": false },
+            "n": 3,
+        });
+        let response = client.post(&url).json(&body).send().await.unwrap();
+        assert_eq!(response.status(), 501);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "NBEST_UNSUPPORTED");
+    }
+
+    #[tokio::test]
+    async fn test_health_probes() {
+        // 上限为 0 的连接计数始终已满，就绪探针返回 503
+        let port = start(0, ConnectionLimiter::new(Some(0))).await.unwrap();
+        let client = reqwest::Client::new();
+
+        let response = client.get(format!("http://127.0.0.1:{}{}", port, HEALTH_PATH)).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!((body["status"].as_str(), body["active_connections"].as_u64()), (Some("ok"), Some(0)));
+
+        let response = client.get(format!("http://127.0.0.1:{}{}", port, READY_PATH)).send().await.unwrap();
+        assert_eq!(response.status(), 503);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!((body["status"].as_str(), body["max_connections"].as_u64()), (Some("not_ready"), Some(0)));
+        assert!(body["engines"].is_object());
+
+        let response = client.get(format!("http://127.0.0.1:{}/unknown", port)).send().await.unwrap();
+        assert_eq!(response.status(), 404);
+    }
+}
@@ -14,7 +14,6 @@ use serde::{Deserialize, Serialize};
 use crate::router::{ModuleHandler, ModuleMessage, ModuleType, RouterError, ServerResponse};
 use crate::server::WsSender;
 
-use futures_util::SinkExt;
 
 use self::sse_parser::{SSEParser, SSEEvent};
 use self::thinking::StreamingThinkingFilter;
@@ -141,6 +140,8 @@ pub struct LLMHandler {
     ws_sender: Arc<TokioMutex<Option<WsSender>>>,
     /// 当前请求的取消令牌
     cancel_token: Arc<TokioMutex<Option<CancellationToken>>>,
+    /// 连接级取消令牌 (请求令牌为其子令牌)
+    connection_token: TokioMutex<CancellationToken>,
     /// HTTP 客户端
     http_client: reqwest::Client,
 }
@@ -151,6 +152,7 @@ impl LLMHandler {
         Self {
             ws_sender: Arc::new(TokioMutex::new(None)),
             cancel_token: Arc::new(TokioMutex::new(None)),
+            connection_token: TokioMutex::new(CancellationToken::new()),
             http_client: reqwest::Client::new(),
         }
     }
@@ -161,12 +163,18 @@ impl LLMHandler {
         *ws = Some(sender);
     }
     
+    /// 设置连接级取消令牌
+    pub async fn set_connection_token(&self, token: CancellationToken) {
+        *self.connection_token.lock().await = token;
+    }
+    
     /// 开始流式请求
     async fn start_stream(&self, config: StreamConfig) -> Result<(), LLMError> {
         log_info!("开始流式请求: endpoint={}", config.endpoint);
         
-        // 创建取消令牌
-        let cancel_token = CancellationToken::new();
+        // 创建取消令牌 (连接关闭时随之取消)
+        let connection_token = self.connection_token.lock().await.clone();
+        let cancel_token = connection_token.child_token();
         {
             let mut token = self.cancel_token.lock().await;
             *token = Some(cancel_token.clone());
@@ -200,6 +208,10 @@ impl LLMHandler {
             ).await;
             
             if let Err(e) = result {
+                if connection_token.is_cancelled() {
+                    log_info!("连接已关闭，流式请求结束");
+                    return;
+                }
                 log_error!("流式请求失败: {}", e);
                 // 发送错误消息
                 let _ = Self::send_error(&ws_sender, &e, request_id.as_deref()).await;
@@ -210,6 +222,7 @@ impl LLMHandler {
     }
     
     /// 执行流式请求
+    #[allow(clippy::too_many_arguments)]
     async fn execute_stream(
         client: reqwest::Client,
         endpoint: String,
@@ -413,11 +426,10 @@ impl LLMHandler {
             request_id: request_id.map(|s| s.to_string()),
         };
         
-        let json = serde_json::to_string(&msg)
+        let value = serde_json::to_value(&msg)
             .map_err(|e| LLMError::ParseError(e.to_string()))?;
         
-        let mut sender = ws_sender.lock().await;
-        sender.send(tokio_tungstenite::tungstenite::Message::Text(json.into())).await
+        ws_sender.send(value).await
             .map_err(|e| LLMError::NetworkError(e.to_string()))?;
         
         Ok(())
@@ -432,11 +444,10 @@ impl LLMHandler {
             request_id: request_id.map(|s| s.to_string()),
         };
         
-        let json = serde_json::to_string(&msg)
+        let value = serde_json::to_value(&msg)
             .map_err(|e| LLMError::ParseError(e.to_string()))?;
         
-        let mut sender = ws_sender.lock().await;
-        sender.send(tokio_tungstenite::tungstenite::Message::Text(json.into())).await
+        ws_sender.send(value).await
             .map_err(|e| LLMError::NetworkError(e.to_string()))?;
         
         Ok(())
@@ -451,11 +462,10 @@ impl LLMHandler {
             request_id: request_id.map(|s| s.to_string()),
         };
         
-        let json = serde_json::to_string(&msg)
+        let value = serde_json::to_value(&msg)
             .map_err(|e| LLMError::ParseError(e.to_string()))?;
         
-        let mut sender = ws_sender.lock().await;
-        sender.send(tokio_tungstenite::tungstenite::Message::Text(json.into())).await
+        ws_sender.send(value).await
             .map_err(|e| LLMError::NetworkError(e.to_string()))?;
         
         Ok(())
@@ -479,11 +489,10 @@ impl LLMHandler {
             request_id: request_id.map(|s| s.to_string()),
         };
         
-        let json = serde_json::to_string(&msg)
+        let value = serde_json::to_value(&msg)
             .map_err(|e| LLMError::ParseError(e.to_string()))?;
         
-        let mut sender = ws_sender.lock().await;
-        sender.send(tokio_tungstenite::tungstenite::Message::Text(json.into())).await
+        ws_sender.send(value).await
             .map_err(|e| LLMError::NetworkError(e.to_string()))?;
         
         Ok(())
@@ -4,21 +4,16 @@
 use serde::{Deserialize, Serialize};
 
 /// API 格式类型
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ApiFormat {
     /// OpenAI Chat Completions API 格式
+    #[default]
     ChatCompletions,
     /// OpenAI Responses API 格式（用于推理模型）
     Responses,
 }
 
-impl Default for ApiFormat {
-    fn default() -> Self {
-        Self::ChatCompletions
-    }
-}
-
 // ============================================================================
 // Chat Completions API 响应结构
 // ============================================================================
@@ -102,8 +102,8 @@ impl SSEParser {
         }
         
         // 注释行
-        if line.starts_with(':') {
-            let comment = line[1..].trim().to_string();
+        if let Some(rest) = line.strip_prefix(':') {
+            let comment = rest.trim().to_string();
             return Some(SSEEvent::Comment(comment));
         }
         
@@ -137,11 +137,7 @@ impl SSEParser {
             let value = if colon_pos + 1 < line.len() {
                 let v = &line[colon_pos + 1..];
                 // 移除值开头的单个空格（如果有）
-                if v.starts_with(' ') {
-                    &v[1..]
-                } else {
-                    v
-                }
+                v.strip_prefix(' ').unwrap_or(v)
             } else {
                 ""
             };
@@ -3,6 +3,10 @@
 
 mod server;
 mod router;
+mod framing;
+mod encoding;
+mod http_server;
+mod outbound;
 
 // 功能模块
 pub mod pty;
@@ -10,8 +14,11 @@ pub mod voice;  // 任务 3 迁移完成
 pub mod llm;    // 任务 5 实现
 pub mod utils;  // 任务 6 实现
 
-use server::{Server, ServerConfig};
+use server::{Listen, Server, ServerConfig};
 use std::env;
+use std::path::PathBuf;
+use voice::stdin::{PcmFormat, StdinOutput};
+use voice::usage::UsageQuota;
 
 /// 日志宏
 macro_rules! log_info {
@@ -28,28 +35,123 @@ macro_rules! log_debug {
     };
 }
 
+/// 标准输入转录参数
+struct StdinArgs {
+    format: PcmFormat,
+    config_path: PathBuf,
+    output: StdinOutput,
+}
+
+/// 参数错误：输出到 stderr 并退出
+fn exit_with_usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(2);
+}
+
 /// 解析命令行参数
-fn parse_args() -> u16 {
+fn parse_args() -> (ServerConfig, Option<StdinArgs>) {
     let args: Vec<String> = env::args().collect();
     let mut port: u16 = 0;
+    let mut quota = UsageQuota::default();
+    let mut http_port: Option<u16> = None;
+    let mut temp_dir = utils::temp_dir::default_temp_dir();
+    let mut max_connections: Option<usize> = None;
+    #[cfg(unix)]
+    let mut unix_socket: Option<PathBuf> = None;
+    let mut stdin = false;
+    let mut stdin_format = PcmFormat::default();
+    let mut stdin_config: Option<PathBuf> = None;
+    let mut stdin_output = StdinOutput::default();
     
     let mut i = 1;
     while i < args.len() {
         match args[i].as_str() {
-            "-p" | "--port" => {
-                if i + 1 < args.len() {
-                    port = args[i + 1].parse().unwrap_or(0);
-                    i += 1;
-                }
+            "-p" | "--port" if i + 1 < args.len() => {
+                port = args[i + 1].parse().unwrap_or(0);
+                i += 1;
             }
             arg if arg.starts_with("--port=") => {
                 port = arg.trim_start_matches("--port=").parse().unwrap_or(0);
             }
+            "--http-port" if i + 1 < args.len() => {
+                http_port = args[i + 1].parse().ok();
+                i += 1;
+            }
+            #[cfg(unix)]
+            "--unix-socket" if i + 1 < args.len() => {
+                unix_socket = Some(PathBuf::from(&args[i + 1]));
+                i += 1;
+            }
+            #[cfg(not(unix))]
+            "--unix-socket" => {
+                eprintln!("当前平台不支持 Unix domain socket");
+                std::process::exit(2);
+            }
+            "--temp-dir" if i + 1 < args.len() => {
+                temp_dir = PathBuf::from(&args[i + 1]);
+                i += 1;
+            }
+            "--max-connections" if i + 1 < args.len() => {
+                max_connections = args[i + 1].parse().ok();
+                i += 1;
+            }
+            "--quota-audio-secs" if i + 1 < args.len() => {
+                quota.max_audio_ms = args[i + 1].parse::<u64>().ok().map(|secs| secs * 1000);
+                i += 1;
+            }
+            "--quota-requests" if i + 1 < args.len() => {
+                quota.max_requests = args[i + 1].parse().ok();
+                i += 1;
+            }
+            "--quota-chars" if i + 1 < args.len() => {
+                quota.max_chars = args[i + 1].parse().ok();
+                i += 1;
+            }
+            "--stdin" => {
+                stdin = true;
+            }
+            "--stdin-rate" if i + 1 < args.len() => {
+                stdin_format.sample_rate = args[i + 1].parse().unwrap_or(0);
+                i += 1;
+            }
+            "--stdin-channels" if i + 1 < args.len() => {
+                stdin_format.channels = args[i + 1].parse().unwrap_or(0);
+                i += 1;
+            }
+            "--stdin-bits" if i + 1 < args.len() => {
+                stdin_format.bits = args[i + 1].parse().unwrap_or(0);
+                i += 1;
+            }
+            "--stdin-config" if i + 1 < args.len() => {
+                stdin_config = Some(PathBuf::from(&args[i + 1]));
+                i += 1;
+            }
+            "--stdin-output" if i + 1 < args.len() => {
+                stdin_output = match args[i + 1].as_str() {
+                    "stdout" => StdinOutput::Stdout,
+                    "ws" => StdinOutput::WebSocket,
+                    other => exit_with_usage_error(&format!("未知的 --stdin-output: {} (可选 stdout、ws)", other)),
+                };
+                i += 1;
+            }
             "-h" | "--help" => {
                 eprintln!("Usage: smart-workflow-server [OPTIONS]");
                 eprintln!("Options:");
-                eprintln!("  -p, --port <PORT>  监听端口 (0 表示随机端口) [默认: 0]");
-                eprintln!("  -h, --help         显示帮助信息");
+                eprintln!("  -p, --port <PORT>           监听端口 (0 表示随机端口) [默认: 0]");
+                eprintln!("      --unix-socket <PATH>    改为监听 Unix domain socket (仅 Unix 平台，忽略 --port)");
+                eprintln!("      --http-port <PORT>      启用 HTTP SSE 转录接口 (0 表示随机端口) [默认: 不启用]");
+                eprintln!("      --temp-dir <DIR>        临时文件目录 (也可用 SMART_WORKFLOW_TEMP_DIR 指定) [默认: 系统临时目录]");
+                eprintln!("      --max-connections <N>   同时保持的最大连接数，超出时拒绝新连接 [默认: 不限]");
+                eprintln!("      --quota-audio-secs <N>  每个连接可转录的音频总时长 (秒) [默认: 不限]");
+                eprintln!("      --quota-requests <N>    每个连接可发起的转录次数 [默认: 不限]");
+                eprintln!("      --quota-chars <N>       每个连接可输出的字符数 [默认: 不限]");
+                eprintln!("      --stdin                 从标准输入读取裸 PCM (小端有符号整数) 持续转录，EOF 时输出最终结果");
+                eprintln!("      --stdin-rate <HZ>       标准输入的采样率 [默认: 16000]");
+                eprintln!("      --stdin-channels <N>    标准输入的声道数 [默认: 1]");
+                eprintln!("      --stdin-bits <BITS>     标准输入的位深 (16、24、32) [默认: 16]");
+                eprintln!("      --stdin-config <FILE>   标准输入转录使用的 ASR 配置 (JSON，同 asr_config)");
+                eprintln!("      --stdin-output <OUT>    结果输出到 stdout (JSON Lines，不启动服务器) 或 ws (广播给 WebSocket 客户端) [默认: stdout]");
+                eprintln!("  -h, --help                  显示帮助信息");
                 std::process::exit(0);
             }
             _ => {}
@@ -57,29 +159,55 @@ fn parse_args() -> u16 {
         i += 1;
     }
     
-    port
+    let listen = Listen::Tcp(port);
+    #[cfg(unix)]
+    let listen = unix_socket.map_or(listen, Listen::Unix);
+    
+    let stdin = stdin.then(|| {
+        let format = PcmFormat::new(stdin_format.sample_rate, stdin_format.channels, stdin_format.bits)
+            .unwrap_or_else(|e| exit_with_usage_error(&format!("标准输入格式无效: {}", e)));
+        let config_path = stdin_config.unwrap_or_else(|| exit_with_usage_error("--stdin 需要 --stdin-config 指定 ASR 配置"));
+        StdinArgs { format, config_path, output: stdin_output }
+    });
+    
+    (ServerConfig { listen, quota, http_port, temp_dir, max_connections }, stdin)
 }
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // 解析命令行参数
-    let port = parse_args();
-
-    log_debug!("启动参数: port={}", port);
+    // 解析命令行参数，创建服务器配置
+    let (config, stdin) = parse_args();
+    let stdin = stdin.map(|args| match voice::stdin::load_config(&args.config_path) {
+        Ok(asr_config) => (args, asr_config),
+        Err(e) => exit_with_usage_error(&e),
+    });
+    
+    // 输出到 stdout 时只做转录，不启动服务器 (stdout 留给转录结果)
+    if let Some((args, asr_config)) = &stdin {
+        if args.output == StdinOutput::Stdout {
+            utils::temp_dir::init(&config.temp_dir)?;
+            let completed = voice::stdin::run_to_stdout(args.format, asr_config.clone()).await;
+            std::process::exit(if completed { 0 } else { 1 });
+        }
+    }
 
-    // 创建服务器配置
-    let config = ServerConfig { port };
+    log_debug!("启动参数: listen={}", config.listen);
 
     // 创建并启动服务器
     let server = Server::new(config);
-    let port = server.start().await?;
+    let listen = server.start().await?;
 
     // 保持主线程运行
-    log_info!("Smart Workflow Server 已启动，监听端口: {}", port);
+    log_info!("Smart Workflow Server 已启动，监听: {}", listen);
+    if let Some((args, asr_config)) = stdin {
+        log_info!("从标准输入转录: {:?}，结果广播给 WebSocket 客户端", args.format);
+        voice::stdin::start_broadcast(args.format, asr_config);
+    }
     
     // 等待 Ctrl+C 信号
     tokio::signal::ctrl_c().await?;
     log_info!("收到退出信号，正在关闭服务器...");
+    server.shutdown();
 
     Ok(())
 }
@@ -0,0 +1,306 @@
+// WebSocket 发送队列
+// 每个连接一个有界发送队列和专门的写任务：各模块只需入队，不再争用 sink 锁。
+// 队列满时可丢弃的消息 (音量、波形等高频状态) 丢旧保新，关键消息阻塞等待空位；
+// 关键消息的发送方先按 FIFO 排队取得发送权 (tokio Mutex 公平)，空位出现时后来者不会抢在等待者之前。
+// 控制消息以 JSON 值入队，由写任务按连接编码只序列化一次
+
+use futures_util::{Sink, SinkExt};
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tokio::sync::{Mutex as AsyncMutex, Notify};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::encoding::Encoding;
+
+/// 消息的投递方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// 关键消息：队列满时等待空位，保证按序送达
+    Critical,
+    /// 可丢弃消息：队列满时丢弃队列中最旧的可丢弃消息，只保留最新状态
+    Droppable,
+}
+
+/// 待发送的内容
+#[derive(Debug, Clone, PartialEq)]
+pub enum Payload {
+    /// 控制消息，写任务按连接编码序列化为 JSON 文本帧或 MessagePack 控制帧
+    Value(serde_json::Value),
+    /// 已成帧的消息 (原始二进制数据、Pong 等)
+    Frame(Message),
+}
+
+impl From<serde_json::Value> for Payload {
+    fn from(value: serde_json::Value) -> Self {
+        Payload::Value(value)
+    }
+}
+
+impl From<Message> for Payload {
+    fn from(message: Message) -> Self {
+        Payload::Frame(message)
+    }
+}
+
+/// 待发送的消息及其投递方式
+#[derive(Debug)]
+pub struct OutboundMessage {
+    pub payload: Payload,
+    pub delivery: Delivery,
+}
+
+impl OutboundMessage {
+    pub fn critical(payload: impl Into<Payload>) -> Self {
+        Self { payload: payload.into(), delivery: Delivery::Critical }
+    }
+
+    pub fn droppable(payload: impl Into<Payload>) -> Self {
+        Self { payload: payload.into(), delivery: Delivery::Droppable }
+    }
+}
+
+/// 发送队列错误
+#[derive(Debug, Error)]
+pub enum SendError {
+    #[error("发送队列已关闭")]
+    Closed,
+}
+
+struct QueueState {
+    messages: VecDeque<OutboundMessage>,
+    closed: bool,
+    dropped: u64,
+}
+
+struct Shared {
+    state: Mutex<QueueState>,
+    capacity: usize,
+    /// 有新消息或队列关闭
+    readable: Notify,
+    /// 有空位或队列关闭
+    writable: Notify,
+    /// 关键消息的发送权，等待空位的发送方按到达顺序排队
+    critical_turn: AsyncMutex<()>,
+}
+
+impl Shared {
+    fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.readable.notify_waiters();
+        self.writable.notify_waiters();
+    }
+}
+
+/// WebSocket 发送端 (可克隆，所有克隆共享同一连接的发送队列)
+#[derive(Clone)]
+pub struct WsSender {
+    shared: Arc<Shared>,
+}
+
+/// 发送队列的接收端，由写任务独占；丢弃时关闭队列
+pub struct OutboundReceiver {
+    shared: Arc<Shared>,
+}
+
+/// 创建容量为 `capacity` 条消息的发送队列
+pub fn channel(capacity: usize) -> (WsSender, OutboundReceiver) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(QueueState { messages: VecDeque::new(), closed: false, dropped: 0 }),
+        capacity: capacity.max(1),
+        readable: Notify::new(),
+        writable: Notify::new(),
+        critical_turn: AsyncMutex::new(()),
+    });
+    (WsSender { shared: Arc::clone(&shared) }, OutboundReceiver { shared })
+}
+
+impl WsSender {
+    /// 以关键消息入队，队列满时等待写任务腾出空位
+    pub async fn send(&self, message: impl Into<Payload>) -> Result<(), SendError> {
+        self.enqueue(OutboundMessage::critical(message)).await
+    }
+
+    /// 以可丢弃消息入队，从不等待
+    pub fn send_droppable(&self, message: impl Into<Payload>) -> Result<(), SendError> {
+        let mut state = self.shared.state.lock().unwrap();
+        if state.closed {
+            return Err(SendError::Closed);
+        }
+        if state.messages.len() >= self.shared.capacity {
+            match state.messages.iter().position(|m| m.delivery == Delivery::Droppable) {
+                Some(oldest) => {
+                    state.messages.remove(oldest);
+                }
+                None => {
+                    // 队列全是关键消息，丢弃本条
+                    state.dropped += 1;
+                    return Ok(());
+                }
+            }
+            state.dropped += 1;
+        }
+        state.messages.push_back(OutboundMessage::droppable(message));
+        drop(state);
+        self.shared.readable.notify_one();
+        Ok(())
+    }
+
+    /// 按消息自身的投递方式入队
+    pub async fn enqueue(&self, message: OutboundMessage) -> Result<(), SendError> {
+        if message.delivery == Delivery::Droppable {
+            return self.send_droppable(message.payload);
+        }
+        let _turn = self.shared.critical_turn.lock().await;
+        loop {
+            let writable = self.shared.writable.notified();
+            {
+                let mut state = self.shared.state.lock().unwrap();
+                if state.closed {
+                    return Err(SendError::Closed);
+                }
+                if state.messages.len() < self.shared.capacity {
+                    state.messages.push_back(message);
+                    drop(state);
+                    self.shared.readable.notify_one();
+                    return Ok(());
+                }
+            }
+            writable.await;
+        }
+    }
+
+    /// 关闭队列：不再接受新消息，已入队的消息仍由写任务发出
+    pub fn close(&self) {
+        self.shared.close();
+    }
+
+    /// 因队列满而丢弃的消息数
+    pub fn dropped(&self) -> u64 {
+        self.shared.state.lock().unwrap().dropped
+    }
+}
+
+impl OutboundReceiver {
+    /// 取出下一条消息，队列关闭且已取空时返回 None
+    pub async fn recv(&mut self) -> Option<Payload> {
+        loop {
+            let readable = self.shared.readable.notified();
+            {
+                let mut state = self.shared.state.lock().unwrap();
+                if let Some(next) = state.messages.pop_front() {
+                    drop(state);
+                    self.shared.writable.notify_one();
+                    return Some(next.payload);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            readable.await;
+        }
+    }
+}
+
+impl Drop for OutboundReceiver {
+    fn drop(&mut self) {
+        self.shared.close();
+    }
+}
+
+/// 写任务：按序把队列中的消息按连接编码转换后写入 sink，写入失败或队列关闭后结束并关闭 sink
+pub async fn run_writer<S>(mut receiver: OutboundReceiver, mut sink: S, encoding: Encoding)
+where
+    S: Sink<Message> + Unpin,
+    S::Error: Display,
+{
+    while let Some(message) = receiver.recv().await {
+        if let Err(e) = sink.send(encoding.encode(message)).await {
+            eprintln!("[ERROR] WebSocket 写入失败: {}", e);
+            break;
+        }
+    }
+    let dropped = receiver.shared.state.lock().unwrap().dropped;
+    if dropped > 0 {
+        eprintln!("[INFO] 发送队列满，共丢弃 {} 条可丢弃消息", dropped);
+    }
+    let _ = sink.close().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn text(s: &str) -> Payload {
+        Payload::Frame(Message::Text(s.to_string().into()))
+    }
+
+    #[tokio::test]
+    async fn test_droppable_keeps_newest_and_critical_waits() {
+        let (sender, mut receiver) = channel(2);
+        sender.send(text("complete")).await.unwrap();
+        sender.send_droppable(text("level-1")).unwrap();
+        sender.send_droppable(text("level-2")).unwrap();
+        assert_eq!(sender.dropped(), 1);
+
+        // 队列满时关键消息等待空位
+        let blocked = {
+            let sender = sender.clone();
+            tokio::spawn(async move { sender.send(text("error")).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!blocked.is_finished());
+
+        assert_eq!(receiver.recv().await, Some(text("complete")));
+        blocked.await.unwrap().unwrap();
+        assert_eq!(receiver.recv().await, Some(text("level-2")));
+
+        // 队列全是关键消息时丢弃新的可丢弃消息
+        sender.send(text("done")).await.unwrap();
+        sender.send_droppable(text("level-3")).unwrap();
+        assert_eq!(sender.dropped(), 2);
+
+        // 关闭后拒绝新消息，已入队的消息仍按序取出
+        sender.close();
+        assert!(sender.send(text("late")).await.is_err());
+        assert_eq!(receiver.recv().await, Some(text("error")));
+        assert_eq!(receiver.recv().await, Some(text("done")));
+        assert_eq!(receiver.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_critical_messages_keep_order_under_backpressure() {
+        let (sender, mut receiver) = channel(1);
+        sender.send(text("0")).await.unwrap();
+
+        // 队列满时依次到达的关键消息
+        let mut waiting = Vec::new();
+        for i in 1..=3 {
+            let sender = sender.clone();
+            waiting.push(tokio::spawn(async move { sender.send(text(&i.to_string())).await }));
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        // 空位出现后、被唤醒的等待者运行之前到达的发送方不能抢占空位
+        assert_eq!(receiver.recv().await, Some(text("0")));
+        let late = sender.send(text("4"));
+        tokio::pin!(late);
+        assert!(futures_util::poll!(&mut late).is_pending());
+
+        let collect = async {
+            let mut received = Vec::new();
+            for _ in 1..=4 {
+                received.push(receiver.recv().await.unwrap());
+            }
+            received
+        };
+        let (received, late) = tokio::join!(collect, late);
+        late.unwrap();
+        assert_eq!(received, ["1", "2", "3", "4"].map(text));
+        for task in waiting {
+            task.await.unwrap().unwrap();
+        }
+    }
+}
@@ -0,0 +1,94 @@
+// PTY 后端检测
+// Unix 使用 openpty；Windows 需要 ConPTY (Windows 10 1809+ 导出的 CreatePseudoConsole)。
+// portable-pty 0.9 已移除 winpty 后端，ConPTY 不可用时没有可回退的后端，
+// 且 portable-pty 在缺少 ConPTY 时会直接 panic，因此创建会话前先检测并返回明确错误
+
+use std::sync::OnceLock;
+
+/// PTY 后端类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PtyBackend {
+    /// Unix openpty
+    Unix,
+    /// Windows ConPTY
+    Conpty,
+    /// 系统不支持 ConPTY (Windows 10 1809 之前的版本)
+    Unavailable,
+}
+
+impl PtyBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PtyBackend::Unix => "unix",
+            PtyBackend::Conpty => "conpty",
+            PtyBackend::Unavailable => "unavailable",
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        *self != PtyBackend::Unavailable
+    }
+}
+
+/// 当前平台使用的 PTY 后端 (首次调用时检测并记录日志)
+pub fn pty_backend() -> PtyBackend {
+    static BACKEND: OnceLock<PtyBackend> = OnceLock::new();
+    *BACKEND.get_or_init(|| {
+        let backend = detect();
+        if backend.is_available() {
+            eprintln!("[INFO] [PTY] 使用 PTY 后端: {}", backend.as_str());
+        } else {
+            eprintln!("[ERROR] [PTY] 系统不支持 ConPTY (需要 Windows 10 1809 及以上)，终端不可用");
+        }
+        backend
+    })
+}
+
+#[cfg(not(windows))]
+fn detect() -> PtyBackend {
+    PtyBackend::Unix
+}
+
+#[cfg(windows)]
+fn detect() -> PtyBackend {
+    if conpty_available() {
+        PtyBackend::Conpty
+    } else {
+        PtyBackend::Unavailable
+    }
+}
+
+/// kernel32 是否导出 CreatePseudoConsole
+#[cfg(windows)]
+fn conpty_available() -> bool {
+    use std::ffi::c_void;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetModuleHandleW(name: *const u16) -> *mut c_void;
+        fn GetProcAddress(module: *mut c_void, name: *const u8) -> *mut c_void;
+    }
+
+    let kernel32: Vec<u16> = "kernel32.dll".encode_utf16().chain(Some(0)).collect();
+    // SAFETY: 参数均为以 NUL 结尾的字符串，kernel32 在进程生命周期内始终已加载
+    unsafe {
+        let module = GetModuleHandleW(kernel32.as_ptr());
+        !module.is_null() && !GetProcAddress(module, c"CreatePseudoConsole".as_ptr().cast()).is_null()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pty_backend_is_detected() {
+        let backend = pty_backend();
+        assert!(backend.is_available());
+        assert_eq!(serde_json::to_value(backend).unwrap(), backend.as_str());
+        if cfg!(unix) {
+            assert_eq!(backend, PtyBackend::Unix);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+// PTY 输出流控
+// 暂停期间不从 PTY master 读取，让内核缓冲区自然产生背压
+
+use tokio::sync::watch;
+
+/// XOFF (Ctrl-S)
+pub const XOFF: u8 = 0x13;
+/// XON (Ctrl-Q)
+pub const XON: u8 = 0x11;
+
+// ============================================================================
+// 输出闸门
+// ============================================================================
+
+/// PTY 输出闸门
+///
+/// 读取任务每次读取前等待闸门打开；暂停时数据留在 PTY 缓冲区中，恢复后继续读取，不会丢失
+#[derive(Debug, Clone)]
+pub struct OutputGate {
+    tx: watch::Sender<bool>,
+}
+
+impl OutputGate {
+    pub fn new() -> Self {
+        let (tx, _rx) = watch::channel(false);
+        Self { tx }
+    }
+
+    /// 暂停读取 PTY 输出
+    pub fn pause(&self) {
+        self.tx.send_replace(true);
+    }
+
+    /// 恢复读取 PTY 输出
+    pub fn resume(&self) {
+        self.tx.send_replace(false);
+    }
+
+    /// 是否处于暂停状态
+    pub fn is_paused(&self) -> bool {
+        *self.tx.borrow()
+    }
+
+    /// 获取供读取任务使用的等待句柄
+    pub fn waiter(&self) -> OutputGateWaiter {
+        OutputGateWaiter {
+            rx: self.tx.subscribe(),
+        }
+    }
+}
+
+impl Default for OutputGate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 输出闸门等待句柄
+pub struct OutputGateWaiter {
+    rx: watch::Receiver<bool>,
+}
+
+impl OutputGateWaiter {
+    /// 等待闸门打开，闸门已关闭 (处理器被释放) 时立即返回
+    pub async fn wait_open(&mut self) {
+        while *self.rx.borrow_and_update() {
+            if self.rx.changed().await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+// ============================================================================
+// XON/XOFF 解析
+// ============================================================================
+
+/// 流控指令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowCommand {
+    Pause,
+    Resume,
+}
+
+/// 从输入数据中提取 XON/XOFF 控制字符
+///
+/// 返回去除控制字符后的数据，以及最后一个生效的流控指令
+pub fn extract_flow_control(data: &[u8]) -> (Vec<u8>, Option<FlowCommand>) {
+    let mut command = None;
+    let mut filtered = Vec::with_capacity(data.len());
+
+    for &byte in data {
+        match byte {
+            XOFF => command = Some(FlowCommand::Pause),
+            XON => command = Some(FlowCommand::Resume),
+            _ => filtered.push(byte),
+        }
+    }
+
+    (filtered, command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_extract_plain_input() {
+        let (data, cmd) = extract_flow_control(b"ls -la\r");
+        assert_eq!(data, b"ls -la\r");
+        assert_eq!(cmd, None);
+    }
+
+    #[test]
+    fn test_extract_xoff_xon() {
+        let (data, cmd) = extract_flow_control(&[b'a', XOFF, b'b']);
+        assert_eq!(data, b"ab");
+        assert_eq!(cmd, Some(FlowCommand::Pause));
+
+        // 以最后一个控制字符为准
+        let (data, cmd) = extract_flow_control(&[XOFF, XON]);
+        assert!(data.is_empty());
+        assert_eq!(cmd, Some(FlowCommand::Resume));
+    }
+
+    #[tokio::test]
+    async fn test_gate_pause_resume() {
+        let gate = OutputGate::new();
+        let mut waiter = gate.waiter();
+
+        // 未暂停时立即通过
+        waiter.wait_open().await;
+
+        gate.pause();
+        assert!(gate.is_paused());
+
+        let blocked = tokio::time::timeout(Duration::from_millis(20), waiter.wait_open()).await;
+        assert!(blocked.is_err());
+
+        let resumer = gate.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            resumer.resume();
+        });
+        tokio::time::timeout(Duration::from_secs(1), waiter.wait_open())
+            .await
+            .expect("恢复后应继续读取");
+        assert!(!gate.is_paused());
+    }
+}
@@ -0,0 +1,101 @@
+// PTY 输入宏
+// 录制写入 PTY 的输入序列及其相对时间，回放时按原时序 (或尽快) 重新注入
+
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::time::{Duration, Instant};
+
+/// 单条录制的输入
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MacroStep {
+    /// 距上一条输入 (第一条为录制开始) 的毫秒数
+    pub delay_ms: u64,
+    /// 输入字节 (JSON 中为 base64)
+    #[serde(serialize_with = "serialize_base64", deserialize_with = "deserialize_base64")]
+    pub data: Vec<u8>,
+}
+
+/// 录制得到的输入宏
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputMacro {
+    pub steps: Vec<MacroStep>,
+}
+
+impl InputMacro {
+    /// 按原时序回放的总时长
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.steps.iter().map(|step| step.delay_ms).sum())
+    }
+}
+
+/// 输入宏录制器
+#[derive(Debug)]
+pub struct MacroRecorder {
+    last: Instant,
+    steps: Vec<MacroStep>,
+}
+
+impl MacroRecorder {
+    pub fn new() -> Self {
+        Self {
+            last: Instant::now(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// 记录一次输入
+    pub fn record(&mut self, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        let delay_ms = now.duration_since(self.last).as_millis() as u64;
+        self.last = now;
+        self.steps.push(MacroStep { delay_ms, data: data.to_vec() });
+    }
+
+    /// 结束录制
+    pub fn finish(self) -> InputMacro {
+        InputMacro { steps: self.steps }
+    }
+}
+
+impl Default for MacroRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn serialize_base64<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&general_purpose::STANDARD.encode(data))
+}
+
+fn deserialize_base64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let encoded = String::deserialize(deserializer)?;
+    general_purpose::STANDARD.decode(encoded).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_serialize() {
+        let mut recorder = MacroRecorder::new();
+        recorder.record(b"ls");
+        std::thread::sleep(Duration::from_millis(20));
+        recorder.record(b"");
+        recorder.record(b"\r");
+        let recorded = recorder.finish();
+
+        assert_eq!(recorded.steps.len(), 2);
+        assert_eq!(recorded.steps[1].data, b"\r");
+        assert!(recorded.steps[1].delay_ms >= 20);
+        assert!(recorded.duration() >= Duration::from_millis(20));
+
+        let json = serde_json::to_value(&recorded).unwrap();
+        assert_eq!(json["steps"][0]["data"], "bHM=");
+        let parsed: InputMacro = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, recorded);
+    }
+}
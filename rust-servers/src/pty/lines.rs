@@ -0,0 +1,144 @@
+// PTY 输出行缓冲
+// 行模式下把输出字节按 UTF-8 增量解码 (跨读取截断的多字节字符留到下次)，按 `\n` 切分成完整行；
+// `\r\n` 视为一个换行，裸 `\r` 按终端语义回到行首，之后的字符覆盖已有内容 (如进度条重绘)
+
+/// 会话输出模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputMode {
+    /// 原始字节流
+    #[default]
+    Raw,
+    /// 按行发送解码后的文本
+    Lines,
+}
+
+/// 行缓冲
+#[derive(Debug, Default)]
+pub struct LineBuffer {
+    /// 尚未凑成完整 UTF-8 字符的尾部字节
+    pending: Vec<u8>,
+    /// 当前行
+    line: Vec<char>,
+    /// 光标所在列 (裸 `\r` 后从行首覆盖)
+    cursor: usize,
+}
+
+impl LineBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加一段输出，返回其中完成的行 (不含换行符)
+    pub fn feed(&mut self, data: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(data);
+        let mut lines = Vec::new();
+        let data = std::mem::take(&mut self.pending);
+        let mut input = data.as_slice();
+
+        loop {
+            match std::str::from_utf8(input) {
+                Ok(text) => {
+                    self.push_str(text, &mut lines);
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = input.split_at(e.valid_up_to());
+                    // valid_up_to 之前的字节已校验为合法 UTF-8
+                    self.push_str(std::str::from_utf8(valid).unwrap_or_default(), &mut lines);
+                    match e.error_len() {
+                        // 末尾是被截断的字符，留到下次
+                        None => {
+                            self.pending = after.to_vec();
+                            break;
+                        }
+                        Some(len) => {
+                            self.push_char(char::REPLACEMENT_CHARACTER, &mut lines);
+                            input = &after[len..];
+                        }
+                    }
+                }
+            }
+        }
+
+        lines
+    }
+
+    /// 取出未完成的行 (输出结束时调用)，没有内容时返回 None
+    pub fn flush(&mut self) -> Option<String> {
+        if !self.pending.is_empty() {
+            let pending = std::mem::take(&mut self.pending);
+            for c in String::from_utf8_lossy(&pending).chars() {
+                self.put(c);
+            }
+        }
+        (!self.line.is_empty()).then(|| self.take_line())
+    }
+
+    fn push_str(&mut self, text: &str, lines: &mut Vec<String>) {
+        for c in text.chars() {
+            self.push_char(c, lines);
+        }
+    }
+
+    fn push_char(&mut self, c: char, lines: &mut Vec<String>) {
+        match c {
+            '\n' => lines.push(self.take_line()),
+            '\r' => self.cursor = 0,
+            c => self.put(c),
+        }
+    }
+
+    /// 在光标处写入字符，光标在行中时覆盖原字符
+    fn put(&mut self, c: char) {
+        if self.cursor < self.line.len() {
+            self.line[self.cursor] = c;
+        } else {
+            self.line.push(c);
+        }
+        self.cursor += 1;
+    }
+
+    fn take_line(&mut self) -> String {
+        self.cursor = 0;
+        std::mem::take(&mut self.line).into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_lines_across_reads() {
+        let mut buffer = LineBuffer::new();
+        assert_eq!(buffer.feed(b"first\nsec"), vec!["first"]);
+        assert_eq!(buffer.feed(b"ond\r\nthird\r"), vec!["second"]);
+        // 跨读取的 \r\n 仍是一个换行
+        assert_eq!(buffer.feed(b"\n\n"), vec!["third", ""]);
+
+        // 多字节字符被截断在两次读取之间
+        let text = "中文行\n".as_bytes();
+        assert!(buffer.feed(&text[..4]).is_empty());
+        assert_eq!(buffer.feed(&text[4..]), vec!["中文行"]);
+
+        // 非法字节替换为 U+FFFD
+        assert_eq!(buffer.feed(b"a\xffb\n"), vec!["a\u{fffd}b"]);
+
+        // 结束时取出未完成的行
+        assert!(buffer.feed(b"tail").is_empty());
+        assert_eq!(buffer.flush(), Some("tail".to_string()));
+        assert_eq!(buffer.flush(), None);
+    }
+
+    #[test]
+    fn test_bare_carriage_return_overwrites() {
+        let mut buffer = LineBuffer::new();
+        // 进度条重绘：只保留最后一次的内容
+        assert_eq!(buffer.feed(b" 10%\r 50%\r100%\n"), vec!["100%"]);
+        // 较短的重绘只覆盖开头，与终端显示一致
+        assert_eq!(buffer.feed(b"abcdef\rXY\n"), vec!["XYcdef"]);
+        assert!(buffer.feed(b"done\r").is_empty());
+        assert_eq!(buffer.flush(), Some("done".to_string()));
+    }
+}
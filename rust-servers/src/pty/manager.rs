@@ -0,0 +1,721 @@
+// PTY 多会话管理
+// 每个会话独立持有 PTY、读取任务、流控闸门与 cwd 状态
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, Mutex as TokioMutex};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use super::flow::{extract_flow_control, FlowCommand, OutputGate};
+use super::input_macro::{InputMacro, MacroRecorder};
+use super::lines::{LineBuffer, OutputMode};
+use super::osc133::{CommandMark, Osc133Parser};
+use super::osc52::Osc52Parser;
+use super::osc_cwd::CwdParser;
+use super::session::{PtyReader, PtySession, PtyWriter};
+use super::shell::{get_shell_integration_script, EnvFilter, ShellIntegration};
+
+/// 会话 ID (单个管理器内唯一，不复用)
+pub type SessionId = u64;
+
+/// 读取缓冲区大小
+const READ_BUF_SIZE: usize = 8192;
+
+// ============================================================================
+// 会话选项与事件
+// ============================================================================
+
+/// 创建会话的选项
+#[derive(Debug, Clone)]
+pub struct SessionOptions {
+    pub shell_type: Option<String>,
+    pub shell_args: Option<Vec<String>>,
+    pub cwd: Option<String>,
+    pub env: Option<HashMap<String, String>>,
+    /// 继承父进程环境变量的过滤规则
+    pub env_filter: EnvFilter,
+    pub cols: u16,
+    pub rows: u16,
+    /// 是否解析输入中的 Ctrl-S/Ctrl-Q 作为流控
+    pub flow_control: bool,
+    /// 输出以原始字节还是按行发送
+    pub output_mode: OutputMode,
+    /// Shell Integration 脚本的注入方式
+    pub shell_integration: ShellIntegration,
+}
+
+impl Default for SessionOptions {
+    fn default() -> Self {
+        Self {
+            shell_type: None,
+            shell_args: None,
+            cwd: None,
+            env: None,
+            env_filter: EnvFilter::default(),
+            cols: 80,
+            rows: 24,
+            flow_control: false,
+            output_mode: OutputMode::Raw,
+            shell_integration: ShellIntegration::Full,
+        }
+    }
+}
+
+/// 会话读取任务产生的事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    /// PTY 输出
+    Output { id: SessionId, data: Vec<u8> },
+    /// 行模式下完成的输出行 (不含换行符)
+    Lines { id: SessionId, lines: Vec<String> },
+    /// 程序通过 OSC 52 请求写入剪贴板 (由上层决定是否写入)
+    Clipboard { id: SessionId, selection: String, text: String },
+    /// shell integration 通过 OSC 133 报告的命令边界
+    CommandMark { id: SessionId, mark: CommandMark },
+    /// shell 通过 OSC 7 或 OSC 9;9 报告的工作目录变化
+    Cwd { id: SessionId, cwd: String },
+    /// 输入宏回放结束，`interrupted` 表示被用户输入或新的回放打断
+    MacroFinished { id: SessionId, interrupted: bool },
+    /// PTY 输出结束 (shell 退出或读取失败)
+    Exited { id: SessionId },
+}
+
+/// 会话摘要信息
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SessionInfo {
+    pub session_id: SessionId,
+    pub shell_type: Option<String>,
+    pub cwd: Option<String>,
+    pub paused: bool,
+}
+
+// ============================================================================
+// 单个会话
+// ============================================================================
+
+/// 受管理的 PTY 会话
+pub struct ManagedSession {
+    id: SessionId,
+    pty: TokioMutex<PtySession>,
+    writer: Arc<Mutex<PtyWriter>>,
+    read_task: TokioMutex<Option<JoinHandle<()>>>,
+    output_gate: OutputGate,
+    flow_control: bool,
+    shell_type: Option<String>,
+    /// 与读取任务共享，shell 上报 cwd 时由读取任务更新
+    cwd: Arc<Mutex<Option<String>>>,
+    events: mpsc::Sender<SessionEvent>,
+    /// 正在进行的输入宏录制
+    recorder: Mutex<Option<MacroRecorder>>,
+    /// 最近一次录制的输入宏
+    last_macro: Mutex<Option<InputMacro>>,
+    /// 正在进行的回放 (序号, 取消令牌)
+    playback: Mutex<Option<(u64, CancellationToken)>>,
+    playback_seq: AtomicU64,
+}
+
+impl ManagedSession {
+    pub fn id(&self) -> SessionId {
+        self.id
+    }
+
+    /// 调整终端尺寸
+    pub async fn resize(&self, cols: u16, rows: u16) -> Result<(), String> {
+        let mut pty = self.pty.lock().await;
+        pty.resize(cols, rows).map_err(|e| e.to_string())
+    }
+
+    /// 写入用户输入，启用流控时拦截 Ctrl-S/Ctrl-Q
+    ///
+    /// 用户输入优先于宏回放：回放期间收到输入会立即中止回放；录制中的输入同时记入宏
+    pub fn write(&self, data: &[u8]) -> Result<(), String> {
+        self.cancel_playback();
+        if let Some(recorder) = lock(&self.recorder).as_mut() {
+            recorder.record(data);
+        }
+        self.inject(data)
+    }
+
+    /// 写入 PTY (不记入宏，也不打断回放)
+    fn inject(&self, data: &[u8]) -> Result<(), String> {
+        let filtered;
+        let data = if self.flow_control {
+            let (rest, command) = extract_flow_control(data);
+            match command {
+                Some(FlowCommand::Pause) => self.output_gate.pause(),
+                Some(FlowCommand::Resume) => self.output_gate.resume(),
+                None => {}
+            }
+            if rest.is_empty() {
+                return Ok(());
+            }
+            filtered = rest;
+            filtered.as_slice()
+        } else {
+            data
+        };
+
+        let mut writer = self.writer.lock().map_err(|e| e.to_string())?;
+        writer.write(data).map_err(|e| e.to_string())
+    }
+
+    /// 开始录制输入宏 (已在录制时重新开始)
+    pub fn start_macro_recording(&self) {
+        *lock(&self.recorder) = Some(MacroRecorder::new());
+    }
+
+    /// 停止录制并返回录制结果，未在录制时返回 None
+    pub fn stop_macro_recording(&self) -> Option<InputMacro> {
+        let recorded = lock(&self.recorder).take()?.finish();
+        *lock(&self.last_macro) = Some(recorded.clone());
+        Some(recorded)
+    }
+
+    /// 是否正在录制输入宏
+    pub fn is_recording_macro(&self) -> bool {
+        lock(&self.recorder).is_some()
+    }
+
+    /// 最近一次录制的输入宏
+    pub fn last_macro(&self) -> Option<InputMacro> {
+        lock(&self.last_macro).clone()
+    }
+
+    /// 回放输入宏
+    ///
+    /// `respect_timing` 为 false 时忽略录制时的间隔尽快写入；
+    /// 已有回放时先中止旧回放，结束后发送 `MacroFinished` 事件
+    pub fn play_macro(self: &Arc<Self>, input_macro: InputMacro, respect_timing: bool) {
+        self.cancel_playback();
+        let seq = self.playback_seq.fetch_add(1, Ordering::Relaxed);
+        let token = CancellationToken::new();
+        *lock(&self.playback) = Some((seq, token.clone()));
+
+        let session = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interrupted = false;
+            for step in input_macro.steps {
+                if respect_timing && step.delay_ms > 0 {
+                    tokio::select! {
+                        _ = token.cancelled() => {}
+                        _ = tokio::time::sleep(std::time::Duration::from_millis(step.delay_ms)) => {}
+                    }
+                }
+                if token.is_cancelled() {
+                    interrupted = true;
+                    break;
+                }
+                if let Err(e) = session.inject(&step.data) {
+                    eprintln!("[ERROR] [PTY] 会话 {} 回放输入宏失败: {}", session.id, e);
+                    interrupted = true;
+                    break;
+                }
+            }
+
+            // 只清理自己的令牌，避免覆盖更新的回放
+            {
+                let mut playback = lock(&session.playback);
+                if playback.as_ref().is_some_and(|(current, _)| *current == seq) {
+                    *playback = None;
+                }
+            }
+            let _ = session.events.send(SessionEvent::MacroFinished { id: session.id, interrupted }).await;
+        });
+    }
+
+    /// 是否正在回放输入宏
+    pub fn is_playing_macro(&self) -> bool {
+        lock(&self.playback).is_some()
+    }
+
+    /// 中止正在进行的回放
+    pub fn cancel_playback(&self) {
+        if let Some((_, token)) = lock(&self.playback).take() {
+            token.cancel();
+        }
+    }
+
+    /// 暂停读取 PTY 输出
+    pub fn pause_output(&self) {
+        self.output_gate.pause();
+    }
+
+    /// 恢复读取 PTY 输出
+    pub fn resume_output(&self) {
+        self.output_gate.resume();
+    }
+
+    /// 输出是否已暂停
+    pub fn is_paused(&self) -> bool {
+        self.output_gate.is_paused()
+    }
+
+    /// 当前工作目录
+    pub fn cwd(&self) -> Option<String> {
+        self.cwd.lock().ok().and_then(|cwd| cwd.clone())
+    }
+
+    /// 更新工作目录
+    pub fn set_cwd(&self, cwd: String) {
+        if let Ok(mut current) = self.cwd.lock() {
+            *current = Some(cwd);
+        }
+    }
+
+    /// 会话摘要
+    pub fn info(&self) -> SessionInfo {
+        SessionInfo {
+            session_id: self.id,
+            shell_type: self.shell_type.clone(),
+            cwd: self.cwd(),
+            paused: self.is_paused(),
+        }
+    }
+
+    /// 终止进程并等待读取任务结束
+    async fn shutdown(&self) {
+        self.cancel_playback();
+        {
+            let mut pty = self.pty.lock().await;
+            let _ = pty.kill();
+        }
+
+        // 恢复输出，避免读取任务停在暂停状态无法退出
+        self.output_gate.resume();
+
+        let task = self.read_task.lock().await.take();
+        if let Some(task) = task {
+            let _ = task.await;
+        }
+    }
+}
+
+/// 启动会话的输出读取任务
+fn spawn_read_task(
+    id: SessionId,
+    reader: PtyReader,
+    writer: Arc<Mutex<PtyWriter>>,
+    options: &SessionOptions,
+    gate: &OutputGate,
+    cwd: Arc<Mutex<Option<String>>>,
+    events: mpsc::Sender<SessionEvent>,
+) -> JoinHandle<()> {
+    let reader = Arc::new(Mutex::new(reader));
+    let mut gate = gate.waiter();
+    let shell_type = options.shell_type.clone();
+    let output_mode = options.output_mode;
+    let shell_integration = options.shell_integration;
+
+    tokio::spawn(async move {
+        let mut first_output = true;
+        let mut osc52 = Osc52Parser::new();
+        let mut osc133 = Osc133Parser::new();
+        let mut cwd_parser = CwdParser::new();
+        let mut line_buffer = (output_mode == OutputMode::Lines).then(LineBuffer::new);
+
+        loop {
+            // 暂停期间不读取，数据保留在 PTY 缓冲区中形成背压
+            gate.wait_open().await;
+
+            // 在阻塞任务中读取 PTY 输出
+            let reader_clone = Arc::clone(&reader);
+            let result = tokio::task::spawn_blocking(move || -> Result<Vec<u8>, String> {
+                let mut reader = reader_clone.lock().map_err(|e| e.to_string())?;
+                let mut buf = vec![0u8; READ_BUF_SIZE];
+                let n = reader.read(&mut buf).map_err(|e| e.to_string())?;
+                buf.truncate(n);
+                Ok(buf)
+            }).await;
+
+            match result {
+                Ok(Ok(data)) if !data.is_empty() => {
+                    let clipboard_writes = osc52.feed(&data);
+                    let command_marks = osc133.feed(&data);
+                    // 只在目录实际变化时上报 (shell 每次提示符都会重发)
+                    let cwd_change = cwd_parser.feed(&data).filter(|new| {
+                        let mut current = lock(&cwd);
+                        let changed = current.as_deref() != Some(new.as_str());
+                        if changed {
+                            *current = Some(new.clone());
+                        }
+                        changed
+                    });
+
+                    // 接收端满时等待，输出转发跟不上时同样形成背压
+                    let output = match line_buffer.as_mut() {
+                        Some(buffer) => {
+                            let lines = buffer.feed(&data);
+                            (!lines.is_empty()).then_some(SessionEvent::Lines { id, lines })
+                        }
+                        None => Some(SessionEvent::Output { id, data }),
+                    };
+                    if let Some(output) = output {
+                        if events.send(output).await.is_err() {
+                            break;
+                        }
+                    }
+
+                    for write in clipboard_writes {
+                        let event = SessionEvent::Clipboard { id, selection: write.selection, text: write.text };
+                        if events.send(event).await.is_err() {
+                            break;
+                        }
+                    }
+
+                    for mark in command_marks {
+                        if events.send(SessionEvent::CommandMark { id, mark }).await.is_err() {
+                            break;
+                        }
+                    }
+
+                    if let Some(cwd) = cwd_change {
+                        if events.send(SessionEvent::Cwd { id, cwd }).await.is_err() {
+                            break;
+                        }
+                    }
+
+                    // 首次输出后注入 Shell Integration 脚本 (关闭时不注入)
+                    if first_output {
+                        first_output = false;
+                        let script = shell_type
+                            .as_deref()
+                            .and_then(|shell| get_shell_integration_script(shell, shell_integration));
+                        if let Some(script) = script {
+                            if let Ok(mut w) = writer.lock() {
+                                if let Err(e) = w.write(script.as_bytes()) {
+                                    eprintln!("[ERROR] [PTY] 发送 Shell Integration 脚本失败: {}", e);
+                                }
+                            }
+                        }
+                    }
+                }
+                Ok(Ok(_)) => break, // EOF
+                Ok(Err(e)) => {
+                    eprintln!("[ERROR] [PTY] 会话 {} 输出读取错误: {}", id, e);
+                    break;
+                }
+                Err(e) => {
+                    eprintln!("[ERROR] [PTY] 会话 {} 读取任务错误: {}", id, e);
+                    break;
+                }
+            }
+        }
+
+        // 输出结束时发出缓存中未以换行结尾的最后一行
+        if let Some(last) = line_buffer.as_mut().and_then(LineBuffer::flush) {
+            let _ = events.send(SessionEvent::Lines { id, lines: vec![last] }).await;
+        }
+        let _ = events.send(SessionEvent::Exited { id }).await;
+    })
+}
+
+// ============================================================================
+// 会话管理器
+// ============================================================================
+
+/// PTY 会话管理器
+///
+/// 会话表只在短临界区内加锁，取出 `Arc` 后再执行耗时操作
+pub struct SessionManager {
+    sessions: Mutex<HashMap<SessionId, Arc<ManagedSession>>>,
+    next_id: AtomicU64,
+}
+
+impl SessionManager {
+    pub fn new() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// 创建会话并启动读取任务，输出通过 `events` 发送
+    pub fn create_session(
+        &self,
+        options: SessionOptions,
+        events: mpsc::Sender<SessionEvent>,
+    ) -> Result<SessionId, String> {
+        let (pty, reader, writer) = PtySession::new(
+            options.cols,
+            options.rows,
+            options.shell_type.as_deref(),
+            options.shell_args.as_deref(),
+            options.cwd.as_deref(),
+            options.env.as_ref(),
+            &options.env_filter,
+        ).map_err(|e| e.to_string())?;
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let writer = Arc::new(Mutex::new(writer));
+        let output_gate = OutputGate::new();
+        let cwd = Arc::new(Mutex::new(options.cwd.clone()));
+        let task = spawn_read_task(
+            id,
+            reader,
+            Arc::clone(&writer),
+            &options,
+            &output_gate,
+            Arc::clone(&cwd),
+            events.clone(),
+        );
+
+        let session = Arc::new(ManagedSession {
+            id,
+            pty: TokioMutex::new(pty),
+            writer,
+            read_task: TokioMutex::new(Some(task)),
+            output_gate,
+            flow_control: options.flow_control,
+            shell_type: options.shell_type,
+            cwd,
+            events,
+            recorder: Mutex::new(None),
+            last_macro: Mutex::new(None),
+            playback: Mutex::new(None),
+            playback_seq: AtomicU64::new(0),
+        });
+
+        self.lock_sessions().insert(id, session);
+        Ok(id)
+    }
+
+    /// 获取会话
+    pub fn get(&self, id: SessionId) -> Option<Arc<ManagedSession>> {
+        self.lock_sessions().get(&id).cloned()
+    }
+
+    /// 关闭会话并回收资源，会话不存在时返回 false
+    pub async fn close(&self, id: SessionId) -> bool {
+        let session = self.lock_sessions().remove(&id);
+        match session {
+            Some(session) => {
+                session.shutdown().await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 关闭所有会话
+    pub async fn close_all(&self) {
+        let sessions: Vec<_> = self.lock_sessions().drain().map(|(_, s)| s).collect();
+        for session in sessions {
+            session.shutdown().await;
+        }
+    }
+
+    /// 列出所有会话 (按 ID 排序)
+    pub fn list(&self) -> Vec<SessionInfo> {
+        let mut infos: Vec<_> = self.lock_sessions().values().map(|s| s.info()).collect();
+        infos.sort_by_key(|info| info.session_id);
+        infos
+    }
+
+    /// 会话数量
+    pub fn len(&self) -> usize {
+        self.lock_sessions().len()
+    }
+
+    /// 是否没有会话
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock_sessions(&self) -> std::sync::MutexGuard<'_, HashMap<SessionId, Arc<ManagedSession>>> {
+        // 临界区内不会 panic，中毒时直接沿用内部数据
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 加锁，中毒时沿用内部数据 (临界区内不会 panic)
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn sh_options() -> SessionOptions {
+        SessionOptions {
+            shell_type: Some("custom:/bin/sh".to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// 等待指定会话输出中出现目标字符串
+    async fn wait_for_output(rx: &mut mpsc::Receiver<SessionEvent>, id: SessionId, needle: &str) -> bool {
+        let mut collected = Vec::new();
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while let Ok(Some(event)) = tokio::time::timeout_at(deadline, rx.recv()).await {
+            if let SessionEvent::Output { id: from, data } = event {
+                if from == id {
+                    collected.extend(data);
+                    if String::from_utf8_lossy(&collected).contains(needle) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn test_sessions_are_independent() {
+        let manager = SessionManager::new();
+        let (tx, mut rx) = mpsc::channel(64);
+
+        let a = manager.create_session(sh_options(), tx.clone()).unwrap();
+        let b = manager.create_session(
+            SessionOptions { cwd: Some("/tmp".to_string()), ..sh_options() },
+            tx,
+        ).unwrap();
+        assert_ne!(a, b);
+        assert_eq!(manager.len(), 2);
+
+        let list = manager.list();
+        assert_eq!(list.iter().map(|s| s.session_id).collect::<Vec<_>>(), vec![a, b]);
+        assert_eq!(list[1].cwd.as_deref(), Some("/tmp"));
+
+        manager.get(b).unwrap().write(b"echo session-b-ok\n").unwrap();
+        assert!(wait_for_output(&mut rx, b, "session-b-ok").await);
+
+        manager.get(a).unwrap().pause_output();
+        assert!(manager.get(a).unwrap().is_paused());
+        assert!(!manager.get(b).unwrap().is_paused());
+
+        manager.close_all().await;
+        assert!(manager.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_lines_output_mode() {
+        let manager = SessionManager::new();
+        let (tx, mut rx) = mpsc::channel(64);
+        let id = manager.create_session(
+            SessionOptions { output_mode: OutputMode::Lines, ..sh_options() },
+            tx,
+        ).unwrap();
+
+        // 终端把 \n 转为 \r\n，行内不应残留 \r
+        manager.get(id).unwrap().write(b"printf 'line-%s\\n' one two\n").unwrap();
+        let lines = tokio::time::timeout(Duration::from_secs(5), async {
+            let mut collected: Vec<String> = Vec::new();
+            while let Some(event) = rx.recv().await {
+                match event {
+                    SessionEvent::Lines { lines, .. } => collected.extend(lines),
+                    SessionEvent::Output { .. } => panic!("行模式不应发送原始输出"),
+                    _ => {}
+                }
+                if collected.iter().any(|line| line.ends_with("line-two")) {
+                    break;
+                }
+            }
+            collected
+        }).await.expect("等待行输出超时");
+        // 输出可能紧跟在提示符之后
+        assert!(lines.iter().any(|line| line.ends_with("line-one")), "{:?}", lines);
+        assert!(lines.iter().all(|line| !line.contains('\r')));
+
+        manager.close_all().await;
+    }
+
+    #[tokio::test]
+    async fn test_close_releases_session() {
+        let manager = SessionManager::new();
+        let (tx, mut rx) = mpsc::channel(64);
+
+        let id = manager.create_session(sh_options(), tx).unwrap();
+        // 暂停状态下关闭也不应卡住
+        manager.get(id).unwrap().pause_output();
+        tokio::time::timeout(Duration::from_secs(5), manager.close(id))
+            .await
+            .expect("关闭会话超时");
+
+        assert!(manager.get(id).is_none());
+        assert!(!manager.close(id).await);
+
+        // 读取任务结束后发送 Exited
+        let exited = tokio::time::timeout(Duration::from_secs(5), async {
+            while let Some(event) = rx.recv().await {
+                if event == (SessionEvent::Exited { id }) {
+                    return true;
+                }
+            }
+            false
+        }).await;
+        assert_eq!(exited, Ok(true));
+
+        // ID 不复用
+        let (tx, _rx) = mpsc::channel(64);
+        let next = manager.create_session(sh_options(), tx).unwrap();
+        assert!(next > id);
+        manager.close_all().await;
+    }
+
+    #[tokio::test]
+    async fn test_macro_record_and_replay() {
+        let manager = SessionManager::new();
+        let (tx, mut rx) = mpsc::channel(64);
+        let id = manager.create_session(sh_options(), tx).unwrap();
+        let session = manager.get(id).unwrap();
+
+        session.start_macro_recording();
+        session.write(b"echo macro-").unwrap();
+        session.write(b"ok\n").unwrap();
+        let recorded = session.stop_macro_recording().unwrap();
+        assert_eq!(recorded.steps.len(), 2);
+        assert!(session.stop_macro_recording().is_none());
+        assert!(wait_for_output(&mut rx, id, "macro-ok").await);
+
+        // 回放不记入新的录制
+        session.start_macro_recording();
+        session.play_macro(session.last_macro().unwrap(), false);
+        let finished = tokio::time::timeout(Duration::from_secs(5), async {
+            let mut collected = Vec::new();
+            let mut finished = None;
+            while let Some(event) = rx.recv().await {
+                match event {
+                    SessionEvent::Output { data, .. } => collected.extend(data),
+                    SessionEvent::MacroFinished { interrupted, .. } => finished = Some(interrupted),
+                    _ => {}
+                }
+                if finished.is_some() && String::from_utf8_lossy(&collected).contains("macro-ok") {
+                    return finished;
+                }
+            }
+            finished
+        }).await;
+        assert_eq!(finished, Ok(Some(false)));
+        assert!(session.stop_macro_recording().unwrap().steps.is_empty());
+
+        // 按时序回放期间的用户输入会中止回放
+        let slow = InputMacro {
+            steps: vec![super::super::input_macro::MacroStep { delay_ms: 10_000, data: b"echo late\n".to_vec() }],
+        };
+        session.play_macro(slow, true);
+        assert!(session.is_playing_macro());
+        session.write(b"\n").unwrap();
+        assert!(!session.is_playing_macro());
+        let interrupted = tokio::time::timeout(Duration::from_secs(5), async {
+            while let Some(event) = rx.recv().await {
+                if let SessionEvent::MacroFinished { interrupted, .. } = event {
+                    return interrupted;
+                }
+            }
+            false
+        }).await;
+        assert_eq!(interrupted, Ok(true));
+
+        manager.close_all().await;
+    }
+}
@@ -1,19 +1,41 @@
 // PTY 模块
 // 提供终端会话管理功能
 
+mod backend;
+mod flow;
+mod input_macro;
+mod lines;
+mod manager;
+mod osc;
+mod osc133;
+mod osc52;
+mod osc_cwd;
 mod session;
 mod shell;
 
+pub use backend::{pty_backend, PtyBackend};
+pub use flow::{extract_flow_control, FlowCommand, OutputGate};
+pub use input_macro::{InputMacro, MacroRecorder, MacroStep};
+pub use lines::{LineBuffer, OutputMode};
+pub use osc133::{CommandMark, Osc133Parser};
+pub use osc52::{ClipboardWrite, Osc52Parser, MAX_OSC52_PAYLOAD};
+pub use osc_cwd::CwdParser;
+pub use manager::{ManagedSession, SessionEvent, SessionId, SessionInfo, SessionManager, SessionOptions};
 pub use session::{PtySession, PtyReader, PtyWriter};
-pub use shell::{get_shell_by_type, get_shell_integration_script, get_default_shell};
+pub use shell::{
+    detect_available_shells, get_shell_by_type, get_shell_integration_script, get_default_shell, ShellInfo,
+    ShellIntegration,
+};
 
+use crate::outbound::Payload;
 use crate::router::{ModuleHandler, ModuleMessage, ModuleType, RouterError, ServerResponse};
 use crate::server::WsSender;
+use base64::{Engine as _, engine::general_purpose};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use tokio::sync::Mutex as TokioMutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex as TokioMutex};
 use tokio_tungstenite::tungstenite::Message;
-use futures_util::SinkExt;
 
 /// 日志宏
 macro_rules! log_info {
@@ -40,34 +62,39 @@ macro_rules! log_debug {
 // PTY 处理器
 // ============================================================================
 
+/// 会话输出通道容量 (满时读取任务等待，形成背压)
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// 未设置主会话时的占位 ID (会话 ID 从 1 开始)
+const NO_PRIMARY_SESSION: SessionId = 0;
+
 /// PTY 模块处理器
 /// 
-/// 管理 PTY 会话的生命周期，处理终端相关的消息
+/// 通过 SessionManager 管理多个终端会话。`init` 创建的主会话沿用原协议，
+/// 输出以二进制帧发送；`create_session` 创建的会话输出以带 session_id 的
+/// `session_output` 消息发送
 pub struct PtyHandler {
-    /// 当前 PTY 会话 (每个连接一个会话)
-    session: TokioMutex<Option<Arc<TokioMutex<PtySession>>>>,
-    /// PTY 读取器
-    reader: TokioMutex<Option<Arc<Mutex<PtyReader>>>>,
-    /// PTY 写入器
-    writer: TokioMutex<Option<Arc<Mutex<PtyWriter>>>>,
+    /// 会话管理器
+    sessions: SessionManager,
+    /// 主会话 ID (二进制输入输出对应的会话)
+    primary: Arc<AtomicU64>,
     /// WebSocket 发送器 (用于发送 PTY 输出)
     ws_sender: TokioMutex<Option<WsSender>>,
-    /// 读取任务句柄
-    read_task: TokioMutex<Option<tokio::task::JoinHandle<()>>>,
-    /// Shell 类型 (用于 Shell Integration)
-    shell_type: TokioMutex<Option<String>>,
+    /// 会话事件发送器 (首次创建会话时初始化)
+    events_tx: TokioMutex<Option<mpsc::Sender<SessionEvent>>>,
+    /// 输出转发任务句柄
+    forward_task: TokioMutex<Option<tokio::task::JoinHandle<()>>>,
 }
 
 impl PtyHandler {
     /// 创建新的 PTY 处理器
     pub fn new() -> Self {
         Self {
-            session: TokioMutex::new(None),
-            reader: TokioMutex::new(None),
-            writer: TokioMutex::new(None),
+            sessions: SessionManager::new(),
+            primary: Arc::new(AtomicU64::new(NO_PRIMARY_SESSION)),
             ws_sender: TokioMutex::new(None),
-            read_task: TokioMutex::new(None),
-            shell_type: TokioMutex::new(None),
+            events_tx: TokioMutex::new(None),
+            forward_task: TokioMutex::new(None),
         }
     }
     
@@ -77,242 +104,245 @@ impl PtyHandler {
         *ws_sender = Some(sender);
     }
     
-    /// 处理 init 消息 - 创建 PTY 会话
-    async fn handle_init(
-        &self,
-        shell_type: Option<String>,
-        shell_args: Option<Vec<String>>,
-        cwd: Option<String>,
-        env: Option<HashMap<String, String>>,
-    ) -> Result<Option<ServerResponse>, RouterError> {
-        log_info!("初始化 PTY 会话: shell_type={:?}, cwd={:?}", shell_type, cwd);
+    /// 获取会话事件发送器，必要时启动输出转发任务
+    async fn events_sender(&self) -> Result<mpsc::Sender<SessionEvent>, RouterError> {
+        let mut events_tx = self.events_tx.lock().await;
+        if let Some(ref tx) = *events_tx {
+            return Ok(tx.clone());
+        }
         
-        // 创建 PTY 会话
-        let (pty_session, pty_reader, pty_writer) = PtySession::new(
-            80,
-            24,
-            shell_type.as_deref(),
-            shell_args.as_ref().map(|v| v.as_slice()),
-            cwd.as_deref(),
-            env.as_ref(),
-        ).map_err(|e| RouterError::ModuleError(format!("创建 PTY 会话失败: {}", e)))?;
+        let ws_sender = self.ws_sender.lock().await.clone()
+            .ok_or_else(|| RouterError::ModuleError("WebSocket sender not set".to_string()))?;
+        let (tx, rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+        let task = tokio::spawn(forward_events(rx, ws_sender, Arc::clone(&self.primary)));
         
-        // 保存会话和读写器
-        let pty_session = Arc::new(TokioMutex::new(pty_session));
-        let pty_reader = Arc::new(Mutex::new(pty_reader));
-        let pty_writer = Arc::new(Mutex::new(pty_writer));
+        *self.forward_task.lock().await = Some(task);
+        *events_tx = Some(tx.clone());
+        Ok(tx)
+    }
+    
+    /// 创建会话
+    async fn create_session(&self, options: SessionOptions) -> Result<SessionId, RouterError> {
+        log_info!(
+            "创建 PTY 会话: shell_type={:?}, cwd={:?}",
+            options.shell_type, options.cwd
+        );
         
-        {
-            let mut session = self.session.lock().await;
-            *session = Some(Arc::clone(&pty_session));
-        }
-        {
-            let mut reader = self.reader.lock().await;
-            *reader = Some(Arc::clone(&pty_reader));
-        }
-        {
-            let mut writer = self.writer.lock().await;
-            *writer = Some(Arc::clone(&pty_writer));
-        }
-        {
-            let mut st = self.shell_type.lock().await;
-            *st = shell_type.clone();
-        }
+        let events = self.events_sender().await?;
+        let id = self.sessions.create_session(options, events)
+            .map_err(|e| RouterError::ModuleError(format!("创建 PTY 会话失败: {}", e)))?;
         
-        // 启动 PTY 输出读取任务
-        self.start_read_task().await?;
+        log_info!("PTY 会话 {} 创建成功 (共 {} 个)", id, self.sessions.len());
+        Ok(id)
+    }
+    
+    /// 处理 init 消息 - 创建主会话
+    async fn handle_init(&self, options: SessionOptions) -> Result<Option<ServerResponse>, RouterError> {
+        // 重复 init 时替换旧的主会话
+        let previous = self.primary.swap(NO_PRIMARY_SESSION, Ordering::SeqCst);
+        if previous != NO_PRIMARY_SESSION {
+            self.sessions.close(previous).await;
+        }
         
-        log_info!("PTY 会话创建成功");
+        let id = self.create_session(options).await?;
+        self.primary.store(id, Ordering::SeqCst);
         
         // 返回成功响应
         Ok(Some(ServerResponse::new(
             ModuleType::Pty,
             "init_complete",
             serde_json::json!({
-                "success": true
+                "success": true,
+                "session_id": id,
             }),
         )))
     }
     
-    /// 启动 PTY 输出读取任务
-    async fn start_read_task(&self) -> Result<(), RouterError> {
-        let reader = {
-            let reader_guard = self.reader.lock().await;
-            reader_guard.clone()
-        };
-        
-        let ws_sender = {
-            let ws_sender_guard = self.ws_sender.lock().await;
-            ws_sender_guard.clone()
-        };
+    /// 处理 create_session 消息 - 创建附加会话
+    async fn handle_create_session(&self, options: SessionOptions) -> Result<Option<ServerResponse>, RouterError> {
+        let id = self.create_session(options).await?;
         
-        let writer = {
-            let writer_guard = self.writer.lock().await;
-            writer_guard.clone()
-        };
-        
-        let shell_type = {
-            let st = self.shell_type.lock().await;
-            st.clone()
-        };
-        
-        let reader = reader.ok_or_else(|| RouterError::ModuleError("PTY reader not initialized".to_string()))?;
-        let ws_sender = ws_sender.ok_or_else(|| RouterError::ModuleError("WebSocket sender not set".to_string()))?;
+        Ok(Some(ServerResponse::new(
+            ModuleType::Pty,
+            "session_created",
+            serde_json::json!({
+                "session_id": id,
+            }),
+        )))
+    }
+    
+    /// 处理 close_session 消息
+    async fn handle_close_session(&self, id: SessionId) -> Result<Option<ServerResponse>, RouterError> {
+        log_info!("关闭 PTY 会话 {}", id);
         
-        // 启动读取任务
-        let task = tokio::spawn(async move {
-            let mut first_output = true;
-            
-            loop {
-                // 在阻塞任务中读取 PTY 输出
-                let reader_clone = Arc::clone(&reader);
-                let result = tokio::task::spawn_blocking(move || -> Result<(Vec<u8>, usize), String> {
-                    let mut reader = reader_clone.lock().unwrap();
-                    let mut local_buf = vec![0u8; 8192];
-                    match reader.read(&mut local_buf) {
-                        Ok(n) => Ok((local_buf, n)),
-                        Err(e) => Err(e.to_string()),
-                    }
-                }).await;
-                
-                match result {
-                    Ok(Ok((data, n))) if n > 0 => {
-                        log_debug!("读取 PTY 输出: {} 字节", n);
-                        
-                        // 构建带 module 字段的响应
-                        // 对于二进制数据，我们直接发送，TypeScript 端会根据连接上下文处理
-                        let mut sender = ws_sender.lock().await;
-                        if let Err(e) = sender.send(Message::Binary(data[..n].to_vec().into())).await {
-                            log_error!("发送 PTY 输出失败: {}", e);
-                            break;
-                        }
-                        drop(sender);
-                        
-                        // 首次输出后注入 Shell Integration 脚本
-                        if first_output {
-                            first_output = false;
-                            if let Some(ref st) = shell_type {
-                                if let Some(script) = get_shell_integration_script(st) {
-                                    if let Some(ref writer) = writer {
-                                        let mut w = writer.lock().unwrap();
-                                        if let Err(e) = w.write(script.as_bytes()) {
-                                            log_error!("发送 Shell Integration 脚本失败: {}", e);
-                                        } else {
-                                            log_debug!("Shell Integration 脚本已发送");
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    Ok(Ok(_)) => {
-                        // EOF
-                        log_info!("PTY 输出结束");
-                        break;
-                    }
-                    Ok(Err(e)) => {
-                        log_error!("PTY 输出读取错误: {}", e);
-                        break;
-                    }
-                    Err(e) => {
-                        log_error!("PTY 读取任务错误: {}", e);
-                        break;
-                    }
-                }
-            }
-        });
+        if !self.sessions.close(id).await {
+            return Err(RouterError::ModuleError(format!("PTY 会话 {} 不存在", id)));
+        }
+        let _ = self.primary.compare_exchange(id, NO_PRIMARY_SESSION, Ordering::SeqCst, Ordering::SeqCst);
         
-        // 保存任务句柄
-        let mut read_task = self.read_task.lock().await;
-        *read_task = Some(task);
+        Ok(Some(ServerResponse::new(
+            ModuleType::Pty,
+            "session_closed",
+            serde_json::json!({
+                "session_id": id,
+            }),
+        )))
+    }
+    
+    /// 处理 list_sessions 消息
+    fn handle_list_sessions(&self) -> Option<ServerResponse> {
+        let primary = self.primary.load(Ordering::SeqCst);
+        let sessions: Vec<_> = self.sessions.list()
+            .into_iter()
+            .map(|info| {
+                let is_primary = info.session_id == primary;
+                let mut value = serde_json::to_value(info).unwrap_or_default();
+                value["primary"] = serde_json::Value::Bool(is_primary);
+                value
+            })
+            .collect();
         
-        Ok(())
+        Some(ServerResponse::new(
+            ModuleType::Pty,
+            "session_list",
+            serde_json::json!({
+                "sessions": sessions,
+                "backend": pty_backend(),
+            }),
+        ))
+    }
+    
+    /// 查找会话，未指定 ID 时使用主会话
+    fn session(&self, id: Option<SessionId>) -> Result<Arc<ManagedSession>, RouterError> {
+        let id = id.unwrap_or_else(|| self.primary.load(Ordering::SeqCst));
+        self.sessions.get(id)
+            .ok_or_else(|| RouterError::ModuleError("PTY 会话未初始化".to_string()))
     }
     
     /// 处理 resize 消息 - 调整终端尺寸
-    async fn handle_resize(&self, cols: u16, rows: u16) -> Result<Option<ServerResponse>, RouterError> {
+    async fn handle_resize(&self, id: Option<SessionId>, cols: u16, rows: u16) -> Result<Option<ServerResponse>, RouterError> {
         log_info!("调整终端尺寸: {}x{}", cols, rows);
         
-        let session = {
-            let session_guard = self.session.lock().await;
-            session_guard.clone()
-        };
-        
-        if let Some(session) = session {
-            let mut pty = session.lock().await;
-            pty.resize(cols, rows)
-                .map_err(|e| RouterError::ModuleError(format!("调整终端尺寸失败: {}", e)))?;
-        } else {
-            return Err(RouterError::ModuleError("PTY 会话未初始化".to_string()));
-        }
+        self.session(id)?
+            .resize(cols, rows)
+            .await
+            .map_err(|e| RouterError::ModuleError(format!("调整终端尺寸失败: {}", e)))?;
         
         Ok(None) // resize 不需要响应
     }
     
-    /// 写入数据到 PTY
-    pub async fn write_data(&self, data: &[u8]) -> Result<(), RouterError> {
-        let writer = {
-            let writer_guard = self.writer.lock().await;
-            writer_guard.clone()
-        };
-        
-        if let Some(writer) = writer {
-            let mut w = writer.lock().unwrap();
-            w.write(data)
-                .map_err(|e| RouterError::ModuleError(format!("写入 PTY 失败: {}", e)))?;
-        } else {
-            return Err(RouterError::ModuleError("PTY writer 未初始化".to_string()));
-        }
-        
-        Ok(())
+    /// 暂停读取 PTY 输出
+    fn pause_output(&self, id: Option<SessionId>) -> Result<Option<ServerResponse>, RouterError> {
+        log_debug!("暂停 PTY 输出");
+        let session = self.session(id)?;
+        session.pause_output();
+        Ok(Some(flow_state_response(&session)))
     }
     
-    /// 终止 PTY 会话
-    pub async fn kill(&self) -> Result<(), RouterError> {
-        log_info!("终止 PTY 会话");
-        
-        // 终止 PTY 进程
-        let session = {
-            let session_guard = self.session.lock().await;
-            session_guard.clone()
+    /// 恢复读取 PTY 输出
+    fn resume_output(&self, id: Option<SessionId>) -> Result<Option<ServerResponse>, RouterError> {
+        log_debug!("恢复 PTY 输出");
+        let session = self.session(id)?;
+        session.resume_output();
+        Ok(Some(flow_state_response(&session)))
+    }
+    
+    /// 开始录制输入宏
+    fn start_macro_recording(&self, id: Option<SessionId>) -> Result<Option<ServerResponse>, RouterError> {
+        let session = self.session(id)?;
+        session.start_macro_recording();
+        log_info!("PTY 会话 {} 开始录制输入宏", session.id());
+        Ok(Some(ServerResponse::new(
+            ModuleType::Pty,
+            "macro_recording",
+            serde_json::json!({
+                "session_id": session.id(),
+            }),
+        )))
+    }
+    
+    /// 停止录制输入宏，返回录制结果
+    fn stop_macro_recording(&self, id: Option<SessionId>) -> Result<Option<ServerResponse>, RouterError> {
+        let session = self.session(id)?;
+        let recorded = session.stop_macro_recording()
+            .ok_or_else(|| RouterError::ModuleError("PTY 会话未在录制输入宏".to_string()))?;
+        log_info!("PTY 会话 {} 录制输入宏完成: {} 条输入", session.id(), recorded.steps.len());
+        Ok(Some(ServerResponse::new(
+            ModuleType::Pty,
+            "macro_recorded",
+            serde_json::json!({
+                "session_id": session.id(),
+                "steps": recorded.steps,
+                "duration_ms": recorded.duration().as_millis() as u64,
+            }),
+        )))
+    }
+    
+    /// 回放输入宏，未提供 steps 时回放最近一次录制
+    fn play_macro(
+        &self,
+        id: Option<SessionId>,
+        steps: Option<Vec<MacroStep>>,
+        ignore_timing: bool,
+    ) -> Result<Option<ServerResponse>, RouterError> {
+        let session = self.session(id)?;
+        let input_macro = match steps {
+            Some(steps) => InputMacro { steps },
+            None => session.last_macro()
+                .ok_or_else(|| RouterError::ModuleError("没有可回放的输入宏".to_string()))?,
         };
-        
-        if let Some(session) = session {
-            let mut pty = session.lock().await;
-            let _ = pty.kill();
+        log_info!(
+            "PTY 会话 {} 回放输入宏: {} 条输入, ignore_timing={}",
+            session.id(), input_macro.steps.len(), ignore_timing
+        );
+        let step_count = input_macro.steps.len();
+        session.play_macro(input_macro, !ignore_timing);
+        Ok(Some(ServerResponse::new(
+            ModuleType::Pty,
+            "macro_playing",
+            serde_json::json!({
+                "session_id": session.id(),
+                "steps": step_count,
+            }),
+        )))
+    }
+    
+    /// 写入数据到主会话
+    pub async fn write_data(&self, data: &[u8]) -> Result<(), RouterError> {
+        self.write_session(None, data)
+    }
+    
+    /// 写入数据到指定会话
+    fn write_session(&self, id: Option<SessionId>, data: &[u8]) -> Result<(), RouterError> {
+        self.session(id)?
+            .write(data)
+            .map_err(|e| RouterError::ModuleError(format!("写入 PTY 失败: {}", e)))
+    }
+    
+    /// 主会话 ID (未初始化时为 None)
+    pub fn primary_session(&self) -> Option<SessionId> {
+        Some(self.primary.load(Ordering::SeqCst)).filter(|&id| id != NO_PRIMARY_SESSION)
+    }
+    
+    /// 检查主会话是否已初始化
+    pub async fn is_initialized(&self) -> bool {
+        self.sessions.get(self.primary.load(Ordering::SeqCst)).is_some()
+    }
+    
+    /// 清理资源 - 关闭所有会话并停止输出转发
+    pub async fn cleanup(&self) {
+        if !self.sessions.is_empty() {
+            log_info!("终止 {} 个 PTY 会话", self.sessions.len());
         }
+        self.sessions.close_all().await;
+        self.primary.store(NO_PRIMARY_SESSION, Ordering::SeqCst);
         
-        // 等待读取任务结束
-        let task = {
-            let mut read_task = self.read_task.lock().await;
-            read_task.take()
-        };
-        
+        // 所有读取任务已结束，关闭事件通道后转发任务自然退出
+        self.events_tx.lock().await.take();
+        let task = self.forward_task.lock().await.take();
         if let Some(task) = task {
             let _ = task.await;
         }
-        
-        // 清理状态
-        {
-            let mut session = self.session.lock().await;
-            *session = None;
-        }
-        {
-            let mut reader = self.reader.lock().await;
-            *reader = None;
-        }
-        {
-            let mut writer = self.writer.lock().await;
-            *writer = None;
-        }
-        
-        Ok(())
-    }
-    
-    /// 检查会话是否已初始化
-    pub async fn is_initialized(&self) -> bool {
-        let session = self.session.lock().await;
-        session.is_some()
     }
 }
 
@@ -322,6 +352,132 @@ impl Default for PtyHandler {
     }
 }
 
+/// 构建流控状态响应
+fn flow_state_response(session: &ManagedSession) -> ServerResponse {
+    ServerResponse::new(
+        ModuleType::Pty,
+        "flow_state",
+        serde_json::json!({
+            "session_id": session.id(),
+            "paused": session.is_paused(),
+        }),
+    )
+}
+
+/// 从消息中读取会话选项
+fn session_options(msg: &ModuleMessage) -> SessionOptions {
+    SessionOptions {
+        shell_type: msg.get_field("shell_type"),
+        shell_args: msg.get_field("shell_args"),
+        cwd: msg.get_field("cwd"),
+        env: msg.get_field("env"),
+        env_filter: msg.get_field("env_filter").unwrap_or_default(),
+        cols: msg.get_field("cols").unwrap_or(80),
+        rows: msg.get_field("rows").unwrap_or(24),
+        flow_control: msg.get_field("flow_control").unwrap_or(false),
+        output_mode: msg.get_field("output_mode").unwrap_or_default(),
+        shell_integration: msg.get_field("shell_integration").unwrap_or_default(),
+    }
+}
+
+/// 将会话事件转发给客户端
+///
+/// 主会话输出直接以二进制帧发送，TypeScript 端会根据连接上下文处理；
+/// 其他会话输出以 base64 编码后附带 session_id 发送
+async fn forward_events(
+    mut rx: mpsc::Receiver<SessionEvent>,
+    ws_sender: WsSender,
+    primary: Arc<AtomicU64>,
+) {
+    while let Some(event) = rx.recv().await {
+        let is_primary = |id: SessionId| id == primary.load(Ordering::SeqCst);
+        let message = match event {
+            SessionEvent::Output { id, data } if is_primary(id) => {
+                log_debug!("读取 PTY 输出: {} 字节", data.len());
+                Payload::Frame(Message::Binary(data.into()))
+            }
+            SessionEvent::Output { id, data } => {
+                let json = serde_json::json!({
+                    "module": "pty",
+                    "type": "session_output",
+                    "session_id": id,
+                    "data": general_purpose::STANDARD.encode(&data),
+                });
+                Payload::Value(json)
+            }
+            SessionEvent::Lines { id, lines } => {
+                log_debug!("PTY 会话 {} 输出 {} 行", id, lines.len());
+                let json = serde_json::json!({
+                    "module": "pty",
+                    "type": "session_lines",
+                    "session_id": id,
+                    "lines": lines,
+                });
+                Payload::Value(json)
+            }
+            SessionEvent::Clipboard { id, selection, text } => {
+                log_debug!("PTY 会话 {} 请求写入剪贴板: {} 字符", id, text.chars().count());
+                let json = serde_json::json!({
+                    "module": "pty",
+                    "type": "clipboard",
+                    "session_id": id,
+                    "selection": selection,
+                    "text": text,
+                });
+                Payload::Value(json)
+            }
+            SessionEvent::CommandMark { id, mark } => {
+                log_debug!("PTY 会话 {} 命令边界: {:?}", id, mark);
+                let mut json = serde_json::json!({
+                    "module": "pty",
+                    "type": "command_mark",
+                    "session_id": id,
+                });
+                if let (Some(json), Ok(serde_json::Value::Object(fields))) =
+                    (json.as_object_mut(), serde_json::to_value(mark))
+                {
+                    json.extend(fields);
+                }
+                Payload::Value(json)
+            }
+            SessionEvent::Cwd { id, cwd } => {
+                log_debug!("PTY 会话 {} 工作目录: {}", id, cwd);
+                let json = serde_json::json!({
+                    "module": "pty",
+                    "type": "cwd_changed",
+                    "session_id": id,
+                    "cwd": cwd,
+                });
+                Payload::Value(json)
+            }
+            SessionEvent::MacroFinished { id, interrupted } => {
+                log_debug!("PTY 会话 {} 输入宏回放结束: interrupted={}", id, interrupted);
+                let json = serde_json::json!({
+                    "module": "pty",
+                    "type": "macro_finished",
+                    "session_id": id,
+                    "interrupted": interrupted,
+                });
+                Payload::Value(json)
+            }
+            SessionEvent::Exited { id } => {
+                log_info!("PTY 会话 {} 输出结束", id);
+                let json = serde_json::json!({
+                    "module": "pty",
+                    "type": "session_exit",
+                    "session_id": id,
+                });
+                Payload::Value(json)
+            }
+        };
+        
+        if let Err(e) = ws_sender.send(message).await {
+            log_error!("发送 PTY 输出失败: {}", e);
+            break;
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl ModuleHandler for PtyHandler {
     fn module_type(&self) -> ModuleType {
@@ -331,26 +487,72 @@ impl ModuleHandler for PtyHandler {
     async fn handle(&self, msg: &ModuleMessage) -> Result<Option<ServerResponse>, RouterError> {
         log_debug!("处理 PTY 消息: {}", msg.msg_type);
         
+        let session_id: Option<SessionId> = msg.get_field("session_id");
+        
         match msg.msg_type.as_str() {
             "init" => {
-                let shell_type: Option<String> = msg.get_field("shell_type");
-                let shell_args: Option<Vec<String>> = msg.get_field("shell_args");
-                let cwd: Option<String> = msg.get_field("cwd");
-                let env: Option<HashMap<String, String>> = msg.get_field("env");
-                
-                self.handle_init(shell_type, shell_args, cwd, env).await
+                self.handle_init(session_options(msg)).await
+            }
+            "create_session" => {
+                self.handle_create_session(session_options(msg)).await
+            }
+            "close_session" => {
+                let id = session_id
+                    .ok_or_else(|| RouterError::ModuleError("缺少 session_id 字段".to_string()))?;
+                self.handle_close_session(id).await
+            }
+            "list_sessions" => {
+                Ok(self.handle_list_sessions())
+            }
+            "list_shells" => {
+                // 首次探测需要启动各 shell 获取版本，放到阻塞线程中执行
+                let shells = tokio::task::spawn_blocking(detect_available_shells)
+                    .await
+                    .map_err(|e| RouterError::ModuleError(format!("探测可用 shell 失败: {}", e)))?;
+                Ok(Some(ServerResponse::new(
+                    ModuleType::Pty,
+                    "shell_list",
+                    serde_json::json!({ "shells": shells }),
+                )))
+            }
+            "input" => {
+                let data: String = msg.get_field("data")
+                    .ok_or_else(|| RouterError::ModuleError("缺少 data 字段".to_string()))?;
+                self.write_session(session_id, data.as_bytes())?;
+                Ok(None)
             }
             "resize" => {
                 let cols: u16 = msg.get_field("cols").unwrap_or(80);
                 let rows: u16 = msg.get_field("rows").unwrap_or(24);
                 
-                self.handle_resize(cols, rows).await
+                self.handle_resize(session_id, cols, rows).await
+            }
+            "pause_output" => {
+                self.pause_output(session_id)
+            }
+            "resume_output" => {
+                self.resume_output(session_id)
+            }
+            "start_macro_recording" => {
+                self.start_macro_recording(session_id)
+            }
+            "stop_macro_recording" => {
+                self.stop_macro_recording(session_id)
+            }
+            "play_macro" => {
+                let steps: Option<Vec<MacroStep>> = msg.get_field("steps");
+                let ignore_timing: bool = msg.get_field("ignore_timing").unwrap_or(false);
+                self.play_macro(session_id, steps, ignore_timing)
             }
             "env" => {
                 // env 命令在原实现中只是记录日志，实际环境变量在 init 时设置
                 let cwd: Option<String> = msg.get_field("cwd");
                 let env: Option<HashMap<String, String>> = msg.get_field("env");
                 log_info!("收到 env 命令: cwd={:?}, env={:?}", cwd, env);
+                // 记录会话的最新工作目录
+                if let (Some(cwd), Ok(session)) = (cwd, self.session(session_id)) {
+                    session.set_cwd(cwd);
+                }
                 Ok(None)
             }
             _ => {
@@ -0,0 +1,126 @@
+// OSC 序列扫描
+// 从 PTY 输出中提取指定编号的 OSC 序列内容 (`ESC ] <prefix> ... (BEL | ESC \)`)，供各具体解析器使用
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// 普通输出
+    Ground,
+    /// 收到 ESC
+    Escape,
+    /// OSC 序列内
+    Osc,
+    /// OSC 序列内收到 ESC (可能是 ST 的开始)
+    OscEscape,
+}
+
+/// OSC 流式扫描器
+///
+/// 序列可能跨多次读取被截断，扫描状态在调用之间保留；输出数据本身不做修改
+#[derive(Debug)]
+pub struct OscScanner {
+    /// 关注的序列前缀 (`ESC ]` 之后的部分，如 `52;`)
+    prefix: &'static [u8],
+    state: State,
+    /// 当前 OSC 序列内容 (仅前缀匹配时持续收集)
+    body: Vec<u8>,
+    /// 当前序列前缀不匹配或已超限，只等待结束符
+    discard: bool,
+    /// 前缀之后内容的最大字节数，超出的序列整体丢弃
+    max_payload: usize,
+}
+
+impl OscScanner {
+    pub fn new(prefix: &'static [u8], max_payload: usize) -> Self {
+        Self {
+            prefix,
+            state: State::Ground,
+            body: Vec::new(),
+            discard: false,
+            max_payload,
+        }
+    }
+
+    /// 扫描一段输出，对每个完整的匹配序列以去掉前缀后的内容调用 `on_sequence`
+    pub fn feed(&mut self, data: &[u8], mut on_sequence: impl FnMut(&[u8])) {
+        for &byte in data {
+            match self.state {
+                State::Ground => {
+                    if byte == ESC {
+                        self.state = State::Escape;
+                    }
+                }
+                State::Escape => {
+                    if byte == b']' {
+                        self.begin_osc();
+                    } else if byte != ESC {
+                        self.state = State::Ground;
+                    }
+                }
+                State::Osc => match byte {
+                    BEL => self.finish_osc(&mut on_sequence),
+                    ESC => self.state = State::OscEscape,
+                    _ => self.push(byte),
+                },
+                State::OscEscape => {
+                    if byte == b'\\' {
+                        self.finish_osc(&mut on_sequence);
+                    } else if byte == b']' {
+                        // 未正常结束的序列被新的 OSC 打断
+                        self.begin_osc();
+                    } else {
+                        self.reset();
+                    }
+                }
+            }
+        }
+    }
+
+    fn begin_osc(&mut self) {
+        self.state = State::Osc;
+        self.body.clear();
+        self.discard = false;
+    }
+
+    fn push(&mut self, byte: u8) {
+        if self.discard {
+            return;
+        }
+
+        // 前缀确定不匹配时停止收集
+        let len = self.body.len();
+        if len < self.prefix.len() && byte != self.prefix[len] {
+            self.discard = true;
+            self.body.clear();
+            return;
+        }
+
+        if len >= self.prefix.len() + self.max_payload {
+            eprintln!(
+                "[WARN] [PTY] OSC {} 载荷超过 {} 字节，已忽略",
+                String::from_utf8_lossy(&self.prefix[..self.prefix.len() - 1]),
+                self.max_payload
+            );
+            self.discard = true;
+            self.body.clear();
+            return;
+        }
+
+        self.body.push(byte);
+    }
+
+    fn finish_osc(&mut self, on_sequence: &mut impl FnMut(&[u8])) {
+        if !self.discard && self.body.len() >= self.prefix.len() {
+            on_sequence(&self.body[self.prefix.len()..]);
+        }
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.state = State::Ground;
+        self.body.clear();
+        self.discard = false;
+    }
+}
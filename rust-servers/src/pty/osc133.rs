@@ -0,0 +1,113 @@
+// OSC 133 命令边界标记解析
+// 从 PTY 输出中识别 shell integration 发出的 `ESC ] 133 ; <A|B|C|D> [; 参数] (BEL | ESC \)`，
+// 供前端按命令折叠输出与展示退出码
+
+use super::osc::OscScanner;
+
+/// OSC 133 前缀 (`ESC ]` 之后的部分)
+const OSC133_PREFIX: &[u8] = b"133;";
+
+/// 标记参数的最大字节数 (正常只有退出码与少量 key=value)
+const MAX_OSC133_PAYLOAD: usize = 256;
+
+/// 命令边界标记
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "mark", rename_all = "snake_case")]
+pub enum CommandMark {
+    /// A: 提示符开始
+    PromptStart,
+    /// B: 提示符结束，开始输入命令
+    CommandStart,
+    /// C: 命令开始执行，之后为命令输出
+    OutputStart,
+    /// D: 命令结束，shell 未提供退出码时为 None
+    CommandEnd { exit_code: Option<i32> },
+}
+
+/// OSC 133 流式解析器
+#[derive(Debug)]
+pub struct Osc133Parser {
+    scanner: OscScanner,
+}
+
+impl Osc133Parser {
+    pub fn new() -> Self {
+        Self {
+            scanner: OscScanner::new(OSC133_PREFIX, MAX_OSC133_PAYLOAD),
+        }
+    }
+
+    /// 解析一段输出，返回其中完整的命令边界标记
+    pub fn feed(&mut self, data: &[u8]) -> Vec<CommandMark> {
+        let mut marks = Vec::new();
+        self.scanner.feed(data, |body| marks.extend(parse_osc133_body(body)));
+        marks
+    }
+}
+
+impl Default for Osc133Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 解析 `<A|B|C|D>[;参数...]` (已去掉 `133;` 前缀)，未知标记返回 None
+fn parse_osc133_body(body: &[u8]) -> Option<CommandMark> {
+    let body = std::str::from_utf8(body).ok()?;
+    let mut params = body.split(';');
+
+    match params.next()? {
+        "A" => Some(CommandMark::PromptStart),
+        "B" => Some(CommandMark::CommandStart),
+        "C" => Some(CommandMark::OutputStart),
+        "D" => {
+            // 退出码为第一个参数，其后可能跟 key=value 扩展参数
+            let exit_code = params.next().and_then(|code| code.trim().parse().ok());
+            Some(CommandMark::CommandEnd { exit_code })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command_cycle() {
+        let mut parser = Osc133Parser::new();
+        let data = b"\x1b]133;A\x07$ \x1b]133;B\x1b\\ls\r\n\x1b]133;C\x07file\r\n\x1b]133;D;2\x07\x1b]7;file://h/tmp\x07";
+
+        assert_eq!(
+            parser.feed(data),
+            vec![
+                CommandMark::PromptStart,
+                CommandMark::CommandStart,
+                CommandMark::OutputStart,
+                CommandMark::CommandEnd { exit_code: Some(2) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_command_end_variants_and_split_reads() {
+        let mut parser = Osc133Parser::new();
+        assert_eq!(parser.feed(b"\x1b]133;D\x07"), vec![CommandMark::CommandEnd { exit_code: None }]);
+        assert_eq!(
+            parser.feed(b"\x1b]133;D;0;aid=42\x07"),
+            vec![CommandMark::CommandEnd { exit_code: Some(0) }]
+        );
+        assert!(parser.feed(b"\x1b]133;Z\x07\x1b]1337;A\x07").is_empty());
+
+        assert!(parser.feed(b"\x1b]13").is_empty());
+        assert_eq!(parser.feed(b"3;C\x1b\\"), vec![CommandMark::OutputStart]);
+    }
+
+    #[test]
+    fn test_serialize_mark() {
+        let json = serde_json::to_value(CommandMark::CommandEnd { exit_code: Some(1) }).unwrap();
+        assert_eq!(json, serde_json::json!({"mark": "command_end", "exit_code": 1}));
+        let json = serde_json::to_value(CommandMark::PromptStart).unwrap();
+        assert_eq!(json, serde_json::json!({"mark": "prompt_start"}));
+    }
+}
@@ -0,0 +1,150 @@
+// OSC 52 剪贴板序列解析
+// 从 PTY 输出中识别 `ESC ] 52 ; Pc ; Pd (BEL | ESC \)`，解码 base64 后交给上层决定是否写入剪贴板
+
+use base64::{Engine as _, engine::general_purpose};
+
+use super::osc::OscScanner;
+
+/// OSC 52 前缀 (`ESC ]` 之后的部分)
+const OSC52_PREFIX: &[u8] = b"52;";
+
+/// 单个 OSC 52 序列 base64 载荷的最大字节数，超出的序列整体丢弃
+pub const MAX_OSC52_PAYLOAD: usize = 1024 * 1024;
+
+/// 程序请求写入剪贴板的内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardWrite {
+    /// 目标选区 (如 `c` 剪贴板、`p` 主选区)，未指定时为 `c`
+    pub selection: String,
+    /// 解码后的文本
+    pub text: String,
+}
+
+/// OSC 52 流式解析器
+///
+/// 序列可能跨多次读取被截断，解析状态在调用之间保留；输出数据本身不做修改
+#[derive(Debug)]
+pub struct Osc52Parser {
+    scanner: OscScanner,
+}
+
+impl Osc52Parser {
+    pub fn new() -> Self {
+        Self::with_limit(MAX_OSC52_PAYLOAD)
+    }
+
+    pub fn with_limit(max_payload: usize) -> Self {
+        Self {
+            scanner: OscScanner::new(OSC52_PREFIX, max_payload),
+        }
+    }
+
+    /// 解析一段输出，返回其中完整的剪贴板写入请求
+    pub fn feed(&mut self, data: &[u8]) -> Vec<ClipboardWrite> {
+        let mut writes = Vec::new();
+        self.scanner.feed(data, |body| writes.extend(parse_osc52_body(body)));
+        writes
+    }
+}
+
+impl Default for Osc52Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 解析 `Pc;Pd` (已去掉 `52;` 前缀)，查询 (`?`)、非法 base64 与非 UTF-8 内容返回 None
+fn parse_osc52_body(rest: &[u8]) -> Option<ClipboardWrite> {
+    let separator = rest.iter().position(|&b| b == b';')?;
+    let (selection, payload) = (&rest[..separator], &rest[separator + 1..]);
+
+    // 读取剪贴板的查询请求不予响应
+    if payload == b"?" {
+        return None;
+    }
+
+    let selection = match std::str::from_utf8(selection) {
+        Ok("") => "c".to_string(),
+        Ok(s) => s.to_string(),
+        Err(_) => return None,
+    };
+
+    let decoded = match general_purpose::STANDARD.decode(payload) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            eprintln!("[WARN] [PTY] OSC 52 base64 内容非法，已忽略: {}", e);
+            return None;
+        }
+    };
+
+    match String::from_utf8(decoded) {
+        Ok(text) => Some(ClipboardWrite { selection, text }),
+        Err(_) => {
+            eprintln!("[WARN] [PTY] OSC 52 内容不是 UTF-8 文本，已忽略");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn osc52(selection: &str, text: &str, terminator: &str) -> Vec<u8> {
+        format!(
+            "\x1b]52;{};{}{}",
+            selection,
+            general_purpose::STANDARD.encode(text),
+            terminator
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_parse_bel_and_st_terminators() {
+        let mut parser = Osc52Parser::new();
+        let mut data = b"before".to_vec();
+        data.extend(osc52("c", "你好", "\x07"));
+        data.extend(b"middle\x1b]0;title\x07");
+        data.extend(osc52("", "world", "\x1b\\"));
+
+        assert_eq!(
+            parser.feed(&data),
+            vec![
+                ClipboardWrite { selection: "c".into(), text: "你好".into() },
+                ClipboardWrite { selection: "c".into(), text: "world".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sequence_split_across_reads() {
+        let mut parser = Osc52Parser::new();
+        let data = osc52("p", "split payload", "\x1b\\");
+
+        for (i, byte) in data.iter().enumerate() {
+            let writes = parser.feed(std::slice::from_ref(byte));
+            if i + 1 < data.len() {
+                assert!(writes.is_empty());
+            } else {
+                assert_eq!(writes, vec![ClipboardWrite { selection: "p".into(), text: "split payload".into() }]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_and_query_ignored() {
+        let mut parser = Osc52Parser::new();
+        assert!(parser.feed(b"\x1b]52;c;!!not base64!!\x07").is_empty());
+        assert!(parser.feed(b"\x1b]52;c;?\x07").is_empty());
+        // 非法序列之后仍能正常解析
+        assert_eq!(parser.feed(&osc52("c", "ok", "\x07")).len(), 1);
+    }
+
+    #[test]
+    fn test_oversized_payload_dropped() {
+        let mut parser = Osc52Parser::with_limit(16);
+        assert!(parser.feed(&osc52("c", &"x".repeat(64), "\x07")).is_empty());
+        assert_eq!(parser.feed(&osc52("c", "short", "\x07")).len(), 1);
+    }
+}
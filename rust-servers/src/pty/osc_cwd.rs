@@ -0,0 +1,132 @@
+// 工作目录上报解析
+// 识别两种 cwd 上报格式：Unix shell 的 OSC 7 (`ESC ] 7 ; file://host/path`，路径经 URL 编码)
+// 与 Windows shell 的 OSC 9;9 (`ESC ] 9 ; 9 ; C:\path`，纯路径，可能带引号)
+
+use super::osc::OscScanner;
+
+/// OSC 7 前缀 (`ESC ]` 之后的部分)
+const OSC7_PREFIX: &[u8] = b"7;";
+
+/// OSC 9;9 前缀 (ConEmu / Windows Terminal 约定)
+const OSC9_9_PREFIX: &[u8] = b"9;9;";
+
+/// 路径的最大字节数
+const MAX_CWD_PAYLOAD: usize = 4096;
+
+/// cwd 流式解析器
+#[derive(Debug)]
+pub struct CwdParser {
+    osc7: OscScanner,
+    osc9_9: OscScanner,
+}
+
+impl CwdParser {
+    pub fn new() -> Self {
+        Self {
+            osc7: OscScanner::new(OSC7_PREFIX, MAX_CWD_PAYLOAD),
+            osc9_9: OscScanner::new(OSC9_9_PREFIX, MAX_CWD_PAYLOAD),
+        }
+    }
+
+    /// 解析一段输出，返回其中最后一次上报的 cwd
+    pub fn feed(&mut self, data: &[u8]) -> Option<String> {
+        let mut cwd = None;
+        // 两个扫描器逐字节交替推进，保证同一段输出中两种格式混出时取到真正的最后一次
+        for byte in data.chunks(1) {
+            self.osc7.feed(byte, |body| {
+                if let Some(path) = parse_osc7_body(body) {
+                    cwd = Some(path);
+                }
+            });
+            self.osc9_9.feed(byte, |body| {
+                if let Some(path) = parse_osc9_9_body(body) {
+                    cwd = Some(path);
+                }
+            });
+        }
+        cwd
+    }
+}
+
+impl Default for CwdParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 解析 `file://host/path` (已去掉 `7;` 前缀)，主机名忽略，路径做 URL 解码
+fn parse_osc7_body(body: &[u8]) -> Option<String> {
+    let rest = body.strip_prefix(b"file://")?;
+    let path = &rest[rest.iter().position(|&b| b == b'/')?..];
+    let path = String::from_utf8_lossy(&percent_decode(path)).into_owned();
+
+    // Windows 上的 OSC 7 形如 file://host/C:/path，去掉盘符前的斜杠
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+        return Some(path[1..].to_string());
+    }
+    Some(path)
+}
+
+/// 解析 OSC 9;9 的纯路径 (已去掉 `9;9;` 前缀)，原样保留盘符与反斜杠，不做 URL 解码
+fn parse_osc9_9_body(body: &[u8]) -> Option<String> {
+    let path = std::str::from_utf8(body).ok()?;
+    // PowerShell 的常见写法会给路径加双引号
+    let path = path
+        .strip_prefix('"')
+        .and_then(|p| p.strip_suffix('"'))
+        .unwrap_or(path);
+    (!path.is_empty()).then(|| path.to_string())
+}
+
+/// `%XX` 解码，非法的转义原样保留
+fn percent_decode(input: &[u8]) -> Vec<u8> {
+    let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        if input[i] == b'%' && i + 2 < input.len() {
+            if let (Some(high), Some(low)) = (hex(input[i + 1]), hex(input[i + 2])) {
+                out.push(high << 4 | low);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(input[i]);
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_osc7_file_url() {
+        let mut parser = CwdParser::new();
+        assert_eq!(parser.feed(b"\x1b]7;file://host/tmp/my%20dir\x1b\\$ "), Some("/tmp/my dir".to_string()));
+        assert_eq!(parser.feed(b"\x1b]7;file:///home/u\x07"), Some("/home/u".to_string()));
+        assert_eq!(parser.feed(b"\x1b]7;file://pc/C:/Users/u\x07"), Some("C:/Users/u".to_string()));
+
+        // 跨读取截断、非 file:// 格式与无 cwd 的输出
+        assert_eq!(parser.feed(b"\x1b]7;file://h/va"), None);
+        assert_eq!(parser.feed(b"r/log\x07"), Some("/var/log".to_string()));
+        assert_eq!(parser.feed(b"\x1b]7;/plain\x07plain output"), None);
+    }
+
+    #[test]
+    fn test_parse_osc9_9_windows_path() {
+        let mut parser = CwdParser::new();
+        assert_eq!(parser.feed(b"\x1b]9;9;C:\\Users\\u\\My%20Docs\x1b\\"), Some("C:\\Users\\u\\My%20Docs".to_string()));
+        assert_eq!(parser.feed(b"\x1b]9;9;\"D:\\work\"\x07PS D:\\work> "), Some("D:\\work".to_string()));
+        assert_eq!(parser.feed(b"\x1b]9;9;\\\\server\\share\x07"), Some("\\\\server\\share".to_string()));
+
+        // 其他 OSC 9 子命令 (如通知) 不是 cwd；同一段输出中取最后一次上报
+        assert_eq!(parser.feed(b"\x1b]9;4;1;50\x07\x1b]9;done\x07"), None);
+        assert_eq!(
+            parser.feed(b"\x1b]9;9;C:\\a\x07\x1b]7;file://h/b\x07\x1b]9;9;C:\\c\x07"),
+            Some("C:\\c".to_string())
+        );
+    }
+}
@@ -30,14 +30,21 @@ impl PtySession {
     /// - `shell_args`: 可选的 shell 启动参数
     /// - `cwd`: 可选的工作目录
     /// - `env`: 可选的环境变量
+    /// - `env_filter`: 继承父进程环境变量的过滤规则
     pub fn new(
         cols: u16, 
         rows: u16, 
         shell_type: Option<&str>,
         shell_args: Option<&[String]>,
         cwd: Option<&str>,
-        env: Option<&std::collections::HashMap<String, String>>
+        env: Option<&std::collections::HashMap<String, String>>,
+        env_filter: &super::shell::EnvFilter,
     ) -> Result<(Self, PtyReader, PtyWriter), Box<dyn std::error::Error>> {
+        // 旧版 Windows 没有 ConPTY，portable-pty 会直接 panic，提前返回错误
+        if !super::backend::pty_backend().is_available() {
+            return Err("当前系统不支持 ConPTY，终端需要 Windows 10 1809 及以上版本".into());
+        }
+        
         // 获取 PTY 系统
         let pty_system = native_pty_system();
         
@@ -64,6 +71,9 @@ impl PtySession {
             cmd.cwd(cwd_path);
         }
         
+        // 过滤继承的环境变量 (自定义变量在其后设置，不受过滤影响)
+        env_filter.apply(&mut cmd);
+        
         // 设置环境变量
         // 确保 TERM 环境变量存在，否则 clear/vim 等命令无法正常工作
         let term_value = env
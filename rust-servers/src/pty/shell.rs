@@ -1,42 +1,99 @@
 // Shell 检测和配置
 
 use portable_pty::CommandBuilder;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
-/// Shell Integration 脚本 (通过 PTY 注入)
-/// 使用空格前缀防止命令进入历史记录，使用重定向隐藏输出
-/// 注意: bash/zsh 默认配置不记录以空格开头的命令
-/// 仅在 Unix 平台使用，Windows 依赖前端 prompt 解析
+// Shell Integration 脚本 (通过 PTY 注入)
+// 上报 cwd (OSC 7) 与命令边界 (OSC 133: A 提示符开始、B 命令输入开始、C 命令输出开始、D 命令结束及退出码)
+// 使用空格前缀防止命令进入历史记录，使用重定向隐藏输出
+// 注意: bash/zsh 默认配置不记录以空格开头的命令
 
-// Bash: 定义函数并设置 PROMPT_COMMAND，静默执行
+// Bash: PROMPT_COMMAND 上报 D/cwd/A，PS1 末尾追加 B，PS0 (bash 4.4+) 上报 C，静默执行
 #[cfg(not(windows))]
-const SHELL_INTEGRATION_BASH: &str = " eval '__sw_cwd(){ printf \"\\e]7;file://%s%s\\e\\\\\" \"${HOSTNAME:-localhost}\" \"$PWD\";};PROMPT_COMMAND=\"__sw_cwd${PROMPT_COMMAND:+;$PROMPT_COMMAND}\"' 2>/dev/null;__sw_cwd;printf '\\ec'\n";
+const SHELL_INTEGRATION_BASH: &str = " eval '__sw_cwd(){ printf \"\\e]7;file://%s%s\\e\\\\\" \"${HOSTNAME:-localhost}\" \"$PWD\";};__sw_prompt(){ local ec=$?;printf \"\\e]133;D;%s\\e\\\\\" \"$ec\";__sw_cwd;printf \"\\e]133;A\\e\\\\\";};PROMPT_COMMAND=\"__sw_prompt${PROMPT_COMMAND:+;$PROMPT_COMMAND}\";PS1=\"$PS1\\[\\e]133;B\\e\\\\\\\\\\]\";PS0=\"\\e]133;C\\e\\\\\\\\$PS0\"' 2>/dev/null;__sw_cwd;printf '\\ec'\n";
 
-// Zsh: 使用 precmd hook，静默执行
+// Zsh: precmd/preexec hook 上报 D/cwd/A 与 C，PS1 末尾追加 B，静默执行
 #[cfg(not(windows))]
-const SHELL_INTEGRATION_ZSH: &str = " eval '__sw_cwd(){ printf \"\\e]7;file://%s%s\\e\\\\\" \"${HOST:-localhost}\" \"$PWD\";};autoload -Uz add-zsh-hook;add-zsh-hook precmd __sw_cwd;add-zsh-hook chpwd __sw_cwd' 2>/dev/null;__sw_cwd;printf '\\ec'\n";
+const SHELL_INTEGRATION_ZSH: &str = " eval '__sw_cwd(){ printf \"\\e]7;file://%s%s\\e\\\\\" \"${HOST:-localhost}\" \"$PWD\";};__sw_precmd(){ local ec=$?;printf \"\\e]133;D;%s\\e\\\\\" \"$ec\";__sw_cwd;printf \"\\e]133;A\\e\\\\\";};__sw_preexec(){ printf \"\\e]133;C\\e\\\\\";};autoload -Uz add-zsh-hook;add-zsh-hook precmd __sw_precmd;add-zsh-hook preexec __sw_preexec;add-zsh-hook chpwd __sw_cwd;PS1=\"$PS1%{$(printf \"\\e]133;B\\e\\\\\\\\\")%}\"' 2>/dev/null;__sw_cwd;printf '\\ec'\n";
 
-// Fish: 使用事件监听器
+// Fish: 使用事件监听器，并包装 fish_prompt 上报 B
 #[cfg(not(windows))]
-const SHELL_INTEGRATION_FISH: &str = " eval 'function __sw_cwd --on-variable PWD; printf \"\\e]7;file://%s%s\\e\\\\\" (hostname) $PWD; end' 2>/dev/null;__sw_cwd;printf '\\ec'\n";
+const SHELL_INTEGRATION_FISH: &str = " eval 'function __sw_cwd --on-variable PWD; printf \"\\e]7;file://%s%s\\e\\\\\" (hostname) $PWD; end; function __sw_preexec --on-event fish_preexec; printf \"\\e]133;C\\e\\\\\"; end; function __sw_postexec --on-event fish_postexec; printf \"\\e]133;D;%s\\e\\\\\" $status; end; function __sw_prompt_start --on-event fish_prompt; printf \"\\e]133;A\\e\\\\\"; end; functions -c fish_prompt __sw_orig_prompt; function fish_prompt; __sw_orig_prompt; printf \"\\e]133;B\\e\\\\\"; end' 2>/dev/null;__sw_cwd;printf '\\ec'\n";
 
-/// 获取 Shell Integration 脚本
+// PowerShell: 包装 prompt 函数上报 D/A/B，PSReadLine 的 Enter 处理器上报 C
+// 退出码取自 $? 与 $LASTEXITCODE，cwd 以 OSC 9;9 上报
+#[cfg(windows)]
+const SHELL_INTEGRATION_POWERSHELL: &str = "$global:__sw_prompt = $function:prompt; function global:prompt { $ok = $?; $ec = if ($ok) { 0 } elseif ($LASTEXITCODE) { $LASTEXITCODE } else { 1 }; $e = [char]27; \"$e]133;D;$ec$e\\$e]9;9;$((Get-Location).ProviderPath)$e\\$e]133;A$e\\\" + (& $global:__sw_prompt) + \"$e]133;B$e\\\" }; if (Get-Module PSReadLine) { Set-PSReadLineKeyHandler -Chord Enter -ScriptBlock { [Microsoft.PowerShell.PSConsoleReadLine]::AcceptLine(); [Console]::Write(\"$([char]27)]133;C$([char]27)\\\") } }; Clear-Host\r";
+
+// CMD: 尽力而为，PROMPT 无法获取退出码且不会上报 C，会覆盖用户自定义的 PROMPT
+#[cfg(windows)]
+const SHELL_INTEGRATION_CMD: &str = "prompt $e]133;D$e\\$e]9;9;$P$e\\$e]133;A$e\\$P$G$e]133;B$e\\& cls\r";
+
+// 轻量版：只上报 cwd，不修改 PS1/prompt 函数，也不上报命令边界
+
+// Bash: 在 PROMPT_COMMAND 末尾追加 cwd 上报，用户的 PROMPT_COMMAND 仍先读到 $?
+#[cfg(not(windows))]
+const SHELL_INTEGRATION_BASH_LITE: &str = " eval '__sw_cwd(){ printf \"\\e]7;file://%s%s\\e\\\\\" \"${HOSTNAME:-localhost}\" \"$PWD\";};PROMPT_COMMAND=\"${PROMPT_COMMAND:+$PROMPT_COMMAND;}__sw_cwd\"' 2>/dev/null;__sw_cwd;printf '\\ec'\n";
+
+// Zsh: 只注册 chpwd hook
+#[cfg(not(windows))]
+const SHELL_INTEGRATION_ZSH_LITE: &str = " eval '__sw_cwd(){ printf \"\\e]7;file://%s%s\\e\\\\\" \"${HOST:-localhost}\" \"$PWD\";};autoload -Uz add-zsh-hook;add-zsh-hook chpwd __sw_cwd' 2>/dev/null;__sw_cwd;printf '\\ec'\n";
+
+// Fish: 只监听 PWD 变化，不包装 fish_prompt
+#[cfg(not(windows))]
+const SHELL_INTEGRATION_FISH_LITE: &str = " eval 'function __sw_cwd --on-variable PWD; printf \"\\e]7;file://%s%s\\e\\\\\" (hostname) $PWD; end' 2>/dev/null;__sw_cwd;printf '\\ec'\n";
+
+// PowerShell: 用 LocationChangedAction (PowerShell 6.2+) 上报 cwd，不包装 prompt；
+// Windows PowerShell 5.1 不支持该属性，此时只上报初始目录
+#[cfg(windows)]
+const SHELL_INTEGRATION_POWERSHELL_LITE: &str = "function global:__sw_cwd($path) { [Console]::Write(\"$([char]27)]9;9;$path$([char]27)\\\") }; try { $ExecutionContext.InvokeCommand.LocationChangedAction = { __sw_cwd $args[1].NewPath.ProviderPath } } catch {}; __sw_cwd (Get-Location).ProviderPath; Clear-Host\r";
+
+// CMD: 只能借助 PROMPT 上报，在用户已有的 PROMPT (未设置时为默认的 $P$G) 前加 OSC 9;9，显示不变
+#[cfg(windows)]
+const SHELL_INTEGRATION_CMD_LITE: &str = "if defined PROMPT (prompt $e]9;9;$P$e\\%PROMPT%) else (prompt $e]9;9;$P$e\\$P$G)& cls\r";
+
+/// Shell Integration 注入方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShellIntegration {
+    /// 上报 cwd 与命令边界 (会修改 prompt)
+    #[default]
+    Full,
+    /// 只上报 cwd，不修改 prompt
+    CwdOnly,
+    /// 不注入，适用于高度定制的 prompt
+    Off,
+}
+
+/// 获取 Shell Integration 脚本，返回 None 时不注入
 /// 
-/// 注意: Windows 平台的 shell 不使用 Shell Integration，依赖前端 prompt 解析
-pub fn get_shell_integration_script(shell_type: &str) -> Option<&'static str> {
-    // Windows 平台不注入脚本
+/// Unix 平台支持 bash/zsh/fish，Windows 平台支持 PowerShell 与尽力而为的 CMD
+pub fn get_shell_integration_script(shell_type: &str, mode: ShellIntegration) -> Option<&'static str> {
     #[cfg(windows)]
     {
-        let _ = shell_type; // 避免未使用警告
-        None
+        match (shell_type, mode) {
+            (_, ShellIntegration::Off) => None,
+            ("powershell", ShellIntegration::Full) => Some(SHELL_INTEGRATION_POWERSHELL),
+            ("powershell", ShellIntegration::CwdOnly) => Some(SHELL_INTEGRATION_POWERSHELL_LITE),
+            ("cmd", ShellIntegration::Full) => Some(SHELL_INTEGRATION_CMD),
+            ("cmd", ShellIntegration::CwdOnly) => Some(SHELL_INTEGRATION_CMD_LITE),
+            _ => None,
+        }
     }
     
-    // Unix 平台使用 Shell Integration
     #[cfg(not(windows))]
     {
-        match shell_type {
-            "bash" => Some(SHELL_INTEGRATION_BASH),
-            "zsh" => Some(SHELL_INTEGRATION_ZSH),
-            "fish" => Some(SHELL_INTEGRATION_FISH),
+        match (shell_type, mode) {
+            (_, ShellIntegration::Off) => None,
+            ("bash", ShellIntegration::Full) => Some(SHELL_INTEGRATION_BASH),
+            ("bash", ShellIntegration::CwdOnly) => Some(SHELL_INTEGRATION_BASH_LITE),
+            ("zsh", ShellIntegration::Full) => Some(SHELL_INTEGRATION_ZSH),
+            ("zsh", ShellIntegration::CwdOnly) => Some(SHELL_INTEGRATION_ZSH_LITE),
+            ("fish", ShellIntegration::Full) => Some(SHELL_INTEGRATION_FISH),
+            ("fish", ShellIntegration::CwdOnly) => Some(SHELL_INTEGRATION_FISH_LITE),
             _ => None,
         }
     }
@@ -58,8 +115,11 @@ pub fn get_shell_by_type(shell_type: Option<&str>) -> CommandBuilder {
             }
             #[cfg(not(windows))]
             {
-                // 非 Windows 平台，使用默认 shell
-                get_default_shell()
+                // 非 Windows 平台，使用 PowerShell Core，未安装时回退到默认 shell
+                match find_in_path("pwsh") {
+                    Some(path) => CommandBuilder::new(path),
+                    None => get_default_shell(),
+                }
             }
         }
         Some("wsl") => CommandBuilder::new("wsl.exe"),
@@ -85,6 +145,8 @@ pub fn get_shell_by_type(shell_type: Option<&str>) -> CommandBuilder {
         }
         Some("bash") => CommandBuilder::new("bash"),
         Some("zsh") => CommandBuilder::new("zsh"),
+        Some("fish") => CommandBuilder::new("fish"),
+        Some("nu") => CommandBuilder::new("nu"),
         Some(custom) if custom.starts_with("custom:") => {
             // 自定义 shell 路径，格式: "custom:/path/to/shell"
             let path = &custom[7..]; // 移除 "custom:" 前缀
@@ -110,26 +172,262 @@ pub fn get_default_shell() -> CommandBuilder {
     }
 }
 
+// ============================================================================
+// 环境变量继承过滤
+// ============================================================================
+
+/// 无论过滤规则如何都保留的变量 (shell 正常启动所需)
+const ESSENTIAL_ENV_VARS: &[&str] = &[
+    "PATH", "HOME", "USER", "LOGNAME", "SHELL", "TERM", "TMPDIR", "LANG", "LC_ALL", "LC_CTYPE",
+    "SYSTEMROOT", "WINDIR", "COMSPEC", "PATHEXT", "USERPROFILE", "APPDATA", "LOCALAPPDATA", "TEMP", "TMP",
+];
+
+/// 继承父进程环境变量的过滤规则
+///
+/// 规则为变量名模式 (不区分大小写，`*` 匹配任意字符)：
+/// `allow` 非空时只继承匹配的变量，`deny` 中匹配的变量不继承；
+/// 默认两者为空，即全部继承。必要变量 (PATH 等) 始终保留
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct EnvFilter {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl EnvFilter {
+    /// 是否继承指定的变量
+    pub fn keeps(&self, name: &str) -> bool {
+        if ESSENTIAL_ENV_VARS.iter().any(|essential| essential.eq_ignore_ascii_case(name)) {
+            return true;
+        }
+        let matches = |patterns: &[String]| patterns.iter().any(|pattern| wildcard_match(pattern, name));
+        (self.allow.is_empty() || matches(&self.allow)) && !matches(&self.deny)
+    }
+
+    /// 从命令继承的环境中移除被过滤的变量 (需在设置自定义变量之前调用)
+    pub fn apply(&self, cmd: &mut CommandBuilder) {
+        let removed: Vec<String> = cmd
+            .iter_full_env_as_str()
+            .map(|(key, _)| key)
+            .filter(|key| !self.keeps(key))
+            .map(str::to_string)
+            .collect();
+        for key in removed {
+            cmd.env_remove(key);
+        }
+    }
+}
+
+/// 不区分大小写的通配符匹配，仅支持 `*`
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_ascii_uppercase();
+    let name = name.to_ascii_uppercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !name.starts_with(first) || name.len() < first.len() + last.len() || !name.ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+// ============================================================================
+// 可用 Shell 探测
+// ============================================================================
+
+/// 获取版本号的超时时间 (超时的 shell 仍列出，版本为空)
+const VERSION_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 系统上可用的 shell
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ShellInfo {
+    /// 传给 init/create_session 的 shell_type
+    pub shell_type: String,
+    /// 显示名称
+    pub name: String,
+    /// 可执行文件路径
+    pub path: String,
+    /// 版本号 (如 "5.2.15")，无法获取时为空
+    pub version: Option<String>,
+}
+
+/// 探测系统上可用的 shell (首次调用时探测，之后返回缓存结果)
+///
+/// Unix 平台在 PATH 中查找 bash/zsh/fish/pwsh/nu 等，指向同一文件的只保留第一个；
+/// Windows 平台查找 CMD、PowerShell (优先 pwsh)、Git Bash、WSL 与 nu
+pub fn detect_available_shells() -> Vec<ShellInfo> {
+    static SHELLS: OnceLock<Vec<ShellInfo>> = OnceLock::new();
+    SHELLS.get_or_init(probe_shells).clone()
+}
+
+/// 探测候选: (shell_type, 显示名称, 可执行文件名, 获取版本的参数)
+#[cfg(not(windows))]
+const SHELL_CANDIDATES: &[(&str, &str, &str, &[&str])] = &[
+    ("bash", "Bash", "bash", &["--version"]),
+    ("zsh", "Zsh", "zsh", &["--version"]),
+    ("fish", "Fish", "fish", &["--version"]),
+    ("powershell", "PowerShell", "pwsh", &["-NoProfile", "-Command", "$PSVersionTable.PSVersion.ToString()"]),
+    ("nu", "Nushell", "nu", &["--version"]),
+    ("custom:ksh", "Ksh", "ksh", &["-c", "echo $KSH_VERSION"]),
+    ("custom:tcsh", "Tcsh", "tcsh", &["--version"]),
+    ("custom:dash", "Dash", "dash", &[]),
+    ("custom:sh", "sh", "sh", &[]),
+];
+
+#[cfg(not(windows))]
+fn probe_shells() -> Vec<ShellInfo> {
+    let mut seen = Vec::new();
+    let mut shells = Vec::new();
+    for &(shell_type, name, program, version_args) in SHELL_CANDIDATES {
+        let Some(path) = find_in_path(program) else {
+            continue;
+        };
+        // sh 常为 bash/dash 的链接，指向同一文件的不重复列出
+        let canonical = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if seen.contains(&canonical) {
+            continue;
+        }
+        seen.push(canonical);
+
+        let path_str = path.to_string_lossy().into_owned();
+        // 非内置类型使用 custom: 前缀加完整路径
+        let shell_type = match shell_type.strip_prefix("custom:") {
+            Some(_) => format!("custom:{}", path_str),
+            None => shell_type.to_string(),
+        };
+        let version = if version_args.is_empty() { None } else { probe_version(&path, version_args) };
+        shells.push(ShellInfo { shell_type, name: name.to_string(), path: path_str, version });
+    }
+    shells
+}
+
 #[cfg(windows)]
-fn which_powershell() -> Result<String, ()> {
-    // 尝试查找 PowerShell
-    let powershell_paths = vec![
-        "pwsh.exe",           // PowerShell Core
-        "powershell.exe",     // Windows PowerShell
-    ];
+fn probe_shells() -> Vec<ShellInfo> {
+    let info = |shell_type: &str, name: &str, path: PathBuf, version: Option<String>| ShellInfo {
+        shell_type: shell_type.to_string(),
+        name: name.to_string(),
+        path: path.to_string_lossy().into_owned(),
+        version,
+    };
+    let mut shells = Vec::new();
+
+    let cmd = std::env::var_os("COMSPEC").map(PathBuf::from).or_else(|| find_in_path("cmd"));
+    if let Some(path) = cmd {
+        let version = probe_version(&path, &["/d", "/c", "ver"]);
+        shells.push(info("cmd", "Command Prompt", path, version));
+    }
+    if let Ok(path) = which_powershell() {
+        let path = PathBuf::from(path);
+        let version = probe_version(&path, &["-NoProfile", "-Command", "$PSVersionTable.PSVersion.ToString()"]);
+        shells.push(info("powershell", "PowerShell", path, version));
+    }
+    if let Ok(path) = which_gitbash() {
+        let path = PathBuf::from(path);
+        let version = probe_version(&path, &["--version"]);
+        shells.push(info("gitbash", "Git Bash", path, version));
+    }
+    // wsl --version 输出 UTF-16，不获取版本
+    if let Some(path) = find_in_path("wsl") {
+        shells.push(info("wsl", "WSL", path, None));
+    }
+    if let Some(path) = find_in_path("nu") {
+        let version = probe_version(&path, &["--version"]);
+        shells.push(info("nu", "Nushell", path, version));
+    }
+    shells
+}
+
+/// 在 PATH 中查找可执行文件 (Windows 平台按 PATHEXT 补全扩展名)
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+
+    #[cfg(windows)]
+    let names: Vec<String> = if Path::new(program).extension().is_some() {
+        vec![program.to_string()]
+    } else {
+        std::env::var("PATHEXT")
+            .unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string())
+            .split(';')
+            .filter(|ext| !ext.is_empty())
+            .map(|ext| format!("{}{}", program, ext.to_ascii_lowercase()))
+            .collect()
+    };
+    #[cfg(not(windows))]
+    let names = [program.to_string()];
 
-    for path in powershell_paths {
-        if std::process::Command::new(path)
-            .arg("-Command")
-            .arg("exit")
-            .output()
-            .is_ok()
-        {
-            return Ok(path.to_string());
+    std::env::split_paths(&path)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(not(windows))]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0).unwrap_or(false)
+}
+
+#[cfg(windows)]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// 运行 shell 获取版本号，超时或失败时返回 None
+fn probe_version(path: &Path, args: &[&str]) -> Option<String> {
+    let mut child = Command::new(path)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .ok()?;
+
+    let deadline = Instant::now() + VERSION_PROBE_TIMEOUT;
+    while child.try_wait().ok()?.is_none() {
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
         }
+        std::thread::sleep(Duration::from_millis(10));
     }
 
-    Err(())
+    // 版本信息只有一两行，不会写满管道缓冲区
+    let output = child.wait_with_output().ok()?;
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+        .or_else(|| parse_version(&String::from_utf8_lossy(&output.stderr)))
+}
+
+/// 从版本输出中提取第一个形如 `数字.数字` 的版本号
+fn parse_version(output: &str) -> Option<String> {
+    output
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | '[' | ']' | '(' | ')'))
+        .map(|word| word.trim_start_matches(['v', 'V']))
+        .map(|word| {
+            let end = word.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(word.len());
+            word[..end].trim_end_matches('.')
+        })
+        .find(|version| version.contains('.') && version.starts_with(|c: char| c.is_ascii_digit()))
+        .map(str::to_string)
+}
+
+#[cfg(windows)]
+fn which_powershell() -> Result<String, ()> {
+    // 优先 PowerShell Core，回退到 Windows PowerShell
+    ["pwsh", "powershell"]
+        .iter()
+        .find_map(|program| find_in_path(program))
+        .map(|path| path.to_string_lossy().into_owned())
+        .ok_or(())
 }
 
 #[cfg(windows)]
@@ -219,9 +517,84 @@ mod tests {
         // 测试不会 panic
     }
     
+    #[cfg(not(windows))]
+    #[test]
+    fn test_integration_scripts_emit_command_marks() {
+        for shell in ["bash", "zsh", "fish"] {
+            let script = get_shell_integration_script(shell, ShellIntegration::Full).unwrap();
+            for mark in ["133;A", "133;B", "133;C", "133;D;"] {
+                assert!(script.contains(mark), "{} 缺少 {}", shell, mark);
+            }
+            assert!(script.contains("]7;file://"), "{} 缺少 cwd 上报", shell);
+        }
+        assert!(get_shell_integration_script("powershell", ShellIntegration::Full).is_none());
+    }
+    
+    #[cfg(not(windows))]
+    #[test]
+    fn test_cwd_only_and_disabled_integration() {
+        for shell in ["bash", "zsh", "fish"] {
+            let script = get_shell_integration_script(shell, ShellIntegration::CwdOnly).unwrap();
+            assert!(script.contains("]7;file://"), "{} 缺少 cwd 上报", shell);
+            // 轻量版不改 prompt，也不上报命令边界
+            for untouched in ["133;", "PS1", "fish_prompt", "precmd"] {
+                assert!(!script.contains(untouched), "{} 轻量版包含 {}", shell, untouched);
+            }
+            assert!(get_shell_integration_script(shell, ShellIntegration::Off).is_none());
+        }
+    }
+    
     #[test]
     fn test_get_shell_by_type_unknown() {
         let _cmd = get_shell_by_type(Some("unknown_shell"));
         // 未知类型应该返回默认 shell
     }
+
+    #[test]
+    fn test_detect_available_shells() {
+        assert_eq!(parse_version("GNU bash, version 5.2.15(1)-release (x86_64-pc-linux-gnu)").as_deref(), Some("5.2.15"));
+        assert_eq!(parse_version("zsh 5.9 (x86_64-apple-darwin23.0)").as_deref(), Some("5.9"));
+        assert_eq!(parse_version("fish, version 3.7.0").as_deref(), Some("3.7.0"));
+        assert_eq!(parse_version("\r\nMicrosoft Windows [Version 10.0.22631.3155]\r\n").as_deref(), Some("10.0.22631.3155"));
+        assert_eq!(parse_version("v0.91.0\n").as_deref(), Some("0.91.0"));
+        assert_eq!(parse_version("usage: sh [options]"), None);
+
+        let shells = detect_available_shells();
+        assert_eq!(shells, detect_available_shells());
+        for shell in &shells {
+            assert!(Path::new(&shell.path).is_file(), "{:?}", shell);
+        }
+        #[cfg(not(windows))]
+        if let Some(path) = find_in_path("sh") {
+            // sh 总会出现 (自身或其链接目标)
+            let canonical = std::fs::canonicalize(&path).unwrap();
+            assert!(shells.iter().any(|s| std::fs::canonicalize(&s.path).unwrap() == canonical));
+        }
+    }
+
+    #[test]
+    fn test_env_filter() {
+        let filter = EnvFilter {
+            allow: Vec::new(),
+            deny: vec!["*SECRET*".to_string(), "*token*".to_string()],
+        };
+        assert!(!filter.keeps("AWS_SECRET_ACCESS_KEY"));
+        assert!(!filter.keeps("GITHUB_TOKEN"));
+        assert!(filter.keeps("EDITOR"));
+
+        let filter = EnvFilter {
+            allow: vec!["LC_*".to_string(), "EDITOR".to_string()],
+            deny: vec!["LC_SECRET".to_string()],
+        };
+        assert!(filter.keeps("LC_TIME") && filter.keeps("editor"));
+        assert!(!filter.keeps("LC_SECRET") && !filter.keeps("NPM_TOKEN"));
+        // 必要变量始终保留
+        assert!(filter.keeps("PATH") && filter.keeps("HOME"));
+        assert!(EnvFilter::default().keeps("API_TOKEN"));
+
+        let mut cmd = CommandBuilder::new("sh");
+        cmd.env("SW_TEST_SECRET", "1");
+        EnvFilter { allow: Vec::new(), deny: vec!["SW_TEST_*".to_string()] }.apply(&mut cmd);
+        assert!(cmd.get_env("SW_TEST_SECRET").is_none());
+    }
 }
@@ -4,6 +4,7 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use crate::server::WsSender;
+use tokio_util::sync::CancellationToken;
 
 /// 日志宏
 macro_rules! log_info {
@@ -66,6 +67,10 @@ pub struct ModuleMessage {
     /// 消息类型
     #[serde(rename = "type")]
     pub msg_type: String,
+    /// 消息 schema 版本 (缺省时按连接协商的版本处理，兼容未携带该字段的旧客户端)
+    #[serde(default)]
+    #[allow(dead_code)]
+    pub protocol_version: Option<u32>,
     /// 消息负载 (保留原始 JSON 以便各模块解析)
     #[serde(flatten)]
     pub payload: serde_json::Value,
@@ -98,7 +103,6 @@ pub struct ServerResponse {
 
 impl ServerResponse {
     /// 创建新的服务器响应
-    #[allow(dead_code)]
     pub fn new(module: ModuleType, msg_type: &str, payload: serde_json::Value) -> Self {
         Self {
             module,
@@ -120,6 +124,69 @@ impl ServerResponse {
     }
 }
 
+// ============================================================================
+// 协议版本
+// ============================================================================
+
+/// 消息协议版本
+///
+/// 握手时通过 Sec-WebSocket-Protocol 协商，连接期间保持不变；
+/// 新增的消息格式应作为新版本加入，旧版本客户端不受影响
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProtocolVersion {
+    V1,
+    /// 二进制帧带序号帧头，服务器逐块确认 (见 framing 模块)
+    V2,
+}
+
+impl ProtocolVersion {
+    /// 服务器支持的全部版本 (由低到高)
+    pub const SUPPORTED: &'static [ProtocolVersion] = &[ProtocolVersion::V1, ProtocolVersion::V2];
+
+    /// 未携带子协议的旧客户端按此版本处理
+    pub const LEGACY: ProtocolVersion = ProtocolVersion::V1;
+
+    /// 子协议名
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProtocolVersion::V1 => "sw-voice.v1",
+            ProtocolVersion::V2 => "sw-voice.v2",
+        }
+    }
+
+    /// 消息中 `protocol_version` 字段对应的版本号
+    pub fn number(&self) -> u32 {
+        match self {
+            ProtocolVersion::V1 => 1,
+            ProtocolVersion::V2 => 2,
+        }
+    }
+
+    /// 二进制帧是否带序号帧头
+    pub fn framed_binary(&self) -> bool {
+        *self >= ProtocolVersion::V2
+    }
+
+    pub fn from_number(number: u32) -> Option<Self> {
+        Self::SUPPORTED.iter().copied().find(|v| v.number() == number)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::SUPPORTED.iter().copied().find(|v| v.name() == name.trim())
+    }
+
+    /// 从客户端提供的子协议列表中选择支持的最高版本
+    pub fn negotiate<'a>(offered: impl IntoIterator<Item = &'a str>) -> Option<Self> {
+        offered.into_iter().filter_map(Self::from_name).max()
+    }
+}
+
+impl std::fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 // ============================================================================
 // 路由器错误
 // ============================================================================
@@ -141,6 +208,14 @@ pub enum RouterError {
     #[error("Module error: {0}")]
     ModuleError(String),
     
+    /// 带稳定错误码的模块错误
+    #[error("Module error [{code}]: {message}")]
+    Coded { code: &'static str, message: String },
+    
+    /// 消息声明的 schema 版本不在连接支持的范围内
+    #[error("Protocol version {requested} not supported (supported: {min}-{max})")]
+    ProtocolMismatch { requested: u32, min: u32, max: u32 },
+    
     /// JSON 序列化/反序列化错误
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
@@ -156,6 +231,7 @@ pub enum RouterError {
 #[async_trait::async_trait]
 pub trait ModuleHandler: Send + Sync {
     /// 获取模块类型
+    #[allow(dead_code)]
     fn module_type(&self) -> ModuleType;
     
     /// 处理消息
@@ -173,6 +249,8 @@ pub trait ModuleHandler: Send + Sync {
 /// 
 /// 负责将消息路由到对应的功能模块
 pub struct MessageRouter {
+    // 本连接协商的协议版本
+    protocol: ProtocolVersion,
     // PTY 模块处理器
     pty_handler: crate::pty::PtyHandler,
     // Voice 模块处理器
@@ -186,7 +264,13 @@ pub struct MessageRouter {
 impl MessageRouter {
     /// 创建新的消息路由器
     pub fn new() -> Self {
+        Self::with_protocol(ProtocolVersion::LEGACY)
+    }
+    
+    /// 创建使用指定协议版本的消息路由器
+    pub fn with_protocol(protocol: ProtocolVersion) -> Self {
         Self {
+            protocol,
             pty_handler: crate::pty::PtyHandler::new(),
             voice_handler: crate::voice::VoiceHandler::new(),
             llm_handler: crate::llm::LLMHandler::new(),
@@ -202,6 +286,17 @@ impl MessageRouter {
         self.utils_handler.set_ws_sender(sender).await;
     }
     
+    /// 设置连接级取消令牌，各模块派生任务监听其子令牌
+    pub async fn set_connection_token(&self, token: &CancellationToken) {
+        self.voice_handler.set_connection_token(token.child_token()).await;
+        self.llm_handler.set_connection_token(token.child_token()).await;
+    }
+    
+    /// 本连接协商的协议版本
+    pub fn protocol(&self) -> ProtocolVersion {
+        self.protocol
+    }
+    
     /// 获取 PTY 处理器引用 (用于写入数据)
     pub fn pty_handler(&self) -> &crate::pty::PtyHandler {
         &self.pty_handler
@@ -226,17 +321,43 @@ impl MessageRouter {
     /// 
     /// 返回 ModuleMessage 或错误
     pub fn parse_message(&self, text: &str) -> Result<ModuleMessage, RouterError> {
-        // 首先尝试解析为 ModuleMessage
-        let msg: ModuleMessage = serde_json::from_str(text)?;
+        let value: serde_json::Value = serde_json::from_str(text)?;
+        self.check_protocol_version(&value)?;
+        
+        // 按协商的协议版本解析 (新版本的消息格式在此分派)
+        let msg: ModuleMessage = match self.protocol {
+            ProtocolVersion::V1 | ProtocolVersion::V2 => serde_json::from_value(value)?,
+        };
         
         log_debug!("解析消息: module={}, type={}", msg.module, msg.msg_type);
         
         Ok(msg)
     }
     
+    /// 校验消息声明的 schema 版本
+    ///
+    /// 须不低于最低支持版本、且不高于连接协商的版本，否则新版本字段会被静默丢弃；
+    /// 未声明版本的消息按协商版本处理
+    fn check_protocol_version(&self, value: &serde_json::Value) -> Result<(), RouterError> {
+        let Some(requested) = value.get("protocol_version").filter(|v| !v.is_null()) else {
+            return Ok(());
+        };
+        let min = ProtocolVersion::SUPPORTED[0].number();
+        let max = self.protocol.number();
+        let requested = requested.as_u64()
+            .map(|v| u32::try_from(v).unwrap_or(u32::MAX))
+            .ok_or_else(|| RouterError::InvalidMessage(format!("protocol_version 必须是正整数: {}", requested)))?;
+        let compatible = ProtocolVersion::from_number(requested).is_some_and(|v| v <= self.protocol);
+        if !compatible {
+            return Err(RouterError::ProtocolMismatch { requested, min, max });
+        }
+        Ok(())
+    }
+    
     /// 尝试从原始 JSON 中解析模块类型
     /// 
     /// 用于在消息解析失败时提取模块信息以便返回正确的错误响应
+    #[allow(dead_code)]
     pub fn try_parse_module(&self, text: &str) -> Option<ModuleType> {
         if let Ok(value) = serde_json::from_str::<serde_json::Value>(text) {
             if let Some(module_str) = value.get("module").and_then(|v| v.as_str()) {
@@ -290,7 +411,17 @@ impl MessageRouter {
             RouterError::UnknownModule(m) => ("UNKNOWN_MODULE", format!("未知模块: {}", m)),
             RouterError::InvalidMessage(m) => ("INVALID_MESSAGE", format!("无效消息: {}", m)),
             RouterError::ModuleError(m) => ("MODULE_ERROR", m.clone()),
+            RouterError::Coded { code, message } => (*code, message.clone()),
             RouterError::JsonError(e) => ("JSON_ERROR", format!("JSON 错误: {}", e)),
+            RouterError::ProtocolMismatch { requested, min, max } => {
+                let mut response = ServerResponse::error(
+                    module,
+                    "PROTOCOL_MISMATCH",
+                    &format!("不支持协议版本 {}，支持范围 {}-{}", requested, min, max),
+                );
+                response.payload["supported"] = serde_json::json!({ "min": min, "max": max });
+                return response;
+            }
         };
         
         ServerResponse::error(module, code, &message)
@@ -322,6 +453,43 @@ impl Default for MessageRouter {
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_negotiate_protocol_version() {
+        assert_eq!(ProtocolVersion::negotiate(["sw-voice.v1"]), Some(ProtocolVersion::V1));
+        assert_eq!(
+            ProtocolVersion::negotiate(["sw-voice.v9", " sw-voice.v1"]),
+            Some(ProtocolVersion::V1)
+        );
+        assert_eq!(ProtocolVersion::negotiate(["sw-voice.v1", "sw-voice.v2"]), Some(ProtocolVersion::V2));
+        assert!(ProtocolVersion::V2.framed_binary() && !ProtocolVersion::V1.framed_binary());
+        assert_eq!(ProtocolVersion::negotiate(["graphql-ws"]), None);
+        assert_eq!(MessageRouter::new().protocol(), ProtocolVersion::LEGACY);
+    }
+    
+    #[test]
+    fn test_parse_checks_protocol_version() {
+        let router = MessageRouter::new();
+        
+        // 未携带版本的旧客户端按协商版本处理
+        let msg = router.parse_message(r#"{"module": "voice", "type": "cancel_transcription"}"#).unwrap();
+        assert_eq!(msg.protocol_version, None);
+        let msg = router
+            .parse_message(r#"{"module": "voice", "type": "cancel_transcription", "protocol_version": 1}"#)
+            .unwrap();
+        assert_eq!(msg.protocol_version, Some(1));
+        assert!(msg.payload.get("protocol_version").is_none());
+        
+        // 版本不兼容时即使模块未知也返回协议错误
+        let error = router.parse_message(r#"{"module": "future", "type": "x", "protocol_version": 2}"#).unwrap_err();
+        assert!(matches!(error, RouterError::ProtocolMismatch { requested: 2, min: 1, max: 1 }));
+        let response = router.create_error_response(ModuleType::Voice, &error);
+        assert_eq!(response.payload["code"], "PROTOCOL_MISMATCH");
+        assert_eq!(response.payload["supported"], serde_json::json!({ "min": 1, "max": 1 }));
+        
+        let error = router.parse_message(r#"{"module": "voice", "type": "x", "protocol_version": "1"}"#).unwrap_err();
+        assert!(matches!(error, RouterError::InvalidMessage(_)));
+    }
+    
     #[test]
     fn test_parse_pty_message() {
         let router = MessageRouter::new();
@@ -466,6 +634,20 @@ mod tests {
         assert_eq!(payload.get("message").unwrap().as_str().unwrap(), "Something went wrong");
     }
     
+    #[test]
+    fn test_create_error_response_coded() {
+        let router = MessageRouter::new();
+        let error = RouterError::Coded {
+            code: "NOT_RECORDING",
+            message: "未在录音中".to_string(),
+        };
+        let response = router.create_error_response(ModuleType::Voice, &error);
+        
+        let payload = response.payload.as_object().unwrap();
+        assert_eq!(payload.get("code").unwrap().as_str().unwrap(), "NOT_RECORDING");
+        assert_eq!(payload.get("message").unwrap().as_str().unwrap(), "未在录音中");
+    }
+    
     #[tokio::test]
     async fn test_utils_module_is_implemented() {
         let router = MessageRouter::new();
@@ -1,13 +1,35 @@
 // WebSocket 服务器实现
 // 统一的 WebSocket 服务器，处理所有模块的消息
 
-use tokio::net::TcpListener;
-use tokio_tungstenite::{accept_async, tungstenite::Message};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+use tokio_tungstenite::{
+    accept_hdr_async,
+    tungstenite::handshake::server::{ErrorResponse, Request, Response},
+    tungstenite::http::{HeaderValue, StatusCode},
+    tungstenite::protocol::{frame::coding::CloseCode, CloseFrame},
+    tungstenite::Message,
+    WebSocketStream,
+};
 use futures_util::{StreamExt, SinkExt};
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::Mutex as TokioMutex;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
-use crate::router::{MessageRouter, ModuleType, RouterError, ServerResponse};
+use crate::encoding::{self, Encoding, Incoming};
+use crate::framing::{self, ChunkOrder, ChunkSequencer};
+use crate::outbound::{self, Payload};
+use crate::pty::SessionId;
+use crate::router::{MessageRouter, ModuleType, ProtocolVersion, RouterError, ServerResponse};
+use crate::voice::usage::UsageQuota;
 
 /// 日志宏
 macro_rules! log_info {
@@ -34,9 +56,37 @@ macro_rules! log_debug {
 // 服务器配置和实现
 // ============================================================================
 
+/// 监听地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Listen {
+    /// 本机 TCP 端口 (0 表示随机端口)
+    Tcp(u16),
+    /// Unix domain socket 路径 (仅 Unix 平台)
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl fmt::Display for Listen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Listen::Tcp(port) => write!(f, "127.0.0.1:{}", port),
+            #[cfg(unix)]
+            Listen::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
 /// WebSocket 服务器配置
 pub struct ServerConfig {
-    pub port: u16,
+    pub listen: Listen,
+    /// 每个连接的转录配额
+    pub quota: UsageQuota,
+    /// HTTP SSE 转录接口端口 (None 表示不启用，0 表示随机端口)
+    pub http_port: Option<u16>,
+    /// 临时文件目录 (启动时校验可写)
+    pub temp_dir: PathBuf,
+    /// 同时保持的最大连接数 (None 表示不限)
+    pub max_connections: Option<usize>,
 }
 
 /// WebSocket 服务器
@@ -49,37 +99,252 @@ impl Server {
         Self { config }
     }
 
-    /// 启动服务器
-    pub async fn start(&self) -> Result<u16, Box<dyn std::error::Error>> {
-        let addr = format!("127.0.0.1:{}", self.config.port);
-        let listener = TcpListener::bind(&addr).await?;
-        let local_addr = listener.local_addr()?;
-        let port = local_addr.port();
+    /// 启动服务器，返回实际监听的地址 (随机端口已解析)
+    pub async fn start(&self) -> Result<Listen, Box<dyn std::error::Error>> {
+        crate::utils::temp_dir::init(&self.config.temp_dir)?;
+        log_info!("临时目录: {}", self.config.temp_dir.display());
+
+        let listener = ServerListener::bind(&self.config.listen).await?;
+        let listen = listener.local_listen()?;
 
-        log_info!("服务器绑定到 {}", local_addr);
+        log_info!("服务器绑定到 {}", listen);
+        if !self.config.quota.is_unlimited() {
+            log_info!("连接配额: {:?}", self.config.quota);
+        }
+        if let Some(max) = self.config.max_connections {
+            log_info!("最大连接数: {}", max);
+        }
 
+        let limiter = ConnectionLimiter::new(self.config.max_connections);
+        let http_port = match self.config.http_port {
+            Some(http_port) => Some(crate::http_server::start(http_port, limiter.clone()).await?),
+            None => None,
+        };
+        
         // 输出端口信息到 stdout (JSON 格式)
-        // TypeScript 端会解析这个 JSON 来获取端口号
-        println!(
-            r#"{{"port": {}, "pid": {}}}"#,
-            port,
-            std::process::id()
-        );
+        // TypeScript 端会解析这个 JSON 来获取端口号 (UDS 时为 socket 路径)
+        match (&listen, http_port) {
+            (Listen::Tcp(port), Some(http_port)) => println!(
+                r#"{{"port": {}, "pid": {}, "http_port": {}}}"#,
+                port,
+                std::process::id(),
+                http_port
+            ),
+            (Listen::Tcp(port), None) => println!(
+                r#"{{"port": {}, "pid": {}}}"#,
+                port,
+                std::process::id()
+            ),
+            #[cfg(unix)]
+            (Listen::Unix(path), http_port) => {
+                let mut info = serde_json::json!({
+                    "socket": path.to_string_lossy(),
+                    "pid": std::process::id(),
+                });
+                if let Some(http_port) = http_port {
+                    info["http_port"] = serde_json::json!(http_port);
+                }
+                println!("{}", info);
+            }
+        }
 
         // 主循环：接受 WebSocket 连接
+        let quota = self.config.quota;
         tokio::spawn(async move {
             log_info!("正在监听 WebSocket 连接...");
-            while let Ok((stream, addr)) = listener.accept().await {
-                log_debug!("接受来自 {} 的连接", addr);
+            while let Ok((stream, peer)) = listener.accept().await {
+                log_debug!("接受来自 {} 的连接", peer);
+                let Some(guard) = limiter.try_acquire() else {
+                    log_info!("连接数已达上限 ({})，拒绝来自 {} 的连接", limiter.active(), peer);
+                    tokio::spawn(async move {
+                        if let Err(e) = reject_connection(stream).await {
+                            log_debug!("拒绝连接时出错: {}", e);
+                        }
+                    });
+                    continue;
+                };
                 tokio::spawn(async move {
-                    if let Err(e) = handle_connection(stream).await {
+                    // guard 随任务结束释放，连接计数在任何返回路径上都会递减
+                    let _guard = guard;
+                    if let Err(e) = handle_connection(stream, quota).await {
                         log_error!("连接处理错误: {}", e);
                     }
                 });
             }
         });
 
-        Ok(port)
+        Ok(listen)
+    }
+
+    /// 退出前清理 (删除 Unix socket 文件)
+    pub fn shutdown(&self) {
+        #[cfg(unix)]
+        if let Listen::Unix(path) = &self.config.listen {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+// ============================================================================
+// 连接数限制
+// ============================================================================
+
+/// 活动连接计数与上限
+#[derive(Clone)]
+pub(crate) struct ConnectionLimiter {
+    active: Arc<AtomicUsize>,
+    max: Option<usize>,
+}
+
+impl ConnectionLimiter {
+    pub(crate) fn new(max: Option<usize>) -> Self {
+        Self {
+            active: Arc::new(AtomicUsize::new(0)),
+            max,
+        }
+    }
+
+    /// 占用一个连接名额，已达上限时返回 None
+    fn try_acquire(&self) -> Option<ConnectionGuard> {
+        let max = self.max.unwrap_or(usize::MAX);
+        self.active
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |active| (active < max).then_some(active + 1))
+            .ok()?;
+        Some(ConnectionGuard { active: Arc::clone(&self.active) })
+    }
+
+    /// 当前活动连接数
+    pub(crate) fn active(&self) -> usize {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// 连接数上限
+    pub(crate) fn max(&self) -> Option<usize> {
+        self.max
+    }
+
+    /// 是否已达上限 (新连接会被拒绝)
+    pub(crate) fn is_full(&self) -> bool {
+        self.max.is_some_and(|max| self.active() >= max)
+    }
+}
+
+/// 连接名额，drop 时归还
+struct ConnectionGuard {
+    active: Arc<AtomicUsize>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// ============================================================================
+// 监听与连接流
+// ============================================================================
+
+/// TCP 或 Unix domain socket 监听器
+enum ServerListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl ServerListener {
+    async fn bind(listen: &Listen) -> io::Result<Self> {
+        match listen {
+            Listen::Tcp(port) => Ok(Self::Tcp(TcpListener::bind(("127.0.0.1", *port)).await?)),
+            #[cfg(unix)]
+            Listen::Unix(path) => {
+                use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+                // 清理上次异常退出遗留的 socket 文件，非 socket 文件不覆盖
+                if let Ok(metadata) = std::fs::symlink_metadata(path) {
+                    if !metadata.file_type().is_socket() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::AlreadyExists,
+                            format!("{} 已存在且不是 socket 文件", path.display()),
+                        ));
+                    }
+                    std::fs::remove_file(path)?;
+                }
+                let listener = UnixListener::bind(path)?;
+                // 仅允许当前用户连接
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+                Ok(Self::Unix(listener))
+            }
+        }
+    }
+
+    fn local_listen(&self) -> io::Result<Listen> {
+        match self {
+            Self::Tcp(listener) => Ok(Listen::Tcp(listener.local_addr()?.port())),
+            #[cfg(unix)]
+            Self::Unix(listener) => listener
+                .local_addr()?
+                .as_pathname()
+                .map(|path| Listen::Unix(path.to_path_buf()))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Unix socket 没有路径")),
+        }
+    }
+
+    /// 接受连接，返回连接流与对端描述 (用于日志)
+    async fn accept(&self) -> io::Result<(ServerStream, String)> {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, addr) = listener.accept().await?;
+                Ok((ServerStream::Tcp(stream), addr.to_string()))
+            }
+            #[cfg(unix)]
+            Self::Unix(listener) => {
+                let (stream, _) = listener.accept().await?;
+                Ok((ServerStream::Unix(stream), "unix socket".to_string()))
+            }
+        }
+    }
+}
+
+/// WebSocket 底层连接流
+pub enum ServerStream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl AsyncRead for ServerStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for ServerStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            #[cfg(unix)]
+            Self::Unix(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+            #[cfg(unix)]
+            Self::Unix(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
     }
 }
 
@@ -87,33 +352,114 @@ impl Server {
 // 连接处理
 // ============================================================================
 
-/// WebSocket 发送器类型别名
-pub type WsSender = Arc<TokioMutex<futures_util::stream::SplitSink<
-    tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>,
-    Message
->>>;
+pub use crate::outbound::WsSender;
+
+/// 每连接发送队列容量 (消息数)
+const SEND_QUEUE_CAPACITY: usize = 256;
+
+/// 连接结束后等待写任务发完剩余消息的最长时间
+const WRITER_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 握手协商的连接参数
+struct Negotiated {
+    protocol: ProtocolVersion,
+    encoding: Encoding,
+}
+
+/// 升级到 WebSocket，同时协商协议版本与消息编码
+async fn accept_websocket(
+    stream: ServerStream,
+) -> Result<(WebSocketStream<ServerStream>, Negotiated), tokio_tungstenite::tungstenite::Error> {
+    let mut negotiated = Negotiated { protocol: ProtocolVersion::LEGACY, encoding: Encoding::Json };
+    #[allow(clippy::result_large_err)] // 签名由 tungstenite Callback 决定
+    let ws_stream = accept_hdr_async(stream, |request: &Request, response: Response| {
+        let mut response = negotiate_protocol(request, response)?;
+        if let Some(version) = response
+            .headers()
+            .get(SEC_WEBSOCKET_PROTOCOL)
+            .and_then(|value| value.to_str().ok())
+            .and_then(ProtocolVersion::from_name)
+        {
+            negotiated.protocol = version;
+        }
+        negotiated.encoding = Encoding::from_query(request.uri().query());
+        if negotiated.encoding != Encoding::Json {
+            response
+                .headers_mut()
+                .insert(encoding::ENCODING_HEADER, HeaderValue::from_static(negotiated.encoding.name()));
+        }
+        Ok(response)
+    }).await?;
+    Ok((ws_stream, negotiated))
+}
+
+/// 连接数已达上限：完成握手后发送错误并关闭连接
+async fn reject_connection(stream: ServerStream) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let (mut ws_stream, negotiated) = accept_websocket(stream).await?;
+    let response = ServerResponse::error(ModuleType::Utils, "TOO_MANY_CONNECTIONS", "连接数已达上限，请稍后重试");
+    let message = negotiated.encoding.encode(Payload::Value(serde_json::to_value(&response)?));
+    ws_stream.send(message).await?;
+    ws_stream.close(Some(CloseFrame {
+        code: CloseCode::Again,
+        reason: "too many connections".into(),
+    })).await?;
+    Ok(())
+}
 
 /// 处理单个 WebSocket 连接
 async fn handle_connection(
-    stream: tokio::net::TcpStream,
+    stream: ServerStream,
+    quota: UsageQuota,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // 升级到 WebSocket
-    let ws_stream = accept_async(stream).await?;
-    
-    log_info!("WebSocket 连接已建立");
+    let (ws_stream, Negotiated { protocol, encoding }) = accept_websocket(stream).await?;
     
-    // 分离读写流
-    let (ws_sender, mut ws_receiver) = ws_stream.split();
-    let ws_sender: WsSender = Arc::new(TokioMutex::new(ws_sender));
+    // 分离读写流，写端交给专门的写任务，各模块通过发送队列发消息
+    let (sink, mut ws_receiver) = ws_stream.split();
+    let (ws_sender, outbound_rx) = outbound::channel(SEND_QUEUE_CAPACITY);
+    let writer = tokio::spawn(outbound::run_writer(outbound_rx, sink, encoding));
     
     // 创建消息路由器
-    let router = Arc::new(MessageRouter::new());
+    let router = Arc::new(MessageRouter::with_protocol(protocol));
+    log_info!("WebSocket 连接已建立，协议版本: {}，消息编码: {}", router.protocol(), encoding);
     
     // 设置 WebSocket 发送器 (用于 PTY 输出)
-    router.set_ws_sender(Arc::clone(&ws_sender)).await;
+    router.set_ws_sender(ws_sender.clone()).await;
+    router.voice_handler().set_usage_quota(quota).await;
+    
+    // 连接级取消令牌：连接关闭 (含异常返回) 时取消，派生任务随之停止
+    let connection_token = CancellationToken::new();
+    router.set_connection_token(&connection_token).await;
+    let _cancel_on_close = connection_token.clone().drop_guard();
+    
+    // 标准输入转录 (--stdin-output ws) 的结果转发给每个连接
+    tokio::spawn(crate::voice::stdin::forward_broadcast(ws_sender.clone(), connection_token.clone()));
     
-    // 消息处理循环
-    while let Some(msg_result) = ws_receiver.next().await {
+    // 消息处理循环 (同时监听录音设备断开)
+    let mut closed_by_client = false;
+    let mut primary_input = PrimaryInput::default();
+    loop {
+        let msg_result = tokio::select! {
+            msg_result = ws_receiver.next() => match msg_result {
+                Some(msg_result) => msg_result,
+                None => break,
+            },
+            recording_id = router.voice_handler().device_lost() => {
+                router.voice_handler().handle_device_lost(recording_id).await;
+                continue;
+            }
+            _ = router.voice_handler().offline_retry_due() => {
+                // 重试可能等待网络超时，放到后台，不阻塞本连接的消息处理
+                let router = Arc::clone(&router);
+                let token = connection_token.clone();
+                tokio::spawn(async move {
+                    tokio::select! {
+                        _ = token.cancelled() => {}
+                        _ = router.voice_handler().retry_offline_queue() => {}
+                    }
+                });
+                continue;
+            }
+        };
         match msg_result {
             Ok(msg) => {
                 log_debug!("收到消息类型: {:?}", std::mem::discriminant(&msg));
@@ -130,22 +476,37 @@ async fn handle_connection(
                         }
                     }
                     Message::Binary(data) => {
-                        // 二进制数据 - 写入 PTY
                         log_debug!("收到二进制数据: {} 字节", data.len());
-                        if router.pty_handler().is_initialized().await {
-                            if let Err(e) = router.pty_handler().write_data(&data).await {
-                                log_error!("写入 PTY 失败: {}", e);
+                        match encoding.decode_binary(&data) {
+                            // MessagePack 控制消息按文本消息处理
+                            Ok(Incoming::Control(text)) => {
+                                if let Err(e) = handle_text_message(&text, &router, &ws_sender).await {
+                                    log_error!("消息处理错误: {}", e);
+                                }
+                            }
+                            // 二进制数据 - 写入 PTY (v2 连接先解析帧头并确认)
+                            Ok(Incoming::Data(data)) => {
+                                if router.protocol().framed_binary() {
+                                    handle_framed_binary(data, &router, &ws_sender, &mut primary_input).await?;
+                                } else {
+                                    write_pty_input(&router, data).await;
+                                }
+                            }
+                            Err(e) => {
+                                log_error!("二进制帧解码失败: {}", e);
+                                let response = ServerResponse::error(ModuleType::Utils, "INVALID_FRAME", &e.to_string());
+                                send_response(&ws_sender, &response).await?;
                             }
                         }
                     }
                     Message::Close(_) => {
                         log_info!("客户端关闭连接");
+                        closed_by_client = true;
                         break;
                     }
                     Message::Ping(data) => {
                         // 响应 Ping
-                        let mut sender = ws_sender.lock().await;
-                        sender.send(Message::Pong(data)).await?;
+                        ws_sender.send(Message::Pong(data)).await?;
                     }
                     Message::Pong(_) => {
                         // 忽略 Pong
@@ -163,13 +524,16 @@ async fn handle_connection(
     }
     
     log_info!("WebSocket 连接已关闭");
+    if primary_input.sequencer.lost() > 0 {
+        log_info!("主会话共丢失 {} 个二进制块", primary_input.sequencer.lost());
+    }
+    connection_token.cancel();
     
     // 清理 PTY 会话
-    if router.pty_handler().is_initialized().await {
-        let _ = router.pty_handler().kill().await;
-    }
+    router.pty_handler().cleanup().await;
     
-    // 清理 Voice 模块资源
+    // 清理 Voice 模块资源 (异常断开时先保存会话快照)
+    router.voice_handler().end_session(closed_by_client).await;
     router.voice_handler().cleanup().await;
     
     // 清理 LLM 模块资源
@@ -178,9 +542,57 @@ async fn handle_connection(
     // 清理 Utils 模块资源
     router.utils_handler().cleanup().await;
     
+    // 关闭发送队列，等待写任务发完已入队的消息
+    ws_sender.close();
+    if tokio::time::timeout(WRITER_DRAIN_TIMEOUT, writer).await.is_err() {
+        log_debug!("写任务未能在限时内发完剩余消息");
+    }
+    
     Ok(())
 }
 
+const SEC_WEBSOCKET_PROTOCOL: &str = "Sec-WebSocket-Protocol";
+
+/// 握手阶段的子协议 (协议版本) 协商
+///
+/// 未携带子协议的旧客户端按 LEGACY 版本处理且不回显；
+/// 携带子协议时选择支持的最高版本回显，均不支持则拒绝握手
+#[allow(clippy::result_large_err)] // 签名由 tungstenite Callback 决定
+fn negotiate_protocol(request: &Request, mut response: Response) -> Result<Response, ErrorResponse> {
+    let offered: Vec<&str> = request
+        .headers()
+        .get_all(SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect();
+
+    if offered.is_empty() {
+        return Ok(response);
+    }
+
+    match ProtocolVersion::negotiate(offered.iter().copied()) {
+        Some(version) => {
+            response
+                .headers_mut()
+                .insert(SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_static(version.name()));
+            Ok(response)
+        }
+        None => {
+            log_error!("不支持的协议版本: {:?}，拒绝握手", offered);
+            let supported: Vec<&str> = ProtocolVersion::SUPPORTED.iter().map(|v| v.name()).collect();
+            let mut error = ErrorResponse::new(Some(format!(
+                "不支持的协议版本，服务器支持: {}",
+                supported.join(", ")
+            )));
+            *error.status_mut() = StatusCode::BAD_REQUEST;
+            Err(error)
+        }
+    }
+}
+
 /// 处理文本消息
 async fn handle_text_message(
     text: &str,
@@ -210,6 +622,12 @@ async fn handle_text_message(
                 }
             }
         }
+        Err(e @ RouterError::ProtocolMismatch { .. }) => {
+            // 版本不兼容的消息不作为 PTY 输入
+            log_error!("协议版本不兼容: {}", e);
+            let error_response = router.create_error_response(extract_module_from_json(text), &e);
+            send_response(ws_sender, &error_response).await?;
+        }
         Err(e) => {
             // 消息解析错误 - 可能是纯文本输入 (用于 PTY)
             // 如果 PTY 已初始化，将文本写入 PTY
@@ -233,6 +651,84 @@ async fn handle_text_message(
     Ok(())
 }
 
+/// 二进制数据写入 PTY (PTY 未初始化时丢弃)
+async fn write_pty_input(router: &Arc<MessageRouter>, data: &[u8]) {
+    if router.pty_handler().is_initialized().await {
+        if let Err(e) = router.pty_handler().write_data(data).await {
+            log_error!("写入 PTY 失败: {}", e);
+        }
+    }
+}
+
+/// 主会话的块序号跟踪 (二进制帧只写入主会话，主会话重建后重新开始)
+#[derive(Default)]
+struct PrimaryInput {
+    session: Option<SessionId>,
+    sequencer: ChunkSequencer,
+}
+
+/// 处理带帧头的二进制消息：解析失败时回 INVALID_FRAME 错误，否则按序写入主会话并回 chunk_ack
+///
+/// 序号跳变后的块先缓存，缺失块重传补齐后按序写入；已收到过的块只确认不重复写入；
+/// 已放弃等待的块丢弃并回 CHUNK_EXPIRED，不确认。尚未补到的块数随确认一并返回
+async fn handle_framed_binary(
+    data: &[u8],
+    router: &Arc<MessageRouter>,
+    ws_sender: &WsSender,
+    input: &mut PrimaryInput,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let (seq, payload) = match framing::parse_chunk(data) {
+        Ok(chunk) => chunk,
+        Err(e) => {
+            log_error!("二进制帧头解析失败: {}", e);
+            let response = ServerResponse::error(ModuleType::Pty, "INVALID_FRAME", &e.to_string());
+            return send_response(ws_sender, &response).await;
+        }
+    };
+
+    let session = router.pty_handler().primary_session();
+    if input.session != session {
+        // 缓存的块属于已关闭的会话，随之丢弃
+        if input.sequencer.lost() > 0 {
+            log_info!("主会话已更换，原会话共丢失 {} 个二进制块", input.sequencer.lost());
+        }
+        *input = PrimaryInput { session, ..PrimaryInput::default() };
+    }
+
+    let accepted = input.sequencer.accept(seq, payload);
+    match accepted.order {
+        ChunkOrder::InOrder => {}
+        ChunkOrder::Buffered { missing } => {
+            log_info!("二进制块序号跳变: 收到 {}，缓存等待 {} 个缺失块", seq, missing);
+        }
+        ChunkOrder::Late => {
+            log_debug!("补到缺失的二进制块: {}，连同缓存共写入 {} 块", seq, accepted.ready.len());
+        }
+        ChunkOrder::Duplicate => {
+            log_debug!("重复的二进制块: {}", seq);
+        }
+        ChunkOrder::Expired => {
+            log_info!("二进制块 {} 已放弃等待，丢弃", seq);
+            let response = ServerResponse::error(
+                ModuleType::Pty,
+                "CHUNK_EXPIRED",
+                &format!("块 {} 已过期，未写入", seq),
+            );
+            return send_response(ws_sender, &response).await;
+        }
+    }
+    // 窗口前移时也会写出此前缓存的块
+    for chunk in &accepted.ready {
+        write_pty_input(router, chunk).await;
+    }
+
+    let ack = ServerResponse::new(ModuleType::Pty, "chunk_ack", serde_json::json!({
+        "seq": seq,
+        "lost": input.sequencer.lost(),
+    }));
+    send_response(ws_sender, &ack).await
+}
+
 /// 从 JSON 中提取 module 字段
 fn extract_module_from_json(text: &str) -> ModuleType {
     // 尝试解析 JSON 并提取 module 字段
@@ -266,28 +762,49 @@ pub async fn send_response(
     ws_sender: &WsSender,
     response: &ServerResponse,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let json = serde_json::to_string(response)?;
-    let mut sender = ws_sender.lock().await;
-    sender.send(Message::Text(json.into())).await?;
+    ws_sender.send(serde_json::to_value(response)?).await?;
     Ok(())
 }
 
 /// 发送原始 JSON 消息
+#[allow(dead_code)]
 pub async fn send_json(
     ws_sender: &WsSender,
     json: &str,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let mut sender = ws_sender.lock().await;
-    sender.send(Message::Text(json.to_string().into())).await?;
+    ws_sender.send(serde_json::from_str::<serde_json::Value>(json)?).await?;
     Ok(())
 }
 
 /// 发送二进制消息
+#[allow(dead_code)]
 pub async fn send_binary(
     ws_sender: &WsSender,
     data: Vec<u8>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let mut sender = ws_sender.lock().await;
-    sender.send(Message::Binary(data.into())).await?;
+    ws_sender.send(Message::Binary(data.into())).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_limiter_releases_on_drop() {
+        let limiter = ConnectionLimiter::new(Some(2));
+        let first = limiter.try_acquire().unwrap();
+        let second = limiter.try_acquire().unwrap();
+        assert!(limiter.try_acquire().is_none());
+        assert_eq!(limiter.active(), 2);
+
+        drop(first);
+        assert_eq!(limiter.active(), 1);
+        let third = limiter.try_acquire().unwrap();
+        assert!(limiter.try_acquire().is_none());
+
+        drop((second, third));
+        assert_eq!(limiter.active(), 0);
+        assert!(ConnectionLimiter::new(None).try_acquire().is_some());
+    }
+}
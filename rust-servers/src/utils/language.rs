@@ -93,9 +93,9 @@ impl LanguageDetector {
                 // 如果是中文，进一步区分简繁体
                 if lang == Lang::Cmn {
                     let is_simplified = self.is_simplified_chinese(text);
-                    LanguageDetectionResult::chinese(confidence as f64, is_simplified)
+                    LanguageDetectionResult::chinese(confidence, is_simplified)
                 } else {
-                    LanguageDetectionResult::new(&iso_code, confidence as f64)
+                    LanguageDetectionResult::new(&iso_code, confidence)
                 }
             }
             None => {
@@ -2,6 +2,7 @@
 // 提供语言检测等通用工具功能
 
 pub mod language;
+pub mod temp_dir;
 
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -216,6 +217,7 @@ mod tests {
         let msg = ModuleMessage {
             module: ModuleType::Utils,
             msg_type: "detect_language".to_string(),
+            protocol_version: None,
             payload: serde_json::json!({
                 "text": "Hello, this is a test message.",
                 "request_id": "test-789"
@@ -245,6 +247,7 @@ mod tests {
         let msg = ModuleMessage {
             module: ModuleType::Utils,
             msg_type: "unknown_type".to_string(),
+            protocol_version: None,
             payload: serde_json::json!({}),
         };
         
@@ -266,6 +269,7 @@ mod tests {
         let msg = ModuleMessage {
             module: ModuleType::Utils,
             msg_type: "detect_language".to_string(),
+            protocol_version: None,
             payload: serde_json::json!({
                 "text": "Hello"
                 // 缺少 request_id
@@ -0,0 +1,76 @@
+// 临时文件目录
+// 调试音频、录音缓存等临时写入统一放在此目录，启动时由 ServerConfig.temp_dir 指定并校验可写
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// 指定临时目录的环境变量
+pub const TEMP_DIR_ENV: &str = "SMART_WORKFLOW_TEMP_DIR";
+
+static TEMP_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// 默认临时目录：环境变量指定的目录，否则为系统临时目录
+pub fn default_temp_dir() -> PathBuf {
+    std::env::var_os(TEMP_DIR_ENV)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+/// 校验目录可写 (不存在时创建)，并设为进程的临时目录
+pub fn init(dir: &Path) -> Result<(), String> {
+    ensure_writable(dir)?;
+    if TEMP_DIR.set(dir.to_path_buf()).is_err() {
+        return Err("临时目录已初始化".to_string());
+    }
+    Ok(())
+}
+
+/// 当前临时目录 (未初始化时为默认目录)
+pub fn temp_dir() -> PathBuf {
+    TEMP_DIR.get().cloned().unwrap_or_else(default_temp_dir)
+}
+
+/// 解析临时写入路径：相对路径放在临时目录下，绝对路径保持不变
+pub fn resolve(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        temp_dir().join(path)
+    }
+}
+
+/// 在目录中实际写入并删除一个探测文件
+fn ensure_writable(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("无法创建临时目录 {}: {}", dir.display(), e))?;
+
+    let probe = dir.join(format!(".smart-workflow-probe-{}", std::process::id()));
+    std::fs::write(&probe, b"probe")
+        .map_err(|e| format!("临时目录不可写 {}: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_writable() {
+        let dir = std::env::temp_dir().join(format!("sw-temp-dir-{}", std::process::id()));
+        assert!(ensure_writable(&dir).is_ok());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir(&dir).unwrap();
+
+        // 以可执行文件作为父目录，必然无法创建
+        let invalid = std::env::current_exe().unwrap().join("tmp");
+        let err = ensure_writable(&invalid).unwrap_err();
+        assert!(err.contains(&invalid.display().to_string()), "{}", err);
+
+        let absolute = std::env::temp_dir().join("a.wav");
+        assert_eq!(resolve(&absolute), absolute);
+        assert_eq!(resolve("debug/a.wav"), temp_dir().join("debug/a.wav"));
+    }
+}
@@ -0,0 +1,287 @@
+// 引擎熔断器
+// 包装 ASREngine：连续失败达到阈值后熔断 (open)，冷却期内直接返回最近一次错误；
+// 冷却结束后进入半开 (half_open)，只放行一个试探请求，成功则恢复，失败则重新熔断
+
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::voice::asr::{ASREngine, ASRError, ASRMode, AudioRequirements, RealtimeSession, RetryConfig, Transcript};
+use crate::voice::audio::AudioData;
+use crate::voice::config::CircuitBreakerConfig;
+
+/// 熔断状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// 正常放行
+    Closed,
+    /// 熔断中，直接快速失败
+    Open,
+    /// 试探请求进行中
+    HalfOpen,
+}
+
+/// 熔断器状态快照 (供指标上报)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct CircuitStatus {
+    pub state: CircuitState,
+    /// 当前连续失败次数
+    pub consecutive_failures: u32,
+    /// 熔断剩余冷却时间 (毫秒，仅 open 状态)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_in_ms: Option<u64>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: CircuitState,
+    consecutive_failures: u32,
+    /// open 状态下允许试探的时间
+    reopen_at: Instant,
+    /// 最近一次失败的错误 (熔断期间原样返回)
+    last_error: Option<ASRError>,
+}
+
+/// 请求准入结果
+enum Admission {
+    Allowed,
+    Probe,
+    Rejected(ASRError),
+}
+
+/// 带熔断的引擎包装
+pub struct CircuitBreakerEngine {
+    engine: Arc<dyn ASREngine>,
+    failure_threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreakerEngine {
+    pub fn new(engine: Arc<dyn ASREngine>, config: CircuitBreakerConfig) -> Self {
+        Self {
+            engine,
+            failure_threshold: config.failure_threshold.max(1),
+            cooldown: Duration::from_millis(config.cooldown_ms),
+            inner: Mutex::new(Inner {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                reopen_at: Instant::now(),
+                last_error: None,
+            }),
+        }
+    }
+
+    /// 当前熔断状态
+    pub fn status(&self) -> CircuitStatus {
+        let inner = self.inner.lock().unwrap();
+        let retry_in_ms = (inner.state == CircuitState::Open)
+            .then(|| inner.reopen_at.saturating_duration_since(Instant::now()).as_millis() as u64);
+        CircuitStatus {
+            state: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+            retry_in_ms,
+        }
+    }
+
+    fn admit(&self) -> Admission {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            CircuitState::Closed => Admission::Allowed,
+            CircuitState::Open if Instant::now() >= inner.reopen_at => {
+                inner.state = CircuitState::HalfOpen;
+                eprintln!("[INFO] 引擎 {} 熔断冷却结束，放行试探请求", self.engine.name());
+                Admission::Probe
+            }
+            CircuitState::Open | CircuitState::HalfOpen => Admission::Rejected(
+                inner.last_error.clone()
+                    .unwrap_or_else(|| ASRError::network(format!("引擎 {} 已熔断", self.engine.name()))),
+            ),
+        }
+    }
+
+    fn record<T>(&self, result: &Result<T, ASRError>) {
+        let mut inner = self.inner.lock().unwrap();
+        match result {
+            Ok(_) => {
+                if inner.state != CircuitState::Closed {
+                    eprintln!("[INFO] 引擎 {} 试探成功，熔断恢复", self.engine.name());
+                }
+                inner.state = CircuitState::Closed;
+                inner.consecutive_failures = 0;
+                inner.last_error = None;
+            }
+            Err(e) if counts_as_failure(e) => {
+                inner.consecutive_failures += 1;
+                inner.last_error = Some(e.clone());
+                if inner.state == CircuitState::HalfOpen || inner.consecutive_failures >= self.failure_threshold {
+                    inner.state = CircuitState::Open;
+                    inner.reopen_at = Instant::now() + self.cooldown;
+                    eprintln!(
+                        "[WARN] 引擎 {} 连续失败 {} 次，熔断 {}ms: {}",
+                        self.engine.name(),
+                        inner.consecutive_failures,
+                        self.cooldown.as_millis(),
+                        e
+                    );
+                }
+            }
+            // 与引擎健康无关的错误：试探结果不确定，允许下一次请求继续试探
+            Err(_) => self.release_probe(&mut inner),
+        }
+    }
+
+    fn release_probe(&self, inner: &mut Inner) {
+        if inner.state == CircuitState::HalfOpen {
+            inner.state = CircuitState::Open;
+            inner.reopen_at = Instant::now();
+        }
+    }
+
+    /// 执行一次受熔断保护的调用
+    async fn call<T>(&self, future: impl std::future::Future<Output = Result<T, ASRError>>) -> Result<T, ASRError> {
+        if let Admission::Rejected(error) = self.admit() {
+            return Err(error);
+        }
+        // 调用被取消 (future 被丢弃) 时释放试探名额，避免一直停留在半开状态
+        let guard = ProbeGuard { breaker: self, armed: true };
+        let result = future.await;
+        guard.disarm();
+        self.record(&result);
+        result
+    }
+}
+
+/// 音频无效、调用取消等与引擎健康无关的错误不计入失败
+fn counts_as_failure(error: &ASRError) -> bool {
+    // 客户端限速不代表服务故障，引擎不支持的操作 (如 n-best) 也不代表
+    !matches!(
+        error,
+        ASRError::InvalidAudio(_) | ASRError::Cancelled | ASRError::RateLimited { .. } | ASRError::UnsupportedOperation(_)
+    )
+}
+
+struct ProbeGuard<'a> {
+    breaker: &'a CircuitBreakerEngine,
+    armed: bool,
+}
+
+impl ProbeGuard<'_> {
+    fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            if let Ok(mut inner) = self.breaker.inner.lock() {
+                self.breaker.release_probe(&mut inner);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ASREngine for CircuitBreakerEngine {
+    fn name(&self) -> &str {
+        self.engine.name()
+    }
+
+    fn supported_modes(&self) -> Vec<ASRMode> {
+        self.engine.supported_modes()
+    }
+
+    fn audio_requirements(&self) -> AudioRequirements {
+        self.engine.audio_requirements()
+    }
+
+    async fn transcribe(&self, audio: &AudioData) -> Result<String, ASRError> {
+        self.call(self.engine.transcribe(audio)).await
+    }
+
+    async fn transcribe_detailed(&self, audio: &AudioData) -> Result<Transcript, ASRError> {
+        self.call(self.engine.transcribe_detailed(audio)).await
+    }
+
+    async fn transcribe_nbest(&self, audio: &AudioData, n: usize) -> Result<Vec<(String, f32)>, ASRError> {
+        self.call(self.engine.transcribe_nbest(audio, n)).await
+    }
+
+    async fn create_realtime_session(&self) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        self.call(self.engine.create_realtime_session()).await
+    }
+
+    async fn create_realtime_session_with_language(&self, language: &str) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        self.call(self.engine.create_realtime_session_with_language(language)).await
+    }
+
+    fn default_retry(&self) -> RetryConfig {
+        self.engine.default_retry()
+    }
+
+    fn is_local(&self) -> bool {
+        self.engine.is_local()
+    }
+
+    async fn warm_up(&self) -> Result<(), ASRError> {
+        self.engine.warm_up().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voice::asr::fake::FakeEngine;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_breaker_opens_fails_fast_and_recovers() {
+        // 按开关成功或失败的引擎，记录实际调用次数
+        let failing = Arc::new(AtomicBool::new(true));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let engine = {
+            let (failing, calls) = (Arc::clone(&failing), Arc::clone(&calls));
+            FakeEngine::http("flaky").with_transcribe(move || {
+                calls.fetch_add(1, Ordering::SeqCst);
+                let result = if failing.load(Ordering::SeqCst) {
+                    Err(ASRError::network("connection reset"))
+                } else {
+                    Ok("ok".to_string())
+                };
+                async move { result }
+            })
+        };
+        let breaker = CircuitBreakerEngine::new(
+            Arc::new(engine),
+            CircuitBreakerConfig { enabled: true, failure_threshold: 3, cooldown_ms: 50 },
+        );
+        let audio = AudioData::new(vec![0.0; 160], 16000, 1);
+
+        for _ in 0..3 {
+            assert!(breaker.transcribe(&audio).await.is_err());
+        }
+        assert_eq!(breaker.status().state, CircuitState::Open);
+
+        // 熔断期间快速失败，返回缓存的错误且不调用引擎
+        let err = breaker.transcribe(&audio).await.unwrap_err();
+        assert!(matches!(err, ASRError::NetworkError { ref message, .. } if message == "connection reset"));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // 冷却后试探失败重新熔断
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(breaker.transcribe(&audio).await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert_eq!(breaker.status().state, CircuitState::Open);
+
+        // 再次冷却后试探成功，恢复正常
+        failing.store(false, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(breaker.transcribe(&audio).await.unwrap(), "ok");
+        assert_eq!(
+            breaker.status(),
+            CircuitStatus { state: CircuitState::Closed, consecutive_failures: 0, retry_in_ms: None }
+        );
+    }
+}
@@ -0,0 +1,159 @@
+// 语音命令识别
+// 整句转录结果恰好是命令词 (如"换行""删除上一句""句号") 时识别为编辑动作，
+// 由前端执行而不是作为文字写入；内置中英文词表，用户词表优先
+
+use serde::{Deserialize, Serialize};
+
+use crate::voice::config::VoiceCommandConfig;
+
+/// 编辑动作
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum EditCommand {
+    /// 换行
+    NewLine,
+    /// 另起一段
+    NewParagraph,
+    /// 删除上一句
+    DeleteLastSentence,
+    /// 撤销上一次输入
+    Undo,
+    /// 插入文本 (标点等)
+    InsertText { text: String },
+}
+
+/// 内置中文命令词
+const ZH_COMMANDS: &[(&str, BuiltinCommand)] = &[
+    ("换行", BuiltinCommand::NewLine),
+    ("下一行", BuiltinCommand::NewLine),
+    ("新段落", BuiltinCommand::NewParagraph),
+    ("另起一段", BuiltinCommand::NewParagraph),
+    ("删除上一句", BuiltinCommand::DeleteLastSentence),
+    ("撤销", BuiltinCommand::Undo),
+    ("句号", BuiltinCommand::Insert("。")),
+    ("逗号", BuiltinCommand::Insert("，")),
+    ("问号", BuiltinCommand::Insert("？")),
+    ("感叹号", BuiltinCommand::Insert("！")),
+];
+
+/// 内置英文命令词 (小写)
+const EN_COMMANDS: &[(&str, BuiltinCommand)] = &[
+    ("new line", BuiltinCommand::NewLine),
+    ("new paragraph", BuiltinCommand::NewParagraph),
+    ("delete last sentence", BuiltinCommand::DeleteLastSentence),
+    ("undo", BuiltinCommand::Undo),
+    ("period", BuiltinCommand::Insert(".")),
+    ("full stop", BuiltinCommand::Insert(".")),
+    ("comma", BuiltinCommand::Insert(",")),
+    ("question mark", BuiltinCommand::Insert("?")),
+    ("exclamation mark", BuiltinCommand::Insert("!")),
+];
+
+/// 词表中的命令 (const 中无法构造 String)
+#[derive(Debug, Clone, Copy)]
+enum BuiltinCommand {
+    NewLine,
+    NewParagraph,
+    DeleteLastSentence,
+    Undo,
+    Insert(&'static str),
+}
+
+impl BuiltinCommand {
+    fn to_command(self) -> EditCommand {
+        match self {
+            BuiltinCommand::NewLine => EditCommand::NewLine,
+            BuiltinCommand::NewParagraph => EditCommand::NewParagraph,
+            BuiltinCommand::DeleteLastSentence => EditCommand::DeleteLastSentence,
+            BuiltinCommand::Undo => EditCommand::Undo,
+            BuiltinCommand::Insert(text) => EditCommand::InsertText { text: text.to_string() },
+        }
+    }
+}
+
+/// 识别整句语音命令
+///
+/// `lang` 为结果语言 (如 zh、en-US)，为空或无法识别时依次尝试所有词表；
+/// 首尾标点与空白、英文大小写不影响匹配
+pub fn parse_command(text: &str, lang: &str, config: &VoiceCommandConfig) -> Option<EditCommand> {
+    let phrase = normalize(text);
+    if phrase.is_empty() {
+        return None;
+    }
+
+    let lang = lang.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+    let lang_matches = |command_lang: Option<&str>| {
+        lang.is_empty() || command_lang.is_none_or(|command_lang| command_lang.eq_ignore_ascii_case(&lang))
+    };
+
+    if let Some(custom) = config.custom.iter()
+        .find(|custom| lang_matches(custom.lang.as_deref()) && normalize(&custom.phrase) == phrase)
+    {
+        return Some(custom.command.clone());
+    }
+
+    let tables: &[&[(&str, BuiltinCommand)]] = match lang.as_str() {
+        "zh" | "cmn" | "yue" => &[ZH_COMMANDS],
+        "en" => &[EN_COMMANDS],
+        _ => &[ZH_COMMANDS, EN_COMMANDS],
+    };
+    tables
+        .iter()
+        .flat_map(|table| table.iter())
+        .find(|(word, _)| *word == phrase)
+        .map(|(_, command)| command.to_command())
+}
+
+/// 去掉首尾标点与空白，英文转小写，连续空白合并为一个空格
+fn normalize(text: &str) -> String {
+    text.trim_matches(|c: char| !c.is_alphanumeric())
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voice::config::CustomVoiceCommand;
+
+    #[test]
+    fn test_builtin_commands() {
+        let config = VoiceCommandConfig::default();
+        assert_eq!(parse_command("换行。", "zh", &config), Some(EditCommand::NewLine));
+        assert_eq!(parse_command(" 删除上一句 ", "zh-CN", &config), Some(EditCommand::DeleteLastSentence));
+        assert_eq!(
+            parse_command("句号", "", &config),
+            Some(EditCommand::InsertText { text: "。".to_string() })
+        );
+        assert_eq!(parse_command("New  Line.", "en-US", &config), Some(EditCommand::NewLine));
+        // 语言不符或不是整句命令时按普通文本处理
+        assert_eq!(parse_command("new line", "zh", &config), None);
+        assert_eq!(parse_command("我们换行吧", "zh", &config), None);
+        assert_eq!(parse_command("。", "zh", &config), None);
+    }
+
+    #[test]
+    fn test_custom_commands_take_priority() {
+        let config: VoiceCommandConfig = serde_json::from_str(r#"{
+            "enabled": true,
+            "custom": [
+                { "phrase": "句号", "action": "insert_text", "text": "." },
+                { "phrase": "scratch that", "lang": "en", "action": "delete_last_sentence" }
+            ]
+        }"#).unwrap();
+        assert_eq!(config.custom[1], CustomVoiceCommand {
+            phrase: "scratch that".to_string(),
+            lang: Some("en".to_string()),
+            command: EditCommand::DeleteLastSentence,
+        });
+
+        assert_eq!(parse_command("句号", "zh", &config), Some(EditCommand::InsertText { text: ".".to_string() }));
+        assert_eq!(parse_command("Scratch that!", "en", &config), Some(EditCommand::DeleteLastSentence));
+        assert_eq!(parse_command("scratch that", "zh", &config), None);
+
+        let action = serde_json::to_value(EditCommand::NewParagraph).unwrap();
+        assert_eq!(action, serde_json::json!({ "action": "new_paragraph" }));
+    }
+}
@@ -0,0 +1,251 @@
+// 部分转录增量计算
+// 对比连续的 partial 结果，提取相对上一次新增的文本，并判定已稳定的前缀
+
+use std::collections::VecDeque;
+
+// ============================================================================
+// 增量追踪器
+// ============================================================================
+
+/// 部分转录增量追踪器
+///
+/// 当新的 partial 以上一次的文本为前缀时，返回新增部分 (追加模式)；
+/// 若引擎回退修正了已输出的文本，则返回 None，前端应使用完整文本替换 (整段模式)。
+/// 因此前端按 "None 替换 / Some 追加" 的规则累积，结果始终等于最新完整文本。
+#[derive(Debug, Default, Clone)]
+pub struct PartialDeltaTracker {
+    /// 上一次的完整文本
+    last_text: String,
+}
+
+impl PartialDeltaTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录新的部分结果，返回相对上一次的增量
+    pub fn update(&mut self, text: &str) -> Option<String> {
+        let delta = text
+            .strip_prefix(self.last_text.as_str())
+            .map(|suffix| suffix.to_string());
+
+        self.last_text.clear();
+        self.last_text.push_str(text);
+
+        delta
+    }
+
+    /// 记录最终结果，返回相对最后一次 partial 的增量
+    ///
+    /// 追踪器随之重置，便于下一次录音复用
+    pub fn finish(&mut self, final_text: &str) -> Option<String> {
+        let delta = self.update(final_text);
+        self.reset();
+        delta
+    }
+
+    /// 重置追踪状态
+    pub fn reset(&mut self) {
+        self.last_text.clear();
+    }
+
+    /// 按最近一次 partial 估算已识别字数
+    pub fn estimated_chars(&self) -> usize {
+        estimate_chars(&self.last_text)
+    }
+}
+
+// ============================================================================
+// 稳定前缀
+// ============================================================================
+
+/// 默认稳定判定所需的连续一致次数
+pub const DEFAULT_STABLE_PARTIALS: usize = 3;
+
+/// 按稳定前缀拆分的部分结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StablePartial {
+    /// 已稳定的前缀
+    pub stable: String,
+    /// 尾部仍可能变化的部分
+    pub unstable: String,
+}
+
+/// partial 稳定前缀追踪器
+///
+/// 最近 `required` 次 partial 都保持一致的前缀视为稳定；已稳定的前缀只要仍是
+/// 新 partial 的前缀就保持不变，引擎改写了稳定部分时按最近的 partial 重新判定
+#[derive(Debug, Clone)]
+pub struct PartialStabilizer {
+    required: usize,
+    recent: VecDeque<String>,
+    /// 已稳定前缀的字节长度
+    stable_len: usize,
+}
+
+impl PartialStabilizer {
+    pub fn new(required: usize) -> Self {
+        let required = required.max(1);
+        Self {
+            required,
+            recent: VecDeque::with_capacity(required),
+            stable_len: 0,
+        }
+    }
+
+    /// 记录新的部分结果，返回按稳定前缀拆分的文本
+    pub fn update(&mut self, text: &str) -> StablePartial {
+        if self.recent.len() == self.required {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(text.to_string());
+
+        let previous = self.recent
+            .iter()
+            .rev()
+            .nth(1)
+            .map(|last| &last[..self.stable_len.min(last.len())]);
+        let kept = previous
+            .filter(|stable| text.starts_with(*stable))
+            .map_or(0, str::len);
+        let agreed = if self.recent.len() == self.required {
+            self.recent
+                .iter()
+                .fold(text.len(), |len, other| common_prefix_len(&text[..len], other))
+        } else {
+            0
+        };
+        self.stable_len = kept.max(agreed);
+
+        StablePartial {
+            stable: text[..self.stable_len].to_string(),
+            unstable: text[self.stable_len..].to_string(),
+        }
+    }
+}
+
+/// 两段文本公共前缀的字节长度 (按字符边界)
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, x), y)| x != y)
+        .map_or_else(|| a.len().min(b.len()), |((index, _), _)| index)
+}
+
+/// 估算文本字数
+///
+/// 非 ASCII 的字母/数字 (如汉字) 每个计一字，连续的 ASCII 字母数字 (英文单词、数字) 计一字，
+/// 空白与标点不计
+pub fn estimate_chars(text: &str) -> usize {
+    let mut count = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            if !in_word {
+                count += 1;
+                in_word = true;
+            }
+        } else {
+            in_word = false;
+            if c.is_alphanumeric() {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+// ============================================================================
+// 测试
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 模拟前端累积规则
+    fn apply(display: &mut String, full_text: &str, delta: Option<String>) {
+        match delta {
+            Some(d) => display.push_str(&d),
+            None => *display = full_text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_append_only_partials() {
+        let mut tracker = PartialDeltaTracker::new();
+        assert_eq!(tracker.update("今天"), Some("今天".to_string()));
+        assert_eq!(tracker.update("今天天气"), Some("天气".to_string()));
+        assert_eq!(tracker.update("今天天气"), Some(String::new()));
+        assert_eq!(tracker.update("今天天气很好"), Some("很好".to_string()));
+    }
+
+    #[test]
+    fn test_revision_falls_back_to_full_text() {
+        let mut tracker = PartialDeltaTracker::new();
+        tracker.update("今天天汽");
+        assert_eq!(tracker.update("今天天气不错"), None);
+        // 修正之后继续以新文本为基准
+        assert_eq!(tracker.update("今天天气不错啊"), Some("啊".to_string()));
+    }
+
+    #[test]
+    fn test_accumulated_deltas_match_final_text() {
+        let partials = ["你", "你好", "你好世", "你好是界", "你好世界，", "你好世界，再见"];
+        let final_text = "你好世界，再见";
+
+        let mut tracker = PartialDeltaTracker::new();
+        let mut display = String::new();
+        for partial in partials {
+            let delta = tracker.update(partial);
+            apply(&mut display, partial, delta);
+            assert_eq!(display, partial);
+        }
+
+        let delta = tracker.finish(final_text);
+        apply(&mut display, final_text, delta);
+        assert_eq!(display, final_text);
+    }
+
+    #[test]
+    fn test_estimate_chars() {
+        assert_eq!(estimate_chars(""), 0);
+        assert_eq!(estimate_chars("你好，世界。"), 4);
+        assert_eq!(estimate_chars("hello world 2024!"), 3);
+        assert_eq!(estimate_chars("打开VS Code"), 4);
+
+        let mut tracker = PartialDeltaTracker::new();
+        tracker.update("今天天气");
+        assert_eq!(tracker.estimated_chars(), 4);
+    }
+
+    #[test]
+    fn test_stable_prefix_requires_consecutive_agreement() {
+        let mut stabilizer = PartialStabilizer::new(3);
+        let split = |s: StablePartial| (s.stable, s.unstable);
+        assert_eq!(split(stabilizer.update("今天")), (String::new(), "今天".to_string()));
+        assert_eq!(split(stabilizer.update("今天天汽")), (String::new(), "今天天汽".to_string()));
+        // 连续 3 次一致的前缀 "今天天" 才稳定
+        assert_eq!(split(stabilizer.update("今天天气")), ("今天".to_string(), "天气".to_string()));
+        assert_eq!(split(stabilizer.update("今天天气不错")), ("今天天".to_string(), "气不错".to_string()));
+        assert_eq!(split(stabilizer.update("今天天气不错啊")), ("今天天气".to_string(), "不错啊".to_string()));
+
+        // 改写了稳定部分时重新判定，稳定前缀始终是当前文本的前缀
+        let partial = stabilizer.update("明天天气不错啊");
+        assert_eq!(partial.stable, "");
+        assert_eq!(partial.unstable, "明天天气不错啊");
+
+        let mut immediate = PartialStabilizer::new(1);
+        assert_eq!(immediate.update("hello").stable, "hello");
+    }
+
+    #[test]
+    fn test_finish_with_stripped_punctuation() {
+        let mut tracker = PartialDeltaTracker::new();
+        tracker.update("好的。");
+        // 最终结果去掉了末尾标点，属于回退修正
+        assert_eq!(tracker.finish("好的"), None);
+        // finish 之后追踪器被重置
+        assert_eq!(tracker.update("下一句"), Some("下一句".to_string()));
+    }
+}
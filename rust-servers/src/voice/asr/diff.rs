@@ -0,0 +1,191 @@
+// 转录结果差异
+// 同一段音频换引擎重转后，按词级 LCS 比较两次结果供 UI 高亮：中文按字、英文与数字按词切分，
+// 空白与标点各自成为独立的词元，相邻的同类操作合并为一段
+
+use serde::Serialize;
+
+use crate::voice::asr::punctuator::is_cjk;
+
+/// 参与 LCS 的词元数上限 (两侧之积)，超出时退化为整段替换，避免长文本占用过多内存
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// 差异操作，按顺序拼接 Equal 与 Delete 得到原文本，拼接 Equal 与 Insert 得到新文本
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "op", content = "text", rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal(String),
+    Delete(String),
+    Insert(String),
+}
+
+impl DiffOp {
+    fn text_mut(&mut self) -> &mut String {
+        match self {
+            DiffOp::Equal(text) | DiffOp::Delete(text) | DiffOp::Insert(text) => text,
+        }
+    }
+
+    fn same_kind(&self, other: &DiffOp) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+/// 比较两次转录结果，返回把 `a` 变为 `b` 的操作序列
+pub fn diff_transcripts(a: &str, b: &str) -> Vec<DiffOp> {
+    let a = tokenize(a);
+    let b = tokenize(b);
+
+    // 去掉公共前后缀，缩小 LCS 规模
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops = Vec::new();
+    push_all(&mut ops, &a[..prefix], DiffOp::Equal);
+    if a_mid.len().saturating_mul(b_mid.len()) > MAX_LCS_CELLS {
+        push_all(&mut ops, a_mid, DiffOp::Delete);
+        push_all(&mut ops, b_mid, DiffOp::Insert);
+    } else {
+        lcs_diff(&mut ops, a_mid, b_mid);
+    }
+    push_all(&mut ops, &a[a.len() - suffix..], DiffOp::Equal);
+    ops
+}
+
+/// 切分词元：连续的字母数字 (含撇号，如 don't) 为一个词，中文每字一个词元，
+/// 连续空白为一个词元，其余字符 (标点) 各自一个词元
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        let continues: Option<fn(char) -> bool> = if c.is_whitespace() {
+            Some(char::is_whitespace)
+        } else if c.is_alphanumeric() && !is_cjk(c) {
+            Some(|c: char| (c.is_alphanumeric() && !is_cjk(c)) || c == '\'')
+        } else {
+            None
+        };
+        if let Some(continues) = continues {
+            while let Some(&(i, next)) = chars.peek() {
+                if !continues(next) {
+                    break;
+                }
+                end = i + next.len_utf8();
+                chars.next();
+            }
+        }
+        tokens.push(&text[start..end]);
+    }
+    tokens
+}
+
+/// 经典 LCS 动态规划，回溯时删除优先于插入
+fn lcs_diff(ops: &mut Vec<DiffOp>, a: &[&str], b: &[&str]) {
+    let width = b.len() + 1;
+    // lengths[i * width + j]: a[i..] 与 b[j..] 的 LCS 长度
+    let mut lengths = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i * width + j] = if a[i] == b[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            push(ops, DiffOp::Equal(a[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if j == b.len() || (i < a.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1]) {
+            push(ops, DiffOp::Delete(a[i].to_string()));
+            i += 1;
+        } else {
+            push(ops, DiffOp::Insert(b[j].to_string()));
+            j += 1;
+        }
+    }
+}
+
+fn push_all(ops: &mut Vec<DiffOp>, tokens: &[&str], op: fn(String) -> DiffOp) {
+    for token in tokens {
+        push(ops, op(token.to_string()));
+    }
+}
+
+/// 追加操作，与上一个同类操作合并
+fn push(ops: &mut Vec<DiffOp>, op: DiffOp) {
+    if let Some(last) = ops.last_mut() {
+        if last.same_kind(&op) {
+            let mut op = op;
+            last.text_mut().push_str(op.text_mut());
+            return;
+        }
+    }
+    ops.push(op);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rebuild(ops: &[DiffOp], keep_insert: bool) -> String {
+        ops.iter()
+            .filter_map(|op| match op {
+                DiffOp::Equal(text) => Some(text.as_str()),
+                DiffOp::Delete(text) if !keep_insert => Some(text.as_str()),
+                DiffOp::Insert(text) if keep_insert => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_words_and_cjk_chars() {
+        // 英文按词比较，不会拆成字母
+        assert_eq!(
+            diff_transcripts("I want to meet tomorrow", "I want to meat tomorrow"),
+            vec![
+                DiffOp::Equal("I want to ".to_string()),
+                DiffOp::Delete("meet".to_string()),
+                DiffOp::Insert("meat".to_string()),
+                DiffOp::Equal(" tomorrow".to_string()),
+            ]
+        );
+
+        // 中文按字比较，中英混排中的英文词整体比较
+        let (a, b) = ("我们明天去 Shanghai 开会。", "我们后天去 Shenzhen 开会");
+        let ops = diff_transcripts(a, b);
+        assert_eq!(
+            ops,
+            vec![
+                DiffOp::Equal("我们".to_string()),
+                DiffOp::Delete("明".to_string()),
+                DiffOp::Insert("后".to_string()),
+                DiffOp::Equal("天去 ".to_string()),
+                DiffOp::Delete("Shanghai".to_string()),
+                DiffOp::Insert("Shenzhen".to_string()),
+                DiffOp::Equal(" 开会".to_string()),
+                DiffOp::Delete("。".to_string()),
+            ]
+        );
+        assert_eq!((rebuild(&ops, false), rebuild(&ops, true)), (a.to_string(), b.to_string()));
+
+        assert_eq!(diff_transcripts("same", "same"), vec![DiffOp::Equal("same".to_string())]);
+        assert_eq!(diff_transcripts("", "新"), vec![DiffOp::Insert("新".to_string())]);
+        assert!(diff_transcripts("", "").is_empty());
+        assert_eq!(
+            serde_json::to_value(DiffOp::Insert("x".to_string())).unwrap(),
+            serde_json::json!({ "op": "insert", "text": "x" })
+        );
+    }
+}
@@ -0,0 +1,231 @@
+// 文稿拼装模块
+// 把多段分句结果连接为带段落的完整文本：按停顿时长、语义转折与段落长度分段，并规整标点与空格
+
+use crate::voice::asr::punctuator::{is_cjk, is_punctuation};
+use crate::voice::asr::sentences;
+use crate::voice::asr::TranscriptionResult;
+use crate::voice::config::DocumentConfig;
+
+/// 句末标点
+const SENTENCE_TERMINATORS: &[char] = &['。', '！', '？', '!', '?', '.', '…'];
+
+/// 紧跟句末标点、仍属于本句的闭合符号
+const CLOSING_MARKS: &[char] = &['”', '’', '」', '』', '）', ')', '"', '\''];
+
+/// 不应在前面留空格的标点
+const NO_SPACE_BEFORE: &[char] = &[
+    '。', '，', '！', '？', '、', '；', '：', '”', '’', '）', '…',
+    '.', ',', '!', '?', ';', ':', ')',
+];
+
+/// 开启新段落的语义转折词 (出现在句首时分段)
+const PARAGRAPH_MARKERS: &[&str] = &[
+    "首先", "其次", "再次", "另外", "此外", "最后", "总之", "总的来说", "综上", "接下来",
+    "第一", "第二", "第三", "第四", "第五",
+    "First", "Second", "Third", "Finally", "Moreover", "In addition", "In conclusion", "Next",
+];
+
+/// 以默认配置拼装文稿
+pub fn assemble_document(segments: &[TranscriptionResult]) -> String {
+    assemble_document_with(segments, &DocumentConfig::default())
+}
+
+/// 拼装文稿，段落之间以空行分隔
+///
+/// 分段依据：相邻分句的停顿 (需分句带起止时间) 不短于 `paragraph_pause_ms`、
+/// 句首出现语义转折词、或段落长度将超过 `max_paragraph_chars`；
+/// 停顿不短于 `line_break_pause_ms` 但不足以分段时在段内换行，更短的停顿照常连接
+pub fn assemble_document_with(segments: &[TranscriptionResult], config: &DocumentConfig) -> String {
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut prev_end_ms: Option<u64> = None;
+
+    for segment in segments {
+        let pause_ms = match (prev_end_ms, segment.start_ms) {
+            (Some(end), Some(start)) => Some(start.saturating_sub(end)),
+            _ => None,
+        };
+        let long_pause = pause_ms.is_some_and(|pause| pause >= config.paragraph_pause_ms);
+        let medium_pause = config.line_break_pause_ms > 0
+            && pause_ms.is_some_and(|pause| pause >= config.line_break_pause_ms);
+        if segment.end_ms.is_some() {
+            prev_end_ms = segment.end_ms;
+        }
+
+        for (index, sentence) in split_sentences(&segment.text).into_iter().enumerate() {
+            let too_long = config.max_paragraph_chars > 0
+                && current.chars().count() + sentence.chars().count() > config.max_paragraph_chars;
+            let new_paragraph = (index == 0 && long_pause) || starts_with_marker(&sentence) || too_long;
+
+            if new_paragraph && !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            } else if index == 0 && medium_pause && !current.is_empty() {
+                current.push('\n');
+            }
+            join_sentence(&mut current, &sentence);
+        }
+    }
+
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+    paragraphs.join("\n\n")
+}
+
+/// 把文本切分为句子，并规整空白、补全句末标点
+pub fn split_sentences(text: &str) -> Vec<String> {
+    sentences::split_sentences(text, "")
+        .into_iter()
+        .map(|sentence| normalize_spacing(&sentence.chars().collect::<Vec<_>>()))
+        .filter(|sentence| !sentence.is_empty())
+        .map(ensure_terminal)
+        .collect()
+}
+
+/// 合并连续空白，去掉标点前与中文字符之间的空白
+fn normalize_spacing(chars: &[char]) -> String {
+    let mut output = String::with_capacity(chars.len());
+    let mut pending_space = false;
+
+    for &c in chars {
+        if c.is_whitespace() {
+            pending_space = !output.is_empty();
+            continue;
+        }
+        if pending_space {
+            let prev = output.chars().last();
+            let drop_space = NO_SPACE_BEFORE.contains(&c)
+                || (is_cjk(c) && prev.is_some_and(is_cjk))
+                || prev.is_some_and(|p| is_punctuation(p) && !p.is_ascii());
+            if !drop_space {
+                output.push(' ');
+            }
+            pending_space = false;
+        }
+        output.push(c);
+    }
+
+    output
+}
+
+/// 句末缺少标点时补全 (中文补句号，其它补英文句点)
+fn ensure_terminal(mut sentence: String) -> String {
+    let last = sentence.chars().rev().find(|c| !CLOSING_MARKS.contains(c));
+    if last.is_some_and(|c| !SENTENCE_TERMINATORS.contains(&c)) {
+        let last = sentence.chars().last();
+        // 以逗号等句中标点结尾时替换为句末标点
+        if last.is_some_and(is_punctuation) && !last.is_some_and(|c| CLOSING_MARKS.contains(&c)) {
+            sentence.pop();
+        }
+        sentence.push(if sentence.chars().any(is_cjk) { '。' } else { '.' });
+    }
+    sentence
+}
+
+fn starts_with_marker(sentence: &str) -> bool {
+    PARAGRAPH_MARKERS.iter().any(|marker| sentence.starts_with(marker))
+}
+
+/// 追加句子：中文之间直接连接，英文句子之间留一个空格 (行首不留空格)
+fn join_sentence(paragraph: &mut String, sentence: &str) {
+    if paragraph.ends_with('\n') {
+        paragraph.push_str(sentence);
+        return;
+    }
+    let needs_space = paragraph.chars().last().is_some_and(|c| c.is_ascii())
+        || (!paragraph.is_empty() && sentence.chars().next().is_some_and(|c| c.is_ascii_alphanumeric())
+            && !paragraph.chars().last().is_some_and(|c| is_punctuation(c) && !c.is_ascii()));
+    if needs_space {
+        paragraph.push(' ');
+    }
+    paragraph.push_str(sentence);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, start_ms: u64, end_ms: u64) -> TranscriptionResult {
+        TranscriptionResult::new(text.to_string(), "qwen".to_string(), false, 100)
+            .with_span(start_ms, end_ms)
+    }
+
+    #[test]
+    fn test_split_sentences_normalizes_punctuation() {
+        assert_eq!(
+            split_sentences("今天 天气 很好。  我们去公园吧 ！版本 1.2 已发布 "),
+            vec!["今天天气很好。", "我们去公园吧！", "版本 1.2 已发布。"]
+        );
+        assert_eq!(
+            split_sentences("hello world , this is fine. ok"),
+            vec!["hello world, this is fine.", "ok."]
+        );
+        assert_eq!(split_sentences("他说：“好的。”然后走了，"), vec!["他说：“好的。”", "然后走了。"]);
+        assert!(split_sentences("   ").is_empty());
+    }
+
+    #[test]
+    fn test_assemble_by_pause_and_markers() {
+        let segments = vec![
+            segment("今天开会讨论了三件事", 0, 2000),
+            segment("首先是预算问题", 2300, 4000),
+            segment("预算需要压缩。", 4200, 5000),
+            segment("会议到此结束", 9000, 10000),
+        ];
+
+        assert_eq!(
+            assemble_document(&segments),
+            "今天开会讨论了三件事。\n\n首先是预算问题。预算需要压缩。\n\n会议到此结束。"
+        );
+
+        let config = DocumentConfig { paragraph_pause_ms: 10_000, ..Default::default() };
+        assert_eq!(
+            assemble_document_with(&segments, &config),
+            "今天开会讨论了三件事。\n\n首先是预算问题。预算需要压缩。会议到此结束。"
+        );
+    }
+
+    #[test]
+    fn test_assemble_pause_lengths() {
+        let segments = vec![
+            segment("First point", 0, 1000),
+            segment("still first", 1300, 2000),
+            segment("second line", 3000, 4000),
+            segment("new paragraph", 6000, 7000),
+            segment("接着说", 7900, 8500),
+            segment("继续", 8700, 9000),
+        ];
+
+        // 默认不换行，仅按长停分段
+        assert_eq!(
+            assemble_document(&segments),
+            "First point. still first. second line.\n\nnew paragraph. 接着说。继续。"
+        );
+
+        // 短停连接、中停换行、长停空行
+        let config = DocumentConfig { line_break_pause_ms: 800, ..Default::default() };
+        assert_eq!(
+            assemble_document_with(&segments, &config),
+            "First point. still first.\nsecond line.\n\nnew paragraph.\n接着说。继续。"
+        );
+
+        let config = DocumentConfig { line_break_pause_ms: 200, paragraph_pause_ms: 900, ..Default::default() };
+        assert_eq!(
+            assemble_document_with(&segments, &config),
+            "First point.\nstill first.\n\nsecond line.\n\nnew paragraph.\n\n接着说。\n继续。"
+        );
+    }
+
+    #[test]
+    fn test_assemble_without_spans_and_length_limit() {
+        let segments = vec![
+            TranscriptionResult::new("This is one. And two".to_string(), "qwen".to_string(), false, 0),
+            TranscriptionResult::new("三".to_string(), "qwen".to_string(), false, 0),
+        ];
+        assert_eq!(assemble_document(&segments), "This is one. And two. 三。");
+
+        let config = DocumentConfig { max_paragraph_chars: 14, ..Default::default() };
+        assert_eq!(assemble_document_with(&segments, &config), "This is one.\n\nAnd two. 三。");
+        assert_eq!(assemble_document(&[]), "");
+    }
+}
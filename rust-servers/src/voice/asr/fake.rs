@@ -0,0 +1,188 @@
+// 测试用的假引擎
+// 由闭包提供转录、实时会话与预热行为，各模块的测试不必各自实现一遍 ASREngine
+
+use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use super::{ASREngine, ASRError, ASRMode, AudioRequirements, RealtimeSession, Transcript};
+use crate::voice::audio::AudioData;
+
+type TranscribeFn = Box<dyn Fn() -> BoxFuture<'static, Result<Transcript, ASRError>> + Send + Sync>;
+type SessionFn = Box<dyn Fn() -> Box<dyn RealtimeSession> + Send + Sync>;
+type LanguageSessionFn = Box<dyn Fn(&str) -> Box<dyn RealtimeSession> + Send + Sync>;
+type WarmUpFn = Box<dyn Fn() -> BoxFuture<'static, Result<(), ASRError>> + Send + Sync>;
+
+/// 假引擎，未设置的行为返回 UnsupportedOperation (预热默认成功)
+pub struct FakeEngine {
+    name: String,
+    modes: Vec<ASRMode>,
+    requirements: AudioRequirements,
+    local: bool,
+    transcribe: Option<TranscribeFn>,
+    session: Option<SessionFn>,
+    language_session: Option<LanguageSessionFn>,
+    warm_up: Option<WarmUpFn>,
+}
+
+impl FakeEngine {
+    /// HTTP 模式引擎
+    pub fn http(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            modes: vec![ASRMode::Http],
+            requirements: AudioRequirements::default(),
+            local: false,
+            transcribe: None,
+            session: None,
+            language_session: None,
+            warm_up: None,
+        }
+    }
+
+    /// 实时模式引擎，每次创建会话时调用 `session`
+    pub fn realtime(
+        name: impl Into<String>,
+        session: impl Fn() -> Box<dyn RealtimeSession> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            modes: vec![ASRMode::Realtime],
+            requirements: AudioRequirements::pcm16_mono(16000),
+            session: Some(Box::new(session)),
+            ..Self::http(name)
+        }
+    }
+
+    /// 转录行为 (只返回文本)
+    pub fn with_transcribe<F, Fut>(self, transcribe: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, ASRError>> + Send + 'static,
+    {
+        self.with_transcript(move || {
+            let request = transcribe();
+            async move { request.await.map(Transcript::from_text) }
+        })
+    }
+
+    /// 转录行为 (附带语言与置信度)
+    pub fn with_transcript<F, Fut>(mut self, transcribe: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Transcript, ASRError>> + Send + 'static,
+    {
+        self.transcribe = Some(Box::new(move || Box::pin(transcribe())));
+        self
+    }
+
+    /// 以指定语言创建实时会话的行为
+    pub fn with_language_session(
+        mut self,
+        session: impl Fn(&str) -> Box<dyn RealtimeSession> + Send + Sync + 'static,
+    ) -> Self {
+        self.language_session = Some(Box::new(session));
+        self
+    }
+
+    /// 预热行为
+    pub fn with_warm_up<F, Fut>(mut self, warm_up: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), ASRError>> + Send + 'static,
+    {
+        self.warm_up = Some(Box::new(move || Box::pin(warm_up())));
+        self
+    }
+
+    pub fn with_requirements(mut self, requirements: AudioRequirements) -> Self {
+        self.requirements = requirements;
+        self
+    }
+
+    pub fn with_local(mut self, local: bool) -> Self {
+        self.local = local;
+        self
+    }
+
+    fn unsupported(&self, operation: &str) -> ASRError {
+        ASRError::UnsupportedOperation(format!("{} 未设置 {}", self.name, operation))
+    }
+}
+
+#[async_trait]
+impl ASREngine for FakeEngine {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn supported_modes(&self) -> Vec<ASRMode> {
+        self.modes.clone()
+    }
+
+    fn audio_requirements(&self) -> AudioRequirements {
+        self.requirements.clone()
+    }
+
+    async fn transcribe(&self, audio: &AudioData) -> Result<String, ASRError> {
+        self.transcribe_detailed(audio).await.map(|transcript| transcript.text)
+    }
+
+    async fn transcribe_detailed(&self, _audio: &AudioData) -> Result<Transcript, ASRError> {
+        match self.transcribe {
+            Some(ref transcribe) => transcribe().await,
+            None => Err(self.unsupported("transcribe")),
+        }
+    }
+
+    async fn create_realtime_session(&self) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        match self.session {
+            Some(ref session) => Ok(session()),
+            None => Err(self.unsupported("realtime")),
+        }
+    }
+
+    async fn create_realtime_session_with_language(&self, language: &str) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        match self.language_session {
+            Some(ref session) => Ok(session(language)),
+            None => Err(self.unsupported("realtime language")),
+        }
+    }
+
+    fn is_local(&self) -> bool {
+        self.local
+    }
+
+    async fn warm_up(&self) -> Result<(), ASRError> {
+        match self.warm_up {
+            Some(ref warm_up) => warm_up().await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// 记录同时在途的调用数峰值
+#[derive(Debug, Default)]
+pub struct InFlight {
+    active: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl InFlight {
+    /// 作为一次在途调用等待 `delay`
+    pub async fn hold(&self, delay: Duration) {
+        let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(active, Ordering::SeqCst);
+        tokio::time::sleep(delay).await;
+        self.active.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+
+    pub fn reset_peak(&self) {
+        self.peak.store(0, Ordering::SeqCst);
+    }
+}
@@ -1,9 +1,10 @@
 // 兜底策略模块
 // 实现主引擎重试和备用引擎并行执行的智能兜底机制
 
-use std::time::{Duration, Instant};
+use std::sync::Arc;
+use std::time::Instant;
 
-use crate::voice::asr::{ASREngine, ASRError, RetryConfig, TranscriptionResult};
+use crate::voice::asr::{transcribe_conformed, transcribe_conformed_with_progress, ASREngine, ASRError, ProgressCallback, ProgressReporter, RetryConfig, TranscriptionResult};
 use crate::voice::audio::AudioData;
 use crate::voice::config::ASRConfig;
 
@@ -58,12 +59,11 @@ impl FallbackStrategy {
     pub async fn transcribe(&self, audio: &AudioData) -> Result<TranscriptionResult, ASRError> {
         let start_time = Instant::now();
         let mut primary_errors: Vec<String> = Vec::new();
+        let mut all_network = true;
         
         for attempt in 0..=self.retry_config.max_retries {
             if attempt > 0 {
-                let delay = Duration::from_millis(
-                    self.retry_config.base_delay_ms * (1 << (attempt - 1))
-                );
+                let delay = self.retry_config.backoff_delay(attempt);
                 eprintln!(
                     "[INFO] 主引擎重试 {}/{}, 等待 {}ms",
                     attempt,
@@ -73,8 +73,8 @@ impl FallbackStrategy {
                 tokio::time::sleep(delay).await;
             }
             
-            match self.primary.transcribe(audio).await {
-                Ok(text) => {
+            match transcribe_conformed(self.primary.as_ref(), audio).await {
+                Ok(transcript) => {
                     let duration_ms = start_time.elapsed().as_millis() as u64;
                     eprintln!(
                         "[INFO] 主引擎 {} 转录成功 (尝试 {}), 耗时 {}ms",
@@ -83,11 +83,11 @@ impl FallbackStrategy {
                         duration_ms
                     );
                     return Ok(TranscriptionResult::new(
-                        text,
+                        transcript.text,
                         self.primary.name().to_string(),
                         false,
                         duration_ms,
-                    ));
+                    ).with_language(transcript.language));
                 }
                 Err(e) => {
                     eprintln!(
@@ -95,8 +95,9 @@ impl FallbackStrategy {
                         self.primary.name(),
                         attempt + 1,
                         self.retry_config.max_retries + 1,
-                        e
+                        e.detailed()
                     );
+                    all_network &= e.is_network();
                     primary_errors.push(e.to_string());
                 }
             }
@@ -106,8 +107,8 @@ impl FallbackStrategy {
         if self.enable_fallback {
             if let Some(ref fallback) = self.fallback {
                 eprintln!("[INFO] 主引擎所有重试失败，尝试兜底引擎...");
-                match fallback.transcribe(audio).await {
-                    Ok(text) => {
+                match transcribe_conformed(fallback.as_ref(), audio).await {
+                    Ok(transcript) => {
                         let duration_ms = start_time.elapsed().as_millis() as u64;
                         eprintln!(
                             "[INFO] 兜底引擎 {} 转录成功，耗时 {}ms",
@@ -115,16 +116,17 @@ impl FallbackStrategy {
                             duration_ms
                         );
                         return Ok(TranscriptionResult::new(
-                            text,
+                            transcript.text,
                             fallback.name().to_string(),
                             true,
                             duration_ms,
-                        ));
+                        ).with_language(transcript.language));
                     }
                     Err(fallback_error) => {
                         return Err(ASRError::AllEnginesFailed {
                             primary_error: primary_errors.join("; "),
                             fallback_error: Some(fallback_error.to_string()),
+                            network: all_network && fallback_error.is_network(),
                         });
                     }
                 }
@@ -134,6 +136,7 @@ impl FallbackStrategy {
         Err(ASRError::AllEnginesFailed {
             primary_error: primary_errors.join("; "),
             fallback_error: None,
+            network: all_network,
         })
     }
     
@@ -152,19 +155,39 @@ impl FallbackStrategy {
 
 /// 带并行执行的兜底策略
 pub struct ParallelFallbackStrategy {
-    primary_config: crate::voice::config::ASRProviderConfig,
-    fallback_config: Option<crate::voice::config::ASRProviderConfig>,
+    primary: Arc<dyn ASREngine>,
+    fallback: Option<Arc<dyn ASREngine>>,
     enable_fallback: bool,
     retry_config: RetryConfig,
+    /// 主引擎转录长音频的进度 (跨重试单调)
+    progress: Option<ProgressReporter>,
 }
 
 impl ParallelFallbackStrategy {
-    pub fn from_config(config: ASRConfig) -> Self {
+    pub fn from_config(config: ASRConfig) -> Result<Self, ASRError> {
+        let primary: Arc<dyn ASREngine> = crate::voice::asr::create_engine(&config.primary)?.into();
+        
+        let fallback: Option<Arc<dyn ASREngine>> = if let Some(ref fallback_config) = config.fallback {
+            Some(crate::voice::asr::create_engine(fallback_config)?.into())
+        } else {
+            None
+        };
+        
+        Ok(Self::from_engines(primary, fallback, config.enable_fallback))
+    }
+    
+    /// 使用已创建的引擎实例 (如连接专属引擎)
+    pub fn from_engines(
+        primary: Arc<dyn ASREngine>,
+        fallback: Option<Arc<dyn ASREngine>>,
+        enable_fallback: bool,
+    ) -> Self {
         Self {
-            primary_config: config.primary,
-            fallback_config: config.fallback,
-            enable_fallback: config.enable_fallback,
+            primary,
+            fallback,
+            enable_fallback,
             retry_config: RetryConfig::default(),
+            progress: None,
         }
     }
     
@@ -173,32 +196,36 @@ impl ParallelFallbackStrategy {
         self
     }
     
+    pub fn with_progress(mut self, progress: Option<ProgressCallback>) -> Self {
+        self.progress = progress.map(ProgressReporter::new);
+        self
+    }
+    
     pub async fn transcribe(&self, audio: &AudioData) -> Result<TranscriptionResult, ASRError> {
         let start_time = Instant::now();
         
         // 启动备用引擎后台任务
-        let fallback_handle = if self.enable_fallback && self.fallback_config.is_some() {
-            let fallback_config = self.fallback_config.clone().unwrap();
-            let audio_clone = audio.clone();
-            
-            Some(tokio::spawn(async move {
-                let engine = crate::voice::asr::create_engine(&fallback_config)?;
-                engine.transcribe(&audio_clone).await
-            }))
-        } else {
-            None
+        let fallback_handle = match self.fallback {
+            Some(ref fallback) if self.enable_fallback => {
+                let engine = Arc::clone(fallback);
+                let audio_clone = audio.clone();
+                
+                Some(tokio::spawn(async move {
+                    transcribe_conformed(engine.as_ref(), &audio_clone).await
+                }))
+            }
+            _ => None,
         };
         
-        let primary_engine = crate::voice::asr::create_engine(&self.primary_config)?;
+        let primary_engine = &self.primary;
         let primary_name = primary_engine.name().to_string();
         
         let mut primary_errors: Vec<String> = Vec::new();
+        let mut all_network = true;
         
         for attempt in 0..=self.retry_config.max_retries {
             if attempt > 0 {
-                let delay = Duration::from_millis(
-                    self.retry_config.base_delay_ms * (1 << (attempt - 1))
-                );
+                let delay = self.retry_config.backoff_delay(attempt);
                 eprintln!(
                     "[INFO] 主引擎重试 {}/{}, 等待 {}ms",
                     attempt,
@@ -208,8 +235,8 @@ impl ParallelFallbackStrategy {
                 tokio::time::sleep(delay).await;
             }
             
-            match primary_engine.transcribe(audio).await {
-                Ok(text) => {
+            match transcribe_conformed_with_progress(primary_engine.as_ref(), audio, self.progress.as_ref()).await {
+                Ok(transcript) => {
                     let duration_ms = start_time.elapsed().as_millis() as u64;
                     eprintln!(
                         "[INFO] 主引擎 {} 转录成功 (尝试 {}), 耗时 {}ms",
@@ -223,11 +250,11 @@ impl ParallelFallbackStrategy {
                     }
                     
                     return Ok(TranscriptionResult::new(
-                        text,
+                        transcript.text,
                         primary_name,
                         false,
                         duration_ms,
-                    ));
+                    ).with_language(transcript.language));
                 }
                 Err(e) => {
                     eprintln!(
@@ -235,8 +262,9 @@ impl ParallelFallbackStrategy {
                         primary_name,
                         attempt + 1,
                         self.retry_config.max_retries + 1,
-                        e
+                        e.detailed()
                     );
+                    all_network &= e.is_network();
                     primary_errors.push(e.to_string());
                 }
             }
@@ -247,11 +275,11 @@ impl ParallelFallbackStrategy {
             eprintln!("[INFO] 主引擎所有重试失败，等待兜底引擎结果...");
             
             match handle.await {
-                Ok(Ok(text)) => {
+                Ok(Ok(transcript)) => {
                     let duration_ms = start_time.elapsed().as_millis() as u64;
-                    let fallback_name = self.fallback_config
+                    let fallback_name = self.fallback
                         .as_ref()
-                        .map(|c| c.provider.to_string())
+                        .map(|e| e.name().to_string())
                         .unwrap_or_else(|| "fallback".to_string());
                     
                     eprintln!(
@@ -261,22 +289,24 @@ impl ParallelFallbackStrategy {
                     );
                     
                     return Ok(TranscriptionResult::new(
-                        text,
+                        transcript.text,
                         fallback_name,
                         true,
                         duration_ms,
-                    ));
+                    ).with_language(transcript.language));
                 }
                 Ok(Err(fallback_error)) => {
                     return Err(ASRError::AllEnginesFailed {
                         primary_error: primary_errors.join("; "),
                         fallback_error: Some(fallback_error.to_string()),
+                        network: all_network && fallback_error.is_network(),
                     });
                 }
                 Err(join_error) => {
                     return Err(ASRError::AllEnginesFailed {
                         primary_error: primary_errors.join("; "),
                         fallback_error: Some(format!("后台任务失败: {}", join_error)),
+                        network: false,
                     });
                 }
             }
@@ -285,18 +315,19 @@ impl ParallelFallbackStrategy {
         Err(ASRError::AllEnginesFailed {
             primary_error: primary_errors.join("; "),
             fallback_error: None,
+            network: all_network,
         })
     }
     
     pub fn primary_provider(&self) -> String {
-        self.primary_config.provider.to_string()
+        self.primary.name().to_string()
     }
     
     pub fn fallback_provider(&self) -> Option<String> {
-        self.fallback_config.as_ref().map(|c| c.provider.to_string())
+        self.fallback.as_ref().map(|e| e.name().to_string())
     }
     
     pub fn is_fallback_enabled(&self) -> bool {
-        self.enable_fallback && self.fallback_config.is_some()
+        self.enable_fallback && self.fallback.is_some()
     }
 }
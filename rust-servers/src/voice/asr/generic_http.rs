@@ -0,0 +1,604 @@
+// 通用 HTTP ASR 引擎
+// 通过配置描述请求与响应结构，无需改代码即可接入任意 HTTP 语音识别接口
+
+use async_trait::async_trait;
+use flate2::{write::GzEncoder, Compression};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::voice::asr::{ASREngine, AudioRequirements, ASRError, ASRMode, RealtimeSession, RetryConfig};
+use crate::voice::audio::AudioData;
+use crate::voice::config::{GenericHttpConfig, RequestCompression};
+
+const ENGINE_NAME: &str = "generic";
+
+/// 预热时连接本机接口的超时
+const WARMUP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 接口在本机时的默认重试配置：本地模型在 CPU 上推理较慢，放宽超时；
+/// 失败多为模型本身的问题，只重试一次
+const LOCAL_RETRY: RetryConfig = RetryConfig {
+    max_retries: 1,
+    base_delay_ms: 500,
+    timeout_ms: 60_000,
+    max_delay_ms: 5000,
+    jitter: false,
+};
+
+// ============================================================================
+// JSON 路径
+// ============================================================================
+
+/// JSON 路径片段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// 对象字段
+    Key(String),
+    /// 数组下标
+    Index(usize),
+}
+
+/// 解析 JSON 路径
+///
+/// 支持 `$.a.b`、`$.items[0].text`、`$["key with space"]` 三种写法，`$` 可省略
+pub fn parse_json_path(path: &str) -> Result<Vec<PathSegment>, ASRError> {
+    let invalid = |reason: &str| ASRError::ConfigError(format!("无效的 JSON 路径 {}: {}", path, reason));
+
+    let rest = path.trim();
+    let rest = rest.strip_prefix('$').unwrap_or(rest);
+    let chars: Vec<char> = rest.chars().collect();
+    let mut segments = Vec::new();
+    let mut i = 0;
+
+    // 省略 `$` 时允许直接以字段名开头
+    if !chars.is_empty() && chars[0] != '.' && chars[0] != '[' {
+        let key = read_key(&chars, &mut i);
+        segments.push(PathSegment::Key(key));
+    }
+
+    while i < chars.len() {
+        match chars[i] {
+            '.' => {
+                i += 1;
+                let key = read_key(&chars, &mut i);
+                if key.is_empty() {
+                    return Err(invalid("字段名为空"));
+                }
+                segments.push(PathSegment::Key(key));
+            }
+            '[' => {
+                let close = chars[i..].iter().position(|&c| c == ']')
+                    .ok_or_else(|| invalid("缺少 ]"))? + i;
+                let inner: String = chars[i + 1..close].iter().collect();
+                let inner = inner.trim();
+                let quoted = inner.len() >= 2
+                    && ((inner.starts_with('"') && inner.ends_with('"'))
+                        || (inner.starts_with('\'') && inner.ends_with('\'')));
+                if quoted {
+                    segments.push(PathSegment::Key(inner[1..inner.len() - 1].to_string()));
+                } else {
+                    let index = inner.parse::<usize>()
+                        .map_err(|_| invalid("下标必须是非负整数"))?;
+                    segments.push(PathSegment::Index(index));
+                }
+                i = close + 1;
+            }
+            _ => return Err(invalid("意外的字符")),
+        }
+    }
+
+    Ok(segments)
+}
+
+/// 读取字段名直到 `.` 或 `[`
+fn read_key(chars: &[char], i: &mut usize) -> String {
+    let start = *i;
+    while *i < chars.len() && chars[*i] != '.' && chars[*i] != '[' {
+        *i += 1;
+    }
+    chars[start..*i].iter().collect()
+}
+
+/// 按路径取值
+pub fn select_json_path<'a>(
+    value: &'a serde_json::Value,
+    segments: &[PathSegment],
+) -> Option<&'a serde_json::Value> {
+    segments.iter().try_fold(value, |current, segment| match segment {
+        PathSegment::Key(key) => current.get(key.as_str()),
+        PathSegment::Index(index) => current.get(*index),
+    })
+}
+
+// ============================================================================
+// 模板
+// ============================================================================
+
+/// 渲染请求模板中的 `{{api_key}}`、`{{sample_rate}}` 变量
+fn render_template(template: &str, api_key: &str, sample_rate: u32) -> String {
+    template
+        .replace("{{api_key}}", api_key)
+        .replace("{{sample_rate}}", &sample_rate.to_string())
+}
+
+// ============================================================================
+// 压缩请求体
+// ============================================================================
+
+/// 手动构造 multipart 请求体 (reqwest 的 Form 是流式的，无法整体压缩)
+fn multipart_body(boundary: &str, fields: &[(String, String)], audio_field: &str, wav_data: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(wav_data.len() + 512);
+    for (name, value) in fields {
+        body.extend_from_slice(format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+            boundary, name, value
+        ).as_bytes());
+    }
+    body.extend_from_slice(format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"audio.wav\"\r\nContent-Type: audio/wav\r\n\r\n",
+        boundary, audio_field
+    ).as_bytes());
+    body.extend_from_slice(wav_data);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+fn gzip(data: &[u8]) -> Result<Vec<u8>, ASRError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| ASRError::InternalError(format!("压缩请求体失败: {}", e)))
+}
+
+// ============================================================================
+// 引擎实现
+// ============================================================================
+
+pub struct GenericHttpEngine {
+    config: GenericHttpConfig,
+    method: reqwest::Method,
+    text_path: Vec<PathSegment>,
+    error_path: Option<Vec<PathSegment>>,
+    client: reqwest::Client,
+    retry_config: RetryConfig,
+    /// 服务端拒绝压缩请求 (415) 后不再压缩
+    compression_rejected: AtomicBool,
+}
+
+impl GenericHttpEngine {
+    pub fn new(config: GenericHttpConfig) -> Result<Self, ASRError> {
+        let retry_config = default_retry_for(&config);
+        Self::with_config(config, retry_config)
+    }
+
+    pub fn with_config(config: GenericHttpConfig, retry_config: RetryConfig) -> Result<Self, ASRError> {
+        let method = reqwest::Method::from_bytes(config.method.to_uppercase().as_bytes())
+            .map_err(|_| ASRError::ConfigError(format!("不支持的请求方法: {}", config.method)))?;
+        let text_path = parse_json_path(&config.text_path)?;
+        let error_path = config.error_path.as_deref().map(parse_json_path).transpose()?;
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(retry_config.timeout_ms))
+            .build()
+            .unwrap_or_default();
+
+        Ok(Self {
+            config,
+            method,
+            text_path,
+            error_path,
+            client,
+            retry_config,
+            compression_rejected: AtomicBool::new(false),
+        })
+    }
+
+    /// 从响应 JSON 中提取识别文本
+    fn extract_text(&self, json: &serde_json::Value) -> Result<String, ASRError> {
+        if let Some(message) = self.extract_error(json) {
+            return Err(ASRError::InternalError(format!("接口返回错误: {}", message)));
+        }
+
+        match select_json_path(json, &self.text_path) {
+            Some(serde_json::Value::String(text)) => Ok(text.trim().to_string()),
+            Some(serde_json::Value::Null) | None => Err(ASRError::InternalError(format!(
+                "响应中未找到文本字段 {}",
+                self.config.text_path
+            ))),
+            Some(other) => Err(ASRError::InternalError(format!(
+                "文本字段 {} 不是字符串: {}",
+                self.config.text_path, other
+            ))),
+        }
+    }
+
+    /// 按错误路径提取错误信息，字段缺失、为 null、false 或空字符串时视为无错误
+    fn extract_error(&self, json: &serde_json::Value) -> Option<String> {
+        let path = self.error_path.as_ref()?;
+        match select_json_path(json, path)? {
+            serde_json::Value::Null | serde_json::Value::Bool(false) => None,
+            serde_json::Value::String(s) if s.is_empty() => None,
+            serde_json::Value::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        }
+    }
+
+    /// 发送一次识别请求，`compress` 为真时以 gzip 压缩请求体
+    async fn send_request(&self, audio: &AudioData, wav_data: &[u8], compress: bool) -> Result<reqwest::Response, ASRError> {
+        let api_key = self.config.api_key.as_deref().unwrap_or("");
+        let render = |template: &str| render_template(template, api_key, audio.sample_rate);
+        let fields: Vec<(String, String)> = self.config.form_fields
+            .iter()
+            .map(|(name, value)| (name.clone(), render(value)))
+            .collect();
+
+        let mut request = self.client.request(self.method.clone(), render(&self.config.url));
+        if compress {
+            let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+            let boundary = format!("smart-workflow-{:x}", nanos);
+            let body = gzip(&multipart_body(&boundary, &fields, &self.config.audio_field, wav_data))?;
+            eprintln!("[INFO] Generic HTTP ASR: 压缩后请求体 {} bytes", body.len());
+            request = request
+                .header(reqwest::header::CONTENT_TYPE, format!("multipart/form-data; boundary={}", boundary))
+                .header(reqwest::header::CONTENT_ENCODING, "gzip")
+                .body(body);
+        } else {
+            let file_part = reqwest::multipart::Part::bytes(wav_data.to_vec())
+                .file_name("audio.wav")
+                .mime_str("audio/wav")
+                .map_err(|e| ASRError::InternalError(format!("创建文件部分失败: {}", e)))?;
+
+            let mut form = reqwest::multipart::Form::new()
+                .part(self.config.audio_field.clone(), file_part);
+            for (name, value) in fields {
+                form = form.text(name, value);
+            }
+            request = request.multipart(form);
+        }
+        for (name, value) in &self.config.headers {
+            request = request.header(name.as_str(), render(value));
+        }
+
+        request.send().await.map_err(|e| {
+            if e.is_timeout() {
+                ASRError::Timeout { timeout_ms: self.retry_config.timeout_ms }
+            } else {
+                ASRError::from(e)
+            }
+        })
+    }
+
+    async fn transcribe_once(&self, audio: &AudioData) -> Result<String, ASRError> {
+        let wav_data = audio.to_wav()
+            .map_err(|e| ASRError::InvalidAudio(e.to_string()))?;
+
+        eprintln!("[INFO] Generic HTTP ASR: 音频数据大小 {} bytes", wav_data.len());
+
+        let compress = self.config.compression == RequestCompression::Gzip
+            && !self.compression_rejected.load(Ordering::Relaxed);
+        let mut response = self.send_request(audio, &wav_data, compress).await?;
+        if compress && response.status() == reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE {
+            // 服务端不支持压缩请求体，改为不压缩并重发
+            eprintln!("[WARN] Generic HTTP ASR: 服务端不支持压缩请求体，改为不压缩");
+            self.compression_rejected.store(true, Ordering::Relaxed);
+            response = self.send_request(audio, &wav_data, false).await?;
+        }
+
+        let status = response.status();
+        let body = response.text().await
+            .map_err(|e| ASRError::network_with("读取响应失败", e))?;
+
+        if !status.is_success() {
+            // 优先使用错误路径中的信息
+            let message = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|json| self.extract_error(&json))
+                .unwrap_or(body);
+
+            return match status.as_u16() {
+                401 | 403 => Err(ASRError::AuthFailed {
+                    engine: ENGINE_NAME.to_string(),
+                    message,
+                }),
+                429 => Err(ASRError::QuotaExceeded {
+                    engine: ENGINE_NAME.to_string(),
+                }),
+                _ => Err(ASRError::network(format!(
+                    "API 请求失败 ({}): {}",
+                    status, message
+                ))),
+            };
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&body)
+            .map_err(|e| ASRError::InternalError(format!("解析响应失败: {}", e)))?;
+
+        self.extract_text(&json)
+    }
+}
+
+#[async_trait]
+impl ASREngine for GenericHttpEngine {
+    fn name(&self) -> &str {
+        ENGINE_NAME
+    }
+
+    fn supported_modes(&self) -> Vec<ASRMode> {
+        vec![ASRMode::Http]
+    }
+
+    fn audio_requirements(&self) -> AudioRequirements {
+        // 自定义接口的约束未知，采样率通过模板变量告知服务端
+        AudioRequirements::default()
+    }
+
+    async fn transcribe(&self, audio: &AudioData) -> Result<String, ASRError> {
+        if audio.is_empty() {
+            return Err(ASRError::InvalidAudio("音频数据为空".to_string()));
+        }
+
+        let start_time = Instant::now();
+        let mut last_error = None;
+
+        for attempt in 0..=self.retry_config.max_retries {
+            if attempt > 0 {
+                tokio::time::sleep(self.retry_config.backoff_delay(attempt)).await;
+            }
+
+            match self.transcribe_once(audio).await {
+                Ok(text) => {
+                    let duration = start_time.elapsed().as_millis() as u64;
+                    eprintln!("[INFO] Generic HTTP 转录成功，耗时 {}ms: {}", duration, text);
+                    return Ok(text);
+                }
+                // 认证失败重试无意义
+                Err(e @ ASRError::AuthFailed { .. }) => return Err(e),
+                Err(e) => {
+                    eprintln!(
+                        "[WARN] Generic HTTP 转录失败 (尝试 {}/{}): {}",
+                        attempt + 1,
+                        self.retry_config.max_retries + 1,
+                        e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| ASRError::InternalError("转录失败，未知错误".to_string())))
+    }
+
+    async fn create_realtime_session(&self) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        Err(ASRError::UnsupportedOperation(
+            "通用 HTTP 引擎不支持 Realtime 模式".to_string()
+        ))
+    }
+
+    fn default_retry(&self) -> RetryConfig {
+        default_retry_for(&self.config)
+    }
+
+    fn is_local(&self) -> bool {
+        is_loopback_url(&self.config.url)
+    }
+
+    /// 本机接口探测端口是否已在监听 (本地模型加载较慢，服务可能尚未启动)
+    async fn warm_up(&self) -> Result<(), ASRError> {
+        if !self.is_local() {
+            return Ok(());
+        }
+        let (host, port) = url_host_port(&self.config.url);
+        let connect = tokio::net::TcpStream::connect((host, port));
+        match tokio::time::timeout(WARMUP_TIMEOUT, connect).await {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(e)) => Err(ASRError::network_with(format!("无法连接 {}:{}", host, port), e)),
+            Err(_) => Err(ASRError::Timeout { timeout_ms: WARMUP_TIMEOUT.as_millis() as u64 }),
+        }
+    }
+}
+
+fn default_retry_for(config: &GenericHttpConfig) -> RetryConfig {
+    if is_loopback_url(&config.url) {
+        LOCAL_RETRY
+    } else {
+        RetryConfig::default()
+    }
+}
+
+/// URL 主机是否为本机 (localhost、127.0.0.0/8、::1)
+fn is_loopback_url(url: &str) -> bool {
+    let (host, _) = url_host_port(url);
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// 解析 URL 的主机与端口，未写端口时按 scheme 取 80 或 443
+fn url_host_port(url: &str) -> (&str, u16) {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("http", url));
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let (host, port) = match host_port.strip_prefix('[') {
+        Some(ipv6) => {
+            let (host, rest) = ipv6.split_once(']').unwrap_or((ipv6, ""));
+            (host, rest.strip_prefix(':'))
+        }
+        None => match host_port.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (host_port, None),
+        },
+    };
+    let default_port = if scheme.eq_ignore_ascii_case("https") { 443 } else { 80 };
+    (host, port.and_then(|port| port.parse().ok()).unwrap_or(default_port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn test_config(text_path: &str, error_path: Option<&str>) -> GenericHttpConfig {
+        GenericHttpConfig {
+            url: "https://asr.example.com/recognize".to_string(),
+            method: "post".to_string(),
+            headers: HashMap::new(),
+            api_key: None,
+            audio_field: "file".to_string(),
+            form_fields: HashMap::new(),
+            text_path: text_path.to_string(),
+            error_path: error_path.map(str::to_string),
+            compression: RequestCompression::None,
+        }
+    }
+
+    #[test]
+    fn test_loopback_url() {
+        for url in ["http://localhost:8080/asr", "http://127.0.0.1/asr", "http://[::1]:9000", "https://user@LOCALHOST"] {
+            assert!(is_loopback_url(url), "{}", url);
+        }
+        for url in ["https://asr.example.com/recognize", "http://localhost.example.com", "http://10.0.0.1:8080"] {
+            assert!(!is_loopback_url(url), "{}", url);
+        }
+        assert_eq!(url_host_port("http://localhost:8080/asr"), ("localhost", 8080));
+        assert_eq!(url_host_port("http://[::1]:9000"), ("::1", 9000));
+        assert_eq!(url_host_port("https://user@LOCALHOST/x"), ("LOCALHOST", 443));
+        let engine = GenericHttpEngine::new(test_config("$.text", None)).unwrap();
+        assert!(!engine.is_local());
+        assert_eq!(engine.retry_config.timeout_ms, RetryConfig::default().timeout_ms);
+    }
+
+    #[test]
+    fn test_parse_json_path() {
+        assert_eq!(
+            parse_json_path("$.result.text").unwrap(),
+            vec![PathSegment::Key("result".into()), PathSegment::Key("text".into())]
+        );
+        assert_eq!(
+            parse_json_path("$.segments[1]['the text']").unwrap(),
+            vec![
+                PathSegment::Key("segments".into()),
+                PathSegment::Index(1),
+                PathSegment::Key("the text".into()),
+            ]
+        );
+        assert_eq!(parse_json_path("text").unwrap(), vec![PathSegment::Key("text".into())]);
+        assert!(parse_json_path("$").unwrap().is_empty());
+
+        assert!(parse_json_path("$.items[").is_err());
+        assert!(parse_json_path("$.items[-1]").is_err());
+        assert!(parse_json_path("$..text").is_err());
+    }
+
+    #[test]
+    fn test_select_json_path() {
+        let value = json!({"result": {"items": [{"text": "a"}, {"text": "b"}]}});
+        let path = parse_json_path("$.result.items[1].text").unwrap();
+        assert_eq!(select_json_path(&value, &path), Some(&json!("b")));
+
+        let missing = parse_json_path("$.result.items[5].text").unwrap();
+        assert_eq!(select_json_path(&value, &missing), None);
+    }
+
+    #[test]
+    fn test_extract_text_and_error() {
+        let engine = GenericHttpEngine::new(test_config("$.result.text", Some("$.error.message"))).unwrap();
+
+        let ok = json!({"result": {"text": " 你好世界 "}, "error": null});
+        assert_eq!(engine.extract_text(&ok).unwrap(), "你好世界");
+
+        let failed = json!({"error": {"message": "quota"}});
+        let err = engine.extract_text(&failed).unwrap_err();
+        assert!(err.to_string().contains("quota"));
+
+        let missing = json!({"result": {}});
+        assert!(engine.extract_text(&missing).is_err());
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(GenericHttpEngine::new(test_config("$.a[", None)).is_err());
+        let mut config = test_config("$.text", None);
+        config.method = "NOT A METHOD".to_string();
+        assert!(GenericHttpEngine::new(config).is_err());
+    }
+
+    /// 接收一个请求并返回指定响应，返回请求头与 (解压后的) 请求体
+    async fn serve_once(listener: &tokio::net::TcpListener, status: &str, body: &str) -> (String, Vec<u8>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        let (head, body_start) = loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            data.extend_from_slice(&buf[..n]);
+            let Some(header_end) = data.windows(4).position(|w| w == b"\r\n\r\n") else { continue };
+            let head = String::from_utf8_lossy(&data[..header_end]).to_ascii_lowercase();
+            let length = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(0);
+            if data.len() >= header_end + 4 + length || n == 0 {
+                break (head, header_end + 4);
+            }
+        };
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status, body.len(), body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+
+        let mut payload = data[body_start..].to_vec();
+        if head.contains("content-encoding: gzip") {
+            use std::io::Read;
+            let mut decoded = Vec::new();
+            flate2::read::GzDecoder::new(payload.as_slice()).read_to_end(&mut decoded).unwrap();
+            payload = decoded;
+        }
+        (head, payload)
+    }
+
+    #[tokio::test]
+    async fn test_gzip_request_falls_back_when_rejected() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = test_config("$.text", None);
+        config.url = format!("http://{}/recognize", listener.local_addr().unwrap());
+        config.form_fields.insert("model".to_string(), "asr-{{sample_rate}}".to_string());
+        config.compression = RequestCompression::Gzip;
+        let engine = GenericHttpEngine::with_config(config, RetryConfig { max_retries: 0, ..Default::default() }).unwrap();
+        let audio = AudioData::new(vec![0.0; 1600], 16000, 1);
+
+        // 压缩后的 multipart 请求体仍能被服务端正确解析
+        let ((head, body), text) = tokio::join!(
+            serve_once(&listener, "200 OK", r#"{"text": "你好"}"#),
+            engine.transcribe(&audio)
+        );
+        assert_eq!(text.unwrap(), "你好");
+        assert!(head.contains("content-encoding: gzip"));
+        let body = String::from_utf8_lossy(&body);
+        assert!(body.contains("name=\"model\"\r\n\r\nasr-16000\r\n"), "{}", body);
+        assert!(body.contains("name=\"file\"; filename=\"audio.wav\"") && body.contains("RIFF"));
+
+        // 服务端返回 415 时改为不压缩重发，之后不再压缩
+        let serve_twice = async {
+            let (rejected, _) = serve_once(&listener, "415 Unsupported Media Type", "{}").await;
+            let (retried, _) = serve_once(&listener, "200 OK", r#"{"text": "ok"}"#).await;
+            (rejected, retried)
+        };
+        let ((rejected, retried), text) = tokio::join!(serve_twice, engine.transcribe(&audio));
+        assert_eq!(text.unwrap(), "ok");
+        assert!(rejected.contains("content-encoding: gzip"));
+        assert!(!retried.contains("content-encoding"));
+        assert!(engine.compression_rejected.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_render_template() {
+        assert_eq!(
+            render_template("Bearer {{api_key}}; rate={{sample_rate}}", "k", 16000),
+            "Bearer k; rate=16000"
+        );
+    }
+}
@@ -5,7 +5,7 @@ use async_trait::async_trait;
 use base64::{Engine as _, engine::general_purpose};
 use std::time::{Duration, Instant};
 
-use crate::voice::asr::{ASREngine, ASRError, ASRMode, RealtimeSession, RetryConfig};
+use crate::voice::asr::{ASREngine, AudioRequirements, ASRError, ASRMode, RealtimeSession, RetryConfig};
 use crate::voice::audio::AudioData;
 
 const DOUBAO_API_URL: &str = "https://openspeech.bytedance.com/api/v3/auc/bigmodel/recognize/flash";
@@ -73,7 +73,7 @@ impl DoubaoHttpEngine {
                 if e.is_timeout() {
                     ASRError::Timeout { timeout_ms: self.retry_config.timeout_ms }
                 } else {
-                    ASRError::NetworkError(e.to_string())
+                    ASRError::from(e)
                 }
             })?;
         
@@ -100,7 +100,7 @@ impl DoubaoHttpEngine {
                 "42900001" => Err(ASRError::QuotaExceeded {
                     engine: "doubao".to_string(),
                 }),
-                _ => Err(ASRError::NetworkError(format!(
+                _ => Err(ASRError::network(format!(
                     "豆包 ASR 失败 ({}): {}",
                     status_code, api_message
                 ))),
@@ -136,6 +136,14 @@ impl ASREngine for DoubaoHttpEngine {
         vec![ASRMode::Http]
     }
     
+    fn audio_requirements(&self) -> AudioRequirements {
+        // 极速版单个文件最长 2 小时
+        AudioRequirements {
+            max_duration_ms: Some(7_200_000),
+            ..AudioRequirements::default()
+        }
+    }
+    
     async fn transcribe(&self, audio: &AudioData) -> Result<String, ASRError> {
         if audio.is_empty() {
             return Err(ASRError::InvalidAudio("音频数据为空".to_string()));
@@ -146,9 +154,7 @@ impl ASREngine for DoubaoHttpEngine {
         
         for attempt in 0..=self.retry_config.max_retries {
             if attempt > 0 {
-                tokio::time::sleep(Duration::from_millis(
-                    self.retry_config.base_delay_ms * (1 << (attempt - 1))
-                )).await;
+                tokio::time::sleep(self.retry_config.backoff_delay(attempt)).await;
             }
             
             match self.transcribe_once(audio).await {
@@ -5,17 +5,22 @@ use async_trait::async_trait;
 use base64::{Engine as _, engine::general_purpose};
 use std::time::{Duration, Instant};
 
-use crate::voice::asr::{ASREngine, ASRError, ASRMode, RealtimeSession, RetryConfig};
+use crate::voice::asr::{ASREngine, AudioRequirements, ASRError, ASRMode, RealtimeSession, RetryConfig};
 use crate::voice::audio::AudioData;
 
 const QWEN_API_URL: &str = "https://dashscope.aliyuncs.com/api/v1/services/aigc/multimodal-generation/generation";
 const DEFAULT_MODEL: &str = "qwen3-asr-flash";
+const DEFAULT_LANGUAGE: &str = "zh";
 
 pub struct QwenHttpEngine {
     api_key: String,
     client: reqwest::Client,
     retry_config: RetryConfig,
     model: String,
+    /// 上下文提示 (作为 system 消息，引导专有名词识别)
+    context: Option<String>,
+    /// 识别语言
+    language: String,
 }
 
 impl QwenHttpEngine {
@@ -34,6 +39,8 @@ impl QwenHttpEngine {
             client,
             retry_config,
             model: DEFAULT_MODEL.to_string(),
+            context: None,
+            language: DEFAULT_LANGUAGE.to_string(),
         }
     }
     
@@ -42,6 +49,16 @@ impl QwenHttpEngine {
         self
     }
     
+    pub fn with_language(mut self, language: String) -> Self {
+        self.language = language;
+        self
+    }
+    
+    pub fn with_context(mut self, context: Option<String>) -> Self {
+        self.context = context;
+        self
+    }
+    
     async fn transcribe_once(&self, audio: &AudioData) -> Result<String, ASRError> {
         let wav_data = audio.to_wav()
             .map_err(|e| ASRError::InvalidAudio(e.to_string()))?;
@@ -54,7 +71,7 @@ impl QwenHttpEngine {
                 "messages": [
                     {
                         "role": "system",
-                        "content": [{"text": ""}]
+                        "content": [{"text": self.context.as_deref().unwrap_or_default()}]
                     },
                     {
                         "role": "user",
@@ -68,7 +85,7 @@ impl QwenHttpEngine {
                 "result_format": "message",
                 "enable_itn": false,
                 "disfluency_removal": true,
-                "language": "zh"
+                "language": self.language
             }
         });
         
@@ -83,7 +100,7 @@ impl QwenHttpEngine {
                 if e.is_timeout() {
                     ASRError::Timeout { timeout_ms: self.retry_config.timeout_ms }
                 } else {
-                    ASRError::NetworkError(e.to_string())
+                    ASRError::from(e)
                 }
             })?;
         
@@ -101,7 +118,7 @@ impl QwenHttpEngine {
                 429 => Err(ASRError::QuotaExceeded {
                     engine: "qwen".to_string(),
                 }),
-                _ => Err(ASRError::NetworkError(format!(
+                _ => Err(ASRError::network(format!(
                     "API 请求失败 ({}): {}",
                     status, error_text
                 ))),
@@ -139,6 +156,14 @@ impl ASREngine for QwenHttpEngine {
         vec![ASRMode::Http]
     }
     
+    fn audio_requirements(&self) -> AudioRequirements {
+        // qwen3-asr-flash 单次请求最长 3 分钟
+        AudioRequirements {
+            max_duration_ms: Some(180_000),
+            ..AudioRequirements::default()
+        }
+    }
+    
     async fn transcribe(&self, audio: &AudioData) -> Result<String, ASRError> {
         if audio.is_empty() {
             return Err(ASRError::InvalidAudio("音频数据为空".to_string()));
@@ -149,9 +174,7 @@ impl ASREngine for QwenHttpEngine {
         
         for attempt in 0..=self.retry_config.max_retries {
             if attempt > 0 {
-                tokio::time::sleep(Duration::from_millis(
-                    self.retry_config.base_delay_ms * (1 << (attempt - 1))
-                )).await;
+                tokio::time::sleep(self.retry_config.backoff_delay(attempt)).await;
             }
             
             match self.transcribe_once(audio).await {
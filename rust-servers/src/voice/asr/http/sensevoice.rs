@@ -4,7 +4,7 @@
 use async_trait::async_trait;
 use std::time::{Duration, Instant};
 
-use crate::voice::asr::{ASREngine, ASRError, ASRMode, RealtimeSession, RetryConfig};
+use crate::voice::asr::{ASREngine, AudioRequirements, ASRError, ASRMode, RealtimeSession, RetryConfig};
 use crate::voice::audio::AudioData;
 
 const SILICONFLOW_API_URL: &str = "https://api.siliconflow.cn/v1/audio/transcriptions";
@@ -66,7 +66,7 @@ impl SenseVoiceHttpEngine {
                 if e.is_timeout() {
                     ASRError::Timeout { timeout_ms: self.retry_config.timeout_ms }
                 } else {
-                    ASRError::NetworkError(e.to_string())
+                    ASRError::from(e)
                 }
             })?;
         
@@ -88,11 +88,11 @@ impl SenseVoiceHttpEngine {
                     "模型不存在或服务不可用: {}",
                     error_text
                 ))),
-                503 | 504 => Err(ASRError::NetworkError(format!(
+                503 | 504 => Err(ASRError::network(format!(
                     "服务暂时不可用 ({}): {}",
                     status, error_text
                 ))),
-                _ => Err(ASRError::NetworkError(format!(
+                _ => Err(ASRError::network(format!(
                     "API 请求失败 ({}): {}",
                     status, error_text
                 ))),
@@ -126,6 +126,14 @@ impl ASREngine for SenseVoiceHttpEngine {
         vec![ASRMode::Http]
     }
     
+    fn audio_requirements(&self) -> AudioRequirements {
+        // SiliconFlow 单个文件最长 1 小时
+        AudioRequirements {
+            max_duration_ms: Some(3_600_000),
+            ..AudioRequirements::default()
+        }
+    }
+    
     async fn transcribe(&self, audio: &AudioData) -> Result<String, ASRError> {
         if audio.is_empty() {
             return Err(ASRError::InvalidAudio("音频数据为空".to_string()));
@@ -136,9 +144,7 @@ impl ASREngine for SenseVoiceHttpEngine {
         
         for attempt in 0..=self.retry_config.max_retries {
             if attempt > 0 {
-                tokio::time::sleep(Duration::from_millis(
-                    self.retry_config.base_delay_ms * (1 << (attempt - 1))
-                )).await;
+                tokio::time::sleep(self.retry_config.backoff_delay(attempt)).await;
             }
             
             match self.transcribe_once(audio).await {
@@ -0,0 +1,789 @@
+// 逆文本规整 (ITN) 模块
+// 把口语化的数字、日期、时间、货币与百分比转换为书面形式，如"二零二四年三月五日" → "2024年3月5日"、
+// "twenty five dollars" → "$25"；内置中英文规则，可实现 ItnRule 追加自定义规则
+
+use std::sync::OnceLock;
+
+// ============================================================================
+// 规则抽象
+// ============================================================================
+
+/// 逆文本规整规则
+pub trait ItnRule: Send + Sync {
+    /// 名称 (用于日志)
+    fn name(&self) -> &'static str;
+
+    /// 规整文本，不匹配的部分原样保留
+    fn apply(&self, text: &str) -> String;
+}
+
+/// 按顺序执行一组规则的规整器
+pub struct InverseTextNormalizer {
+    rules: Vec<Box<dyn ItnRule>>,
+}
+
+impl InverseTextNormalizer {
+    /// 不含任何规则的规整器
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// 追加规则 (按追加顺序执行)
+    pub fn with_rule(mut self, rule: impl ItnRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// 已注册的规则名称
+    pub fn rule_names(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|rule| rule.name()).collect()
+    }
+
+    pub fn normalize(&self, text: &str) -> String {
+        self.rules.iter().fold(text.to_string(), |text, rule| rule.apply(&text))
+    }
+}
+
+impl Default for InverseTextNormalizer {
+    /// 内置中文与英文规则
+    fn default() -> Self {
+        Self::empty().with_rule(ChineseRule).with_rule(EnglishRule)
+    }
+}
+
+/// 以内置规则规整文本
+pub fn normalize(text: &str) -> String {
+    static DEFAULT: OnceLock<InverseTextNormalizer> = OnceLock::new();
+    DEFAULT.get_or_init(InverseTextNormalizer::default).normalize(text)
+}
+
+// ============================================================================
+// 中文规则
+// ============================================================================
+
+/// 中文数字、日期、时间、货币与百分比
+///
+/// 只在有明确上下文时转换 (年/月/日、点+分/半/钟、货币单位、百分之)，
+/// 其余数字须带单位 (十/百/千/万/亿) 且至少两个字，避免改动"一些""十分""万一"等词语；序数 (第X) 保持原样
+#[derive(Debug, Default, Clone)]
+pub struct ChineseRule;
+
+/// 时段词 (其后的"N点"视为时间)
+const ZH_DAY_PERIODS: &[&str] = &["凌晨", "早上", "上午", "中午", "下午", "傍晚", "晚上"];
+
+/// 货币单位 (口语 → 书面)
+const ZH_CURRENCIES: &[(&str, &str)] = &[
+    ("块钱", "元"), ("美元", "美元"), ("欧元", "欧元"), ("英镑", "英镑"), ("日元", "日元"),
+    ("港币", "港币"), ("元", "元"),
+];
+
+impl ItnRule for ChineseRule {
+    fn name(&self) -> &'static str {
+        "zh"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut output = String::with_capacity(text.len());
+        let mut i = 0;
+
+        while i < chars.len() {
+            // 百分之三十五 → 35%
+            if starts_with(&chars, i, "百分之") {
+                if let Some((number, end)) = zh_decimal_at(&chars, i + 3) {
+                    output.push_str(&format!("{}%", number));
+                    i = end;
+                    continue;
+                }
+            }
+            if is_zh_numeral(chars[i]) {
+                let end = i + chars[i..].iter().take_while(|&&c| is_zh_numeral(c)).count();
+                match zh_contextual(&chars, i, end, &output) {
+                    Some((replacement, next)) => {
+                        output.push_str(&replacement);
+                        i = next;
+                    }
+                    None => {
+                        output.extend(&chars[i..end]);
+                        i = end;
+                    }
+                }
+                continue;
+            }
+            output.push(chars[i]);
+            i += 1;
+        }
+        output
+    }
+}
+
+fn zh_digit(c: char) -> Option<u64> {
+    match c {
+        '零' | '〇' => Some(0),
+        '一' => Some(1),
+        '二' | '两' => Some(2),
+        '三' => Some(3),
+        '四' => Some(4),
+        '五' => Some(5),
+        '六' => Some(6),
+        '七' => Some(7),
+        '八' => Some(8),
+        '九' => Some(9),
+        _ => None,
+    }
+}
+
+fn zh_unit(c: char) -> Option<u64> {
+    match c {
+        '十' => Some(10),
+        '百' => Some(100),
+        '千' => Some(1_000),
+        '万' => Some(10_000),
+        '亿' => Some(100_000_000),
+        _ => None,
+    }
+}
+
+fn is_zh_numeral(c: char) -> bool {
+    zh_digit(c).is_some() || zh_unit(c).is_some()
+}
+
+fn starts_with(chars: &[char], start: usize, pattern: &str) -> bool {
+    pattern.chars().enumerate().all(|(offset, p)| chars.get(start + offset) == Some(&p))
+}
+
+/// 解析带单位的中文数字 (如"一千二百零五"、"十二"、口语省略的"三万五")，结构不合法时返回 None
+fn parse_zh_number(chars: &[char]) -> Option<u64> {
+    if chars.is_empty() {
+        return None;
+    }
+    let mut total = 0u64;
+    let mut section = 0u64;
+    let mut digit: Option<u64> = None;
+    let mut small_unit = u64::MAX;
+    let mut big_unit = u64::MAX;
+    let mut last_unit = 1u64;
+    let mut after_zero = false;
+
+    for &c in chars {
+        if let Some(d) = zh_digit(c) {
+            // 数字相连 (如"二零""三四") 不是数值写法
+            if digit.is_some() {
+                return None;
+            }
+            if d == 0 {
+                after_zero = true;
+            } else {
+                digit = Some(d);
+            }
+            continue;
+        }
+        let unit = zh_unit(c)?;
+        if unit < 10_000 {
+            if unit >= small_unit {
+                return None;
+            }
+            let d = match digit.take() {
+                Some(d) => d,
+                // 句首的"十"可省略"一" (十二)
+                None if unit == 10 && section == 0 && small_unit == u64::MAX => 1,
+                None => return None,
+            };
+            section += d * unit;
+            small_unit = unit;
+        } else {
+            if unit >= big_unit {
+                return None;
+            }
+            section += digit.take().unwrap_or(0);
+            if section == 0 {
+                return None;
+            }
+            total += section * unit;
+            section = 0;
+            small_unit = u64::MAX;
+            big_unit = unit;
+        }
+        last_unit = unit;
+        after_zero = false;
+    }
+
+    if let Some(d) = digit {
+        // 口语省略末位单位：三万五 = 35000、一千二 = 1200；"零"之后按个位 (一千零五)
+        if !after_zero && last_unit >= 100 {
+            section += d * last_unit / 10;
+        } else {
+            section += d;
+        }
+    }
+    Some(total + section)
+}
+
+/// 逐位读的数字 (如"二零二四")
+fn parse_zh_digits(chars: &[char]) -> Option<String> {
+    chars.iter()
+        .map(|&c| match c {
+            '两' => None,
+            c => zh_digit(c).map(|d| char::from(b'0' + d as u8)),
+        })
+        .collect()
+}
+
+/// start 处的数值 (可带"点"小数，如"三点五")，返回书面形式与结束位置
+fn zh_decimal_at(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let end = start + chars[start..].iter().take_while(|&&c| is_zh_numeral(c)).count();
+    let integer = parse_zh_number(&chars[start..end])?;
+    if chars.get(end) == Some(&'点') {
+        let fraction_end = end + 1 + chars[end + 1..].iter().take_while(|&&c| zh_digit(c).is_some()).count();
+        if let Some(fraction) = parse_zh_digits(&chars[end + 1..fraction_end]).filter(|f| !f.is_empty()) {
+            return Some((format!("{}.{}", integer, fraction), fraction_end));
+        }
+    }
+    Some((integer.to_string(), end))
+}
+
+/// 按上下文转换 [start, end) 的数字串，返回替换文本与下一个待处理位置
+fn zh_contextual(chars: &[char], start: usize, end: usize, output: &str) -> Option<(String, usize)> {
+    let run = &chars[start..end];
+    let prev = output.chars().last();
+    if prev == Some('第') {
+        return None;
+    }
+    let value = parse_zh_number(run);
+    let next = chars.get(end).copied();
+
+    match next {
+        Some('年') => {
+            // 年份可逐位读 (二零二四年)
+            let year = parse_zh_digits(run)
+                .filter(|digits| digits.len() == 2 || digits.len() == 4)
+                .or_else(|| value.filter(|&v| v >= 10).map(|v| v.to_string()))?;
+            return Some((format!("{}年", year), end + 1));
+        }
+        Some('月') => {
+            let month = value.filter(|v| (1..=12).contains(v))?;
+            return Some((format!("{}月", month), end + 1));
+        }
+        Some(c @ ('日' | '号')) if prev == Some('月') => {
+            let day = value.filter(|v| (1..=31).contains(v))?;
+            return Some((format!("{}{}", day, c), end + 1));
+        }
+        Some('点') => {
+            if let Some(time) = zh_time(chars, end, value, output) {
+                return Some(time);
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(money) = zh_currency(chars, start) {
+        return Some(money);
+    }
+    zh_plain(run, value).map(|number| (number, end))
+}
+
+/// "N点" 之后的时间：三点半 → 3:30、三点十五分 → 3:15、三点钟 / 下午三点 → 3点
+fn zh_time(chars: &[char], dot: usize, hour: Option<u64>, output: &str) -> Option<(String, usize)> {
+    let hour = hour.filter(|h| *h <= 24)?;
+    match chars.get(dot + 1) {
+        Some('半') => return Some((format!("{}:30", hour), dot + 2)),
+        Some('钟') => return Some((format!("{}点", hour), dot + 2)),
+        _ => {}
+    }
+    let minute_end = dot + 1 + chars[dot + 1..].iter().take_while(|&&c| is_zh_numeral(c)).count();
+    if minute_end > dot + 1 && chars.get(minute_end) == Some(&'分') {
+        let minute = parse_zh_number(&chars[dot + 1..minute_end]).filter(|m| *m < 60)?;
+        return Some((format!("{}:{:02}", hour, minute), minute_end + 1));
+    }
+    ZH_DAY_PERIODS.iter()
+        .any(|period| output.ends_with(period))
+        .then(|| (format!("{}点", hour), dot + 1))
+}
+
+/// 货币：一百块钱 → 100元、三块五 → 3.5元、二十美元 → 20美元
+fn zh_currency(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let (amount, end) = zh_decimal_at(chars, start)?;
+    if let Some((spoken, written)) = ZH_CURRENCIES.iter().find(|(spoken, _)| starts_with(chars, end, spoken)) {
+        return Some((format!("{}{}", amount, written), end + spoken.chars().count()));
+    }
+    if chars.get(end) != Some(&'块') || amount.contains('.') {
+        return None;
+    }
+    // "块"也是量词 (一块蛋糕、一块儿)，仅在其后是角数、句末或标点时视为货币
+    match chars.get(end + 1) {
+        Some(&c) if zh_digit(c).is_some_and(|d| d > 0) => {
+            let jiao_end = end + 2 + usize::from(matches!(chars.get(end + 2), Some('毛' | '角')));
+            Some((format!("{}.{}元", amount, zh_digit(c)?), jiao_end))
+        }
+        Some(c) if c.is_alphanumeric() => None,
+        _ => Some((format!("{}元", amount), end + 1)),
+    }
+}
+
+/// 无上下文的数字：须以数字 (或"十") 开头、带单位且至少两个字；以万/亿结尾时保留单位 (三千万 → 3000万)
+fn zh_plain(run: &[char], value: Option<u64>) -> Option<String> {
+    let first = *run.first()?;
+    let starts_with_digit = zh_digit(first).is_some_and(|d| d > 0) || first == '十';
+    if run.len() < 2 || !starts_with_digit || !run.iter().any(|&c| zh_unit(c).is_some()) {
+        return None;
+    }
+    let value = value?;
+    let last = run[run.len() - 1];
+    if matches!(last, '万' | '亿') {
+        let head = &run[..run.len() - 1];
+        if !head.iter().any(|&c| matches!(c, '万' | '亿')) {
+            if let Some(head_value) = parse_zh_number(head) {
+                return Some(format!("{}{}", head_value, last));
+            }
+        }
+    }
+    Some(value.to_string())
+}
+
+// ============================================================================
+// 英文规则
+// ============================================================================
+
+/// 英文数字、日期、时间、货币与百分比
+///
+/// 单个小于十的数词 (one、two) 保持原样，其余数词短语转为阿拉伯数字
+#[derive(Debug, Default, Clone)]
+pub struct EnglishRule;
+
+const EN_UNITS: &[&str] = &[
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+
+const EN_TENS: &[(&str, u64)] = &[
+    ("twenty", 20), ("thirty", 30), ("forty", 40), ("fifty", 50),
+    ("sixty", 60), ("seventy", 70), ("eighty", 80), ("ninety", 90),
+];
+
+const EN_SCALES: &[(&str, u64)] = &[
+    ("thousand", 1_000), ("million", 1_000_000), ("billion", 1_000_000_000),
+];
+
+const EN_ORDINALS: &[(&str, u64)] = &[
+    ("first", 1), ("second", 2), ("third", 3), ("fourth", 4), ("fifth", 5), ("sixth", 6),
+    ("seventh", 7), ("eighth", 8), ("ninth", 9), ("tenth", 10), ("eleventh", 11), ("twelfth", 12),
+    ("thirteenth", 13), ("fourteenth", 14), ("fifteenth", 15), ("sixteenth", 16), ("seventeenth", 17),
+    ("eighteenth", 18), ("nineteenth", 19), ("twentieth", 20), ("thirtieth", 30),
+];
+
+const EN_MONTHS: &[&str] = &[
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+/// 货币单位 → 符号
+const EN_CURRENCIES: &[(&str, &str)] = &[
+    ("dollars", "$"), ("dollar", "$"), ("bucks", "$"), ("euros", "€"), ("euro", "€"),
+    ("pounds", "£"), ("pound", "£"), ("yuan", "¥"),
+];
+
+/// 原文中的一个单词 (字节区间)
+struct Word<'a> {
+    lower: String,
+    start: usize,
+    end: usize,
+    text: &'a str,
+}
+
+impl ItnRule for EnglishRule {
+    fn name(&self) -> &'static str {
+        "en"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let words = split_words(text);
+        let mut output = String::with_capacity(text.len());
+        let mut cursor = 0;
+        let mut index = 0;
+
+        while index < words.len() {
+            let matched = en_date(text, &words, index)
+                .or_else(|| en_time(text, &words, index))
+                .or_else(|| en_number(text, &words, index));
+            match matched {
+                Some((replacement, consumed)) => {
+                    output.push_str(&text[cursor..words[index].start]);
+                    output.push_str(&replacement);
+                    cursor = words[index + consumed - 1].end;
+                    index += consumed;
+                }
+                None => index += 1,
+            }
+        }
+        output.push_str(&text[cursor..]);
+        output
+    }
+}
+
+/// 切出 ASCII 单词 (字母间的 `-`、`'`、`.` 属于单词，如 twenty-four、o'clock、a.m)
+fn split_words(text: &str) -> Vec<Word<'_>> {
+    let bytes = text.as_bytes();
+    let mut words = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_alphabetic() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len()
+            && (bytes[i].is_ascii_alphabetic()
+                || (matches!(bytes[i], b'-' | b'\'' | b'.') && bytes.get(i + 1).is_some_and(u8::is_ascii_alphabetic)))
+        {
+            i += 1;
+        }
+        // a.m. 之后句子仍在继续时，末尾的点属于缩写
+        if matches!(text[start..i].to_ascii_lowercase().as_str(), "a.m" | "p.m") && bytes.get(i) == Some(&b'.') {
+            let rest = text[i + 1..].trim_start();
+            if rest.len() < text.len() - i - 1 && rest.as_bytes().first().is_some_and(u8::is_ascii_lowercase) {
+                i += 1;
+            }
+        }
+        words.push(Word {
+            lower: text[start..i].to_ascii_lowercase(),
+            start,
+            end: i,
+            text: &text[start..i],
+        });
+    }
+    words
+}
+
+/// 相邻两词之间只有空白
+fn joined(text: &str, words: &[Word], index: usize) -> bool {
+    index + 1 < words.len() && {
+        let gap = &text[words[index].end..words[index + 1].start];
+        !gap.is_empty() && gap.chars().all(char::is_whitespace)
+    }
+}
+
+/// index 之后紧邻的单词 (小写)
+fn next_word<'a>(text: &str, words: &'a [Word], index: usize) -> Option<&'a str> {
+    joined(text, words, index).then(|| words[index + 1].lower.as_str())
+}
+
+fn en_unit(word: &str) -> Option<u64> {
+    EN_UNITS.iter().position(|&unit| unit == word).map(|v| v as u64)
+}
+
+fn en_tens(word: &str) -> Option<u64> {
+    EN_TENS.iter().find(|(tens, _)| *tens == word).map(|(_, v)| *v)
+}
+
+fn en_scale(word: &str) -> Option<u64> {
+    EN_SCALES.iter().find(|(scale, _)| *scale == word).map(|(_, v)| *v)
+}
+
+fn is_number_part(part: &str) -> bool {
+    en_unit(part).is_some() || en_tens(part).is_some() || en_scale(part).is_some() || part == "hundred"
+}
+
+/// 0-99：tens [unit] | unit
+fn parse_below_hundred(parts: &[&str], pos: usize) -> Option<(u64, usize)> {
+    let word = *parts.get(pos)?;
+    if let Some(tens) = en_tens(word) {
+        return match parts.get(pos + 1).and_then(|w| en_unit(w)).filter(|u| (1..=9).contains(u)) {
+            Some(unit) => Some((tens + unit, pos + 2)),
+            None => Some((tens, pos + 1)),
+        };
+    }
+    en_unit(word).map(|unit| (unit, pos + 1))
+}
+
+/// 0-9999：[a | 0-99] [hundred [and] 0-99]
+fn parse_below_thousand(parts: &[&str], pos: usize) -> Option<(u64, usize)> {
+    let (mut value, mut pos) = if parts.get(pos) == Some(&"a") {
+        let next = parts.get(pos + 1).copied()?;
+        if next != "hundred" && en_scale(next).is_none() {
+            return None;
+        }
+        (1, pos + 1)
+    } else {
+        parse_below_hundred(parts, pos)?
+    };
+    if parts.get(pos) == Some(&"hundred") {
+        value *= 100;
+        pos += 1;
+        let after_and = pos + usize::from(parts.get(pos) == Some(&"and"));
+        if let Some((rest, next)) = parse_below_hundred(parts, after_and) {
+            value += rest;
+            pos = next;
+        }
+    }
+    Some((value, pos))
+}
+
+/// 标准读法：two thousand and twenty four、one hundred five、a million
+fn parse_en_number(parts: &[&str]) -> Option<u64> {
+    let mut pos = 0;
+    let mut total = 0;
+    let mut last_scale = u64::MAX;
+    loop {
+        let (group, next) = parse_below_thousand(parts, pos)?;
+        pos = next;
+        match parts.get(pos).and_then(|word| en_scale(word)) {
+            Some(scale) if scale < last_scale => {
+                total += group * scale;
+                last_scale = scale;
+                pos += 1;
+                pos += usize::from(parts.get(pos) == Some(&"and"));
+                if pos == parts.len() {
+                    return Some(total);
+                }
+            }
+            Some(_) => return None,
+            None => return (pos == parts.len()).then_some(total + group),
+        }
+    }
+}
+
+/// 年份读法：nineteen eighty four、twenty twenty four、nineteen oh five
+fn parse_en_year(parts: &[&str]) -> Option<u64> {
+    let (century, pos) = parse_below_hundred(parts, 0).filter(|(v, _)| *v >= 10)?;
+    let rest = &parts[pos..];
+    let year = match rest {
+        ["oh", unit] => en_unit(unit).filter(|u| (1..=9).contains(u))?,
+        _ => parse_below_hundred(rest, 0).filter(|(v, p)| *v >= 10 && *p == rest.len())?.0,
+    };
+    Some(century * 100 + year)
+}
+
+/// index 处的数词短语：返回 (数值文本, 整数部分, 消耗的单词数)
+fn number_phrase(text: &str, words: &[Word], index: usize) -> Option<(String, Option<u64>, usize)> {
+    let mut parts: Vec<&str> = Vec::new();
+    let mut count = 0;
+    while index + count < words.len() {
+        let word = words[index + count].lower.as_str();
+        let is_number = word.split('-').all(is_number_part);
+        let accepted = is_number
+            || (word == "and" && !parts.is_empty()
+                && next_word(text, words, index + count).is_some_and(is_number_part))
+            || (word == "oh" && !parts.is_empty()
+                && next_word(text, words, index + count).is_some_and(|w| en_unit(w).is_some()))
+            || (word == "a" && parts.is_empty()
+                && next_word(text, words, index).is_some_and(|w| w == "hundred" || en_scale(w).is_some()));
+        if !accepted {
+            break;
+        }
+        parts.extend(word.split('-'));
+        count += 1;
+        if !joined(text, words, index + count - 1) {
+            break;
+        }
+    }
+    if count == 0 {
+        return None;
+    }
+    let integer = parse_en_number(&parts).or_else(|| parse_en_year(&parts))?;
+
+    // three point five → 3.5
+    let mut number = integer.to_string();
+    if next_word(text, words, index + count - 1) == Some("point") {
+        let mut digits = String::new();
+        let mut end = index + count;
+        while let Some(digit) = next_word(text, words, end).and_then(en_unit).filter(|d| *d <= 9) {
+            digits.push(char::from(b'0' + digit as u8));
+            end += 1;
+        }
+        if !digits.is_empty() {
+            number = format!("{}.{}", integer, digits);
+            return Some((number, None, end - index + 1));
+        }
+    }
+    Some((number, Some(integer), count))
+}
+
+/// 数字与其后的百分比、货币单位
+fn en_number(text: &str, words: &[Word], index: usize) -> Option<(String, usize)> {
+    let (number, integer, count) = number_phrase(text, words, index)?;
+    let last = index + count - 1;
+
+    match next_word(text, words, last) {
+        Some("percent") => return Some((format!("{}%", number), count + 1)),
+        Some(unit) => {
+            if let Some((_, symbol)) = EN_CURRENCIES.iter().find(|(name, _)| *name == unit) {
+                // five dollars and twenty cents → $5.20
+                if let (Some("and"), Some(_)) = (next_word(text, words, last + 1), integer) {
+                    if let Some((_, Some(value), cents_count)) = joined(text, words, last + 2)
+                        .then(|| number_phrase(text, words, last + 3))
+                        .flatten()
+                    {
+                        let cents_end = last + 3 + cents_count - 1;
+                        if value < 100 && matches!(next_word(text, words, cents_end), Some("cents" | "cent")) {
+                            return Some((format!("{}{}.{:02}", symbol, number, value), cents_end + 2 - index));
+                        }
+                    }
+                }
+                return Some((format!("{}{}", symbol, number), count + 1));
+            }
+        }
+        None => {}
+    }
+
+    // 单个小于十的数词保持原样 (one of them)
+    (count > 1 || integer.is_none_or(|value| value >= 10)).then_some((number, count))
+}
+
+fn en_meridiem(word: &str) -> Option<&'static str> {
+    match word {
+        "am" | "a.m" | "a.m." => Some("AM"),
+        "pm" | "p.m" | "p.m." => Some("PM"),
+        _ => None,
+    }
+}
+
+/// 时间：three thirty pm → 3:30 PM、seven oh five am → 7:05 AM、ten o'clock → 10:00、nine am → 9 AM
+fn en_time(text: &str, words: &[Word], index: usize) -> Option<(String, usize)> {
+    let hour = en_unit(&words[index].lower).filter(|h| (1..=12).contains(h))?;
+    let next = next_word(text, words, index)?;
+    if next == "o'clock" {
+        return Some((format!("{}:00", hour), 2));
+    }
+    if let Some(meridiem) = en_meridiem(next) {
+        return Some((format!("{} {}", hour, meridiem), 2));
+    }
+
+    let (minute, minute_words) = if next == "oh" {
+        let unit = next_word(text, words, index + 1).and_then(en_unit).filter(|u| (1..=9).contains(u))?;
+        (unit, 2)
+    } else {
+        let parts: Vec<&str> = next.split('-').collect();
+        let (mut minute, _) = parse_below_hundred(&parts, 0).filter(|(_, pos)| *pos == parts.len())?;
+        let mut minute_words = 1;
+        // thirty five 分两词
+        if parts.len() == 1 && en_tens(next).is_some() {
+            if let Some(unit) = next_word(text, words, index + 1).and_then(en_unit).filter(|u| (1..=9).contains(u)) {
+                minute += unit;
+                minute_words = 2;
+            }
+        }
+        (minute, minute_words)
+    };
+    if !(10..60).contains(&minute) && next != "oh" {
+        return None;
+    }
+    let meridiem = next_word(text, words, index + minute_words).and_then(en_meridiem)?;
+    Some((format!("{}:{:02} {}", hour, minute, meridiem), minute_words + 2))
+}
+
+/// 序数日：fifth、twenty first、twenty-first
+fn en_ordinal_day(text: &str, words: &[Word], index: usize) -> Option<(u64, usize)> {
+    let ordinal = |word: &str| EN_ORDINALS.iter().find(|(name, _)| *name == word).map(|(_, v)| *v);
+    let word = words.get(index)?.lower.as_str();
+    if let Some((tens, unit)) = word.split_once('-') {
+        let day = en_tens(tens)? + ordinal(unit).filter(|u| *u <= 9)?;
+        return Some((day, 1));
+    }
+    if let Some(day) = ordinal(word) {
+        return Some((day, 1));
+    }
+    let tens = en_tens(word)?;
+    let unit = next_word(text, words, index).and_then(ordinal).filter(|u| *u <= 9)?;
+    Some((tens + unit, 2))
+}
+
+/// 日期：march fifth → March 5、may twenty first twenty twenty four → May 21, 2024
+fn en_date(text: &str, words: &[Word], index: usize) -> Option<(String, usize)> {
+    let month = EN_MONTHS.iter().find(|month| month.eq_ignore_ascii_case(words[index].text))?;
+    if !joined(text, words, index) {
+        return None;
+    }
+    let (day, day_words) = en_ordinal_day(text, words, index + 1).filter(|(day, _)| (1..=31).contains(day))?;
+    let day_end = index + day_words;
+
+    let separator_ok = day_end + 1 < words.len() && {
+        let gap = text[words[day_end].end..words[day_end + 1].start].trim_start_matches(',');
+        !gap.is_empty() && gap.chars().all(char::is_whitespace)
+    };
+    if separator_ok {
+        let year_count = words[day_end + 1..].iter()
+            .take_while(|word| word.lower.split('-').all(is_number_part) || word.lower == "oh")
+            .count();
+        let year_words: Vec<&str> = words[day_end + 1..day_end + 1 + year_count].iter()
+            .flat_map(|word| word.lower.split('-'))
+            .collect();
+        if let Some(year) = parse_en_year(&year_words)
+            .or_else(|| parse_en_number(&year_words))
+            .filter(|year| (1000..=2999).contains(year))
+        {
+            return Some((format!("{} {}, {}", month, day, year), day_words + year_count + 1));
+        }
+    }
+    Some((format!("{} {}", month, day), day_words + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chinese_numbers_dates_times_and_money() {
+        let cases = [
+            ("会议定在二零二四年三月五日下午三点半", "会议定在2024年3月5日下午3:30"),
+            ("两千零二十四年十二月三十一号", "2024年12月31号"),
+            ("晚上八点十五分出发，早上七点到", "晚上8:15出发，早上7点到"),
+            ("一共一百二十三个人，预算三千万", "一共123个人，预算3000万"),
+            ("这个卖三块五，那个一百块钱", "这个卖3.5元，那个100元"),
+            ("增长了百分之十二点五", "增长了12.5%"),
+            ("大概三万五", "大概35000"),
+            ("还有二十美元", "还有20美元"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(normalize(input), expected, "{}", input);
+        }
+
+        // 不改动含数字的常用词、序数与量词
+        for text in ["一些人十分开心", "万一不行就算了", "第二十五章", "给我一块蛋糕", "我们一块儿去", "等一点点"] {
+            assert_eq!(normalize(text), text);
+        }
+    }
+
+    #[test]
+    fn test_english_numbers_dates_times_and_money() {
+        let cases = [
+            ("in two thousand twenty four we grew", "in 2024 we grew"),
+            ("born in nineteen eighty four", "born in 1984"),
+            ("the year twenty twenty four", "the year 2024"),
+            ("one hundred and five people", "105 people"),
+            ("twenty-five dollars and fifty cents", "$25.50"),
+            ("it costs forty euros", "it costs €40"),
+            ("up three point five percent", "up 3.5%"),
+            ("meet at three thirty pm.", "meet at 3:30 PM."),
+            ("at seven oh five a.m. sharp", "at 7:05 AM sharp"),
+            ("ten o'clock", "10:00"),
+            ("on March fifth, twenty twenty four", "on March 5, 2024"),
+            ("due may twenty-first", "due May 21"),
+            ("a million users", "1000000 users"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(normalize(input), expected, "{}", input);
+        }
+
+        for text in ["one of them", "I may be late", "one two three", "five dollars"] {
+            let expected = if text == "five dollars" { "$5" } else { text };
+            assert_eq!(normalize(text), expected);
+        }
+    }
+
+    #[test]
+    fn test_custom_rule() {
+        struct Ampersand;
+        impl ItnRule for Ampersand {
+            fn name(&self) -> &'static str {
+                "ampersand"
+            }
+            fn apply(&self, text: &str) -> String {
+                text.replace(" and ", " & ")
+            }
+        }
+
+        let normalizer = InverseTextNormalizer::default().with_rule(Ampersand);
+        assert_eq!(normalizer.rule_names(), vec!["zh", "en", "ampersand"]);
+        assert_eq!(normalizer.normalize("salt and twelve peppers"), "salt & 12 peppers");
+    }
+}
@@ -0,0 +1,463 @@
+// 引擎并发与速率限制
+// 包装 ASREngine：同名引擎在进程内共享一个信号量，限制所有连接合计的在途转录请求数，
+// 超出时排队等待，上限变化时就地增减许可；另可按令牌桶限制每秒发起的请求数，避免触发云引擎的 QPS 限制。
+// 实时会话持续整个录音，排队会丢失音频，因此两者都不限制实时会话
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{AcquireError, Semaphore, SemaphorePermit};
+
+use crate::voice::asr::{ASREngine, ASRError, ASRMode, AudioRequirements, RealtimeSession, RetryConfig, Transcript};
+use crate::voice::audio::AudioData;
+use crate::voice::config::{RateLimitAction, RateLimitConfig};
+
+/// 云引擎默认的在途请求数上限
+pub const DEFAULT_REMOTE_CONCURRENCY: usize = 8;
+
+/// 引擎默认的在途请求数上限：本地引擎取 CPU 核数，云引擎取 `DEFAULT_REMOTE_CONCURRENCY`
+pub fn default_concurrency(engine: &dyn ASREngine) -> usize {
+    if engine.is_local() {
+        std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    } else {
+        DEFAULT_REMOTE_CONCURRENCY
+    }
+}
+
+/// 同名引擎共享的在途请求上限
+struct SharedLimit {
+    semaphore: Semaphore,
+    state: Mutex<LimitState>,
+}
+
+struct LimitState {
+    limit: usize,
+    /// 调低上限时仍在途、归还时需收回的许可数
+    debt: usize,
+}
+
+impl SharedLimit {
+    fn new(permits: usize) -> Self {
+        Self { semaphore: Semaphore::new(permits), state: Mutex::new(LimitState { limit: permits, debt: 0 }) }
+    }
+
+    /// 调整上限：调高时补发许可，调低时收回空闲许可，不足部分在在途请求结束时收回
+    fn resize(&self, permits: usize) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if permits > state.limit {
+            let grow = permits - state.limit;
+            let repaid = grow.min(state.debt);
+            state.debt -= repaid;
+            self.semaphore.add_permits(grow - repaid);
+        } else {
+            let shrink = state.limit - permits;
+            let forgotten = self.semaphore.forget_permits(shrink);
+            state.debt += shrink - forgotten;
+        }
+        state.limit = permits;
+    }
+
+    async fn acquire(&self) -> Result<LimitPermit<'_>, AcquireError> {
+        let permit = self.semaphore.acquire().await?;
+        Ok(LimitPermit { permit: Some(permit), limit: self })
+    }
+}
+
+/// 在途请求的许可，归还时先抵扣调低上限欠下的许可
+struct LimitPermit<'a> {
+    permit: Option<SemaphorePermit<'a>>,
+    limit: &'a SharedLimit,
+}
+
+impl Drop for LimitPermit<'_> {
+    fn drop(&mut self) {
+        let mut state = self.limit.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.debt > 0 {
+            state.debt -= 1;
+            if let Some(permit) = self.permit.take() {
+                permit.forget();
+            }
+        }
+    }
+}
+
+/// 进程级共享上限 (按引擎名共享；上限变化时调整同一信号量，所有连接始终共用一个上限)
+fn shared_limit(name: &str, permits: usize) -> Arc<SharedLimit> {
+    static LIMITS: OnceLock<Mutex<HashMap<String, Arc<SharedLimit>>>> = OnceLock::new();
+    let mut limits = LIMITS.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+
+    let limit = limits.entry(name.to_string()).or_insert_with(|| Arc::new(SharedLimit::new(permits)));
+    limit.resize(permits);
+    Arc::clone(limit)
+}
+
+/// 限制在途请求数的引擎包装
+pub struct ConcurrencyLimitedEngine {
+    engine: Arc<dyn ASREngine>,
+    limit: Arc<SharedLimit>,
+}
+
+impl ConcurrencyLimitedEngine {
+    /// `max_concurrency` 为空时使用引擎默认上限
+    pub fn new(engine: Arc<dyn ASREngine>, max_concurrency: Option<usize>) -> Self {
+        let permits = max_concurrency.unwrap_or_else(|| default_concurrency(engine.as_ref())).max(1);
+        let limit = shared_limit(engine.name(), permits);
+        Self { engine, limit }
+    }
+
+    /// 当前可立即发起的请求数
+    #[allow(dead_code)]
+    pub fn available_permits(&self) -> usize {
+        self.limit.semaphore.available_permits()
+    }
+
+    async fn call<T>(&self, request: impl std::future::Future<Output = Result<T, ASRError>>) -> Result<T, ASRError> {
+        let _permit = self.limit
+            .acquire()
+            .await
+            .map_err(|_| ASRError::InternalError("并发限制信号量已关闭".to_string()))?;
+        request.await
+    }
+}
+
+#[async_trait]
+impl ASREngine for ConcurrencyLimitedEngine {
+    fn name(&self) -> &str {
+        self.engine.name()
+    }
+
+    fn supported_modes(&self) -> Vec<ASRMode> {
+        self.engine.supported_modes()
+    }
+
+    fn audio_requirements(&self) -> AudioRequirements {
+        self.engine.audio_requirements()
+    }
+
+    async fn transcribe(&self, audio: &AudioData) -> Result<String, ASRError> {
+        self.call(self.engine.transcribe(audio)).await
+    }
+
+    async fn transcribe_detailed(&self, audio: &AudioData) -> Result<Transcript, ASRError> {
+        self.call(self.engine.transcribe_detailed(audio)).await
+    }
+
+    async fn transcribe_nbest(&self, audio: &AudioData, n: usize) -> Result<Vec<(String, f32)>, ASRError> {
+        self.call(self.engine.transcribe_nbest(audio, n)).await
+    }
+
+    async fn create_realtime_session(&self) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        self.engine.create_realtime_session().await
+    }
+
+    async fn create_realtime_session_with_language(&self, language: &str) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        self.engine.create_realtime_session_with_language(language).await
+    }
+
+    fn default_retry(&self) -> RetryConfig {
+        self.engine.default_retry()
+    }
+
+    fn is_local(&self) -> bool {
+        self.engine.is_local()
+    }
+
+    async fn warm_up(&self) -> Result<(), ASRError> {
+        self.engine.warm_up().await
+    }
+}
+
+// ============================================================================
+// 速率限制
+// ============================================================================
+
+/// 令牌桶：按 `rate` 每秒连续补充，最多累积 `capacity` 个
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// 创建满桶
+    pub fn new(rate: f64, capacity: u32, now: Instant) -> Self {
+        let capacity = capacity.max(1) as f64;
+        Self { rate, capacity, tokens: capacity, updated: now }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// 预订一个令牌，返回需等待的时长 (令牌可透支，排队者按预订顺序依次放行)
+    pub fn reserve(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+
+    /// 有可用令牌时取走，否则返回距下一个令牌的时长
+    pub fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+/// 引擎名到 (配置, 令牌桶) 的映射
+type BucketMap = HashMap<String, (RateLimitConfig, Arc<Mutex<TokenBucket>>)>;
+
+/// 进程级令牌桶 (按引擎名共享，同一 API 的 QPS 限制通常按账户计；配置变化时换用新桶)
+fn shared_bucket(name: &str, config: RateLimitConfig) -> Arc<Mutex<TokenBucket>> {
+    static BUCKETS: OnceLock<Mutex<BucketMap>> = OnceLock::new();
+    let mut buckets = BUCKETS.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+
+    match buckets.get(name) {
+        Some((current, bucket)) if *current == config => Arc::clone(bucket),
+        _ => {
+            let bucket = Arc::new(Mutex::new(TokenBucket::new(config.requests_per_sec, config.burst, Instant::now())));
+            buckets.insert(name.to_string(), (config, Arc::clone(&bucket)));
+            bucket
+        }
+    }
+}
+
+/// 限制请求速率的引擎包装 (只限制 HTTP 转录请求)
+pub struct RateLimitedEngine {
+    engine: Arc<dyn ASREngine>,
+    bucket: Arc<Mutex<TokenBucket>>,
+    on_limit: RateLimitAction,
+}
+
+impl RateLimitedEngine {
+    pub fn new(engine: Arc<dyn ASREngine>, config: RateLimitConfig) -> Self {
+        let bucket = shared_bucket(engine.name(), config);
+        Self { engine, bucket, on_limit: config.on_limit }
+    }
+
+    /// 取得发起请求的许可：排队模式等到预订的令牌，快速失败模式没有令牌时返回 RateLimited
+    async fn admit(&self) -> Result<(), ASRError> {
+        let wait = {
+            let now = Instant::now();
+            let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+            match self.on_limit {
+                RateLimitAction::Wait => bucket.reserve(now),
+                RateLimitAction::Fail => {
+                    return bucket.try_acquire(now).map_err(|wait| ASRError::RateLimited {
+                        engine: self.engine.name().to_string(),
+                        retry_after_ms: wait.as_millis().max(1) as u64,
+                    });
+                }
+            }
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ASREngine for RateLimitedEngine {
+    fn name(&self) -> &str {
+        self.engine.name()
+    }
+
+    fn supported_modes(&self) -> Vec<ASRMode> {
+        self.engine.supported_modes()
+    }
+
+    fn audio_requirements(&self) -> AudioRequirements {
+        self.engine.audio_requirements()
+    }
+
+    async fn transcribe(&self, audio: &AudioData) -> Result<String, ASRError> {
+        self.admit().await?;
+        self.engine.transcribe(audio).await
+    }
+
+    async fn transcribe_detailed(&self, audio: &AudioData) -> Result<Transcript, ASRError> {
+        self.admit().await?;
+        self.engine.transcribe_detailed(audio).await
+    }
+
+    async fn transcribe_nbest(&self, audio: &AudioData, n: usize) -> Result<Vec<(String, f32)>, ASRError> {
+        self.admit().await?;
+        self.engine.transcribe_nbest(audio, n).await
+    }
+
+    async fn create_realtime_session(&self) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        self.engine.create_realtime_session().await
+    }
+
+    async fn create_realtime_session_with_language(&self, language: &str) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        self.engine.create_realtime_session_with_language(language).await
+    }
+
+    fn default_retry(&self) -> RetryConfig {
+        self.engine.default_retry()
+    }
+
+    fn is_local(&self) -> bool {
+        self.engine.is_local()
+    }
+
+    async fn warm_up(&self) -> Result<(), ASRError> {
+        self.engine.warm_up().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voice::asr::fake::{FakeEngine, InFlight};
+
+    /// 每次请求在途 20ms 的引擎，返回记录并发峰值的计数器
+    fn slow_engine(name: &str, local: bool) -> (Arc<FakeEngine>, Arc<InFlight>) {
+        let in_flight = Arc::new(InFlight::default());
+        let counter = Arc::clone(&in_flight);
+        let engine = FakeEngine::http(name)
+            .with_local(local)
+            .with_transcribe(move || {
+                let in_flight = Arc::clone(&counter);
+                async move {
+                    in_flight.hold(Duration::from_millis(20)).await;
+                    Ok("ok".to_string())
+                }
+            });
+        (Arc::new(engine), in_flight)
+    }
+
+    #[tokio::test]
+    async fn test_limit_shared_across_wrappers() {
+        let (engine, in_flight) = slow_engine("limiter-test", false);
+        // 两个连接各自包装同一引擎，共享上限
+        let first = Arc::new(ConcurrencyLimitedEngine::new(Arc::clone(&engine) as Arc<dyn ASREngine>, Some(2)));
+        let second = Arc::new(ConcurrencyLimitedEngine::new(Arc::clone(&engine) as Arc<dyn ASREngine>, Some(2)));
+        let audio = AudioData::new(vec![0.0; 160], 16000, 1);
+
+        let tasks: Vec<_> = (0..6)
+            .map(|i| {
+                let wrapper = if i % 2 == 0 { Arc::clone(&first) } else { Arc::clone(&second) };
+                let audio = audio.clone();
+                tokio::spawn(async move { wrapper.transcribe(&audio).await })
+            })
+            .collect();
+        for task in tasks {
+            assert_eq!(task.await.unwrap().unwrap(), "ok");
+        }
+        assert_eq!(in_flight.peak(), 2);
+        assert_eq!(first.available_permits(), 2);
+
+        // 本地引擎默认上限为 CPU 核数
+        let cpus = std::thread::available_parallelism().unwrap().get();
+        assert_eq!(default_concurrency(slow_engine("limiter-local", true).0.as_ref()), cpus);
+        assert_eq!(default_concurrency(engine.as_ref()), DEFAULT_REMOTE_CONCURRENCY);
+        let local = ConcurrencyLimitedEngine::new(slow_engine("limiter-local", true).0, None);
+        assert_eq!(local.available_permits(), cpus);
+    }
+
+    #[tokio::test]
+    async fn test_limit_change_resizes_shared_semaphore() {
+        let (engine, in_flight) = slow_engine("limiter-resize", false);
+        let audio = AudioData::new(vec![0.0; 160], 16000, 1);
+        let old = Arc::new(ConcurrencyLimitedEngine::new(Arc::clone(&engine) as Arc<dyn ASREngine>, Some(3)));
+        let running: Vec<_> = (0..3)
+            .map(|_| {
+                let old = Arc::clone(&old);
+                let audio = audio.clone();
+                tokio::spawn(async move { old.transcribe(&audio).await })
+            })
+            .collect();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        // 请求在途时调低上限：新旧配置的连接仍共用一个上限，不会合计超出
+        let new = Arc::new(ConcurrencyLimitedEngine::new(Arc::clone(&engine) as Arc<dyn ASREngine>, Some(1)));
+        let tasks: Vec<_> = (0..4)
+            .map(|i| {
+                let wrapper = if i % 2 == 0 { Arc::clone(&old) } else { Arc::clone(&new) };
+                let audio = audio.clone();
+                tokio::spawn(async move { wrapper.transcribe(&audio).await })
+            })
+            .collect();
+        for task in running {
+            task.await.unwrap().unwrap();
+        }
+        in_flight.reset_peak();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+        assert_eq!(in_flight.peak(), 1);
+        assert_eq!(old.available_permits(), 1);
+
+        // 调高上限补发许可
+        let _wider = ConcurrencyLimitedEngine::new(Arc::clone(&engine) as Arc<dyn ASREngine>, Some(4));
+        assert_eq!(new.available_permits(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_caps_qps() {
+        let audio = AudioData::new(vec![0.0; 160], 16000, 1);
+        let config = RateLimitConfig { requests_per_sec: 20.0, burst: 2, on_limit: RateLimitAction::Wait };
+        let (engine, _) = slow_engine("rate-limit-test", false);
+        let limited = Arc::new(RateLimitedEngine::new(Arc::clone(&engine) as Arc<dyn ASREngine>, config));
+
+        // 突发 2 个立即放行，其余 8 个按 50ms 间隔放行
+        let start = Instant::now();
+        let tasks: Vec<_> = (0..10)
+            .map(|_| {
+                let limited = Arc::clone(&limited);
+                let audio = audio.clone();
+                tokio::spawn(async move { limited.transcribe(&audio).await })
+            })
+            .collect();
+        for task in tasks {
+            assert_eq!(task.await.unwrap().unwrap(), "ok");
+        }
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(390), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(1000), "{:?}", elapsed);
+
+        // 快速失败：桶空时返回 RateLimited 并给出等待时长
+        let config = RateLimitConfig { requests_per_sec: 10.0, burst: 1, on_limit: RateLimitAction::Fail };
+        let failing = RateLimitedEngine::new(slow_engine("rate-limit-fail", false).0, config);
+        assert!(failing.transcribe(&audio).await.is_ok());
+        match failing.transcribe(&audio).await {
+            Err(ASRError::RateLimited { engine, retry_after_ms }) => {
+                assert_eq!(engine, "rate-limit-fail");
+                assert!(retry_after_ms > 0 && retry_after_ms <= 100, "{}", retry_after_ms);
+            }
+            other => panic!("{:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_token_bucket_refill() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(10.0, 3, start);
+        for _ in 0..3 {
+            assert_eq!(bucket.try_acquire(start), Ok(()));
+        }
+        assert_eq!(bucket.try_acquire(start), Err(Duration::from_millis(100)));
+        // 补充不超过容量
+        let later = start + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(later), Duration::ZERO);
+        assert_eq!(bucket.reserve(later), Duration::ZERO);
+        assert_eq!(bucket.reserve(later), Duration::ZERO);
+        assert!((bucket.reserve(later).as_secs_f64() - 0.1).abs() < 1e-9);
+        assert!((bucket.reserve(later).as_secs_f64() - 0.2).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,177 @@
+// Markdown 格式化模块
+// 将转录结果按模板渲染为 Obsidian 友好的 Markdown (含 frontmatter)
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::voice::asr::TranscriptionResult;
+
+/// 默认 Markdown 模板
+pub const DEFAULT_MARKDOWN_TEMPLATE: &str = "---\nengine: {{engine}}\ndate: {{date}}\nduration: {{duration}}\n---\n\n{{text}}\n";
+
+/// 按模板渲染转录结果
+///
+/// 支持的变量: `{{text}}`、`{{engine}}`、`{{date}}` (UTC, YYYY-MM-DD)、`{{duration}}` (如 `1200ms`)。
+/// 未知变量与未闭合的 `{{` 原样保留。
+pub fn to_markdown(result: &TranscriptionResult, template: &str) -> String {
+    render(result, template, &format_date(SystemTime::now()))
+}
+
+/// 渲染模板 (日期由调用方提供，便于测试)
+fn render(result: &TranscriptionResult, template: &str, date: &str) -> String {
+    let mut output = String::with_capacity(template.len() + result.text.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+
+        let Some(end) = after_open.find("}}") else {
+            // 未闭合，剩余部分原样输出
+            output.push_str(&rest[start..]);
+            return output;
+        };
+
+        let name = after_open[..end].trim();
+        match name {
+            "text" => output.push_str(&result.text),
+            "engine" => output.push_str(&result.engine),
+            "date" => output.push_str(date),
+            "duration" => output.push_str(&format!("{}ms", result.duration_ms)),
+            _ => output.push_str(&rest[start..start + 2 + end + 2]),
+        }
+
+        rest = &after_open[end + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// 带说话人的转录分段
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeakerSegment {
+    /// 分段在录音中的起始位置 (毫秒)
+    pub start_ms: u64,
+    /// 说话人标签，为空表示未识别
+    pub speaker: String,
+    pub text: String,
+}
+
+/// 说话人为空时的占位
+const UNKNOWN_SPEAKER: &str = "未知";
+
+/// 把分段渲染为 `| 时间 | 说话人 | 内容 |` 表格，时间为 mm:ss
+///
+/// 单元格中的 `|` 转义为 `\|`，换行替换为 `<br>`，避免破坏表格结构
+#[allow(dead_code)]
+pub fn to_markdown_table(segments: &[SpeakerSegment]) -> String {
+    let mut output = String::from("| 时间 | 说话人 | 内容 |\n| --- | --- | --- |\n");
+    for segment in segments {
+        let speaker = segment.speaker.trim();
+        let speaker = if speaker.is_empty() { UNKNOWN_SPEAKER } else { speaker };
+        output.push_str(&format!(
+            "| {} | {} | {} |\n",
+            format_timestamp(segment.start_ms),
+            escape_cell(speaker),
+            escape_cell(segment.text.trim()),
+        ));
+    }
+    output
+}
+
+/// 格式化时间戳为 mm:ss (超过一小时时分钟数继续累加)
+fn format_timestamp(ms: u64) -> String {
+    let seconds = ms / 1000;
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// 转义表格单元格
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace("\r\n", "<br>").replace('\n', "<br>")
+}
+
+/// 格式化日期 (UTC, YYYY-MM-DD)
+fn format_date(time: SystemTime) -> String {
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0) as i64;
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// 将 Unix 纪元天数转换为公历日期
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn sample_result() -> TranscriptionResult {
+        TranscriptionResult::new("你好世界".to_string(), "qwen".to_string(), false, 1200)
+    }
+
+    #[test]
+    fn test_render_default_template() {
+        let md = render(&sample_result(), DEFAULT_MARKDOWN_TEMPLATE, "2024-01-02");
+        assert_eq!(
+            md,
+            "---\nengine: qwen\ndate: 2024-01-02\nduration: 1200ms\n---\n\n你好世界\n"
+        );
+    }
+
+    #[test]
+    fn test_render_keeps_unknown_variables() {
+        let md = render(&sample_result(), "{{ text }} {{author}} {{", "2024-01-02");
+        assert_eq!(md, "你好世界 {{author}} {{");
+    }
+
+    #[test]
+    fn test_render_unclosed_after_known() {
+        let md = render(&sample_result(), "{{engine}}: {{text", "2024-01-02");
+        assert_eq!(md, "qwen: {{text");
+    }
+
+    #[test]
+    fn test_markdown_table() {
+        let segment = |start_ms, speaker: &str, text: &str| SpeakerSegment {
+            start_ms,
+            speaker: speaker.to_string(),
+            text: text.to_string(),
+        };
+        let table = to_markdown_table(&[
+            segment(5_300, "张三", "先看预算"),
+            segment(65_000, " ", "a|b 两种方案\n都可以"),
+            segment(3_725_000, "李四", "好"),
+        ]);
+        assert_eq!(
+            table,
+            "| 时间 | 说话人 | 内容 |\n| --- | --- | --- |\n\
+             | 00:05 | 张三 | 先看预算 |\n\
+             | 01:05 | 未知 | a\\|b 两种方案<br>都可以 |\n\
+             | 62:05 | 李四 | 好 |\n"
+        );
+        assert_eq!(to_markdown_table(&[]), "| 时间 | 说话人 | 内容 |\n| --- | --- | --- |\n");
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(UNIX_EPOCH), "1970-01-01");
+        // 2024-02-29 00:00:00 UTC
+        let leap_day = UNIX_EPOCH + Duration::from_secs(1_709_164_800);
+        assert_eq!(format_date(leap_day), "2024-02-29");
+    }
+}
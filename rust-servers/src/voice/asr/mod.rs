@@ -2,30 +2,80 @@
 // 包含 ASR 引擎抽象层和各供应商实现
 
 use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
 use crate::voice::audio::AudioData;
-use crate::voice::config::{ASRProviderConfig, ASRProvider, ASRMode as ConfigASRMode};
+pub use crate::voice::audio::{AudioFormat, AudioRequirements};
+use crate::voice::config::{masked, ASRProviderConfig, ASRProvider, ASRMode as ConfigASRMode, GenericHttpConfig, GoogleConfig, OpenAIConfig, RetryOverride};
 
 pub mod http;
 pub mod realtime;
 pub mod realtime_task;
 pub mod fallback;
+pub mod circuit_breaker;
+pub mod limiter;
+pub mod commands;
+pub mod multi_lang;
+pub mod delta;
+pub mod diff;
+pub mod document;
+pub mod itn;
+pub mod markdown;
+pub mod pinyin;
+pub mod progress;
+pub mod punctuator;
+pub mod replacements;
+pub mod script;
+pub mod sentences;
+pub mod volcengine;
+pub mod warmup;
+pub mod generic_http;
+pub mod google;
+pub mod openai;
+#[cfg(test)]
+pub mod fake;
 
 pub use http::QwenHttpEngine;
 pub use http::DoubaoHttpEngine;
 pub use http::SenseVoiceHttpEngine;
 pub use realtime::QwenRealtimeEngine;
 pub use realtime::DoubaoRealtimeEngine;
-pub use realtime_task::{RealtimeTranscriptionTask, PartialResultCallback, RealtimeTaskResult};
+pub use volcengine::VolcengineEngine;
+pub use generic_http::GenericHttpEngine;
+pub use google::GoogleEngine;
+pub use openai::OpenAIRealtimeEngine;
+pub use realtime_task::{split_like_segments, RealtimeTranscriptionTask, PartialResultCallback, RealtimeTaskResult};
 pub use fallback::{FallbackStrategy, ParallelFallbackStrategy};
+pub use circuit_breaker::{CircuitBreakerEngine, CircuitState, CircuitStatus};
+pub use limiter::{ConcurrencyLimitedEngine, RateLimitedEngine};
+pub use progress::{ProgressCallback, ProgressReporter};
+pub use multi_lang::MultiLangEngine;
+pub use delta::{PartialDeltaTracker, PartialStabilizer};
+pub use diff::{diff_transcripts, DiffOp};
+pub use document::{assemble_document, assemble_document_with};
+pub use itn::{InverseTextNormalizer, ItnRule};
+pub use markdown::{to_markdown, DEFAULT_MARKDOWN_TEMPLATE};
+pub use punctuator::{create_punctuator, Punctuator, RulePunctuator, LlmPunctuator};
+pub use replacements::{apply_replacements, apply_replacements_with, ReplaceOptions};
+pub use script::convert_script;
+pub use sentences::split_sentences;
 
 // ============================================================================
 // 错误类型
 // ============================================================================
 
+/// 底层错误源 (以 Arc 共享，使 ASRError 仍可克隆)
+pub type ErrorSource = Arc<dyn std::error::Error + Send + Sync>;
+
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum ASRError {
-    #[error("网络错误: {0}")]
-    NetworkError(String),
+    /// `message` 为面向用户的简短说明，底层 reqwest/io 等错误保留在 `source` 中
+    #[error("网络错误: {message}")]
+    NetworkError {
+        message: String,
+        #[source]
+        source: Option<ErrorSource>,
+    },
     
     #[error("认证失败 ({engine}): {message}")]
     AuthFailed {
@@ -38,6 +88,13 @@ pub enum ASRError {
         engine: String,
     },
     
+    /// 客户端侧限速拒绝 (`retry_after_ms` 后才有可用令牌)
+    #[error("请求过于频繁 ({engine})，{retry_after_ms}ms 后可重试")]
+    RateLimited {
+        engine: String,
+        retry_after_ms: u64,
+    },
+    
     #[error("无效的音频格式: {0}")]
     InvalidAudio(String),
     
@@ -46,13 +103,19 @@ pub enum ASRError {
         timeout_ms: u64,
     },
     
-    #[error("WebSocket 错误: {0}")]
-    WebSocketError(String),
+    #[error("WebSocket 错误: {message}")]
+    WebSocketError {
+        message: String,
+        #[source]
+        source: Option<ErrorSource>,
+    },
     
     #[error("所有 ASR 引擎失败: 主引擎={primary_error}, 备用引擎={fallback_error:?}")]
     AllEnginesFailed {
         primary_error: String,
         fallback_error: Option<String>,
+        /// 每次尝试都是网络类错误 (疑似离线)
+        network: bool,
     },
     
     #[error("引擎未初始化")]
@@ -66,6 +129,111 @@ pub enum ASRError {
     
     #[error("内部错误: {0}")]
     InternalError(String),
+    
+    #[error("转录已取消")]
+    Cancelled,
+}
+
+impl ASRError {
+    /// 没有底层错误的网络错误 (如 HTTP 状态码异常)
+    pub fn network(message: impl Into<String>) -> Self {
+        ASRError::NetworkError { message: message.into(), source: None }
+    }
+
+    /// 保留底层错误源的网络错误
+    pub fn network_with(message: impl Into<String>, source: impl std::error::Error + Send + Sync + 'static) -> Self {
+        ASRError::NetworkError { message: message.into(), source: Some(Arc::new(source)) }
+    }
+
+    /// 没有底层错误的 WebSocket 错误 (如服务端返回的错误事件)
+    pub fn websocket(message: impl Into<String>) -> Self {
+        ASRError::WebSocketError { message: message.into(), source: None }
+    }
+
+    /// 保留底层错误源的 WebSocket 错误
+    pub fn websocket_with(message: impl Into<String>, source: impl std::error::Error + Send + Sync + 'static) -> Self {
+        ASRError::WebSocketError { message: message.into(), source: Some(Arc::new(source)) }
+    }
+
+    /// Display 文案后接完整的 source 链，供日志排查
+    pub fn detailed(&self) -> String {
+        let mut text = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(error) = source {
+            text.push_str(": ");
+            text.push_str(&error.to_string());
+            source = error.source();
+        }
+        text
+    }
+
+    /// 原样重试是否可能成功 (网络、超时等暂时性错误)
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ASRError::NetworkError { .. } => true,
+            ASRError::AuthFailed { .. } => false,
+            ASRError::QuotaExceeded { .. } => false,
+            ASRError::RateLimited { .. } => true,
+            ASRError::InvalidAudio(_) => false,
+            ASRError::Timeout { .. } => true,
+            ASRError::WebSocketError { .. } => true,
+            ASRError::AllEnginesFailed { .. } => true,
+            ASRError::NotInitialized => true,
+            ASRError::UnsupportedOperation(_) => false,
+            ASRError::ConfigError(_) => false,
+            ASRError::InternalError(_) => true,
+            ASRError::Cancelled => false,
+        }
+    }
+
+    /// 是否为网络不可用导致的错误 (连接失败、超时；主备引擎均因此失败时也算)
+    pub fn is_network(&self) -> bool {
+        match self {
+            ASRError::NetworkError { .. } | ASRError::Timeout { .. } | ASRError::WebSocketError { .. } => true,
+            ASRError::AllEnginesFailed { network, .. } => *network,
+            _ => false,
+        }
+    }
+
+    /// 面向用户的建议动作
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            ASRError::NetworkError { .. } => "网络连接异常，请检查网络后重试",
+            ASRError::AuthFailed { .. } => "请检查 API Key 或凭据配置",
+            ASRError::QuotaExceeded { .. } => "服务配额已用尽，请检查账户额度或更换引擎",
+            ASRError::RateLimited { .. } => "请求过于频繁，请稍后重试",
+            ASRError::InvalidAudio(_) => "音频数据无效，请检查麦克风后重新录音",
+            ASRError::Timeout { .. } => "网络超时，可重试",
+            ASRError::WebSocketError { .. } => "实时连接中断，可重试或切换到 HTTP 模式",
+            ASRError::AllEnginesFailed { .. } => "所有引擎均失败，请检查网络与引擎配置后重试",
+            ASRError::NotInitialized => "引擎尚未就绪，请稍后重试",
+            ASRError::UnsupportedOperation(_) => "当前引擎不支持该操作，请更换引擎或识别模式",
+            ASRError::ConfigError(_) => "请检查 ASR 配置",
+            ASRError::InternalError(_) => "内部错误，可重试；若持续出现请反馈日志",
+            ASRError::Cancelled => "转录已取消",
+        }
+    }
+}
+
+/// reqwest 错误按类别给出简短说明，原始错误保留为 source
+///
+/// 超时不在此转换：各引擎知道自己的超时设置，应显式构造 `ASRError::Timeout`
+impl From<reqwest::Error> for ASRError {
+    fn from(error: reqwest::Error) -> Self {
+        let host = error.url().and_then(|url| url.host_str()).map(|host| format!(" ({})", host)).unwrap_or_default();
+        let message = if error.is_connect() {
+            "无法连接服务器"
+        } else if error.is_timeout() {
+            "请求超时"
+        } else if error.is_body() || error.is_decode() {
+            "读取响应失败"
+        } else if error.is_request() {
+            "请求发送失败"
+        } else {
+            "请求失败"
+        };
+        ASRError::network_with(format!("{}{}", message, host), error)
+    }
 }
 
 // ============================================================================
@@ -100,12 +268,37 @@ impl std::fmt::Display for ASRMode {
 // 转录结果
 // ============================================================================
 
+/// 转录各阶段耗时 (毫秒，均由单调时钟测量)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct Timings {
+    /// 录音时长
+    pub recording_ms: u64,
+    /// 音频编码耗时
+    pub encoding_ms: u64,
+    /// 网络与推理耗时
+    pub network_ms: u64,
+    /// 后处理耗时 (标点恢复、格式化等)
+    pub post_process_ms: u64,
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct TranscriptionResult {
     pub text: String,
     pub engine: String,
     pub used_fallback: bool,
     pub duration_ms: u64,
+    pub timings: Timings,
+    /// 该段在录音中的起止位置 (毫秒，分句结果才有)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_ms: Option<u64>,
+    /// 识别语言 (多语言择优时标注实际选用的语言)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// 按停顿切分的分句 (带起止时间，实时转录才有)，用于拼装文稿
+    #[serde(skip)]
+    pub segments: Vec<TranscriptionResult>,
 }
 
 impl TranscriptionResult {
@@ -115,8 +308,52 @@ impl TranscriptionResult {
             engine,
             used_fallback,
             duration_ms,
+            // 引擎耗时即网络与推理耗时，其余阶段由调用方补充
+            timings: Timings {
+                network_ms: duration_ms,
+                ..Timings::default()
+            },
+            start_ms: None,
+            end_ms: None,
+            language: None,
+            segments: Vec::new(),
         }
     }
+
+    /// 标注该段在录音中的起止位置
+    pub fn with_span(mut self, start_ms: u64, end_ms: u64) -> Self {
+        self.start_ms = Some(start_ms);
+        self.end_ms = Some(end_ms);
+        self
+    }
+
+    /// 标注识别语言
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// 附带按停顿切分的分句
+    pub fn with_segments(mut self, segments: Vec<TranscriptionResult>) -> Self {
+        self.segments = segments;
+        self
+    }
+}
+
+/// 引擎单次转录的输出 (语言与置信度仅在引擎提供时才有)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcript {
+    pub text: String,
+    /// 识别语言
+    pub language: Option<String>,
+    /// 置信度 (0.0 - 1.0)
+    pub confidence: Option<f32>,
+}
+
+impl Transcript {
+    pub fn from_text(text: String) -> Self {
+        Self { text, ..Self::default() }
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -143,9 +380,129 @@ pub trait ASREngine: Send + Sync {
     fn supports_mode(&self, mode: ASRMode) -> bool {
         self.supported_modes().contains(&mode)
     }
+
+    /// 输入音频的约束 (采样率、声道、单次时长与格式)
+    fn audio_requirements(&self) -> AudioRequirements;
     
     async fn transcribe(&self, audio: &AudioData) -> Result<String, ASRError>;
+    
+    /// 转录并附带语言与置信度，默认只有文本
+    async fn transcribe_detailed(&self, audio: &AudioData) -> Result<Transcript, ASRError> {
+        self.transcribe(audio).await.map(Transcript::from_text)
+    }
+    
+    /// 返回最多 `n` 个候选结果 (文本, 分数)，按分数降序排列
+    async fn transcribe_nbest(&self, _audio: &AudioData, _n: usize) -> Result<Vec<(String, f32)>, ASRError> {
+        Err(ASRError::UnsupportedOperation(format!("{} 不支持 n-best 转录", self.name())))
+    }
+    
     async fn create_realtime_session(&self) -> Result<Box<dyn RealtimeSession>, ASRError>;
+
+    /// 以指定识别语言创建实时会话 (会话不支持中途切换语言时用于重建)
+    async fn create_realtime_session_with_language(&self, _language: &str) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        Err(ASRError::UnsupportedOperation(format!("{} 不支持指定实时识别语言", self.name())))
+    }
+
+    /// 引擎默认的重试与超时 (创建引擎时使用，用户配置了 retry 时覆盖对应字段)
+    fn default_retry(&self) -> RetryConfig {
+        RetryConfig::default()
+    }
+
+    /// 是否在本机推理 (默认并发上限取 CPU 核数)
+    fn is_local(&self) -> bool {
+        false
+    }
+
+    /// 预热：确认引擎可以开始服务 (默认无需预热，云引擎在首次请求时建立连接)
+    async fn warm_up(&self) -> Result<(), ASRError> {
+        Ok(())
+    }
+}
+
+/// 整理 n-best 候选：按分数降序 (同分保持引擎给出的顺序)，去掉空文本与重复文本，最多保留 `n` 个
+pub fn rank_nbest(mut candidates: Vec<(String, f32)>, n: usize) -> Vec<(String, f32)> {
+    candidates.retain(|(text, _)| !text.trim().is_empty());
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut ranked: Vec<(String, f32)> = Vec::with_capacity(n.min(candidates.len()));
+    for (text, score) in candidates {
+        if ranked.len() == n {
+            break;
+        }
+        if !ranked.iter().any(|(existing, _)| *existing == text) {
+            ranked.push((text, score));
+        }
+    }
+    ranked
+}
+
+/// 按引擎约束调整音频后转录
+///
+/// 超过单次时长上限的音频切分为多段依次转录，结果按中英文规则拼接
+pub async fn transcribe_conformed(engine: &dyn ASREngine, audio: &AudioData) -> Result<Transcript, ASRError> {
+    transcribe_conformed_with_progress(engine, audio, None).await
+}
+
+/// 同 [`transcribe_conformed`]，长音频 (见 `PROGRESS_MIN_AUDIO_MS`) 转录过程中经 `progress` 上报进度
+pub async fn transcribe_conformed_with_progress(
+    engine: &dyn ASREngine,
+    audio: &AudioData,
+    progress: Option<&ProgressReporter>,
+) -> Result<Transcript, ASRError> {
+    let progress = progress.filter(|_| audio.duration_ms >= progress::PROGRESS_MIN_AUDIO_MS);
+    let requirements = engine.audio_requirements();
+    if requirements.is_satisfied_by(audio) {
+        let Some(progress) = progress else {
+            return engine.transcribe_detailed(audio).await;
+        };
+        let transcript = progress.track("", 0, 1, audio.duration_ms, engine.transcribe_detailed(audio)).await?;
+        progress.report(&transcript.text, 100.0);
+        return Ok(transcript);
+    }
+
+    let segments = requirements.conform(audio.clone());
+    if segments.len() > 1 {
+        eprintln!(
+            "[INFO] 音频 {}ms 超过 {} 单次上限，分 {} 段转录",
+            audio.duration_ms,
+            engine.name(),
+            segments.len()
+        );
+    }
+
+    // 置信度取各段的最小值
+    let mut transcript = Transcript::default();
+    for (index, segment) in segments.iter().enumerate() {
+        let request = engine.transcribe_detailed(segment);
+        let part = match progress {
+            Some(progress) => progress.track(&transcript.text, index, segments.len(), segment.duration_ms, request).await?,
+            None => request.await?,
+        };
+        join_segment_text(&mut transcript.text, part.text.trim());
+        if let Some(progress) = progress {
+            progress.report(&transcript.text, (index + 1) as f64 / segments.len() as f64 * 100.0);
+        }
+        transcript.language = transcript.language.or(part.language);
+        transcript.confidence = match (transcript.confidence, part.confidence) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+    Ok(transcript)
+}
+
+/// 拼接分段结果：中文之间直接连接，其余情况留一个空格
+fn join_segment_text(text: &mut String, segment: &str) {
+    let (Some(last), Some(first)) = (text.chars().last(), segment.chars().next()) else {
+        text.push_str(segment);
+        return;
+    };
+    let no_space = punctuator::is_cjk(last)
+        || punctuator::is_cjk(first)
+        || (punctuator::is_punctuation(last) && !last.is_ascii());
+    if !no_space {
+        text.push(' ');
+    }
+    text.push_str(segment);
 }
 
 // ============================================================================
@@ -161,9 +518,35 @@ pub trait RealtimeSession: Send {
     }
     
     async fn close(&mut self) -> Result<String, ASRError>;
+
+    /// 立即中止会话并关闭底层连接 (等待最终结果超时后调用)
+    fn abort(&mut self) {}
+
+    /// 发送引擎专用的保活消息，返回 false 表示引擎没有专用保活 (由调用方改发静音帧)
+    async fn keep_alive(&mut self) -> Result<bool, ASRError> {
+        Ok(false)
+    }
+
+    /// 运行中切换识别语言，返回 false 表示会话不支持 (由调用方在分句边界重建会话)
+    async fn set_language(&mut self, _language: &str) -> Result<bool, ASRError> {
+        Ok(false)
+    }
+
     fn set_partial_callback(&mut self, callback: Box<dyn Fn(&str) + Send + 'static>);
+
+    /// 设置带候选的部分结果回调，候选按置信度排列且首个即 partial 本身；
+    /// 不支持 n-best 的会话候选为空，只返回单一结果
+    fn set_alternatives_callback(&mut self, callback: AlternativesCallback) {
+        self.set_partial_callback(Box::new(move |text| callback(text, &[])));
+    }
 }
 
+/// 带 n-best 候选的部分结果回调
+pub type AlternativesCallback = Box<dyn Fn(&str, &[String]) + Send + 'static>;
+
+/// partial 附带的最大候选数
+pub const MAX_PARTIAL_ALTERNATIVES: usize = 5;
+
 // ============================================================================
 // 重试配置
 // ============================================================================
@@ -173,6 +556,10 @@ pub struct RetryConfig {
     pub max_retries: u32,
     pub base_delay_ms: u64,
     pub timeout_ms: u64,
+    /// 单次退避的上限
+    pub max_delay_ms: u64,
+    /// 是否在退避时间内随机抖动 (full jitter)，避免多个连接同时重试
+    pub jitter: bool,
 }
 
 impl Default for RetryConfig {
@@ -181,8 +568,52 @@ impl Default for RetryConfig {
             max_retries: 2,
             base_delay_ms: 500,
             timeout_ms: 6000,
+            max_delay_ms: 5000,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// 以用户显式指定的字段覆盖
+    pub fn with_overrides(self, overrides: &RetryOverride) -> Self {
+        Self {
+            max_retries: overrides.max_retries.unwrap_or(self.max_retries),
+            base_delay_ms: overrides.base_delay_ms.unwrap_or(self.base_delay_ms),
+            timeout_ms: overrides.timeout_ms.unwrap_or(self.timeout_ms),
+            max_delay_ms: overrides.max_delay_ms.unwrap_or(self.max_delay_ms),
+            jitter: overrides.jitter.unwrap_or(self.jitter),
         }
     }
+
+    /// 第 `attempt` 次重试 (从 1 开始) 前的等待时间
+    ///
+    /// 指数退避 `base_delay_ms * 2^(attempt-1)`，不超过 `max_delay_ms`；
+    /// 启用抖动时在 `[0, 退避时间]` 内均匀取值
+    pub fn backoff_delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay_ms = self.base_delay_ms
+            .saturating_mul(1u64 << exponent)
+            .min(self.max_delay_ms);
+        if !self.jitter || delay_ms == 0 {
+            return Duration::from_millis(delay_ms);
+        }
+        Duration::from_millis(random_u64() % (delay_ms + 1))
+    }
+}
+
+/// 系统随机源生成的随机数，不可用时退化为时间戳纳秒
+fn random_u64() -> u64 {
+    use ring::rand::SecureRandom;
+
+    let mut bytes = [0u8; 8];
+    if ring::rand::SystemRandom::new().fill(&mut bytes).is_ok() {
+        return u64::from_le_bytes(bytes);
+    }
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or_default()
 }
 
 // ============================================================================
@@ -194,6 +625,10 @@ pub enum EngineType {
     Qwen,
     Doubao,
     SenseVoice,
+    Volcengine,
+    Generic,
+    Google,
+    OpenAI,
 }
 
 impl From<ASRProvider> for EngineType {
@@ -202,6 +637,10 @@ impl From<ASRProvider> for EngineType {
             ASRProvider::Qwen => EngineType::Qwen,
             ASRProvider::Doubao => EngineType::Doubao,
             ASRProvider::SenseVoice => EngineType::SenseVoice,
+            ASRProvider::Volcengine => EngineType::Volcengine,
+            ASRProvider::Generic => EngineType::Generic,
+            ASRProvider::Google => EngineType::Google,
+            ASRProvider::OpenAI => EngineType::OpenAI,
         }
     }
 }
@@ -212,15 +651,37 @@ impl std::fmt::Display for EngineType {
             EngineType::Qwen => write!(f, "qwen"),
             EngineType::Doubao => write!(f, "doubao"),
             EngineType::SenseVoice => write!(f, "sensevoice"),
+            EngineType::Volcengine => write!(f, "volcengine"),
+            EngineType::Generic => write!(f, "generic"),
+            EngineType::Google => write!(f, "google"),
+            EngineType::OpenAI => write!(f, "openai"),
         }
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Clone, Default)]
 pub struct EngineCredentials {
     pub api_key: Option<String>,
     pub app_id: Option<String>,
     pub access_token: Option<String>,
+    pub cluster: Option<String>,
+    pub generic_http: Option<GenericHttpConfig>,
+    pub google: Option<GoogleConfig>,
+    pub openai: Option<OpenAIConfig>,
+}
+
+impl std::fmt::Debug for EngineCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EngineCredentials")
+            .field("api_key", &masked(&self.api_key))
+            .field("app_id", &self.app_id)
+            .field("access_token", &masked(&self.access_token))
+            .field("cluster", &self.cluster)
+            .field("generic_http", &self.generic_http)
+            .field("google", &self.google)
+            .field("openai", &self.openai)
+            .finish()
+    }
 }
 
 impl EngineCredentials {
@@ -242,8 +703,44 @@ impl EngineCredentials {
 
 /// 创建 ASR 引擎
 pub fn create_engine(config: &ASRProviderConfig) -> Result<Box<dyn ASREngine>, ASRError> {
+    create_engine_with_context(config, None)
+}
+
+/// 创建 ASR 引擎，并把上下文提示传给支持 prompt 的引擎 (Qwen HTTP、OpenAI)，其余引擎忽略
+pub fn create_engine_with_context(
+    config: &ASRProviderConfig,
+    context_prompt: Option<&str>,
+) -> Result<Box<dyn ASREngine>, ASRError> {
     config.validate().map_err(|e| ASRError::ConfigError(e.to_string()))?;
     
+    // 多个候选语言：每种语言一个引擎，并行转录后择优
+    if config.candidate_languages.len() > 1 {
+        let candidates = config.candidate_languages.iter()
+            .map(|language| {
+                let engine = create_language_engine(config, language, context_prompt)?;
+                Ok((language.clone(), Arc::from(engine)))
+            })
+            .collect::<Result<Vec<_>, ASRError>>()?;
+        return Ok(Box::new(MultiLangEngine::new(candidates)));
+    }
+    
+    let engine = build_engine(config, context_prompt, None)?;
+    if config.retry.is_empty() {
+        return Ok(engine);
+    }
+    // 用户覆盖了部分重试参数：与引擎默认值合并后重建
+    let retry = engine.default_retry().with_overrides(&config.retry);
+    build_engine(config, context_prompt, Some(retry))
+}
+
+/// 创建单个引擎，`retry` 为空时使用引擎默认的重试配置 (仅 HTTP 引擎有内部重试)
+fn build_engine(
+    config: &ASRProviderConfig,
+    context_prompt: Option<&str>,
+    retry: Option<RetryConfig>,
+) -> Result<Box<dyn ASREngine>, ASRError> {
+    let context_prompt = context_prompt.map(str::to_string);
+    
     let engine_type = EngineType::from(config.provider.clone());
     let mode = ASRMode::from(config.mode.clone());
     
@@ -253,7 +750,13 @@ pub fn create_engine(config: &ASRProviderConfig) -> Result<Box<dyn ASREngine>, A
                 .ok_or_else(|| ASRError::ConfigError("缺少 dashscope_api_key".to_string()))?;
             
             match mode {
-                ASRMode::Http => Ok(Box::new(QwenHttpEngine::new(api_key))),
+                ASRMode::Http => {
+                    let engine = match retry {
+                        Some(retry) => QwenHttpEngine::with_config(api_key, retry),
+                        None => QwenHttpEngine::new(api_key),
+                    };
+                    Ok(Box::new(engine.with_context(context_prompt)))
+                }
                 ASRMode::Realtime => Ok(Box::new(QwenRealtimeEngine::new(api_key))),
             }
         }
@@ -264,18 +767,83 @@ pub fn create_engine(config: &ASRProviderConfig) -> Result<Box<dyn ASREngine>, A
                 .ok_or_else(|| ASRError::ConfigError("缺少 access_token".to_string()))?;
             
             match mode {
-                ASRMode::Http => Ok(Box::new(DoubaoHttpEngine::new(app_id, access_token))),
+                ASRMode::Http => Ok(Box::new(match retry {
+                    Some(retry) => DoubaoHttpEngine::with_config(app_id, access_token, retry),
+                    None => DoubaoHttpEngine::new(app_id, access_token),
+                })),
                 ASRMode::Realtime => Ok(Box::new(DoubaoRealtimeEngine::new(app_id, access_token))),
             }
         }
         EngineType::SenseVoice => {
             let api_key = config.siliconflow_api_key.clone()
                 .ok_or_else(|| ASRError::ConfigError("缺少 siliconflow_api_key".to_string()))?;
-            Ok(Box::new(SenseVoiceHttpEngine::new(api_key)))
+            Ok(Box::new(match retry {
+                Some(retry) => SenseVoiceHttpEngine::with_config(api_key, retry),
+                None => SenseVoiceHttpEngine::new(api_key),
+            }))
+        }
+        EngineType::Volcengine => {
+            let app_id = config.app_id.clone()
+                .ok_or_else(|| ASRError::ConfigError("缺少 app_id".to_string()))?;
+            let access_token = config.access_token.clone()
+                .ok_or_else(|| ASRError::ConfigError("缺少 access_token".to_string()))?;
+            let cluster = config.cluster.clone()
+                .unwrap_or_else(|| volcengine::DEFAULT_CLUSTER.to_string());
+            Ok(Box::new(VolcengineEngine::new(app_id, access_token, cluster).with_nbest(config.partial_alternatives)))
+        }
+        EngineType::Generic => {
+            let generic_http = config.generic_http.clone()
+                .ok_or_else(|| ASRError::ConfigError("缺少 generic_http 配置".to_string()))?;
+            Ok(Box::new(match retry {
+                Some(retry) => GenericHttpEngine::with_config(generic_http, retry)?,
+                None => GenericHttpEngine::new(generic_http)?,
+            }))
+        }
+        EngineType::Google => {
+            let google = config.google.clone()
+                .ok_or_else(|| ASRError::ConfigError("缺少 google 配置".to_string()))?;
+            Ok(Box::new(match retry {
+                Some(retry) => GoogleEngine::with_config(google, retry)?,
+                None => GoogleEngine::new(google)?,
+            }))
+        }
+        EngineType::OpenAI => {
+            let openai = config.openai.clone()
+                .ok_or_else(|| ASRError::ConfigError("缺少 openai 配置".to_string()))?;
+            Ok(Box::new(OpenAIRealtimeEngine::new(openai).with_prompt(context_prompt)))
         }
     }
 }
 
+/// 按指定识别语言创建引擎 (多语言择优的单个候选)
+fn create_language_engine(
+    config: &ASRProviderConfig,
+    language: &str,
+    context_prompt: Option<&str>,
+) -> Result<Box<dyn ASREngine>, ASRError> {
+    let mut config = config.clone();
+    config.candidate_languages.clear();
+    
+    match config.provider {
+        ASRProvider::Qwen => {
+            let api_key = config.dashscope_api_key.clone()
+                .ok_or_else(|| ASRError::ConfigError("缺少 dashscope_api_key".to_string()))?;
+            Ok(Box::new(
+                QwenHttpEngine::with_config(api_key, RetryConfig::default().with_overrides(&config.retry))
+                    .with_language(language.to_string())
+                    .with_context(context_prompt.map(str::to_string)),
+            ))
+        }
+        ASRProvider::Google => {
+            if let Some(ref mut google) = config.google {
+                google.language_code = language.to_string();
+            }
+            create_engine_with_context(&config, context_prompt)
+        }
+        _ => Err(ASRError::ConfigError(format!("供应商 {} 不支持多语言择优", config.provider))),
+    }
+}
+
 /// 根据引擎类型创建引擎
 pub fn create_engine_by_type(
     engine_type: EngineType,
@@ -308,5 +876,164 @@ pub fn create_engine_by_type(
                 .ok_or_else(|| ASRError::ConfigError("缺少 API Key".to_string()))?;
             Ok(Box::new(SenseVoiceHttpEngine::new(api_key)))
         }
+        EngineType::Volcengine => {
+            let app_id = credentials.app_id
+                .ok_or_else(|| ASRError::ConfigError("缺少 app_id".to_string()))?;
+            let access_token = credentials.access_token
+                .ok_or_else(|| ASRError::ConfigError("缺少 access_token".to_string()))?;
+            let cluster = credentials.cluster
+                .unwrap_or_else(|| volcengine::DEFAULT_CLUSTER.to_string());
+            Ok(Box::new(VolcengineEngine::new(app_id, access_token, cluster)))
+        }
+        EngineType::Generic => {
+            let generic_http = credentials.generic_http
+                .ok_or_else(|| ASRError::ConfigError("缺少 generic_http 配置".to_string()))?;
+            Ok(Box::new(GenericHttpEngine::new(generic_http)?))
+        }
+        EngineType::Google => {
+            let google = credentials.google
+                .ok_or_else(|| ASRError::ConfigError("缺少 google 配置".to_string()))?;
+            Ok(Box::new(GoogleEngine::new(google)?))
+        }
+        EngineType::OpenAI => {
+            let openai = credentials.openai
+                .ok_or_else(|| ASRError::ConfigError("缺少 openai 配置".to_string()))?;
+            Ok(Box::new(OpenAIRealtimeEngine::new(openai)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_retryable_and_suggestion() {
+        assert!(ASRError::Timeout { timeout_ms: 5000 }.is_retryable());
+        assert!(ASRError::network("reset").is_retryable());
+
+        // 底层错误保留在 source 链中，Display 只显示简短说明
+        let io = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "connection reset by peer");
+        let network = ASRError::network_with("读取响应失败", io);
+        assert_eq!(network.to_string(), "网络错误: 读取响应失败");
+        let source = std::error::Error::source(&network).unwrap();
+        assert_eq!(source.to_string(), "connection reset by peer");
+        assert_eq!(network.detailed(), "网络错误: 读取响应失败: connection reset by peer");
+        assert!(format!("{:?}", network.clone()).contains("ConnectionReset"));
+        assert!(network.is_network());
+        assert_eq!(ASRError::websocket("closed").detailed(), "WebSocket 错误: closed");
+
+        let auth = ASRError::AuthFailed { engine: "qwen".into(), message: "invalid key".into() };
+        assert!(!auth.is_retryable());
+        assert!(auth.suggestion().contains("API Key"));
+        assert!(!ASRError::ConfigError("missing".into()).is_retryable());
+
+        // 主备引擎均因网络失败时视为离线
+        let offline = |network| ASRError::AllEnginesFailed { primary_error: String::new(), fallback_error: None, network };
+        assert!(offline(true).is_network());
+        assert!(!offline(false).is_network() && offline(false).is_retryable());
+        assert!(!auth.is_network());
+    }
+
+    #[test]
+    fn test_join_segment_text() {
+        let mut text = String::new();
+        for segment in ["今天天气很好，", "我们去公园吧。", "Then we", "went home.", "好的"] {
+            join_segment_text(&mut text, segment);
+        }
+        assert_eq!(text, "今天天气很好，我们去公园吧。Then we went home.好的");
+    }
+
+    #[test]
+    fn test_rank_nbest() {
+        let candidates = vec![
+            ("第二".to_string(), 0.5),
+            ("第一".to_string(), 0.9),
+            ("".to_string(), 1.0),
+            ("同分在后".to_string(), 0.5),
+            ("第一".to_string(), 0.3),
+        ];
+        let ranked = rank_nbest(candidates.clone(), 5);
+        let texts: Vec<&str> = ranked.iter().map(|(text, _)| text.as_str()).collect();
+        assert_eq!(texts, vec!["第一", "第二", "同分在后"]);
+        assert_eq!(ranked[0].1, 0.9);
+        assert_eq!(rank_nbest(candidates, 2).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_segment_progress() {
+        let reported = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = std::sync::Arc::clone(&reported);
+        let reporter = ProgressReporter::new(std::sync::Arc::new(move |text: &str, percent| {
+            sink.lock().unwrap().push((text.to_string(), percent));
+        }));
+        // 单次最长 10 秒、按调用次序返回 "段N" 的引擎
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let engine = fake::FakeEngine::http("segment")
+            .with_requirements(AudioRequirements { max_duration_ms: Some(10_000), ..AudioRequirements::default() })
+            .with_transcribe(move || {
+                let index = calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                async move { Ok(format!("段{}", index + 1)) }
+            });
+
+        let audio = AudioData::from_i16(vec![100; 16000 * 25], 16000, 1);
+        let transcript = transcribe_conformed_with_progress(&engine, &audio, Some(&reporter)).await.unwrap();
+        assert_eq!(transcript.text, "段1段2段3");
+        assert_eq!(
+            *reported.lock().unwrap(),
+            vec![("段1".to_string(), 33), ("段1段2".to_string(), 66), ("段1段2段3".to_string(), 100)]
+        );
+
+        // 短录音不上报进度
+        let reporter = ProgressReporter::new(std::sync::Arc::new(|_: &str, _| panic!("unexpected progress")));
+        let short = AudioData::from_i16(vec![100; 16000 * 5], 16000, 1);
+        transcribe_conformed_with_progress(&engine, &short, Some(&reporter)).await.unwrap();
+    }
+
+    #[test]
+    fn test_backoff_delay_capped_with_full_jitter() {
+        let config = RetryConfig { base_delay_ms: 100, max_delay_ms: 350, jitter: false, ..Default::default() };
+        let delays: Vec<u64> = (1..=4).map(|attempt| config.backoff_delay(attempt).as_millis() as u64).collect();
+        assert_eq!(delays, vec![100, 200, 350, 350]);
+        assert_eq!(config.backoff_delay(64), Duration::from_millis(350));
+
+        let config = RetryConfig { jitter: true, ..config };
+        let mut distinct = std::collections::HashSet::new();
+        for attempt in 1..=4 {
+            let cap = [100, 200, 350, 350][attempt as usize - 1];
+            for _ in 0..50 {
+                let delay = config.backoff_delay(attempt).as_millis() as u64;
+                assert!(delay <= cap, "attempt {}: {}ms > {}ms", attempt, delay, cap);
+                distinct.insert(delay);
+            }
+        }
+        // 抖动使延迟分散，而非固定值
+        assert!(distinct.len() > 10);
+    }
+
+    #[test]
+    fn test_engine_default_retry_and_override() {
+        let mut config: ASRProviderConfig = serde_json::from_value(serde_json::json!({
+            "provider": "generic",
+            "mode": "http",
+            "generic_http": { "url": "http://localhost:9000/asr", "text_path": "$.text" },
+        }))
+        .unwrap();
+        // 本机接口使用引擎声明的默认值
+        let engine = create_engine(&config).unwrap();
+        assert!(engine.is_local());
+        assert_eq!((engine.default_retry().timeout_ms, engine.default_retry().max_retries), (60_000, 1));
+
+        // 用户只覆盖部分字段，其余沿用引擎默认值
+        config.retry = serde_json::from_value(serde_json::json!({ "timeout_ms": 90000 })).unwrap();
+        let merged = engine.default_retry().with_overrides(&config.retry);
+        assert_eq!((merged.timeout_ms, merged.max_retries, merged.jitter), (90_000, 1, false));
+        assert!(create_engine(&config).is_ok());
+
+        config.max_concurrency = Some(0);
+        assert!(create_engine(&config).is_err());
+        config.max_concurrency = None;
+        config.rate_limit = Some(crate::voice::config::RateLimitConfig { requests_per_sec: 0.0, ..Default::default() });
+        assert!(create_engine(&config).is_err());
     }
 }
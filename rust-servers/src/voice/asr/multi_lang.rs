@@ -0,0 +1,237 @@
+// 多语言并行转录择优
+// 对语言不确定的音频，用每种候选语言分别转录，按置信度选出最优结果并标注实际语言；
+// 引擎未返回置信度时，以结果文本的语言检测置信度 (与候选语言一致时) 作为评分
+
+use async_trait::async_trait;
+use futures_util::stream::{self, StreamExt};
+use std::sync::Arc;
+
+use crate::utils::language::LanguageDetector;
+use crate::voice::asr::{ASREngine, ASRError, ASRMode, AudioRequirements, RealtimeSession, RetryConfig, Transcript};
+use crate::voice::audio::AudioData;
+
+/// 默认最多同时进行的转录数
+pub const DEFAULT_MAX_CONCURRENCY: usize = 2;
+
+/// 多语言择优包装
+pub struct MultiLangEngine {
+    /// (候选语言, 按该语言配置的引擎)
+    candidates: Vec<(String, Arc<dyn ASREngine>)>,
+    max_concurrency: usize,
+}
+
+impl MultiLangEngine {
+    pub fn new(candidates: Vec<(String, Arc<dyn ASREngine>)>) -> Self {
+        Self {
+            candidates,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+        }
+    }
+
+    fn first_engine(&self) -> Result<&Arc<dyn ASREngine>, ASRError> {
+        self.candidates
+            .first()
+            .map(|(_, engine)| engine)
+            .ok_or(ASRError::NotInitialized)
+    }
+}
+
+/// 结果评分：优先使用引擎置信度，否则按文本语言检测
+fn score(transcript: &Transcript, language: &str) -> f32 {
+    if transcript.text.trim().is_empty() {
+        return 0.0;
+    }
+    if let Some(confidence) = transcript.confidence {
+        return confidence;
+    }
+    let detected = LanguageDetector::new().detect(&transcript.text);
+    if detected.language == primary_subtag(language) {
+        detected.confidence as f32
+    } else {
+        0.0
+    }
+}
+
+/// 语言标签的主语言部分，映射为 ISO 639-1 (如 cmn-Hans-CN -> zh，en-US -> en)
+fn primary_subtag(language: &str) -> String {
+    let primary = language.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+    match primary.as_str() {
+        "cmn" | "yue" | "wuu" => "zh".to_string(),
+        _ => primary,
+    }
+}
+
+#[async_trait]
+impl ASREngine for MultiLangEngine {
+    fn name(&self) -> &str {
+        self.candidates
+            .first()
+            .map(|(_, engine)| engine.name())
+            .unwrap_or("multi_lang")
+    }
+
+    fn supported_modes(&self) -> Vec<ASRMode> {
+        vec![ASRMode::Http]
+    }
+
+    fn audio_requirements(&self) -> AudioRequirements {
+        self.candidates
+            .first()
+            .map(|(_, engine)| engine.audio_requirements())
+            .unwrap_or_default()
+    }
+
+    async fn transcribe(&self, audio: &AudioData) -> Result<String, ASRError> {
+        self.transcribe_detailed(audio).await.map(|transcript| transcript.text)
+    }
+
+    async fn transcribe_detailed(&self, audio: &AudioData) -> Result<Transcript, ASRError> {
+        let first = self.first_engine()?;
+        if self.candidates.len() == 1 {
+            let mut transcript = first.transcribe_detailed(audio).await?;
+            transcript.language.get_or_insert_with(|| self.candidates[0].0.clone());
+            return Ok(transcript);
+        }
+
+        let tasks: Vec<_> = self.candidates
+            .iter()
+            .map(|(language, engine)| {
+                let (language, engine) = (language.clone(), Arc::clone(engine));
+                async move {
+                    let result = engine.transcribe_detailed(audio).await;
+                    (language, result)
+                }
+            })
+            .collect();
+        let results: Vec<(String, Result<Transcript, ASRError>)> = stream::iter(tasks)
+            .buffer_unordered(self.max_concurrency)
+            .collect()
+            .await;
+
+        let mut errors = Vec::new();
+        let mut all_network = true;
+        let mut best: Option<(f32, Transcript)> = None;
+        for (language, result) in results {
+            match result {
+                Ok(mut transcript) => {
+                    let score = score(&transcript, &language);
+                    eprintln!("[INFO] 多语言转录 [{}] 评分 {:.2}: {}", language, score, transcript.text);
+                    transcript.language = Some(language);
+                    transcript.confidence.get_or_insert(score);
+                    if best.as_ref().is_none_or(|(best_score, _)| score > *best_score) {
+                        best = Some((score, transcript));
+                    }
+                }
+                Err(e) => {
+                    eprintln!("[WARN] 多语言转录 [{}] 失败: {}", language, e);
+                    all_network &= e.is_network();
+                    errors.push(format!("{}: {}", language, e));
+                }
+            }
+        }
+
+        best.map(|(_, transcript)| transcript)
+            .ok_or_else(|| ASRError::AllEnginesFailed {
+                primary_error: errors.join("; "),
+                fallback_error: None,
+                network: all_network,
+            })
+    }
+
+    /// 候选结果只在单一语言内比较，使用首个候选语言的引擎
+    async fn transcribe_nbest(&self, audio: &AudioData, n: usize) -> Result<Vec<(String, f32)>, ASRError> {
+        self.first_engine()?.transcribe_nbest(audio, n).await
+    }
+
+    async fn create_realtime_session(&self) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        Err(ASRError::UnsupportedOperation(
+            "多语言择优仅支持 HTTP 模式".to_string()
+        ))
+    }
+
+    fn default_retry(&self) -> RetryConfig {
+        self.candidates
+            .first()
+            .map(|(_, engine)| engine.default_retry())
+            .unwrap_or_default()
+    }
+
+    fn is_local(&self) -> bool {
+        !self.candidates.is_empty() && self.candidates.iter().all(|(_, engine)| engine.is_local())
+    }
+
+    async fn warm_up(&self) -> Result<(), ASRError> {
+        for (_, engine) in &self.candidates {
+            engine.warm_up().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voice::asr::fake::{FakeEngine, InFlight};
+    use std::time::Duration;
+
+    /// 各语言返回固定结果的候选引擎，共用一个并发计数
+    fn engines(results: Vec<(&str, Result<Transcript, ASRError>)>) -> (MultiLangEngine, Arc<InFlight>) {
+        let in_flight = Arc::new(InFlight::default());
+        let candidates = results
+            .into_iter()
+            .map(|(language, result)| {
+                let counter = Arc::clone(&in_flight);
+                let engine: Arc<dyn ASREngine> = Arc::new(FakeEngine::http("fixed").with_transcript(move || {
+                    let (in_flight, result) = (Arc::clone(&counter), result.clone());
+                    async move {
+                        in_flight.hold(Duration::from_millis(20)).await;
+                        result
+                    }
+                }));
+                (language.to_string(), engine)
+            })
+            .collect();
+        (MultiLangEngine::new(candidates), in_flight)
+    }
+
+    #[tokio::test]
+    async fn test_picks_best_language_with_limited_concurrency() {
+        let audio = AudioData::new(vec![0.1; 1600], 16000, 1);
+        let (engine, peak) = engines(vec![
+            ("zh", Ok(Transcript::from_text("Hello world, this is a test of the speech engine.".to_string()))),
+            ("en", Ok(Transcript::from_text("Hello world, this is a test of the speech engine.".to_string()))),
+            ("ja", Err(ASRError::network("timeout"))),
+        ]);
+
+        let transcript = engine.transcribe_detailed(&audio).await.unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert!(transcript.confidence.unwrap() > 0.0);
+        assert!(peak.peak() <= DEFAULT_MAX_CONCURRENCY);
+
+        // 引擎给出的置信度优先
+        let scored = |text: &str, confidence| Ok(Transcript { text: text.to_string(), language: None, confidence: Some(confidence) });
+        let (engine, _) = engines(vec![("en-US", scored("hello", 0.4)), ("cmn-Hans-CN", scored("哈喽", 0.8))]);
+        let transcript = engine.transcribe_detailed(&audio).await.unwrap();
+        assert_eq!(transcript.text, "哈喽");
+        assert_eq!(transcript.language.as_deref(), Some("cmn-Hans-CN"));
+    }
+
+    #[tokio::test]
+    async fn test_all_languages_failed() {
+        let audio = AudioData::new(vec![0.1; 1600], 16000, 1);
+        let (engine, _) = engines(vec![
+            ("zh", Err(ASRError::network("reset"))),
+            ("en", Err(ASRError::Timeout { timeout_ms: 1000 })),
+        ]);
+
+        let err = engine.transcribe_detailed(&audio).await.unwrap_err();
+        match err {
+            ASRError::AllEnginesFailed { primary_error, network, .. } => {
+                assert!(primary_error.contains("zh") && primary_error.contains("en"), "{}", primary_error);
+                assert!(network);
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+        assert_eq!(primary_subtag("cmn-Hans-CN"), "zh");
+    }
+}
@@ -0,0 +1,543 @@
+// OpenAI Realtime API 转录引擎
+// 通过 Realtime WebSocket 接口 (transcription 会话) 边录边转录，Bearer 鉴权
+
+use async_trait::async_trait;
+use base64::{Engine as _, engine::general_purpose};
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{self, client::IntoClientRequest, http, Message},
+};
+
+use crate::voice::asr::{ASREngine, AudioRequirements, ASRError, ASRMode, PartialResultCallback, RealtimeSession};
+use crate::voice::audio::recorder::{convert_f32_to_i16, convert_i16_to_f32, resample, TARGET_SAMPLE_RATE};
+use crate::voice::audio::AudioData;
+use crate::voice::config::OpenAIConfig;
+
+const ENGINE_NAME: &str = "openai";
+const DEFAULT_REALTIME_URL: &str = "wss://api.openai.com/v1/realtime?intent=transcription";
+/// Realtime 接口要求的 PCM 采样率
+const REALTIME_SAMPLE_RATE: u32 = 24000;
+const TRANSCRIPTION_TIMEOUT_SECS: u64 = 10;
+
+/// 提交空缓冲区时服务端返回的错误码 (结束时没有新音频，属正常情况)
+const COMMIT_EMPTY_CODE: &str = "input_audio_buffer_commit_empty";
+
+/// 会话内共享的部分结果回调
+type SharedPartialCallback = Arc<std::sync::Mutex<Option<PartialResultCallback>>>;
+
+pub struct OpenAIRealtimeEngine {
+    config: OpenAIConfig,
+    /// 转录提示词 (引导专有名词拼写与风格)
+    prompt: Option<String>,
+}
+
+impl OpenAIRealtimeEngine {
+    pub fn new(config: OpenAIConfig) -> Self {
+        Self { config, prompt: None }
+    }
+
+    pub fn with_prompt(mut self, prompt: Option<String>) -> Self {
+        self.prompt = prompt;
+        self
+    }
+}
+
+#[async_trait]
+impl ASREngine for OpenAIRealtimeEngine {
+    fn name(&self) -> &str {
+        ENGINE_NAME
+    }
+
+    fn supported_modes(&self) -> Vec<ASRMode> {
+        vec![ASRMode::Realtime]
+    }
+
+    fn audio_requirements(&self) -> AudioRequirements {
+        // 输入为 16kHz，发送前内部转换为接口要求的 24kHz
+        AudioRequirements::pcm16_mono(TARGET_SAMPLE_RATE)
+    }
+
+    async fn transcribe(&self, _audio: &AudioData) -> Result<String, ASRError> {
+        Err(ASRError::UnsupportedOperation(
+            "OpenAIRealtimeEngine 仅支持 Realtime 模式".to_string()
+        ))
+    }
+
+    async fn create_realtime_session(&self) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        let session = OpenAIRealtimeSession::connect(&self.config, self.prompt.as_deref()).await?;
+        Ok(Box::new(session))
+    }
+
+    async fn create_realtime_session_with_language(&self, language: &str) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        let config = OpenAIConfig { language: Some(language.to_string()), ..self.config.clone() };
+        let session = OpenAIRealtimeSession::connect(&config, self.prompt.as_deref()).await?;
+        Ok(Box::new(session))
+    }
+}
+
+// ============================================================================
+// 协议
+// ============================================================================
+
+/// 构造 session.update 事件：配置转录模型、音频格式，关闭服务端 VAD (结束时统一提交)
+fn session_update_event(config: &OpenAIConfig, prompt: Option<&str>) -> serde_json::Value {
+    let mut transcription = serde_json::json!({ "model": config.model });
+    if let Some(ref language) = config.language {
+        transcription["language"] = serde_json::json!(language);
+    }
+    if let Some(prompt) = prompt {
+        transcription["prompt"] = serde_json::json!(prompt);
+    }
+
+    serde_json::json!({
+        "type": "session.update",
+        "session": {
+            "type": "transcription",
+            "audio": {
+                "input": {
+                    "format": { "type": "audio/pcm", "rate": REALTIME_SAMPLE_RATE },
+                    "transcription": transcription,
+                    "turn_detection": serde_json::Value::Null
+                }
+            }
+        }
+    })
+}
+
+/// 16kHz 16-bit PCM 转为 Realtime 接口要求的 24kHz
+fn to_realtime_pcm(chunk: &[u8]) -> Vec<u8> {
+    let samples: Vec<i16> = chunk
+        .chunks_exact(2)
+        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    let resampled = resample(&convert_i16_to_f32(&samples), TARGET_SAMPLE_RATE, REALTIME_SAMPLE_RATE);
+    convert_f32_to_i16(&resampled)
+        .into_iter()
+        .flat_map(i16::to_le_bytes)
+        .collect()
+}
+
+/// 服务端事件 (只关心转录相关部分)
+#[derive(Debug)]
+enum ServerEvent {
+    /// 音频缓冲区已提交为一个对话项
+    Committed { item_id: String },
+    /// 转录增量
+    Delta { item_id: String, delta: String },
+    /// 某个对话项转录完成
+    Completed { item_id: String, transcript: String },
+    /// 服务端错误 (附原始错误码)
+    Error { code: String, error: ASRError },
+    Other,
+}
+
+fn parse_server_event(data: &serde_json::Value) -> ServerEvent {
+    let item_id = || data["item_id"].as_str().unwrap_or_default().to_string();
+
+    match data["type"].as_str().unwrap_or("") {
+        "input_audio_buffer.committed" => ServerEvent::Committed { item_id: item_id() },
+        "conversation.item.input_audio_transcription.delta" => ServerEvent::Delta {
+            item_id: item_id(),
+            delta: data["delta"].as_str().unwrap_or_default().to_string(),
+        },
+        "conversation.item.input_audio_transcription.completed" => ServerEvent::Completed {
+            item_id: item_id(),
+            transcript: data["transcript"].as_str().unwrap_or_default().to_string(),
+        },
+        "conversation.item.input_audio_transcription.failed" | "error" => ServerEvent::Error {
+            code: data["error"]["code"].as_str().unwrap_or_default().to_string(),
+            error: map_error_event(&data["error"]),
+        },
+        _ => ServerEvent::Other,
+    }
+}
+
+/// 把服务端 error 对象映射为 ASRError
+fn map_error_event(error: &serde_json::Value) -> ASRError {
+    let error_type = error["type"].as_str().unwrap_or_default();
+    let code = error["code"].as_str().unwrap_or_default();
+    let message = error["message"].as_str().unwrap_or("未知错误").to_string();
+
+    match (error_type, code) {
+        ("authentication_error", _) | (_, "invalid_api_key") => ASRError::AuthFailed {
+            engine: ENGINE_NAME.to_string(),
+            message,
+        },
+        ("rate_limit_error" | "insufficient_quota", _) | (_, "rate_limit_exceeded" | "insufficient_quota") => {
+            ASRError::QuotaExceeded { engine: ENGINE_NAME.to_string() }
+        }
+        (_, code) if code.contains("audio") => ASRError::InvalidAudio(message),
+        ("invalid_request_error", _) => ASRError::ConfigError(format!("OpenAI 请求无效: {}", message)),
+        _ => ASRError::websocket(format!("API 错误: {}", message)),
+    }
+}
+
+/// 把握手失败映射为 ASRError (鉴权与配额错误单独区分)
+fn map_connect_error(error: tungstenite::Error) -> ASRError {
+    match error {
+        tungstenite::Error::Http(response) => match response.status().as_u16() {
+            401 | 403 => ASRError::AuthFailed {
+                engine: ENGINE_NAME.to_string(),
+                message: format!("HTTP {}", response.status()),
+            },
+            429 => ASRError::QuotaExceeded { engine: ENGINE_NAME.to_string() },
+            _ => ASRError::websocket(format!("WebSocket 握手失败: HTTP {}", response.status())),
+        },
+        e => ASRError::websocket_with("WebSocket 连接失败", e),
+    }
+}
+
+/// 按提交顺序拼接各对话项的转录文本
+#[derive(Debug, Default)]
+struct TranscriptBuffer {
+    order: Vec<String>,
+    texts: HashMap<String, String>,
+    completed: usize,
+}
+
+impl TranscriptBuffer {
+    fn ensure_item(&mut self, item_id: &str) -> &mut String {
+        if !self.texts.contains_key(item_id) {
+            self.order.push(item_id.to_string());
+        }
+        self.texts.entry(item_id.to_string()).or_default()
+    }
+
+    fn committed(&mut self, item_id: &str) {
+        self.ensure_item(item_id);
+    }
+
+    fn delta(&mut self, item_id: &str, delta: &str) {
+        self.ensure_item(item_id).push_str(delta);
+    }
+
+    fn complete(&mut self, item_id: &str, transcript: &str) {
+        let text = self.ensure_item(item_id);
+        text.clear();
+        text.push_str(transcript);
+        self.completed += 1;
+    }
+
+    /// 已提交的对话项是否都已转录完成
+    fn all_completed(&self) -> bool {
+        self.completed >= self.order.len()
+    }
+
+    fn text(&self) -> String {
+        let mut output = String::new();
+        for text in self.order.iter().filter_map(|id| self.texts.get(id)) {
+            let text = text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            // 英文片段之间补空格，中文直接连接
+            let needs_space = output.chars().last().is_some_and(|c| c.is_ascii_alphanumeric() || c.is_ascii_punctuation())
+                && text.chars().next().is_some_and(|c| c.is_ascii_alphanumeric());
+            if needs_space {
+                output.push(' ');
+            }
+            output.push_str(text);
+        }
+        output
+    }
+}
+
+// ============================================================================
+// 会话
+// ============================================================================
+
+enum SessionCommand {
+    SendAudio(Vec<u8>),
+    Commit,
+    /// 切换识别语言 (重新发送 session.update)
+    SetLanguage(String),
+    Close,
+}
+
+pub struct OpenAIRealtimeSession {
+    cmd_sender: mpsc::Sender<SessionCommand>,
+    result_receiver: Option<oneshot::Receiver<Result<String, ASRError>>>,
+    partial_callback: SharedPartialCallback,
+    /// 已调用 close，等待最后一次提交的转录结果
+    finishing: Arc<AtomicBool>,
+    /// WebSocket 读写任务 (中止时据此关闭底层连接)
+    io_tasks: Vec<JoinHandle<()>>,
+}
+
+impl OpenAIRealtimeSession {
+    async fn connect(config: &OpenAIConfig, prompt: Option<&str>) -> Result<Self, ASRError> {
+        let url = config.endpoint.as_deref().unwrap_or(DEFAULT_REALTIME_URL);
+        eprintln!("[INFO] 创建 OpenAI Realtime WebSocket 连接: {}", url);
+
+        let mut request = url
+            .into_client_request()
+            .map_err(|e| ASRError::websocket_with("构建请求失败", e))?;
+        let auth = http::HeaderValue::from_str(&format!("Bearer {}", config.api_key))
+            .map_err(|_| ASRError::ConfigError("API Key 含有非法字符".to_string()))?;
+        request.headers_mut().insert(http::header::AUTHORIZATION, auth);
+
+        let (ws_stream, _) = connect_async(request).await.map_err(map_connect_error)?;
+        eprintln!("[INFO] OpenAI Realtime WebSocket 连接成功");
+
+        let (mut write, mut read) = ws_stream.split();
+
+        write.send(Message::Text(session_update_event(config, prompt).to_string().into())).await
+            .map_err(|e| ASRError::websocket_with("发送 session.update 失败", e))?;
+
+        let (cmd_tx, mut cmd_rx) = mpsc::channel::<SessionCommand>(100);
+        let (result_tx, result_rx) = oneshot::channel::<Result<String, ASRError>>();
+        let commits_sent = Arc::new(AtomicUsize::new(0));
+        let finishing = Arc::new(AtomicBool::new(false));
+        let partial_callback: SharedPartialCallback = Arc::new(std::sync::Mutex::new(None));
+
+        let writer_commits = Arc::clone(&commits_sent);
+        let mut writer_config = config.clone();
+        let writer_prompt = prompt.map(str::to_string);
+        let writer_task = tokio::spawn(async move {
+            while let Some(cmd) = cmd_rx.recv().await {
+                let event = match cmd {
+                    SessionCommand::SendAudio(chunk) => serde_json::json!({
+                        "type": "input_audio_buffer.append",
+                        "audio": general_purpose::STANDARD.encode(to_realtime_pcm(&chunk)),
+                    }),
+                    SessionCommand::Commit => {
+                        writer_commits.fetch_add(1, Ordering::SeqCst);
+                        serde_json::json!({ "type": "input_audio_buffer.commit" })
+                    }
+                    SessionCommand::SetLanguage(language) => {
+                        writer_config.language = Some(language);
+                        session_update_event(&writer_config, writer_prompt.as_deref())
+                    }
+                    SessionCommand::Close => {
+                        let _ = write.close().await;
+                        break;
+                    }
+                };
+
+                if let Err(e) = write.send(Message::Text(event.to_string().into())).await {
+                    eprintln!("[ERROR] 发送 OpenAI Realtime 事件失败: {}", e);
+                    break;
+                }
+            }
+        });
+
+        let reader_finishing = Arc::clone(&finishing);
+        let reader_callback = Arc::clone(&partial_callback);
+        let reader_task = tokio::spawn(async move {
+            let mut transcripts = TranscriptBuffer::default();
+            let mut committed = 0usize;
+            let mut result_tx = Some(result_tx);
+            let mut finish = |result: Result<String, ASRError>| {
+                if let Some(tx) = result_tx.take() {
+                    let _ = tx.send(result);
+                }
+            };
+
+            while let Some(msg) = read.next().await {
+                let text = match msg {
+                    Ok(Message::Text(text)) => text,
+                    Ok(Message::Close(_)) => {
+                        eprintln!("[INFO] OpenAI Realtime WebSocket 连接关闭");
+                        break;
+                    }
+                    Ok(_) => continue,
+                    Err(e) => {
+                        finish(Err(ASRError::websocket_with("读取消息失败", e)));
+                        return;
+                    }
+                };
+
+                let data: serde_json::Value = match serde_json::from_str(&text) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        eprintln!("[WARN] 解析 OpenAI Realtime 消息失败: {}", e);
+                        continue;
+                    }
+                };
+
+                let finishing = reader_finishing.load(Ordering::SeqCst);
+                match parse_server_event(&data) {
+                    ServerEvent::Committed { item_id } => {
+                        committed += 1;
+                        transcripts.committed(&item_id);
+                    }
+                    ServerEvent::Delta { item_id, delta } => {
+                        transcripts.delta(&item_id, &delta);
+                        if let Ok(slot) = reader_callback.lock() {
+                            if let Some(ref cb) = *slot {
+                                cb(&transcripts.text());
+                            }
+                        }
+                    }
+                    ServerEvent::Completed { item_id, transcript } => {
+                        transcripts.complete(&item_id, &transcript);
+                        eprintln!("[INFO] OpenAI 转录完成: {}", transcript);
+                    }
+                    ServerEvent::Error { code, .. } if finishing && code == COMMIT_EMPTY_CODE => {
+                        // 结束前没有新音频，已有结果即为最终结果
+                        committed = commits_sent.load(Ordering::SeqCst);
+                    }
+                    ServerEvent::Error { error, .. } => {
+                        eprintln!("[ERROR] OpenAI Realtime 错误: {}", error);
+                        finish(Err(error));
+                        return;
+                    }
+                    ServerEvent::Other => {}
+                }
+
+                if finishing && committed >= commits_sent.load(Ordering::SeqCst) && transcripts.all_completed() {
+                    finish(Ok(transcripts.text()));
+                    return;
+                }
+            }
+
+            finish(Err(ASRError::InternalError("未收到转录结果".to_string())));
+        });
+
+        Ok(Self {
+            cmd_sender: cmd_tx,
+            result_receiver: Some(result_rx),
+            partial_callback,
+            finishing,
+            io_tasks: vec![writer_task, reader_task],
+        })
+    }
+}
+
+#[async_trait]
+impl RealtimeSession for OpenAIRealtimeSession {
+    async fn send_chunk(&mut self, chunk: &[u8]) -> Result<(), ASRError> {
+        self.cmd_sender.send(SessionCommand::SendAudio(chunk.to_vec())).await
+            .map_err(|_| ASRError::websocket("发送音频块失败：通道已关闭"))
+    }
+
+    async fn commit(&mut self) -> Result<(), ASRError> {
+        self.cmd_sender.send(SessionCommand::Commit).await
+            .map_err(|_| ASRError::websocket("提交音频失败：通道已关闭"))
+    }
+
+    async fn set_language(&mut self, language: &str) -> Result<bool, ASRError> {
+        self.cmd_sender.send(SessionCommand::SetLanguage(language.to_string())).await
+            .map_err(|_| ASRError::websocket("切换语言失败：通道已关闭"))?;
+        Ok(true)
+    }
+
+    async fn close(&mut self) -> Result<String, ASRError> {
+        self.finishing.store(true, Ordering::SeqCst);
+        self.commit().await?;
+
+        let result_rx = self.result_receiver.take()
+            .ok_or_else(|| ASRError::InternalError("会话已关闭".to_string()))?;
+
+        let result = tokio::time::timeout(Duration::from_secs(TRANSCRIPTION_TIMEOUT_SECS), result_rx)
+            .await
+            .map_err(|_| ASRError::Timeout { timeout_ms: TRANSCRIPTION_TIMEOUT_SECS * 1000 })?
+            .map_err(|_| ASRError::InternalError("结果通道已关闭".to_string()))?;
+
+        let _ = self.cmd_sender.send(SessionCommand::Close).await;
+
+        result
+    }
+
+    fn abort(&mut self) {
+        for task in self.io_tasks.drain(..) {
+            task.abort();
+        }
+    }
+
+    fn set_partial_callback(&mut self, callback: Box<dyn Fn(&str) + Send + 'static>) {
+        if let Ok(mut slot) = self.partial_callback.lock() {
+            *slot = Some(callback);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> OpenAIConfig {
+        OpenAIConfig {
+            api_key: "sk-test".to_string(),
+            model: "gpt-4o-transcribe".to_string(),
+            language: Some("zh".to_string()),
+            endpoint: None,
+        }
+    }
+
+    #[test]
+    fn test_session_update_event() {
+        let event = session_update_event(&config(), None);
+        assert_eq!(event["type"], "session.update");
+        let input = &event["session"]["audio"]["input"];
+        assert_eq!(input["format"]["rate"], REALTIME_SAMPLE_RATE);
+        assert_eq!(input["transcription"]["model"], "gpt-4o-transcribe");
+        assert_eq!(input["transcription"]["language"], "zh");
+        assert!(input["turn_detection"].is_null());
+        assert!(input["transcription"]["prompt"].is_null());
+
+        let event = session_update_event(&config(), Some("Obsidian 插件"));
+        assert_eq!(event["session"]["audio"]["input"]["transcription"]["prompt"], "Obsidian 插件");
+
+        // 3200 个 16kHz 采样重采样为 4800 个 24kHz 采样
+        assert_eq!(to_realtime_pcm(&[0u8; 6400]).len(), 9600);
+    }
+
+    #[test]
+    fn test_parse_transcription_events() {
+        let mut transcripts = TranscriptBuffer::default();
+        let events = [
+            serde_json::json!({"type": "input_audio_buffer.committed", "item_id": "a"}),
+            serde_json::json!({"type": "conversation.item.input_audio_transcription.delta", "item_id": "a", "delta": "Hello"}),
+            serde_json::json!({"type": "conversation.item.input_audio_transcription.delta", "item_id": "a", "delta": " wor"}),
+            serde_json::json!({"type": "input_audio_buffer.committed", "item_id": "b"}),
+            serde_json::json!({"type": "conversation.item.input_audio_transcription.completed", "item_id": "a", "transcript": "Hello world."}),
+        ];
+        for event in &events {
+            match parse_server_event(event) {
+                ServerEvent::Committed { item_id } => transcripts.committed(&item_id),
+                ServerEvent::Delta { item_id, delta } => transcripts.delta(&item_id, &delta),
+                ServerEvent::Completed { item_id, transcript } => transcripts.complete(&item_id, &transcript),
+                other => panic!("unexpected event: {:?}", other),
+            }
+        }
+        assert!(!transcripts.all_completed());
+
+        transcripts.complete("b", "How are you");
+        assert!(transcripts.all_completed());
+        assert_eq!(transcripts.text(), "Hello world. How are you");
+    }
+
+    #[test]
+    fn test_map_error_events() {
+        let parse = |error: serde_json::Value| match parse_server_event(&serde_json::json!({"type": "error", "error": error})) {
+            ServerEvent::Error { code, error } => (code, error),
+            other => panic!("unexpected event: {:?}", other),
+        };
+
+        let (_, error) = parse(serde_json::json!({"type": "invalid_request_error", "code": "invalid_api_key", "message": "bad key"}));
+        assert!(matches!(error, ASRError::AuthFailed { .. }));
+        let (_, error) = parse(serde_json::json!({"type": "insufficient_quota", "message": "quota"}));
+        assert!(matches!(error, ASRError::QuotaExceeded { .. }));
+        let (code, error) = parse(serde_json::json!({"type": "invalid_request_error", "code": COMMIT_EMPTY_CODE, "message": "empty"}));
+        assert_eq!(code, COMMIT_EMPTY_CODE);
+        assert!(matches!(error, ASRError::InvalidAudio(_)));
+        let (_, error) = parse(serde_json::json!({"type": "invalid_request_error", "message": "unknown model"}));
+        assert!(matches!(error, ASRError::ConfigError(_)));
+        let (_, error) = parse(serde_json::json!({"type": "server_error", "message": "boom"}));
+        assert!(error.is_retryable());
+
+        let response = http::Response::builder().status(401).body(None).unwrap();
+        assert!(matches!(
+            map_connect_error(tungstenite::Error::Http(Box::new(response))),
+            ASRError::AuthFailed { .. }
+        ));
+    }
+}
@@ -0,0 +1,360 @@
+// 拼音标注
+// 按内置常用字读音表为中文转录结果逐字注音 (带声调符号)，供语言学习场景使用。
+// 多音字取常用读音，少数高频词组按词组读音覆盖 (如 "银行" 的 "行" 读 háng)；
+// 繁体字先转为简体再查表，表外汉字与非汉字字符原样返回
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::voice::asr::punctuator::is_cjk;
+use crate::voice::asr::script::to_simplified_map;
+
+/// 读音 → 汉字表。多音字只放在常用读音下；同一字重复出现时以先出现的为准
+const CHAR_READINGS: &[(&str, &str)] = &[
+    ("a", "啊"), ("ā", "阿"), ("āi", "哎唉埃挨"), ("ái", "癌"), ("ǎi", "矮蔼"), ("ài", "爱碍艾隘"),
+    ("ān", "安鞍氨"), ("àn", "案按暗岸"), ("áng", "昂"), ("āo", "凹"), ("áo", "熬"), ("ào", "奥傲澳"),
+    ("ba", "吧"), ("bā", "八巴扒疤"), ("bá", "拔"), ("bǎ", "把靶"), ("bà", "爸罢霸坝"), ("bái", "白"),
+    ("bǎi", "百摆柏"), ("bài", "败拜"), ("bān", "班般搬斑颁"), ("bǎn", "板版"), ("bàn", "办半伴扮拌瓣"),
+    ("bāng", "帮邦"), ("bǎng", "绑榜"), ("bàng", "棒傍磅谤"), ("bāo", "包胞"), ("báo", "薄雹"),
+    ("bǎo", "保宝饱堡"), ("bào", "报抱暴爆豹"), ("bēi", "杯悲碑卑"), ("běi", "北"),
+    ("bèi", "被备倍辈贝背"), ("bēn", "奔"), ("běn", "本"), ("bèn", "笨"), ("bēng", "崩绷"),
+    ("bèng", "蹦"), ("bī", "逼"), ("bí", "鼻"), ("bǐ", "比笔彼币"), ("bì", "必毕闭避壁臂弊碧蔽毙"),
+    ("biān", "边编鞭"), ("biǎn", "扁贬"), ("biàn", "变便遍辩辨"), ("biāo", "标彪"), ("biǎo", "表"),
+    ("bié", "别"), ("bīn", "宾滨"), ("bīng", "兵冰"), ("bǐng", "丙柄饼"), ("bìng", "并病"),
+    ("bō", "波播拨玻剥"), ("bó", "博伯脖膊驳泊勃"), ("bǔ", "补捕"), ("bù", "不部布步怖"),
+    ("cā", "擦"), ("cāi", "猜"), ("cái", "才材财裁"), ("cǎi", "采彩踩"), ("cài", "菜"),
+    ("cān", "参餐"), ("cán", "残蚕惭"), ("cǎn", "惨"), ("càn", "灿"), ("cāng", "仓苍舱"),
+    ("cáng", "藏"), ("cāo", "操糙"), ("cǎo", "草"), ("cè", "册侧厕测策"), ("céng", "层曾"),
+    ("chā", "差插叉"), ("chá", "查茶察"), ("chà", "岔"), ("chāi", "拆"), ("chái", "柴"),
+    ("chǎn", "产铲"), ("chàn", "颤"), ("chāng", "昌"), ("cháng", "长常场尝肠偿"), ("chǎng", "厂敞"),
+    ("chàng", "唱畅倡"), ("chāo", "超抄钞"), ("cháo", "朝潮巢"), ("chǎo", "吵炒"), ("chē", "车"),
+    ("chě", "扯"), ("chè", "彻撤"), ("chén", "陈沉晨尘臣"), ("chèn", "趁衬"), ("chēng", "称撑"),
+    ("chéng", "成城程承乘诚呈惩橙"), ("chī", "吃痴"), ("chí", "持迟池驰"), ("chǐ", "尺齿耻"),
+    ("chì", "赤翅斥"), ("chōng", "冲充"), ("chóng", "虫崇"), ("chǒng", "宠"), ("chōu", "抽"),
+    ("chóu", "愁仇筹酬绸"), ("chǒu", "丑"), ("chòu", "臭"), ("chū", "出初"), ("chú", "除厨锄"),
+    ("chǔ", "处础储楚"), ("chù", "触畜"), ("chuān", "穿川"), ("chuán", "传船"), ("chuǎn", "喘"),
+    ("chuāng", "窗疮"), ("chuáng", "床"), ("chuǎng", "闯"), ("chuàng", "创"), ("chuī", "吹炊"),
+    ("chuí", "垂锤"), ("chūn", "春"), ("chún", "纯唇"), ("cí", "词磁辞慈瓷"), ("cǐ", "此"),
+    ("cì", "次刺赐"), ("cōng", "聪葱匆"), ("cóng", "从丛"), ("còu", "凑"), ("cū", "粗"),
+    ("cù", "促醋"), ("cuī", "催摧"), ("cuì", "脆翠"), ("cūn", "村"), ("cún", "存"), ("cùn", "寸"),
+    ("cuò", "错措挫"), ("dā", "搭"), ("dá", "达答"), ("dǎ", "打"), ("dà", "大"), ("dāi", "呆"),
+    ("dài", "代带待袋戴贷"), ("dān", "单担丹耽"), ("dǎn", "胆"), ("dàn", "但蛋淡旦诞"),
+    ("dāng", "当"), ("dǎng", "党挡"), ("dàng", "档荡"), ("dāo", "刀"), ("dǎo", "导岛倒蹈"),
+    ("dào", "到道盗稻"), ("dé", "得德"), ("de", "的"), ("dēng", "灯登"), ("děng", "等"),
+    ("dèng", "邓瞪"), ("dī", "低滴堤"), ("dí", "敌笛"), ("dǐ", "底抵"), ("dì", "地第弟帝递"),
+    ("diǎn", "点典"), ("diàn", "电店垫殿"), ("diāo", "雕叼"), ("diào", "掉吊钓调"), ("diē", "跌爹"),
+    ("dié", "叠蝶"), ("dīng", "丁盯钉"), ("dǐng", "顶"), ("dìng", "定订"), ("diū", "丢"),
+    ("dōng", "东冬"), ("dǒng", "懂董"), ("dòng", "动洞冻栋"), ("dōu", "都兜"), ("dǒu", "抖陡"),
+    ("dòu", "豆逗斗"), ("dú", "读独毒"), ("dǔ", "堵赌"), ("dù", "度渡肚杜"), ("duān", "端"),
+    ("duǎn", "短"), ("duàn", "段断锻"), ("duī", "堆"), ("duì", "对队兑"), ("dūn", "吨蹲"),
+    ("dùn", "顿盾"), ("duō", "多"), ("duó", "夺"), ("duǒ", "朵躲"), ("é", "额鹅俄"),
+    ("è", "饿恶"), ("ēn", "恩"), ("ér", "而儿"), ("ěr", "耳尔"), ("èr", "二"), ("fā", "发"),
+    ("fá", "罚乏伐阀"), ("fǎ", "法"), ("fān", "翻番帆"), ("fán", "凡烦繁"), ("fǎn", "反返"),
+    ("fàn", "饭范犯泛贩"), ("fāng", "方芳"), ("fáng", "房防妨"), ("fǎng", "访仿纺"), ("fàng", "放"),
+    ("fēi", "非飞"), ("féi", "肥"), ("fèi", "费废肺"), ("fēn", "分纷芬吩"), ("fěn", "粉"),
+    ("fèn", "份奋愤"), ("fēng", "风丰封峰锋疯蜂"), ("féng", "逢缝"), ("fèng", "凤奉"), ("fó", "佛"),
+    ("fǒu", "否"), ("fū", "夫肤"), ("fú", "服福幅扶符浮伏"), ("fǔ", "府辅腐抚"),
+    ("fù", "父付负富复副附妇傅腹覆"), ("gāi", "该"), ("gǎi", "改"), ("gài", "概盖钙"),
+    ("gān", "干甘肝杆"), ("gǎn", "感敢赶"), ("gàn", "赣"), ("gāng", "刚钢纲缸"), ("gǎng", "港岗"),
+    ("gāo", "高糕"), ("gǎo", "搞稿"), ("gào", "告"), ("gē", "哥歌割鸽"), ("gé", "格革隔阁"),
+    ("gè", "个各"), ("gěi", "给"), ("gēn", "根跟"), ("gēng", "耕"), ("gèng", "更"),
+    ("gōng", "工公功攻宫供弓"), ("gǒng", "巩拱"), ("gòng", "共贡"), ("gōu", "沟钩勾"),
+    ("gǒu", "狗"), ("gòu", "够构购"), ("gū", "估姑孤"), ("gǔ", "古股骨鼓谷"), ("gù", "故顾固雇"),
+    ("guā", "瓜刮"), ("guà", "挂"), ("guāi", "乖"), ("guài", "怪"), ("guān", "关观官冠"),
+    ("guǎn", "管馆"), ("guàn", "惯贯灌罐"), ("guāng", "光"), ("guǎng", "广"), ("guī", "规归龟"),
+    ("guǐ", "鬼轨"), ("guì", "贵柜跪"), ("gǔn", "滚"), ("guō", "锅郭"), ("guó", "国"),
+    ("guǒ", "果裹"), ("guò", "过"), ("hā", "哈"), ("hái", "还孩"), ("hǎi", "海"), ("hài", "害"),
+    ("hán", "含寒韩函"), ("hǎn", "喊罕"), ("hàn", "汉汗旱"), ("háng", "航"), ("háo", "毫豪"),
+    ("hǎo", "好"), ("hào", "号耗浩"), ("hē", "喝"), ("hé", "和合河何核盒"), ("hè", "贺"),
+    ("hēi", "黑"), ("hěn", "很狠"), ("hèn", "恨"), ("héng", "横恒衡"), ("hōng", "轰烘"),
+    ("hóng", "红宏洪"), ("hóu", "猴喉"), ("hòu", "后候厚"), ("hū", "呼忽乎"), ("hú", "胡湖糊壶"),
+    ("hǔ", "虎"), ("hù", "户护互"), ("huā", "花"), ("huá", "华滑划"), ("huà", "话化画"),
+    ("huái", "怀"), ("huài", "坏"), ("huān", "欢"), ("huán", "环"), ("huǎn", "缓"),
+    ("huàn", "换患幻"), ("huāng", "荒慌"), ("huáng", "黄皇"), ("huī", "灰挥辉恢"), ("huí", "回"),
+    ("huǐ", "毁悔"), ("huì", "会汇惠慧绘"), ("hūn", "婚昏"), ("hún", "混魂"), ("huó", "活"),
+    ("huǒ", "火伙"), ("huò", "或获货祸惑"), ("jī", "机基几鸡积击激极饥肌"), ("jí", "级及即急集籍吉疾"),
+    ("jǐ", "己挤"), ("jì", "记计技际季纪济既继寄迹绩"), ("jiā", "家加佳夹"), ("jiá", "颊"),
+    ("jiǎ", "假甲"), ("jià", "价架驾嫁"), ("jiān", "间坚尖肩兼监艰"), ("jiǎn", "简检减剪捡"),
+    ("jiàn", "见件建健键渐箭鉴"), ("jiāng", "将江姜疆"), ("jiǎng", "讲奖"), ("jiàng", "降酱"),
+    ("jiāo", "交教焦胶骄郊"), ("jiǎo", "角脚搅"), ("jiào", "叫较轿"), ("jiē", "接街阶皆"),
+    ("jié", "节结洁杰"), ("jiě", "解姐"), ("jiè", "界借介届戒"), ("jīn", "今金斤津"),
+    ("jǐn", "仅紧尽谨"), ("jìn", "进近劲禁晋"), ("jīng", "经京精惊睛晶"), ("jǐng", "景警井"),
+    ("jìng", "竞净静境镜敬径"), ("jiū", "究纠"), ("jiǔ", "九久酒"), ("jiù", "就旧救舅"),
+    ("jū", "居局"), ("jú", "菊"), ("jǔ", "举"), ("jù", "具据句剧巨聚拒距"), ("juān", "捐"),
+    ("juǎn", "卷"), ("jué", "决觉绝"), ("jūn", "军均君"), ("kā", "咖"), ("kǎ", "卡"), ("kāi", "开"),
+    ("kǎi", "凯"), ("kān", "刊"), ("kǎn", "砍"), ("kàn", "看"), ("kāng", "康"), ("káng", "扛"),
+    ("kàng", "抗"), ("kǎo", "考烤"), ("kào", "靠"), ("kē", "科颗棵"), ("ké", "咳"), ("kě", "可渴"),
+    ("kè", "课客刻克"), ("kěn", "肯"), ("kōng", "空"), ("kǒng", "恐孔"), ("kòng", "控"),
+    ("kǒu", "口"), ("kū", "哭枯"), ("kǔ", "苦"), ("kù", "库裤酷"), ("kuā", "夸"), ("kuà", "跨"),
+    ("kuài", "快块筷"), ("kuān", "宽"), ("kuǎn", "款"), ("kuáng", "狂"), ("kuàng", "况矿框"),
+    ("kùn", "困"), ("kuò", "扩括阔"), ("la", "啦"), ("lā", "拉"), ("là", "辣蜡"), ("lái", "来"), ("lán", "蓝兰拦篮"),
+    ("lǎn", "懒览"), ("làn", "烂滥"), ("láng", "狼"), ("lǎng", "朗"), ("làng", "浪"), ("láo", "劳牢"),
+    ("lǎo", "老"), ("lè", "乐"), ("le", "了"), ("léi", "雷"), ("lèi", "类泪累"), ("lěng", "冷"),
+    ("lí", "离梨璃"), ("lǐ", "里理李礼"), ("lì", "力立利例历丽励粒"), ("liǎ", "俩"),
+    ("lián", "连联莲怜"), ("liǎn", "脸"), ("liàn", "练恋链炼"), ("liáng", "良粮凉量梁"),
+    ("liǎng", "两"), ("liàng", "亮辆谅"), ("liáo", "聊疗辽"), ("liào", "料"),
+    ("liè", "列烈裂劣猎"), ("lín", "林临邻"), ("líng", "零灵龄铃"), ("lǐng", "领岭"), ("lìng", "令另"),
+    ("liú", "流留刘"), ("liù", "六"), ("lóng", "龙聋笼"), ("lóu", "楼"), ("lú", "炉"), ("lǔ", "鲁"),
+    ("lù", "路录陆露鹿"), ("lǘ", "驴"), ("lǚ", "旅履屡"), ("lǜ", "律绿率虑"), ("luàn", "乱"),
+    ("lüè", "略"), ("lún", "论轮伦"), ("luó", "罗逻萝"), ("luò", "落络"), ("mā", "妈"), ("má", "麻"),
+    ("mǎ", "马码"), ("mà", "骂"), ("ma", "吗嘛"), ("mái", "埋"), ("mǎi", "买"), ("mài", "卖麦迈"),
+    ("mǎn", "满"), ("màn", "慢漫"), ("máng", "忙盲"), ("māo", "猫"), ("máo", "毛矛"), ("mào", "冒帽贸貌"),
+    ("me", "么"), ("méi", "没煤眉媒"), ("měi", "美每"), ("mèi", "妹"), ("mén", "门"), ("men", "们"),
+    ("mèng", "梦"), ("mí", "迷谜"), ("mǐ", "米"), ("mì", "密秘蜜"), ("mián", "棉眠"), ("miǎn", "免"),
+    ("miàn", "面"), ("miáo", "苗描"), ("miǎo", "秒"), ("miào", "妙庙"), ("miè", "灭"), ("mín", "民"),
+    ("mǐn", "敏"), ("míng", "名明鸣"), ("mìng", "命"), ("mó", "模磨摸魔"), ("mò", "末莫默墨"),
+    ("móu", "谋"), ("mǒu", "某"), ("mǔ", "母亩"), ("mù", "目木幕慕牧"), ("ná", "拿"), ("nǎ", "哪"),
+    ("nà", "那纳"), ("nǎi", "奶乃"), ("nài", "耐"), ("nán", "南男难"), ("nǎo", "脑恼"), ("nào", "闹"),
+    ("ne", "呢"), ("nèi", "内"), ("néng", "能"), ("ní", "泥"), ("nǐ", "你拟"), ("nián", "年"),
+    ("niàn", "念"), ("niáng", "娘"), ("niǎo", "鸟"), ("nín", "您"), ("níng", "宁"), ("niú", "牛"),
+    ("niǔ", "扭纽"), ("nóng", "农浓"), ("nòng", "弄"), ("nǔ", "努"), ("nù", "怒"), ("nǚ", "女"),
+    ("nuǎn", "暖"), ("ōu", "欧"), ("pá", "爬"), ("pà", "怕"), ("pāi", "拍"), ("pái", "排牌"),
+    ("pài", "派"), ("pán", "盘"), ("pàn", "判盼"), ("páng", "旁"), ("pàng", "胖"), ("pǎo", "跑"),
+    ("pào", "炮泡"), ("péi", "陪培赔"), ("pèi", "配佩"), ("pén", "盆"), ("péng", "朋鹏棚"),
+    ("pěng", "捧"), ("pèng", "碰"), ("pī", "批披"), ("pí", "皮疲"), ("pì", "屁"), ("piān", "篇偏"),
+    ("piàn", "片骗"), ("piāo", "飘"), ("piào", "票漂"), ("pīn", "拼"), ("pín", "频贫"),
+    ("pǐn", "品"), ("píng", "平评瓶凭屏苹"), ("pō", "坡泼"), ("pó", "婆"), ("pò", "破迫"),
+    ("pū", "扑铺"), ("pǔ", "普朴谱"), ("qī", "七期妻欺"), ("qí", "其奇齐骑旗棋"), ("qǐ", "起启企乞"),
+    ("qì", "气器汽弃"), ("qià", "恰"), ("qiān", "千签牵迁铅"), ("qián", "前钱潜"), ("qiǎn", "浅"),
+    ("qiàn", "欠"), ("qiāng", "枪腔"), ("qiáng", "强墙"), ("qiǎng", "抢"), ("qiāo", "敲"),
+    ("qiáo", "桥乔"), ("qiǎo", "巧"), ("qiē", "切"), ("qiě", "且"), ("qīn", "亲侵"), ("qín", "勤琴"),
+    ("qīng", "清青轻倾"), ("qíng", "情晴"), ("qǐng", "请"), ("qìng", "庆"), ("qióng", "穷"),
+    ("qiū", "秋丘"), ("qiú", "求球"), ("qū", "区曲趋驱屈"), ("qǔ", "取娶"), ("qù", "去趣"),
+    ("quān", "圈"), ("quán", "全权泉拳"), ("quǎn", "犬"), ("quàn", "劝券"), ("quē", "缺"),
+    ("què", "确却雀"), ("qún", "群裙"), ("rán", "然燃"), ("rǎn", "染"), ("ràng", "让"), ("rǎo", "扰"),
+    ("rào", "绕"), ("rè", "热"), ("rén", "人仁"), ("rěn", "忍"), ("rèn", "认任"), ("rēng", "扔"),
+    ("réng", "仍"), ("rì", "日"), ("róng", "容荣融绒"), ("ròu", "肉"), ("rú", "如"), ("rù", "入"),
+    ("ruǎn", "软"), ("ruì", "瑞锐"), ("ruò", "若弱"), ("sǎ", "洒撒"), ("sài", "赛"), ("sān", "三"),
+    ("sǎn", "伞散"), ("sāng", "桑"), ("sǎo", "扫嫂"), ("sè", "色"), ("sēn", "森"), ("shā", "沙杀"),
+    ("shǎ", "傻"), ("shài", "晒"), ("shān", "山删衫"), ("shǎn", "闪"), ("shàn", "善扇"),
+    ("shāng", "商伤"), ("shǎng", "赏"), ("shàng", "上尚"), ("shāo", "烧稍"), ("shǎo", "少"),
+    ("shào", "绍哨"), ("shé", "蛇舌"), ("shě", "舍"), ("shè", "设社射涉摄"), ("shéi", "谁"),
+    ("shēn", "身深申伸"), ("shén", "神什"), ("shěn", "审"), ("shèn", "甚慎"), ("shēng", "生声升"),
+    ("shéng", "绳"), ("shěng", "省"), ("shèng", "胜剩圣"), ("shī", "师失诗施狮湿"),
+    ("shí", "时十实识石食拾"), ("shǐ", "使始史"), ("shì", "是事市式世室视试示势适释饰士"),
+    ("shōu", "收"), ("shǒu", "手首守"), ("shòu", "受授售瘦寿"), ("shū", "书输叔舒疏"),
+    ("shú", "熟"), ("shǔ", "属鼠暑"), ("shù", "数树术束述"), ("shuā", "刷"), ("shuāi", "摔衰"),
+    ("shuài", "帅"), ("shuāng", "双霜"), ("shuǎng", "爽"), ("shuǐ", "水"), ("shuì", "睡税"),
+    ("shùn", "顺"), ("shuō", "说"), ("sī", "思司私丝斯"), ("sǐ", "死"), ("sì", "四似寺"),
+    ("sōng", "松"), ("sòng", "送宋"), ("sōu", "搜"), ("sù", "速素诉宿塑"), ("suān", "酸"),
+    ("suàn", "算"), ("suī", "虽"), ("suí", "随"), ("suì", "岁碎"), ("sūn", "孙"), ("sǔn", "损"),
+    ("suō", "缩"), ("suǒ", "所锁索"), ("tā", "他她它塌"), ("tǎ", "塔"), ("tài", "太态泰"),
+    ("tān", "摊贪"), ("tán", "谈弹坛"), ("tǎn", "坦"), ("tàn", "探叹炭"), ("tāng", "汤"),
+    ("táng", "糖堂唐"), ("tǎng", "躺"), ("tàng", "趟烫"), ("tāo", "涛掏"), ("táo", "逃桃陶"),
+    ("tǎo", "讨"), ("tào", "套"), ("tè", "特"), ("téng", "疼腾"), ("tī", "踢梯"), ("tí", "题提"),
+    ("tǐ", "体"), ("tì", "替"), ("tiān", "天添"), ("tián", "田甜填"), ("tiāo", "挑"), ("tiáo", "条"),
+    ("tiào", "跳"), ("tiē", "贴"), ("tiě", "铁"), ("tīng", "听厅"), ("tíng", "停庭亭"), ("tǐng", "挺"),
+    ("tōng", "通"), ("tóng", "同童铜"), ("tǒng", "统桶"), ("tòng", "痛"), ("tōu", "偷"), ("tóu", "头投"),
+    ("tū", "突"), ("tú", "图途"), ("tǔ", "土"), ("tù", "兔"), ("tuán", "团"), ("tuī", "推"),
+    ("tuǐ", "腿"), ("tuì", "退"), ("tuō", "脱托拖"), ("wā", "挖"), ("wà", "袜"), ("wài", "外"),
+    ("wān", "弯湾"), ("wán", "完玩顽"), ("wǎn", "晚碗"), ("wàn", "万"), ("wáng", "王亡"),
+    ("wǎng", "网往"), ("wàng", "望忘"), ("wēi", "微危威"), ("wéi", "为维围违唯"), ("wěi", "委伟尾伪"),
+    ("wèi", "位未味卫喂胃谓"), ("wēn", "温"), ("wén", "文闻"), ("wěn", "稳"), ("wèn", "问"),
+    ("wǒ", "我"), ("wò", "握卧"), ("wū", "屋污"), ("wú", "无吴"), ("wǔ", "五午武舞"),
+    ("wù", "物务误雾悟"), ("xī", "西希息吸析悉惜溪"), ("xí", "习席袭"), ("xǐ", "喜洗"),
+    ("xì", "系细戏"), ("xiā", "虾瞎"), ("xià", "下夏吓"), ("xiān", "先鲜仙"), ("xián", "闲嫌咸"),
+    ("xiǎn", "显险"), ("xiàn", "现线限县献宪"), ("xiāng", "相香乡箱"), ("xiáng", "详"),
+    ("xiǎng", "想响享"), ("xiàng", "向像项象"), ("xiāo", "消销"), ("xiǎo", "小晓"),
+    ("xiào", "笑校效"), ("xiē", "些"), ("xié", "鞋协斜"), ("xiě", "写"), ("xiè", "谢泄"),
+    ("xīn", "新心辛欣"), ("xìn", "信"), ("xīng", "星兴"), ("xíng", "行形型刑"), ("xǐng", "醒"),
+    ("xìng", "性姓幸"), ("xiōng", "兄胸凶"), ("xióng", "雄熊"), ("xiū", "修休"), ("xiù", "秀袖"),
+    ("xū", "需须虚"), ("xǔ", "许"), ("xù", "续序绪"), ("xuān", "宣"), ("xuǎn", "选"), ("xué", "学"),
+    ("xuě", "雪"), ("xuè", "血"), ("xún", "寻询循"), ("xùn", "训讯迅"), ("ya", "呀"), ("yā", "压鸭"),
+    ("yá", "牙"), ("yà", "亚"), ("yān", "烟"), ("yán", "言严研颜盐延"), ("yǎn", "眼演"),
+    ("yàn", "验宴燕"), ("yáng", "阳扬羊洋"), ("yǎng", "养"), ("yàng", "样"), ("yāo", "腰邀"),
+    ("yáo", "摇遥"), ("yǎo", "咬"), ("yào", "要药"), ("yé", "爷"), ("yě", "也野"), ("yè", "业夜页叶"),
+    ("yī", "一医衣依"), ("yí", "疑移仪宜遗"), ("yǐ", "已以椅乙"), ("yì", "意义议易益艺亿忆异译"),
+    ("yīn", "因音阴"), ("yín", "银"), ("yǐn", "引饮隐"), ("yìn", "印"), ("yīng", "应英"),
+    ("yíng", "营迎赢"), ("yǐng", "影"), ("yìng", "硬映"), ("yōng", "拥"), ("yǒng", "永勇泳"),
+    ("yòng", "用"), ("yōu", "优忧幽"), ("yóu", "由油游邮"), ("yǒu", "有友"), ("yòu", "又右幼"),
+    ("yú", "于鱼余愉"), ("yǔ", "语与雨"), ("yù", "预育遇域欲玉"), ("yuán", "元员原园圆源"),
+    ("yuǎn", "远"), ("yuàn", "院愿怨"), ("yuē", "约"), ("yuè", "月越阅跃"), ("yún", "云"),
+    ("yǔn", "允"), ("yùn", "运"), ("zá", "杂"), ("zāi", "灾"), ("zài", "在再载"), ("zán", "咱"),
+    ("zàn", "赞暂"), ("zāng", "脏"), ("zǎo", "早"), ("zào", "造"), ("zé", "则责择"), ("zěn", "怎"),
+    ("zēng", "增"), ("zhā", "扎"), ("zhǎ", "眨"), ("zhà", "炸"), ("zhāi", "摘"), ("zhǎi", "窄"),
+    ("zhàn", "站战占"), ("zhāng", "张章"), ("zhǎng", "涨掌"), ("zhàng", "丈账"), ("zhāo", "招"),
+    ("zhǎo", "找"), ("zhào", "照召"), ("zhé", "折哲"), ("zhè", "这"), ("zhe", "着"), ("zhēn", "真针珍"),
+    ("zhěn", "诊"), ("zhèn", "阵镇振"), ("zhēng", "争征睁"), ("zhěng", "整"), ("zhèng", "正政证"),
+    ("zhī", "之知支织汁"), ("zhí", "直值职植执"), ("zhǐ", "只指止纸址"), ("zhì", "制治质至志置致智"),
+    ("zhōng", "中钟终"), ("zhǒng", "种"), ("zhòng", "重众"), ("zhōu", "周州"), ("zhòu", "皱"),
+    ("zhū", "猪珠朱"), ("zhú", "竹逐"), ("zhǔ", "主煮嘱"), ("zhù", "住注助祝著"), ("zhuā", "抓"),
+    ("zhuān", "专砖"), ("zhuǎn", "转"), ("zhuàn", "赚"), ("zhuāng", "装庄"), ("zhuàng", "状撞"),
+    ("zhuī", "追"), ("zhǔn", "准"), ("zhuō", "桌捉"), ("zī", "资姿"), ("zǐ", "子紫"), ("zì", "自字"),
+    ("zōng", "宗综"), ("zǒng", "总"), ("zǒu", "走"), ("zū", "租"), ("zú", "足族"), ("zǔ", "组祖阻"),
+    ("zuǐ", "嘴"), ("zuì", "最醉罪"), ("zūn", "尊遵"), ("zuó", "昨"), ("zuǒ", "左"), ("zuò", "做作坐座"),
+];
+
+/// 多音字常见词组的读音 (空格分隔，与词组逐字对应)，优先于单字读音
+const PHRASE_READINGS: &[(&str, &str)] = &[
+    ("银行", "yín háng"), ("行业", "háng yè"), ("一行", "yī háng"),
+    ("长大", "zhǎng dà"), ("成长", "chéng zhǎng"), ("校长", "xiào zhǎng"), ("部长", "bù zhǎng"),
+    ("重新", "chóng xīn"), ("重复", "chóng fù"),
+    ("音乐", "yīn yuè"), ("乐器", "yuè qì"),
+    ("睡觉", "shuì jiào"), ("午觉", "wǔ jiào"),
+    ("觉得", "jué de"), ("得到", "dé dào"), ("获得", "huò dé"), ("值得", "zhí de"), ("记得", "jì de"),
+    ("得很", "de hěn"), ("必须得", "bì xū děi"),
+    ("地方", "dì fāng"), ("地球", "dì qiú"), ("地图", "dì tú"), ("当地", "dāng dì"), ("土地", "tǔ dì"),
+    ("目的", "mù dì"), ("的确", "dí què"),
+    ("还是", "hái shì"), ("还有", "hái yǒu"), ("归还", "guī huán"), ("还钱", "huán qián"),
+    ("只有", "zhǐ yǒu"), ("一只", "yī zhī"), ("两只", "liǎng zhī"),
+    ("为了", "wèi le"), ("因为", "yīn wèi"), ("为什么", "wèi shén me"),
+    ("了解", "liǎo jiě"), ("了不起", "liǎo bù qǐ"),
+    ("朝代", "cháo dài"), ("朝阳", "zhāo yáng"),
+    ("数据", "shù jù"), ("数学", "shù xué"), ("数字", "shù zì"),
+    ("调查", "diào chá"), ("调整", "tiáo zhěng"), ("空调", "kōng tiáo"), ("调试", "tiáo shì"),
+    ("好奇", "hào qí"), ("爱好", "ài hào"),
+    ("要求", "yāo qiú"), ("需要", "xū yào"), ("重要", "zhòng yào"), ("主要", "zhǔ yào"), ("要是", "yào shì"),
+    ("便宜", "pián yi"), ("方便", "fāng biàn"),
+    ("看守", "kān shǒu"), ("会计", "kuài jì"),
+    ("更新", "gēng xīn"), ("更加", "gèng jiā"), ("更好", "gèng hǎo"),
+    ("差不多", "chà bù duō"), ("出差", "chū chāi"), ("差点", "chà diǎn"),
+    ("处理", "chǔ lǐ"), ("到处", "dào chù"), ("好处", "hǎo chù"),
+    ("着急", "zháo jí"), ("睡着", "shuì zháo"),
+    ("干净", "gān jìng"), ("干活", "gàn huó"), ("能干", "néng gàn"),
+    ("相信", "xiāng xìn"), ("照相", "zhào xiàng"),
+    ("高兴", "gāo xìng"), ("兴趣", "xìng qù"),
+    ("教室", "jiào shì"), ("教育", "jiào yù"), ("教学", "jiào xué"),
+    ("发现", "fā xiàn"), ("头发", "tóu fa"), ("理发", "lǐ fà"),
+    ("参差", "cēn cī"), ("人参", "rén shēn"),
+    ("种类", "zhǒng lèi"), ("种植", "zhòng zhí"), ("种地", "zhòng dì"),
+    ("都市", "dū shì"), ("首都", "shǒu dū"),
+    ("大夫", "dài fu"), ("东西", "dōng xi"), ("什么", "shén me"), ("怎么", "zěn me"), ("这么", "zhè me"), ("那么", "nà me"),
+    ("一个", "yī gè"), ("哪里", "nǎ lǐ"),
+];
+
+/// 汉字 → 常用读音
+fn char_map() -> &'static HashMap<char, &'static str> {
+    static MAP: OnceLock<HashMap<char, &'static str>> = OnceLock::new();
+    MAP.get_or_init(|| {
+        let mut map = HashMap::new();
+        for &(reading, chars) in CHAR_READINGS {
+            for c in chars.chars() {
+                map.entry(c).or_insert(reading);
+            }
+        }
+        map
+    })
+}
+
+/// 词组字符与逐字读音
+type Phrase = (Vec<char>, Vec<&'static str>);
+
+/// 词组首字 → 词组列表，按词组长度降序，保证最长匹配
+fn phrase_map() -> &'static HashMap<char, Vec<Phrase>> {
+    static MAP: OnceLock<HashMap<char, Vec<Phrase>>> = OnceLock::new();
+    MAP.get_or_init(|| {
+        let mut map: HashMap<char, Vec<Phrase>> = HashMap::new();
+        for &(phrase, readings) in PHRASE_READINGS {
+            let chars: Vec<char> = phrase.chars().collect();
+            let readings: Vec<&str> = readings.split(' ').collect();
+            debug_assert_eq!(chars.len(), readings.len(), "{}", phrase);
+            map.entry(chars[0]).or_default().push((chars, readings));
+        }
+        for entries in map.values_mut() {
+            entries.sort_by_key(|(chars, _)| std::cmp::Reverse(chars.len()));
+        }
+        map
+    })
+}
+
+/// 逐字标注拼音，返回 (原文片段, 读音) 列表
+///
+/// 每个汉字一项；连续的非汉字字符 (英文、数字、标点、空白) 合为一项，读音即原文。
+/// 繁体字按对应简体查表，表外的汉字读音也为原字
+pub fn annotate_pinyin(text: &str) -> Vec<(String, String)> {
+    let chars: Vec<char> = text.chars().collect();
+    // 词组按简体匹配，繁体原文同样能命中
+    let simplified: Vec<char> = chars
+        .iter()
+        .map(|c| to_simplified_map().get(c).copied().unwrap_or(*c))
+        .collect();
+
+    let mut annotations: Vec<(String, String)> = Vec::with_capacity(chars.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if !is_cjk(c) {
+            match annotations.last_mut() {
+                Some((segment, reading)) if !segment.chars().last().is_some_and(is_cjk) => {
+                    segment.push(c);
+                    reading.push(c);
+                }
+                _ => annotations.push((c.to_string(), c.to_string())),
+            }
+            i += 1;
+            continue;
+        }
+
+        let phrase = phrase_map().get(&simplified[i]).and_then(|entries| {
+            entries
+                .iter()
+                .find(|(phrase, _)| simplified[i..].starts_with(phrase))
+        });
+        if let Some((phrase, readings)) = phrase {
+            for (offset, reading) in readings.iter().enumerate() {
+                annotations.push((chars[i + offset].to_string(), reading.to_string()));
+            }
+            i += phrase.len();
+            continue;
+        }
+
+        let reading = char_map()
+            .get(&simplified[i])
+            .map_or_else(|| c.to_string(), |reading| reading.to_string());
+        annotations.push((c.to_string(), reading));
+        i += 1;
+    }
+    annotations
+}
+
+/// 文本是否包含汉字 (决定是否需要附带注音)
+pub fn has_han(text: &str) -> bool {
+    text.chars().any(is_cjk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn readings(text: &str) -> Vec<String> {
+        annotate_pinyin(text).into_iter().map(|(_, reading)| reading).collect()
+    }
+
+    #[test]
+    fn test_annotate_pinyin() {
+        assert_eq!(
+            annotate_pinyin("你好"),
+            vec![("你".to_string(), "nǐ".to_string()), ("好".to_string(), "hǎo".to_string())]
+        );
+
+        // 多音字：单字取常用读音，常见词组按词组读音
+        assert_eq!(readings("我们去银行"), ["wǒ", "men", "qù", "yín", "háng"]);
+        assert_eq!(readings("行人"), ["xíng", "rén"]);
+        assert_eq!(readings("重新开始很重要"), ["chóng", "xīn", "kāi", "shǐ", "hěn", "zhòng", "yào"]);
+        assert_eq!(readings("他长大了"), ["tā", "zhǎng", "dà", "le"]);
+
+        // 非汉字连续片段合为一项并原样返回，繁体按简体读音标注
+        assert_eq!(
+            annotate_pinyin("用JSON 2 次。"),
+            vec![
+                ("用".to_string(), "yòng".to_string()),
+                ("JSON 2 ".to_string(), "JSON 2 ".to_string()),
+                ("次".to_string(), "cì".to_string()),
+                ("。".to_string(), "。".to_string()),
+            ]
+        );
+        assert_eq!(readings("銀行開會"), ["yín", "háng", "kāi", "huì"]);
+
+        // 表外汉字原样返回
+        assert_eq!(readings("龘"), ["龘"]);
+        assert!(annotate_pinyin("").is_empty());
+        assert!(has_han("abc中") && !has_han("abc 123"));
+    }
+
+    #[test]
+    fn test_phrase_readings_align() {
+        for (phrase, readings) in PHRASE_READINGS {
+            assert_eq!(phrase.chars().count(), readings.split(' ').count(), "{}", phrase);
+        }
+    }
+}
@@ -0,0 +1,111 @@
+// HTTP 转录进度估计
+// 长音频分段转录时按已完成段数/总段数计算进度；单个请求内无法得知服务端进度，
+// 按已耗时与预计耗时 (与音频时长成正比) 估计，估计值逐渐逼近但不会到达该段结束
+// 上报经 ProgressReporter 过滤，保证百分比单调不回退 (如主引擎重试时重新计时)
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 进度回调：(已完成分段拼接的文本, 百分比 0-100)
+pub type ProgressCallback = Arc<dyn Fn(&str, u8) + Send + Sync>;
+
+/// 上报进度的最短音频时长 (毫秒)，更短的录音很快完成，不值得发送进度
+pub const PROGRESS_MIN_AUDIO_MS: u64 = 10_000;
+
+/// 请求进行中按时间估计上报的间隔
+const TICK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 预计服务端处理耗时与音频时长之比
+const EXPECTED_REALTIME_FACTOR: f64 = 0.25;
+
+/// 单个请求的最短预计耗时 (毫秒)
+const MIN_EXPECTED_MS: f64 = 2000.0;
+
+/// 时间估计的完成比例上限，剩余部分留给请求实际完成
+const MAX_ESTIMATED_FRACTION: f64 = 0.95;
+
+/// 单调进度上报：只在百分比上升时回调
+pub struct ProgressReporter {
+    callback: ProgressCallback,
+    last: AtomicU8,
+}
+
+impl ProgressReporter {
+    pub fn new(callback: ProgressCallback) -> Self {
+        Self { callback, last: AtomicU8::new(0) }
+    }
+
+    /// 上报进度，低于或等于已上报值时忽略
+    pub fn report(&self, text: &str, percent: f64) {
+        let percent = percent.clamp(0.0, 100.0) as u8;
+        if self.last.fetch_max(percent, Ordering::SeqCst) < percent {
+            (self.callback)(text, percent);
+        }
+    }
+
+    /// 等待一个分段请求完成，期间按时间估计上报进度
+    ///
+    /// `done`/`total` 为已完成段数与总段数，`text` 为已完成分段的文本，`segment_ms` 为该段音频时长
+    pub async fn track<T>(
+        &self,
+        text: &str,
+        done: usize,
+        total: usize,
+        segment_ms: u64,
+        request: impl Future<Output = T>,
+    ) -> T {
+        tokio::pin!(request);
+        let start = Instant::now();
+        let mut ticker = tokio::time::interval(TICK_INTERVAL);
+        loop {
+            tokio::select! {
+                result = &mut request => return result,
+                _ = ticker.tick() => {
+                    let fraction = estimate_fraction(start.elapsed(), segment_ms);
+                    self.report(text, (done as f64 + fraction) / total as f64 * 100.0);
+                }
+            }
+        }
+    }
+}
+
+/// 按耗时估计单个请求的完成比例：以 1 - e^(-t/预计耗时) 逼近 MAX_ESTIMATED_FRACTION
+pub fn estimate_fraction(elapsed: Duration, audio_ms: u64) -> f64 {
+    let expected_ms = (audio_ms as f64 * EXPECTED_REALTIME_FACTOR).max(MIN_EXPECTED_MS);
+    let fraction = 1.0 - (-(elapsed.as_millis() as f64) / expected_ms).exp();
+    fraction.min(MAX_ESTIMATED_FRACTION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_reporter_is_monotonic() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reported);
+        let reporter = ProgressReporter::new(Arc::new(move |text: &str, percent| {
+            sink.lock().unwrap().push((text.to_string(), percent));
+        }));
+
+        reporter.report("", 30.0);
+        // 重试重新计时，估计值回落时不上报
+        reporter.report("", 12.5);
+        reporter.report("", 30.4);
+        reporter.report("第一段", 50.0);
+        reporter.report("第一段", 130.0);
+        assert_eq!(
+            *reported.lock().unwrap(),
+            vec![("".to_string(), 30), ("第一段".to_string(), 50), ("第一段".to_string(), 100)]
+        );
+
+        // 估计值随时间增长，且不超过上限
+        let a = estimate_fraction(Duration::from_secs(1), 60_000);
+        let b = estimate_fraction(Duration::from_secs(10), 60_000);
+        assert!(0.0 < a && a < b && b < MAX_ESTIMATED_FRACTION);
+        assert_eq!(estimate_fraction(Duration::from_secs(3600), 60_000), MAX_ESTIMATED_FRACTION);
+    }
+}
@@ -0,0 +1,342 @@
+// 标点恢复模块
+// 为无标点的转录文本插入标点，只插入标点、不改动原词
+
+use async_trait::async_trait;
+use std::time::Duration;
+
+use crate::voice::asr::ASRError;
+use crate::voice::config::{ASRConfig, PunctuationMode};
+
+/// 标点字符集合 (用于校验与判断)
+const PUNCTUATION: &[char] = &[
+    '。', '，', '！', '？', '、', '；', '：', '“', '”', '‘', '’',
+    '.', ',', '!', '?', ';', ':', '"', '\'',
+    '（', '）', '(', ')', '【', '】', '[', ']', '《', '》', '—', '…', '·',
+];
+
+/// 句末疑问语气词
+const QUESTION_PARTICLES: &[char] = &['吗', '呢', '么'];
+
+/// 常见的分句连词 (前面插入逗号)
+const CLAUSE_CONJUNCTIONS: &[&str] = &["但是", "所以", "然后", "因为", "而且", "不过", "如果"];
+
+/// 插入逗号前要求的最短分句长度 (字符数)
+const MIN_CLAUSE_CHARS: usize = 6;
+
+/// LLM 标点恢复提示词
+const LLM_SYSTEM_PROMPT: &str = "你是标点恢复助手。为用户给出的语音转录文本添加合适的标点符号。\
+只能插入标点，不得增删或修改任何文字，不要输出解释，直接输出结果。";
+
+// ============================================================================
+// Punctuator Trait
+// ============================================================================
+
+/// 标点恢复器
+#[async_trait]
+pub trait Punctuator: Send + Sync {
+    /// 名称 (用于日志)
+    fn name(&self) -> &str;
+
+    /// 为文本插入标点
+    async fn punctuate(&self, text: &str) -> Result<String, ASRError>;
+}
+
+/// 判断字符是否为标点
+pub fn is_punctuation(c: char) -> bool {
+    PUNCTUATION.contains(&c)
+}
+
+/// 校验候选文本相对原文只插入了标点 (忽略空白)
+pub fn only_inserts_punctuation(original: &str, candidate: &str) -> bool {
+    let strip = |s: &str| -> String {
+        s.chars()
+            .filter(|c| !c.is_whitespace() && !is_punctuation(*c))
+            .collect()
+    };
+    strip(original) == strip(candidate)
+}
+
+// ============================================================================
+// 规则实现
+// ============================================================================
+
+/// 基于简单规则的标点恢复
+///
+/// - 中文字符之间的空白替换为逗号
+/// - 分句连词前插入逗号 (分句足够长时)
+/// - 句末补全句号，疑问语气词结尾补问号
+/// - 已包含句中标点的文本仅补全句末标点
+#[derive(Debug, Default, Clone)]
+pub struct RulePunctuator;
+
+impl RulePunctuator {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 同步执行规则标点恢复
+    pub fn apply(&self, text: &str) -> String {
+        let text = text.trim();
+        if text.is_empty() {
+            return String::new();
+        }
+
+        let has_inner_punctuation = text.chars().any(is_punctuation);
+        let mut output = if has_inner_punctuation {
+            text.to_string()
+        } else {
+            insert_clause_commas(text)
+        };
+
+        if let Some(last) = output.chars().last() {
+            if !is_punctuation(last) {
+                let is_cjk_text = output.chars().any(is_cjk);
+                let mark = match (is_cjk_text, QUESTION_PARTICLES.contains(&last)) {
+                    (true, true) => '？',
+                    (true, false) => '。',
+                    (false, _) => '.',
+                };
+                output.push(mark);
+            }
+        }
+
+        output
+    }
+}
+
+#[async_trait]
+impl Punctuator for RulePunctuator {
+    fn name(&self) -> &str {
+        "rule"
+    }
+
+    async fn punctuate(&self, text: &str) -> Result<String, ASRError> {
+        Ok(self.apply(text))
+    }
+}
+
+/// 判断是否为中日韩统一表意文字
+pub fn is_cjk(c: char) -> bool {
+    ('\u{4e00}'..='\u{9fff}').contains(&c) || ('\u{3400}'..='\u{4dbf}').contains(&c)
+}
+
+/// 在空白分隔处与分句连词前插入逗号
+fn insert_clause_commas(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::with_capacity(text.len() + 8);
+    let mut clause_len = 0usize;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            // 合并连续空白
+            let start = i;
+            while i < chars.len() && chars[i].is_whitespace() {
+                i += 1;
+            }
+            let prev_cjk = start > 0 && is_cjk(chars[start - 1]);
+            let next_cjk = i < chars.len() && is_cjk(chars[i]);
+            if prev_cjk && next_cjk {
+                output.push('，');
+                clause_len = 0;
+            } else {
+                output.extend(&chars[start..i]);
+            }
+            continue;
+        }
+
+        if clause_len >= MIN_CLAUSE_CHARS && starts_with_conjunction(&chars[i..]) {
+            output.push('，');
+            clause_len = 0;
+        }
+
+        output.push(c);
+        clause_len += 1;
+        i += 1;
+    }
+
+    output
+}
+
+fn starts_with_conjunction(chars: &[char]) -> bool {
+    CLAUSE_CONJUNCTIONS.iter().any(|conj| {
+        let conj: Vec<char> = conj.chars().collect();
+        chars.len() >= conj.len() && chars[..conj.len()] == conj[..]
+    })
+}
+
+// ============================================================================
+// LLM 实现
+// ============================================================================
+
+/// 调用 LLM (OpenAI Chat Completions 兼容接口) 恢复标点
+///
+/// LLM 输出若改动了原词，则退回规则实现的结果
+pub struct LlmPunctuator {
+    client: reqwest::Client,
+    endpoint: String,
+    api_key: String,
+    model: String,
+    fallback: RulePunctuator,
+}
+
+impl LlmPunctuator {
+    pub fn new(endpoint: String, api_key: String, model: String, timeout_ms: u64) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(timeout_ms))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            client,
+            endpoint,
+            api_key,
+            model,
+            fallback: RulePunctuator::new(),
+        }
+    }
+
+    async fn request(&self, text: &str) -> Result<String, ASRError> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "temperature": 0,
+            "messages": [
+                {"role": "system", "content": LLM_SYSTEM_PROMPT},
+                {"role": "user", "content": text},
+            ],
+        });
+
+        let response = self.client
+            .post(&self.endpoint)
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| ASRError::network_with("标点恢复请求失败", e))?;
+
+        let status = response.status();
+        if status.as_u16() == 401 || status.as_u16() == 403 {
+            return Err(ASRError::AuthFailed {
+                engine: "punctuator".to_string(),
+                message: format!("HTTP {}", status),
+            });
+        }
+        if !status.is_success() {
+            return Err(ASRError::network(format!("标点恢复请求失败: HTTP {}", status)));
+        }
+
+        let json: serde_json::Value = response.json().await
+            .map_err(|e| ASRError::InternalError(format!("解析标点恢复响应失败: {}", e)))?;
+
+        json["choices"][0]["message"]["content"]
+            .as_str()
+            .map(|s| s.trim().to_string())
+            .ok_or_else(|| ASRError::InternalError("标点恢复响应缺少 content".to_string()))
+    }
+}
+
+#[async_trait]
+impl Punctuator for LlmPunctuator {
+    fn name(&self) -> &str {
+        "llm"
+    }
+
+    async fn punctuate(&self, text: &str) -> Result<String, ASRError> {
+        if text.trim().is_empty() {
+            return Ok(String::new());
+        }
+
+        match self.request(text).await {
+            Ok(candidate) if only_inserts_punctuation(text, &candidate) => Ok(candidate),
+            Ok(_) => {
+                eprintln!("[WARN] [Punctuator] LLM 输出改动了原词，退回规则标点");
+                Ok(self.fallback.apply(text))
+            }
+            Err(e) => {
+                eprintln!("[WARN] [Punctuator] LLM 标点恢复失败，退回规则标点: {}", e);
+                Ok(self.fallback.apply(text))
+            }
+        }
+    }
+}
+
+// ============================================================================
+// 工厂
+// ============================================================================
+
+/// LLM 标点恢复请求超时 (毫秒)
+const LLM_TIMEOUT_MS: u64 = 5000;
+
+/// 按配置创建标点恢复器，未启用时返回 None
+pub fn create_punctuator(config: &ASRConfig) -> Option<Box<dyn Punctuator>> {
+    match config.punctuation {
+        PunctuationMode::None => None,
+        PunctuationMode::Rule => Some(Box::new(RulePunctuator::new())),
+        PunctuationMode::Llm => match config.punctuation_llm {
+            Some(ref llm) => Some(Box::new(LlmPunctuator::new(
+                llm.endpoint.clone(),
+                llm.api_key.clone(),
+                llm.model.clone(),
+                LLM_TIMEOUT_MS,
+            ))),
+            None => {
+                eprintln!("[WARN] [Punctuator] 未配置 punctuation_llm，使用规则标点");
+                Some(Box::new(RulePunctuator::new()))
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_sentence_end() {
+        let p = RulePunctuator::new();
+        assert_eq!(p.apply("今天天气很好"), "今天天气很好。");
+        assert_eq!(p.apply("你吃饭了吗"), "你吃饭了吗？");
+        assert_eq!(p.apply("hello world"), "hello world.");
+        assert_eq!(p.apply(""), "");
+    }
+
+    #[test]
+    fn test_rule_whitespace_to_comma() {
+        let p = RulePunctuator::new();
+        assert_eq!(p.apply("我们明天出发  大家准备一下"), "我们明天出发，大家准备一下。");
+        // 英文单词间空白保持不变
+        assert_eq!(p.apply("打开 VS Code"), "打开 VS Code。");
+    }
+
+    #[test]
+    fn test_rule_conjunction_comma() {
+        let p = RulePunctuator::new();
+        assert_eq!(p.apply("我本来想去公园但是下雨了"), "我本来想去公园，但是下雨了。");
+        // 分句过短时不插入
+        assert_eq!(p.apply("我想但是不行"), "我想但是不行。");
+    }
+
+    #[test]
+    fn test_rule_keeps_existing_punctuation() {
+        let p = RulePunctuator::new();
+        assert_eq!(p.apply("好的，我知道了"), "好的，我知道了。");
+        assert_eq!(p.apply("好的，我知道了！"), "好的，我知道了！");
+    }
+
+    #[test]
+    fn test_rule_only_inserts_punctuation() {
+        let p = RulePunctuator::new();
+        for text in ["我本来想去公园但是下雨了", "我们 明天 出发", "你好吗", "mixed 中文 text"] {
+            assert!(only_inserts_punctuation(text, &p.apply(text)), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_only_inserts_punctuation_detects_changes() {
+        assert!(only_inserts_punctuation("你好世界", "你好，世界。"));
+        assert!(!only_inserts_punctuation("你好世界", "您好，世界。"));
+        assert!(!only_inserts_punctuation("你好世界", "你好世界啊。"));
+    }
+}
@@ -3,12 +3,11 @@
 
 use async_trait::async_trait;
 use base64::{Engine as _, engine::general_purpose};
-use flate2::{write::GzEncoder, read::GzDecoder, Compression};
 use futures_util::{SinkExt, StreamExt, stream::SplitSink};
-use std::io::{Write, Read};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{Mutex, mpsc, oneshot};
+use tokio::task::JoinHandle;
 use tokio::net::TcpStream;
 use tokio_tungstenite::{
     connect_async, 
@@ -17,9 +16,15 @@ use tokio_tungstenite::{
     WebSocketStream
 };
 
-use crate::voice::asr::{ASREngine, ASRError, ASRMode, RealtimeSession, RetryConfig};
+use crate::voice::asr::{ASREngine, AudioRequirements, ASRError, ASRMode, PartialResultCallback, RealtimeSession, RetryConfig};
+use crate::voice::asr::volcengine::{
+    decode_header, encode_frame, gzip_decompress, CompressionType, MessageType, ProtocolHeader, Serialization,
+};
 use crate::voice::audio::AudioData;
 
+/// 会话内共享的部分结果回调
+type SharedPartialCallback = Arc<std::sync::Mutex<Option<PartialResultCallback>>>;
+
 const WEBSOCKET_URL: &str = "wss://openspeech.bytedance.com/api/v3/sauc/bigmodel_nostream";
 const RESOURCE_ID: &str = "volc.seedasr.sauc.duration";
 const TRANSCRIPTION_TIMEOUT_SECS: u64 = 10;
@@ -53,6 +58,10 @@ impl ASREngine for DoubaoRealtimeEngine {
         vec![ASRMode::Realtime]
     }
     
+    fn audio_requirements(&self) -> AudioRequirements {
+        AudioRequirements::pcm16_mono(16000)
+    }
+    
     async fn transcribe(&self, _audio: &AudioData) -> Result<String, ASRError> {
         Err(ASRError::UnsupportedOperation(
             "DoubaoRealtimeEngine 不支持 HTTP 模式，请使用 DoubaoHttpEngine 或创建 Realtime 会话".to_string()
@@ -77,7 +86,9 @@ enum SessionCommand {
 pub struct DoubaoRealtimeSession {
     cmd_sender: mpsc::Sender<SessionCommand>,
     result_receiver: Option<oneshot::Receiver<Result<String, ASRError>>>,
-    partial_callback: Option<Arc<Mutex<Box<dyn Fn(&str) + Send + 'static>>>>,
+    partial_callback: SharedPartialCallback,
+    /// WebSocket 读写任务 (中止时据此关闭底层连接)
+    io_tasks: Vec<JoinHandle<()>>,
 }
 
 impl DoubaoRealtimeSession {
@@ -99,10 +110,10 @@ impl DoubaoRealtimeSession {
             .header("X-Api-Resource-Id", RESOURCE_ID)
             .header("X-Api-Connect-Id", &request_id)
             .body(())
-            .map_err(|e| ASRError::WebSocketError(format!("构建请求失败: {}", e)))?;
+            .map_err(|e| ASRError::websocket_with("构建请求失败", e))?;
         
         let (ws_stream, _) = connect_async(request).await
-            .map_err(|e| ASRError::WebSocketError(format!("WebSocket 连接失败: {}", e)))?;
+            .map_err(|e| ASRError::websocket_with("WebSocket 连接失败", e))?;
         
         eprintln!("[INFO] 豆包 Realtime WebSocket 连接成功");
         
@@ -120,7 +131,7 @@ impl DoubaoRealtimeSession {
             .map_err(|e| ASRError::InternalError(format!("序列化配置失败: {}", e)))?, 0x1)?;
         
         write.send(Message::Binary(msg.clone().into())).await
-            .map_err(|e| ASRError::WebSocketError(format!("发送 Full Client Request 失败: {}", e)))?;
+            .map_err(|e| ASRError::websocket_with("发送 Full Client Request 失败", e))?;
         
         eprintln!("[DEBUG] 豆包 Full Client Request 已发送: {} bytes", msg.len());
         
@@ -143,9 +154,7 @@ impl DoubaoRealtimeSession {
                     eprintln!("[WARN] 豆包 Full Client Request 收到非二进制响应: {:?}", other);
                 }
                 Err(e) => {
-                    return Err(ASRError::WebSocketError(format!(
-                        "豆包 Full Client Request 响应错误: {}", e
-                    )));
+                    return Err(ASRError::websocket_with("豆包 Full Client Request 响应错误", e));
                 }
             }
         }
@@ -157,7 +166,7 @@ impl DoubaoRealtimeSession {
         let write: Arc<Mutex<WsSink>> = Arc::new(Mutex::new(write));
         let write_clone = Arc::clone(&write);
         
-        tokio::spawn(async move {
+        let writer_task = tokio::spawn(async move {
             let mut sequence = 1i32;
             
             while let Some(cmd) = cmd_rx.recv().await {
@@ -200,7 +209,7 @@ impl DoubaoRealtimeSession {
         });
         
         let partial_tx_clone = partial_tx.clone();
-        tokio::spawn(async move {
+        let reader_task = tokio::spawn(async move {
             let mut accumulated_text = String::new();
             let mut result_tx = Some(result_tx);
             
@@ -239,9 +248,7 @@ impl DoubaoRealtimeSession {
                         } else {
                             eprintln!("[WARN] 豆包连接关闭，无转录结果");
                             if let Some(tx) = result_tx.take() {
-                                let _ = tx.send(Err(ASRError::WebSocketError(
-                                    "WebSocket 连接被关闭".to_string()
-                                )));
+                                let _ = tx.send(Err(ASRError::websocket("WebSocket 连接被关闭")));
                             }
                         }
                         break;
@@ -252,9 +259,7 @@ impl DoubaoRealtimeSession {
                     Err(e) => {
                         eprintln!("[ERROR] 豆包 WebSocket 接收错误: {}", e);
                         if let Some(tx) = result_tx.take() {
-                            let _ = tx.send(Err(ASRError::WebSocketError(
-                                format!("WebSocket 错误: {}", e)
-                            )));
+                            let _ = tx.send(Err(ASRError::websocket_with("读取消息失败", e)));
                         }
                         break;
                     }
@@ -270,28 +275,28 @@ impl DoubaoRealtimeSession {
                 } else {
                     eprintln!("[WARN] 豆包连接结束，无转录结果");
                     if let Some(tx) = result_tx.take() {
-                        let _ = tx.send(Err(ASRError::WebSocketError(
-                            "WebSocket 连接结束，无转录结果".to_string()
-                        )));
+                        let _ = tx.send(Err(ASRError::websocket("WebSocket 连接结束，无转录结果")));
                     }
                 }
             }
             eprintln!("[DEBUG] 豆包 WebSocket 接收任务结束");
         });
         
-        let partial_callback: Option<Arc<Mutex<Box<dyn Fn(&str) + Send + 'static>>>> = None;
-        let partial_callback_clone = partial_callback.clone();
+        let partial_callback: SharedPartialCallback = Arc::new(std::sync::Mutex::new(None));
+        let partial_callback_clone = Arc::clone(&partial_callback);
         tokio::spawn(async move {
             while let Some(text) = partial_rx.recv().await {
-                if let Some(ref callback) = partial_callback_clone {
-                    let cb = callback.lock().await;
-                    cb(&text);
+                if let Ok(slot) = partial_callback_clone.lock() {
+                    if let Some(ref cb) = *slot {
+                        cb(&text);
+                    }
                 }
             }
         });
         
         Ok(Self {
             cmd_sender: cmd_tx,
+            io_tasks: vec![writer_task, reader_task],
             result_receiver: Some(result_rx),
             partial_callback,
         })
@@ -302,7 +307,7 @@ impl DoubaoRealtimeSession {
 impl RealtimeSession for DoubaoRealtimeSession {
     async fn send_chunk(&mut self, chunk: &[u8]) -> Result<(), ASRError> {
         self.cmd_sender.send(SessionCommand::SendAudio(chunk.to_vec())).await
-            .map_err(|_| ASRError::WebSocketError("发送音频块失败：通道已关闭".to_string()))
+            .map_err(|_| ASRError::websocket("发送音频块失败：通道已关闭"))
     }
     
     async fn close(&mut self) -> Result<String, ASRError> {
@@ -321,8 +326,17 @@ impl RealtimeSession for DoubaoRealtimeSession {
         result
     }
     
+    fn abort(&mut self) {
+        for task in self.io_tasks.drain(..) {
+            task.abort();
+        }
+    }
+    
     fn set_partial_callback(&mut self, callback: Box<dyn Fn(&str) + Send + 'static>) {
-        self.partial_callback = Some(Arc::new(Mutex::new(callback)));
+        // 回调槽在连接时已交给部分结果转发任务，这里只需填充
+        if let Ok(mut slot) = self.partial_callback.lock() {
+            *slot = Some(callback);
+        }
     }
 }
 
@@ -351,47 +365,28 @@ fn build_message(
     payload: &[u8],
     compression_type: u8,
 ) -> Result<Vec<u8>, ASRError> {
-    let final_payload = if compression_type == 0x1 {
-        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
-        encoder.write_all(payload)
-            .map_err(|e| ASRError::InternalError(format!("Gzip 压缩失败: {}", e)))?;
-        encoder.finish()
-            .map_err(|e| ASRError::InternalError(format!("Gzip 完成失败: {}", e)))?
+    let message_type = if msg_type == 0x1 {
+        MessageType::FullClientRequest
     } else {
-        payload.to_vec()
+        MessageType::AudioOnlyRequest
     };
+    let serialization = if msg_type == 0x1 { Serialization::Json } else { Serialization::None };
+    let compression = if compression_type == 0x1 { CompressionType::Gzip } else { CompressionType::None };
     
-    let serialization = if msg_type == 0x1 { 0x1 } else { 0x0 };
-    
-    let mut msg = vec![
-        0x11,
-        (msg_type << 4) | flags,
-        (serialization << 4) | compression_type,
-        0x00,
-    ];
-    msg.extend_from_slice(&sequence.to_be_bytes());
-    msg.extend_from_slice(&(final_payload.len() as u32).to_be_bytes());
-    msg.extend_from_slice(&final_payload);
-    
-    Ok(msg)
+    let header = ProtocolHeader::new(message_type, flags, serialization, compression);
+    encode_frame(&header, Some(sequence), payload)
 }
 
 fn parse_response(data: &[u8]) -> Result<(String, bool), ASRError> {
-    if data.len() < 4 {
-        return Err(ASRError::InternalError(format!("响应太短: {} bytes", data.len())));
-    }
-    
-    let header_size = (data[0] & 0x0f) as usize * 4;
-    let message_type = data[1] >> 4;
-    let message_flags = data[1] & 0x0f;
-    let compression = data[2] & 0x0f;
+    let (header, header_size) = decode_header(data)?;
+    let message_flags = header.flags;
     
     eprintln!(
-        "[DEBUG] 豆包响应 header: size={}, type={:#x}, flags={:#x}, compression={}",
-        header_size, message_type, message_flags, compression
+        "[DEBUG] 豆包响应 header: size={}, type={:?}, flags={:#x}, compression={:?}",
+        header_size, header.message_type, message_flags, header.compression
     );
     
-    if message_type == 0xf {
+    if header.message_type == MessageType::ServerError {
         let error_code = if data.len() >= header_size + 4 {
             u32::from_be_bytes([
                 data[header_size],
@@ -402,7 +397,7 @@ fn parse_response(data: &[u8]) -> Result<(String, bool), ASRError> {
         } else {
             0
         };
-        return Err(ASRError::WebSocketError(format!("服务器返回错误: code={}", error_code)));
+        return Err(ASRError::websocket(format!("服务器返回错误: code={}", error_code)));
     }
     
     let mut offset = header_size;
@@ -441,12 +436,9 @@ fn parse_response(data: &[u8]) -> Result<(String, bool), ASRError> {
     }
     
     let payload_data = &data[offset..offset + payload_size];
-    let json_str = if compression == 0x1 {
-        let mut decoder = GzDecoder::new(payload_data);
-        let mut s = String::new();
-        decoder.read_to_string(&mut s)
-            .map_err(|e| ASRError::InternalError(format!("Gzip 解压失败: {}", e)))?;
-        s
+    let json_str = if header.compression == CompressionType::Gzip {
+        String::from_utf8(gzip_decompress(payload_data)?)
+            .map_err(|e| ASRError::InternalError(format!("UTF-8 解码失败: {}", e)))?
     } else {
         String::from_utf8(payload_data.to_vec())
             .map_err(|e| ASRError::InternalError(format!("UTF-8 解码失败: {}", e)))?
@@ -7,6 +7,7 @@ use futures_util::{SinkExt, StreamExt, stream::SplitSink};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{Mutex, mpsc, oneshot};
+use tokio::task::JoinHandle;
 use tokio::net::TcpStream;
 use tokio_tungstenite::{
     connect_async, 
@@ -15,12 +16,17 @@ use tokio_tungstenite::{
     WebSocketStream
 };
 
-use crate::voice::asr::{ASREngine, ASRError, ASRMode, RealtimeSession, RetryConfig};
+use crate::voice::asr::{ASREngine, AudioRequirements, ASRError, ASRMode, PartialResultCallback, RealtimeSession, RetryConfig};
 use crate::voice::audio::AudioData;
 
+/// 会话内共享的部分结果回调
+type SharedPartialCallback = Arc<std::sync::Mutex<Option<PartialResultCallback>>>;
+
 const WEBSOCKET_URL: &str = "wss://dashscope.aliyuncs.com/api-ws/v1/realtime";
 const DEFAULT_MODEL: &str = "qwen3-asr-flash-realtime";
 const TRANSCRIPTION_TIMEOUT_SECS: u64 = 10;
+/// 默认识别语言
+const DEFAULT_LANGUAGE: &str = "zh";
 
 type WsSink = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
 
@@ -56,6 +62,10 @@ impl ASREngine for QwenRealtimeEngine {
         vec![ASRMode::Realtime]
     }
     
+    fn audio_requirements(&self) -> AudioRequirements {
+        AudioRequirements::pcm16_mono(16000)
+    }
+    
     async fn transcribe(&self, _audio: &AudioData) -> Result<String, ASRError> {
         Err(ASRError::UnsupportedOperation(
             "QwenRealtimeEngine 不支持 HTTP 模式，请使用 QwenHttpEngine 或创建 Realtime 会话".to_string()
@@ -63,31 +73,56 @@ impl ASREngine for QwenRealtimeEngine {
     }
     
     async fn create_realtime_session(&self) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        self.create_realtime_session_with_language(DEFAULT_LANGUAGE).await
+    }
+    
+    async fn create_realtime_session_with_language(&self, language: &str) -> Result<Box<dyn RealtimeSession>, ASRError> {
         let session = QwenRealtimeSession::connect(
             self.api_key.clone(),
             self.model.clone(),
+            language,
         ).await?;
         
         Ok(Box::new(session))
     }
 }
 
+/// 构造 session.update 事件 (连接时配置会话，运行中用于切换识别语言)
+fn session_update_event(language: &str) -> serde_json::Value {
+    serde_json::json!({
+        "event_id": format!("event_{}", timestamp_ms()),
+        "type": "session.update",
+        "session": {
+            "modalities": ["text"],
+            "input_audio_format": "pcm",
+            "sample_rate": 16000,
+            "input_audio_transcription": {
+                "language": language
+            },
+            "turn_detection": serde_json::Value::Null
+        }
+    })
+}
+
 enum SessionCommand {
     SendAudio(Vec<u8>),
     Commit,
+    SetLanguage(String),
     Close,
 }
 
 pub struct QwenRealtimeSession {
     cmd_sender: mpsc::Sender<SessionCommand>,
     result_receiver: Option<oneshot::Receiver<Result<String, ASRError>>>,
-    partial_callback: Option<Arc<Mutex<Box<dyn Fn(&str) + Send + 'static>>>>,
+    partial_callback: SharedPartialCallback,
     #[allow(dead_code)]
     partial_sender: mpsc::Sender<String>,
+    /// WebSocket 读写任务 (中止时据此关闭底层连接)
+    io_tasks: Vec<JoinHandle<()>>,
 }
 
 impl QwenRealtimeSession {
-    async fn connect(api_key: String, model: String) -> Result<Self, ASRError> {
+    async fn connect(api_key: String, model: String, language: &str) -> Result<Self, ASRError> {
         let url = format!("{}?model={}", WEBSOCKET_URL, model);
         eprintln!("[INFO] 创建 Qwen Realtime WebSocket 连接: {}", url);
         
@@ -101,31 +136,19 @@ impl QwenRealtimeSession {
             .header("Sec-WebSocket-Version", "13")
             .header("Sec-WebSocket-Key", generate_websocket_key())
             .body(())
-            .map_err(|e| ASRError::WebSocketError(format!("构建请求失败: {}", e)))?;
+            .map_err(|e| ASRError::websocket_with("构建请求失败", e))?;
         
         let (ws_stream, _) = connect_async(request).await
-            .map_err(|e| ASRError::WebSocketError(format!("WebSocket 连接失败: {}", e)))?;
+            .map_err(|e| ASRError::websocket_with("WebSocket 连接失败", e))?;
         
         eprintln!("[INFO] Qwen Realtime WebSocket 连接成功");
         
         let (mut write, mut read) = ws_stream.split();
         
-        let session_update = serde_json::json!({
-            "event_id": format!("event_{}", timestamp_ms()),
-            "type": "session.update",
-            "session": {
-                "modalities": ["text"],
-                "input_audio_format": "pcm",
-                "sample_rate": 16000,
-                "input_audio_transcription": {
-                    "language": "zh"
-                },
-                "turn_detection": serde_json::Value::Null
-            }
-        });
+        let session_update = session_update_event(language);
         
         write.send(Message::Text(session_update.to_string().into())).await
-            .map_err(|e| ASRError::WebSocketError(format!("发送 session.update 失败: {}", e)))?;
+            .map_err(|e| ASRError::websocket_with("发送 session.update 失败", e))?;
         
         eprintln!("[INFO] 已发送 session.update 配置");
         
@@ -136,7 +159,7 @@ impl QwenRealtimeSession {
         let write: Arc<Mutex<WsSink>> = Arc::new(Mutex::new(write));
         let write_clone = Arc::clone(&write);
         
-        tokio::spawn(async move {
+        let writer_task = tokio::spawn(async move {
             while let Some(cmd) = cmd_rx.recv().await {
                 match cmd {
                     SessionCommand::SendAudio(pcm_bytes) => {
@@ -165,6 +188,16 @@ impl QwenRealtimeSession {
                         }
                         eprintln!("[INFO] 已发送 input_audio_buffer.commit");
                     }
+                    SessionCommand::SetLanguage(language) => {
+                        // 已发送的音频仍按原语言识别，之后的音频使用新语言
+                        let event = session_update_event(&language);
+                        let mut w = write_clone.lock().await;
+                        if let Err(e) = w.send(Message::Text(event.to_string().into())).await {
+                            eprintln!("[ERROR] 发送 session.update 失败: {}", e);
+                            break;
+                        }
+                        eprintln!("[INFO] 已切换识别语言: {}", language);
+                    }
                     SessionCommand::Close => {
                         let mut w = write_clone.lock().await;
                         let _ = w.close().await;
@@ -175,7 +208,7 @@ impl QwenRealtimeSession {
         });
         
         let partial_tx_clone = partial_tx.clone();
-        tokio::spawn(async move {
+        let reader_task = tokio::spawn(async move {
             let mut final_text = String::new();
             let mut has_result = false;
             let mut result_tx = Some(result_tx);
@@ -223,7 +256,7 @@ impl QwenRealtimeSession {
                                             .unwrap_or("未知错误");
                                         eprintln!("[ERROR] API 错误: {}", error_msg);
                                         if let Some(tx) = result_tx.take() {
-                                            let _ = tx.send(Err(ASRError::WebSocketError(
+                                            let _ = tx.send(Err(ASRError::websocket(
                                                 format!("API 错误: {}", error_msg)
                                             )));
                                         }
@@ -244,9 +277,7 @@ impl QwenRealtimeSession {
                     Err(e) => {
                         eprintln!("[ERROR] WebSocket 错误: {}", e);
                         if let Some(tx) = result_tx.take() {
-                            let _ = tx.send(Err(ASRError::WebSocketError(
-                                format!("WebSocket 错误: {}", e)
-                            )));
+                            let _ = tx.send(Err(ASRError::websocket_with("读取消息失败", e)));
                         }
                         return;
                     }
@@ -269,19 +300,21 @@ impl QwenRealtimeSession {
             }
         });
         
-        let partial_callback: Option<Arc<Mutex<Box<dyn Fn(&str) + Send + 'static>>>> = None;
-        let partial_callback_clone = partial_callback.clone();
+        let partial_callback: SharedPartialCallback = Arc::new(std::sync::Mutex::new(None));
+        let partial_callback_clone = Arc::clone(&partial_callback);
         tokio::spawn(async move {
             while let Some(text) = partial_rx.recv().await {
-                if let Some(ref callback) = partial_callback_clone {
-                    let cb = callback.lock().await;
-                    cb(&text);
+                if let Ok(slot) = partial_callback_clone.lock() {
+                    if let Some(ref cb) = *slot {
+                        cb(&text);
+                    }
                 }
             }
         });
         
         Ok(Self {
             cmd_sender: cmd_tx,
+            io_tasks: vec![writer_task, reader_task],
             result_receiver: Some(result_rx),
             partial_callback,
             partial_sender: partial_tx,
@@ -293,12 +326,18 @@ impl QwenRealtimeSession {
 impl RealtimeSession for QwenRealtimeSession {
     async fn send_chunk(&mut self, chunk: &[u8]) -> Result<(), ASRError> {
         self.cmd_sender.send(SessionCommand::SendAudio(chunk.to_vec())).await
-            .map_err(|_| ASRError::WebSocketError("发送音频块失败：通道已关闭".to_string()))
+            .map_err(|_| ASRError::websocket("发送音频块失败：通道已关闭"))
     }
     
     async fn commit(&mut self) -> Result<(), ASRError> {
         self.cmd_sender.send(SessionCommand::Commit).await
-            .map_err(|_| ASRError::WebSocketError("提交音频失败：通道已关闭".to_string()))
+            .map_err(|_| ASRError::websocket("提交音频失败：通道已关闭"))
+    }
+    
+    async fn set_language(&mut self, language: &str) -> Result<bool, ASRError> {
+        self.cmd_sender.send(SessionCommand::SetLanguage(language.to_string())).await
+            .map_err(|_| ASRError::websocket("切换语言失败：通道已关闭"))?;
+        Ok(true)
     }
     
     async fn close(&mut self) -> Result<String, ASRError> {
@@ -319,8 +358,17 @@ impl RealtimeSession for QwenRealtimeSession {
         result
     }
     
+    fn abort(&mut self) {
+        for task in self.io_tasks.drain(..) {
+            task.abort();
+        }
+    }
+    
     fn set_partial_callback(&mut self, callback: Box<dyn Fn(&str) + Send + 'static>) {
-        self.partial_callback = Some(Arc::new(Mutex::new(callback)));
+        // 回调槽在连接时已交给部分结果转发任务，这里只需填充
+        if let Ok(mut slot) = self.partial_callback.lock() {
+            *slot = Some(callback);
+        }
     }
 }
 
@@ -2,11 +2,13 @@
 // 协调 StreamingRecorder 和 RealtimeSession，实现边录边转录
 
 use std::sync::Arc;
-use tokio::sync::{mpsc, Mutex, oneshot};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
 
-use crate::voice::asr::{ASRError, TranscriptionResult, create_engine};
+use crate::voice::asr::{ASREngine, ASRError, AlternativesCallback, RealtimeSession, RetryConfig, TranscriptionResult, create_engine};
 use crate::voice::audio::streaming::AudioChunkData;
-use crate::voice::config::ASRProviderConfig;
+use crate::voice::config::{ASRProviderConfig, AdaptiveModeConfig, KeepaliveConfig};
 
 macro_rules! log_info {
     ($($arg:tt)*) => {
@@ -36,12 +38,19 @@ macro_rules! log_error {
 #[derive(Debug)]
 pub enum RealtimeTaskResult {
     Success(TranscriptionResult),
+    /// 等待最终结果超时，返回已累积的部分文本
+    Partial {
+        result: TranscriptionResult,
+        timeout_ms: u64,
+    },
     Failed {
         error: ASRError,
         engine_name: String,
         chunks_sent: u64,
         samples_sent: u64,
     },
+    /// 自适应模式下录音在有声时长达到阈值前结束，未建立实时会话 (应改用 HTTP 转录整段录音)
+    ShortAudio,
 }
 
 impl RealtimeTaskResult {
@@ -52,7 +61,9 @@ impl RealtimeTaskResult {
     pub fn into_result(self) -> Result<TranscriptionResult, ASRError> {
         match self {
             RealtimeTaskResult::Success(result) => Ok(result),
+            RealtimeTaskResult::Partial { result, .. } => Ok(result),
             RealtimeTaskResult::Failed { error, .. } => Err(error),
+            RealtimeTaskResult::ShortAudio => Err(ASRError::UnsupportedOperation("短录音未建立实时会话".to_string())),
         }
     }
     
@@ -67,35 +78,223 @@ impl RealtimeTaskResult {
 /// 部分结果回调类型
 pub type PartialResultCallback = Box<dyn Fn(&str) + Send + 'static>;
 
+/// 静音持续多久视为分句边界 (重建会话以切换语言的时机)
+const SEGMENT_SILENCE_MS: u64 = 300;
+/// 低于该 RMS (归一化) 的音频块视为静音
+const SILENCE_RMS: f32 = 0.01;
+/// 静音保活帧的时长 (毫秒)
+const KEEPALIVE_FRAME_MS: u64 = 100;
+
 /// 实时转录任务
 pub struct RealtimeTranscriptionTask {
     asr_config: ASRProviderConfig,
+    /// 预先创建的引擎实例 (为空时按配置创建)
+    engine: Option<Arc<dyn ASREngine>>,
     chunk_receiver: mpsc::Receiver<AudioChunkData>,
-    partial_callback: Arc<Mutex<Option<PartialResultCallback>>>,
+    partial_callback: Arc<std::sync::Mutex<Option<AlternativesCallback>>>,
     stop_receiver: Option<oneshot::Receiver<()>>,
+    retry_config: RetryConfig,
+    /// 取消令牌 (连接关闭或录音取消时触发，立即中止会话)
+    cancel_token: CancellationToken,
+    /// 运行中切换识别语言的请求
+    language_receiver: Option<mpsc::UnboundedReceiver<String>>,
+    /// 停顿期间的保活间隔 (为空时不保活)
+    keepalive_interval: Option<Duration>,
+    /// 自适应模式下建立会话所需的有声时长 (为空时立即建立会话)
+    realtime_after_ms: Option<u64>,
+}
+
+/// 自适应模式缓冲阶段的结果
+#[derive(Debug, PartialEq, Eq)]
+enum HoldOutcome {
+    /// 有声时长达到阈值
+    Speech,
+    /// 录音在达到阈值前结束
+    Stopped,
+    Cancelled,
+}
+
+/// 分句跟踪：按有声块之间的停顿切分录音，记录每个分句结束时的累计文本，
+/// 会话结束后据此把最终文本拆成带起止时间的分句 (拼装文稿时按停顿分段、换行)
+#[derive(Debug, Default)]
+struct SegmentTracker {
+    /// 当前分句的起点与最近一个有声块的终点 (录音时间，毫秒)
+    open: Option<(u64, u64)>,
+    /// 已结束分句的起止时间及下一分句开始时的累计文本
+    closed: Vec<(u64, u64, String)>,
+}
+
+impl SegmentTracker {
+    /// 记录一个有声块；与上个有声块相隔不短于 SEGMENT_SILENCE_MS 时以当前累计文本结束上一分句
+    ///
+    /// 按块时间戳计算停顿，噪声门丢弃的静音块同样计入停顿
+    fn voiced(&mut self, chunk: &AudioChunkData, current_text: impl FnOnce() -> String) {
+        let end = chunk.timestamp_ms;
+        let start = end.saturating_sub(chunk.samples.len() as u64 * 1000 / 16000);
+        self.open = match self.open {
+            Some((open_start, last_end)) if start.saturating_sub(last_end) >= SEGMENT_SILENCE_MS => {
+                self.closed.push((open_start, last_end, current_text()));
+                Some((start, end))
+            }
+            Some((open_start, _)) => Some((open_start, end)),
+            None => Some((start, end)),
+        };
+    }
+
+    /// 按记录的分句拆分最终文本
+    ///
+    /// 只有一个分句、或累计文本与最终文本对不上 (引擎修订了先前的结果) 时返回空，由调用方按整段处理
+    fn split(&self, final_text: &str, engine: &str) -> Vec<TranscriptionResult> {
+        let Some((open_start, open_end)) = self.open else {
+            return Vec::new();
+        };
+        if self.closed.is_empty() {
+            return Vec::new();
+        }
+        let segment = |text: &str, start_ms, end_ms| {
+            TranscriptionResult::new(text.to_string(), engine.to_string(), false, 0).with_span(start_ms, end_ms)
+        };
+        let mut segments = Vec::new();
+        let mut offset = 0;
+        for (start_ms, end_ms, text) in &self.closed {
+            let Some(cut) = aligned_prefix_len(final_text, text) else {
+                log_debug!("分句文本与最终结果不一致，按整段拼装文稿");
+                return Vec::new();
+            };
+            let cut = cut.max(offset);
+            let text = final_text[offset..cut].trim();
+            if !text.is_empty() {
+                segments.push(segment(text, *start_ms, *end_ms));
+            }
+            offset = cut;
+        }
+        let rest = final_text[offset..].trim();
+        if !rest.is_empty() {
+            segments.push(segment(rest, open_start, open_end));
+        }
+        segments
+    }
+}
+
+/// 按各分句的文字 (忽略标点、空白与大小写) 依次切分 `text`，分句后的标点归入该句，最后一句取剩余全部文本
+///
+/// 用于把整段后处理 (如 LLM 标点) 的结果分回各分句；某句对不上时返回 None
+pub fn split_like_segments<'a>(text: &'a str, segments: &[&str]) -> Option<Vec<&'a str>> {
+    let (_, init) = segments.split_last()?;
+    let mut parts = Vec::with_capacity(segments.len());
+    let mut offset = 0;
+    for segment in init {
+        let cut = offset + aligned_prefix_len(&text[offset..], segment)?;
+        parts.push(text[offset..cut].trim());
+        offset = cut;
+    }
+    parts.push(text[offset..].trim());
+    Some(parts)
+}
+
+/// `prefix` 的文字 (忽略标点、空白与大小写) 在 `text` 中结束处的字节位置，随后的标点归入前缀；
+/// `text` 不以这些文字开头时返回 None
+fn aligned_prefix_len(text: &str, prefix: &str) -> Option<usize> {
+    let mut wanted = prefix.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).peekable();
+    if wanted.peek().is_none() {
+        return Some(0);
+    }
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        if !c.is_alphanumeric() {
+            continue;
+        }
+        if !c.to_lowercase().all(|lower| wanted.next() == Some(lower)) {
+            return None;
+        }
+        if wanted.peek().is_none() {
+            let mut end = index + c.len_utf8();
+            while let Some(&(index, c)) = chars.peek() {
+                if !(super::punctuator::is_punctuation(c) || c.is_whitespace()) {
+                    break;
+                }
+                end = index + c.len_utf8();
+                chars.next();
+            }
+            return Some(end);
+        }
+    }
+    None
+}
+
+/// 关闭会话的结果
+#[derive(Debug)]
+enum CloseOutcome {
+    Final(String),
+    /// 超时，携带已累积的部分文本
+    TimedOut(String),
 }
 
 impl RealtimeTranscriptionTask {
     pub fn new(
         asr_config: ASRProviderConfig,
         chunk_receiver: mpsc::Receiver<AudioChunkData>,
-        partial_callback: Option<PartialResultCallback>,
+        partial_callback: Option<AlternativesCallback>,
     ) -> (Self, oneshot::Sender<()>) {
         let (stop_tx, stop_rx) = oneshot::channel();
         
         let task = Self {
             asr_config,
+            engine: None,
             chunk_receiver,
-            partial_callback: Arc::new(Mutex::new(partial_callback)),
+            partial_callback: Arc::new(std::sync::Mutex::new(partial_callback)),
             stop_receiver: Some(stop_rx),
+            retry_config: RetryConfig::default(),
+            cancel_token: CancellationToken::new(),
+            language_receiver: None,
+            keepalive_interval: None,
+            realtime_after_ms: None,
         };
         
         (task, stop_tx)
     }
     
+    /// 使用已创建的引擎实例 (如连接专属引擎)
+    pub fn with_engine(mut self, engine: Arc<dyn ASREngine>) -> Self {
+        self.engine = Some(engine);
+        self
+    }
+    
+    /// 设置重试配置 (timeout_ms 用作等待最终结果的超时)
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+    
+    /// 设置取消令牌
+    pub fn with_cancel_token(mut self, cancel_token: CancellationToken) -> Self {
+        self.cancel_token = cancel_token;
+        self
+    }
+    
+    /// 设置切换识别语言的请求通道
+    pub fn with_language_receiver(mut self, language_receiver: mpsc::UnboundedReceiver<String>) -> Self {
+        self.language_receiver = Some(language_receiver);
+        self
+    }
+    
+    /// 设置停顿期间的保活 (噪声门丢弃静音块时会话可能长时间收不到音频)
+    pub fn with_keepalive(mut self, config: KeepaliveConfig) -> Self {
+        self.keepalive_interval = (config.enabled && config.interval_ms > 0)
+            .then(|| Duration::from_millis(config.interval_ms));
+        self
+    }
+    
+    /// 设置自适应模式：有声时长超过阈值才建立实时会话，之前的录音缓冲后补发
+    pub fn with_adaptive_mode(mut self, config: AdaptiveModeConfig) -> Self {
+        self.realtime_after_ms = config.enabled.then_some(config.realtime_after_ms);
+        self
+    }
+    
     pub async fn run(self) -> Result<TranscriptionResult, ASRError> {
         match self.run_with_details().await {
             RealtimeTaskResult::Success(result) => Ok(result),
+            RealtimeTaskResult::Partial { result, .. } => Ok(result),
             RealtimeTaskResult::Failed { error, engine_name, chunks_sent, samples_sent } => {
                 log_error!(
                     "实时转录失败: 引擎={}, 已发送块={}, 样本={}, 错误={}",
@@ -103,6 +302,7 @@ impl RealtimeTranscriptionTask {
                 );
                 Err(error)
             }
+            RealtimeTaskResult::ShortAudio => Err(ASRError::UnsupportedOperation("短录音未建立实时会话".to_string())),
         }
     }
     
@@ -118,26 +318,75 @@ impl RealtimeTranscriptionTask {
             self.asr_config.mode
         );
         
-        let engine = match create_engine(&self.asr_config) {
-            Ok(e) => e,
-            Err(e) => {
-                log_error!("创建 ASR 引擎失败: {}", e);
-                return RealtimeTaskResult::Failed {
-                    error: e,
-                    engine_name,
-                    chunks_sent: 0,
-                    samples_sent: 0,
-                };
-            }
+        let engine: Arc<dyn ASREngine> = match self.engine.take() {
+            Some(e) => e,
+            None => match create_engine(&self.asr_config) {
+                Ok(e) => e.into(),
+                Err(e) => {
+                    log_error!("创建 ASR 引擎失败: {}", e);
+                    return RealtimeTaskResult::Failed {
+                        error: e,
+                        engine_name,
+                        chunks_sent: 0,
+                        samples_sent: 0,
+                    };
+                }
+            },
         };
         engine_name = engine.name().to_string();
         
         log_debug!("创建 ASR 引擎: {}", engine_name);
         
-        let mut session = match engine.create_realtime_session().await {
+        // 尚未送入会话的录音块
+        let mut backlog: Vec<AudioChunkData> = Vec::new();
+        if let Some(threshold_ms) = self.realtime_after_ms {
+            match self.hold_until_speech(threshold_ms, &mut backlog).await {
+                HoldOutcome::Speech => {
+                    log_info!("有声时长超过 {}ms，切换到实时流式 (已缓冲 {} 块)", threshold_ms, backlog.len());
+                }
+                HoldOutcome::Stopped => {
+                    log_info!("录音在 {}ms 有声时长前结束，不建立实时会话", threshold_ms);
+                    return RealtimeTaskResult::ShortAudio;
+                }
+                HoldOutcome::Cancelled => {
+                    return RealtimeTaskResult::Failed {
+                        error: ASRError::Cancelled,
+                        engine_name,
+                        chunks_sent: 0,
+                        samples_sent: 0,
+                    };
+                }
+            }
+        }
+        
+        // 建立会话期间继续接收录音块，避免录音端通道写满丢块
+        let cancel_token = self.cancel_token.clone();
+        let create = engine.create_realtime_session();
+        tokio::pin!(create);
+        let mut receiving = true;
+        let created = loop {
+            tokio::select! {
+                created = &mut create => break created,
+                _ = cancel_token.cancelled() => break Err(ASRError::Cancelled),
+                chunk = self.chunk_receiver.recv(), if receiving => match chunk {
+                    Some(chunk) => backlog.push(chunk),
+                    None => receiving = false,
+                },
+            }
+        };
+        let mut session = match created {
             Ok(s) => s,
+            Err(ASRError::Cancelled) => {
+                log_info!("任务已取消，放弃创建实时会话");
+                return RealtimeTaskResult::Failed {
+                    error: ASRError::Cancelled,
+                    engine_name,
+                    chunks_sent: 0,
+                    samples_sent: 0,
+                };
+            }
             Err(e) => {
-                log_error!("创建实时会话失败 (WebSocket 连接失败): {}", e);
+                log_error!("创建实时会话失败 (WebSocket 连接失败): {}", e.detailed());
                 return RealtimeTaskResult::Failed {
                     error: e,
                     engine_name,
@@ -149,23 +398,53 @@ impl RealtimeTranscriptionTask {
         
         log_info!("实时会话已创建");
         
-        let partial_callback = Arc::clone(&self.partial_callback);
-        session.set_partial_callback(Box::new(move |text| {
-            let text_owned = text.to_string();
-            let callback = partial_callback.clone();
-            tokio::spawn(async move {
-                if let Some(ref cb) = *callback.lock().await {
-                    cb(&text_owned);
-                }
-            });
-        }));
+        // 当前会话最新的部分结果 (超时时作为兜底文本)
+        let latest_partial: Arc<std::sync::Mutex<String>> = Arc::new(std::sync::Mutex::new(String::new()));
+        // 切换语言重建会话前，已关闭会话的文本
+        let committed_text: Arc<std::sync::Mutex<String>> = Arc::new(std::sync::Mutex::new(String::new()));
+        self.bind_partial_callback(session.as_mut(), &latest_partial, &committed_text);
+        let current_text = || {
+            let committed = committed_text.lock().map(|t| t.clone()).unwrap_or_default();
+            join_committed(&committed, &latest_partial.lock().map(|t| t.clone()).unwrap_or_default())
+        };
+        let mut segments = SegmentTracker::default();
         
         let mut stop_rx = self.stop_receiver.take();
         let mut consecutive_send_failures = 0u32;
         const MAX_CONSECUTIVE_FAILURES: u32 = 5;
+        let timeout_ms = self.retry_config.timeout_ms;
+        // 等待在分句边界重建会话的目标语言，以及当前连续静音时长
+        let mut pending_language: Option<String> = None;
+        let mut silence_ms = 0u64;
+        // 最近一次向会话发送数据的时间，超过保活间隔仍无音频时发送保活
+        let mut last_sent = tokio::time::Instant::now();
+        let mut keepalive_count = 0u64;
+        
+        // 先按顺序补发缓冲的录音，再处理停止信号，保证切换前的音频不丢失
+        for audio_chunk in backlog.drain(..) {
+            if !is_silent(&audio_chunk.samples) {
+                segments.voiced(&audio_chunk, current_text);
+            }
+            chunk_count += 1;
+            total_samples += audio_chunk.samples.len() as u64;
+            if let Err(e) = session.send_chunk(&samples_to_bytes(&audio_chunk.samples)).await {
+                log_warn!("补发缓冲音频块失败: {}", e);
+            }
+        }
         
         loop {
             tokio::select! {
+                _ = cancel_token.cancelled() => {
+                    log_info!("任务已取消，中止实时会话");
+                    session.abort();
+                    return RealtimeTaskResult::Failed {
+                        error: ASRError::Cancelled,
+                        engine_name,
+                        chunks_sent: chunk_count,
+                        samples_sent: total_samples,
+                    };
+                }
+                
                 _ = async {
                     if let Some(ref mut rx) = stop_rx {
                         rx.await.ok()
@@ -177,11 +456,42 @@ impl RealtimeTranscriptionTask {
                     break;
                 }
                 
+                language = next_language(&mut self.language_receiver) => {
+                    match session.set_language(&language).await {
+                        Ok(true) => {
+                            log_info!("已切换识别语言: {}", language);
+                            pending_language = None;
+                        }
+                        Ok(false) => {
+                            log_info!("会话不支持中途切换语言，将在下一分句边界重建会话: {}", language);
+                            pending_language = Some(language);
+                        }
+                        Err(e) => {
+                            log_warn!("切换识别语言失败: {}", e);
+                        }
+                    }
+                }
+                
+                _ = keepalive_due(self.keepalive_interval, last_sent) => {
+                    last_sent = tokio::time::Instant::now();
+                    keepalive_count += 1;
+                    if let Err(e) = send_keepalive(session.as_mut()).await {
+                        log_warn!("发送保活失败: {}", e);
+                    }
+                }
+                
                 chunk = self.chunk_receiver.recv() => {
                     match chunk {
                         Some(audio_chunk) => {
+                            last_sent = tokio::time::Instant::now();
                             chunk_count += 1;
                             total_samples += audio_chunk.samples.len() as u64;
+                            if is_silent(&audio_chunk.samples) {
+                                silence_ms += audio_chunk.samples.len() as u64 * 1000 / 16000;
+                            } else {
+                                silence_ms = 0;
+                                segments.voiced(&audio_chunk, current_text);
+                            }
                             
                             let pcm_bytes = samples_to_bytes(&audio_chunk.samples);
                             
@@ -201,10 +511,10 @@ impl RealtimeTranscriptionTask {
                                     if consecutive_send_failures >= MAX_CONSECUTIVE_FAILURES {
                                         log_error!("连续发送失败次数过多，中止任务");
                                         return RealtimeTaskResult::Failed {
-                                            error: ASRError::WebSocketError(format!(
-                                                "连续 {} 次发送失败: {}",
-                                                consecutive_send_failures, e
-                                            )),
+                                            error: ASRError::websocket_with(
+                                                format!("连续 {} 次发送失败", consecutive_send_failures),
+                                                e,
+                                            ),
                                             engine_name,
                                             chunks_sent: chunk_count,
                                             samples_sent: total_samples,
@@ -213,7 +523,7 @@ impl RealtimeTranscriptionTask {
                                 }
                             }
                             
-                            if chunk_count % 10 == 0 {
+                            if chunk_count.is_multiple_of(10) {
                                 log_debug!(
                                     "已发送 {} 个音频块，共 {} 样本",
                                     chunk_count,
@@ -228,18 +538,91 @@ impl RealtimeTranscriptionTask {
                     }
                 }
             }
+            
+            // 静音处视为分句边界：关闭当前会话保留其文本，按新语言建立会话
+            if silence_ms >= SEGMENT_SILENCE_MS {
+                if let Some(language) = pending_language.take() {
+                    let created = tokio::select! {
+                        created = engine.create_realtime_session_with_language(&language) => created,
+                        _ = cancel_token.cancelled() => continue,
+                    };
+                    match created {
+                        Ok(mut new_session) => {
+                            let closed = close_with_timeout(session.as_mut(), timeout_ms, &latest_partial).await;
+                            let text = match closed {
+                                Ok(CloseOutcome::Final(text)) | Ok(CloseOutcome::TimedOut(text)) => text,
+                                Err(e) => {
+                                    log_warn!("关闭旧会话失败，保留已有部分结果: {}", e);
+                                    session.abort();
+                                    latest_partial.lock().map(|t| t.clone()).unwrap_or_default()
+                                }
+                            };
+                            if let Ok(mut committed) = committed_text.lock() {
+                                super::join_segment_text(&mut committed, text.trim());
+                            }
+                            if let Ok(mut latest) = latest_partial.lock() {
+                                latest.clear();
+                            }
+                            self.bind_partial_callback(new_session.as_mut(), &latest_partial, &committed_text);
+                            session = new_session;
+                            last_sent = tokio::time::Instant::now();
+                            log_info!("已按语言 {} 重建实时会话", language);
+                        }
+                        Err(e) => {
+                            log_warn!("重建实时会话失败，继续使用原语言: {}", e);
+                        }
+                    }
+                }
+            }
         }
         
         log_info!(
-            "共发送 {} 个音频块，{} 样本，约 {:.1} 秒",
+            "共发送 {} 个音频块，{} 样本，约 {:.1} 秒，保活 {} 次",
             chunk_count,
             total_samples,
-            total_samples as f64 / 16000.0
+            total_samples as f64 / 16000.0,
+            keepalive_count
         );
         
         log_info!("关闭 ASR 会话，等待最终结果...");
-        let final_text = match session.close().await {
-            Ok(text) => text,
+        let closed = tokio::select! {
+            closed = close_with_timeout(session.as_mut(), timeout_ms, &latest_partial) => closed,
+            _ = cancel_token.cancelled() => Err(ASRError::Cancelled),
+        };
+        if matches!(closed, Err(ASRError::Cancelled)) {
+            session.abort();
+        }
+        // 拼上切换语言前已关闭会话的文本
+        let committed = committed_text.lock().map(|t| t.clone()).unwrap_or_default();
+        let closed = closed.map(|outcome| match outcome {
+            CloseOutcome::Final(text) => CloseOutcome::Final(join_committed(&committed, &text)),
+            CloseOutcome::TimedOut(text) => CloseOutcome::TimedOut(join_committed(&committed, &text)),
+        });
+        let final_text = match closed {
+            Ok(CloseOutcome::Final(text)) => text,
+            Ok(CloseOutcome::TimedOut(text)) if text.is_empty() => {
+                log_error!("等待最终结果超时 ({}ms)，且没有部分结果", timeout_ms);
+                return RealtimeTaskResult::Failed {
+                    error: ASRError::Timeout { timeout_ms },
+                    engine_name,
+                    chunks_sent: chunk_count,
+                    samples_sent: total_samples,
+                };
+            }
+            Ok(CloseOutcome::TimedOut(text)) => {
+                log_warn!("等待最终结果超时 ({}ms)，使用已累积的部分结果", timeout_ms);
+                let segments = segments.split(&text, &engine_name);
+                return RealtimeTaskResult::Partial {
+                    result: TranscriptionResult::new(
+                        text,
+                        engine_name,
+                        false,
+                        start_time.elapsed().as_millis() as u64,
+                    )
+                    .with_segments(segments),
+                    timeout_ms,
+                };
+            }
             Err(e) => {
                 log_error!("关闭会话失败: {}", e);
                 return RealtimeTaskResult::Failed {
@@ -263,12 +646,132 @@ impl RealtimeTranscriptionTask {
             }
         );
         
-        RealtimeTaskResult::Success(TranscriptionResult::new(
-            final_text,
-            engine_name,
-            false,
-            duration_ms,
-        ))
+        let segments = segments.split(&final_text, &engine_name);
+        RealtimeTaskResult::Success(
+            TranscriptionResult::new(final_text, engine_name, false, duration_ms).with_segments(segments),
+        )
+    }
+}
+
+impl RealtimeTranscriptionTask {
+    /// 缓冲录音块，直到有声 (非静音) 时长达到阈值、录音结束或任务取消
+    async fn hold_until_speech(&mut self, threshold_ms: u64, backlog: &mut Vec<AudioChunkData>) -> HoldOutcome {
+        let mut voiced_ms = 0u64;
+        let stop_rx = &mut self.stop_receiver;
+        loop {
+            tokio::select! {
+                _ = self.cancel_token.cancelled() => return HoldOutcome::Cancelled,
+                _ = async {
+                    match stop_rx.as_mut() {
+                        Some(rx) => rx.await.ok(),
+                        None => std::future::pending::<Option<()>>().await,
+                    }
+                } => return HoldOutcome::Stopped,
+                chunk = self.chunk_receiver.recv() => match chunk {
+                    Some(chunk) => {
+                        if !is_silent(&chunk.samples) {
+                            voiced_ms += chunk.samples.len() as u64 * 1000 / 16000;
+                        }
+                        backlog.push(chunk);
+                        if voiced_ms >= threshold_ms {
+                            return HoldOutcome::Speech;
+                        }
+                    }
+                    None => return HoldOutcome::Stopped,
+                },
+            }
+        }
+    }
+    
+    /// 为会话设置部分结果回调，转发时拼上已关闭会话的文本
+    fn bind_partial_callback(
+        &self,
+        session: &mut dyn RealtimeSession,
+        latest_partial: &Arc<std::sync::Mutex<String>>,
+        committed_text: &Arc<std::sync::Mutex<String>>,
+    ) {
+        let latest_partial = Arc::clone(latest_partial);
+        let committed_text = Arc::clone(committed_text);
+        let partial_callback = Arc::clone(&self.partial_callback);
+        let callback_token = self.cancel_token.clone();
+        session.set_alternatives_callback(Box::new(move |text, alternatives| {
+            if let Ok(mut latest) = latest_partial.lock() {
+                *latest = text.to_string();
+            }
+            if callback_token.is_cancelled() {
+                return;
+            }
+            let committed = committed_text.lock().map(|t| t.clone()).unwrap_or_default();
+            let text_owned = join_committed(&committed, text);
+            let alternatives: Vec<String> = alternatives.iter()
+                .map(|alternative| join_committed(&committed, alternative))
+                .collect();
+            // 在会话回调中同步调用，保证部分结果按引擎返回的顺序送出
+            if let Ok(callback) = partial_callback.lock() {
+                if let Some(ref cb) = *callback {
+                    cb(&text_owned, &alternatives);
+                }
+            }
+        }));
+    }
+}
+
+/// 等待下一个切换语言请求，通道关闭或未设置时永不返回
+async fn next_language(receiver: &mut Option<mpsc::UnboundedReceiver<String>>) -> String {
+    if let Some(rx) = receiver {
+        if let Some(language) = rx.recv().await {
+            return language;
+        }
+        *receiver = None;
+    }
+    std::future::pending().await
+}
+
+/// 等到距上次发送满保活间隔，未启用保活时永不返回
+async fn keepalive_due(interval: Option<Duration>, last_sent: tokio::time::Instant) {
+    match interval {
+        Some(interval) => tokio::time::sleep_until(last_sent + interval).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// 发送保活：优先使用引擎专用的保活消息，没有时发送一帧静音 PCM
+async fn send_keepalive(session: &mut dyn RealtimeSession) -> Result<(), ASRError> {
+    if session.keep_alive().await? {
+        return Ok(());
+    }
+    let silence = vec![0u8; (16000 * KEEPALIVE_FRAME_MS / 1000) as usize * 2];
+    session.send_chunk(&silence).await
+}
+
+/// 拼接已关闭会话的文本与当前会话文本
+fn join_committed(committed: &str, text: &str) -> String {
+    let mut joined = committed.to_string();
+    super::join_segment_text(&mut joined, text.trim());
+    joined
+}
+
+fn is_silent(samples: &[i16]) -> bool {
+    if samples.is_empty() {
+        return true;
+    }
+    let sum: f64 = samples.iter().map(|&s| (s as f64 / 32768.0).powi(2)).sum();
+    ((sum / samples.len() as f64).sqrt() as f32) < SILENCE_RMS
+}
+
+/// 带超时关闭会话，超时后中止会话以释放底层连接
+async fn close_with_timeout(
+    session: &mut dyn RealtimeSession,
+    timeout_ms: u64,
+    latest_partial: &std::sync::Mutex<String>,
+) -> Result<CloseOutcome, ASRError> {
+    match tokio::time::timeout(Duration::from_millis(timeout_ms), session.close()).await {
+        Ok(result) => result.map(CloseOutcome::Final),
+        Err(_) => {
+            session.abort();
+            let partial = latest_partial.lock().map(|t| t.clone()).unwrap_or_default();
+            Ok(CloseOutcome::TimedOut(partial))
+        }
     }
 }
 
@@ -279,3 +782,355 @@ fn samples_to_bytes(samples: &[i16]) -> Vec<u8> {
     }
     bytes
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voice::asr::fake::FakeEngine;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// close() 永不返回的会话
+    struct HangingSession {
+        aborted: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl RealtimeSession for HangingSession {
+        async fn send_chunk(&mut self, _chunk: &[u8]) -> Result<(), ASRError> {
+            Ok(())
+        }
+
+        async fn close(&mut self) -> Result<String, ASRError> {
+            std::future::pending().await
+        }
+
+        fn abort(&mut self) {
+            self.aborted.store(true, Ordering::SeqCst);
+        }
+
+        fn set_partial_callback(&mut self, _callback: Box<dyn Fn(&str) + Send + 'static>) {}
+    }
+
+    #[tokio::test]
+    async fn test_close_timeout_returns_partial_and_aborts() {
+        let aborted = Arc::new(AtomicBool::new(false));
+        let mut session = HangingSession { aborted: Arc::clone(&aborted) };
+        let latest = std::sync::Mutex::new("部分结果".to_string());
+
+        let outcome = close_with_timeout(&mut session, 20, &latest).await.unwrap();
+
+        assert!(matches!(outcome, CloseOutcome::TimedOut(ref text) if text == "部分结果"));
+        assert!(aborted.load(Ordering::SeqCst));
+    }
+
+    /// 创建 HangingSession 的引擎
+    fn hanging_engine(aborted: &Arc<AtomicBool>) -> Arc<FakeEngine> {
+        let aborted = Arc::clone(aborted);
+        Arc::new(FakeEngine::realtime("hanging", move || Box::new(HangingSession { aborted: Arc::clone(&aborted) })))
+    }
+
+    /// 不支持中途切换语言的会话，结束时返回按语言固定的文本，记录收到的音频块数
+    struct LanguageSession {
+        text: String,
+        chunks: Arc<std::sync::Mutex<Vec<(String, usize)>>>,
+    }
+
+    #[async_trait]
+    impl RealtimeSession for LanguageSession {
+        async fn send_chunk(&mut self, _chunk: &[u8]) -> Result<(), ASRError> {
+            if let Ok(mut chunks) = self.chunks.lock() {
+                match chunks.last_mut() {
+                    Some((text, count)) if *text == self.text => *count += 1,
+                    _ => chunks.push((self.text.clone(), 1)),
+                }
+            }
+            Ok(())
+        }
+
+        async fn close(&mut self) -> Result<String, ASRError> {
+            Ok(self.text.clone())
+        }
+
+        fn set_partial_callback(&mut self, _callback: Box<dyn Fn(&str) + Send + 'static>) {}
+    }
+
+    #[tokio::test]
+    async fn test_language_switch_rebuilds_session_at_silence() {
+        let chunks = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let session = |chunks: &Arc<std::sync::Mutex<Vec<(String, usize)>>>, text: &str| -> Box<dyn RealtimeSession> {
+            Box::new(LanguageSession { text: text.to_string(), chunks: Arc::clone(chunks) })
+        };
+        let (default_chunks, language_chunks) = (Arc::clone(&chunks), Arc::clone(&chunks));
+        let engine = Arc::new(
+            FakeEngine::realtime("language", move || session(&default_chunks, "你好"))
+                .with_language_session(move |language| {
+                    session(&language_chunks, if language == "en" { "hello" } else { "你好" })
+                }),
+        );
+        let (chunk_tx, chunk_rx) = mpsc::channel(16);
+        let (language_tx, language_rx) = mpsc::unbounded_channel();
+        let config = ASRProviderConfig::qwen(crate::voice::config::ASRMode::Realtime, "key".to_string());
+
+        let (task, _stop_tx) = RealtimeTranscriptionTask::new(config, chunk_rx, None);
+        let task = task.with_engine(engine).with_language_receiver(language_rx);
+        let handle = tokio::spawn(task.run_with_details());
+
+        let speech = || AudioChunkData { samples: vec![8000; 1600], timestamp_ms: 0 };
+        let silence = || AudioChunkData { samples: vec![0; 1600], timestamp_ms: 0 };
+        chunk_tx.send(speech()).await.unwrap();
+        language_tx.send("en".to_string()).unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        // 说话中不重建，静音达到分句边界后才切换
+        chunk_tx.send(speech()).await.unwrap();
+        for _ in 0..3 {
+            chunk_tx.send(silence()).await.unwrap();
+        }
+        chunk_tx.send(speech()).await.unwrap();
+        drop(chunk_tx);
+
+        let result = tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap().unwrap();
+        assert_eq!(result.into_result().unwrap().text, "你好hello");
+        let chunks = chunks.lock().unwrap().clone();
+        assert_eq!(chunks, vec![("你好".to_string(), 5), ("hello".to_string(), 1)]);
+    }
+
+    /// 每收到一个音频块就以递增文本回调部分结果的会话
+    #[derive(Default)]
+    struct PartialSession {
+        callback: Option<PartialResultCallback>,
+        text: String,
+    }
+
+    #[async_trait]
+    impl RealtimeSession for PartialSession {
+        async fn send_chunk(&mut self, _chunk: &[u8]) -> Result<(), ASRError> {
+            self.text.push_str(&(self.text.len() % 10).to_string());
+            if let Some(ref callback) = self.callback {
+                callback(&self.text);
+            }
+            Ok(())
+        }
+
+        async fn close(&mut self) -> Result<String, ASRError> {
+            Ok(self.text.clone())
+        }
+
+        fn set_partial_callback(&mut self, callback: Box<dyn Fn(&str) + Send + 'static>) {
+            self.callback = Some(callback);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_partials_forwarded_in_order() {
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&received);
+        let callback: AlternativesCallback = Box::new(move |text: &str, _alternatives: &[String]| {
+            sink.lock().unwrap().push(text.to_string());
+        });
+        let (chunk_tx, chunk_rx) = mpsc::channel(64);
+        let config = ASRProviderConfig::qwen(crate::voice::config::ASRMode::Realtime, "key".to_string());
+        let (task, _stop_tx) = RealtimeTranscriptionTask::new(config, chunk_rx, Some(callback));
+        let handle = tokio::spawn(task.with_engine(Arc::new(FakeEngine::realtime("partial", || Box::new(PartialSession::default())))).run_with_details());
+
+        for _ in 0..30 {
+            chunk_tx.send(AudioChunkData { samples: vec![8000; 1600], timestamp_ms: 0 }).await.unwrap();
+        }
+        drop(chunk_tx);
+        let result = tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap().unwrap();
+        let text = result.into_result().unwrap().text;
+
+        // 部分结果与引擎回调顺序一致，每次都是上一次的延长
+        let received = received.lock().unwrap().clone();
+        assert_eq!(received.len(), 30);
+        assert!(received.windows(2).all(|pair| pair[1].starts_with(pair[0].as_str()) && pair[1].len() > pair[0].len()));
+        assert_eq!(received.last(), Some(&text));
+    }
+
+    /// 记录收到的数据块长度的会话，`dedicated` 时使用专用保活消息 (记为长度 0)
+    struct KeepaliveSession {
+        dedicated: bool,
+        sent: Arc<std::sync::Mutex<Vec<usize>>>,
+    }
+
+    #[async_trait]
+    impl RealtimeSession for KeepaliveSession {
+        async fn send_chunk(&mut self, chunk: &[u8]) -> Result<(), ASRError> {
+            self.sent.lock().unwrap().push(chunk.len());
+            Ok(())
+        }
+
+        async fn keep_alive(&mut self) -> Result<bool, ASRError> {
+            if self.dedicated {
+                self.sent.lock().unwrap().push(0);
+            }
+            Ok(self.dedicated)
+        }
+
+        async fn close(&mut self) -> Result<String, ASRError> {
+            Ok("好".to_string())
+        }
+
+        fn set_partial_callback(&mut self, _callback: Box<dyn Fn(&str) + Send + 'static>) {}
+    }
+
+    fn keepalive_engine(dedicated: bool, sent: &Arc<std::sync::Mutex<Vec<usize>>>) -> Arc<FakeEngine> {
+        let sent = Arc::clone(sent);
+        Arc::new(FakeEngine::realtime("keepalive", move || {
+            Box::new(KeepaliveSession { dedicated, sent: Arc::clone(&sent) })
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_during_pause_until_close() {
+        for dedicated in [false, true] {
+            let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let engine = keepalive_engine(dedicated, &sent);
+            let (chunk_tx, chunk_rx) = mpsc::channel(4);
+            let config = ASRProviderConfig::qwen(crate::voice::config::ASRMode::Realtime, "key".to_string());
+
+            let (task, stop_tx) = RealtimeTranscriptionTask::new(config, chunk_rx, None);
+            let task = task
+                .with_engine(engine)
+                .with_keepalive(KeepaliveConfig { enabled: true, interval_ms: 40 });
+            let handle = tokio::spawn(task.run_with_details());
+
+            chunk_tx.send(AudioChunkData { samples: vec![8000; 320], timestamp_ms: 0 }).await.unwrap();
+            // 停顿期间 (噪声门丢弃了静音块) 按间隔保活
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            stop_tx.send(()).unwrap();
+            let result = tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap().unwrap();
+            assert!(result.is_success());
+
+            // 关闭后不再发送
+            let count = sent.lock().unwrap().len();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let sent = sent.lock().unwrap().clone();
+            assert_eq!(sent.len(), count);
+            assert_eq!(sent[0], 640);
+            let keepalive = if dedicated { 0 } else { 3200 };
+            assert!(sent.len() >= 3, "{:?}", sent);
+            assert!(sent[1..].iter().all(|&len| len == keepalive), "{:?}", sent);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_mode_holds_until_speech() {
+        let config = ASRProviderConfig::qwen(crate::voice::config::ASRMode::Realtime, "key".to_string());
+        let adaptive = AdaptiveModeConfig { enabled: true, realtime_after_ms: 100 };
+        let voiced = || AudioChunkData { samples: vec![8000; 320], timestamp_ms: 0 };
+        let silent = || AudioChunkData { samples: vec![0; 320], timestamp_ms: 0 };
+
+        // 静音不计入有声时长，阈值前停止时不建立会话
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let engine = keepalive_engine(true, &sent);
+        let (chunk_tx, chunk_rx) = mpsc::channel(16);
+        let (task, stop_tx) = RealtimeTranscriptionTask::new(config.clone(), chunk_rx, None);
+        let handle = tokio::spawn(task.with_engine(engine).with_adaptive_mode(adaptive).run_with_details());
+        for _ in 0..10 {
+            chunk_tx.send(silent()).await.unwrap();
+        }
+        chunk_tx.send(voiced()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        stop_tx.send(()).unwrap();
+        let result = tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap().unwrap();
+        assert!(matches!(result, RealtimeTaskResult::ShortAudio));
+        assert!(sent.lock().unwrap().is_empty());
+
+        // 超过阈值后建立会话，缓冲的录音按顺序补发
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let engine = keepalive_engine(true, &sent);
+        let (chunk_tx, chunk_rx) = mpsc::channel(16);
+        let (task, stop_tx) = RealtimeTranscriptionTask::new(config, chunk_rx, None);
+        let handle = tokio::spawn(task.with_engine(engine).with_adaptive_mode(adaptive).run_with_details());
+        chunk_tx.send(silent()).await.unwrap();
+        for _ in 0..8 {
+            chunk_tx.send(voiced()).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stop_tx.send(()).unwrap();
+        let result = tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap().unwrap();
+        assert!(result.is_success());
+        assert_eq!(*sent.lock().unwrap(), vec![640; 9]);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_token_aborts_session() {
+        let aborted = Arc::new(AtomicBool::new(false));
+        let engine = hanging_engine(&aborted);
+        let (chunk_tx, chunk_rx) = mpsc::channel(4);
+        let config = ASRProviderConfig::qwen(crate::voice::config::ASRMode::Realtime, "key".to_string());
+        let cancel_token = CancellationToken::new();
+
+        let (task, _stop_tx) = RealtimeTranscriptionTask::new(config, chunk_rx, None);
+        let task = task.with_engine(engine).with_cancel_token(cancel_token.child_token());
+        let handle = tokio::spawn(task.run_with_details());
+
+        chunk_tx.send(AudioChunkData { samples: vec![0; 160], timestamp_ms: 0 }).await.unwrap();
+        // 关闭音频通道后任务进入等待最终结果阶段 (HangingSession 永不返回)
+        drop(chunk_tx);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        cancel_token.cancel();
+
+        let result = tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap().unwrap();
+        assert!(matches!(result, RealtimeTaskResult::Failed { error: ASRError::Cancelled, chunks_sent: 1, .. }));
+        assert!(aborted.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_split_like_segments_follows_full_text() {
+        let full = "你好，世界。今天 天气不错！";
+        assert_eq!(
+            split_like_segments(full, &["你好", "世界", "今天天气不错"]),
+            Some(vec!["你好，", "世界。", "今天 天气不错！"])
+        );
+        // 分句与整段文本对不上时由调用方另行处理
+        assert_eq!(split_like_segments(full, &["你们", "世界"]), None);
+        assert_eq!(split_like_segments(full, &[]), None);
+    }
+
+    #[test]
+    fn test_segments_split_final_text_at_pauses() {
+        // 100ms 的有声块，时间戳为块的结束时间
+        let voiced = |timestamp_ms| AudioChunkData { samples: vec![8000; 1600], timestamp_ms };
+        let mut tracker = SegmentTracker::default();
+        let speak = |tracker: &mut SegmentTracker, from: u64, to: u64| {
+            for end in (from..=to).step_by(100) {
+                tracker.voiced(&voiced(end), || unreachable!());
+            }
+        };
+        speak(&mut tracker, 100, 1000);
+        // 停顿 1600ms 后继续说话，此时的部分结果属于上一分句
+        tracker.voiced(&voiced(2700), || "今天开会讨论了三件事，".to_string());
+        speak(&mut tracker, 2800, 3500);
+        tracker.voiced(&voiced(4400), || "今天开会讨论了三件事，预算需要压缩".to_string());
+        speak(&mut tracker, 4500, 5000);
+
+        let segments = tracker.split("今天开会讨论了三件事。预算需要压缩。会议到此结束。", "qwen");
+        let spans: Vec<_> = segments.iter()
+            .map(|s| (s.text.as_str(), s.start_ms.unwrap(), s.end_ms.unwrap()))
+            .collect();
+        assert_eq!(spans, [
+            ("今天开会讨论了三件事。", 0, 1000),
+            ("预算需要压缩。", 2600, 3500),
+            ("会议到此结束。", 4300, 5000),
+        ]);
+        assert_eq!(
+            crate::voice::asr::assemble_document(&segments),
+            "今天开会讨论了三件事。\n\n预算需要压缩。会议到此结束。"
+        );
+        // 中等停顿在段内换行
+        let config = crate::voice::config::DocumentConfig { line_break_pause_ms: 500, ..Default::default() };
+        assert_eq!(
+            crate::voice::asr::assemble_document_with(&segments, &config),
+            "今天开会讨论了三件事。\n\n预算需要压缩。\n会议到此结束。"
+        );
+
+        // 最终结果修订了先前的文本时不拆分
+        assert!(tracker.split("今天讨论了两件事。", "qwen").is_empty());
+        // 没有停顿时不拆分
+        let mut single = SegmentTracker::default();
+        single.voiced(&voiced(100), || unreachable!());
+        assert!(single.split("你好。", "qwen").is_empty());
+    }
+}
@@ -0,0 +1,125 @@
+// 术语替换表
+// 按用户的固定纠错映射 (如 "杰森" → "JSON") 改写转录文本。从左到右一次扫描，
+// 替换结果不再参与匹配，避免 A→B、B→C 这类连锁替换
+
+use crate::voice::asr::punctuator::is_cjk;
+
+/// 匹配选项
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplaceOptions {
+    /// 忽略大小写
+    pub ignore_case: bool,
+    /// 仅匹配整词：以字母数字开头/结尾的词条，相邻字符不能也是字母数字 (中文字符不受限制)
+    pub whole_word: bool,
+}
+
+/// 按替换表改写文本 (区分大小写，不要求整词)
+#[allow(dead_code)]
+pub fn apply_replacements(text: &str, map: &[(String, String)]) -> String {
+    apply_replacements_with(text, map, ReplaceOptions::default())
+}
+
+/// 按替换表与匹配选项改写文本
+///
+/// 同一位置有多个词条匹配时取最长的，等长时取表中靠前的；空词条忽略
+pub fn apply_replacements_with(text: &str, map: &[(String, String)], options: ReplaceOptions) -> String {
+    let patterns: Vec<(Vec<char>, &str)> = map
+        .iter()
+        .filter(|(from, _)| !from.is_empty())
+        .map(|(from, to)| (from.chars().collect(), to.as_str()))
+        .collect();
+    if patterns.is_empty() {
+        return text.to_string();
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let best = patterns
+            .iter()
+            .filter(|(pattern, _)| matches_at(&chars, i, pattern, options))
+            .fold(None, |best: Option<&(Vec<char>, &str)>, candidate| match best {
+                Some(best) if best.0.len() >= candidate.0.len() => Some(best),
+                _ => Some(candidate),
+            });
+        match best {
+            Some((pattern, replacement)) => {
+                out.push_str(replacement);
+                i += pattern.len();
+            }
+            None => {
+                out.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+fn matches_at(chars: &[char], start: usize, pattern: &[char], options: ReplaceOptions) -> bool {
+    let end = start + pattern.len();
+    if end > chars.len() {
+        return false;
+    }
+    let equal = chars[start..end].iter().zip(pattern).all(|(&c, &p)| {
+        c == p || (options.ignore_case && c.to_lowercase().eq(p.to_lowercase()))
+    });
+    if !equal {
+        return false;
+    }
+
+    if options.whole_word {
+        if is_word_char(pattern[0]) && start > 0 && is_word_char(chars[start - 1]) {
+            return false;
+        }
+        if is_word_char(pattern[pattern.len() - 1]) && end < chars.len() && is_word_char(chars[end]) {
+            return false;
+        }
+    }
+    true
+}
+
+/// 参与整词判断的字符：非中文的字母与数字
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() && !is_cjk(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+        entries.iter().map(|(from, to)| (from.to_string(), to.to_string())).collect()
+    }
+
+    #[test]
+    fn test_apply_replacements_options_and_single_pass() {
+        let map = table(&[("杰森", "JSON"), ("react", "React"), ("api", "API")]);
+        assert_eq!(apply_replacements("把杰森传给 react 的api", &map), "把JSON传给 React 的API");
+
+        // 一次扫描：替换结果不再被匹配，A→B、B→A 互换不会来回替换
+        let swap = table(&[("甲", "乙"), ("乙", "甲"), ("JSON", "杰森")]);
+        assert_eq!(apply_replacements("甲乙杰森", &swap), "乙甲杰森");
+        assert_eq!(apply_replacements("把杰森转成", &table(&[("杰森", "JSON"), ("JSON", "YAML")])), "把JSON转成");
+
+        // 同一位置取最长词条
+        let longest = table(&[("深度", "deep"), ("深度学习", "deep learning")]);
+        assert_eq!(apply_replacements("深度学习与深度", &longest), "deep learning与deep");
+
+        // 大小写
+        let case = table(&[("github", "GitHub")]);
+        assert_eq!(apply_replacements("Github github", &case), "Github GitHub");
+        let ignore_case = ReplaceOptions { ignore_case: true, whole_word: false };
+        assert_eq!(apply_replacements_with("Github GITHUB", &case, ignore_case), "GitHub GitHub");
+
+        // 整词：英文词内部不替换，中文词条不受词边界限制
+        let whole_word = ReplaceOptions { ignore_case: false, whole_word: true };
+        assert_eq!(apply_replacements_with("api apis rapid,api", &map, whole_word), "API apis rapid,API");
+        assert_eq!(apply_replacements_with("用杰森吧", &map, whole_word), "用JSON吧");
+        assert_eq!(apply_replacements_with("调用api接口", &map, whole_word), "调用API接口");
+
+        assert_eq!(apply_replacements("原文", &table(&[("", "x")])), "原文");
+        assert_eq!(apply_replacements("", &map), "");
+    }
+}
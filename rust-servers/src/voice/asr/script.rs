@@ -0,0 +1,132 @@
+// 字形与大小写规整模块
+// 简繁转换 (内置常用字映射表) 与英文大小写统一，作为转录文本后处理的一步
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::voice::config::{ChineseScript, LetterCase, ScriptTarget};
+
+/// 简体字表，与 TRADITIONAL_CHARS 按字符位置一一对应
+const SIMPLIFIED_CHARS: &str = concat!(
+    "爱罢备贝笔边变宾补参仓产长尝车陈称惩迟齿虫处传创词从错达带单当党导灯敌递点电东动",
+    "冻独断对队吨夺儿尔发范饭访飞费丰风复该盖赶刚钢个给关观广归国过汉号后护华画话怀欢",
+    "环还换会汇获机鸡积极几计记际继见将奖讲阶节结紧进尽惊经旧举觉开课块来劳乐类离礼历",
+    "丽连联两辆疗邻灵刘龙楼录陆乱论罗马买卖满门们梦难脑内鸟宁农盘评齐气钱强桥亲轻请区",
+    "权劝让热认荣伤设声胜师时实识视试书数双说顺丝岁孙谈态汤头图团万网为卫问无务习戏细",
+    "现县线乡响项写谢兴许选学压亚严验阳样药爷业页医仪忆艺议亿阴银应营忧邮优犹鱼语与员",
+    "园远愿约跃云运杂灾载则择张这针阵证郑织职纸钟种众专转装状总组吗读么没题谁间络软讯",
+    "码质标确据库执帮储缓级测显键输闭删链术续审阅览缩释诉讨调误档构础场报纪馆体贵贸资",
+    "购货价账厂绍统虽坏虑养顾闻闹阔随隐须顿预领频颜饮饿驱骂鲁鲜黄龟岛湾闪闯闷简妈奋奂",
+    "币纲丢乔亏亩亵仅仑伞伟伦伪佣侠侣侥侦侧侨俭债倾偿兑兰兹兽冈册军冯冲决况净凉减凑凛",
+    "凤凭凯击凿刍划别刹剂剑剧办励劲势勋匀协卢却厅厉厌厕叙叠叹吓吕听启吴呐呕哑哗唤啸喷",
+    "围圆圣坚坛坝坟坠垄垒垦埘尘层届屡岂岗岭岳帅帐并庄庆庐庙废异弃弥弯弹彦彻径忏恋恒恶",
+    "恼悦悬惧惨惯愤懒战户扑扩扫扬扰抚抛抢担拟拥拦拨挂挣挤挥捞损捡掷揽搀携摄摆摇撑斋斩",
+    "旷昼晋晒晓暂杀条杨枪枣柜栈栋树检横欧残毕毙沟沪泪泻泼泽洁浅济浏浓涂涛润涨渊渐温湿",
+    "滚滤滥灭灿炉炼烁烂烛烟烦烧焕牵狭狮猎献猫玛琐畅疯痒皱盏盐监着睁矫砖祸税穷窃竞笋筑",
+    "签粮纠红纯纳纵纷纹练终绕绘绝绩绪维绵综绿编缘罚聪肃肠肤肿胆脉脚腾舰苏苹荐莱萝蓝虚",
+    "虾蚁蛮规触训译诗诚询详诸谋谓谱负贡财责败贩贪贯贴贺赏赔赖赚赛赞赠赵趋践踪轨轮较辅",
+    "辈辉辞辽迁违逻遗邓酱钓钞钥铁铃铜铺销锁锅锋锦镇镜闲阀阁险雾静韩顶顽额飘饰饱饼驰驶",
+    "驻驾骑骗鸣麦",
+);
+
+/// 繁体字表
+const TRADITIONAL_CHARS: &str = concat!(
+    "愛罷備貝筆邊變賓補參倉產長嘗車陳稱懲遲齒蟲處傳創詞從錯達帶單當黨導燈敵遞點電東動",
+    "凍獨斷對隊噸奪兒爾發範飯訪飛費豐風復該蓋趕剛鋼個給關觀廣歸國過漢號後護華畫話懷歡",
+    "環還換會匯獲機雞積極幾計記際繼見將獎講階節結緊進盡驚經舊舉覺開課塊來勞樂類離禮歷",
+    "麗連聯兩輛療鄰靈劉龍樓錄陸亂論羅馬買賣滿門們夢難腦內鳥寧農盤評齊氣錢強橋親輕請區",
+    "權勸讓熱認榮傷設聲勝師時實識視試書數雙說順絲歲孫談態湯頭圖團萬網為衛問無務習戲細",
+    "現縣線鄉響項寫謝興許選學壓亞嚴驗陽樣藥爺業頁醫儀憶藝議億陰銀應營憂郵優猶魚語與員",
+    "園遠願約躍雲運雜災載則擇張這針陣證鄭織職紙鐘種眾專轉裝狀總組嗎讀麼沒題誰間絡軟訊",
+    "碼質標確據庫執幫儲緩級測顯鍵輸閉刪鏈術續審閱覽縮釋訴討調誤檔構礎場報紀館體貴貿資",
+    "購貨價賬廠紹統雖壞慮養顧聞鬧闊隨隱須頓預領頻顏飲餓驅罵魯鮮黃龜島灣閃闖悶簡媽奮奐",
+    "幣綱丟喬虧畝褻僅崙傘偉倫偽傭俠侶僥偵側僑儉債傾償兌蘭茲獸岡冊軍馮衝決況淨涼減湊凜",
+    "鳳憑凱擊鑿芻劃別剎劑劍劇辦勵勁勢勳勻協盧卻廳厲厭廁敘疊嘆嚇呂聽啟吳吶嘔啞嘩喚嘯噴",
+    "圍圓聖堅壇壩墳墜壟壘墾塒塵層屆屢豈崗嶺嶽帥帳並莊慶廬廟廢異棄彌彎彈彥徹徑懺戀恆惡",
+    "惱悅懸懼慘慣憤懶戰戶撲擴掃揚擾撫拋搶擔擬擁攔撥掛掙擠揮撈損撿擲攬攙攜攝擺搖撐齋斬",
+    "曠晝晉曬曉暫殺條楊槍棗櫃棧棟樹檢橫歐殘畢斃溝滬淚瀉潑澤潔淺濟瀏濃塗濤潤漲淵漸溫濕",
+    "滾濾濫滅燦爐煉爍爛燭煙煩燒煥牽狹獅獵獻貓瑪瑣暢瘋癢皺盞鹽監著睜矯磚禍稅窮竊競筍築",
+    "簽糧糾紅純納縱紛紋練終繞繪絕績緒維綿綜綠編緣罰聰肅腸膚腫膽脈腳騰艦蘇蘋薦萊蘿藍虛",
+    "蝦蟻蠻規觸訓譯詩誠詢詳諸謀謂譜負貢財責敗販貪貫貼賀賞賠賴賺賽贊贈趙趨踐蹤軌輪較輔",
+    "輩輝辭遼遷違邏遺鄧醬釣鈔鑰鐵鈴銅鋪銷鎖鍋鋒錦鎮鏡閒閥閣險霧靜韓頂頑額飄飾飽餅馳駛",
+    "駐駕騎騙鳴麥",
+);
+
+/// 简 → 繁映射
+fn to_traditional_map() -> &'static HashMap<char, char> {
+    static MAP: OnceLock<HashMap<char, char>> = OnceLock::new();
+    MAP.get_or_init(|| SIMPLIFIED_CHARS.chars().zip(TRADITIONAL_CHARS.chars()).collect())
+}
+
+/// 繁 → 简映射
+pub(crate) fn to_simplified_map() -> &'static HashMap<char, char> {
+    static MAP: OnceLock<HashMap<char, char>> = OnceLock::new();
+    MAP.get_or_init(|| TRADITIONAL_CHARS.chars().zip(SIMPLIFIED_CHARS.chars()).collect())
+}
+
+/// 按目标字形与大小写转换文本
+///
+/// 映射表只包含汉字，标点、数字与其他字符原样保留；大小写只作用于 ASCII 字母
+pub fn convert_script(text: &str, target: ScriptTarget) -> String {
+    let map = match target.chinese {
+        ChineseScript::Keep => None,
+        ChineseScript::Simplified => Some(to_simplified_map()),
+        ChineseScript::Traditional => Some(to_traditional_map()),
+    };
+
+    text.chars()
+        .map(|c| map.and_then(|m| m.get(&c).copied()).unwrap_or(c))
+        .map(|c| match target.case {
+            LetterCase::Keep => c,
+            LetterCase::Lower => c.to_ascii_lowercase(),
+            LetterCase::Upper => c.to_ascii_uppercase(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(chinese: ChineseScript, case: LetterCase) -> ScriptTarget {
+        ScriptTarget { chinese, case }
+    }
+
+    #[test]
+    fn test_tables_are_consistent() {
+        assert_eq!(SIMPLIFIED_CHARS.chars().count(), TRADITIONAL_CHARS.chars().count());
+        // 双向映射互逆
+        assert_eq!(to_traditional_map().len(), to_simplified_map().len());
+        for (s, t) in to_traditional_map() {
+            assert_eq!(to_simplified_map().get(t), Some(s));
+        }
+    }
+
+    #[test]
+    fn test_simplified_traditional_round_trip() {
+        let simplified = "这个软件的语音识别准确率很高，欢迎试用！";
+        let traditional = convert_script(simplified, target(ChineseScript::Traditional, LetterCase::Keep));
+        assert_eq!(traditional, "這個軟件的語音識別准確率很高，歡迎試用！");
+        assert_eq!(
+            convert_script(&traditional, target(ChineseScript::Simplified, LetterCase::Keep)),
+            simplified
+        );
+    }
+
+    #[test]
+    fn test_punctuation_and_digits_untouched() {
+        let text = "2024年“会议”：第3项（见附件）…… 100%";
+        let converted = convert_script(text, target(ChineseScript::Traditional, LetterCase::Keep));
+        assert_eq!(converted, "2024年“會議”：第3項（見附件）…… 100%");
+        let non_han = |s: &str| s.chars().filter(|c| !('\u{4e00}'..='\u{9fff}').contains(c)).collect::<String>();
+        assert_eq!(non_han(&converted), non_han(text));
+    }
+
+    #[test]
+    fn test_letter_case() {
+        let text = "Open VS Code 打开";
+        assert_eq!(convert_script(text, target(ChineseScript::Keep, LetterCase::Lower)), "open vs code 打开");
+        assert_eq!(convert_script(text, target(ChineseScript::Keep, LetterCase::Upper)), "OPEN VS CODE 打开");
+        assert_eq!(convert_script(text, ScriptTarget::default()), text);
+    }
+}
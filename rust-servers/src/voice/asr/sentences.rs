@@ -0,0 +1,145 @@
+// 分句模块
+// 把转录文本按句末标点拆成句子数组，供前端逐句编辑。只切分、不改写文本 (仅去掉句间空白)，
+// 英文句点需排除缩写、姓名首字母与小数，省略号按后文判断是否断句
+
+use crate::voice::asr::punctuator::is_cjk;
+
+/// 总是断句的句末标点
+const TERMINATORS: &[char] = &['。', '！', '？', '!', '?'];
+
+/// 紧跟句末标点、仍属于本句的闭合符号
+const CLOSING_MARKS: &[char] = &['”', '’', '」', '』', '）', ')', '"', '\''];
+
+/// 其后永不断句的缩写 (称谓与引用，小写、不含末尾句点)
+const TITLE_ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "mt", "vs", "e.g", "i.e", "cf",
+];
+
+/// 其后为小写或数字时不断句的缩写 (也常出现在句末)
+const ABBREVIATIONS: &[&str] = &[
+    "etc", "a.m", "p.m", "inc", "ltd", "co", "corp", "no", "fig", "approx", "dept", "u.s",
+];
+
+/// 把文本拆成句子，去掉句间空白，句子内容保持原样
+///
+/// 中文按句号/问号/叹号断句；英文句点后跟空白、中文或结尾时断句，缩写 (Mr. 等)、
+/// 姓名首字母与小数不断句。省略号后为中文、大写字母或结尾时断句，
+/// `lang` 为中文 (zh*) 时省略号总是断句。末尾没有句末标点的部分作为最后一句
+pub fn split_sentences(text: &str, lang: &str) -> Vec<String> {
+    let chinese = lang.to_ascii_lowercase().starts_with("zh");
+    let chars: Vec<char> = text.chars().collect();
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let is_end = if is_ellipsis_at(&chars, i) {
+            while i < chars.len() && (chars[i] == '…' || chars[i] == '.') {
+                i += 1;
+            }
+            ellipsis_ends_sentence(&chars, i, chinese)
+        } else {
+            i += 1;
+            TERMINATORS.contains(&c) || (c == '.' && period_ends_sentence(&chars, i - 1))
+        };
+
+        if is_end {
+            while i < chars.len() && (TERMINATORS.contains(&chars[i]) || CLOSING_MARKS.contains(&chars[i])) {
+                i += 1;
+            }
+            push_sentence(&mut sentences, &chars[start..i]);
+            start = i;
+        }
+    }
+    push_sentence(&mut sentences, &chars[start..]);
+
+    sentences
+}
+
+fn push_sentence(sentences: &mut Vec<String>, chars: &[char]) {
+    let sentence: String = chars.iter().collect();
+    let sentence = sentence.trim();
+    if !sentence.is_empty() {
+        sentences.push(sentence.to_string());
+    }
+}
+
+/// `…` 或连续两个以上的 `.`
+fn is_ellipsis_at(chars: &[char], i: usize) -> bool {
+    chars[i] == '…' || (chars[i] == '.' && chars.get(i + 1) == Some(&'.'))
+}
+
+/// 跳过闭合符号与空白后的下一个字符
+fn next_visible(chars: &[char], mut i: usize) -> Option<char> {
+    while i < chars.len() && (chars[i].is_whitespace() || CLOSING_MARKS.contains(&chars[i])) {
+        i += 1;
+    }
+    chars.get(i).copied()
+}
+
+fn ellipsis_ends_sentence(chars: &[char], end: usize, chinese: bool) -> bool {
+    match next_visible(chars, end) {
+        None => true,
+        Some(next) => chinese || is_cjk(next) || next.is_uppercase() || TERMINATORS.contains(&next),
+    }
+}
+
+/// 英文句点是否为句末 (i 为句点位置)
+fn period_ends_sentence(chars: &[char], i: usize) -> bool {
+    // 句点后紧跟字母数字 (小数、网址、a.m 中间的点) 不断句
+    if chars
+        .get(i + 1)
+        .is_some_and(|&next| !next.is_whitespace() && !is_cjk(next) && !CLOSING_MARKS.contains(&next) && !TERMINATORS.contains(&next))
+    {
+        return false;
+    }
+
+    // 句点前的单词 (可含内部句点，如 e.g)
+    let mut word_start = i;
+    while word_start > 0 && (chars[word_start - 1].is_ascii_alphabetic() || chars[word_start - 1] == '.') {
+        word_start -= 1;
+    }
+    let word: String = chars[word_start..i].iter().collect();
+    let lower = word.to_ascii_lowercase();
+
+    if TITLE_ABBREVIATIONS.contains(&lower.as_str()) {
+        return false;
+    }
+    // 姓名首字母 (如 J. K. Rowling)，代词 I 除外
+    if word.len() == 1 && word != "I" && word.chars().all(|c| c.is_ascii_uppercase()) {
+        return false;
+    }
+    if ABBREVIATIONS.contains(&lower.as_str()) {
+        return next_visible(chars, i + 1).is_none_or(|next| !next.is_lowercase() && !next.is_ascii_digit());
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_mixed_language_and_ellipsis() {
+        assert_eq!(
+            split_sentences("我今天见了 Mr. Wang 和 Dr. Lee。他说 OK. 然后我们走了！还有问题吗？", "zh"),
+            vec!["我今天见了 Mr. Wang 和 Dr. Lee。", "他说 OK.", "然后我们走了！", "还有问题吗？"]
+        );
+        assert_eq!(
+            split_sentences("Version 1.2 was released at 3 p.m. yesterday. J. K. Rowling wrote it, etc. Thanks!", "en"),
+            vec!["Version 1.2 was released at 3 p.m. yesterday.", "J. K. Rowling wrote it, etc.", "Thanks!"]
+        );
+        // 引号等闭合符号留在本句，未结束的尾部作为最后一句
+        assert_eq!(split_sentences("他说：“好的。”然后走了", "zh"), vec!["他说：“好的。”", "然后走了"]);
+
+        // 省略号：英文后接小写时不断句，中文总是断句
+        assert_eq!(
+            split_sentences("I was... thinking about it... Maybe not.", "en"),
+            vec!["I was... thinking about it...", "Maybe not."]
+        );
+        assert_eq!(split_sentences("我想想……好吧。嗯… ok", "zh"), vec!["我想想……", "好吧。", "嗯…", "ok"]);
+        assert_eq!(split_sentences("Well… 那就这样吧…", "en"), vec!["Well…", "那就这样吧…"]);
+        assert!(split_sentences("  ", "en").is_empty());
+    }
+}
@@ -0,0 +1,779 @@
+// 火山引擎 (豆包) 流式语音识别实现
+// 基于 openspeech v2 WebSocket 二进制协议，支持 cluster 配置
+//
+// 协议帧格式: [4 字节协议头][可选 4 字节 sequence][4 字节 payload 长度][payload]
+// 协议头: version(4bit) | header size(4bit) | message type(4bit) | flags(4bit)
+//        | serialization(4bit) | compression(4bit) | reserved(8bit)
+
+use async_trait::async_trait;
+use flate2::{write::GzEncoder, read::GzDecoder, Compression};
+use futures_util::{SinkExt, StreamExt};
+use std::io::{Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{connect_async, tungstenite::{Message, http}};
+
+use crate::voice::asr::{ASREngine, AlternativesCallback, AudioRequirements, ASRError, ASRMode, RealtimeSession, RetryConfig, MAX_PARTIAL_ALTERNATIVES};
+use crate::voice::audio::{AudioData, CHUNK_SAMPLES};
+use crate::voice::audio::recorder::convert_f32_to_i16;
+
+/// 会话内共享的部分结果回调 (附带 n-best 候选)
+type SharedPartialCallback = Arc<std::sync::Mutex<Option<AlternativesCallback>>>;
+
+const WEBSOCKET_URL: &str = "wss://openspeech.bytedance.com/api/v2/asr";
+/// 默认集群 (通用流式识别)
+pub const DEFAULT_CLUSTER: &str = "volcengine_streaming_common";
+const TRANSCRIPTION_TIMEOUT_SECS: u64 = 10;
+
+/// 协议版本
+const PROTOCOL_VERSION: u8 = 0x1;
+/// 协议头长度 (单位: 4 字节)
+const HEADER_SIZE_UNITS: u8 = 0x1;
+
+/// 成功状态码
+const CODE_SUCCESS: u32 = 1000;
+/// 鉴权失败状态码
+const CODE_AUTH_FAILED: u32 = 1002;
+/// 超出频率/配额限制状态码
+const CODE_RATE_LIMITED: u32 = 1003;
+
+// ============================================================================
+// 协议头
+// ============================================================================
+
+/// 消息类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageType {
+    /// 客户端完整请求 (携带配置)
+    FullClientRequest,
+    /// 客户端纯音频请求
+    AudioOnlyRequest,
+    /// 服务端完整响应
+    FullServerResponse,
+    /// 服务端确认
+    ServerAck,
+    /// 服务端错误
+    ServerError,
+}
+
+impl MessageType {
+    fn to_bits(self) -> u8 {
+        match self {
+            MessageType::FullClientRequest => 0x1,
+            MessageType::AudioOnlyRequest => 0x2,
+            MessageType::FullServerResponse => 0x9,
+            MessageType::ServerAck => 0xb,
+            MessageType::ServerError => 0xf,
+        }
+    }
+
+    fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0x1 => Some(MessageType::FullClientRequest),
+            0x2 => Some(MessageType::AudioOnlyRequest),
+            0x9 => Some(MessageType::FullServerResponse),
+            0xb => Some(MessageType::ServerAck),
+            0xf => Some(MessageType::ServerError),
+            _ => None,
+        }
+    }
+}
+
+/// 序列化方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Serialization {
+    None,
+    Json,
+}
+
+impl Serialization {
+    fn to_bits(self) -> u8 {
+        match self {
+            Serialization::None => 0x0,
+            Serialization::Json => 0x1,
+        }
+    }
+
+    fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0x0 => Some(Serialization::None),
+            0x1 => Some(Serialization::Json),
+            _ => None,
+        }
+    }
+}
+
+/// 压缩方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionType {
+    None,
+    Gzip,
+}
+
+impl CompressionType {
+    fn to_bits(self) -> u8 {
+        match self {
+            CompressionType::None => 0x0,
+            CompressionType::Gzip => 0x1,
+        }
+    }
+
+    fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0x0 => Some(CompressionType::None),
+            0x1 => Some(CompressionType::Gzip),
+            _ => None,
+        }
+    }
+}
+
+/// 消息标志: 携带 sequence
+pub const FLAG_WITH_SEQUENCE: u8 = 0x1;
+/// 消息标志: 最后一包
+pub const FLAG_LAST_PACKET: u8 = 0x2;
+
+/// 协议头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolHeader {
+    pub message_type: MessageType,
+    pub flags: u8,
+    pub serialization: Serialization,
+    pub compression: CompressionType,
+}
+
+impl ProtocolHeader {
+    pub fn new(
+        message_type: MessageType,
+        flags: u8,
+        serialization: Serialization,
+        compression: CompressionType,
+    ) -> Self {
+        Self {
+            message_type,
+            flags,
+            serialization,
+            compression,
+        }
+    }
+
+    /// 是否携带 sequence 字段
+    pub fn has_sequence(&self) -> bool {
+        self.flags & FLAG_WITH_SEQUENCE != 0
+    }
+
+    /// 是否为最后一包
+    pub fn is_last(&self) -> bool {
+        self.flags & FLAG_LAST_PACKET != 0
+    }
+}
+
+/// 编码协议头
+pub fn encode_header(header: &ProtocolHeader) -> [u8; 4] {
+    [
+        (PROTOCOL_VERSION << 4) | HEADER_SIZE_UNITS,
+        (header.message_type.to_bits() << 4) | (header.flags & 0x0f),
+        (header.serialization.to_bits() << 4) | header.compression.to_bits(),
+        0x00,
+    ]
+}
+
+/// 解码协议头，返回协议头和头部字节长度
+pub fn decode_header(data: &[u8]) -> Result<(ProtocolHeader, usize), ASRError> {
+    if data.len() < 4 {
+        return Err(ASRError::InternalError(format!("协议头太短: {} bytes", data.len())));
+    }
+
+    let header_size = (data[0] & 0x0f) as usize * 4;
+    if header_size < 4 || data.len() < header_size {
+        return Err(ASRError::InternalError(format!("无效的协议头长度: {}", header_size)));
+    }
+
+    let message_type = MessageType::from_bits(data[1] >> 4)
+        .ok_or_else(|| ASRError::InternalError(format!("未知的消息类型: {:#x}", data[1] >> 4)))?;
+    let serialization = Serialization::from_bits(data[2] >> 4)
+        .ok_or_else(|| ASRError::InternalError(format!("未知的序列化方式: {:#x}", data[2] >> 4)))?;
+    let compression = CompressionType::from_bits(data[2] & 0x0f)
+        .ok_or_else(|| ASRError::InternalError(format!("未知的压缩方式: {:#x}", data[2] & 0x0f)))?;
+
+    Ok((
+        ProtocolHeader::new(message_type, data[1] & 0x0f, serialization, compression),
+        header_size,
+    ))
+}
+
+// ============================================================================
+// 帧编解码
+// ============================================================================
+
+/// Gzip 压缩
+pub fn gzip_compress(data: &[u8]) -> Result<Vec<u8>, ASRError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)
+        .map_err(|e| ASRError::InternalError(format!("Gzip 压缩失败: {}", e)))?;
+    encoder.finish()
+        .map_err(|e| ASRError::InternalError(format!("Gzip 完成失败: {}", e)))
+}
+
+/// Gzip 解压
+pub fn gzip_decompress(data: &[u8]) -> Result<Vec<u8>, ASRError> {
+    let mut decoder = GzDecoder::new(data);
+    let mut out = Vec::new();
+    decoder.read_to_end(&mut out)
+        .map_err(|e| ASRError::InternalError(format!("Gzip 解压失败: {}", e)))?;
+    Ok(out)
+}
+
+/// 构建一帧消息 (按协议头的压缩方式处理 payload)
+pub fn encode_frame(header: &ProtocolHeader, sequence: Option<i32>, payload: &[u8]) -> Result<Vec<u8>, ASRError> {
+    let payload = match header.compression {
+        CompressionType::Gzip => gzip_compress(payload)?,
+        CompressionType::None => payload.to_vec(),
+    };
+
+    let mut frame = Vec::with_capacity(12 + payload.len());
+    frame.extend_from_slice(&encode_header(header));
+    if let Some(seq) = sequence {
+        frame.extend_from_slice(&seq.to_be_bytes());
+    }
+    frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+/// 构建完整客户端请求，`language` 为空时使用服务端默认语言 (zh-CN)，
+/// `nbest` 为每次返回的候选数
+pub fn build_full_client_request(
+    app_id: &str,
+    access_token: &str,
+    cluster: &str,
+    request_id: &str,
+    language: Option<&str>,
+    nbest: usize,
+) -> Result<Vec<u8>, ASRError> {
+    let mut request = serde_json::json!({
+        "app": {"appid": app_id, "token": access_token, "cluster": cluster},
+        "user": {"uid": app_id},
+        "audio": {"format": "raw", "codec": "raw", "rate": 16000, "bits": 16, "channel": 1},
+        "request": {
+            "reqid": request_id,
+            "nbest": nbest.clamp(1, MAX_PARTIAL_ALTERNATIVES),
+            "sequence": 1,
+            "result_type": "full",
+            "show_utterances": false,
+        }
+    });
+    if let Some(language) = language {
+        request["audio"]["language"] = serde_json::json!(language);
+    }
+    let payload = serde_json::to_vec(&request)
+        .map_err(|e| ASRError::InternalError(format!("序列化配置失败: {}", e)))?;
+
+    let header = ProtocolHeader::new(
+        MessageType::FullClientRequest,
+        0x0,
+        Serialization::Json,
+        CompressionType::Gzip,
+    );
+    encode_frame(&header, None, &payload)
+}
+
+/// 构建音频请求
+pub fn build_audio_request(audio: &[u8], is_last: bool) -> Result<Vec<u8>, ASRError> {
+    let flags = if is_last { FLAG_LAST_PACKET } else { 0x0 };
+    let header = ProtocolHeader::new(
+        MessageType::AudioOnlyRequest,
+        flags,
+        Serialization::None,
+        CompressionType::Gzip,
+    );
+    encode_frame(&header, None, audio)
+}
+
+/// 构建鉴权 header 值
+pub fn build_auth_header(access_token: &str) -> String {
+    // 火山引擎要求 "Bearer;" 后接 token (分号分隔)
+    format!("Bearer; {}", access_token)
+}
+
+/// 识别响应
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecognitionResponse {
+    /// 识别文本 (整段)
+    pub text: String,
+    /// n-best 候选 (首个即 text，去重后最多 `MAX_PARTIAL_ALTERNATIVES` 个)
+    pub alternatives: Vec<String>,
+    /// 是否为最终结果
+    pub is_final: bool,
+}
+
+/// 解析服务端响应帧
+pub fn parse_server_response(data: &[u8]) -> Result<RecognitionResponse, ASRError> {
+    let (header, mut offset) = decode_header(data)?;
+
+    let read_u32 = |data: &[u8], offset: usize| -> Result<u32, ASRError> {
+        data.get(offset..offset + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| ASRError::InternalError("响应数据不完整".to_string()))
+    };
+
+    if header.message_type == MessageType::ServerError {
+        let code = read_u32(data, offset)?;
+        let message_len = read_u32(data, offset + 4).unwrap_or(0) as usize;
+        let message = data.get(offset + 8..offset + 8 + message_len)
+            .map(|b| String::from_utf8_lossy(b).to_string())
+            .unwrap_or_default();
+        return Err(map_error_code(code, &message));
+    }
+
+    if header.has_sequence() {
+        offset += 4;
+    }
+
+    let payload_size = read_u32(data, offset)? as usize;
+    offset += 4;
+    let payload = data.get(offset..offset + payload_size)
+        .ok_or_else(|| ASRError::InternalError(format!(
+            "数据不完整: 需要 {} bytes，实际 {} bytes",
+            offset + payload_size,
+            data.len()
+        )))?;
+
+    let payload = match header.compression {
+        CompressionType::Gzip => gzip_decompress(payload)?,
+        CompressionType::None => payload.to_vec(),
+    };
+
+    let json: serde_json::Value = serde_json::from_slice(&payload)
+        .map_err(|e| ASRError::InternalError(format!("JSON 解析失败: {}", e)))?;
+
+    let code = json["code"].as_u64().unwrap_or(CODE_SUCCESS as u64) as u32;
+    if code != CODE_SUCCESS {
+        let message = json["message"].as_str().unwrap_or("未知错误");
+        return Err(map_error_code(code, message));
+    }
+
+    let text = json["result"]
+        .as_array()
+        .and_then(|results| results.first())
+        .and_then(|r| r["text"].as_str())
+        .unwrap_or("")
+        .to_string();
+
+    let mut alternatives: Vec<String> = Vec::new();
+    for candidate in json["result"].as_array().into_iter().flatten().filter_map(|r| r["text"].as_str()) {
+        if !candidate.is_empty() && !alternatives.iter().any(|a| a == candidate) {
+            alternatives.push(candidate.to_string());
+        }
+    }
+    alternatives.truncate(MAX_PARTIAL_ALTERNATIVES);
+
+    // 服务端以负数 sequence 标记最后一包
+    let is_final = header.is_last() || json["sequence"].as_i64().is_some_and(|s| s < 0);
+
+    Ok(RecognitionResponse { text, alternatives, is_final })
+}
+
+/// 将服务端状态码映射为 ASRError
+fn map_error_code(code: u32, message: &str) -> ASRError {
+    match code {
+        CODE_AUTH_FAILED => ASRError::AuthFailed {
+            engine: "volcengine".to_string(),
+            message: message.to_string(),
+        },
+        CODE_RATE_LIMITED => ASRError::QuotaExceeded {
+            engine: "volcengine".to_string(),
+        },
+        _ => ASRError::websocket(format!("服务器返回错误: code={}, message={}", code, message)),
+    }
+}
+
+// ============================================================================
+// 引擎实现
+// ============================================================================
+
+/// 火山引擎 ASR 引擎
+pub struct VolcengineEngine {
+    app_id: String,
+    access_token: String,
+    cluster: String,
+    /// 请求的 n-best 候选数
+    nbest: usize,
+    #[allow(dead_code)]
+    retry_config: RetryConfig,
+}
+
+impl VolcengineEngine {
+    pub fn new(app_id: String, access_token: String, cluster: String) -> Self {
+        Self {
+            app_id,
+            access_token,
+            cluster,
+            nbest: 1,
+            retry_config: RetryConfig::default(),
+        }
+    }
+
+    /// 设置 partial 附带的候选数 (0 或 1 为只返回单一结果)
+    pub fn with_nbest(mut self, nbest: usize) -> Self {
+        self.nbest = nbest.clamp(1, MAX_PARTIAL_ALTERNATIVES);
+        self
+    }
+}
+
+#[async_trait]
+impl ASREngine for VolcengineEngine {
+    fn name(&self) -> &str {
+        "volcengine"
+    }
+
+    fn supported_modes(&self) -> Vec<ASRMode> {
+        vec![ASRMode::Realtime, ASRMode::Http]
+    }
+
+    fn audio_requirements(&self) -> AudioRequirements {
+        // 实时流与整段识别均按 16kHz 单声道裸 PCM 发送
+        AudioRequirements::pcm16_mono(16000)
+    }
+
+    /// 一次性转录：建立流式会话并分块发送整段音频
+    async fn transcribe(&self, audio: &AudioData) -> Result<String, ASRError> {
+        let mut session = self.create_realtime_session().await?;
+
+        let samples = convert_f32_to_i16(&audio.samples);
+        for chunk in samples.chunks(CHUNK_SAMPLES) {
+            let bytes: Vec<u8> = chunk.iter().flat_map(|s| s.to_le_bytes()).collect();
+            session.send_chunk(&bytes).await?;
+        }
+
+        session.close().await
+    }
+
+    async fn create_realtime_session(&self) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        let session = VolcengineSession::connect(
+            &self.app_id,
+            &self.access_token,
+            &self.cluster,
+            None,
+            self.nbest,
+        ).await?;
+
+        Ok(Box::new(session))
+    }
+
+    async fn create_realtime_session_with_language(&self, language: &str) -> Result<Box<dyn RealtimeSession>, ASRError> {
+        let session = VolcengineSession::connect(
+            &self.app_id,
+            &self.access_token,
+            &self.cluster,
+            Some(language),
+            self.nbest,
+        ).await?;
+
+        Ok(Box::new(session))
+    }
+}
+
+enum SessionCommand {
+    SendAudio(Vec<u8>),
+    Finish,
+}
+
+/// 火山引擎流式会话
+pub struct VolcengineSession {
+    cmd_sender: mpsc::Sender<SessionCommand>,
+    result_receiver: Option<oneshot::Receiver<Result<String, ASRError>>>,
+    partial_callback: SharedPartialCallback,
+    /// WebSocket 读写任务 (中止时据此关闭底层连接)
+    io_tasks: Vec<JoinHandle<()>>,
+}
+
+impl VolcengineSession {
+    async fn connect(
+        app_id: &str,
+        access_token: &str,
+        cluster: &str,
+        language: Option<&str>,
+        nbest: usize,
+    ) -> Result<Self, ASRError> {
+        let request_id = generate_request_id();
+
+        eprintln!("[INFO] 创建火山引擎 WebSocket 连接: cluster={}", cluster);
+
+        let request = http::Request::builder()
+            .uri(WEBSOCKET_URL)
+            .header("Host", "openspeech.bytedance.com")
+            .header("Connection", "Upgrade")
+            .header("Upgrade", "websocket")
+            .header("Sec-WebSocket-Version", "13")
+            .header("Sec-WebSocket-Key", tokio_tungstenite::tungstenite::handshake::client::generate_key())
+            .header("Authorization", build_auth_header(access_token))
+            .body(())
+            .map_err(|e| ASRError::websocket_with("构建请求失败", e))?;
+
+        let (ws_stream, _) = connect_async(request).await
+            .map_err(|e| ASRError::websocket_with("WebSocket 连接失败", e))?;
+
+        let (mut write, mut read) = ws_stream.split();
+
+        let msg = build_full_client_request(app_id, access_token, cluster, &request_id, language, nbest)?;
+        write.send(Message::Binary(msg.into())).await
+            .map_err(|e| ASRError::websocket_with("发送 Full Client Request 失败", e))?;
+
+        // 首个响应用于确认鉴权与参数
+        if let Some(response) = read.next().await {
+            match response {
+                Ok(Message::Binary(data)) => {
+                    parse_server_response(&data)?;
+                }
+                Ok(other) => {
+                    eprintln!("[WARN] 火山引擎 Full Client Request 收到非二进制响应: {:?}", other);
+                }
+                Err(e) => {
+                    return Err(ASRError::websocket_with("Full Client Request 响应错误", e));
+                }
+            }
+        }
+
+        eprintln!("[INFO] 火山引擎 WebSocket 会话已建立");
+
+        let (cmd_tx, mut cmd_rx) = mpsc::channel::<SessionCommand>(100);
+        let (result_tx, result_rx) = oneshot::channel::<Result<String, ASRError>>();
+        let (partial_tx, mut partial_rx) = mpsc::channel::<(String, Vec<String>)>(100);
+
+        let writer_task = tokio::spawn(async move {
+            while let Some(cmd) = cmd_rx.recv().await {
+                let (audio, is_last) = match cmd {
+                    SessionCommand::SendAudio(audio) => (audio, false),
+                    SessionCommand::Finish => (Vec::new(), true),
+                };
+                match build_audio_request(&audio, is_last) {
+                    Ok(msg) => {
+                        if let Err(e) = write.send(Message::Binary(msg.into())).await {
+                            eprintln!("[ERROR] 火山引擎发送音频失败: {}", e);
+                            break;
+                        }
+                    }
+                    Err(e) => eprintln!("[ERROR] 火山引擎构建音频消息失败: {}", e),
+                }
+                if is_last {
+                    break;
+                }
+            }
+        });
+
+        let reader_task = tokio::spawn(async move {
+            let mut latest_text = String::new();
+            let mut result_tx = Some(result_tx);
+
+            while let Some(msg) = read.next().await {
+                match msg {
+                    Ok(Message::Binary(data)) => match parse_server_response(&data) {
+                        Ok(response) => {
+                            if !response.text.is_empty() {
+                                latest_text = response.text;
+                                let _ = partial_tx.send((latest_text.clone(), response.alternatives)).await;
+                            }
+                            if response.is_final {
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("[ERROR] 火山引擎识别失败: {}", e);
+                            if let Some(tx) = result_tx.take() {
+                                let _ = tx.send(Err(e));
+                            }
+                            return;
+                        }
+                    },
+                    Ok(Message::Close(_)) => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        if let Some(tx) = result_tx.take() {
+                            let _ = tx.send(Err(ASRError::websocket_with("读取消息失败", e)));
+                        }
+                        return;
+                    }
+                }
+            }
+
+            if let Some(tx) = result_tx.take() {
+                let _ = tx.send(Ok(latest_text));
+            }
+        });
+
+        let partial_callback: SharedPartialCallback = Arc::new(std::sync::Mutex::new(None));
+        let partial_callback_clone = Arc::clone(&partial_callback);
+        tokio::spawn(async move {
+            while let Some((text, alternatives)) = partial_rx.recv().await {
+                if let Ok(slot) = partial_callback_clone.lock() {
+                    if let Some(ref cb) = *slot {
+                        cb(&text, &alternatives);
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            cmd_sender: cmd_tx,
+            io_tasks: vec![writer_task, reader_task],
+            result_receiver: Some(result_rx),
+            partial_callback,
+        })
+    }
+}
+
+#[async_trait]
+impl RealtimeSession for VolcengineSession {
+    async fn send_chunk(&mut self, chunk: &[u8]) -> Result<(), ASRError> {
+        self.cmd_sender.send(SessionCommand::SendAudio(chunk.to_vec())).await
+            .map_err(|_| ASRError::websocket("发送音频块失败：通道已关闭"))
+    }
+
+    async fn close(&mut self) -> Result<String, ASRError> {
+        let _ = self.cmd_sender.send(SessionCommand::Finish).await;
+
+        let result_rx = self.result_receiver.take()
+            .ok_or_else(|| ASRError::InternalError("会话已关闭".to_string()))?;
+
+        tokio::time::timeout(Duration::from_secs(TRANSCRIPTION_TIMEOUT_SECS), result_rx)
+            .await
+            .map_err(|_| ASRError::Timeout { timeout_ms: TRANSCRIPTION_TIMEOUT_SECS * 1000 })?
+            .map_err(|_| ASRError::InternalError("结果通道已关闭".to_string()))?
+    }
+
+    fn abort(&mut self) {
+        for task in self.io_tasks.drain(..) {
+            task.abort();
+        }
+    }
+
+    fn set_partial_callback(&mut self, callback: Box<dyn Fn(&str) + Send + 'static>) {
+        self.set_alternatives_callback(Box::new(move |text, _| callback(text)));
+    }
+
+    fn set_alternatives_callback(&mut self, callback: AlternativesCallback) {
+        if let Ok(mut slot) = self.partial_callback.lock() {
+            *slot = Some(callback);
+        }
+    }
+}
+
+fn generate_request_id() -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("req_{}", timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 构建服务端响应帧 (测试用)
+    fn server_frame(flags: u8, sequence: Option<i32>, json: &serde_json::Value) -> Vec<u8> {
+        let header = ProtocolHeader::new(
+            MessageType::FullServerResponse,
+            flags,
+            Serialization::Json,
+            CompressionType::Gzip,
+        );
+        encode_frame(&header, sequence, &serde_json::to_vec(json).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_header_roundtrip() {
+        let header = ProtocolHeader::new(
+            MessageType::AudioOnlyRequest,
+            FLAG_LAST_PACKET,
+            Serialization::None,
+            CompressionType::Gzip,
+        );
+        let bytes = encode_header(&header);
+        assert_eq!(bytes, [0x11, 0x22, 0x01, 0x00]);
+
+        let (decoded, size) = decode_header(&bytes).unwrap();
+        assert_eq!(decoded, header);
+        assert_eq!(size, 4);
+        assert!(decoded.is_last());
+        assert!(!decoded.has_sequence());
+    }
+
+    #[test]
+    fn test_full_client_request_header() {
+        let frame = build_full_client_request("app", "token", "volcengine_streaming_common", "req", None, 0).unwrap();
+        assert_eq!(&frame[..4], &[0x11, 0x10, 0x11, 0x00]);
+
+        let size = u32::from_be_bytes([frame[4], frame[5], frame[6], frame[7]]) as usize;
+        let json: serde_json::Value = serde_json::from_slice(&gzip_decompress(&frame[8..8 + size]).unwrap()).unwrap();
+        assert_eq!(json["app"]["cluster"], "volcengine_streaming_common");
+        assert_eq!(json["app"]["appid"], "app");
+        assert!(json["audio"]["language"].is_null());
+        assert_eq!(json["request"]["nbest"], 1);
+
+        let frame = build_full_client_request("app", "token", "volcengine_streaming_common", "req", Some("en-US"), 3).unwrap();
+        let size = u32::from_be_bytes([frame[4], frame[5], frame[6], frame[7]]) as usize;
+        let json: serde_json::Value = serde_json::from_slice(&gzip_decompress(&frame[8..8 + size]).unwrap()).unwrap();
+        assert_eq!(json["audio"]["language"], "en-US");
+        assert_eq!(json["request"]["nbest"], 3);
+    }
+
+    #[test]
+    fn test_decode_header_invalid() {
+        assert!(decode_header(&[0x11, 0x90]).is_err());
+        // 未知消息类型
+        assert!(decode_header(&[0x11, 0x50, 0x11, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_gzip_roundtrip() {
+        let data = vec![1u8, 2, 3, 4, 5, 0, 0, 0];
+        assert_eq!(gzip_decompress(&gzip_compress(&data).unwrap()).unwrap(), data);
+    }
+
+    #[test]
+    fn test_parse_partial_and_final() {
+        let partial = server_frame(0x0, None, &serde_json::json!({
+            "code": 1000, "sequence": 2, "result": [{"text": "你好"}, {"text": "您好"}, {"text": "你好"}, {"text": ""}]
+        }));
+        let response = parse_server_response(&partial).unwrap();
+        assert_eq!(response.text, "你好");
+        assert_eq!(response.alternatives, vec!["你好", "您好"]);
+        assert!(!response.is_final);
+
+        let last = server_frame(0x0, None, &serde_json::json!({
+            "code": 1000, "sequence": -3, "result": [{"text": "你好世界"}]
+        }));
+        let response = parse_server_response(&last).unwrap();
+        assert_eq!(response.text, "你好世界");
+        assert!(response.is_final);
+    }
+
+    #[test]
+    fn test_parse_auth_error() {
+        let frame = server_frame(0x0, None, &serde_json::json!({
+            "code": 1002, "message": "invalid token"
+        }));
+        assert!(matches!(parse_server_response(&frame), Err(ASRError::AuthFailed { .. })));
+
+        // 错误帧
+        let mut error_frame = encode_header(&ProtocolHeader::new(
+            MessageType::ServerError,
+            0x0,
+            Serialization::Json,
+            CompressionType::None,
+        )).to_vec();
+        error_frame.extend_from_slice(&1003u32.to_be_bytes());
+        error_frame.extend_from_slice(&4u32.to_be_bytes());
+        error_frame.extend_from_slice(b"busy");
+        assert!(matches!(parse_server_response(&error_frame), Err(ASRError::QuotaExceeded { .. })));
+    }
+
+    #[test]
+    fn test_auth_header() {
+        assert_eq!(build_auth_header("abc"), "Bearer; abc");
+    }
+}
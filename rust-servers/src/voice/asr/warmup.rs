@@ -0,0 +1,107 @@
+// 引擎预热
+// 连接创建引擎后在后台预热 (如探测本地推理服务的端口)，结果按引擎名记录在进程级表中，
+// 供健康检查判断服务是否就绪
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::voice::asr::ASREngine;
+
+/// 引擎预热状态
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum WarmupState {
+    Warming,
+    Ready,
+    Failed { error: String },
+}
+
+fn registry() -> &'static Mutex<HashMap<String, WarmupState>> {
+    static STATES: OnceLock<Mutex<HashMap<String, WarmupState>>> = OnceLock::new();
+    STATES.get_or_init(Default::default)
+}
+
+fn set_state(name: &str, state: WarmupState) {
+    registry().lock().unwrap_or_else(|e| e.into_inner()).insert(name.to_string(), state);
+}
+
+/// 在后台预热引擎；同名引擎已就绪或正在预热时跳过，上次失败的重新预热
+pub fn spawn_warmup(engine: Arc<dyn ASREngine>) {
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    let name = engine.name().to_string();
+    {
+        let mut states = registry().lock().unwrap_or_else(|e| e.into_inner());
+        if matches!(states.get(&name), Some(WarmupState::Warming | WarmupState::Ready)) {
+            return;
+        }
+        states.insert(name.clone(), WarmupState::Warming);
+    }
+
+    runtime.spawn(async move {
+        let state = match engine.warm_up().await {
+            Ok(()) => WarmupState::Ready,
+            Err(e) => {
+                eprintln!("[WARN] [Voice] 引擎 {} 预热失败: {}", name, e);
+                WarmupState::Failed { error: e.to_string() }
+            }
+        };
+        set_state(&name, state);
+    });
+}
+
+/// 各引擎的预热状态 (按引擎名排序)
+pub fn snapshot() -> BTreeMap<String, WarmupState> {
+    registry()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|(name, state)| (name.clone(), state.clone()))
+        .collect()
+}
+
+/// 引擎是否可服务：没有正在预热的引擎，且不是全部预热失败 (尚未创建任何引擎时视为就绪)
+pub fn engines_ready(states: &BTreeMap<String, WarmupState>) -> bool {
+    !states.values().any(|state| *state == WarmupState::Warming)
+        && (states.is_empty() || states.values().any(|state| *state == WarmupState::Ready))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voice::asr::fake::FakeEngine;
+    use crate::voice::asr::ASRError;
+
+    fn warmup_engine(name: &str, fail: bool) -> Arc<FakeEngine> {
+        Arc::new(FakeEngine::http(name).with_warm_up(move || async move {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            if fail {
+                Err(ASRError::network("connection refused"))
+            } else {
+                Ok(())
+            }
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_warmup_states_and_readiness() {
+        spawn_warmup(warmup_engine("warmup-test-ok", false));
+        spawn_warmup(warmup_engine("warmup-test-down", true));
+        assert_eq!(snapshot()["warmup-test-ok"], WarmupState::Warming);
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let states = snapshot();
+        assert_eq!(states["warmup-test-ok"], WarmupState::Ready);
+        assert!(matches!(&states["warmup-test-down"], WarmupState::Failed { error } if error.contains("refused")));
+
+        let only = |name: &str| states.iter().filter(|(n, _)| *n == name).map(|(n, s)| (n.clone(), s.clone())).collect();
+        assert!(engines_ready(&only("warmup-test-ok")));
+        assert!(!engines_ready(&only("warmup-test-down")));
+        assert!(engines_ready(&BTreeMap::new()));
+        let mut warming = only("warmup-test-ok");
+        warming.insert("other".to_string(), WarmupState::Warming);
+        assert!(!engines_ready(&warming));
+    }
+}
@@ -0,0 +1,225 @@
+// 回声消除 (AEC)
+// 扬声器外放的声音会被麦克风录入形成回声。已知播放内容 (参考信号) 时，
+// 先用互相关估计参考信号在录音中的延迟，再用 NLMS 自适应滤波器估计回声路径并从录音中减去
+
+use super::recorder::{resample, to_mono};
+use super::AudioData;
+
+/// 自适应滤波器长度 (毫秒)，需覆盖扬声器到麦克风的回声路径
+const FILTER_MS: usize = 16;
+
+/// NLMS 步长 (0-2，越大收敛越快、稳态误差越大)
+const STEP_SIZE: f32 = 0.5;
+
+/// 归一化时的正则项，避免参考信号静音时除零
+const REGULARIZATION: f32 = 1e-6;
+
+/// 参考信号与录音之间允许的最大偏移 (毫秒，正负两个方向)
+const MAX_DELAY_MS: usize = 500;
+
+/// 估计延迟时使用的参考信号时长上限 (毫秒)
+const CORRELATION_MS: usize = 1000;
+
+/// 估计延迟时粗搜的降采样倍数
+const DECIMATION: usize = 4;
+
+/// 参考信号的 RMS 低于此值时视为静音，不做处理
+const SILENT_REFERENCE_RMS: f32 = 1e-4;
+
+/// 从麦克风录音中消除参考信号 (播放内容) 的回声
+///
+/// 参考信号先转为单声道并重采样到录音的采样率，再按估计的延迟对齐：
+/// 参考信号比录音长时截断，短时补零，延迟超出 ±500ms 的部分视为未对齐的静音。
+/// 多声道录音逐声道独立滤波，输出的采样率与声道数与录音一致
+pub fn acoustic_echo_cancel(mic: &AudioData, reference: &AudioData) -> AudioData {
+    if mic.is_empty() || reference.is_empty() || mic.sample_rate == 0 || mic.channels == 0 {
+        return mic.clone();
+    }
+
+    let reference = resample(
+        &to_mono(&reference.samples, reference.channels.max(1)),
+        reference.sample_rate.max(1),
+        mic.sample_rate,
+    );
+    if rms(&reference) < SILENT_REFERENCE_RMS {
+        return mic.clone();
+    }
+
+    let channels = mic.channels as usize;
+    let frames = mic.samples.len() / channels;
+    let max_delay = MAX_DELAY_MS * mic.sample_rate as usize / 1000;
+    let mono_mic = to_mono(&mic.samples[..frames * channels], mic.channels);
+    let delay = estimate_delay(&mono_mic, &reference, max_delay, CORRELATION_MS * mic.sample_rate as usize / 1000);
+    let aligned = align(&reference, delay, frames);
+
+    let taps = (FILTER_MS * mic.sample_rate as usize / 1000).max(1);
+    let mut output = mic.samples.clone();
+    for channel in 0..channels {
+        let near: Vec<f32> = (0..frames).map(|i| mic.samples[i * channels + channel]).collect();
+        for (i, sample) in nlms(&near, &aligned, taps).into_iter().enumerate() {
+            output[i * channels + channel] = sample;
+        }
+    }
+
+    AudioData::new(output, mic.sample_rate, mic.channels)
+}
+
+/// 估计参考信号在录音中的延迟 (采样数，负数表示参考信号超前于录音开始)
+///
+/// 在 ±max_delay 范围内取归一化互相关最大的偏移：先在降采样后的信号上粗搜，再在原始采样率下细化
+fn estimate_delay(mic: &[f32], reference: &[f32], max_delay: usize, window: usize) -> isize {
+    let reference = &reference[..reference.len().min(window.max(1))];
+    let coarse = best_lag(
+        &decimate(mic),
+        &decimate(reference),
+        -((max_delay / DECIMATION) as isize)..=(max_delay / DECIMATION) as isize,
+    ) * DECIMATION as isize;
+    let refine = DECIMATION as isize;
+    best_lag(mic, reference, (coarse - refine).max(-(max_delay as isize))..=(coarse + refine).min(max_delay as isize))
+}
+
+/// 按块平均降采样
+fn decimate(samples: &[f32]) -> Vec<f32> {
+    samples.chunks(DECIMATION).map(|chunk| chunk.iter().sum::<f32>() / chunk.len() as f32).collect()
+}
+
+/// 在给定范围内取归一化互相关最大的偏移
+fn best_lag(mic: &[f32], reference: &[f32], lags: std::ops::RangeInclusive<isize>) -> isize {
+    let mut best = (0isize, f32::MIN);
+    for delay in lags {
+        let mut dot = 0.0f32;
+        let mut energy = 0.0f32;
+        let start = (-delay).max(0) as usize;
+        for (k, &r) in reference.iter().enumerate().skip(start) {
+            let Some(&m) = mic.get((k as isize + delay) as usize) else {
+                break;
+            };
+            dot += m * r;
+            energy += m * m;
+        }
+        if energy > 0.0 {
+            let score = dot.abs() / energy.sqrt();
+            if score > best.1 {
+                best = (delay, score);
+            }
+        }
+    }
+    best.0
+}
+
+/// 按延迟把参考信号对齐到录音时间轴，长度与录音一致 (超出截断、不足补零)
+fn align(reference: &[f32], delay: isize, len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| {
+            let index = i as isize - delay;
+            if index < 0 {
+                0.0
+            } else {
+                reference.get(index as usize).copied().unwrap_or(0.0)
+            }
+        })
+        .collect()
+}
+
+/// NLMS 自适应滤波，返回误差信号 (即去除回声后的录音)
+///
+/// 参考信号静音的时段不更新滤波器，避免近端语音使滤波器发散
+fn nlms(near: &[f32], reference: &[f32], taps: usize) -> Vec<f32> {
+    let mut weights = vec![0.0f32; taps];
+    let mut history = vec![0.0f32; taps];
+    let mut power = 0.0f32;
+    // 连续为零的参考采样数，窗口全为零时回声估计为零，直接输出录音
+    let mut zero_run = taps;
+    let mut output = Vec::with_capacity(near.len());
+
+    for (n, &d) in near.iter().enumerate() {
+        let incoming = reference.get(n).copied().unwrap_or(0.0);
+        zero_run = if incoming == 0.0 { zero_run + 1 } else { 0 };
+        if zero_run > taps {
+            output.push(d);
+            continue;
+        }
+
+        // 滑动窗口：history[0] 为最新的参考采样
+        let outgoing = history[taps - 1];
+        history.rotate_right(1);
+        history[0] = incoming;
+        power = (power + incoming * incoming - outgoing * outgoing).max(0.0);
+
+        let estimate: f32 = weights.iter().zip(&history).map(|(w, x)| w * x).sum();
+        let error = d - estimate;
+        output.push(error);
+
+        if power / taps as f32 > SILENT_REFERENCE_RMS * SILENT_REFERENCE_RMS {
+            let gain = STEP_SIZE * error / (power + REGULARIZATION);
+            for (w, x) in weights.iter_mut().zip(&history) {
+                *w += gain * x;
+            }
+        }
+    }
+    output
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(freq: f32, len: usize, amplitude: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * freq * i as f32 / 16000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_cancels_delayed_echo() {
+        // 参考信号为扫频 (近似提示音)，回声延迟 40ms、衰减 0.6
+        let reference: Vec<f32> = (0..8000)
+            .map(|i| {
+                let t = i as f32 / 16000.0;
+                0.5 * (2.0 * std::f32::consts::PI * (440.0 + 440.0 * t) * t).sin()
+            })
+            .collect();
+        let delay = 640;
+        let speech = tone(300.0, 16000, 0.05);
+        let mic: Vec<f32> = (0..16000)
+            .map(|i| speech[i] + if i >= delay && i - delay < reference.len() { 0.6 * reference[i - delay] } else { 0.0 })
+            .collect();
+
+        assert_eq!(estimate_delay(&mic, &reference, 8000, 16000), delay as isize);
+        assert_eq!(estimate_delay(&mic[delay..], &reference, 8000, 16000), 0);
+
+        let mic = AudioData::new(mic, 16000, 1);
+        // 参考信号以 44.1kHz 提供，需先重采样
+        let reference_44k = AudioData::new(resample(&reference, 16000, 44100), 44100, 1);
+        let cleaned = acoustic_echo_cancel(&mic, &reference_44k);
+        assert_eq!(cleaned.samples.len(), mic.samples.len());
+
+        // 收敛后 (后半段回声区间) 残余应远小于原始回声
+        let residual: Vec<f32> = cleaned.samples[4000..8600].iter().zip(&speech[4000..8600]).map(|(c, s)| c - s).collect();
+        let echo: Vec<f32> = mic.samples[4000..8600].iter().zip(&speech[4000..8600]).map(|(m, s)| m - s).collect();
+        assert!(rms(&residual) < rms(&echo) * 0.2, "residual {} echo {}", rms(&residual), rms(&echo));
+    }
+
+    #[test]
+    fn test_tolerates_misaligned_or_silent_reference() {
+        let mic = AudioData::new(tone(300.0, 3200, 0.1), 16000, 2);
+
+        // 参考信号比录音长且与录音无关
+        let long_reference = AudioData::new(tone(500.0, 48000, 0.3), 16000, 1);
+        let cleaned = acoustic_echo_cancel(&mic, &long_reference);
+        assert_eq!((cleaned.samples.len(), cleaned.channels), (3200, 2));
+        assert!(cleaned.samples.iter().all(|s| s.is_finite()));
+
+        // 静音或空参考信号时原样返回
+        let silent = AudioData::new(vec![0.0; 1600], 16000, 1);
+        assert_eq!(acoustic_echo_cancel(&mic, &silent).samples, mic.samples);
+        assert_eq!(acoustic_echo_cancel(&mic, &AudioData::new(Vec::new(), 16000, 1)).samples, mic.samples);
+    }
+}
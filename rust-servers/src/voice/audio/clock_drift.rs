@@ -0,0 +1,137 @@
+// 采样时钟漂移估计
+// 声卡晶振与名义采样率存在微小偏差 (通常几十到几百 ppm)，长录音按名义速率重采样会累积时长漂移。
+// 这里以回调到达时间为横轴、累计帧数为纵轴做在线线性回归，斜率即设备的实际采样率
+
+use serde::Serialize;
+use std::time::Instant;
+
+/// 开始给出估计所需的最短观测时长 (秒)，太短时回调抖动占比过大
+const MIN_ESTIMATE_SECS: f64 = 5.0;
+
+/// 实测速率与名义速率的最大可信偏差，超出时视为回调停顿等异常而不做修正
+const MAX_DRIFT_RATIO: f64 = 0.01;
+
+/// 设备采样时钟
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DeviceClock {
+    /// 设备声明的采样率
+    pub nominal_rate: u32,
+    /// 根据回调时间估计出的实际采样率
+    pub measured_rate: f64,
+}
+
+impl DeviceClock {
+    /// 相对名义速率的偏差 (ppm，快为正)
+    pub fn drift_ppm(&self) -> f64 {
+        (self.measured_rate / self.nominal_rate as f64 - 1.0) * 1e6
+    }
+}
+
+/// 设备采样率在线估计器
+#[derive(Debug)]
+pub struct ClockDriftEstimator {
+    nominal_rate: u32,
+    /// 第一次回调的时间与累计帧数，之后的观测都相对于它
+    origin: Option<Instant>,
+    frames: u64,
+    /// 在线回归 (Welford 形式，避免长录音下大数相减的精度损失)
+    count: u64,
+    mean_t: f64,
+    mean_n: f64,
+    m2_t: f64,
+    c_tn: f64,
+    /// 最后一次观测的相对时间 (秒)
+    last_t: f64,
+}
+
+impl ClockDriftEstimator {
+    pub fn new(nominal_rate: u32) -> Self {
+        Self {
+            nominal_rate,
+            origin: None,
+            frames: 0,
+            count: 0,
+            mean_t: 0.0,
+            mean_n: 0.0,
+            m2_t: 0.0,
+            c_tn: 0.0,
+            last_t: 0.0,
+        }
+    }
+
+    /// 记录一次回调：`frames` 为本次回调的帧数 (每声道采样数)，`now` 为回调到达时间
+    pub fn record(&mut self, frames: usize, now: Instant) {
+        let Some(origin) = self.origin else {
+            // 第一块的到达时间包含设备启动延迟，只作为起点
+            self.origin = Some(now);
+            return;
+        };
+        self.frames += frames as u64;
+
+        let t = now.saturating_duration_since(origin).as_secs_f64();
+        let n = self.frames as f64;
+        self.count += 1;
+        let dt = t - self.mean_t;
+        self.mean_t += dt / self.count as f64;
+        self.mean_n += (n - self.mean_n) / self.count as f64;
+        self.m2_t += dt * (t - self.mean_t);
+        self.c_tn += dt * (n - self.mean_n);
+        self.last_t = t;
+    }
+
+    /// 估计出的设备时钟，观测不足或偏差不可信时返回 None
+    pub fn estimate(&self) -> Option<DeviceClock> {
+        if self.last_t < MIN_ESTIMATE_SECS || self.m2_t <= 0.0 {
+            return None;
+        }
+        let measured_rate = self.c_tn / self.m2_t;
+        let nominal = self.nominal_rate as f64;
+        if (measured_rate / nominal - 1.0).abs() > MAX_DRIFT_RATIO {
+            return None;
+        }
+        Some(DeviceClock { nominal_rate: self.nominal_rate, measured_rate })
+    }
+
+    /// 重采样应使用的源速率 (有可信估计时为实测值，否则为名义值)
+    pub fn effective_rate(&self) -> f64 {
+        self.estimate().map_or(self.nominal_rate as f64, |clock| clock.measured_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// 按实际速率 `rate` 产生每块 `block` 帧的回调，到达时间带有 ±`jitter_ms` 的交替抖动
+    fn feed(estimator: &mut ClockDriftEstimator, rate: f64, block: usize, seconds: f64, jitter_ms: f64) {
+        let origin = Instant::now();
+        let blocks = (seconds * rate / block as f64) as usize;
+        for i in 0..=blocks {
+            let jitter = if i % 2 == 0 { jitter_ms } else { -jitter_ms };
+            let t = ((i * block) as f64 / rate + 0.004 + jitter / 1000.0).max(0.0);
+            estimator.record(block, origin + Duration::from_secs_f64(t));
+        }
+    }
+
+    #[test]
+    fn test_estimate_device_rate() {
+        // 快 200ppm 的 48kHz 设备，10ms 回调带 ±2ms 抖动
+        let mut estimator = ClockDriftEstimator::new(48000);
+        feed(&mut estimator, 48009.6, 480, 60.0, 2.0);
+        let clock = estimator.estimate().unwrap();
+        assert!((clock.drift_ppm() - 200.0).abs() < 20.0, "{}", clock.drift_ppm());
+        assert_eq!(estimator.effective_rate(), clock.measured_rate);
+
+        // 观测时间不足时沿用名义速率
+        let mut short = ClockDriftEstimator::new(44100);
+        feed(&mut short, 44100.0, 441, 2.0, 0.0);
+        assert!(short.estimate().is_none());
+        assert_eq!(short.effective_rate(), 44100.0);
+
+        // 偏差过大 (如回调停顿) 不可信
+        let mut stalled = ClockDriftEstimator::new(16000);
+        feed(&mut stalled, 15000.0, 160, 10.0, 0.0);
+        assert!(stalled.estimate().is_none());
+    }
+}
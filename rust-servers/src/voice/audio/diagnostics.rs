@@ -0,0 +1,340 @@
+// 音频诊断模块
+// 统计峰值、RMS、响度、削波与静音比例，并对疑似错误的采样率做合理性校验
+// `RunningDiagnostics` 在录音回调中逐块累积，录音过程中即可发现削波与采样率异常
+
+use std::time::Instant;
+
+use super::clock_drift::DeviceClock;
+use super::loudness::measure_lufs;
+use super::utils::{calculate_peak, calculate_raw_rms, clipping_ratio, CLIPPING_LEVEL, VAD_THRESHOLD};
+use super::AudioData;
+
+/// 静音统计的分帧时长 (毫秒)
+const SILENCE_FRAME_MS: u32 = 20;
+
+/// 采样率推断所需的最短录音时长 (毫秒)，过短时启动延迟占比太大
+const MIN_WALL_CLOCK_MS: u64 = 1000;
+
+/// 音频时长与实际录音时长的容许偏差范围
+const DURATION_RATIO_RANGE: (f64, f64) = (0.75, 1.33);
+
+/// 常见采样率 (用于推断结果对齐)
+const COMMON_SAMPLE_RATES: [u32; 8] = [8000, 11025, 16000, 22050, 24000, 32000, 44100, 48000];
+
+// ============================================================================
+// 诊断结果
+// ============================================================================
+
+/// 音频诊断结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioDiagnostics {
+    /// 峰值 (0.0 - 1.0)
+    pub peak: f32,
+    /// 原始 RMS
+    pub rms: f32,
+    /// 积分响度 (LUFS，静音时为负无穷)
+    pub loudness_lufs: f32,
+    /// 削波样本占比 (0.0 - 1.0)
+    pub clipping_ratio: f32,
+    /// 静音帧占比 (0.0 - 1.0)
+    pub silence_ratio: f32,
+    /// 音频时长 (毫秒)
+    pub duration_ms: u64,
+    /// 录音设备的实测采样时钟 (由录音器估计，`diagnose` 本身不填写)
+    pub device_clock: Option<DeviceClock>,
+}
+
+/// 诊断音频数据
+pub fn diagnose(audio: &AudioData) -> AudioDiagnostics {
+    let samples = &audio.samples;
+
+    AudioDiagnostics {
+        peak: calculate_peak(samples),
+        rms: calculate_raw_rms(samples),
+        loudness_lufs: measure_lufs(audio),
+        clipping_ratio: clipping_ratio(samples),
+        silence_ratio: silence_ratio(audio),
+        duration_ms: audio.duration_ms,
+        device_clock: None,
+    }
+}
+
+/// 计算静音帧占比
+fn silence_ratio(audio: &AudioData) -> f32 {
+    let frame_len = (audio.sample_rate as usize * audio.channels.max(1) as usize
+        * SILENCE_FRAME_MS as usize)
+        / 1000;
+    if audio.samples.is_empty() || frame_len == 0 {
+        return 0.0;
+    }
+
+    let frames: Vec<&[f32]> = audio.samples.chunks(frame_len).collect();
+    let silent = frames
+        .iter()
+        .filter(|frame| calculate_raw_rms(frame) < VAD_THRESHOLD)
+        .count();
+    silent as f32 / frames.len() as f32
+}
+
+// ============================================================================
+// 增量诊断
+// ============================================================================
+
+/// 录音过程中逐块累积的诊断统计
+///
+/// 静音按与 `diagnose` 相同的 20ms 分帧统计，跨块的帧会拼接后再判断。
+/// 响度需要整段音频做门限，只在停止后由 `diagnose` 计算。
+#[derive(Debug, Clone)]
+pub struct RunningDiagnostics {
+    sample_rate: u32,
+    channels: u16,
+    frame_len: usize,
+    samples: u64,
+    peak: f32,
+    sum_squares: f64,
+    clipped: u64,
+    frames: u64,
+    silent_frames: u64,
+    /// 未满一帧的尾部 (样本数与平方和)
+    frame_fill: usize,
+    frame_squares: f64,
+    /// 第一块到达的时间，采样率推断以它为起点，不受设备启动延迟影响
+    started: Option<Instant>,
+}
+
+impl RunningDiagnostics {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            sample_rate,
+            channels,
+            frame_len: (sample_rate as usize * channels.max(1) as usize * SILENCE_FRAME_MS as usize) / 1000,
+            samples: 0,
+            peak: 0.0,
+            sum_squares: 0.0,
+            clipped: 0,
+            frames: 0,
+            silent_frames: 0,
+            frame_fill: 0,
+            frame_squares: 0.0,
+            started: None,
+        }
+    }
+
+    /// 累积一块交错采样
+    pub fn push(&mut self, samples: &[f32]) {
+        self.started.get_or_insert_with(Instant::now);
+        for &sample in samples {
+            let abs = sample.abs();
+            self.peak = self.peak.max(abs);
+            if abs >= CLIPPING_LEVEL {
+                self.clipped += 1;
+            }
+            let square = sample as f64 * sample as f64;
+            self.sum_squares += square;
+
+            if self.frame_len > 0 {
+                self.frame_squares += square;
+                self.frame_fill += 1;
+                if self.frame_fill == self.frame_len {
+                    self.close_frame();
+                }
+            }
+        }
+        self.samples += samples.len() as u64;
+    }
+
+    fn close_frame(&mut self) {
+        let rms = (self.frame_squares / self.frame_fill as f64).sqrt() as f32;
+        self.frames += 1;
+        if rms < VAD_THRESHOLD {
+            self.silent_frames += 1;
+        }
+        self.frame_fill = 0;
+        self.frame_squares = 0.0;
+    }
+
+    /// 已累积音频的时长 (毫秒)
+    pub fn duration_ms(&self) -> u64 {
+        let per_second = self.sample_rate as u64 * self.channels.max(1) as u64;
+        if per_second == 0 {
+            return 0;
+        }
+        self.samples * 1000 / per_second
+    }
+
+    /// 当前的削波样本占比
+    pub fn clipping_ratio(&self) -> f32 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        self.clipped as f32 / self.samples as f32
+    }
+
+    /// 当前统计 (未满一帧的尾部按一帧计入静音比例，与 `diagnose` 一致)
+    pub fn snapshot(&self) -> AudioDiagnostics {
+        let mut tail = self.clone();
+        if tail.frame_fill > 0 {
+            tail.close_frame();
+        }
+        AudioDiagnostics {
+            peak: self.peak,
+            rms: if self.samples == 0 {
+                0.0
+            } else {
+                (self.sum_squares / self.samples as f64).sqrt() as f32
+            },
+            loudness_lufs: f32::NEG_INFINITY,
+            clipping_ratio: self.clipping_ratio(),
+            silence_ratio: if tail.frames == 0 {
+                0.0
+            } else {
+                tail.silent_frames as f32 / tail.frames as f32
+            },
+            duration_ms: self.duration_ms(),
+            device_clock: None,
+        }
+    }
+
+    /// 根据第一块到达以来的实际时长推断疑似的真实采样率，规则同 `infer_sample_rate_mismatch`
+    pub fn sample_rate_mismatch(&self) -> Option<u32> {
+        let started = self.started?;
+        self.rate_mismatch_after(started.elapsed().as_millis() as u64)
+    }
+
+    fn rate_mismatch_after(&self, wall_clock_ms: u64) -> Option<u32> {
+        if self.samples == 0 {
+            return None;
+        }
+        infer_rate(self.sample_rate, self.duration_ms(), wall_clock_ms)
+    }
+}
+
+// ============================================================================
+// 采样率校验
+// ============================================================================
+
+/// 根据实际录音时长推断疑似的真实采样率
+///
+/// 若按声明采样率计算的音频时长与实际录音时长明显不符，返回推断出的采样率；
+/// 否则返回 None。例如声明 16kHz 但样本数是录音时长的 3 倍，则推断为 48kHz。
+pub fn infer_sample_rate_mismatch(audio: &AudioData, wall_clock_ms: u64) -> Option<u32> {
+    if audio.is_empty() {
+        return None;
+    }
+    infer_rate(audio.sample_rate, audio.duration_ms, wall_clock_ms)
+}
+
+fn infer_rate(sample_rate: u32, duration_ms: u64, wall_clock_ms: u64) -> Option<u32> {
+    if wall_clock_ms < MIN_WALL_CLOCK_MS || sample_rate == 0 {
+        return None;
+    }
+
+    let ratio = duration_ms as f64 / wall_clock_ms as f64;
+    if ratio >= DURATION_RATIO_RANGE.0 && ratio <= DURATION_RATIO_RANGE.1 {
+        return None;
+    }
+
+    let estimated = sample_rate as f64 * ratio;
+    let snapped = COMMON_SAMPLE_RATES
+        .iter()
+        .copied()
+        .min_by(|a, b| {
+            let da = (*a as f64 - estimated).abs();
+            let db = (*b as f64 - estimated).abs();
+            da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .unwrap_or(sample_rate);
+
+    if snapped == sample_rate {
+        None
+    } else {
+        Some(snapped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnose_empty() {
+        let audio = AudioData::new(Vec::new(), 16000, 1);
+        let diag = diagnose(&audio);
+
+        assert_eq!(diag.peak, 0.0);
+        assert_eq!(diag.clipping_ratio, 0.0);
+        assert_eq!(diag.silence_ratio, 0.0);
+    }
+
+    #[test]
+    fn test_diagnose_clipping_and_silence() {
+        // 前半秒静音，后半秒满幅方波
+        let mut samples = vec![0.0f32; 8000];
+        samples.extend((0..8000).map(|i| if i % 2 == 0 { 1.0 } else { -1.0 }));
+        let audio = AudioData::new(samples, 16000, 1);
+        let diag = diagnose(&audio);
+
+        assert_eq!(diag.peak, 1.0);
+        assert!((diag.clipping_ratio - 0.5).abs() < 1e-6);
+        assert!((diag.silence_ratio - 0.5).abs() < 1e-6);
+        assert_eq!(diag.duration_ms, 1000);
+    }
+
+    #[test]
+    fn test_sample_rate_consistent() {
+        let audio = AudioData::new(vec![0.1f32; 32000], 16000, 1);
+        assert_eq!(infer_sample_rate_mismatch(&audio, 2000), None);
+        // 录音时长过短不做推断
+        assert_eq!(infer_sample_rate_mismatch(&audio, 500), None);
+    }
+
+    #[test]
+    fn test_sample_rate_mismatch_48k() {
+        // 实际录了 1 秒 48kHz 数据，却按 16kHz 声明
+        let audio = AudioData::new(vec![0.1f32; 48000], 16000, 1);
+        assert_eq!(infer_sample_rate_mismatch(&audio, 1000), Some(48000));
+    }
+
+    #[test]
+    fn test_sample_rate_mismatch_8k() {
+        let audio = AudioData::new(vec![0.1f32; 16000], 16000, 1);
+        assert_eq!(infer_sample_rate_mismatch(&audio, 2000), Some(8000));
+    }
+
+    #[test]
+    fn test_running_diagnostics_matches_diagnose() {
+        // 与 test_diagnose_clipping_and_silence 相同的数据，按不整齐的块送入
+        let mut samples = vec![0.0f32; 8000];
+        samples.extend((0..8000).map(|i| if i % 2 == 0 { 1.0 } else { -1.0 }));
+        samples.extend(vec![0.0f32; 100]);
+        let audio = AudioData::new(samples.clone(), 16000, 1);
+
+        let mut running = RunningDiagnostics::new(16000, 1);
+        for block in samples.chunks(333) {
+            running.push(block);
+        }
+        let expected = diagnose(&audio);
+        let actual = running.snapshot();
+
+        assert_eq!(actual.peak, expected.peak);
+        assert!((actual.rms - expected.rms).abs() < 1e-5);
+        assert!((actual.clipping_ratio - expected.clipping_ratio).abs() < 1e-6);
+        assert!((actual.silence_ratio - expected.silence_ratio).abs() < 1e-6);
+        assert_eq!(actual.duration_ms, expected.duration_ms);
+    }
+
+    #[test]
+    fn test_running_sample_rate_mismatch_mid_stream() {
+        // 声明 16kHz，实际每秒到达 48000 个采样
+        let mut running = RunningDiagnostics::new(16000, 1);
+        running.push(&vec![0.1f32; 24000]);
+        // 录音不足 1 秒时不做推断
+        assert_eq!(running.rate_mismatch_after(500), None);
+        running.push(&vec![0.1f32; 24000]);
+        assert_eq!(running.rate_mismatch_after(1000), Some(48000));
+
+        let mut consistent = RunningDiagnostics::new(16000, 1);
+        consistent.push(&vec![0.1f32; 16000]);
+        assert_eq!(consistent.rate_mismatch_after(1000), None);
+    }
+}
@@ -1,11 +1,13 @@
 // 音频编码模块
 // 使用 hound 实现 WAV 编码
 
-use hound::{SampleFormat, WavSpec, WavWriter};
-use std::io::Cursor;
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
-use super::recorder::TARGET_SAMPLE_RATE;
+use super::recorder::{f32_to_i16, TARGET_SAMPLE_RATE};
 use super::AudioData;
 
 /// 编码错误类型
@@ -56,11 +58,17 @@ impl WavEncoder {
     }
 
     /// 将 AudioData 编码为 WAV 格式字节数组
+    ///
+    /// 音频保留了原始 i16 采样时直接透传，不经 f32 往返
     pub fn encode(&self, audio: &AudioData) -> Result<Vec<u8>, EncodingError> {
         if audio.is_empty() {
             return Err(EncodingError::InvalidAudioData);
         }
 
+        if let Some(pcm) = audio.pcm_i16() {
+            return self.encode_i16_samples(pcm);
+        }
+
         let spec = WavSpec {
             channels: self.channels,
             sample_rate: self.sample_rate,
@@ -72,9 +80,7 @@ impl WavEncoder {
         {
             let mut writer = WavWriter::new(&mut cursor, spec)?;
             for &sample in audio.samples.iter() {
-                let amplitude =
-                    (sample * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
-                writer.write_sample(amplitude)?;
+                writer.write_sample(f32_to_i16(sample))?;
             }
             writer.finalize()?;
         }
@@ -99,9 +105,7 @@ impl WavEncoder {
         {
             let mut writer = WavWriter::new(&mut cursor, spec)?;
             for &sample in samples.iter() {
-                let amplitude =
-                    (sample * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
-                writer.write_sample(amplitude)?;
+                writer.write_sample(f32_to_i16(sample))?;
             }
             writer.finalize()?;
         }
@@ -160,3 +164,238 @@ pub fn encode_i16_to_wav(
     let encoder = WavEncoder::new(sample_rate, channels, 16);
     encoder.encode_i16_samples(samples)
 }
+
+// ============================================================================
+// 增量 WAV 写入 (边录边写)
+// ============================================================================
+
+/// 增量 WAV 写入器
+///
+/// 录音过程中持续追加采样，并定期回填头部，进程崩溃时文件仍可读到最近一次 flush 的位置
+pub struct IncrementalWavWriter {
+    writer: WavWriter<BufWriter<File>>,
+    path: PathBuf,
+    samples_since_flush: usize,
+    flush_every: usize,
+}
+
+impl IncrementalWavWriter {
+    /// 创建写入器，默认约每秒音频回填一次头部
+    pub fn create(path: impl AsRef<Path>, sample_rate: u32, channels: u16) -> Result<Self, EncodingError> {
+        let spec = WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let path = path.as_ref().to_path_buf();
+        let writer = WavWriter::create(&path, spec)?;
+
+        Ok(Self {
+            writer,
+            path,
+            samples_since_flush: 0,
+            flush_every: (sample_rate as usize * channels as usize).max(1),
+        })
+    }
+
+    /// 追加 f32 采样
+    pub fn write_samples(&mut self, samples: &[f32]) -> Result<(), EncodingError> {
+        for &sample in samples {
+            self.writer.write_sample(f32_to_i16(sample))?;
+        }
+        self.after_write(samples.len())
+    }
+
+    /// 追加 i16 采样
+    pub fn write_i16_samples(&mut self, samples: &[i16]) -> Result<(), EncodingError> {
+        for &sample in samples {
+            self.writer.write_sample(sample)?;
+        }
+        self.after_write(samples.len())
+    }
+
+    fn after_write(&mut self, written: usize) -> Result<(), EncodingError> {
+        self.samples_since_flush += written;
+        // 交错采样只在完整帧边界回填头部，否则 hound 会报告未完成的采样
+        if self.samples_since_flush >= self.flush_every
+            && self.writer.len().is_multiple_of(self.writer.spec().channels as u32)
+        {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// 回填当前样本数并刷盘
+    pub fn flush(&mut self) -> Result<(), EncodingError> {
+        self.writer.flush()?;
+        self.samples_since_flush = 0;
+        Ok(())
+    }
+
+    /// 已写入的采样数 (所有声道合计)
+    pub fn len(&self) -> u32 {
+        self.writer.len()
+    }
+
+    /// 是否尚未写入采样
+    pub fn is_empty(&self) -> bool {
+        self.writer.len() == 0
+    }
+
+    /// 文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 完成写入，回填头部中的样本数
+    pub fn finalize(self) -> Result<PathBuf, EncodingError> {
+        self.writer.finalize()?;
+        Ok(self.path)
+    }
+}
+
+/// 读取 16 位 PCM WAV 文件
+pub fn read_wav(path: impl AsRef<Path>) -> Result<AudioData, EncodingError> {
+    read_pcm16(WavReader::open(path)?)
+}
+
+/// 解码内存中的 16 位 PCM WAV 数据
+pub fn decode_wav(bytes: &[u8]) -> Result<AudioData, EncodingError> {
+    read_pcm16(WavReader::new(Cursor::new(bytes))?)
+}
+
+fn read_pcm16<R: Read>(reader: WavReader<R>) -> Result<AudioData, EncodingError> {
+    let spec = reader.spec();
+    if spec.sample_format != SampleFormat::Int || spec.bits_per_sample != 16 {
+        return Err(EncodingError::WavError(format!(
+            "仅支持 16 位 PCM，实际为 {:?} {} 位",
+            spec.sample_format, spec.bits_per_sample
+        )));
+    }
+
+    let pcm = reader
+        .into_samples::<i16>()
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(AudioData::from_i16(pcm, spec.sample_rate, spec.channels))
+}
+
+/// 修复未正常 finalize 的 WAV 文件并读取
+///
+/// 按实际文件长度回填 RIFF 与 data 块大小 (丢弃末尾不完整的帧)，
+/// 可恢复最近一次 flush 之后写入的数据
+pub fn recover_wav(path: impl AsRef<Path>) -> Result<AudioData, EncodingError> {
+    let path = path.as_ref();
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let file_len = file.metadata()?.len();
+
+    let mut riff = [0u8; 12];
+    file.read_exact(&mut riff)?;
+    if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+        return Err(EncodingError::InvalidAudioData);
+    }
+
+    // 逐块查找 fmt 与 data，data 块之后的内容视为采样
+    let mut offset = 12u64;
+    let mut block_align = 0u64;
+    let data_offset = loop {
+        if offset + 8 > file_len {
+            return Err(EncodingError::InvalidAudioData);
+        }
+        let mut header = [0u8; 8];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut header)?;
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+
+        match &header[0..4] {
+            b"fmt " => {
+                let mut fmt = [0u8; 14];
+                file.read_exact(&mut fmt)?;
+                block_align = u16::from_le_bytes([fmt[12], fmt[13]]) as u64;
+            }
+            b"data" => break offset + 8,
+            _ => {}
+        }
+        // 块按偶数字节对齐
+        offset += 8 + size + (size & 1);
+    };
+
+    if block_align == 0 {
+        return Err(EncodingError::InvalidAudioData);
+    }
+
+    let data_len = (file_len - data_offset) / block_align * block_align;
+    let riff_len = data_offset + data_len - 8;
+    if riff_len > u32::MAX as u64 {
+        return Err(EncodingError::WavError("文件超过 WAV 大小上限".to_string()));
+    }
+
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&(riff_len as u32).to_le_bytes())?;
+    file.seek(SeekFrom::Start(data_offset - 4))?;
+    file.write_all(&(data_len as u32).to_le_bytes())?;
+    file.flush()?;
+    drop(file);
+
+    read_wav(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_wav(name: &str) -> PathBuf {
+        crate::utils::temp_dir::temp_dir().join(format!("sw-encoder-{}-{}.wav", name, std::process::id()))
+    }
+
+    fn tone(len: usize) -> Vec<i16> {
+        (0..len).map(|i| ((i as i32 * 37) % 20000 - 10000) as i16).collect()
+    }
+
+    #[test]
+    fn test_incremental_writer_finalize_backfills_length() {
+        let path = temp_wav("finalize");
+        let pcm = tone(5000);
+
+        let mut writer = IncrementalWavWriter::create(&path, TARGET_SAMPLE_RATE, 1).unwrap();
+        for chunk in pcm.chunks(700) {
+            writer.write_i16_samples(chunk).unwrap();
+        }
+        assert_eq!(writer.len(), 5000);
+        let path = writer.finalize().unwrap();
+
+        let audio = read_wav(&path).unwrap();
+        assert_eq!(audio.pcm_i16(), Some(pcm.as_slice()));
+        assert_eq!(audio.sample_rate, TARGET_SAMPLE_RATE);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_recover_unfinalized_file() {
+        let path = temp_wav("recover");
+        let pcm = tone(TARGET_SAMPLE_RATE as usize * 2 + 123);
+
+        let mut writer = IncrementalWavWriter::create(&path, TARGET_SAMPLE_RATE, 2).unwrap();
+        writer.write_i16_samples(&pcm[..TARGET_SAMPLE_RATE as usize * 2]).unwrap();
+        writer.write_i16_samples(&pcm[TARGET_SAMPLE_RATE as usize * 2..]).unwrap();
+        // 模拟崩溃：不 finalize，缓冲写出后直接丢弃
+        // 末尾帧不完整时 hound 仍会写出数据，只是返回错误
+        let _ = writer.writer.flush();
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .and_then(|mut f| {
+                // 头部停留在上一次回填的长度
+                f.seek(SeekFrom::Start(40))?;
+                f.write_all(&(TARGET_SAMPLE_RATE * 2).to_le_bytes())
+            })
+            .unwrap();
+        std::mem::forget(writer);
+
+        let audio = recover_wav(&path).unwrap();
+        // 末尾不完整的立体声帧被丢弃
+        assert_eq!(audio.pcm_i16(), Some(&pcm[..pcm.len() - 1]));
+        assert_eq!(audio.channels, 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,141 @@
+// 淡入淡出与交叉淡化
+// 拼接片段时在首尾或接缝处做线性增益过渡，避免波形突变产生咔哒声。
+// 增益按帧计算 (多声道同一帧使用相同增益)，i16 透传数据直接在 i16 上处理，保留透传
+
+use super::AudioData;
+
+/// 对首尾做线性淡入淡出
+///
+/// 淡入淡出总时长超过音频时长时按比例缩短两者，使其恰好覆盖整段音频 (不重叠)
+#[allow(dead_code)]
+pub fn fade(audio: &AudioData, fade_in_ms: u64, fade_out_ms: u64) -> AudioData {
+    let channels = audio.channels.max(1) as usize;
+    let frames = audio.samples.len() / channels;
+    let (fade_in, fade_out) = fit_fades(
+        ms_to_frames(fade_in_ms, audio.sample_rate),
+        ms_to_frames(fade_out_ms, audio.sample_rate),
+        frames,
+    );
+    let gain = |frame: usize| {
+        let mut gain = 1.0;
+        if frame < fade_in {
+            gain *= frame as f32 / fade_in as f32;
+        }
+        // 采样数不是声道数整数倍时，末尾不完整的帧按最后一帧处理
+        let from_end = frames.saturating_sub(frame + 1);
+        if from_end < fade_out {
+            gain *= from_end as f32 / fade_out as f32;
+        }
+        gain
+    };
+
+    match audio.pcm_i16() {
+        Some(pcm) => {
+            let faded = apply_gain(pcm, channels, gain, |sample, gain| (sample as f32 * gain).round() as i16);
+            AudioData::from_i16(faded, audio.sample_rate, audio.channels)
+        }
+        None => {
+            let faded = apply_gain(&audio.samples, channels, gain, |sample, gain| sample * gain);
+            AudioData::new(faded, audio.sample_rate, audio.channels)
+        }
+    }
+}
+
+/// 按交叉淡化拼接采样：相邻两段在接缝处重叠 `overlap` 帧，前段线性淡出的同时后段淡入
+///
+/// 重叠帧数不超过相邻两段中较短者
+pub(super) fn crossfade_join<T: Copy>(
+    parts: &[&[T]],
+    channels: usize,
+    overlap: usize,
+    mix: impl Fn(T, T, f32) -> T,
+) -> Vec<T> {
+    let mut joined: Vec<T> = Vec::with_capacity(parts.iter().map(|part| part.len()).sum());
+    for (index, part) in parts.iter().enumerate() {
+        let frames = (joined.len() / channels).min(part.len() / channels);
+        let overlap = if index == 0 { 0 } else { overlap.min(frames) };
+        let start = joined.len() - overlap * channels;
+        for (offset, &sample) in part[..overlap * channels].iter().enumerate() {
+            // 取帧中点处的比例，使两端都不与原段完全重合
+            let t = ((offset / channels) as f32 + 0.5) / overlap as f32;
+            joined[start + offset] = mix(joined[start + offset], sample, t);
+        }
+        joined.extend_from_slice(&part[overlap * channels..]);
+    }
+    joined
+}
+
+/// 毫秒换算为帧数
+pub(super) fn ms_to_frames(ms: u64, sample_rate: u32) -> usize {
+    (ms as u128 * sample_rate as u128 / 1000) as usize
+}
+
+/// 淡入淡出总帧数超过音频帧数时按比例缩短
+fn fit_fades(fade_in: usize, fade_out: usize, frames: usize) -> (usize, usize) {
+    let total = fade_in + fade_out;
+    if total <= frames {
+        return (fade_in, fade_out);
+    }
+    let fade_in = (fade_in as u128 * frames as u128 / total as u128) as usize;
+    (fade_in, frames - fade_in)
+}
+
+fn apply_gain<T: Copy>(samples: &[T], channels: usize, gain: impl Fn(usize) -> f32, scale: impl Fn(T, f32) -> T) -> Vec<T> {
+    samples
+        .iter()
+        .enumerate()
+        .map(|(index, &sample)| scale(sample, gain(index / channels)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fade_in_out() {
+        // 100ms 单声道，淡入 10ms、淡出 20ms
+        let audio = AudioData::new(vec![1.0; 1600], 16000, 1);
+        let faded = fade(&audio, 10, 20);
+        assert_eq!(faded.samples.len(), 1600);
+        assert_eq!(faded.samples[0], 0.0);
+        assert!((faded.samples[80] - 0.5).abs() < 1e-6);
+        assert_eq!(faded.samples[160], 1.0);
+        assert_eq!(faded.samples[1000], 1.0);
+        assert!((faded.samples[1599 - 160] - 0.5).abs() < 1e-6);
+        assert_eq!(faded.samples[1599], 0.0);
+
+        // 立体声同一帧增益相同，i16 透传保留
+        let stereo = AudioData::from_i16([1000, -1000].repeat(1600), 16000, 2);
+        let faded = fade(&stereo, 10, 0);
+        let pcm = faded.pcm_i16().unwrap();
+        assert_eq!((pcm[160], pcm[161]), (500, -500));
+        assert_eq!(pcm[pcm.len() - 1], -1000);
+
+        // 淡化时长超过音频，按比例缩短到恰好覆盖整段
+        let short = AudioData::new(vec![1.0; 160], 16000, 1);
+        let faded = fade(&short, 30, 10);
+        assert_eq!((faded.samples[0], faded.samples[159]), (0.0, 0.0));
+        assert!(faded.samples.iter().all(|&s| (0.0..=1.0).contains(&s)));
+        let peak = faded.samples.iter().cloned().fold(0.0, f32::max);
+        assert!(peak > 0.9, "{}", peak);
+        assert!((faded.samples[60] - 0.5).abs() < 0.01);
+
+        assert!(fade(&AudioData::new(Vec::new(), 16000, 1), 10, 10).is_empty());
+    }
+
+    #[test]
+    fn test_fade_stereo_with_partial_frame() {
+        // 立体声奇数个采样：末尾多出半帧
+        let mut samples = [1000i16, -1000].repeat(160);
+        samples.push(1000);
+        let faded = fade(&AudioData::from_i16(samples, 16000, 2), 0, 5);
+        let pcm = faded.pcm_i16().unwrap();
+        assert_eq!(pcm.len(), 321);
+        assert_eq!(&pcm[318..], &[0, 0, 0]);
+        assert_eq!((pcm[0], pcm[1]), (1000, -1000));
+
+        let faded = fade(&AudioData::new(vec![1.0; 3], 16000, 2), 1, 1);
+        assert_eq!(faded.samples.len(), 3);
+    }
+}
@@ -0,0 +1,116 @@
+// G.711 解码模块
+// 电话/VoIP 来源常见的 8kHz A-law 与 μ-law 音频解码为线性 PCM
+
+use super::recorder::convert_i16_to_f32;
+use super::AudioData;
+
+/// G.711 采样率
+pub const G711_SAMPLE_RATE: u32 = 8000;
+
+/// μ-law 偏置
+const ULAW_BIAS: i16 = 0x84;
+
+/// G.711 编码律
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum G711Law {
+    /// A-law (欧洲/国际线路)
+    ALaw,
+    /// μ-law (北美/日本线路)
+    MuLaw,
+}
+
+/// 解码单个 A-law 字节为 16-bit 线性采样
+pub fn alaw_to_linear(byte: u8) -> i16 {
+    let value = byte ^ 0x55;
+    let segment = (value >> 4) & 0x07;
+    let mut sample = ((value & 0x0f) as i16) << 4;
+
+    sample = match segment {
+        0 => sample + 8,
+        1 => sample + 0x108,
+        _ => (sample + 0x108) << (segment - 1),
+    };
+
+    // A-law 符号位为 1 表示正值
+    if value & 0x80 != 0 {
+        sample
+    } else {
+        -sample
+    }
+}
+
+/// 解码单个 μ-law 字节为 16-bit 线性采样
+pub fn ulaw_to_linear(byte: u8) -> i16 {
+    let value = !byte;
+    let segment = (value >> 4) & 0x07;
+    let magnitude = ((((value & 0x0f) as i16) << 3) + ULAW_BIAS) << segment;
+
+    if value & 0x80 != 0 {
+        ULAW_BIAS - magnitude
+    } else {
+        magnitude - ULAW_BIAS
+    }
+}
+
+/// 解码 A-law 字节流
+pub fn decode_alaw(bytes: &[u8]) -> Vec<f32> {
+    let pcm: Vec<i16> = bytes.iter().map(|&b| alaw_to_linear(b)).collect();
+    convert_i16_to_f32(&pcm)
+}
+
+/// 解码 μ-law 字节流
+pub fn decode_ulaw(bytes: &[u8]) -> Vec<f32> {
+    let pcm: Vec<i16> = bytes.iter().map(|&b| ulaw_to_linear(b)).collect();
+    convert_i16_to_f32(&pcm)
+}
+
+/// 解码为 8kHz 单声道音频 (转录前由预处理管线重采样到 16kHz)
+pub fn decode_g711(bytes: &[u8], law: G711Law) -> AudioData {
+    let decode = match law {
+        G711Law::ALaw => alaw_to_linear,
+        G711Law::MuLaw => ulaw_to_linear,
+    };
+    AudioData::from_i16(bytes.iter().map(|&b| decode(b)).collect(), G711_SAMPLE_RATE, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ulaw_reference_values() {
+        // ITU-T G.711 μ-law 解码参考值
+        let table: &[(u8, i16)] = &[
+            (0x00, -32124), (0x0f, -16764), (0x10, -15996), (0x3f, -1980), (0x70, -120), (0x7e, -8), (0x7f, 0),
+            (0x80, 32124), (0x8f, 16764), (0xbf, 1980), (0xf0, 120), (0xfe, 8), (0xff, 0),
+        ];
+        for &(byte, expected) in table {
+            assert_eq!(ulaw_to_linear(byte), expected, "μ-law 0x{:02x}", byte);
+        }
+    }
+
+    #[test]
+    fn test_alaw_reference_values() {
+        // ITU-T G.711 A-law 解码参考值
+        let table: &[(u8, i16)] = &[
+            (0x55, -8), (0xd5, 8), (0x54, -24), (0xd4, 24), (0x2a, -32256), (0xaa, 32256), (0x00, -5504), (0x80, 5504),
+        ];
+        for &(byte, expected) in table {
+            assert_eq!(alaw_to_linear(byte), expected, "A-law 0x{:02x}", byte);
+        }
+    }
+
+    #[test]
+    fn test_decode_to_audio() {
+        let samples = decode_ulaw(&[0x80, 0xff, 0x00]);
+        assert!((samples[0] - 32124.0 / i16::MAX as f32).abs() < 1e-6);
+        assert_eq!(samples[1], 0.0);
+        assert!(samples[2] < -0.98);
+
+        let audio = decode_g711(&[0xd5; 8000], G711Law::ALaw);
+        assert_eq!(audio.sample_rate, G711_SAMPLE_RATE);
+        assert_eq!(audio.channels, 1);
+        assert_eq!(audio.duration_ms, 1000);
+        assert_eq!(audio.pcm_i16().map(|pcm| pcm[0]), Some(8));
+    }
+}
@@ -0,0 +1,224 @@
+// 录音电平监测
+// 录音过程中根据原始 RMS 与削波占比判断输入是否长时间过低或持续削波，
+// 同一段异常只告警一次，避免每次电平上报都重复发送；
+// 另外检测用户开口 (持续超过能量阈值)，供上层打断 TTS 播报
+
+use super::utils::LevelStats;
+use crate::voice::config::{BargeInConfig, LevelAlertConfig};
+
+/// 异常中断超过此时长 (毫秒) 才视为恢复正常，容忍短暂的咳嗽、噪声或削波间隙
+const RECOVERY_GRACE_MS: u64 = 500;
+
+/// 语音能量中断超过此时长 (毫秒) 时重新累计，键盘敲击之间的空隙会打断累计
+const SPEECH_GAP_MS: u64 = 100;
+
+/// 开口检测触发后需安静此时长 (毫秒) 才能再次触发，同一段话只通知一次
+const SPEECH_RELEASE_MS: u64 = 800;
+
+/// 电平告警
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelAlert {
+    /// 长时间接近静音
+    TooQuiet,
+    /// 持续削波
+    TooLoud,
+}
+
+impl LevelAlert {
+    /// 告警代码 (与 warning 消息的 code 一致)
+    pub fn code(&self) -> &'static str {
+        match self {
+            LevelAlert::TooQuiet => "TOO_QUIET",
+            LevelAlert::TooLoud => "TOO_LOUD",
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            LevelAlert::TooQuiet => "输入音量过低，请检查麦克风是否静音或靠近麦克风说话",
+            LevelAlert::TooLoud => "输入音量过高导致削波，请降低麦克风增益或远离麦克风",
+        }
+    }
+}
+
+/// 单个异常条件的持续时间追踪
+#[derive(Debug, Default)]
+struct ConditionTracker {
+    /// 本段异常开始时间
+    since: Option<u64>,
+    /// 最近一次满足条件的时间
+    last_seen: u64,
+    /// 本段异常是否已告警
+    alerted: bool,
+}
+
+impl ConditionTracker {
+    /// 更新状态，持续时间达到 `trigger_ms` 且本段尚未告警时返回 true
+    fn update(&mut self, active: bool, now_ms: u64, trigger_ms: u64) -> bool {
+        if active {
+            let since = *self.since.get_or_insert(now_ms);
+            self.last_seen = now_ms;
+            if !self.alerted && now_ms.saturating_sub(since) >= trigger_ms {
+                self.alerted = true;
+                return true;
+            }
+        } else if self.since.is_some() && now_ms.saturating_sub(self.last_seen) > RECOVERY_GRACE_MS {
+            *self = Self::default();
+        }
+        false
+    }
+}
+
+/// 录音电平监测器 (每次录音新建)
+#[derive(Debug)]
+pub struct LevelMonitor {
+    config: LevelAlertConfig,
+    quiet: ConditionTracker,
+    loud: ConditionTracker,
+}
+
+impl LevelMonitor {
+    pub fn new(config: LevelAlertConfig) -> Self {
+        Self {
+            config,
+            quiet: ConditionTracker::default(),
+            loud: ConditionTracker::default(),
+        }
+    }
+
+    /// 处理一次电平上报，`now_ms` 为录音开始后的毫秒数
+    pub fn update(&mut self, stats: LevelStats, now_ms: u64) -> Option<LevelAlert> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let loud = stats.clipping_ratio >= self.config.loud_clipping_ratio;
+        let quiet = !loud && stats.rms < self.config.quiet_rms;
+
+        let too_loud = self.loud.update(loud, now_ms, self.config.loud_ms);
+        let too_quiet = self.quiet.update(quiet, now_ms, self.config.quiet_ms);
+
+        if too_loud {
+            Some(LevelAlert::TooLoud)
+        } else if too_quiet {
+            Some(LevelAlert::TooQuiet)
+        } else {
+            None
+        }
+    }
+}
+
+/// 用户开口检测器 (每次录音新建)
+#[derive(Debug)]
+pub struct SpeechDetector {
+    config: BargeInConfig,
+    /// 当前连续语音的开始时间
+    speech_since: Option<u64>,
+    /// 最近一次超过阈值的时间
+    last_speech: Option<u64>,
+    /// 本段说话是否已通知
+    triggered: bool,
+}
+
+impl SpeechDetector {
+    pub fn new(config: BargeInConfig) -> Self {
+        Self {
+            config,
+            speech_since: None,
+            last_speech: None,
+            triggered: false,
+        }
+    }
+
+    /// 处理一次电平上报，持续说话达到 `min_speech_ms` 时返回 true (每段话一次)
+    pub fn update(&mut self, stats: LevelStats, now_ms: u64) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+
+        if stats.rms >= self.config.threshold_rms {
+            if self.last_speech.is_none_or(|t| now_ms.saturating_sub(t) > SPEECH_GAP_MS) {
+                self.speech_since = Some(now_ms);
+            }
+            self.last_speech = Some(now_ms);
+            let since = self.speech_since.unwrap_or(now_ms);
+            if !self.triggered && now_ms.saturating_sub(since) >= self.config.min_speech_ms {
+                self.triggered = true;
+                return true;
+            }
+        } else if self.triggered && self.last_speech.is_some_and(|t| now_ms.saturating_sub(t) >= SPEECH_RELEASE_MS) {
+            self.triggered = false;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SILENT: LevelStats = LevelStats { rms: 0.001, clipping_ratio: 0.0 };
+    const SPEECH: LevelStats = LevelStats { rms: 0.1, clipping_ratio: 0.0 };
+    const CLIPPED: LevelStats = LevelStats { rms: 0.7, clipping_ratio: 0.2 };
+
+    /// 以 20ms 间隔喂入同一电平，收集告警
+    fn feed(monitor: &mut LevelMonitor, stats: LevelStats, from_ms: u64, to_ms: u64) -> Vec<LevelAlert> {
+        (from_ms..to_ms)
+            .step_by(20)
+            .filter_map(|t| monitor.update(stats, t))
+            .collect()
+    }
+
+    #[test]
+    fn test_quiet_alert_fires_once_per_episode() {
+        let mut monitor = LevelMonitor::new(LevelAlertConfig::default());
+
+        assert!(feed(&mut monitor, SILENT, 0, 2900).is_empty());
+        assert_eq!(feed(&mut monitor, SILENT, 2900, 8000), vec![LevelAlert::TooQuiet]);
+
+        // 短暂出声不算恢复
+        assert!(feed(&mut monitor, SPEECH, 8000, 8200).is_empty());
+        assert!(feed(&mut monitor, SILENT, 8200, 9000).is_empty());
+
+        // 恢复正常后再次静音才重新告警
+        assert!(feed(&mut monitor, SPEECH, 9000, 10000).is_empty());
+        assert_eq!(feed(&mut monitor, SILENT, 10000, 14000), vec![LevelAlert::TooQuiet]);
+    }
+
+    #[test]
+    fn test_loud_alert_and_config() {
+        let mut monitor = LevelMonitor::new(LevelAlertConfig::default());
+        assert!(feed(&mut monitor, CLIPPED, 0, 900).is_empty());
+        assert_eq!(feed(&mut monitor, CLIPPED, 900, 5000), vec![LevelAlert::TooLoud]);
+
+        let mut monitor = LevelMonitor::new(LevelAlertConfig { enabled: false, ..Default::default() });
+        assert!(feed(&mut monitor, SILENT, 0, 10000).is_empty());
+    }
+
+    #[test]
+    fn test_speech_detector_ignores_keyboard_clicks() {
+        let config = BargeInConfig { enabled: true, ..Default::default() };
+        let mut detector = SpeechDetector::new(config);
+        let feed = |detector: &mut SpeechDetector, stats: LevelStats, from_ms: u64, to_ms: u64| {
+            (from_ms..to_ms).step_by(20).filter(|&t| detector.update(stats, t)).count()
+        };
+
+        // 敲键盘：每 200ms 一次 20ms 的能量脉冲
+        for t in (0..3000).step_by(200) {
+            assert!(!detector.update(SPEECH, t));
+            assert_eq!(feed(&mut detector, SILENT, t + 20, t + 200), 0);
+        }
+
+        // 持续说话触发一次，句间短停顿不重复触发
+        assert_eq!(feed(&mut detector, SPEECH, 3000, 4000), 1);
+        assert_eq!(feed(&mut detector, SILENT, 4000, 4300), 0);
+        assert_eq!(feed(&mut detector, SPEECH, 4300, 5000), 0);
+
+        // 安静一段时间后再次开口重新触发
+        assert_eq!(feed(&mut detector, SILENT, 5000, 6000), 0);
+        assert_eq!(feed(&mut detector, SPEECH, 6000, 7000), 1);
+
+        let mut disabled = SpeechDetector::new(BargeInConfig::default());
+        assert_eq!(feed(&mut disabled, SPEECH, 0, 2000), 0);
+    }
+}
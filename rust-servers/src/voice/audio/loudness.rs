@@ -0,0 +1,227 @@
+// 响度测量 (EBU R128 / ITU-R BS.1770)
+// K 加权滤波后按 400ms 块 (75% 重叠) 计算均方，经绝对门限 (-70 LUFS) 与
+// 相对门限 (-10 LU) 两级门控得到积分响度
+
+use super::utils::calculate_peak;
+use super::AudioData;
+
+/// EBU R128 推荐的节目响度
+pub const TARGET_LUFS: f32 = -23.0;
+
+/// 门控块时长 (毫秒)
+const BLOCK_MS: usize = 400;
+
+/// 相邻门控块的间隔 (毫秒，即 75% 重叠)
+const STEP_MS: usize = 100;
+
+/// 绝对门限
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+
+/// 相对门限 (相对于绝对门限内的平均响度)
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+/// 均方换算为响度时的常数，使 1kHz 正弦经 K 加权后响度等于其有效值电平
+const LOUDNESS_OFFSET: f64 = -0.691;
+
+/// 测量积分响度 (LUFS)
+///
+/// 不足一个门控块的音频整段作为一块计算；静音或全部被门限滤除时返回负无穷
+pub fn measure_lufs(audio: &AudioData) -> f32 {
+    let channels = audio.channels.max(1) as usize;
+    let frames = audio.samples.len() / channels;
+    if frames == 0 || audio.sample_rate == 0 {
+        return f32::NEG_INFINITY;
+    }
+
+    // 各声道 K 加权后的平方
+    let squared: Vec<Vec<f64>> = (0..channels)
+        .map(|channel| {
+            let mut filter = KWeighting::new(audio.sample_rate);
+            (0..frames)
+                .map(|i| {
+                    let y = filter.process(audio.samples[i * channels + channel] as f64);
+                    y * y
+                })
+                .collect()
+        })
+        .collect();
+    let weights: Vec<f64> = (0..channels).map(|channel| channel_weight(channel, channels)).collect();
+
+    let rate = audio.sample_rate as usize;
+    let block = (BLOCK_MS * rate / 1000).clamp(1, frames);
+    let step = (STEP_MS * rate / 1000).max(1);
+
+    // 每块各声道加权均方之和
+    let blocks: Vec<f64> = (0..=(frames - block) / step)
+        .map(|b| {
+            let range = b * step..b * step + block;
+            squared
+                .iter()
+                .zip(&weights)
+                .map(|(power, weight)| weight * power[range.clone()].iter().sum::<f64>() / block as f64)
+                .sum()
+        })
+        .collect();
+
+    let above_absolute: Vec<f64> = blocks.iter().copied().filter(|&z| loudness(z) > ABSOLUTE_GATE_LUFS).collect();
+    if above_absolute.is_empty() {
+        return f32::NEG_INFINITY;
+    }
+    let relative_gate = loudness(mean(&above_absolute)) + RELATIVE_GATE_LU;
+    let gated: Vec<f64> = above_absolute.into_iter().filter(|&z| loudness(z) > relative_gate).collect();
+    if gated.is_empty() {
+        return f32::NEG_INFINITY;
+    }
+    loudness(mean(&gated)) as f32
+}
+
+/// 按增益把积分响度调整到目标值
+///
+/// 增益不超过使峰值达到满幅的值，避免削波 (此时响度会低于目标)；无法测量响度时原样返回
+pub fn normalize_loudness(audio: &AudioData, target_lufs: f32) -> AudioData {
+    let lufs = measure_lufs(audio);
+    let peak = calculate_peak(&audio.samples);
+    if !lufs.is_finite() || peak <= 0.0 {
+        return audio.clone();
+    }
+
+    let gain = 10f32.powf((target_lufs - lufs) / 20.0).min(1.0 / peak);
+    let samples = audio.samples.iter().map(|s| s * gain).collect();
+    AudioData::new(samples, audio.sample_rate, audio.channels)
+}
+
+fn loudness(mean_square: f64) -> f64 {
+    LOUDNESS_OFFSET + 10.0 * mean_square.log10()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// 声道权重：5.1 布局中 LFE 不计入、环绕声道 +1.5dB，其余为 1
+fn channel_weight(channel: usize, channels: usize) -> f64 {
+    match (channels, channel) {
+        (6, 3) => 0.0,
+        (6, 4) | (6, 5) => 1.41,
+        _ => 1.0,
+    }
+}
+
+// ============================================================================
+// K 加权滤波
+// ============================================================================
+
+/// 二阶 IIR 滤波器 (直接 II 型)
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+/// K 加权：高频搁架滤波 (模拟头部声学效应) 串联 RLB 高通滤波
+///
+/// 系数按 BS.1770 在 48kHz 下给出的模拟原型经双线性变换求得，适用于任意采样率
+struct KWeighting {
+    shelf: Biquad,
+    highpass: Biquad,
+}
+
+impl KWeighting {
+    fn new(sample_rate: u32) -> Self {
+        let fs = sample_rate as f64;
+
+        let f0 = 1681.974450955533;
+        let gain_db = 3.999843853973347;
+        let q = 0.7071752369554196;
+        let k = (std::f64::consts::PI * f0 / fs).tan();
+        let vh = 10f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        let shelf = Biquad {
+            b: [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            z: [0.0; 2],
+        };
+
+        let f0 = 38.13547087602444;
+        let q = 0.5003270373238773;
+        let k = (std::f64::consts::PI * f0 / fs).tan();
+        let a0 = 1.0 + k / q + k * k;
+        let highpass = Biquad {
+            b: [1.0, -2.0, 1.0],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            z: [0.0; 2],
+        };
+
+        Self { shelf, highpass }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        self.highpass.process(self.shelf.process(x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1kHz 正弦，幅度为 dbfs (峰值电平)，每声道相同
+    fn sine(dbfs: f32, seconds: f32, sample_rate: u32, channels: u16) -> Vec<f32> {
+        let amplitude = 10f32.powf(dbfs / 20.0);
+        (0..(seconds * sample_rate as f32) as usize)
+            .flat_map(|i| {
+                let s = amplitude * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / sample_rate as f32).sin();
+                std::iter::repeat_n(s, channels as usize)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_measure_lufs_reference_signals() {
+        // EBU Tech 3341 案例 1：立体声 1kHz 正弦 -23dBFS 应为 -23 LUFS (±0.1)
+        for rate in [48000, 44100] {
+            let audio = AudioData::new(sine(-23.0, 2.0, rate, 2), rate, 2);
+            let lufs = measure_lufs(&audio);
+            assert!((lufs + 23.0).abs() < 0.1, "{}Hz: {}", rate, lufs);
+        }
+        // 单声道少一个声道的能量，约低 3dB
+        let mono = AudioData::new(sine(-20.0, 2.0, 48000, 1), 48000, 1);
+        assert!((measure_lufs(&mono) + 23.01).abs() < 0.1, "{}", measure_lufs(&mono));
+
+        // 案例 3 (缩短时长)：-36/-23/-36dBFS 分段，较轻的两段被相对门限滤除
+        let mut gated = sine(-36.0, 2.0, 48000, 2);
+        gated.extend(sine(-23.0, 20.0, 48000, 2));
+        gated.extend(sine(-36.0, 2.0, 48000, 2));
+        let lufs = measure_lufs(&AudioData::new(gated, 48000, 2));
+        assert!((lufs + 23.0).abs() < 0.1, "{}", lufs);
+
+        // 静音部分被绝对门限滤除
+        let mut padded = vec![0.0; 96000];
+        padded.extend(sine(-23.0, 20.0, 48000, 2));
+        let lufs = measure_lufs(&AudioData::new(padded, 48000, 2));
+        assert!((lufs + 23.0).abs() < 0.1, "{}", lufs);
+
+        assert_eq!(measure_lufs(&AudioData::new(vec![0.0; 16000], 16000, 1)), f32::NEG_INFINITY);
+        assert_eq!(measure_lufs(&AudioData::new(Vec::new(), 16000, 1)), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_normalize_loudness() {
+        let quiet = AudioData::new(sine(-40.0, 1.0, 16000, 1), 16000, 1);
+        let normalized = normalize_loudness(&quiet, TARGET_LUFS);
+        assert!((measure_lufs(&normalized) - TARGET_LUFS).abs() < 0.1, "{}", measure_lufs(&normalized));
+
+        // 达到目标需要超过满幅时以峰值为限
+        let limited = normalize_loudness(&quiet, 0.0);
+        assert!((calculate_peak(&limited.samples) - 1.0).abs() < 1e-4);
+    }
+}
@@ -1,15 +1,66 @@
 // 音频模块
 // 包含录音、流式处理、编码和工具函数
 
+pub mod aec;
+pub mod clock_drift;
+pub mod diagnostics;
 pub mod encoder;
+pub mod fade;
+pub mod g711;
+pub mod level_monitor;
+pub mod loudness;
+pub mod noise_gate;
+pub mod pipeline;
+pub mod preset;
 pub mod recorder;
+pub mod requirements;
+pub mod stream_resampler;
 pub mod streaming;
+pub mod tee;
 pub mod utils;
 
 // 重新导出常用类型
-pub use encoder::{encode_to_wav, encode_samples_to_wav, encode_i16_to_wav, WavEncoder, EncodingError};
-pub use recorder::{AudioRecorder, RecordingError, RecordingMode, TARGET_SAMPLE_RATE};
+pub use aec::acoustic_echo_cancel;
+pub use clock_drift::{ClockDriftEstimator, DeviceClock};
+pub use diagnostics::{diagnose, infer_sample_rate_mismatch, AudioDiagnostics, RunningDiagnostics};
+pub use encoder::{
+    decode_wav, encode_to_wav, encode_samples_to_wav, encode_i16_to_wav, read_wav, recover_wav,
+    IncrementalWavWriter, WavEncoder, EncodingError,
+};
+pub use fade::fade;
+pub use g711::{decode_alaw, decode_g711, decode_ulaw, G711Law, G711_SAMPLE_RATE};
+pub use level_monitor::{LevelAlert, LevelMonitor, SpeechDetector};
+pub use loudness::{measure_lufs, normalize_loudness};
+pub use noise_gate::NoiseGate;
+pub use pipeline::{Pipeline, PipelineError, PipelineStage};
+pub use preset::{AudioPreset, PresetProfile};
+pub use recorder::{
+    default_input_device_name, AudioRecorder, CaptureParams, CaptureRequest, DeviceLostCallback, RecordingError,
+    RecordingMode, TARGET_SAMPLE_RATE,
+};
+pub use requirements::{AudioFormat, AudioRequirements};
+pub use stream_resampler::StreamResampler;
 pub use streaming::{StreamingRecorder, AudioChunkData, CHUNK_SAMPLES};
+pub use tee::{AudioTee, DeviceTee};
+
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// 音频编辑错误
+#[derive(Debug, Error, PartialEq)]
+pub enum AudioError {
+    #[error("没有可拼接的音频片段")]
+    EmptyInput,
+
+    #[error("音频格式不一致: 第 {index} 段为 {sample_rate}Hz/{channels} 声道，应为 {expected_rate}Hz/{expected_channels} 声道")]
+    FormatMismatch {
+        index: usize,
+        sample_rate: u32,
+        channels: u16,
+        expected_rate: u32,
+        expected_channels: u16,
+    },
+}
 
 /// 音频数据
 #[derive(Debug, Clone)]
@@ -22,6 +73,10 @@ pub struct AudioData {
     pub channels: u16,
     /// 时长 (毫秒)
     pub duration_ms: u64,
+    /// WAV 编码缓存 (首次编码后复用，编码后不应再修改 samples)
+    wav_cache: OnceLock<Vec<u8>>,
+    /// 原始 i16 采样 (设备输出即目标格式时保留，编码时直接透传)
+    pcm_i16: Option<Vec<i16>>,
 }
 
 impl AudioData {
@@ -38,9 +93,25 @@ impl AudioData {
             sample_rate,
             channels,
             duration_ms,
+            wav_cache: OnceLock::new(),
+            pcm_i16: None,
         }
     }
 
+    /// 由 i16 PCM 采样创建音频数据
+    ///
+    /// 保留原始采样，编码 WAV 时不经 f32 往返
+    pub fn from_i16(pcm: Vec<i16>, sample_rate: u32, channels: u16) -> Self {
+        let mut audio = Self::new(recorder::convert_i16_to_f32(&pcm), sample_rate, channels);
+        audio.pcm_i16 = Some(pcm);
+        audio
+    }
+
+    /// 原始 i16 采样 (仅透传路径创建的音频存在)
+    pub fn pcm_i16(&self) -> Option<&[i16]> {
+        self.pcm_i16.as_deref()
+    }
+
     /// 检查音频数据是否为空
     pub fn is_empty(&self) -> bool {
         self.samples.is_empty()
@@ -51,9 +122,73 @@ impl AudioData {
         self.samples.len()
     }
 
+    /// 按顺序拼接多段音频，各段采样率与声道数必须一致
+    ///
+    /// 各段均为 i16 透传数据时拼接结果同样保留透传
+    #[allow(dead_code)]
+    pub fn concat(parts: &[AudioData]) -> Result<AudioData, AudioError> {
+        Self::concat_with_crossfade(parts, 0)
+    }
+
+    /// 按顺序拼接多段音频，相邻两段在接缝处交叉淡化 `crossfade_ms` (0 为直接拼接)
+    ///
+    /// 每个接缝重叠的时长不超过两侧片段的长度，拼接结果相应变短
+    #[allow(dead_code)]
+    pub fn concat_with_crossfade(parts: &[AudioData], crossfade_ms: u64) -> Result<AudioData, AudioError> {
+        let first = parts.first().ok_or(AudioError::EmptyInput)?;
+        if let Some((index, part)) = parts.iter().enumerate()
+            .find(|(_, part)| part.sample_rate != first.sample_rate || part.channels != first.channels)
+        {
+            return Err(AudioError::FormatMismatch {
+                index,
+                sample_rate: part.sample_rate,
+                channels: part.channels,
+                expected_rate: first.sample_rate,
+                expected_channels: first.channels,
+            });
+        }
+
+        let channels = first.channels.max(1) as usize;
+        let overlap = fade::ms_to_frames(crossfade_ms, first.sample_rate);
+        if parts.iter().all(|part| part.pcm_i16.is_some()) {
+            let pcm: Vec<&[i16]> = parts.iter().map(|part| part.pcm_i16().unwrap_or_default()).collect();
+            let pcm = fade::crossfade_join(&pcm, channels, overlap, |a, b, t| {
+                (a as f32 * (1.0 - t) + b as f32 * t).round() as i16
+            });
+            return Ok(Self::from_i16(pcm, first.sample_rate, first.channels));
+        }
+        let samples: Vec<&[f32]> = parts.iter().map(|part| part.samples.as_slice()).collect();
+        let samples = fade::crossfade_join(&samples, channels, overlap, |a, b, t| a * (1.0 - t) + b * t);
+        Ok(Self::new(samples, first.sample_rate, first.channels))
+    }
+
+    /// 截取 `[start_ms, end_ms)` 的片段
+    ///
+    /// 时间范围超出音频时截到音频边界，范围为空时返回空音频；按整帧 (多声道对齐) 截取
+    #[allow(dead_code)]
+    pub fn slice_ms(&self, start_ms: u64, end_ms: u64) -> AudioData {
+        let channels = self.channels.max(1) as usize;
+        let frames = self.samples.len() / channels;
+        let to_frame = |ms: u64| ((ms as u128 * self.sample_rate as u128 / 1000) as usize).min(frames);
+        let start = to_frame(start_ms);
+        let end = to_frame(end_ms).max(start);
+        let range = start * channels..end * channels;
+
+        match self.pcm_i16() {
+            Some(pcm) => Self::from_i16(pcm[range].to_vec(), self.sample_rate, self.channels),
+            None => Self::new(self.samples[range].to_vec(), self.sample_rate, self.channels),
+        }
+    }
+
     /// 编码为 WAV 格式
+    ///
+    /// 结果会被缓存，重试与并行兜底不必重复编码
     pub fn to_wav(&self) -> Result<Vec<u8>, EncodingError> {
-        encode_to_wav(self)
+        if let Some(wav) = self.wav_cache.get() {
+            return Ok(wav.clone());
+        }
+        let wav = encode_to_wav(self)?;
+        Ok(self.wav_cache.get_or_init(|| wav).clone())
     }
 }
 
@@ -132,6 +267,76 @@ mod tests {
         assert_eq!(&wav[0..4], b"RIFF");
     }
 
+    #[test]
+    fn test_audio_data_to_wav_cached() {
+        let audio = AudioData::new(vec![0.1f32; 1600], 16000, 1);
+
+        let first = audio.to_wav().unwrap();
+        let second = audio.to_wav().unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first, encode_to_wav(&audio).unwrap());
+    }
+
+    #[test]
+    fn test_concat_and_slice() {
+        let a = AudioData::new(vec![0.1; 1600], 16000, 1);
+        let b = AudioData::new(vec![0.2; 3200], 16000, 1);
+        let joined = AudioData::concat(&[a.clone(), b.clone()]).unwrap();
+        assert_eq!(joined.duration_ms, 300);
+        assert_eq!(joined.samples[1599], 0.1);
+        assert_eq!(joined.samples[1600], 0.2);
+
+        assert_eq!(AudioData::concat(&[]).unwrap_err(), AudioError::EmptyInput);
+        let stereo = AudioData::new(vec![0.0; 3200], 16000, 2);
+        assert!(matches!(
+            AudioData::concat(&[a, stereo]),
+            Err(AudioError::FormatMismatch { index: 1, channels: 2, .. })
+        ));
+
+        let middle = joined.slice_ms(50, 150);
+        assert_eq!(middle.duration_ms, 100);
+        assert_eq!(middle.samples[0], 0.1);
+        assert_eq!(middle.samples[middle.samples.len() - 1], 0.2);
+        // 越界范围截到音频边界，倒置范围为空
+        assert_eq!(joined.slice_ms(250, 10_000).duration_ms, 50);
+        assert!(joined.slice_ms(200, 100).is_empty());
+        assert!(joined.slice_ms(5_000, 6_000).is_empty());
+    }
+
+    #[test]
+    fn test_concat_with_crossfade() {
+        let a = AudioData::new(vec![1.0; 1600], 16000, 1);
+        let b = AudioData::new(vec![0.0; 1600], 16000, 1);
+        // 接缝处重叠 10ms，总长相应缩短
+        let joined = AudioData::concat_with_crossfade(&[a.clone(), b.clone()], 10).unwrap();
+        assert_eq!(joined.samples.len(), 3200 - 160);
+        assert_eq!(joined.samples[1439], 1.0);
+        assert!((joined.samples[1440] - (1.0 - 0.5 / 160.0)).abs() < 1e-6);
+        assert!(joined.samples[1440..1600].windows(2).all(|w| w[0] > w[1]));
+        assert_eq!(joined.samples[1600], 0.0);
+
+        // 重叠不超过较短的一段
+        let tiny = AudioData::from_i16(vec![0; 32], 16000, 2);
+        let long = AudioData::from_i16(vec![1000; 3200], 16000, 2);
+        let joined = AudioData::concat_with_crossfade(&[long, tiny], 100).unwrap();
+        assert_eq!(joined.pcm_i16().unwrap().len(), 3200);
+        assert_eq!(AudioData::concat_with_crossfade(&[a], 10).unwrap().samples.len(), 1600);
+    }
+
+    #[test]
+    fn test_slice_keeps_frames_and_pcm_passthrough() {
+        let pcm: Vec<i16> = (0..3200).map(|i| i as i16).collect();
+        let stereo = AudioData::from_i16(pcm, 16000, 2);
+        let slice = stereo.slice_ms(10, 20);
+        assert_eq!(slice.pcm_i16().unwrap().len(), 320);
+        // 从左声道开始
+        assert_eq!(slice.pcm_i16().unwrap()[0], 320);
+
+        let joined = AudioData::concat(&[slice.clone(), slice]).unwrap();
+        assert_eq!(joined.pcm_i16().unwrap().len(), 640);
+        assert_eq!(joined.duration_ms, 20);
+    }
+
     #[test]
     fn test_waveform_data() {
         let waveform = WaveformData::new(vec![0.5; 9], 1000);
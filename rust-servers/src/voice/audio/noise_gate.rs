@@ -0,0 +1,126 @@
+// 实时音频噪声门
+// 实时模式下把 RMS 低于门限的静音块丢弃、不送入引擎，节省流量与计费；
+// 电平与波形在录音回调中计算，不受影响。说话结束后保持 hangover 时长再关门，
+// 开门时补发前一块，避免裁掉词尾与词首的低能量部分
+
+use tokio::sync::mpsc;
+
+use super::recorder::TARGET_SAMPLE_RATE;
+use super::streaming::{AudioChunkData, CHUNK_CHANNEL_BUFFER};
+use crate::voice::config::NoiseGateConfig;
+
+/// 噪声门状态
+#[derive(Debug)]
+pub struct NoiseGate {
+    config: NoiseGateConfig,
+    /// 距离上次超过门限的时长 (毫秒)，None 表示门已关闭
+    since_loud_ms: Option<u64>,
+    /// 门关闭期间最近的一块 (开门时补发)
+    held: Option<AudioChunkData>,
+    /// 已丢弃的块数
+    dropped: u64,
+}
+
+impl NoiseGate {
+    pub fn new(config: NoiseGateConfig) -> Self {
+        Self {
+            config,
+            since_loud_ms: None,
+            held: None,
+            dropped: 0,
+        }
+    }
+
+    /// 处理一块音频，返回应转发给引擎的块 (按顺序)
+    pub fn process(&mut self, chunk: AudioChunkData) -> Vec<AudioChunkData> {
+        let duration_ms = chunk.samples.len() as u64 * 1000 / TARGET_SAMPLE_RATE as u64;
+
+        if chunk_rms(&chunk.samples) >= self.config.threshold_rms {
+            self.since_loud_ms = Some(0);
+            let mut output: Vec<_> = self.held.take().into_iter().collect();
+            output.push(chunk);
+            return output;
+        }
+
+        match self.since_loud_ms {
+            Some(elapsed) if elapsed < self.config.hangover_ms => {
+                self.since_loud_ms = Some(elapsed + duration_ms);
+                vec![chunk]
+            }
+            _ => {
+                self.since_loud_ms = None;
+                if self.held.replace(chunk).is_some() {
+                    self.dropped += 1;
+                }
+                Vec::new()
+            }
+        }
+    }
+
+    /// 已丢弃的块数 (不含仍暂存的一块)
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// 过滤录音块，返回只含有声部分的接收端
+    pub fn gate_chunks(mut self, mut chunk_rx: mpsc::Receiver<AudioChunkData>) -> mpsc::Receiver<AudioChunkData> {
+        let (tx, rx) = mpsc::channel(CHUNK_CHANNEL_BUFFER);
+        tokio::spawn(async move {
+            'recv: while let Some(chunk) = chunk_rx.recv().await {
+                for chunk in self.process(chunk) {
+                    if tx.send(chunk).await.is_err() {
+                        break 'recv;
+                    }
+                }
+            }
+            if self.dropped > 0 {
+                eprintln!("[DEBUG] [Voice] 噪声门丢弃 {} 个静音块", self.dropped);
+            }
+        });
+        rx
+    }
+}
+
+/// i16 采样的 RMS (归一化到 0-1)
+fn chunk_rms(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum: f64 = samples.iter().map(|&s| (s as f64 / 32768.0).powi(2)).sum();
+    (sum / samples.len() as f64).sqrt() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 100ms 的块，按幅度填充
+    fn chunk(amplitude: i16, timestamp_ms: u64) -> AudioChunkData {
+        AudioChunkData {
+            samples: vec![amplitude; 1600],
+            timestamp_ms,
+        }
+    }
+
+    #[test]
+    fn test_gate_drops_silence_with_hangover() {
+        let mut gate = NoiseGate::new(NoiseGateConfig {
+            enabled: true,
+            threshold_rms: 0.01,
+            hangover_ms: 200,
+        });
+        let loud = 3000;
+        let quiet = 50;
+        let amplitudes = [quiet, quiet, quiet, loud, loud, quiet, quiet, quiet, quiet, quiet, loud];
+
+        let forwarded: Vec<u64> = amplitudes.iter()
+            .enumerate()
+            .flat_map(|(i, &amplitude)| gate.process(chunk(amplitude, i as u64 * 100)))
+            .map(|chunk| chunk.timestamp_ms)
+            .collect();
+
+        // 开门时补发前一块；说话后保持 200ms；静音期间只暂存最近一块
+        assert_eq!(forwarded, vec![200, 300, 400, 500, 600, 900, 1000]);
+        assert_eq!(gate.dropped(), 4);
+    }
+}
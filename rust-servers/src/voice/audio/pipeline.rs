@@ -0,0 +1,283 @@
+// 音频预处理管线
+// 转录前按配置顺序依次应用去直流偏置、降噪、裁剪、重采样、归一化、响度归一化等步骤
+
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+use super::recorder::{resample, to_mono, TARGET_SAMPLE_RATE};
+use super::loudness::{self, TARGET_LUFS};
+use super::utils::{self, VAD_THRESHOLD};
+use super::AudioData;
+
+/// 静音检测与噪声门的分帧时长 (毫秒)
+const FRAME_MS: usize = 20;
+
+/// 裁剪静音时在语音前后保留的时长 (毫秒)，避免切掉弱起音与尾音
+const TRIM_PADDING_MS: usize = 100;
+
+/// 默认管线
+pub const DEFAULT_PIPELINE: &[PipelineStage] = &[
+    PipelineStage::DcOffset,
+    PipelineStage::Trim,
+    PipelineStage::Resample,
+    PipelineStage::Normalize,
+];
+
+/// 管线配置错误
+#[derive(Debug, Error, PartialEq)]
+pub enum PipelineError {
+    #[error("未知的音频预处理阶段: {0} (可选: dc_offset, denoise, trim, resample, normalize, loudness)")]
+    UnknownStage(String),
+}
+
+/// 管线阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipelineStage {
+    /// 去除直流偏置
+    DcOffset,
+    /// 噪声门：静音帧置零
+    Denoise,
+    /// 裁剪首尾静音
+    Trim,
+    /// 转单声道并重采样到目标采样率
+    Resample,
+    /// 峰值归一化
+    Normalize,
+    /// 响度归一化到 -23 LUFS (EBU R128)
+    Loudness,
+}
+
+impl PipelineStage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PipelineStage::DcOffset => "dc_offset",
+            PipelineStage::Denoise => "denoise",
+            PipelineStage::Trim => "trim",
+            PipelineStage::Resample => "resample",
+            PipelineStage::Normalize => "normalize",
+            PipelineStage::Loudness => "loudness",
+        }
+    }
+
+    /// 应用本阶段；无需处理时原样返回 (保留 i16 透传数据)
+    pub fn apply(&self, audio: AudioData) -> AudioData {
+        self.apply_with(audio, VAD_THRESHOLD)
+    }
+
+    /// 应用本阶段，降噪时 RMS 低于 `denoise_threshold` 的帧置零
+    fn apply_with(&self, audio: AudioData, denoise_threshold: f32) -> AudioData {
+        if audio.is_empty() {
+            return audio;
+        }
+
+        match self {
+            PipelineStage::DcOffset => utils::remove_dc_offset(&audio),
+            PipelineStage::Denoise => denoise(audio, denoise_threshold),
+            PipelineStage::Trim => trim(audio),
+            PipelineStage::Resample => resample_to_target(audio),
+            PipelineStage::Normalize => normalize(audio),
+            PipelineStage::Loudness => loudness::normalize_loudness(&audio, TARGET_LUFS),
+        }
+    }
+}
+
+impl FromStr for PipelineStage {
+    type Err = PipelineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "dc_offset" => Ok(PipelineStage::DcOffset),
+            "denoise" => Ok(PipelineStage::Denoise),
+            "trim" => Ok(PipelineStage::Trim),
+            "resample" => Ok(PipelineStage::Resample),
+            "normalize" => Ok(PipelineStage::Normalize),
+            "loudness" => Ok(PipelineStage::Loudness),
+            _ => Err(PipelineError::UnknownStage(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for PipelineStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 音频预处理管线
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pipeline {
+    stages: Vec<PipelineStage>,
+    /// 降噪阶段的帧 RMS 门限，越高降噪越强
+    denoise_threshold: f32,
+}
+
+impl Pipeline {
+    pub fn new(stages: Vec<PipelineStage>) -> Self {
+        Self { stages, denoise_threshold: VAD_THRESHOLD }
+    }
+
+    /// 设置降噪门限
+    pub fn with_denoise_threshold(mut self, threshold: f32) -> Self {
+        self.denoise_threshold = threshold;
+        self
+    }
+
+    /// 由阶段名列表构建，遇到未知阶段名时报错
+    pub fn from_names<S: AsRef<str>>(names: &[S]) -> Result<Self, PipelineError> {
+        let stages = names
+            .iter()
+            .map(|name| name.as_ref().parse())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(stages))
+    }
+
+    pub fn stages(&self) -> &[PipelineStage] {
+        &self.stages
+    }
+
+    pub fn denoise_threshold(&self) -> f32 {
+        self.denoise_threshold
+    }
+
+    /// 依次应用各阶段
+    pub fn process(&self, audio: AudioData) -> AudioData {
+        self.stages
+            .iter()
+            .fold(audio, |audio, stage| stage.apply_with(audio, self.denoise_threshold))
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new(DEFAULT_PIPELINE.to_vec())
+    }
+}
+
+/// 默认管线的阶段名 (用于配置默认值)
+pub fn default_pipeline_names() -> Vec<String> {
+    DEFAULT_PIPELINE.iter().map(|stage| stage.name().to_string()).collect()
+}
+
+// ============================================================================
+// 各阶段实现
+// ============================================================================
+
+/// 一帧包含的采样数 (所有声道合计，按整帧对齐)
+fn frame_len(audio: &AudioData) -> usize {
+    let channels = audio.channels.max(1) as usize;
+    (audio.sample_rate as usize * FRAME_MS / 1000).max(1) * channels
+}
+
+fn is_silent_frame(frame: &[f32]) -> bool {
+    is_below(frame, VAD_THRESHOLD)
+}
+
+fn is_below(frame: &[f32], threshold: f32) -> bool {
+    utils::calculate_raw_rms(frame) < threshold
+}
+
+fn denoise(audio: AudioData, threshold: f32) -> AudioData {
+    let frame_len = frame_len(&audio);
+    if !audio.samples.chunks(frame_len).any(|frame| is_below(frame, threshold)) {
+        return audio;
+    }
+
+    let mut samples = audio.samples;
+    for frame in samples.chunks_mut(frame_len) {
+        if is_below(frame, threshold) {
+            frame.fill(0.0);
+        }
+    }
+    AudioData::new(samples, audio.sample_rate, audio.channels)
+}
+
+fn trim(audio: AudioData) -> AudioData {
+    let frame_len = frame_len(&audio);
+    let frames: Vec<&[f32]> = audio.samples.chunks(frame_len).collect();
+
+    // 全部为静音时保持原样，由音频诊断提示
+    let Some(first) = frames.iter().position(|frame| !is_silent_frame(frame)) else {
+        return audio;
+    };
+    let last = frames.iter().rposition(|frame| !is_silent_frame(frame)).unwrap_or(first);
+
+    let padding = TRIM_PADDING_MS.div_ceil(FRAME_MS);
+    let start = first.saturating_sub(padding) * frame_len;
+    let end = ((last + 1 + padding) * frame_len).min(audio.samples.len());
+    if start == 0 && end == audio.samples.len() {
+        return audio;
+    }
+
+    AudioData::new(audio.samples[start..end].to_vec(), audio.sample_rate, audio.channels)
+}
+
+fn resample_to_target(audio: AudioData) -> AudioData {
+    if audio.channels == 1 && audio.sample_rate == TARGET_SAMPLE_RATE {
+        return audio;
+    }
+
+    let mono = to_mono(&audio.samples, audio.channels);
+    AudioData::new(resample(&mono, audio.sample_rate, TARGET_SAMPLE_RATE), TARGET_SAMPLE_RATE, 1)
+}
+
+fn normalize(audio: AudioData) -> AudioData {
+    let peak = utils::calculate_peak(&audio.samples);
+    if peak <= 0.0 || peak >= 1.0 {
+        return audio;
+    }
+
+    let mut samples = audio.samples;
+    utils::normalize(&mut samples);
+    AudioData::new(samples, audio.sample_rate, audio.channels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 前后各 1 秒静音，中间 1 秒 440Hz 正弦 (幅度 0.5)
+    fn padded_tone(sample_rate: u32) -> AudioData {
+        let second = sample_rate as usize;
+        let mut samples = vec![0.0; second];
+        samples.extend((0..second).map(|i| {
+            0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / sample_rate as f32).sin()
+        }));
+        samples.extend(vec![0.0; second]);
+        AudioData::new(samples, sample_rate, 1)
+    }
+
+    #[test]
+    fn test_from_names_validates_stages() {
+        let pipeline = Pipeline::from_names(&["dc_offset", "trim", " Resample ", "normalize"]).unwrap();
+        assert_eq!(pipeline, Pipeline::default());
+
+        assert_eq!(
+            Pipeline::from_names(&["trim", "reverb"]),
+            Err(PipelineError::UnknownStage("reverb".to_string()))
+        );
+        assert!(Pipeline::from_names::<&str>(&[]).unwrap().stages().is_empty());
+    }
+
+    #[test]
+    fn test_default_pipeline_trims_resamples_and_normalizes() {
+        let audio = Pipeline::default().process(padded_tone(48000));
+
+        assert_eq!(audio.sample_rate, TARGET_SAMPLE_RATE);
+        // 1 秒语音加前后各 100ms 保留
+        assert!((1150..=1250).contains(&audio.duration_ms), "{}", audio.duration_ms);
+        assert!((utils::calculate_peak(&audio.samples) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_noop_stages_keep_pcm_passthrough() {
+        let pcm: Vec<i16> = (0..16000).map(|i| ((i % 200) as i16 - 100) * 300).collect();
+        let audio = AudioData::from_i16(pcm.clone(), TARGET_SAMPLE_RATE, 1);
+
+        let audio = Pipeline::new(vec![PipelineStage::Trim, PipelineStage::Resample]).process(audio);
+        assert_eq!(audio.pcm_i16(), Some(pcm.as_slice()));
+
+        let audio = Pipeline::new(vec![PipelineStage::Normalize]).process(audio);
+        assert!(audio.pcm_i16().is_none());
+    }
+}
@@ -0,0 +1,95 @@
+// 录音处理预设
+// 每个预设对应一套预处理管线、降噪强度、实时噪声门与静音压缩参数，免去逐项调整 DSP 参数；
+// 预设只提供默认值，ASRConfig 中显式设置的字段优先 (见 ASRConfig::audio_pipeline 等)
+
+use serde::{Deserialize, Serialize};
+
+use super::pipeline::PipelineStage;
+use crate::voice::config::NoiseGateConfig;
+
+/// 录音处理预设
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioPreset {
+    /// 会议：多人远近不一，响度归一化拉齐音量，压缩长停顿
+    Meeting,
+    /// 口述：近讲麦克风，尽量少处理以保留细节
+    Dictation,
+    /// 嘈杂环境：强降噪，噪声门门限更高
+    Noisy,
+}
+
+/// 预设对应的处理参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresetProfile {
+    /// 预处理管线
+    pub stages: &'static [PipelineStage],
+    /// 降噪阶段的帧 RMS 门限
+    pub denoise_threshold: f32,
+    /// 实时模式噪声门
+    pub noise_gate: NoiseGateConfig,
+    /// 转录前压缩静音段的最长保留时长 (毫秒)
+    pub compress_silence_ms: Option<u64>,
+}
+
+const MEETING_STAGES: &[PipelineStage] = &[
+    PipelineStage::DcOffset,
+    PipelineStage::Denoise,
+    PipelineStage::Trim,
+    PipelineStage::Resample,
+    PipelineStage::Loudness,
+];
+
+const DICTATION_STAGES: &[PipelineStage] = &[
+    PipelineStage::DcOffset,
+    PipelineStage::Trim,
+    PipelineStage::Resample,
+    PipelineStage::Normalize,
+];
+
+const NOISY_STAGES: &[PipelineStage] = &[
+    PipelineStage::DcOffset,
+    PipelineStage::Denoise,
+    PipelineStage::Trim,
+    PipelineStage::Resample,
+    PipelineStage::Loudness,
+];
+
+impl AudioPreset {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AudioPreset::Meeting => "meeting",
+            AudioPreset::Dictation => "dictation",
+            AudioPreset::Noisy => "noisy",
+        }
+    }
+
+    pub fn profile(&self) -> PresetProfile {
+        match self {
+            AudioPreset::Meeting => PresetProfile {
+                stages: MEETING_STAGES,
+                denoise_threshold: 0.01,
+                noise_gate: NoiseGateConfig { enabled: true, threshold_rms: 0.01, hangover_ms: 500 },
+                compress_silence_ms: Some(1500),
+            },
+            AudioPreset::Dictation => PresetProfile {
+                stages: DICTATION_STAGES,
+                denoise_threshold: 0.01,
+                noise_gate: NoiseGateConfig::default(),
+                compress_silence_ms: None,
+            },
+            AudioPreset::Noisy => PresetProfile {
+                stages: NOISY_STAGES,
+                denoise_threshold: 0.03,
+                noise_gate: NoiseGateConfig { enabled: true, threshold_rms: 0.03, hangover_ms: 400 },
+                compress_silence_ms: Some(1000),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for AudioPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -29,10 +29,16 @@ macro_rules! log_error {
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::Stream;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use thiserror::Error;
 
+use super::clock_drift::{ClockDriftEstimator, DeviceClock};
+use super::diagnostics::RunningDiagnostics;
+use super::encoder::{read_wav, recover_wav, IncrementalWavWriter};
+use super::tee::{AudioTee, DeviceTee};
 use super::{AudioData, utils};
+use crate::voice::config::{DownmixStrategy, WaveScale};
 
 /// API 要求的目标采样率 (16kHz)
 pub const TARGET_SAMPLE_RATE: u32 = 16000;
@@ -69,8 +75,119 @@ pub enum RecordingError {
     UnsupportedSampleFormat(String),
 }
 
-/// 音频级别回调类型
-pub type AudioLevelCallback = Box<dyn Fn(f32, Vec<f32>) + Send + 'static>;
+/// 客户端期望的采集参数 (缺省字段沿用设备默认配置)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+pub struct CaptureRequest {
+    #[serde(default)]
+    pub sample_rate: Option<u32>,
+    #[serde(default)]
+    pub channels: Option<u16>,
+}
+
+impl CaptureRequest {
+    pub fn is_empty(&self) -> bool {
+        self.sample_rate.is_none() && self.channels.is_none()
+    }
+
+    /// 实际参数是否与请求的字段一致
+    pub fn is_satisfied_by(&self, actual: CaptureParams) -> bool {
+        self.sample_rate.is_none_or(|rate| rate == actual.sample_rate)
+            && self.channels.is_none_or(|channels| channels == actual.channels)
+    }
+}
+
+/// 录音设备实际使用的采集参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct CaptureParams {
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+/// 选择输入配置：无请求时使用设备默认配置，否则取最接近请求的配置
+pub(crate) fn select_input_config(
+    device: &cpal::Device,
+    request: CaptureRequest,
+) -> Result<cpal::SupportedStreamConfig, RecordingError> {
+    let default = device
+        .default_input_config()
+        .map_err(|e| RecordingError::DeviceError(format!("无法获取默认音频配置: {}", e)))?;
+    if request.is_empty() {
+        return Ok(default);
+    }
+
+    match device.supported_input_configs() {
+        Ok(ranges) => Ok(closest_config(ranges.collect(), &default, request)),
+        Err(e) => {
+            log_warn!("无法枚举设备支持的配置，使用默认配置: {}", e);
+            Ok(default)
+        }
+    }
+}
+
+/// 在支持的配置范围中取最接近请求的配置
+///
+/// 优先匹配采样率，其次声道数，同等条件下沿用默认配置的采样格式
+fn closest_config(
+    ranges: Vec<cpal::SupportedStreamConfigRange>,
+    default: &cpal::SupportedStreamConfig,
+    request: CaptureRequest,
+) -> cpal::SupportedStreamConfig {
+    let want_rate = request.sample_rate.unwrap_or(default.sample_rate().0);
+    let want_channels = request.channels.unwrap_or(default.channels());
+
+    ranges
+        .into_iter()
+        .filter(|range| {
+            matches!(
+                range.sample_format(),
+                cpal::SampleFormat::F32 | cpal::SampleFormat::I16 | cpal::SampleFormat::U16
+            )
+        })
+        .map(|range| {
+            let rate = want_rate.clamp(range.min_sample_rate().0, range.max_sample_rate().0);
+            let score = (
+                rate.abs_diff(want_rate),
+                range.channels().abs_diff(want_channels),
+                range.sample_format() != default.sample_format(),
+            );
+            (score, range.with_sample_rate(cpal::SampleRate(rate)))
+        })
+        .min_by_key(|(score, _)| *score)
+        .map(|(_, config)| config)
+        .unwrap_or_else(|| default.clone())
+}
+
+/// 边录边写时未完成文件的后缀 (正常停止后去掉)
+const SPOOL_PART_SUFFIX: &str = ".part";
+
+/// 音频级别回调类型 (电平、波形、电平统计、本次上报对应的单声道采样及其采样率)
+pub type AudioLevelCallback = Box<dyn Fn(f32, Vec<f32>, utils::LevelStats, &[f32], u32) + Send + 'static>;
+
+/// 当前系统默认输入设备的名称 (没有可用设备时为 None)
+pub fn default_input_device_name() -> Option<String> {
+    cpal::default_host().default_input_device().and_then(|device| device.name().ok())
+}
+
+/// 录音设备断开回调类型 (在音频线程中调用，每次录音最多一次)
+pub type DeviceLostCallback = Arc<dyn Fn() + Send + Sync + 'static>;
+
+/// 创建录音流错误回调
+///
+/// 设备断开 (如拔出 USB 麦克风) 时通知上层停止录音，其他错误只记录日志
+pub(crate) fn stream_error_handler(
+    on_device_lost: Option<DeviceLostCallback>,
+) -> impl FnMut(cpal::StreamError) + Send + 'static {
+    let mut notified = false;
+    move |err| {
+        log_error!("录音流错误: {}", err);
+        if matches!(err, cpal::StreamError::DeviceNotAvailable) && !notified {
+            notified = true;
+            if let Some(ref callback) = on_device_lost {
+                callback();
+            }
+        }
+    }
+}
 
 /// 音频录制器
 pub struct AudioRecorder {
@@ -82,6 +199,24 @@ pub struct AudioRecorder {
     stream: Option<Stream>,
     level_callback: Arc<Mutex<Option<AudioLevelCallback>>>,
     smoothed_level: Arc<Mutex<f32>>,
+    /// 边录边写的目标文件 (None 时仅保存在内存)
+    spool_path: Option<PathBuf>,
+    spool: Arc<Mutex<Option<IncrementalWavWriter>>>,
+    /// 期望的采集参数
+    capture_request: CaptureRequest,
+    /// 下次录音使用的旁路转发
+    tee_target: Option<AudioTee>,
+    tee: Arc<Mutex<Option<DeviceTee>>>,
+    /// 整段录音转单声道的方式
+    downmix: DownmixStrategy,
+    /// 波形柱高的刻度
+    wave_scale: WaveScale,
+    /// 录音设备断开时的通知
+    device_lost_callback: Option<DeviceLostCallback>,
+    /// 设备实际采样率估计
+    clock: Arc<Mutex<ClockDriftEstimator>>,
+    /// 边录边累积的音频诊断
+    diagnostics: Arc<Mutex<RunningDiagnostics>>,
 }
 
 impl AudioRecorder {
@@ -95,12 +230,72 @@ impl AudioRecorder {
             stream: None,
             level_callback: Arc::new(Mutex::new(None)),
             smoothed_level: Arc::new(Mutex::new(0.0)),
+            spool_path: None,
+            spool: Arc::new(Mutex::new(None)),
+            capture_request: CaptureRequest::default(),
+            downmix: DownmixStrategy::default(),
+            wave_scale: WaveScale::default(),
+            tee_target: None,
+            tee: Arc::new(Mutex::new(None)),
+            device_lost_callback: None,
+            clock: Arc::new(Mutex::new(ClockDriftEstimator::new(48000))),
+            diagnostics: Arc::new(Mutex::new(RunningDiagnostics::new(48000, 1))),
         })
     }
 
+    /// 设置边录边写的 WAV 文件路径
+    ///
+    /// 录音期间写入 `<path>.part` 并定期回填头部，停止时 finalize 并重命名为 `<path>`
+    pub fn set_spool_path(&mut self, path: Option<PathBuf>) {
+        self.spool_path = path;
+    }
+
+    /// 设置期望的采集参数 (下次 `start` 生效，设备不支持时取最接近的配置)
+    pub fn set_capture_request(&mut self, request: CaptureRequest) {
+        self.capture_request = request;
+    }
+
+    /// 设置多声道录音转单声道的方式 (停止时作用于整段录音)
+    pub fn set_downmix(&mut self, strategy: DownmixStrategy) {
+        self.downmix = strategy;
+    }
+
+    /// 设置波形柱高的刻度 (下次 `start` 生效)
+    pub fn set_wave_scale(&mut self, scale: WaveScale) {
+        self.wave_scale = scale;
+    }
+
+    /// 设置录音设备断开时的通知 (下次 `start` 生效)
+    pub fn set_device_lost_callback(&mut self, callback: DeviceLostCallback) {
+        self.device_lost_callback = Some(callback);
+    }
+
+    /// 设备实际使用的采集参数 (`start` 之后有效)
+    pub fn capture_params(&self) -> CaptureParams {
+        CaptureParams {
+            sample_rate: self.device_sample_rate,
+            channels: self.channels,
+        }
+    }
+
+    /// 根据回调时间估计出的设备时钟，录音时长不足以估计时为 None
+    pub fn device_clock(&self) -> Option<DeviceClock> {
+        self.clock.lock().unwrap().estimate()
+    }
+
+    /// 本次录音的增量诊断 (`start` 时重置，录音过程中持续累积)
+    pub fn diagnostics(&self) -> Arc<Mutex<RunningDiagnostics>> {
+        Arc::clone(&self.diagnostics)
+    }
+
+    /// 设置录音旁路转发 (下次 `start` 生效)
+    pub fn set_tee(&mut self, tee: Option<AudioTee>) {
+        self.tee_target = tee;
+    }
+
     pub fn set_level_callback<F>(&mut self, callback: F)
     where
-        F: Fn(f32, Vec<f32>) + Send + 'static,
+        F: Fn(f32, Vec<f32>, utils::LevelStats, &[f32], u32) + Send + 'static,
     {
         let mut cb = self.level_callback.lock().unwrap();
         *cb = Some(Box::new(callback));
@@ -126,9 +321,7 @@ impl AudioRecorder {
             .default_input_device()
             .ok_or_else(|| RecordingError::MicrophoneUnavailable("没有找到默认音频输入设备".to_string()))?;
 
-        let supported_config = device
-            .default_input_config()
-            .map_err(|e| RecordingError::DeviceError(format!("无法获取默认音频配置: {}", e)))?;
+        let supported_config = select_input_config(&device, self.capture_request)?;
 
         log_debug!("设备支持的配置: {:?}", supported_config);
 
@@ -143,15 +336,38 @@ impl AudioRecorder {
             TARGET_SAMPLE_RATE
         );
 
+        if let Some(ref path) = self.spool_path {
+            let part_path = spool_part_path(path);
+            match IncrementalWavWriter::create(&part_path, self.device_sample_rate, self.channels) {
+                Ok(writer) => {
+                    log_info!("录音同时写入文件: {}", part_path.display());
+                    *self.spool.lock().unwrap() = Some(writer);
+                }
+                Err(e) => {
+                    log_warn!("创建录音文件失败，仅保存在内存: {}", e);
+                }
+            }
+        }
+
+        *self.tee.lock().unwrap() = self.tee_target.take()
+            .map(|tee| DeviceTee::new(tee, self.device_sample_rate, self.channels));
+        *self.clock.lock().unwrap() = ClockDriftEstimator::new(self.device_sample_rate);
+        *self.diagnostics.lock().unwrap() = RunningDiagnostics::new(self.device_sample_rate, self.channels);
+
         let audio_data = Arc::clone(&self.audio_data);
         let is_recording = Arc::clone(&self.is_recording);
         let level_callback = Arc::clone(&self.level_callback);
         let smoothed_level = Arc::clone(&self.smoothed_level);
+        let spool = Arc::clone(&self.spool);
+        let tee = Arc::clone(&self.tee);
+        let clock = Arc::clone(&self.clock);
+        let diagnostics = Arc::clone(&self.diagnostics);
         let device_sample_rate = self.device_sample_rate;
         let channels = self.channels;
+        let wave_scale = self.wave_scale;
         let callback_counter = Arc::new(Mutex::new(0u32));
 
-        let err_fn = |err| log_error!("录音流错误: {}", err);
+        let err_fn = stream_error_handler(self.device_lost_callback.clone());
 
         let stream = match supported_config.sample_format() {
             cpal::SampleFormat::F32 => {
@@ -166,9 +382,14 @@ impl AudioRecorder {
                                 &is_recording,
                                 &level_callback,
                                 &smoothed_level,
+                                &spool,
+                                &tee,
+                                &clock,
+                                &diagnostics,
                                 &callback_counter,
                                 device_sample_rate,
                                 channels,
+                                wave_scale,
                             );
                         },
                         err_fn,
@@ -181,6 +402,10 @@ impl AudioRecorder {
                 let is_recording = Arc::clone(&is_recording);
                 let level_callback = Arc::clone(&level_callback);
                 let smoothed_level = Arc::clone(&smoothed_level);
+                let spool = Arc::clone(&spool);
+                let tee = Arc::clone(&tee);
+                let clock = Arc::clone(&clock);
+                let diagnostics = Arc::clone(&diagnostics);
                 let callback_counter = Arc::clone(&callback_counter);
 
                 device
@@ -194,9 +419,14 @@ impl AudioRecorder {
                                 &is_recording,
                                 &level_callback,
                                 &smoothed_level,
+                                &spool,
+                                &tee,
+                                &clock,
+                                &diagnostics,
                                 &callback_counter,
                                 device_sample_rate,
                                 channels,
+                                wave_scale,
                             );
                         },
                         err_fn,
@@ -209,6 +439,10 @@ impl AudioRecorder {
                 let is_recording = Arc::clone(&is_recording);
                 let level_callback = Arc::clone(&level_callback);
                 let smoothed_level = Arc::clone(&smoothed_level);
+                let spool = Arc::clone(&spool);
+                let tee = Arc::clone(&tee);
+                let clock = Arc::clone(&clock);
+                let diagnostics = Arc::clone(&diagnostics);
                 let callback_counter = Arc::clone(&callback_counter);
 
                 device
@@ -222,9 +456,14 @@ impl AudioRecorder {
                                 &is_recording,
                                 &level_callback,
                                 &smoothed_level,
+                                &spool,
+                                &tee,
+                                &clock,
+                                &diagnostics,
                                 &callback_counter,
                                 device_sample_rate,
                                 channels,
+                                wave_scale,
                             );
                         },
                         err_fn,
@@ -246,33 +485,58 @@ impl AudioRecorder {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn handle_audio_callback(
         data: &[f32],
         audio_data: &Arc<Mutex<Vec<f32>>>,
         is_recording: &Arc<Mutex<bool>>,
         level_callback: &Arc<Mutex<Option<AudioLevelCallback>>>,
         smoothed_level: &Arc<Mutex<f32>>,
+        spool: &Arc<Mutex<Option<IncrementalWavWriter>>>,
+        tee: &Arc<Mutex<Option<DeviceTee>>>,
+        clock: &Arc<Mutex<ClockDriftEstimator>>,
+        diagnostics: &Arc<Mutex<RunningDiagnostics>>,
         callback_counter: &Arc<Mutex<u32>>,
-        _device_sample_rate: u32,
-        _channels: u16,
+        device_sample_rate: u32,
+        channels: u16,
+        wave_scale: WaveScale,
     ) {
         if !*is_recording.lock().unwrap() {
             return;
         }
-
-        audio_data.lock().unwrap().extend_from_slice(data);
+        clock.lock().unwrap().record(data.len() / channels.max(1) as usize, std::time::Instant::now());
+        diagnostics.lock().unwrap().push(data);
+
+        let spooled = match spool.lock().unwrap().as_mut() {
+            Some(writer) => match writer.write_samples(data) {
+                Ok(()) => true,
+                Err(e) => {
+                    // 写文件失败后的数据留在内存，停止时与文件内容拼接
+                    log_error!("写入录音文件失败: {}", e);
+                    false
+                }
+            },
+            None => false,
+        };
+        if !spooled {
+            audio_data.lock().unwrap().extend_from_slice(data);
+        }
+        if let Some(tee) = tee.lock().unwrap().as_mut() {
+            tee.push(data);
+        }
 
         let mut counter = callback_counter.lock().unwrap();
         *counter += 1;
 
-        if *counter % 2 == 0 {
+        if counter.is_multiple_of(2) {
             let raw_level = utils::calculate_rms(data);
             let mut current_smoothed = smoothed_level.lock().unwrap();
             *current_smoothed = utils::smooth_level(*current_smoothed, raw_level);
-            let waveform = utils::generate_waveform(data, 9);
+            let waveform = utils::generate_waveform_with(data, 9, wave_scale);
 
             if let Some(ref callback) = *level_callback.lock().unwrap() {
-                callback(*current_smoothed, waveform);
+                let mono = to_mono(data, channels);
+                callback(*current_smoothed, waveform, utils::LevelStats::measure(data), &mono, device_sample_rate);
             }
         }
     }
@@ -290,10 +554,14 @@ impl AudioRecorder {
         *self.is_recording.lock().unwrap() = false;
         *self.recording_mode.lock().unwrap() = None;
         self.stream = None;
+        if let Some(tee) = self.tee.lock().unwrap().take() {
+            tee.finish();
+        }
 
         std::thread::sleep(std::time::Duration::from_millis(100));
 
-        let raw_audio = self.audio_data.lock().unwrap().clone();
+        let mut raw_audio = self.take_spooled_audio();
+        raw_audio.extend_from_slice(&self.audio_data.lock().unwrap());
         let original_len = raw_audio.len();
 
         if raw_audio.is_empty() {
@@ -301,13 +569,18 @@ impl AudioRecorder {
             return Ok(AudioData::new(Vec::new(), TARGET_SAMPLE_RATE, 1));
         }
 
-        let mono_audio = to_mono(&raw_audio, self.channels);
-        log_debug!("转单声道: {} -> {} 样本", original_len, mono_audio.len());
+        let mono_audio = utils::downmix(&AudioData::new(raw_audio, self.device_sample_rate, self.channels), self.downmix).samples;
+        log_debug!("转单声道 ({:?}): {} -> {} 样本", self.downmix, original_len, mono_audio.len());
 
-        let resampled_audio = resample(&mono_audio, self.device_sample_rate, TARGET_SAMPLE_RATE);
+        // 按实测的设备速率重采样，修正长录音的时钟漂移
+        let source_rate = self.clock.lock().unwrap().effective_rate();
+        if let Some(clock) = self.device_clock() {
+            log_info!("设备时钟: 名义 {}Hz, 实测 {:.2}Hz ({:+.0}ppm)", clock.nominal_rate, clock.measured_rate, clock.drift_ppm());
+        }
+        let resampled_audio = resample_from(&mono_audio, source_rate, TARGET_SAMPLE_RATE);
         log_debug!(
-            "降采样: {}Hz -> {}Hz, {} -> {} 样本",
-            self.device_sample_rate,
+            "降采样: {:.2}Hz -> {}Hz, {} -> {} 样本",
+            source_rate,
             TARGET_SAMPLE_RATE,
             mono_audio.len(),
             resampled_audio.len()
@@ -325,6 +598,50 @@ impl AudioRecorder {
         *self.recording_mode.lock().unwrap() = None;
         self.stream = None;
         self.audio_data.lock().unwrap().clear();
+        self.tee.lock().unwrap().take();
+
+        if let Some(writer) = self.spool.lock().unwrap().take() {
+            let path = writer.path().to_path_buf();
+            let _ = writer.finalize();
+            if let Err(e) = std::fs::remove_file(&path) {
+                log_warn!("删除录音文件失败: {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    /// finalize 录音文件并读回其中的采样 (设备采样率与声道)
+    fn take_spooled_audio(&mut self) -> Vec<f32> {
+        let Some(writer) = self.spool.lock().unwrap().take() else {
+            return Vec::new();
+        };
+
+        let part_path = match writer.finalize() {
+            Ok(path) => path,
+            Err(e) => {
+                log_error!("完成录音文件失败: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let final_path = self.spool_path.clone().unwrap_or_else(|| part_path.clone());
+        let path = match std::fs::rename(&part_path, &final_path) {
+            Ok(()) => final_path,
+            Err(e) => {
+                log_warn!("重命名录音文件失败: {}", e);
+                part_path
+            }
+        };
+
+        match read_wav(&path) {
+            Ok(audio) => {
+                log_info!("录音文件已保存: {}", path.display());
+                audio.samples
+            }
+            Err(e) => {
+                log_error!("读取录音文件失败: {}", e);
+                Vec::new()
+            }
+        }
     }
 
     pub fn is_recording(&self) -> bool {
@@ -336,6 +653,55 @@ impl AudioRecorder {
     }
 }
 
+// ============================================================================
+// 录音文件恢复
+// ============================================================================
+
+/// 录音期间使用的未完成文件路径
+pub fn spool_part_path(path: &Path) -> PathBuf {
+    let mut part = path.as_os_str().to_os_string();
+    part.push(SPOOL_PART_SUFFIX);
+    PathBuf::from(part)
+}
+
+/// 恢复目录下因崩溃未 finalize 的录音文件
+///
+/// 修复头部后去掉 `.part` 后缀，返回恢复成功的文件路径
+pub fn recover_spool_dir(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut recovered = Vec::new();
+    for entry in entries.flatten() {
+        let part_path = entry.path();
+        let Some(final_path) = part_path
+            .to_str()
+            .and_then(|p| p.strip_suffix(SPOOL_PART_SUFFIX))
+            .map(PathBuf::from)
+        else {
+            continue;
+        };
+
+        match recover_wav(&part_path) {
+            Ok(audio) => match std::fs::rename(&part_path, &final_path) {
+                Ok(()) => {
+                    log_info!("已恢复录音文件: {} ({}ms)", final_path.display(), audio.duration_ms);
+                    recovered.push(final_path);
+                }
+                Err(e) => {
+                    log_warn!("重命名恢复的录音文件失败: {}", e);
+                }
+            },
+            Err(e) => {
+                log_warn!("恢复录音文件失败: {}: {}", part_path.display(), e);
+            }
+        }
+    }
+
+    recovered
+}
+
 // ============================================================================
 // 音频格式转换函数
 // ============================================================================
@@ -354,9 +720,13 @@ pub fn convert_u16_to_f32(data: &[u16]) -> Vec<f32> {
 
 #[inline]
 pub fn convert_f32_to_i16(data: &[f32]) -> Vec<i16> {
-    data.iter()
-        .map(|&s| (s * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16)
-        .collect()
+    data.iter().map(|&s| f32_to_i16(s)).collect()
+}
+
+/// 单个 f32 采样量化为 i16 (与 convert_i16_to_f32 互逆)
+#[inline]
+pub fn f32_to_i16(sample: f32) -> i16 {
+    (sample * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16
 }
 
 pub fn to_mono(input: &[f32], channels: u16) -> Vec<f32> {
@@ -380,11 +750,16 @@ pub fn to_mono(input: &[f32], channels: u16) -> Vec<f32> {
 }
 
 pub fn resample(input: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
-    if from_rate == to_rate {
+    resample_from(input, from_rate as f64, to_rate)
+}
+
+/// 按非整数源速率重采样 (时钟漂移修正后的实测速率)
+pub fn resample_from(input: &[f32], from_rate: f64, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate as f64 {
         return input.to_vec();
     }
 
-    let ratio = from_rate as f64 / to_rate as f64;
+    let ratio = from_rate / to_rate as f64;
     let output_len = (input.len() as f64 / ratio) as usize;
     let mut output = Vec::with_capacity(output_len);
 
@@ -406,3 +781,55 @@ pub fn resample(input: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
 
 unsafe impl Send for AudioRecorder {}
 unsafe impl Sync for AudioRecorder {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cpal::{SampleFormat, SampleRate, SupportedBufferSize, SupportedStreamConfig, SupportedStreamConfigRange};
+
+    fn range(channels: u16, min: u32, max: u32, format: SampleFormat) -> SupportedStreamConfigRange {
+        SupportedStreamConfigRange::new(channels, SampleRate(min), SampleRate(max), SupportedBufferSize::Unknown, format)
+    }
+
+    #[test]
+    fn test_closest_config_prefers_rate_then_channels() {
+        let default = SupportedStreamConfig::new(2, SampleRate(48000), SupportedBufferSize::Unknown, SampleFormat::F32);
+        let ranges = || vec![
+            range(2, 44100, 48000, SampleFormat::F32),
+            range(1, 44100, 48000, SampleFormat::I16),
+            range(1, 8000, 16000, SampleFormat::I16),
+        ];
+
+        let request = CaptureRequest { sample_rate: Some(16000), channels: Some(1) };
+        let config = closest_config(ranges(), &default, request);
+        assert_eq!((config.sample_rate().0, config.channels()), (16000, 1));
+
+        // 不支持的采样率取最接近的值，声道沿用默认配置
+        let request = CaptureRequest { sample_rate: Some(96000), channels: None };
+        let config = closest_config(ranges(), &default, request);
+        assert_eq!((config.sample_rate().0, config.channels(), config.sample_format()), (48000, 2, SampleFormat::F32));
+
+        let actual = CaptureParams { sample_rate: 48000, channels: 2 };
+        assert!(!request.is_satisfied_by(actual));
+        assert!(CaptureRequest { sample_rate: None, channels: Some(2) }.is_satisfied_by(actual));
+        assert!(CaptureRequest::default().is_satisfied_by(actual));
+    }
+
+    #[test]
+    fn test_stream_error_handler_reports_device_lost_once() {
+        let count = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = Arc::clone(&count);
+        let mut handler = stream_error_handler(Some(Arc::new(move || {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        })));
+
+        handler(cpal::StreamError::BackendSpecific {
+            err: cpal::BackendSpecificError { description: "xrun".to_string() },
+        });
+        assert_eq!(count.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        handler(cpal::StreamError::DeviceNotAvailable);
+        handler(cpal::StreamError::DeviceNotAvailable);
+        assert_eq!(count.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+}
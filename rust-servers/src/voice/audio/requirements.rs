@@ -0,0 +1,180 @@
+// 引擎音频约束
+// 各 ASR 引擎声明可接受的采样率、声道、单次时长与格式，
+// 转录前据此转单声道、重采样，并在停顿处把超长音频切分为多段
+
+use super::recorder::{resample, to_mono, TARGET_SAMPLE_RATE};
+use super::utils;
+use super::AudioData;
+
+/// 切分时的分帧时长 (毫秒)
+const SPLIT_FRAME_MS: u64 = 20;
+
+/// 超长音频切分时，从时长上限往回寻找停顿的最大范围 (毫秒)
+const SPLIT_SEARCH_MS: u64 = 5_000;
+
+/// 引擎接受的音频格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    /// WAV 文件 (HTTP 上传)
+    Wav,
+    /// 16 位小端裸 PCM (实时流)
+    Pcm16,
+}
+
+/// 引擎对输入音频的约束
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioRequirements {
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    /// 最大声道数
+    pub channels: u16,
+    /// 单次请求的最大时长 (毫秒)，None 表示不限
+    pub max_duration_ms: Option<u64>,
+    pub accepted_formats: &'static [AudioFormat],
+}
+
+impl Default for AudioRequirements {
+    /// 常见 HTTP 接口的约束：8k-48k 单声道 WAV，不限时长
+    fn default() -> Self {
+        Self {
+            min_sample_rate: 8000,
+            max_sample_rate: 48000,
+            channels: 1,
+            max_duration_ms: None,
+            accepted_formats: &[AudioFormat::Wav],
+        }
+    }
+}
+
+impl AudioRequirements {
+    /// 固定采样率的单声道 PCM 实时流
+    pub fn pcm16_mono(sample_rate: u32) -> Self {
+        Self {
+            min_sample_rate: sample_rate,
+            max_sample_rate: sample_rate,
+            channels: 1,
+            max_duration_ms: None,
+            accepted_formats: &[AudioFormat::Pcm16],
+        }
+    }
+
+    /// 音频是否无需处理即可直接提交
+    pub fn is_satisfied_by(&self, audio: &AudioData) -> bool {
+        (self.min_sample_rate..=self.max_sample_rate).contains(&audio.sample_rate)
+            && audio.channels <= self.channels
+            && self.max_duration_ms.is_none_or(|max| audio.duration_ms <= max)
+    }
+
+    /// 约束范围内的目标采样率 (优先 16kHz，否则取最近的边界)
+    fn target_sample_rate(&self, sample_rate: u32) -> u32 {
+        if (self.min_sample_rate..=self.max_sample_rate).contains(&sample_rate) {
+            sample_rate
+        } else {
+            TARGET_SAMPLE_RATE.clamp(self.min_sample_rate, self.max_sample_rate)
+        }
+    }
+
+    /// 把音频调整为满足约束的一段或多段
+    pub fn conform(&self, audio: AudioData) -> Vec<AudioData> {
+        if self.is_satisfied_by(&audio) {
+            return vec![audio];
+        }
+
+        let audio = if audio.channels > self.channels {
+            AudioData::new(to_mono(&audio.samples, audio.channels), audio.sample_rate, 1)
+        } else {
+            audio
+        };
+
+        let sample_rate = self.target_sample_rate(audio.sample_rate);
+        let audio = if sample_rate != audio.sample_rate {
+            let mono = to_mono(&audio.samples, audio.channels);
+            AudioData::new(resample(&mono, audio.sample_rate, sample_rate), sample_rate, 1)
+        } else {
+            audio
+        };
+
+        match self.max_duration_ms {
+            Some(max_ms) if audio.duration_ms > max_ms => split_at_pauses(audio, max_ms),
+            _ => vec![audio],
+        }
+    }
+}
+
+/// 按时长上限切分，切点尽量落在上限之前最近的静音帧
+fn split_at_pauses(audio: AudioData, max_ms: u64) -> Vec<AudioData> {
+    let channels = audio.channels.max(1) as usize;
+    let rate = audio.sample_rate as u64;
+    let frame_len = (rate * SPLIT_FRAME_MS / 1000).max(1) as usize * channels;
+    let max_len = (rate * max_ms / 1000) as usize * channels;
+    if max_len < frame_len {
+        return vec![audio];
+    }
+    let search_frames = ((rate * SPLIT_SEARCH_MS / 1000) as usize * channels).min(max_len / 4) / frame_len;
+
+    let samples = &audio.samples;
+    let mut segments = Vec::new();
+    let mut start = 0;
+    while samples.len() - start > max_len {
+        let limit = start + max_len;
+        let cut = (1..=search_frames)
+            .map(|k| limit - k * frame_len)
+            .find(|&pos| utils::is_silence(&samples[pos..pos + frame_len]))
+            .map(|pos| pos + frame_len)
+            .unwrap_or(limit);
+        segments.push(AudioData::new(samples[start..cut].to_vec(), audio.sample_rate, audio.channels));
+        start = cut;
+    }
+    segments.push(AudioData::new(samples[start..].to_vec(), audio.sample_rate, audio.channels));
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(ms: usize, rate: usize) -> Vec<f32> {
+        (0..ms * rate / 1000)
+            .map(|i| (2.0 * std::f32::consts::PI * 300.0 * i as f32 / rate as f32).sin() * 0.3)
+            .collect()
+    }
+
+    #[test]
+    fn test_conform_downmixes_and_resamples() {
+        let requirements = AudioRequirements::pcm16_mono(16000);
+        let stereo = AudioData::new(vec![0.2; 48000 * 2], 48000, 2);
+
+        let segments = requirements.conform(stereo);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].sample_rate, 16000);
+        assert_eq!(segments[0].channels, 1);
+        assert_eq!(segments[0].duration_ms, 1000);
+        assert!(requirements.is_satisfied_by(&segments[0]));
+
+        // 范围内的采样率保持不变
+        let audio = AudioData::new(vec![0.0; 8000], 8000, 1);
+        assert!(AudioRequirements::default().is_satisfied_by(&audio));
+    }
+
+    #[test]
+    fn test_conform_splits_long_audio_at_pauses() {
+        let rate = 16000;
+        let requirements = AudioRequirements { max_duration_ms: Some(10_000), ..Default::default() };
+
+        // 8s 语音 + 0.5s 停顿 + 8s 语音：应在停顿处切开而不是 10s 处
+        let mut samples = tone(8000, rate);
+        samples.extend(vec![0.0; rate / 2]);
+        samples.extend(tone(8000, rate));
+        let segments = requirements.conform(AudioData::new(samples, rate as u32, 1));
+
+        assert_eq!(segments.len(), 2);
+        assert!(segments.iter().all(|s| requirements.is_satisfied_by(s)));
+        assert!(segments[0].duration_ms > 8000 && segments[0].duration_ms <= 8500);
+        assert_eq!(segments.iter().map(|s| s.duration_ms).sum::<u64>(), 16_500);
+
+        // 没有停顿时按上限硬切
+        let segments = requirements.conform(AudioData::new(tone(25_000, rate), rate as u32, 1));
+        let durations: Vec<u64> = segments.iter().map(|s| s.duration_ms).collect();
+        assert_eq!(durations, vec![10_000, 10_000, 5_000]);
+    }
+}
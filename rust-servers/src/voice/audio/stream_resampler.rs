@@ -0,0 +1,194 @@
+// 流式重采样模块
+// 实时模式下音频按小块到达，逐块独立重采样会在块边界丢失插值相位，
+// 这里保留上一块的尾部采样与输出位置，使分块结果与整段重采样一致
+
+/// 流式线性插值重采样器 (交错多声道)
+#[derive(Debug)]
+pub struct StreamResampler {
+    from_rate: f64,
+    to_rate: u32,
+    /// 名义速率相同，直接透传
+    passthrough: bool,
+    channels: usize,
+    /// 尚未完全消费的输入 (交错采样，可能含不完整的帧)
+    pending: Vec<f32>,
+    /// 已从 pending 中丢弃的帧数
+    consumed_frames: u64,
+    /// 下一个输出帧的序号
+    next_output: u64,
+    /// 最近一次调整源速率时的输出帧序号及其对应的源位置 (帧)
+    anchor_output: u64,
+    anchor_position: f64,
+}
+
+impl StreamResampler {
+    pub fn new(from_rate: u32, to_rate: u32, channels: u16) -> Self {
+        Self {
+            from_rate: from_rate as f64,
+            to_rate,
+            passthrough: from_rate == to_rate,
+            channels: channels.max(1) as usize,
+            pending: Vec::new(),
+            consumed_frames: 0,
+            next_output: 0,
+            anchor_output: 0,
+            anchor_position: 0.0,
+        }
+    }
+
+    /// 调整源速率 (时钟漂移修正)，之后的输出从当前位置起按新速率插值，已输出的部分不变
+    ///
+    /// 透传模式不做修正
+    pub fn set_from_rate(&mut self, from_rate: f64) {
+        if self.passthrough || from_rate <= 0.0 {
+            return;
+        }
+        self.anchor_position = self.source_position(self.next_output);
+        self.anchor_output = self.next_output;
+        self.from_rate = from_rate;
+    }
+
+    /// 第 `output` 个输出帧对应的源位置 (自开始以来的帧)
+    fn source_position(&self, output: u64) -> f64 {
+        self.anchor_position + (output - self.anchor_output) as f64 * self.from_rate / self.to_rate as f64
+    }
+
+    /// 处理一块输入，返回当前可确定的输出采样
+    ///
+    /// 插值需要右侧相邻帧，块末尾的最后一帧留到下一块或 `flush` 时输出
+    pub fn process(&mut self, chunk: &[f32]) -> Vec<f32> {
+        if self.passthrough {
+            return chunk.to_vec();
+        }
+        self.pending.extend_from_slice(chunk);
+        self.drain(false)
+    }
+
+    /// 输出剩余尾部 (末帧之后以末帧补齐插值) 并重置状态
+    pub fn flush(&mut self) -> Vec<f32> {
+        if self.passthrough {
+            return Vec::new();
+        }
+        let output = self.drain(true);
+        self.pending.clear();
+        self.consumed_frames = 0;
+        self.next_output = 0;
+        self.anchor_output = 0;
+        self.anchor_position = 0.0;
+        output
+    }
+
+    fn drain(&mut self, flush: bool) -> Vec<f32> {
+        let channels = self.channels;
+        let frames = self.pending.len() / channels;
+        let mut output = Vec::new();
+
+        loop {
+            // 以整数序号计算源位置，避免累加误差
+            let position = self.source_position(self.next_output) - self.consumed_frames as f64;
+            let idx_floor = position.floor() as usize;
+            let available = if flush { idx_floor < frames } else { idx_floor + 1 < frames };
+            if !available {
+                break;
+            }
+
+            let idx_ceil = (idx_floor + 1).min(frames - 1);
+            let frac = position - idx_floor as f64;
+            for channel in 0..channels {
+                let left = self.pending[idx_floor * channels + channel] as f64;
+                let right = self.pending[idx_ceil * channels + channel] as f64;
+                output.push((left * (1.0 - frac) + right * frac) as f32);
+            }
+            self.next_output += 1;
+        }
+
+        // 丢弃之后不再参与插值的帧
+        let position = self.source_position(self.next_output) - self.consumed_frames as f64;
+        let drop_frames = (position.floor() as usize).min(frames);
+        self.pending.drain(..drop_frames * channels);
+        self.consumed_frames += drop_frames as u64;
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voice::audio::recorder::{resample, resample_from};
+
+    fn sine(len: usize, rate: u32) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * std::f32::consts::PI * 440.0 * i as f32 / rate as f32).sin() * 0.5)
+            .collect()
+    }
+
+    fn process_chunked(resampler: &mut StreamResampler, input: &[f32], sizes: &[usize]) -> Vec<f32> {
+        let mut output = Vec::new();
+        let mut offset = 0;
+        for &size in sizes.iter().cycle() {
+            if offset >= input.len() {
+                break;
+            }
+            let end = (offset + size).min(input.len());
+            output.extend(resampler.process(&input[offset..end]));
+            offset = end;
+        }
+        output.extend(resampler.flush());
+        output
+    }
+
+    #[test]
+    fn test_chunked_matches_whole_buffer() {
+        for &(from, to) in &[(48000, 16000), (44100, 16000), (8000, 16000)] {
+            let input = sine(from as usize / 2, from);
+            let whole = resample(&input, from, to);
+
+            let mut resampler = StreamResampler::new(from, to, 1);
+            let chunked = process_chunked(&mut resampler, &input, &[480, 127, 1, 1024]);
+
+            // 整段重采样按向下取整截断长度，流式 flush 可能多输出末尾一个采样
+            assert!(chunked.len() >= whole.len() && chunked.len() <= whole.len() + 1, "{} -> {}", from, to);
+            for (i, (a, b)) in whole.iter().zip(&chunked).enumerate() {
+                assert!((a - b).abs() < 1e-5, "{} -> {} 第 {} 个采样: {} vs {}", from, to, i, a, b);
+            }
+        }
+
+        // 修正后的非整数源速率与整段按同一速率重采样一致
+        let input = sine(24000, 48000);
+        let whole = resample_from(&input, 48009.6, 16000);
+        let mut resampler = StreamResampler::new(48000, 16000, 1);
+        resampler.set_from_rate(48009.6);
+        let chunked = process_chunked(&mut resampler, &input, &[480, 333]);
+        assert!(chunked.len() >= whole.len() && chunked.len() <= whole.len() + 1);
+        assert!(whole.iter().zip(&chunked).all(|(a, b)| (a - b).abs() < 1e-5));
+
+        // 中途调整速率时，下一个输出仍在原位置，此后按新速率步进
+        let mut resampler = StreamResampler::new(48000, 16000, 1);
+        let ramp: Vec<f32> = (0..4800).map(|i| i as f32).collect();
+        let mut output = resampler.process(&ramp[..2400]);
+        let before = output.len();
+        resampler.set_from_rate(48480.0);
+        output.extend(resampler.process(&ramp[2400..]));
+        assert_eq!(output[before], (before * 3) as f32);
+        assert!((output[before + 1] - (output[before] + 3.03)).abs() < 1e-3, "{}", output[before + 1]);
+    }
+
+    #[test]
+    fn test_multichannel_and_passthrough() {
+        // 左声道为递增序列，右声道为其相反数
+        let input: Vec<f32> = (0..96).flat_map(|i| [i as f32, -(i as f32)]).collect();
+        let mut resampler = StreamResampler::new(48000, 16000, 2);
+        let output = process_chunked(&mut resampler, &input, &[7, 30, 64]);
+
+        assert_eq!(output.len(), 64);
+        for (frame, pair) in output.chunks(2).enumerate() {
+            assert_eq!(pair[0], (frame * 3) as f32);
+            assert_eq!(pair[1], -((frame * 3) as f32));
+        }
+
+        let mut same_rate = StreamResampler::new(16000, 16000, 1);
+        assert_eq!(same_rate.process(&[0.1, 0.2]), vec![0.1, 0.2]);
+        assert!(same_rate.flush().is_empty());
+    }
+}
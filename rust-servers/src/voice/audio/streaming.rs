@@ -13,23 +13,21 @@ macro_rules! log_warn {
     };
 }
 
-macro_rules! log_error {
-    ($($arg:tt)*) => {{
-        eprintln!("[ERROR] [streaming] {}", format!($($arg)*))
-    }};
-}
-
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::Stream;
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 
 use super::recorder::{
-    convert_i16_to_f32, convert_u16_to_f32, resample, to_mono, RecordingError, RecordingMode,
-    TARGET_SAMPLE_RATE,
+    convert_i16_to_f32, convert_u16_to_f32, f32_to_i16, resample_from, select_input_config, stream_error_handler,
+    to_mono, CaptureParams, CaptureRequest, DeviceLostCallback, RecordingError, RecordingMode, TARGET_SAMPLE_RATE,
 };
+use super::clock_drift::{ClockDriftEstimator, DeviceClock};
+use super::diagnostics::RunningDiagnostics;
+use super::stream_resampler::StreamResampler;
 use super::utils;
 use super::AudioData;
+use crate::voice::config::{DownmixStrategy, WaveScale};
 
 /// 每个音频块的样本数 (0.2秒 @ 16kHz = 3200 样本)
 pub const CHUNK_SAMPLES: usize = 3200;
@@ -44,8 +42,54 @@ pub struct AudioChunkData {
     pub timestamp_ms: u64,
 }
 
-/// 音频级别回调类型
-pub type StreamingLevelCallback = Box<dyn Fn(f32, Vec<f32>) + Send + 'static>;
+/// 音频级别回调类型 (电平、波形、电平统计、本次上报对应的单声道采样及其采样率)
+pub type StreamingLevelCallback = Box<dyn Fn(f32, Vec<f32>, utils::LevelStats, &[f32], u32) + Send + 'static>;
+
+/// PCM 块累加器
+///
+/// 内部以 i16 存储：f32 输入入队时量化，i16 输入直接透传
+#[derive(Debug, Default)]
+pub struct ChunkAccumulator {
+    pending: Vec<i16>,
+}
+
+impl ChunkAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加 f32 采样 (范围 -1.0 到 1.0)
+    pub fn push_f32(&mut self, samples: &[f32]) {
+        self.pending.extend(samples.iter().map(|&s| f32_to_i16(s)));
+    }
+
+    /// 追加 i16 采样 (不做任何转换)
+    pub fn push_i16(&mut self, samples: &[i16]) {
+        self.pending.extend_from_slice(samples);
+    }
+
+    /// 取出一个完整的块，不足 CHUNK_SAMPLES 时返回 None
+    pub fn pop_chunk(&mut self) -> Option<Vec<i16>> {
+        if self.pending.len() < CHUNK_SAMPLES {
+            return None;
+        }
+        Some(self.pending.drain(..CHUNK_SAMPLES).collect())
+    }
+
+    /// 取出剩余不足一块的采样 (输入结束时发送尾部)
+    pub fn take_rest(&mut self) -> Option<Vec<i16>> {
+        (!self.pending.is_empty()).then(|| std::mem::take(&mut self.pending))
+    }
+
+    /// 未成块的采样数
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
 
 /// 流式音频录制器
 pub struct StreamingRecorder {
@@ -56,9 +100,23 @@ pub struct StreamingRecorder {
     stream: Option<Stream>,
     chunk_sender: Option<mpsc::Sender<AudioChunkData>>,
     full_audio_data: Arc<Mutex<Vec<f32>>>,
+    /// 透传路径录制的原始 i16 采样 (设备已是 16kHz 单声道 i16 时使用)
+    full_pcm_data: Arc<Mutex<Vec<i16>>>,
     level_callback: Arc<Mutex<Option<StreamingLevelCallback>>>,
     smoothed_level: Arc<Mutex<f32>>,
     start_time: Arc<Mutex<Option<std::time::Instant>>>,
+    /// 期望的采集参数
+    capture_request: CaptureRequest,
+    /// 整段录音转单声道的方式
+    downmix: DownmixStrategy,
+    /// 波形柱高的刻度
+    wave_scale: WaveScale,
+    /// 录音设备断开时的通知
+    device_lost_callback: Option<DeviceLostCallback>,
+    /// 设备实际采样率估计
+    clock: Arc<Mutex<ClockDriftEstimator>>,
+    /// 边录边累积的音频诊断
+    diagnostics: Arc<Mutex<RunningDiagnostics>>,
 }
 
 impl StreamingRecorder {
@@ -71,15 +129,60 @@ impl StreamingRecorder {
             stream: None,
             chunk_sender: None,
             full_audio_data: Arc::new(Mutex::new(Vec::new())),
+            full_pcm_data: Arc::new(Mutex::new(Vec::new())),
             level_callback: Arc::new(Mutex::new(None)),
             smoothed_level: Arc::new(Mutex::new(0.0)),
             start_time: Arc::new(Mutex::new(None)),
+            capture_request: CaptureRequest::default(),
+            downmix: DownmixStrategy::default(),
+            wave_scale: WaveScale::default(),
+            device_lost_callback: None,
+            clock: Arc::new(Mutex::new(ClockDriftEstimator::new(48000))),
+            diagnostics: Arc::new(Mutex::new(RunningDiagnostics::new(48000, 1))),
         })
     }
 
+    /// 设置期望的采集参数 (下次 `start_streaming` 生效，设备不支持时取最接近的配置)
+    pub fn set_capture_request(&mut self, request: CaptureRequest) {
+        self.capture_request = request;
+    }
+
+    /// 设置多声道录音转单声道的方式 (停止时作用于整段录音)
+    pub fn set_downmix(&mut self, strategy: DownmixStrategy) {
+        self.downmix = strategy;
+    }
+
+    /// 设置波形柱高的刻度 (下次 `start_streaming` 生效)
+    pub fn set_wave_scale(&mut self, scale: WaveScale) {
+        self.wave_scale = scale;
+    }
+
+    /// 设置录音设备断开时的通知 (下次 `start_streaming` 生效)
+    pub fn set_device_lost_callback(&mut self, callback: DeviceLostCallback) {
+        self.device_lost_callback = Some(callback);
+    }
+
+    /// 设备实际使用的采集参数 (`start_streaming` 之后有效)
+    pub fn capture_params(&self) -> CaptureParams {
+        CaptureParams {
+            sample_rate: self.device_sample_rate,
+            channels: self.channels,
+        }
+    }
+
+    /// 根据回调时间估计出的设备时钟，录音时长不足以估计时为 None
+    pub fn device_clock(&self) -> Option<DeviceClock> {
+        self.clock.lock().unwrap().estimate()
+    }
+
+    /// 本次录音的增量诊断 (`start_streaming` 时重置，录音过程中持续累积)
+    pub fn diagnostics(&self) -> Arc<Mutex<RunningDiagnostics>> {
+        Arc::clone(&self.diagnostics)
+    }
+
     pub fn set_level_callback<F>(&mut self, callback: F)
     where
-        F: Fn(f32, Vec<f32>) + Send + 'static,
+        F: Fn(f32, Vec<f32>, utils::LevelStats, &[f32], u32) + Send + 'static,
     {
         let mut cb = self.level_callback.lock().unwrap();
         *cb = Some(Box::new(callback));
@@ -99,6 +202,7 @@ impl StreamingRecorder {
         log_info!("开始流式录音，模式: {:?}", mode);
 
         self.full_audio_data.lock().unwrap().clear();
+        self.full_pcm_data.lock().unwrap().clear();
         *self.is_recording.lock().unwrap() = true;
         *self.recording_mode.lock().unwrap() = Some(mode);
         *self.smoothed_level.lock().unwrap() = 0.0;
@@ -112,9 +216,7 @@ impl StreamingRecorder {
             RecordingError::MicrophoneUnavailable("没有找到默认音频输入设备".to_string())
         })?;
 
-        let supported_config = device
-            .default_input_config()
-            .map_err(|e| RecordingError::DeviceError(format!("无法获取默认音频配置: {}", e)))?;
+        let supported_config = select_input_config(&device, self.capture_request)?;
 
         let config = supported_config.config();
         self.device_sample_rate = config.sample_rate.0;
@@ -135,11 +237,23 @@ impl StreamingRecorder {
         let start_time = Arc::clone(&self.start_time);
         let device_sample_rate = self.device_sample_rate;
         let channels = self.channels;
-
-        let pending_samples: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
+        let wave_scale = self.wave_scale;
+        *self.clock.lock().unwrap() = ClockDriftEstimator::new(device_sample_rate);
+        let clock = Arc::clone(&self.clock);
+        *self.diagnostics.lock().unwrap() = RunningDiagnostics::new(device_sample_rate, channels);
+        let diagnostics = Arc::clone(&self.diagnostics);
+
+        let pending_samples: Arc<Mutex<ChunkAccumulator>> =
+            Arc::new(Mutex::new(ChunkAccumulator::new()));
         let callback_counter: Arc<Mutex<u32>> = Arc::new(Mutex::new(0));
+        // 跨回调保持插值相位，避免逐块重采样在块边界产生不连续
+        let resampler: Arc<Mutex<StreamResampler>> = Arc::new(Mutex::new(StreamResampler::new(
+            device_sample_rate,
+            TARGET_SAMPLE_RATE,
+            1,
+        )));
 
-        let err_fn = |err| log_error!("录音流错误: {}", err);
+        let err_fn = stream_error_handler(self.device_lost_callback.clone());
 
         let stream = match supported_config.sample_format() {
             cpal::SampleFormat::F32 => {
@@ -161,8 +275,11 @@ impl StreamingRecorder {
                                 &smoothed_level,
                                 &counter,
                                 &start_time,
-                                device_sample_rate,
+                                &resampler,
+                                &clock,
+                                &diagnostics,
                                 channels,
+                                wave_scale,
                             );
                         },
                         err_fn,
@@ -173,17 +290,43 @@ impl StreamingRecorder {
             cpal::SampleFormat::I16 => {
                 let is_recording = Arc::clone(&is_recording);
                 let full_audio_data = Arc::clone(&full_audio_data);
+                let full_pcm_data = Arc::clone(&self.full_pcm_data);
                 let pending = Arc::clone(&pending_samples);
                 let level_callback = Arc::clone(&level_callback);
                 let smoothed_level = Arc::clone(&smoothed_level);
                 let counter = Arc::clone(&callback_counter);
                 let start_time = Arc::clone(&start_time);
+                let clock = Arc::clone(&clock);
+                let diagnostics = Arc::clone(&diagnostics);
                 let chunk_tx = chunk_tx.clone();
+                // 设备输出已是目标格式时跳过 f32 转换与重采样
+                let passthrough = channels == 1 && device_sample_rate == TARGET_SAMPLE_RATE;
+                if passthrough {
+                    log_info!("设备输出为 16kHz 单声道 i16，启用 PCM 透传");
+                }
 
                 device
                     .build_input_stream(
                         &config,
                         move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                            if passthrough {
+                                Self::handle_passthrough_callback(
+                                    data,
+                                    &is_recording,
+                                    &full_pcm_data,
+                                    &pending,
+                                    &chunk_tx,
+                                    &level_callback,
+                                    &smoothed_level,
+                                    &counter,
+                                    &start_time,
+                                    &clock,
+                                    &diagnostics,
+                                    channels,
+                                    wave_scale,
+                                );
+                                return;
+                            }
                             let f32_data = convert_i16_to_f32(data);
                             Self::handle_streaming_callback(
                                 &f32_data,
@@ -195,8 +338,11 @@ impl StreamingRecorder {
                                 &smoothed_level,
                                 &counter,
                                 &start_time,
-                                device_sample_rate,
+                                &resampler,
+                                &clock,
+                                &diagnostics,
                                 channels,
+                                wave_scale,
                             );
                         },
                         err_fn,
@@ -212,6 +358,8 @@ impl StreamingRecorder {
                 let smoothed_level = Arc::clone(&smoothed_level);
                 let counter = Arc::clone(&callback_counter);
                 let start_time = Arc::clone(&start_time);
+                let clock = Arc::clone(&clock);
+                let diagnostics = Arc::clone(&diagnostics);
                 let chunk_tx = chunk_tx.clone();
 
                 device
@@ -229,8 +377,11 @@ impl StreamingRecorder {
                                 &smoothed_level,
                                 &counter,
                                 &start_time,
-                                device_sample_rate,
+                                &resampler,
+                                &clock,
+                                &diagnostics,
                                 channels,
+                                wave_scale,
                             );
                         },
                         err_fn,
@@ -261,51 +412,113 @@ impl StreamingRecorder {
         data: &[f32],
         is_recording: &Arc<Mutex<bool>>,
         full_audio_data: &Arc<Mutex<Vec<f32>>>,
-        pending_samples: &Arc<Mutex<Vec<f32>>>,
+        pending_samples: &Arc<Mutex<ChunkAccumulator>>,
         chunk_tx: &mpsc::Sender<AudioChunkData>,
         level_callback: &Arc<Mutex<Option<StreamingLevelCallback>>>,
         smoothed_level: &Arc<Mutex<f32>>,
         callback_counter: &Arc<Mutex<u32>>,
         start_time: &Arc<Mutex<Option<std::time::Instant>>>,
-        device_sample_rate: u32,
+        resampler: &Arc<Mutex<StreamResampler>>,
+        clock: &Arc<Mutex<ClockDriftEstimator>>,
+        diagnostics: &Arc<Mutex<RunningDiagnostics>>,
         channels: u16,
+        wave_scale: WaveScale,
     ) {
         if !*is_recording.lock().unwrap() {
             return;
         }
 
         full_audio_data.lock().unwrap().extend_from_slice(data);
+        diagnostics.lock().unwrap().push(data);
 
         let mono = to_mono(data, channels);
-        let resampled = resample(&mono, device_sample_rate, TARGET_SAMPLE_RATE);
-
+        let mut resampler = resampler.lock().unwrap();
         {
-            let mut counter = callback_counter.lock().unwrap();
-            *counter += 1;
+            // 有可信估计后按实测速率重采样，避免长录音发给引擎的音频时长漂移
+            let mut clock = clock.lock().unwrap();
+            clock.record(mono.len(), std::time::Instant::now());
+            if let Some(estimate) = clock.estimate() {
+                resampler.set_from_rate(estimate.measured_rate);
+            }
+        }
+        let resampled = resampler.process(&mono);
+        drop(resampler);
 
-            if *counter % 2 == 0 {
-                let raw_level = utils::calculate_rms(&resampled);
-                let mut current_smoothed = smoothed_level.lock().unwrap();
-                *current_smoothed = utils::smooth_level(*current_smoothed, raw_level);
+        if Self::should_report_level(callback_counter) {
+            Self::report_level(&resampled, level_callback, smoothed_level, wave_scale);
+        }
 
-                let waveform = utils::generate_waveform(&resampled, 9);
+        let mut pending = pending_samples.lock().unwrap();
+        pending.push_f32(&resampled);
+        Self::emit_chunks(&mut pending, chunk_tx, start_time);
+    }
 
-                if let Some(ref callback) = *level_callback.lock().unwrap() {
-                    callback(*current_smoothed, waveform);
-                }
-            }
+    /// i16 透传回调 (设备已是 16kHz 单声道，无需重采样)
+    #[allow(clippy::too_many_arguments)]
+    fn handle_passthrough_callback(
+        data: &[i16],
+        is_recording: &Arc<Mutex<bool>>,
+        full_pcm_data: &Arc<Mutex<Vec<i16>>>,
+        pending_samples: &Arc<Mutex<ChunkAccumulator>>,
+        chunk_tx: &mpsc::Sender<AudioChunkData>,
+        level_callback: &Arc<Mutex<Option<StreamingLevelCallback>>>,
+        smoothed_level: &Arc<Mutex<f32>>,
+        callback_counter: &Arc<Mutex<u32>>,
+        start_time: &Arc<Mutex<Option<std::time::Instant>>>,
+        clock: &Arc<Mutex<ClockDriftEstimator>>,
+        diagnostics: &Arc<Mutex<RunningDiagnostics>>,
+        channels: u16,
+        wave_scale: WaveScale,
+    ) {
+        if !*is_recording.lock().unwrap() {
+            return;
+        }
+        // 透传不重采样，估计值仅用于诊断
+        clock.lock().unwrap().record(data.len() / channels.max(1) as usize, std::time::Instant::now());
+
+        full_pcm_data.lock().unwrap().extend_from_slice(data);
+
+        let f32_data = convert_i16_to_f32(data);
+        diagnostics.lock().unwrap().push(&f32_data);
+        if Self::should_report_level(callback_counter) {
+            Self::report_level(&f32_data, level_callback, smoothed_level, wave_scale);
         }
 
         let mut pending = pending_samples.lock().unwrap();
-        pending.extend(resampled);
+        pending.push_i16(data);
+        Self::emit_chunks(&mut pending, chunk_tx, start_time);
+    }
+
+    /// 每两次回调上报一次音量
+    fn should_report_level(callback_counter: &Arc<Mutex<u32>>) -> bool {
+        let mut counter = callback_counter.lock().unwrap();
+        *counter += 1;
+        counter.is_multiple_of(2)
+    }
+
+    fn report_level(
+        samples: &[f32],
+        level_callback: &Arc<Mutex<Option<StreamingLevelCallback>>>,
+        smoothed_level: &Arc<Mutex<f32>>,
+        wave_scale: WaveScale,
+    ) {
+        let raw_level = utils::calculate_rms(samples);
+        let mut current_smoothed = smoothed_level.lock().unwrap();
+        *current_smoothed = utils::smooth_level(*current_smoothed, raw_level);
 
-        while pending.len() >= CHUNK_SAMPLES {
-            let chunk_f32: Vec<f32> = pending.drain(..CHUNK_SAMPLES).collect();
-            let chunk_i16: Vec<i16> = chunk_f32
-                .iter()
-                .map(|&s| (s * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16)
-                .collect();
+        let waveform = utils::generate_waveform_with(samples, 9, wave_scale);
 
+        if let Some(ref callback) = *level_callback.lock().unwrap() {
+            callback(*current_smoothed, waveform, utils::LevelStats::measure(samples), samples, TARGET_SAMPLE_RATE);
+        }
+    }
+
+    fn emit_chunks(
+        pending: &mut ChunkAccumulator,
+        chunk_tx: &mpsc::Sender<AudioChunkData>,
+        start_time: &Arc<Mutex<Option<std::time::Instant>>>,
+    ) {
+        while let Some(samples) = pending.pop_chunk() {
             let timestamp_ms = start_time
                 .lock()
                 .unwrap()
@@ -313,7 +526,7 @@ impl StreamingRecorder {
                 .unwrap_or(0);
 
             let chunk_data = AudioChunkData {
-                samples: chunk_i16,
+                samples,
                 timestamp_ms,
             };
 
@@ -343,6 +556,16 @@ impl StreamingRecorder {
         self.stream = None;
         self.chunk_sender = None;
 
+        let raw_pcm = std::mem::take(&mut *self.full_pcm_data.lock().unwrap());
+        if !raw_pcm.is_empty() {
+            let audio_data = AudioData::from_i16(raw_pcm, TARGET_SAMPLE_RATE, 1);
+            log_info!(
+                "流式录音停止 (PCM 透传)，完整音频时长: {}ms",
+                audio_data.duration_ms
+            );
+            return Ok(audio_data);
+        }
+
         let raw_audio = self.full_audio_data.lock().unwrap().clone();
 
         if raw_audio.is_empty() {
@@ -350,8 +573,12 @@ impl StreamingRecorder {
             return Ok(AudioData::new(Vec::new(), TARGET_SAMPLE_RATE, 1));
         }
 
-        let mono_audio = to_mono(&raw_audio, self.channels);
-        let resampled_audio = resample(&mono_audio, self.device_sample_rate, TARGET_SAMPLE_RATE);
+        let mono_audio = utils::downmix(&AudioData::new(raw_audio, self.device_sample_rate, self.channels), self.downmix).samples;
+        let source_rate = self.clock.lock().unwrap().effective_rate();
+        if let Some(clock) = self.device_clock() {
+            log_info!("设备时钟: 名义 {}Hz, 实测 {:.2}Hz ({:+.0}ppm)", clock.nominal_rate, clock.measured_rate, clock.drift_ppm());
+        }
+        let resampled_audio = resample_from(&mono_audio, source_rate, TARGET_SAMPLE_RATE);
 
         let audio_data = AudioData::new(resampled_audio, TARGET_SAMPLE_RATE, 1);
         log_info!(
@@ -370,6 +597,7 @@ impl StreamingRecorder {
         self.stream = None;
         self.chunk_sender = None;
         self.full_audio_data.lock().unwrap().clear();
+        self.full_pcm_data.lock().unwrap().clear();
     }
 
     pub fn is_recording(&self) -> bool {
@@ -383,3 +611,47 @@ impl StreamingRecorder {
 
 unsafe impl Send for StreamingRecorder {}
 unsafe impl Sync for StreamingRecorder {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voice::audio::encode_to_wav;
+
+    fn test_pcm() -> Vec<i16> {
+        (0..CHUNK_SAMPLES * 2 + 100)
+            .map(|i| ((i as i32 * 37) % 65536 - 32768) as i16)
+            .collect()
+    }
+
+    #[test]
+    fn test_passthrough_chunks_match_float_path() {
+        let pcm = test_pcm();
+
+        let mut direct = ChunkAccumulator::new();
+        direct.push_i16(&pcm);
+        let mut float = ChunkAccumulator::new();
+        float.push_f32(&convert_i16_to_f32(&pcm));
+
+        for _ in 0..2 {
+            let chunk = direct.pop_chunk().unwrap();
+            assert_eq!(chunk.len(), CHUNK_SAMPLES);
+            assert_eq!(Some(chunk), float.pop_chunk());
+        }
+        assert!(direct.pop_chunk().is_none());
+        assert_eq!(direct.len(), 100);
+        assert_eq!(float.len(), 100);
+    }
+
+    #[test]
+    fn test_passthrough_wav_matches_float_path() {
+        let pcm: Vec<i16> = (i16::MIN..=i16::MAX).collect();
+
+        let direct = AudioData::from_i16(pcm.clone(), TARGET_SAMPLE_RATE, 1);
+        let float = AudioData::new(convert_i16_to_f32(&pcm), TARGET_SAMPLE_RATE, 1);
+
+        assert_eq!(direct.pcm_i16(), Some(pcm.as_slice()));
+        assert!(float.pcm_i16().is_none());
+        assert_eq!(direct.duration_ms, float.duration_ms);
+        assert_eq!(encode_to_wav(&direct).unwrap(), encode_to_wav(&float).unwrap());
+    }
+}
@@ -0,0 +1,291 @@
+// 录音旁路转发 (tee)
+// 录音块在送入转录的同时复制一份写入可配置的目的地 (WAV 文件、UDP、WebSocket)，
+// 旁路在独立任务中运行，写入失败或跟不上时只丢弃旁路数据并告警，不影响主转录
+
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        eprintln!("[INFO] [tee] {}", format!($($arg)*));
+    };
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        eprintln!("[WARN] [tee] {}", format!($($arg)*));
+    };
+}
+
+use futures_util::SinkExt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_util::sync::CancellationToken;
+
+use super::encoder::IncrementalWavWriter;
+use super::recorder::{f32_to_i16, to_mono, TARGET_SAMPLE_RATE};
+use super::stream_resampler::StreamResampler;
+use super::streaming::{AudioChunkData, CHUNK_CHANNEL_BUFFER};
+use crate::voice::config::AudioTeeConfig;
+
+/// 旁路缓冲的块数，写入跟不上时超出部分直接丢弃
+const TEE_BUFFER: usize = 64;
+
+/// 连接 WebSocket/UDP 目的地的超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 旁路告警回调 (仅在旁路任务中调用)
+pub type TeeWarningCallback = Box<dyn Fn(&str) + Send + 'static>;
+
+type WsStream = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// 旁路写入端
+///
+/// `send` 不阻塞，可在音频回调线程中调用；全部句柄释放后目的地随之关闭
+#[derive(Clone)]
+pub struct AudioTee {
+    tx: mpsc::Sender<Vec<i16>>,
+    dropped: Arc<AtomicBool>,
+}
+
+impl AudioTee {
+    /// 启动旁路任务 (16kHz 单声道 16 位 PCM)
+    ///
+    /// 目的地打开或写入失败后旁路停止，之后的数据被忽略
+    pub fn start(config: AudioTeeConfig, cancel_token: CancellationToken, on_warning: TeeWarningCallback) -> Self {
+        let (tx, rx) = mpsc::channel(TEE_BUFFER);
+        let dropped = Arc::new(AtomicBool::new(false));
+        tokio::spawn(run(config, rx, Arc::clone(&dropped), cancel_token, on_warning));
+        Self { tx, dropped }
+    }
+
+    pub fn send(&self, samples: &[i16]) {
+        if let Err(mpsc::error::TrySendError::Full(_)) = self.tx.try_send(samples.to_vec()) {
+            self.dropped.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// 把录音块复制到旁路后原样转发，返回供转录使用的接收端
+    pub fn tee_chunks(self, mut chunk_rx: mpsc::Receiver<AudioChunkData>) -> mpsc::Receiver<AudioChunkData> {
+        let (tx, rx) = mpsc::channel(CHUNK_CHANNEL_BUFFER);
+        tokio::spawn(async move {
+            while let Some(chunk) = chunk_rx.recv().await {
+                self.send(&chunk.samples);
+                if tx.send(chunk).await.is_err() {
+                    break;
+                }
+            }
+        });
+        rx
+    }
+}
+
+/// 设备原始采样 (设备采样率、多声道) 的旁路入口，转换为 16kHz 单声道后写入
+pub struct DeviceTee {
+    tee: AudioTee,
+    channels: u16,
+    resampler: StreamResampler,
+}
+
+impl DeviceTee {
+    pub fn new(tee: AudioTee, device_sample_rate: u32, channels: u16) -> Self {
+        Self {
+            tee,
+            channels,
+            resampler: StreamResampler::new(device_sample_rate, TARGET_SAMPLE_RATE, 1),
+        }
+    }
+
+    pub fn push(&mut self, data: &[f32]) {
+        let resampled = self.resampler.process(&to_mono(data, self.channels));
+        self.send(&resampled);
+    }
+
+    /// 写出重采样器中剩余的尾部
+    pub fn finish(mut self) {
+        let tail = self.resampler.flush();
+        self.send(&tail);
+    }
+
+    fn send(&self, samples: &[f32]) {
+        if !samples.is_empty() {
+            let pcm: Vec<i16> = samples.iter().copied().map(f32_to_i16).collect();
+            self.tee.send(&pcm);
+        }
+    }
+}
+
+async fn run(
+    config: AudioTeeConfig,
+    mut rx: mpsc::Receiver<Vec<i16>>,
+    dropped: Arc<AtomicBool>,
+    cancel_token: CancellationToken,
+    on_warning: TeeWarningCallback,
+) {
+    let mut sink = match TeeSink::open(&config).await {
+        Ok(sink) => sink,
+        Err(e) => {
+            let message = format!("音频旁路 {} 打开失败: {}", config.describe(), e);
+            log_warn!("{}", message);
+            on_warning(&message);
+            return;
+        }
+    };
+    log_info!("音频旁路已启动: {}", config.describe());
+
+    let mut reported_drop = false;
+    loop {
+        let samples = tokio::select! {
+            _ = cancel_token.cancelled() => break,
+            samples = rx.recv() => match samples {
+                Some(samples) => samples,
+                None => break,
+            },
+        };
+
+        if let Err(e) = sink.write(&samples).await {
+            let message = format!("音频旁路 {} 写入失败，已停止转发: {}", config.describe(), e);
+            log_warn!("{}", message);
+            on_warning(&message);
+            return;
+        }
+
+        if !reported_drop && dropped.load(Ordering::Relaxed) {
+            reported_drop = true;
+            let message = format!("音频旁路 {} 写入过慢，部分音频未转发", config.describe());
+            log_warn!("{}", message);
+            on_warning(&message);
+        }
+    }
+
+    sink.close().await;
+    log_info!("音频旁路已关闭: {}", config.describe());
+}
+
+/// 旁路目的地
+enum TeeSink {
+    File(IncrementalWavWriter),
+    Udp(UdpSocket),
+    WebSocket(Box<WsStream>),
+}
+
+impl TeeSink {
+    async fn open(config: &AudioTeeConfig) -> Result<Self, String> {
+        match config {
+            AudioTeeConfig::File { path } => IncrementalWavWriter::create(crate::utils::temp_dir::resolve(path), TARGET_SAMPLE_RATE, 1)
+                .map(TeeSink::File)
+                .map_err(|e| e.to_string()),
+            AudioTeeConfig::Udp { address } => {
+                let target = tokio::net::lookup_host(address.as_str())
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .next()
+                    .ok_or_else(|| format!("无法解析地址: {}", address))?;
+                let local = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+                let socket = UdpSocket::bind(local).await.map_err(|e| e.to_string())?;
+                socket.connect(target).await.map_err(|e| e.to_string())?;
+                Ok(TeeSink::Udp(socket))
+            }
+            AudioTeeConfig::WebSocket { url } => {
+                let (stream, _) = tokio::time::timeout(CONNECT_TIMEOUT, tokio_tungstenite::connect_async(url.as_str()))
+                    .await
+                    .map_err(|_| format!("连接超时 ({}s)", CONNECT_TIMEOUT.as_secs()))?
+                    .map_err(|e| e.to_string())?;
+                Ok(TeeSink::WebSocket(Box::new(stream)))
+            }
+        }
+    }
+
+    async fn write(&mut self, samples: &[i16]) -> Result<(), String> {
+        match self {
+            TeeSink::File(writer) => writer.write_i16_samples(samples).map_err(|e| e.to_string()),
+            TeeSink::Udp(socket) => socket.send(&pcm_bytes(samples)).await.map(|_| ()).map_err(|e| e.to_string()),
+            TeeSink::WebSocket(stream) => stream
+                .send(Message::Binary(pcm_bytes(samples).into()))
+                .await
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    async fn close(self) {
+        match self {
+            TeeSink::File(writer) => {
+                if let Err(e) = writer.finalize() {
+                    log_warn!("完成旁路录音文件失败: {}", e);
+                }
+            }
+            TeeSink::Udp(_) => {}
+            TeeSink::WebSocket(mut stream) => {
+                let _ = stream.close().await;
+            }
+        }
+    }
+}
+
+/// 16 位小端 PCM 字节
+fn pcm_bytes(samples: &[i16]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn collect_warnings() -> (Arc<Mutex<Vec<String>>>, TeeWarningCallback) {
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&warnings);
+        (warnings, Box::new(move |message: &str| sink.lock().unwrap().push(message.to_string())))
+    }
+
+    #[tokio::test]
+    async fn test_udp_tee_forwards_chunks_unchanged() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = receiver.local_addr().unwrap().to_string();
+        let (warnings, on_warning) = collect_warnings();
+
+        let tee = AudioTee::start(AudioTeeConfig::Udp { address }, CancellationToken::new(), on_warning);
+        let (chunk_tx, chunk_rx) = mpsc::channel(4);
+        let mut teed = tee.tee_chunks(chunk_rx);
+
+        chunk_tx.send(AudioChunkData { samples: vec![1, -2, 300], timestamp_ms: 0 }).await.unwrap();
+        let chunk = teed.recv().await.unwrap();
+        assert_eq!(chunk.samples, vec![1, -2, 300]);
+
+        let mut buf = [0u8; 64];
+        let len = tokio::time::timeout(Duration::from_secs(2), receiver.recv(&mut buf)).await.unwrap().unwrap();
+        assert_eq!(&buf[..len], &pcm_bytes(&[1, -2, 300])[..]);
+        assert!(warnings.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_sink_only_warns() {
+        // 以可执行文件作为目录，创建文件必然失败
+        let path = std::env::current_exe().unwrap().join("out.wav");
+        let config = AudioTeeConfig::File { path: path.to_string_lossy().into_owned() };
+        let (warnings, on_warning) = collect_warnings();
+
+        let tee = AudioTee::start(config, CancellationToken::new(), on_warning);
+        let (chunk_tx, chunk_rx) = mpsc::channel(4);
+        let mut teed = tee.tee_chunks(chunk_rx);
+
+        // 旁路失败后录音块仍完整送达转录
+        for index in 0..10 {
+            chunk_tx.send(AudioChunkData { samples: vec![index; 320], timestamp_ms: 0 }).await.unwrap();
+            assert_eq!(teed.recv().await.unwrap().samples, vec![index; 320]);
+        }
+        drop(chunk_tx);
+        assert!(teed.recv().await.is_none());
+
+        for _ in 0..100 {
+            if !warnings.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let warnings = warnings.lock().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("打开失败"), "{}", warnings[0]);
+    }
+}
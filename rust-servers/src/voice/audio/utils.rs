@@ -1,12 +1,43 @@
 // 音频工具函数模块
-// 提供 VAD (静音检测)、RMS 计算、波形生成等功能
+// 提供 VAD (静音检测)、RMS 计算、波形生成、频谱分析、基频估计、静音压缩、直流偏置去除、声道选择等功能
+
+use super::AudioData;
+use crate::voice::config::{DownmixStrategy, WaveScale};
 
 /// 静音检测阈值 (RMS 值低于此阈值视为静音)
 pub const VAD_THRESHOLD: f32 = 0.01;
 
+/// 削波判定阈值 (绝对值不低于此值的样本视为削波)
+pub const CLIPPING_LEVEL: f32 = 0.99;
+
 /// RMS 放大系数 (使音量显示更敏感)
 pub const RMS_AMPLIFICATION: f32 = 1.5;
 
+/// 静音压缩的分帧时长 (毫秒)
+const SILENCE_FRAME_MS: u64 = 20;
+
+/// 静音帧的峰值上限 (RMS 低但含瞬态的帧可能是辅音，不视为纯静音)
+const SILENCE_PEAK: f32 = 0.05;
+
+/// 直流偏置低于此值时视为无偏置，不做处理
+const DC_OFFSET_EPSILON: f32 = 1e-4;
+
+/// 评估声道质量的分帧时长 (毫秒)
+const CHANNEL_FRAME_MS: u64 = 20;
+
+/// 声道评分的电平下限 (避免除零，也让全零声道得分为零)
+const CHANNEL_LEVEL_FLOOR: f32 = 1e-4;
+
+/// 基频估计的搜索范围 (Hz，覆盖成人与儿童语音)
+const MIN_PITCH_HZ: f32 = 60.0;
+const MAX_PITCH_HZ: f32 = 1000.0;
+
+/// YIN 累积均值归一化差分的阈值，最小值高于此值时视为清音或噪声
+const YIN_THRESHOLD: f32 = 0.15;
+
+/// 对数刻度波形的下限 (dB)，不高于此值的柱高为 0
+const WAVE_MIN_DB: f32 = -60.0;
+
 /// 平滑过渡参数
 pub const SMOOTH_RISE_NEW: f32 = 0.7;
 pub const SMOOTH_RISE_OLD: f32 = 0.3;
@@ -25,7 +56,7 @@ pub fn calculate_rms(samples: &[f32]) -> f32 {
     let normalized = (amplified * 3.0).min(1.0);
 
     if normalized > 0.0 {
-        ((normalized.ln() + 4.0) / 4.0).max(0.0).min(1.0)
+        ((normalized.ln() + 4.0) / 4.0).clamp(0.0, 1.0)
     } else {
         0.0
     }
@@ -50,15 +81,24 @@ pub fn smooth_level(current: f32, target: f32) -> f32 {
 }
 
 /// 生成波形数据 (用于 UI 显示)
+#[allow(dead_code)]
 pub fn generate_waveform(samples: &[f32], num_bars: usize) -> Vec<f32> {
+    generate_waveform_with(samples, num_bars, WaveScale::Linear)
+}
+
+/// 按指定刻度生成波形数据
+pub fn generate_waveform_with(samples: &[f32], num_bars: usize, scale: WaveScale) -> Vec<f32> {
     if samples.is_empty() || num_bars == 0 {
         return vec![0.0; num_bars];
     }
 
+    let bar_level = |chunk: &[f32]| match scale {
+        WaveScale::Linear => calculate_rms(chunk),
+        WaveScale::Log => log_level(calculate_raw_rms(chunk)),
+    };
     let chunk_size = samples.len() / num_bars;
     if chunk_size == 0 {
-        let rms = calculate_rms(samples);
-        return vec![rms; num_bars];
+        return vec![bar_level(samples); num_bars];
     }
 
     let mut waveform = Vec::with_capacity(num_bars);
@@ -69,13 +109,150 @@ pub fn generate_waveform(samples: &[f32], num_bars: usize) -> Vec<f32> {
         } else {
             (i + 1) * chunk_size
         };
-        let chunk = &samples[start..end];
-        let rms = calculate_rms(chunk);
-        waveform.push(rms);
+        waveform.push(bar_level(&samples[start..end]));
     }
     waveform
 }
 
+/// RMS 换算为 dB 并把 [WAVE_MIN_DB, 0] 归一化到 [0, 1]，零输入与 NaN 得 0
+fn log_level(rms: f32) -> f32 {
+    if rms.is_nan() || rms <= 0.0 {
+        return 0.0;
+    }
+    let db = 20.0 * rms.log10();
+    ((db - WAVE_MIN_DB) / -WAVE_MIN_DB).clamp(0.0, 1.0)
+}
+
+/// 默认峰值保持的衰减速率 (每秒下降的电平)
+pub const DEFAULT_PEAK_DECAY_PER_SEC: f32 = 1.5;
+
+/// 波形每柱的峰值保持 (VU 表)
+///
+/// 新值高于保持值时立即更新，否则按 `decay_per_sec` 随时间线性下降，不低于当前值
+#[derive(Debug, Clone)]
+pub struct PeakHold {
+    decay_per_sec: f32,
+    peaks: Vec<f32>,
+    last_ms: Option<u64>,
+}
+
+impl PeakHold {
+    pub fn new(decay_per_sec: f32) -> Self {
+        Self {
+            decay_per_sec: decay_per_sec.max(0.0),
+            peaks: Vec::new(),
+            last_ms: None,
+        }
+    }
+
+    /// 以 `now_ms` 时刻的波形更新峰值，返回各柱的保持值
+    pub fn update(&mut self, levels: &[f32], now_ms: u64) -> &[f32] {
+        let elapsed_secs = self.last_ms
+            .map_or(0.0, |last| now_ms.saturating_sub(last) as f32 / 1000.0);
+        self.last_ms = Some(now_ms);
+        // 柱数变化时重新开始
+        if self.peaks.len() != levels.len() {
+            self.peaks = levels.to_vec();
+            return &self.peaks;
+        }
+
+        let decay = self.decay_per_sec * elapsed_secs;
+        for (peak, &level) in self.peaks.iter_mut().zip(levels) {
+            *peak = (*peak - decay).max(level);
+        }
+        &self.peaks
+    }
+}
+
+/// 计算一帧的幅度谱 (用于 UI 频谱显示)
+///
+/// 加 Hann 窗后做 FFT，样本数不是 2 的幂时补零；0 到奈奎斯特频率均分为 `bins` 个频段，
+/// 每段取平均幅度并按最大值归一化到 0-1
+pub fn compute_spectrum(samples: &[f32], bins: usize) -> Vec<f32> {
+    if samples.is_empty() || bins == 0 {
+        return vec![0.0; bins];
+    }
+
+    let len = samples.len().next_power_of_two();
+    let window_len = samples.len().max(2) as f32 - 1.0;
+    let mut buffer: Vec<rustfft::num_complex::Complex<f32>> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, &s)| {
+            let window = 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / window_len).cos();
+            rustfft::num_complex::Complex::new(s * window, 0.0)
+        })
+        .collect();
+    buffer.resize(len, rustfft::num_complex::Complex::new(0.0, 0.0));
+    rustfft::FftPlanner::new().plan_fft_forward(len).process(&mut buffer);
+
+    // 实信号的频谱对称，只取前一半
+    let magnitudes: Vec<f32> = buffer[..(len / 2).max(1)].iter().map(|c| c.norm()).collect();
+    let mut spectrum: Vec<f32> = (0..bins)
+        .map(|band| {
+            let start = band * magnitudes.len() / bins;
+            let end = ((band + 1) * magnitudes.len() / bins).max(start + 1).min(magnitudes.len());
+            let band = &magnitudes[start.min(end - 1)..end];
+            band.iter().sum::<f32>() / band.len() as f32
+        })
+        .collect();
+
+    let max = spectrum.iter().copied().fold(0.0f32, f32::max);
+    if max > 0.0 {
+        for value in spectrum.iter_mut() {
+            *value /= max;
+        }
+    }
+    spectrum
+}
+
+/// 估计一帧单声道采样的基频 (Hz，YIN 算法)
+///
+/// 静音、清音 (无明显周期) 或帧过短时返回 None。可搜索的最低频率受帧长限制
+/// (帧需覆盖两个周期)，帧短于最高频率的两个周期时直接返回 None
+pub fn estimate_pitch(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    if sample_rate == 0 || is_silence(samples) {
+        return None;
+    }
+    let min_lag = ((sample_rate as f32 / MAX_PITCH_HZ) as usize).max(2);
+    let max_lag = ((sample_rate as f32 / MIN_PITCH_HZ).ceil() as usize).min(samples.len() / 2);
+    if max_lag <= min_lag {
+        return None;
+    }
+    // 窗口取一个最大周期，控制在音频回调中的计算量
+    let frame = &samples[samples.len() - 2 * max_lag..];
+    let window = max_lag;
+
+    // 差分函数 d(τ) 与累积均值归一化 d'(τ)
+    let mut normalized = vec![1.0f32; max_lag + 1];
+    let mut running = 0.0f32;
+    for tau in 1..=max_lag {
+        let diff: f32 = (0..window).map(|j| (frame[j] - frame[j + tau]).powi(2)).sum();
+        running += diff;
+        normalized[tau] = if running > 0.0 { diff * tau as f32 / running } else { 1.0 };
+    }
+
+    // 取第一个低于阈值的谷底
+    let mut tau = (min_lag..=max_lag).find(|&tau| normalized[tau] < YIN_THRESHOLD)?;
+    while tau < max_lag && normalized[tau + 1] < normalized[tau] {
+        tau += 1;
+    }
+
+    // 抛物线插值细化周期
+    let period = if tau < max_lag {
+        let (prev, curr, next) = (normalized[tau - 1], normalized[tau], normalized[tau + 1]);
+        let denominator = prev - 2.0 * curr + next;
+        if denominator.abs() > f32::EPSILON {
+            tau as f32 + 0.5 * (prev - next) / denominator
+        } else {
+            tau as f32
+        }
+    } else {
+        tau as f32
+    };
+    Some(sample_rate as f32 / period)
+}
+
 /// 检测是否为静音
 pub fn is_silence(samples: &[f32]) -> bool {
     calculate_raw_rms(samples) < VAD_THRESHOLD
@@ -98,6 +275,33 @@ pub fn calculate_peak(samples: &[f32]) -> f32 {
         .unwrap_or(0.0)
 }
 
+/// 计算削波样本占比 (接近 ±1.0 的样本比例)
+pub fn clipping_ratio(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let clipped = samples.iter().filter(|s| s.abs() >= CLIPPING_LEVEL).count();
+    clipped as f32 / samples.len() as f32
+}
+
+/// 一段采样的原始电平统计 (用于录音中的电平告警)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LevelStats {
+    /// 原始 RMS
+    pub rms: f32,
+    /// 削波样本占比
+    pub clipping_ratio: f32,
+}
+
+impl LevelStats {
+    pub fn measure(samples: &[f32]) -> Self {
+        Self {
+            rms: calculate_raw_rms(samples),
+            clipping_ratio: clipping_ratio(samples),
+        }
+    }
+}
+
 /// 归一化音频数据
 pub fn normalize(samples: &mut [f32]) {
     let peak = calculate_peak(samples);
@@ -108,3 +312,328 @@ pub fn normalize(samples: &mut [f32]) {
         }
     }
 }
+
+/// 去除直流偏置：各声道分别减去整段的均值
+///
+/// 偏置可忽略时原样返回 (保留 i16 透传数据)
+pub fn remove_dc_offset(audio: &AudioData) -> AudioData {
+    let channels = audio.channels.max(1) as usize;
+    let frames = audio.samples.len() / channels;
+    if frames == 0 {
+        return audio.clone();
+    }
+
+    let mut offsets = vec![0.0f64; channels];
+    for frame in audio.samples.chunks_exact(channels) {
+        for (offset, &sample) in offsets.iter_mut().zip(frame) {
+            *offset += sample as f64;
+        }
+    }
+    let offsets: Vec<f32> = offsets.iter().map(|sum| (sum / frames as f64) as f32).collect();
+    if offsets.iter().all(|offset| offset.abs() < DC_OFFSET_EPSILON) {
+        return audio.clone();
+    }
+
+    let samples = audio
+        .samples
+        .iter()
+        .enumerate()
+        .map(|(index, &sample)| (sample - offsets[index % channels]).clamp(-1.0, 1.0))
+        .collect();
+    AudioData::new(samples, audio.sample_rate, audio.channels)
+}
+
+/// 按策略把多声道音频转为单声道，单声道输入直接返回
+pub fn downmix(audio: &AudioData, strategy: DownmixStrategy) -> AudioData {
+    if audio.channels <= 1 {
+        return audio.clone();
+    }
+    match strategy {
+        DownmixStrategy::Mix => AudioData::new(
+            super::recorder::to_mono(&audio.samples, audio.channels),
+            audio.sample_rate,
+            1,
+        ),
+        DownmixStrategy::BestChannel => choose_best_channel(audio),
+        DownmixStrategy::Left => extract_channel(audio, 0),
+        DownmixStrategy::Right => extract_channel(audio, 1),
+    }
+}
+
+/// 选出信噪比最高的声道
+///
+/// 以分帧 RMS 的高分位作为语音电平、低分位作为底噪，损坏 (全零或只有噪声) 的声道得分接近零；
+/// 单声道输入直接返回
+pub fn choose_best_channel(audio: &AudioData) -> AudioData {
+    if audio.channels <= 1 {
+        return audio.clone();
+    }
+    let best = (0..audio.channels as usize)
+        .map(|channel| (channel, channel_score(&channel_samples(audio, channel), audio.sample_rate)))
+        .fold((0, f32::MIN), |best, current| if current.1 > best.1 { current } else { best })
+        .0;
+    extract_channel(audio, best)
+}
+
+/// 取出指定声道 (超出范围时取最后一个声道)
+fn extract_channel(audio: &AudioData, channel: usize) -> AudioData {
+    let channel = channel.min(audio.channels.max(1) as usize - 1);
+    AudioData::new(channel_samples(audio, channel), audio.sample_rate, 1)
+}
+
+fn channel_samples(audio: &AudioData, channel: usize) -> Vec<f32> {
+    audio.samples
+        .iter()
+        .skip(channel)
+        .step_by(audio.channels.max(1) as usize)
+        .copied()
+        .collect()
+}
+
+/// 声道质量评分：语音电平 (90 分位帧 RMS) 与底噪 (10 分位帧 RMS) 之比
+fn channel_score(samples: &[f32], sample_rate: u32) -> f32 {
+    let frame_len = ((sample_rate as u64 * CHANNEL_FRAME_MS / 1000) as usize).max(1);
+    let mut frames: Vec<f32> = samples.chunks(frame_len).map(calculate_raw_rms).collect();
+    if frames.is_empty() {
+        return 0.0;
+    }
+    frames.sort_by(|a, b| a.total_cmp(b));
+    let percentile = |p: usize| frames[(frames.len() - 1) * p / 100];
+    let signal = percentile(90);
+    if signal < CHANNEL_LEVEL_FLOOR {
+        return 0.0;
+    }
+    signal / (percentile(10) + CHANNEL_LEVEL_FLOOR)
+}
+
+/// 压缩长停顿：超过 `max_silence_ms` 的连续静音段缩短到 `max_silence_ms`
+///
+/// 只压缩纯静音帧，保留静音段首尾各一半贴近语音的部分，不会切到词边界
+pub fn compress_silence(audio: &AudioData, max_silence_ms: u64) -> AudioData {
+    let channels = audio.channels.max(1) as usize;
+    let frame_len = (audio.sample_rate as u64 * SILENCE_FRAME_MS / 1000).max(1) as usize * channels;
+    let max_samples = (audio.sample_rate as u64 * max_silence_ms / 1000) as usize * channels;
+
+    let mut output = Vec::with_capacity(audio.samples.len());
+    let mut silence_start: Option<usize> = None;
+    let mut changed = false;
+
+    let mut flush_silence = |output: &mut Vec<f32>, start: usize, end: usize| {
+        let silence = &audio.samples[start..end];
+        if silence.len() <= max_samples {
+            output.extend_from_slice(silence);
+            return;
+        }
+        // 按整帧 (多声道对齐) 保留首尾
+        let head = max_samples / 2 / channels * channels;
+        let tail = max_samples - head;
+        output.extend_from_slice(&silence[..head]);
+        output.extend_from_slice(&silence[silence.len() - tail..]);
+        changed = true;
+    };
+
+    for (index, frame) in audio.samples.chunks(frame_len).enumerate() {
+        let offset = index * frame_len;
+        let pure_silence = is_silence(frame) && calculate_peak(frame) < SILENCE_PEAK;
+        if pure_silence {
+            silence_start.get_or_insert(offset);
+            continue;
+        }
+        if let Some(start) = silence_start.take() {
+            flush_silence(&mut output, start, offset);
+        }
+        output.extend_from_slice(frame);
+    }
+    if let Some(start) = silence_start {
+        flush_silence(&mut output, start, audio.samples.len());
+    }
+
+    if !changed {
+        return audio.clone();
+    }
+    AudioData::new(output, audio.sample_rate, audio.channels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_waveform_log_scale() {
+        // 零输入、空输入与 NaN 都是 0
+        assert_eq!(generate_waveform_with(&[0.0; 900], 9, WaveScale::Log), vec![0.0; 9]);
+        assert_eq!(generate_waveform_with(&[], 3, WaveScale::Log), vec![0.0; 3]);
+        assert_eq!(generate_waveform_with(&[f32::NAN; 4], 2, WaveScale::Log), vec![0.0; 2]);
+
+        // 满幅为 1，-20dB 为 2/3，-60dB 以下为 0
+        let bars = |amplitude: f32| generate_waveform_with(&vec![amplitude; 90], 9, WaveScale::Log)[0];
+        assert!((bars(1.0) - 1.0).abs() < 1e-4);
+        assert!((bars(0.1) - 2.0 / 3.0).abs() < 1e-4);
+        assert_eq!(bars(0.0005), 0.0);
+
+        // 小音量：对数刻度的柱高明显高于线性刻度
+        let quiet = vec![0.003; 90];
+        assert!(generate_waveform_with(&quiet, 9, WaveScale::Log)[0] > 0.15);
+        assert!(generate_waveform(&quiet, 9)[0] < 0.05);
+        assert_eq!(generate_waveform(&quiet, 9), generate_waveform_with(&quiet, 9, WaveScale::Linear));
+    }
+
+    fn tone(ms: usize, rate: usize) -> Vec<f32> {
+        (0..ms * rate / 1000)
+            .map(|i| (2.0 * std::f32::consts::PI * 300.0 * i as f32 / rate as f32).sin() * 0.3)
+            .collect()
+    }
+
+    #[test]
+    fn test_compute_spectrum_peaks_at_tone() {
+        // 300Hz 正弦，1000 个样本 (非 2 的幂，补零到 1024)
+        let rate = 16000;
+        let samples = &tone(1000, rate)[..1000];
+        let spectrum = compute_spectrum(samples, 16);
+        assert_eq!(spectrum.len(), 16);
+        // 每段 500Hz，峰值落在第一段
+        assert_eq!(spectrum[0], 1.0);
+        assert!(spectrum[4..].iter().all(|&v| v < 0.05), "{:?}", spectrum);
+
+        assert_eq!(compute_spectrum(&[0.0; 256], 8), vec![0.0; 8]);
+        assert_eq!(compute_spectrum(&[], 4), vec![0.0; 4]);
+        // 频段多于频点时仍返回 bins 个值
+        assert_eq!(compute_spectrum(&[0.5, -0.5, 0.5], 8).len(), 8);
+    }
+
+    #[test]
+    fn test_estimate_pitch() {
+        // 含谐波的 120Hz 信号 (近似浊音)，48kHz 下 40ms 一帧
+        let voiced: Vec<f32> = (0..1920)
+            .map(|i| {
+                let phase = 2.0 * std::f32::consts::PI * 120.0 * i as f32 / 48000.0;
+                0.3 * phase.sin() + 0.2 * (2.0 * phase).sin() + 0.1 * (3.0 * phase).sin()
+            })
+            .collect();
+        let pitch = estimate_pitch(&voiced, 48000).unwrap();
+        assert!((pitch - 120.0).abs() < 1.0, "{}", pitch);
+
+        let pitch = estimate_pitch(&tone(40, 16000), 16000).unwrap();
+        assert!((pitch - 300.0).abs() < 2.0, "{}", pitch);
+
+        // 噪声 (清音) 与静音无基频
+        let mut seed = 12345u32;
+        let noise: Vec<f32> = (0..1920)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (seed >> 16) as f32 / 32768.0 - 1.0
+            })
+            .collect();
+        assert_eq!(estimate_pitch(&noise, 48000), None);
+        assert_eq!(estimate_pitch(&[0.0; 1920], 48000), None);
+
+        // 帧过短或采样率无效时返回 None 而非出错
+        assert_eq!(estimate_pitch(&voiced[..60], 48000), None);
+        assert_eq!(estimate_pitch(&[], 16000), None);
+        assert_eq!(estimate_pitch(&voiced, 0), None);
+    }
+
+    #[test]
+    fn test_peak_hold_decays_and_jumps() {
+        let mut hold = PeakHold::new(1.0);
+        assert_eq!(hold.update(&[0.2, 0.8], 0), &[0.2, 0.8]);
+        // 更高的新值立即生效，否则按时间衰减
+        let peaks = hold.update(&[0.5, 0.1], 100).to_vec();
+        assert_eq!(peaks[0], 0.5);
+        assert!((peaks[1] - 0.7).abs() < 1e-6);
+        // 不低于当前值
+        assert_eq!(hold.update(&[0.0, 0.3], 1100), &[0.0, 0.3]);
+        // 柱数变化时重新开始
+        assert_eq!(hold.update(&[0.4; 3], 1200), &[0.4; 3]);
+    }
+
+    #[test]
+    fn test_choose_best_channel() {
+        // 左声道为带停顿的语音，右声道只有持续底噪
+        let frames = 16000 / 50;
+        let mut samples = Vec::new();
+        for i in 0..16000 {
+            let speech = if (i / frames) % 2 == 0 { (i as f32 * 0.3).sin() * 0.5 } else { 0.0 };
+            let noise = if i % 2 == 0 { 0.2 } else { -0.2 };
+            samples.push(speech);
+            samples.push(noise);
+        }
+        let audio = AudioData::new(samples, 16000, 2);
+
+        let best = choose_best_channel(&audio);
+        assert_eq!(best.channels, 1);
+        assert_eq!(best.samples, channel_samples(&audio, 0));
+        assert_eq!(downmix(&audio, DownmixStrategy::Right).samples, channel_samples(&audio, 1));
+        let mixed = downmix(&audio, DownmixStrategy::Mix);
+        assert!((mixed.samples[1] - (audio.samples[2] + audio.samples[3]) / 2.0).abs() < 1e-6);
+
+        let mono = AudioData::new(vec![0.1; 160], 16000, 1);
+        assert_eq!(choose_best_channel(&mono).samples, mono.samples);
+    }
+
+    #[test]
+    fn test_remove_dc_offset() {
+        let rate = 16000;
+        let biased: Vec<f32> = tone(500, rate).iter().map(|s| s + 0.2).collect();
+        let audio = AudioData::new(biased, rate as u32, 1);
+        let mean = |samples: &[f32]| samples.iter().sum::<f32>() / samples.len() as f32;
+        assert!((mean(&audio.samples) - 0.2).abs() < 1e-3);
+
+        let centered = remove_dc_offset(&audio);
+        assert!(mean(&centered.samples).abs() < 1e-4);
+        assert!((calculate_peak(&centered.samples) - 0.3).abs() < 1e-2);
+
+        // 立体声按声道分别去除
+        let stereo: Vec<f32> = (0..1000).flat_map(|i| [0.1 + (i % 2) as f32 * 0.01, -0.3]).collect();
+        let centered = remove_dc_offset(&AudioData::new(stereo, rate as u32, 2));
+        let left: Vec<f32> = centered.samples.iter().step_by(2).copied().collect();
+        let right: Vec<f32> = centered.samples.iter().skip(1).step_by(2).copied().collect();
+        assert!(mean(&left).abs() < 1e-4 && mean(&right).abs() < 1e-4);
+
+        // 无偏置时保留 i16 透传数据
+        let pcm: Vec<i16> = (0..1600).map(|i| if i % 2 == 0 { 1000 } else { -1000 }).collect();
+        let unbiased = AudioData::from_i16(pcm.clone(), rate as u32, 1);
+        assert_eq!(remove_dc_offset(&unbiased).pcm_i16(), Some(pcm.as_slice()));
+    }
+
+    #[test]
+    fn test_compress_long_pause() {
+        let rate = 16000;
+        let speech = tone(500, rate);
+        let mut samples = speech.clone();
+        samples.extend(vec![0.0; 3 * rate]);
+        samples.extend(&speech);
+        // 短停顿保持不变
+        samples.extend(vec![0.0; rate * 300 / 1000]);
+        samples.extend(&speech);
+        let audio = AudioData::new(samples, rate as u32, 1);
+
+        let compressed = compress_silence(&audio, 500);
+        assert_eq!(compressed.duration_ms, 500 + 500 + 500 + 300 + 500);
+        // 语音部分原样保留
+        assert_eq!(&compressed.samples[..speech.len()], speech.as_slice());
+        let second = speech.len() + rate / 2;
+        assert_eq!(&compressed.samples[second..second + speech.len()], speech.as_slice());
+
+        // 无需压缩时原样返回
+        assert_eq!(compress_silence(&audio, 5000).samples.len(), audio.samples.len());
+    }
+
+    #[test]
+    fn test_compress_keeps_faint_transients() {
+        let rate = 16000;
+        // 低 RMS 但含瞬态的片段不属于纯静音
+        let mut faint = vec![0.0; 2 * rate];
+        for i in (0..faint.len()).step_by(rate / 50) {
+            faint[i] = 0.15;
+        }
+        let audio = AudioData::new(faint, rate as u32, 1);
+        assert_eq!(compress_silence(&audio, 200).duration_ms, 2000);
+
+        // 立体声按整帧压缩
+        let stereo = AudioData::new(vec![0.0; 2 * 2 * rate], rate as u32, 2);
+        let compressed = compress_silence(&stereo, 250);
+        assert_eq!(compressed.duration_ms, 250);
+        assert_eq!(compressed.samples.len() % 2, 0);
+    }
+}
@@ -6,6 +6,8 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
+use super::audio::AudioData;
+
 /// 日志宏
 macro_rules! log_debug {
     ($($arg:tt)*) => {
@@ -30,6 +32,20 @@ pub enum BeepType {
     RecordingStop,
 }
 
+/// 由前端播放的提示音 (随 play_sound 消息发送)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SoundKind {
+    /// 录音开始
+    Start,
+    /// 录音停止
+    Stop,
+    /// 录音取消
+    Cancel,
+    /// 转录失败
+    Error,
+}
+
 /// 音频反馈播放器
 /// 
 /// 使用 rodio 生成简单的正弦波提示音
@@ -111,17 +127,15 @@ impl BeepPlayer {
     }
 }
 
-/// 阻塞式播放提示音
-fn play_beep_blocking(beep_type: BeepType, volume: f32) -> Result<(), BeepError> {
-    // 获取音频输出流 (rodio 0.21 新 API)
-    let stream = OutputStreamBuilder::open_default_stream()
-        .map_err(|e| BeepError::OutputStreamError(e.to_string()))?;
-    
-    let mixer = stream.mixer();
-    let sink = Sink::connect_new(&mixer);
+/// 提示音的波形 (单声道，与播放内容一致，用作回声消除的参考信号)
+pub fn render(beep_type: BeepType, volume: f32) -> AudioData {
+    let tone = beep_source(beep_type, volume);
+    let sample_rate = tone.sample_rate;
+    AudioData::new(tone.collect(), sample_rate, 1)
+}
 
-    // 根据提示音类型生成不同的音调
-    let source = match beep_type {
+fn beep_source(beep_type: BeepType, volume: f32) -> SweepTone {
+    match beep_type {
         BeepType::RecordingStart => {
             // 上升音调: 440Hz -> 880Hz (A4 -> A5)
             create_sweep_tone(440.0, 880.0, 150, volume)
@@ -130,7 +144,20 @@ fn play_beep_blocking(beep_type: BeepType, volume: f32) -> Result<(), BeepError>
             // 下降音调: 880Hz -> 440Hz (A5 -> A4)
             create_sweep_tone(880.0, 440.0, 150, volume)
         }
-    };
+    }
+}
+
+/// 阻塞式播放提示音
+fn play_beep_blocking(beep_type: BeepType, volume: f32) -> Result<(), BeepError> {
+    // 获取音频输出流 (rodio 0.21 新 API)
+    let stream = OutputStreamBuilder::open_default_stream()
+        .map_err(|e| BeepError::OutputStreamError(e.to_string()))?;
+    
+    let mixer = stream.mixer();
+    let sink = Sink::connect_new(mixer);
+
+    // 根据提示音类型生成不同的音调
+    let source = beep_source(beep_type, volume);
 
     sink.append(source);
     sink.sleep_until_end();
@@ -310,6 +337,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_render_matches_playback() {
+        let audio = render(BeepType::RecordingStart, 0.5);
+        assert_eq!((audio.sample_rate, audio.channels, audio.duration_ms), (44100, 1, 150));
+        let peak = audio.samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak > 0.4 && peak <= 0.5, "{}", peak);
+    }
+
     #[test]
     fn test_envelope_calculation() {
         // 开始时应该是 0
@@ -322,6 +357,12 @@ mod tests {
         assert!((calculate_envelope(1.0) - 0.0).abs() < 0.001);
     }
 
+    #[test]
+    fn test_sound_kind_serialization() {
+        assert_eq!(serde_json::to_value(SoundKind::Start).unwrap(), "start");
+        assert_eq!(serde_json::to_value(SoundKind::Cancel).unwrap(), "cancel");
+    }
+
     #[test]
     fn test_beep_type_equality() {
         assert_eq!(BeepType::RecordingStart, BeepType::RecordingStart);
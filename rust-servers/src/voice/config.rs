@@ -2,6 +2,8 @@
 // 定义 ASR 供应商配置和相关数据结构
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 
 /// ASR 供应商类型
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -14,6 +16,15 @@ pub enum ASRProvider {
     /// 硅基流动 SenseVoice
     #[serde(rename = "sensevoice")]
     SenseVoice,
+    /// 火山引擎 (豆包 v2 流式识别，支持 cluster)
+    Volcengine,
+    /// 通用 HTTP 接口 (按模板描述请求，接入自建服务)
+    Generic,
+    /// Google Cloud Speech-to-Text (服务账号鉴权)
+    Google,
+    /// OpenAI Realtime API 转录
+    #[serde(rename = "openai")]
+    OpenAI,
 }
 
 impl std::fmt::Display for ASRProvider {
@@ -22,6 +33,10 @@ impl std::fmt::Display for ASRProvider {
             ASRProvider::Qwen => write!(f, "qwen"),
             ASRProvider::Doubao => write!(f, "doubao"),
             ASRProvider::SenseVoice => write!(f, "sensevoice"),
+            ASRProvider::Volcengine => write!(f, "volcengine"),
+            ASRProvider::Generic => write!(f, "generic"),
+            ASRProvider::Google => write!(f, "google"),
+            ASRProvider::OpenAI => write!(f, "openai"),
         }
     }
 }
@@ -46,7 +61,7 @@ impl std::fmt::Display for ASRMode {
 }
 
 /// ASR 供应商配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct ASRProviderConfig {
     /// 供应商类型
     pub provider: ASRProvider,
@@ -66,10 +81,189 @@ pub struct ASRProviderConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub access_token: Option<String>,
     
+    // 火山引擎特有配置 (app_id/access_token 与豆包共用)
+    /// 集群 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cluster: Option<String>,
+    
     // SenseVoice 特有配置
     /// 硅基流动 API Key
     #[serde(skip_serializing_if = "Option::is_none")]
     pub siliconflow_api_key: Option<String>,
+    
+    // 通用 HTTP 引擎配置
+    /// 请求模板
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generic_http: Option<GenericHttpConfig>,
+    
+    // Google 特有配置
+    /// 服务账号与识别参数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub google: Option<GoogleConfig>,
+    
+    // OpenAI 特有配置
+    /// API Key 与转录参数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openai: Option<OpenAIConfig>,
+    
+    /// 候选识别语言，多于一个时并行转录并按置信度择优 (Qwen HTTP、Google)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidate_languages: Vec<String>,
+    /// 实时 partial 附带的候选数 (仅支持 n-best 的引擎，目前为火山引擎)，0 或 1 时只返回单一结果
+    #[serde(default)]
+    pub partial_alternatives: usize,
+    /// 覆盖引擎默认的重试与超时 (未设置的字段沿用引擎默认值)
+    #[serde(default, skip_serializing_if = "RetryOverride::is_empty")]
+    pub retry: RetryOverride,
+    /// 该引擎的在途请求数上限 (进程内所有连接共享)，为空时本地引擎取 CPU 核数、云引擎取 8
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<usize>,
+    /// 该引擎的请求速率上限 (进程内所有连接共享)，为空时不限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitConfig>,
+}
+
+/// 超出速率上限时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitAction {
+    /// 排队等待令牌
+    #[default]
+    Wait,
+    /// 立即失败 (有备用引擎时随即回退)
+    Fail,
+}
+
+/// 客户端侧令牌桶限速
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// 每秒补充的令牌数 (即长期平均 QPS 上限)
+    pub requests_per_sec: f64,
+    /// 桶容量 (空闲后允许的突发请求数)
+    pub burst: u32,
+    pub on_limit: RateLimitAction,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_sec: 5.0,
+            burst: 1,
+            on_limit: RateLimitAction::Wait,
+        }
+    }
+}
+
+/// 用户显式指定的重试参数，覆盖引擎默认 RetryConfig 的对应字段
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_delay_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_delay_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jitter: Option<bool>,
+}
+
+impl RetryOverride {
+    /// 是否未覆盖任何字段
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// 通用 HTTP ASR 请求模板
+///
+/// URL、header 与表单字段中可使用 `{{api_key}}`、`{{sample_rate}}` 变量
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenericHttpConfig {
+    /// 接口地址
+    pub url: String,
+    /// 请求方法 (POST/PUT/PATCH)
+    #[serde(default = "default_http_method")]
+    pub method: String,
+    /// 请求头模板
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// API Key (供模板引用)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// multipart 表单中音频文件的字段名
+    #[serde(default = "default_audio_field")]
+    pub audio_field: String,
+    /// 额外的表单字段模板 (如 model)
+    #[serde(default)]
+    pub form_fields: HashMap<String, String>,
+    /// 响应中识别文本的 JSON 路径 (如 `$.result.text`)
+    pub text_path: String,
+    /// 响应中错误信息的 JSON 路径 (存在且非空时视为失败)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_path: Option<String>,
+    /// 请求体压缩方式 (服务端需支持 Content-Encoding)
+    #[serde(default)]
+    pub compression: RequestCompression,
+}
+
+/// HTTP 请求体压缩方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestCompression {
+    /// 不压缩
+    #[default]
+    None,
+    /// gzip 压缩并设置 `Content-Encoding: gzip`
+    Gzip,
+}
+
+/// Google Cloud Speech-to-Text 配置
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoogleConfig {
+    /// 服务账号凭据：JSON 密钥文件路径或 JSON 内容
+    pub credentials: String,
+    /// 识别语言 (BCP-47，如 `cmn-Hans-CN`、`en-US`)
+    #[serde(default = "default_google_language_code")]
+    pub language_code: String,
+    /// 识别模型 (如 `latest_long`)，为空时使用服务端默认模型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// OpenAI Realtime 转录配置
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenAIConfig {
+    /// API Key (Bearer 鉴权)
+    pub api_key: String,
+    /// 转录模型
+    #[serde(default = "default_openai_model")]
+    pub model: String,
+    /// 识别语言 (ISO-639-1，如 `zh`)，为空时由模型自动判断
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// 自定义 Realtime 接口地址 (兼容代理)，为空时使用官方地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+}
+
+fn default_openai_model() -> String {
+    "gpt-4o-transcribe".to_string()
+}
+
+fn default_google_language_code() -> String {
+    "cmn-Hans-CN".to_string()
+}
+
+fn default_http_method() -> String {
+    "POST".to_string()
+}
+
+fn default_audio_field() -> String {
+    "file".to_string()
 }
 
 impl ASRProviderConfig {
@@ -81,7 +275,16 @@ impl ASRProviderConfig {
             dashscope_api_key: Some(api_key),
             app_id: None,
             access_token: None,
+            cluster: None,
             siliconflow_api_key: None,
+            generic_http: None,
+            google: None,
+            openai: None,
+            candidate_languages: Vec::new(),
+            partial_alternatives: 0,
+            retry: RetryOverride::default(),
+            max_concurrency: None,
+            rate_limit: None,
         }
     }
     
@@ -93,7 +296,37 @@ impl ASRProviderConfig {
             dashscope_api_key: None,
             app_id: Some(app_id),
             access_token: Some(access_token),
+            cluster: None,
+            siliconflow_api_key: None,
+            generic_http: None,
+            google: None,
+            openai: None,
+            candidate_languages: Vec::new(),
+            partial_alternatives: 0,
+            retry: RetryOverride::default(),
+            max_concurrency: None,
+            rate_limit: None,
+        }
+    }
+    
+    /// 创建火山引擎配置
+    pub fn volcengine(mode: ASRMode, app_id: String, access_token: String, cluster: String) -> Self {
+        Self {
+            provider: ASRProvider::Volcengine,
+            mode,
+            dashscope_api_key: None,
+            app_id: Some(app_id),
+            access_token: Some(access_token),
+            cluster: Some(cluster),
             siliconflow_api_key: None,
+            generic_http: None,
+            google: None,
+            openai: None,
+            candidate_languages: Vec::new(),
+            partial_alternatives: 0,
+            retry: RetryOverride::default(),
+            max_concurrency: None,
+            rate_limit: None,
         }
     }
     
@@ -105,28 +338,122 @@ impl ASRProviderConfig {
             dashscope_api_key: None,
             app_id: None,
             access_token: None,
+            cluster: None,
             siliconflow_api_key: Some(api_key),
+            generic_http: None,
+            google: None,
+            openai: None,
+            candidate_languages: Vec::new(),
+            partial_alternatives: 0,
+            retry: RetryOverride::default(),
+            max_concurrency: None,
+            rate_limit: None,
+        }
+    }
+    
+    /// 创建通用 HTTP 配置 (仅支持 HTTP 模式)
+    pub fn generic(generic_http: GenericHttpConfig) -> Self {
+        Self {
+            provider: ASRProvider::Generic,
+            mode: ASRMode::Http,
+            dashscope_api_key: None,
+            app_id: None,
+            access_token: None,
+            cluster: None,
+            siliconflow_api_key: None,
+            generic_http: Some(generic_http),
+            google: None,
+            openai: None,
+            candidate_languages: Vec::new(),
+            partial_alternatives: 0,
+            retry: RetryOverride::default(),
+            max_concurrency: None,
+            rate_limit: None,
+        }
+    }
+    
+    /// 创建 Google 配置 (仅支持 HTTP 模式)
+    pub fn google(google: GoogleConfig) -> Self {
+        Self {
+            provider: ASRProvider::Google,
+            mode: ASRMode::Http,
+            dashscope_api_key: None,
+            app_id: None,
+            access_token: None,
+            cluster: None,
+            siliconflow_api_key: None,
+            generic_http: None,
+            google: Some(google),
+            openai: None,
+            candidate_languages: Vec::new(),
+            partial_alternatives: 0,
+            retry: RetryOverride::default(),
+            max_concurrency: None,
+            rate_limit: None,
         }
     }
     
+    /// 创建 OpenAI 配置 (仅支持 Realtime 模式)
+    pub fn openai(openai: OpenAIConfig) -> Self {
+        Self {
+            provider: ASRProvider::OpenAI,
+            mode: ASRMode::Realtime,
+            dashscope_api_key: None,
+            app_id: None,
+            access_token: None,
+            cluster: None,
+            siliconflow_api_key: None,
+            generic_http: None,
+            google: None,
+            openai: Some(openai),
+            candidate_languages: Vec::new(),
+            partial_alternatives: 0,
+            retry: RetryOverride::default(),
+            max_concurrency: None,
+            rate_limit: None,
+        }
+    }
+    
+    /// 是否可以按语言分别创建引擎 (多语言择优)
+    pub fn supports_language_selection(&self) -> bool {
+        matches!(
+            (&self.provider, &self.mode),
+            (ASRProvider::Qwen, ASRMode::Http) | (ASRProvider::Google, ASRMode::Http)
+        )
+    }
+    
     /// 验证配置是否完整
     pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.max_concurrency == Some(0) {
+            return Err(ConfigError::InvalidConfig("max_concurrency 必须大于 0".to_string()));
+        }
+        if let Some(rate_limit) = self.rate_limit {
+            if !(rate_limit.requests_per_sec.is_finite() && rate_limit.requests_per_sec > 0.0) {
+                return Err(ConfigError::InvalidConfig("rate_limit.requests_per_sec 必须大于 0".to_string()));
+            }
+            if rate_limit.burst == 0 {
+                return Err(ConfigError::InvalidConfig("rate_limit.burst 必须大于 0".to_string()));
+            }
+        }
+        if self.retry.timeout_ms == Some(0) {
+            return Err(ConfigError::InvalidConfig("retry.timeout_ms 必须大于 0".to_string()));
+        }
         match self.provider {
             ASRProvider::Qwen => {
-                if self.dashscope_api_key.as_ref().map_or(true, |k| k.is_empty()) {
+                if self.dashscope_api_key.as_ref().is_none_or(|k| k.is_empty()) {
                     return Err(ConfigError::MissingApiKey("dashscope_api_key".to_string()));
                 }
             }
-            ASRProvider::Doubao => {
-                if self.app_id.as_ref().map_or(true, |k| k.is_empty()) {
+            ASRProvider::Doubao | ASRProvider::Volcengine => {
+                if self.app_id.as_ref().is_none_or(|k| k.is_empty()) {
                     return Err(ConfigError::MissingApiKey("app_id".to_string()));
                 }
-                if self.access_token.as_ref().map_or(true, |k| k.is_empty()) {
+                if self.access_token.as_ref().is_none_or(|k| k.is_empty()) {
                     return Err(ConfigError::MissingApiKey("access_token".to_string()));
                 }
             }
             ASRProvider::SenseVoice => {
-                if self.siliconflow_api_key.as_ref().map_or(true, |k| k.is_empty()) {
+                if self.siliconflow_api_key.as_ref().is_none_or(|k| k.is_empty()) {
                     return Err(ConfigError::MissingApiKey("siliconflow_api_key".to_string()));
                 }
                 // SenseVoice 仅支持 HTTP 模式
@@ -137,13 +464,448 @@ impl ASRProviderConfig {
                     });
                 }
             }
+            ASRProvider::Generic => {
+                let generic = self.generic_http.as_ref()
+                    .ok_or_else(|| ConfigError::InvalidConfig("缺少 generic_http 配置".to_string()))?;
+                if !generic.url.starts_with("http://") && !generic.url.starts_with("https://") {
+                    return Err(ConfigError::InvalidConfig(format!("无效的 URL: {}", generic.url)));
+                }
+                if !matches!(generic.method.to_uppercase().as_str(), "POST" | "PUT" | "PATCH") {
+                    return Err(ConfigError::InvalidConfig(format!("不支持的请求方法: {}", generic.method)));
+                }
+                if generic.text_path.is_empty() {
+                    return Err(ConfigError::InvalidConfig("缺少 text_path".to_string()));
+                }
+                // 通用引擎仅支持 HTTP 模式
+                if self.mode != ASRMode::Http {
+                    return Err(ConfigError::UnsupportedMode {
+                        provider: self.provider.to_string(),
+                        mode: self.mode.to_string(),
+                    });
+                }
+            }
+            ASRProvider::Google => {
+                let google = self.google.as_ref()
+                    .ok_or_else(|| ConfigError::InvalidConfig("缺少 google 配置".to_string()))?;
+                if google.credentials.trim().is_empty() {
+                    return Err(ConfigError::MissingApiKey("google.credentials".to_string()));
+                }
+                if google.language_code.is_empty() {
+                    return Err(ConfigError::InvalidConfig("缺少 language_code".to_string()));
+                }
+                // Google 引擎使用 REST 接口，仅支持 HTTP 模式
+                if self.mode != ASRMode::Http {
+                    return Err(ConfigError::UnsupportedMode {
+                        provider: self.provider.to_string(),
+                        mode: self.mode.to_string(),
+                    });
+                }
+            }
+            ASRProvider::OpenAI => {
+                let openai = self.openai.as_ref()
+                    .ok_or_else(|| ConfigError::InvalidConfig("缺少 openai 配置".to_string()))?;
+                if openai.api_key.trim().is_empty() {
+                    return Err(ConfigError::MissingApiKey("openai.api_key".to_string()));
+                }
+                if let Some(ref endpoint) = openai.endpoint {
+                    if !endpoint.starts_with("ws://") && !endpoint.starts_with("wss://") {
+                        return Err(ConfigError::InvalidConfig(format!("无效的 Realtime 地址: {}", endpoint)));
+                    }
+                }
+                // OpenAI 引擎使用 Realtime WebSocket 接口，仅支持 Realtime 模式
+                if self.mode != ASRMode::Realtime {
+                    return Err(ConfigError::UnsupportedMode {
+                        provider: self.provider.to_string(),
+                        mode: self.mode.to_string(),
+                    });
+                }
+            }
+        }
+        if self.candidate_languages.len() > 1 && !self.supports_language_selection() {
+            return Err(ConfigError::InvalidConfig(format!(
+                "供应商 {} ({} 模式) 不支持多语言择优",
+                self.provider, self.mode
+            )));
         }
         Ok(())
     }
 }
 
+/// 转录结果输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// 纯文本
+    #[default]
+    Text,
+    /// 按模板渲染的 Markdown
+    Markdown,
+}
+
+/// 标点恢复方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PunctuationMode {
+    /// 不做标点恢复
+    #[default]
+    None,
+    /// 基于规则
+    Rule,
+    /// 调用 LLM (失败时退回规则)
+    Llm,
+}
+
+/// 多声道录音转单声道的方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownmixStrategy {
+    /// 各声道平均
+    #[default]
+    Mix,
+    /// 选信噪比最高的声道 (某个声道损坏或只有底噪时使用)
+    BestChannel,
+    /// 只取左声道
+    Left,
+    /// 只取右声道
+    Right,
+}
+
+/// 波形柱高的刻度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WaveScale {
+    /// 沿用音量条的 RMS 映射
+    #[default]
+    Linear,
+    /// RMS 换算为 dB 后归一化，小音量也有明显起伏
+    Log,
+}
+
+/// 中文字形目标
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChineseScript {
+    /// 保持原样
+    #[default]
+    Keep,
+    /// 统一为简体
+    Simplified,
+    /// 统一为繁体
+    Traditional,
+}
+
+/// 英文大小写目标
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LetterCase {
+    /// 保持原样
+    #[default]
+    Keep,
+    /// 全小写
+    Lower,
+    /// 全大写
+    Upper,
+}
+
+/// 输出文本的字形与大小写规整目标
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptTarget {
+    #[serde(default)]
+    pub chinese: ChineseScript,
+    #[serde(default)]
+    pub case: LetterCase,
+}
+
+/// LLM 标点恢复配置 (OpenAI Chat Completions 兼容接口)
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct PunctuationLlmConfig {
+    /// 接口地址
+    pub endpoint: String,
+    /// API Key
+    pub api_key: String,
+    /// 模型名称
+    pub model: String,
+}
+
+/// 录音中输入电平告警配置
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelAlertConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 原始 RMS 低于此值视为接近静音
+    pub quiet_rms: f32,
+    /// 持续接近静音多久后发送 TOO_QUIET (毫秒)
+    pub quiet_ms: u64,
+    /// 削波样本占比不低于此值视为削波
+    pub loud_clipping_ratio: f32,
+    /// 持续削波多久后发送 TOO_LOUD (毫秒)
+    pub loud_ms: u64,
+}
+
+impl Default for LevelAlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            quiet_rms: 0.005,
+            quiet_ms: 3000,
+            loud_clipping_ratio: 0.01,
+            loud_ms: 1000,
+        }
+    }
+}
+
+/// 录音中检测用户开口 (用于打断 TTS 播报)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BargeInConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 原始 RMS 不低于此值视为说话
+    pub threshold_rms: f32,
+    /// 持续说话多久后发送 speech_detected (毫秒)，过短容易被键盘声误触发
+    pub min_speech_ms: u64,
+}
+
+impl Default for BargeInConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_rms: 0.03,
+            min_speech_ms: 300,
+        }
+    }
+}
+
+/// 实时模式噪声门配置 (静音块不送入引擎)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoiseGateConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 块 RMS 低于此值视为静音
+    pub threshold_rms: f32,
+    /// 说话结束后继续转发的时长 (毫秒)，避免裁掉词尾
+    pub hangover_ms: u64,
+}
+
+impl Default for NoiseGateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_rms: 0.01,
+            hangover_ms: 300,
+        }
+    }
+}
+
+/// 实时会话保活配置 (停顿期间没有音频送出时维持连接)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeepaliveConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 距上次发送超过此时长 (毫秒) 仍无音频时发送保活
+    pub interval_ms: u64,
+}
+
+impl Default for KeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_ms: 5000,
+        }
+    }
+}
+
+/// 自适应转录模式配置 (仅对 realtime 模式的主引擎生效)
+///
+/// 录音开始时先缓冲音频，有声时长超过阈值才建立实时会话并补发缓冲；
+/// 阈值之前停止的短录音改用主引擎的 HTTP 模式整段转录
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveModeConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 有声时长 (毫秒) 超过此值时切换到实时流式
+    pub realtime_after_ms: u64,
+}
+
+impl Default for AdaptiveModeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            realtime_after_ms: 3000,
+        }
+    }
+}
+
+/// 离线队列配置 (网络不可用时暂存录音，恢复后自动转录)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OfflineQueueConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 队列目录 (相对路径位于临时目录下)
+    pub dir: String,
+    /// 队列中 WAV 文件的总大小上限 (MB)，超出时不再入队
+    pub max_total_mb: u64,
+    /// 网络仍不可用时的重试间隔 (毫秒)
+    pub retry_interval_ms: u64,
+}
+
+impl Default for OfflineQueueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: "offline-queue".to_string(),
+            max_total_mb: 200,
+            retry_interval_ms: 30_000,
+        }
+    }
+}
+
+/// 引擎熔断配置
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 连续失败多少次后熔断
+    pub failure_threshold: u32,
+    /// 熔断后多久放行试探请求 (毫秒)
+    pub cooldown_ms: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            failure_threshold: 5,
+            cooldown_ms: 30_000,
+        }
+    }
+}
+
+/// 文稿拼装配置 (多段分句合并为带段落的文本)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocumentConfig {
+    /// 相邻分句停顿不短于此值时另起段落 (毫秒)
+    pub paragraph_pause_ms: u64,
+    /// 相邻分句停顿不短于此值 (且短于 paragraph_pause_ms) 时在段内换行 (毫秒，0 表示不换行)
+    pub line_break_pause_ms: u64,
+    /// 段落最大字符数，超出时在句子边界另起段落 (0 表示不限制)
+    pub max_paragraph_chars: usize,
+}
+
+impl Default for DocumentConfig {
+    fn default() -> Self {
+        Self {
+            paragraph_pause_ms: 1500,
+            line_break_pause_ms: 0,
+            max_paragraph_chars: 300,
+        }
+    }
+}
+
+/// 语音命令配置
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VoiceCommandConfig {
+    /// 是否识别语音命令 (整句为命令词时发送 command 而非写入文字)
+    pub enabled: bool,
+    /// 用户命令词，优先于内置词表
+    pub custom: Vec<CustomVoiceCommand>,
+}
+
+/// 术语替换表配置
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplacementConfig {
+    /// 替换项 `[原词, 替换为]`，同一位置取最长的原词
+    pub terms: Vec<(String, String)>,
+    /// 忽略大小写
+    pub ignore_case: bool,
+    /// 仅匹配整词 (只约束英文与数字，中文原词总是匹配)
+    pub whole_word: bool,
+}
+
+/// 用户自定义的语音命令
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomVoiceCommand {
+    /// 命令词
+    pub phrase: String,
+    /// 适用语言 (如 zh、en)，为空时适用于所有语言
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    /// 对应的编辑动作
+    #[serde(flatten)]
+    pub command: super::asr::commands::EditCommand,
+}
+
+/// Obsidian Local REST API 插件的连接配置
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObsidianRestConfig {
+    #[serde(default = "default_obsidian_host")]
+    pub host: String,
+    #[serde(default = "default_obsidian_port")]
+    pub port: u16,
+    /// 是否使用 HTTPS (插件默认只开启自签名证书的 HTTPS 端口)
+    #[serde(default = "default_obsidian_https")]
+    pub https: bool,
+    /// 插件设置中的 API Key
+    pub token: String,
+    /// 追加到当前笔记的此标题下，为空时追加到笔记末尾
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
+}
+
+fn default_obsidian_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_obsidian_port() -> u16 {
+    27124
+}
+
+fn default_obsidian_https() -> bool {
+    true
+}
+
+impl ObsidianRestConfig {
+    pub fn base_url(&self) -> String {
+        let scheme = if self.https { "https" } else { "http" };
+        format!("{}://{}:{}", scheme, self.host, self.port)
+    }
+}
+
+/// 录音旁路转发目的地 (16kHz 单声道 16 位 PCM)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AudioTeeConfig {
+    /// 写入 WAV 文件
+    File { path: String },
+    /// 每个录音块作为一个 UDP 数据报发送到 `host:port`
+    Udp { address: String },
+    /// 每个录音块作为一条 WebSocket 二进制消息发送
+    #[serde(rename = "websocket")]
+    WebSocket { url: String },
+}
+
+impl AudioTeeConfig {
+    /// 日志与告警中展示的目的地
+    pub fn describe(&self) -> String {
+        match self {
+            AudioTeeConfig::File { path } => format!("file:{}", path),
+            AudioTeeConfig::Udp { address } => format!("udp://{}", address),
+            AudioTeeConfig::WebSocket { url } => url.clone(),
+        }
+    }
+}
+
 /// 完整 ASR 配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct ASRConfig {
     /// 主 ASR 引擎配置
     pub primary: ASRProviderConfig,
@@ -152,6 +914,149 @@ pub struct ASRConfig {
     pub fallback: Option<ASRProviderConfig>,
     /// 是否启用自动兜底
     pub enable_fallback: bool,
+    /// 转录结果输出格式
+    #[serde(default)]
+    pub output_format: OutputFormat,
+    /// Markdown 模板 (为空时使用默认模板)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub markdown_template: Option<String>,
+    /// 削波警告阈值 (削波样本占比超过此值时提示，默认 1%)
+    #[serde(default = "default_clipping_threshold")]
+    pub clipping_threshold: f32,
+    /// 标点恢复方式
+    #[serde(default)]
+    pub punctuation: PunctuationMode,
+    /// LLM 标点恢复配置 (punctuation 为 llm 时使用)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub punctuation_llm: Option<PunctuationLlmConfig>,
+    /// 简繁与大小写规整 (在标点恢复之后应用)
+    #[serde(default)]
+    pub script: ScriptTarget,
+    /// 逆文本规整：把口语化的数字、日期、时间、货币转为书面形式 (在标点恢复之前应用)
+    #[serde(default)]
+    pub itn: bool,
+    /// transcription_complete 附带按句拆分的 sentences 数组，供前端逐句编辑
+    #[serde(default)]
+    pub sentences: bool,
+    /// transcription_complete 为含汉字的文本附带逐字拼音 pinyin，供语言学习使用
+    #[serde(default)]
+    pub pinyin: bool,
+    /// 边录边写 WAV 的目录 (HTTP 模式，为空时录音仅保存在内存)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recording_dir: Option<String>,
+    /// 保留的转录历史条数
+    #[serde(default = "default_history_capacity")]
+    pub history_capacity: usize,
+    /// 转录前的音频预处理阶段 (按顺序应用，可选 dc_offset/denoise/trim/resample/normalize/loudness)
+    #[serde(default = "super::audio::pipeline::default_pipeline_names")]
+    pub pipeline: Vec<String>,
+    /// 录音处理预设 (meeting/dictation/noisy)，为管线、降噪、噪声门与静音压缩提供默认值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<super::audio::AudioPreset>,
+    /// 降噪阶段的帧 RMS 门限，越高降噪越强，为空时取预设值或 0.01
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub denoise_threshold: Option<f32>,
+    /// 录音中输入电平过低/过高告警
+    #[serde(default)]
+    pub level_alert: LevelAlertConfig,
+    /// 录音中随 audio_level 发送的频谱频段数，为空时不发送 spectrum
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spectrum_bins: Option<usize>,
+    /// 录音中随 audio_level 发送基频估计 pitch (Hz)
+    #[serde(default)]
+    pub pitch_tracking: bool,
+    /// audio_level 中 peak_levels 峰值保持的衰减速率 (每秒下降的电平)
+    #[serde(default = "default_peak_decay_per_sec")]
+    pub peak_decay_per_sec: f32,
+    /// 录音状态变化与转录失败时发送 play_sound，由前端播放提示音
+    #[serde(default)]
+    pub play_sound_events: bool,
+    /// 录音中检测到用户开口时发送 speech_detected
+    #[serde(default)]
+    pub barge_in: BargeInConfig,
+    /// 实时模式下丢弃静音块
+    #[serde(default)]
+    pub noise_gate: NoiseGateConfig,
+    /// 实时会话停顿期间的保活
+    #[serde(default)]
+    pub keepalive: KeepaliveConfig,
+    /// 按录音长短自动选择 HTTP 或实时流式
+    #[serde(default)]
+    pub adaptive_mode: AdaptiveModeConfig,
+    /// 用服务器播放的提示音作参考信号，消除其被麦克风录入的回声
+    #[serde(default)]
+    pub echo_cancel: bool,
+    /// 网络不可用时把录音放入磁盘队列，恢复后补发转录结果
+    #[serde(default)]
+    pub offline_queue: OfflineQueueConfig,
+    /// 引擎连续失败时熔断
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// 文稿分段
+    #[serde(default)]
+    pub document: DocumentConfig,
+    /// 多声道录音转单声道的方式 (作用于整段录音，实时流仍按平均混合)
+    #[serde(default)]
+    pub downmix: DownmixStrategy,
+    /// 波形柱高的刻度
+    #[serde(default)]
+    pub wave_scale: WaveScale,
+    /// 转录前把超过此时长 (毫秒) 的静音段缩短到此时长，为空时不压缩
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compress_silence_ms: Option<u64>,
+    /// 录音同时转发到的旁路目的地，为空时不转发
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_tee: Option<AudioTeeConfig>,
+    /// 转录完成后 POST 结果 JSON 的回调地址，为空时不回调
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    /// 转录上下文提示 (领域术语、上次内容等)，传给支持 prompt 的引擎
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_prompt: Option<String>,
+    /// 回调签名密钥，设置后附带 HMAC-SHA256 签名 header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_secret: Option<String>,
+    /// partial 前缀连续一致多少次后视为稳定
+    #[serde(default = "default_partial_stability")]
+    pub partial_stability: usize,
+    /// 语音命令识别
+    #[serde(default)]
+    pub voice_commands: VoiceCommandConfig,
+    /// 术语替换表 (在其余文本后处理之后应用)
+    #[serde(default)]
+    pub replacements: ReplacementConfig,
+    /// 转录完成后写入 Obsidian 当前笔记，为空时不写入
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obsidian_rest: Option<ObsidianRestConfig>,
+    /// 转录结果以 JSON Lines 追加写入的文件路径，为空时不写入
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript_log: Option<String>,
+    /// 用户评分与修正文本 (脱敏后) 以 JSON Lines 追加写入的文件路径，为空时仅统计不写入
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback_log: Option<String>,
+}
+
+/// 默认削波警告阈值
+fn default_clipping_threshold() -> f32 {
+    0.01
+}
+
+/// 上下文提示的最大字符数，超出时保留末尾 (最近的上下文)
+pub const MAX_CONTEXT_PROMPT_CHARS: usize = 500;
+
+/// 默认峰值保持衰减速率
+fn default_peak_decay_per_sec() -> f32 {
+    super::audio::utils::DEFAULT_PEAK_DECAY_PER_SEC
+}
+
+/// 默认稳定判定次数
+fn default_partial_stability() -> usize {
+    super::asr::delta::DEFAULT_STABLE_PARTIALS
+}
+
+/// 默认转录历史条数
+fn default_history_capacity() -> usize {
+    super::history::DEFAULT_HISTORY_CAPACITY
 }
 
 impl ASRConfig {
@@ -161,28 +1066,314 @@ impl ASRConfig {
             primary,
             fallback: None,
             enable_fallback: false,
+            output_format: OutputFormat::default(),
+            markdown_template: None,
+            clipping_threshold: default_clipping_threshold(),
+            punctuation: PunctuationMode::default(),
+            punctuation_llm: None,
+            script: ScriptTarget::default(),
+            itn: false,
+            sentences: false,
+            pinyin: false,
+            recording_dir: None,
+            history_capacity: default_history_capacity(),
+            pipeline: super::audio::pipeline::default_pipeline_names(),
+            preset: None,
+            denoise_threshold: None,
+            level_alert: LevelAlertConfig::default(),
+            spectrum_bins: None,
+            pitch_tracking: false,
+            peak_decay_per_sec: default_peak_decay_per_sec(),
+            play_sound_events: false,
+            barge_in: BargeInConfig::default(),
+            noise_gate: NoiseGateConfig::default(),
+            keepalive: KeepaliveConfig::default(),
+            adaptive_mode: AdaptiveModeConfig::default(),
+            echo_cancel: false,
+            offline_queue: OfflineQueueConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            document: DocumentConfig::default(),
+            downmix: DownmixStrategy::default(),
+            wave_scale: WaveScale::default(),
+            compress_silence_ms: None,
+            audio_tee: None,
+            webhook_url: None,
+            webhook_secret: None,
+            context_prompt: None,
+            partial_stability: default_partial_stability(),
+            voice_commands: VoiceCommandConfig::default(),
+            replacements: ReplacementConfig::default(),
+            obsidian_rest: None,
+            transcript_log: None,
+            feedback_log: None,
         }
     }
     
     /// 创建带兜底的配置
     pub fn with_fallback(primary: ASRProviderConfig, fallback: ASRProviderConfig) -> Self {
         Self {
-            primary,
             fallback: Some(fallback),
             enable_fallback: true,
+            ..Self::primary_only(primary)
         }
     }
     
+    /// 截断到 `MAX_CONTEXT_PROMPT_CHARS` 的上下文提示，为空时返回 None
+    pub fn context_prompt(&self) -> Option<&str> {
+        let prompt = self.context_prompt.as_deref()?.trim();
+        let skip = prompt.chars().count().saturating_sub(MAX_CONTEXT_PROMPT_CHARS);
+        let start = prompt.char_indices().nth(skip).map_or(prompt.len(), |(i, _)| i);
+        Some(&prompt[start..]).filter(|p| !p.is_empty())
+    }
+    
+    /// 转录前的预处理管线
+    ///
+    /// 配置了预设时，未改动的 pipeline (仍为默认值) 与未设置的 denoise_threshold 取预设值
+    pub fn audio_pipeline(&self) -> Result<super::audio::Pipeline, super::audio::PipelineError> {
+        let profile = self.preset.map(|preset| preset.profile());
+        let pipeline = match &profile {
+            Some(profile) if self.pipeline == super::audio::pipeline::default_pipeline_names() => {
+                super::audio::Pipeline::new(profile.stages.to_vec())
+            }
+            _ => super::audio::Pipeline::from_names(&self.pipeline)?,
+        };
+        let threshold = self
+            .denoise_threshold
+            .or(profile.map(|profile| profile.denoise_threshold))
+            .unwrap_or(super::audio::utils::VAD_THRESHOLD);
+        Ok(pipeline.with_denoise_threshold(threshold))
+    }
+
+    /// 实时模式噪声门：noise_gate 未改动 (仍为默认值) 时取预设值
+    pub fn effective_noise_gate(&self) -> NoiseGateConfig {
+        match self.preset {
+            Some(preset) if self.noise_gate == NoiseGateConfig::default() => preset.profile().noise_gate,
+            _ => self.noise_gate,
+        }
+    }
+
+    /// 转录前压缩静音的时长：未设置时取预设值
+    pub fn effective_compress_silence_ms(&self) -> Option<u64> {
+        self.compress_silence_ms
+            .or_else(|| self.preset.and_then(|preset| preset.profile().compress_silence_ms))
+    }
+    
     /// 验证配置
     pub fn validate(&self) -> Result<(), ConfigError> {
         self.primary.validate()?;
         if let Some(ref fallback) = self.fallback {
             fallback.validate()?;
         }
+        self.audio_pipeline()
+            .map_err(|e| ConfigError::InvalidConfig(e.to_string()))?;
+        if self.denoise_threshold.is_some_and(|threshold| !(threshold > 0.0 && threshold < 1.0)) {
+            return Err(ConfigError::InvalidConfig("denoise_threshold 须在 0 到 1 之间".to_string()));
+        }
+        if let Some(ref url) = self.webhook_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(ConfigError::InvalidConfig(format!("无效的 webhook URL: {}", url)));
+            }
+        }
+        if self.obsidian_rest.as_ref().is_some_and(|obsidian| obsidian.token.trim().is_empty()) {
+            return Err(ConfigError::MissingApiKey("obsidian_rest.token".to_string()));
+        }
+        if self.transcript_log.as_ref().is_some_and(|path| path.trim().is_empty()) {
+            return Err(ConfigError::InvalidConfig("transcript_log 路径为空".to_string()));
+        }
+        if self.feedback_log.as_ref().is_some_and(|path| path.trim().is_empty()) {
+            return Err(ConfigError::InvalidConfig("feedback_log 路径为空".to_string()));
+        }
+        if self.offline_queue.enabled && self.offline_queue.dir.trim().is_empty() {
+            return Err(ConfigError::InvalidConfig("offline_queue.dir 为空".to_string()));
+        }
         Ok(())
     }
 }
 
+// ============================================================================
+// 密钥脱敏
+// ============================================================================
+
+/// 可部分展示的最短密钥长度，更短的密钥完全隐藏
+const MIN_PARTIAL_SECRET_CHARS: usize = 12;
+
+/// 密钥前后各展示的字符数
+const SECRET_VISIBLE_CHARS: usize = 4;
+
+/// 日志中展示的密钥：仅保留前后各 4 位
+pub fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() < MIN_PARTIAL_SECRET_CHARS {
+        return "***".to_string();
+    }
+    let head: String = chars[..SECRET_VISIBLE_CHARS].iter().collect();
+    let tail: String = chars[chars.len() - SECRET_VISIBLE_CHARS..].iter().collect();
+    format!("{}***{}", head, tail)
+}
+
+/// Debug 输出时脱敏的密钥
+pub struct Masked<'a>(pub &'a str);
+
+impl fmt::Debug for Masked<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&mask_secret(self.0), f)
+    }
+}
+
+/// 可选密钥的脱敏形式
+pub fn masked(secret: &Option<String>) -> Option<Masked<'_>> {
+    secret.as_deref().map(Masked)
+}
+
+/// 名称像鉴权信息的 header (值整体视为密钥)
+fn is_sensitive_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    ["auth", "key", "token", "secret", "cookie"].iter().any(|word| name.contains(word))
+}
+
+impl fmt::Debug for ASRProviderConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ASRProviderConfig")
+            .field("provider", &self.provider)
+            .field("mode", &self.mode)
+            .field("dashscope_api_key", &masked(&self.dashscope_api_key))
+            .field("app_id", &self.app_id)
+            .field("access_token", &masked(&self.access_token))
+            .field("cluster", &self.cluster)
+            .field("siliconflow_api_key", &masked(&self.siliconflow_api_key))
+            .field("generic_http", &self.generic_http)
+            .field("google", &self.google)
+            .field("openai", &self.openai)
+            .field("candidate_languages", &self.candidate_languages)
+            .field("partial_alternatives", &self.partial_alternatives)
+            .field("retry", &self.retry)
+            .field("max_concurrency", &self.max_concurrency)
+            .field("rate_limit", &self.rate_limit)
+            .finish()
+    }
+}
+
+impl fmt::Debug for GenericHttpConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers: HashMap<&String, String> = self.headers.iter()
+            .map(|(name, value)| {
+                let value = if is_sensitive_header(name) { mask_secret(value) } else { value.clone() };
+                (name, value)
+            })
+            .collect();
+        f.debug_struct("GenericHttpConfig")
+            .field("url", &self.url)
+            .field("method", &self.method)
+            .field("headers", &headers)
+            .field("api_key", &masked(&self.api_key))
+            .field("audio_field", &self.audio_field)
+            .field("form_fields", &self.form_fields)
+            .field("text_path", &self.text_path)
+            .field("error_path", &self.error_path)
+            .field("compression", &self.compression)
+            .finish()
+    }
+}
+
+impl fmt::Debug for GoogleConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("GoogleConfig");
+        // 密钥文件路径照常输出，内联的 JSON 凭据脱敏
+        if self.credentials.trim_start().starts_with('{') {
+            debug.field("credentials", &Masked(&self.credentials));
+        } else {
+            debug.field("credentials", &self.credentials);
+        }
+        debug
+            .field("language_code", &self.language_code)
+            .field("model", &self.model)
+            .finish()
+    }
+}
+
+impl fmt::Debug for OpenAIConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpenAIConfig")
+            .field("api_key", &Masked(&self.api_key))
+            .field("model", &self.model)
+            .field("language", &self.language)
+            .field("endpoint", &self.endpoint)
+            .finish()
+    }
+}
+
+impl fmt::Debug for PunctuationLlmConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PunctuationLlmConfig")
+            .field("endpoint", &self.endpoint)
+            .field("api_key", &Masked(&self.api_key))
+            .field("model", &self.model)
+            .finish()
+    }
+}
+
+impl fmt::Debug for ObsidianRestConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObsidianRestConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("https", &self.https)
+            .field("token", &Masked(&self.token))
+            .field("heading", &self.heading)
+            .finish()
+    }
+}
+
+impl fmt::Debug for ASRConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ASRConfig")
+            .field("primary", &self.primary)
+            .field("fallback", &self.fallback)
+            .field("enable_fallback", &self.enable_fallback)
+            .field("output_format", &self.output_format)
+            .field("markdown_template", &self.markdown_template)
+            .field("clipping_threshold", &self.clipping_threshold)
+            .field("punctuation", &self.punctuation)
+            .field("punctuation_llm", &self.punctuation_llm)
+            .field("script", &self.script)
+            .field("itn", &self.itn)
+            .field("sentences", &self.sentences)
+            .field("pinyin", &self.pinyin)
+            .field("recording_dir", &self.recording_dir)
+            .field("history_capacity", &self.history_capacity)
+            .field("pipeline", &self.pipeline)
+            .field("preset", &self.preset)
+            .field("denoise_threshold", &self.denoise_threshold)
+            .field("level_alert", &self.level_alert)
+            .field("spectrum_bins", &self.spectrum_bins)
+            .field("pitch_tracking", &self.pitch_tracking)
+            .field("peak_decay_per_sec", &self.peak_decay_per_sec)
+            .field("play_sound_events", &self.play_sound_events)
+            .field("barge_in", &self.barge_in)
+            .field("noise_gate", &self.noise_gate)
+            .field("keepalive", &self.keepalive)
+            .field("adaptive_mode", &self.adaptive_mode)
+            .field("echo_cancel", &self.echo_cancel)
+            .field("offline_queue", &self.offline_queue)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .field("document", &self.document)
+            .field("downmix", &self.downmix)
+            .field("wave_scale", &self.wave_scale)
+            .field("compress_silence_ms", &self.compress_silence_ms)
+            .field("audio_tee", &self.audio_tee)
+            .field("webhook_url", &self.webhook_url)
+            .field("context_prompt", &self.context_prompt)
+            .field("webhook_secret", &masked(&self.webhook_secret))
+            .field("partial_stability", &self.partial_stability)
+            .field("voice_commands", &self.voice_commands)
+            .field("replacements", &self.replacements)
+            .field("obsidian_rest", &self.obsidian_rest)
+            .field("transcript_log", &self.transcript_log)
+            .field("feedback_log", &self.feedback_log)
+            .finish()
+    }
+}
+
 /// 配置错误
 #[derive(Debug, thiserror::Error)]
 pub enum ConfigError {
@@ -203,6 +1394,67 @@ pub enum ConfigError {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_debug_masks_secrets() {
+        const KEYS: [&str; 7] = [
+            "sk-dashscope-0123456789abcdef",
+            "volc-token-0123456789abcdef",
+            "sk-openai-0123456789abcdef",
+            "sk-llm-0123456789abcdef",
+            "obsidian-token-0123456789abcdef",
+            "webhook-secret-0123456789abcdef",
+            "Bearer generic-0123456789abcdef",
+        ];
+        let mut primary = ASRProviderConfig::qwen(ASRMode::Http, KEYS[0].to_string());
+        primary.access_token = Some(KEYS[1].to_string());
+        primary.openai = Some(OpenAIConfig {
+            api_key: KEYS[2].to_string(),
+            model: default_openai_model(),
+            language: None,
+            endpoint: None,
+        });
+        primary.generic_http = Some(GenericHttpConfig {
+            url: "https://asr.example.com".to_string(),
+            method: default_http_method(),
+            headers: HashMap::from([
+                ("Authorization".to_string(), KEYS[6].to_string()),
+                ("Accept".to_string(), "application/json".to_string()),
+            ]),
+            api_key: Some("short".to_string()),
+            audio_field: default_audio_field(),
+            form_fields: HashMap::new(),
+            text_path: "$.text".to_string(),
+            error_path: None,
+            compression: RequestCompression::None,
+        });
+        let mut config = ASRConfig::primary_only(primary);
+        config.punctuation_llm = Some(PunctuationLlmConfig {
+            endpoint: "https://llm.example.com".to_string(),
+            api_key: KEYS[3].to_string(),
+            model: "gpt".to_string(),
+        });
+        config.obsidian_rest = Some(ObsidianRestConfig {
+            host: default_obsidian_host(),
+            port: default_obsidian_port(),
+            https: true,
+            token: KEYS[4].to_string(),
+            heading: None,
+        });
+        config.webhook_secret = Some(KEYS[5].to_string());
+
+        for output in [format!("{:?}", config), format!("{:#?}", config)] {
+            for key in KEYS {
+                assert!(!output.contains(key), "泄漏密钥 {}: {}", key, output);
+            }
+            assert!(!output.contains("short"));
+            assert!(output.contains("\"sk-d***cdef\""));
+            assert!(output.contains("application/json"));
+        }
+
+        assert_eq!(mask_secret("sk-0123456789"), "sk-0***6789");
+        assert_eq!(mask_secret("tiny"), "***");
+    }
+
     #[test]
     fn test_qwen_config_validation() {
         let config = ASRProviderConfig::qwen(ASRMode::Realtime, "test-key".to_string());
@@ -214,7 +1466,16 @@ mod tests {
             dashscope_api_key: None,
             app_id: None,
             access_token: None,
+            cluster: None,
             siliconflow_api_key: None,
+            generic_http: None,
+            google: None,
+            openai: None,
+            candidate_languages: Vec::new(),
+            partial_alternatives: 0,
+            retry: RetryOverride::default(),
+            max_concurrency: None,
+            rate_limit: None,
         };
         assert!(invalid_config.validate().is_err());
     }
@@ -235,11 +1496,216 @@ mod tests {
             dashscope_api_key: None,
             app_id: None,
             access_token: Some("token".to_string()),
+            cluster: None,
             siliconflow_api_key: None,
+            generic_http: None,
+            google: None,
+            openai: None,
+            candidate_languages: Vec::new(),
+            partial_alternatives: 0,
+            retry: RetryOverride::default(),
+            max_concurrency: None,
+            rate_limit: None,
         };
         assert!(invalid_config.validate().is_err());
     }
 
+    #[test]
+    fn test_volcengine_config_from_json() {
+        let json = r#"{
+            "provider": "volcengine",
+            "mode": "realtime",
+            "app_id": "app-123",
+            "access_token": "token-456",
+            "cluster": "volcengine_streaming_common"
+        }"#;
+        
+        let config: ASRProviderConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.provider, ASRProvider::Volcengine);
+        assert_eq!(config.cluster, Some("volcengine_streaming_common".to_string()));
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_generic_config_from_json() {
+        let json = r#"{
+            "provider": "generic",
+            "mode": "http",
+            "generic_http": {
+                "url": "https://asr.example.com/v1/recognize",
+                "headers": { "Authorization": "Bearer {{api_key}}" },
+                "api_key": "key-123",
+                "text_path": "$.result.text"
+            }
+        }"#;
+        
+        let config: ASRProviderConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.provider, ASRProvider::Generic);
+        let generic = config.generic_http.as_ref().unwrap();
+        assert_eq!(generic.method, "POST");
+        assert_eq!(generic.audio_field, "file");
+        assert!(config.validate().is_ok());
+        
+        // 缺少请求模板或使用 Realtime 模式应该失败
+        let mut invalid = config.clone();
+        invalid.generic_http = None;
+        assert!(invalid.validate().is_err());
+        let mut invalid = config;
+        invalid.mode = ASRMode::Realtime;
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_google_config_from_json() {
+        let json = r#"{
+            "provider": "google",
+            "mode": "http",
+            "google": {
+                "credentials": "/path/to/service-account.json",
+                "model": "latest_long"
+            }
+        }"#;
+        
+        let config: ASRProviderConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.provider, ASRProvider::Google);
+        let google = config.google.as_ref().unwrap();
+        assert_eq!(google.language_code, "cmn-Hans-CN");
+        assert_eq!(google.model.as_deref(), Some("latest_long"));
+        assert!(config.validate().is_ok());
+        
+        let mut invalid = config.clone();
+        invalid.google = None;
+        assert!(invalid.validate().is_err());
+        let mut invalid = config;
+        invalid.mode = ASRMode::Realtime;
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_openai_config_from_json() {
+        let json = r#"{
+            "provider": "openai",
+            "mode": "realtime",
+            "openai": { "api_key": "sk-xxx", "language": "zh" }
+        }"#;
+        
+        let config: ASRProviderConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.provider, ASRProvider::OpenAI);
+        let openai = config.openai.as_ref().unwrap();
+        assert_eq!(openai.model, "gpt-4o-transcribe");
+        assert_eq!(openai.language.as_deref(), Some("zh"));
+        assert!(config.validate().is_ok());
+        
+        let mut invalid = config.clone();
+        invalid.openai.as_mut().unwrap().endpoint = Some("https://api.openai.com".to_string());
+        assert!(invalid.validate().is_err());
+        let mut invalid = config;
+        invalid.mode = ASRMode::Http;
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_pipeline_config() {
+        let mut config = ASRConfig::primary_only(ASRProviderConfig::qwen(ASRMode::Http, "key".to_string()));
+        assert_eq!(config.pipeline, vec!["dc_offset", "trim", "resample", "normalize"]);
+        assert!(config.validate().is_ok());
+
+        config.pipeline = vec!["denoise".to_string(), "echo".to_string()];
+        assert!(matches!(config.validate(), Err(ConfigError::InvalidConfig(msg)) if msg.contains("echo")));
+    }
+
+    #[test]
+    fn test_audio_preset_with_overrides() {
+        use super::super::audio::{PipelineStage, AudioPreset};
+
+        let json = r#"{
+            "primary": { "provider": "sensevoice", "mode": "http", "siliconflow_api_key": "key" },
+            "enable_fallback": false,
+            "preset": "noisy"
+        }"#;
+        let config: ASRConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.preset, Some(AudioPreset::Noisy));
+        let pipeline = config.audio_pipeline().unwrap();
+        assert!(pipeline.stages().contains(&PipelineStage::Denoise));
+        assert_eq!(pipeline.denoise_threshold(), 0.03);
+        assert!(config.effective_noise_gate().enabled);
+        assert_eq!(config.effective_compress_silence_ms(), Some(1000));
+
+        // 显式设置的字段覆盖预设
+        let json = r#"{
+            "primary": { "provider": "sensevoice", "mode": "http", "siliconflow_api_key": "key" },
+            "enable_fallback": false,
+            "preset": "noisy",
+            "pipeline": ["resample"],
+            "denoise_threshold": 0.02,
+            "noise_gate": { "enabled": true, "threshold_rms": 0.05 },
+            "compress_silence_ms": 800
+        }"#;
+        let config: ASRConfig = serde_json::from_str(json).unwrap();
+        let pipeline = config.audio_pipeline().unwrap();
+        assert_eq!(pipeline.stages(), &[PipelineStage::Resample]);
+        assert_eq!(pipeline.denoise_threshold(), 0.02);
+        assert_eq!(config.effective_noise_gate().threshold_rms, 0.05);
+        assert_eq!(config.effective_compress_silence_ms(), Some(800));
+
+        // 无预设时行为不变
+        let mut config = ASRConfig::primary_only(ASRProviderConfig::qwen(ASRMode::Http, "key".to_string()));
+        assert_eq!(config.audio_pipeline().unwrap(), super::super::audio::Pipeline::default());
+        assert!(!config.effective_noise_gate().enabled);
+        config.denoise_threshold = Some(1.5);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_script_target_from_json() {
+        let json = r#"{
+            "primary": { "provider": "sensevoice", "mode": "http", "siliconflow_api_key": "key" },
+            "enable_fallback": false,
+            "script": { "chinese": "traditional" }
+        }"#;
+        
+        let config: ASRConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.script.chinese, ChineseScript::Traditional);
+        assert_eq!(config.script.case, LetterCase::Keep);
+        
+        // 未配置时保持原样
+        let config = ASRConfig::primary_only(ASRProviderConfig::sensevoice("key".to_string()));
+        assert_eq!(config.script, ScriptTarget::default());
+    }
+
+    #[test]
+    fn test_audio_tee_from_json() {
+        let json = r#"{
+            "primary": { "provider": "sensevoice", "mode": "http", "siliconflow_api_key": "key" },
+            "enable_fallback": false,
+            "audio_tee": { "type": "websocket", "url": "ws://127.0.0.1:9000/archive" }
+        }"#;
+
+        let config: ASRConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.audio_tee,
+            Some(AudioTeeConfig::WebSocket { url: "ws://127.0.0.1:9000/archive".to_string() })
+        );
+
+        let udp: AudioTeeConfig = serde_json::from_str(r#"{ "type": "udp", "address": "127.0.0.1:5004" }"#).unwrap();
+        assert_eq!(udp.describe(), "udp://127.0.0.1:5004");
+    }
+
+    #[test]
+    fn test_context_prompt_keeps_recent_tail() {
+        let mut config = ASRConfig::primary_only(ASRProviderConfig::sensevoice("key".to_string()));
+        assert_eq!(config.context_prompt(), None);
+
+        config.context_prompt = Some("  Obsidian 插件  ".to_string());
+        assert_eq!(config.context_prompt(), Some("Obsidian 插件"));
+
+        config.context_prompt = Some(format!("{}{}", "旧".repeat(100), "新".repeat(MAX_CONTEXT_PROMPT_CHARS)));
+        assert_eq!(config.context_prompt(), Some("新".repeat(MAX_CONTEXT_PROMPT_CHARS).as_str()));
+
+        config.context_prompt = Some("   ".to_string());
+        assert_eq!(config.context_prompt(), None);
+    }
+
     #[test]
     fn test_sensevoice_mode_validation() {
         // SenseVoice 仅支持 HTTP 模式
@@ -297,6 +1763,37 @@ mod tests {
         assert!(config.enable_fallback);
     }
 
+    #[test]
+    fn test_clipping_threshold_default() {
+        let json = r#"{
+            "primary": {"provider": "qwen", "mode": "http", "dashscope_api_key": "sk-xxx"},
+            "enable_fallback": false
+        }"#;
+        let config: ASRConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.clipping_threshold, 0.01);
+        
+        let json = r#"{
+            "primary": {"provider": "qwen", "mode": "http", "dashscope_api_key": "sk-xxx"},
+            "enable_fallback": false,
+            "clipping_threshold": 0.05
+        }"#;
+        let config: ASRConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.clipping_threshold, 0.05);
+    }
+
+    #[test]
+    fn test_level_alert_config() {
+        let json = r#"{
+            "primary": {"provider": "qwen", "mode": "http", "dashscope_api_key": "sk-xxx"},
+            "enable_fallback": false,
+            "level_alert": {"quiet_ms": 5000}
+        }"#;
+        let config: ASRConfig = serde_json::from_str(json).unwrap();
+        assert!(config.level_alert.enabled);
+        assert_eq!(config.level_alert.quiet_ms, 5000);
+        assert_eq!(config.level_alert.loud_ms, LevelAlertConfig::default().loud_ms);
+    }
+
     #[test]
     fn test_primary_only_config() {
         let config = ASRConfig::primary_only(
@@ -0,0 +1,330 @@
+// 转录质量反馈
+// 客户端可对转录结果评分 (1-5) 并附带修正文本，服务器按引擎累计平均评分与字错误率 (CER)，
+// 配置 ASRConfig.feedback_log 时逐条写入 JSON Lines；转录文本与修正文本都先脱敏再落盘
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+use serde::Serialize;
+
+use super::asr::TranscriptionResult;
+use super::resume;
+use super::transcript_log;
+
+/// 可评分的最近结果条数，超出后最旧的结果不再接受评分
+pub const MAX_RATABLE_RESULTS: usize = 200;
+
+/// 评分范围
+pub const RATING_RANGE: std::ops::RangeInclusive<u8> = 1..=5;
+
+/// 连续数字达到该位数时视为电话、证件号等敏感信息
+const MIN_SENSITIVE_DIGITS: usize = 6;
+
+/// 反馈错误
+#[derive(Debug, thiserror::Error)]
+pub enum FeedbackError {
+    #[error("转录结果不存在或已评分: {0}")]
+    UnknownRequest(String),
+
+    #[error("评分必须在 1-5 之间: {0}")]
+    InvalidRating(i64),
+}
+
+impl FeedbackError {
+    /// 错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            FeedbackError::UnknownRequest(_) => "REQUEST_NOT_FOUND",
+            FeedbackError::InvalidRating(_) => "INVALID_RATING",
+        }
+    }
+}
+
+/// 待评分的转录结果
+struct RatableResult {
+    request_id: String,
+    engine: String,
+    text: String,
+}
+
+/// 单个引擎的累计反馈
+#[derive(Debug, Default)]
+struct EngineTotals {
+    ratings: u64,
+    rating_sum: u64,
+    corrections: u64,
+    /// 修正文本与转录文本的编辑距离之和
+    edit_distance: u64,
+    /// 修正文本字符数之和 (CER 分母)
+    reference_chars: u64,
+}
+
+impl EngineTotals {
+    fn snapshot(&self) -> EngineFeedback {
+        EngineFeedback {
+            ratings: self.ratings,
+            average_rating: (self.ratings > 0).then(|| self.rating_sum as f64 / self.ratings as f64),
+            corrections: self.corrections,
+            char_error_rate: (self.reference_chars > 0)
+                .then(|| self.edit_distance as f64 / self.reference_chars as f64),
+        }
+    }
+}
+
+/// 单个引擎的反馈统计
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EngineFeedback {
+    /// 评分次数
+    pub ratings: u64,
+    /// 平均评分
+    pub average_rating: Option<f64>,
+    /// 附带修正文本的次数
+    pub corrections: u64,
+    /// 按修正文本计算的累计字错误率
+    pub char_error_rate: Option<f64>,
+}
+
+/// 评分结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct RatingOutcome {
+    pub engine: String,
+    /// 本次修正的字错误率，未附带修正文本时为 None
+    pub char_error_rate: Option<f64>,
+}
+
+/// JSONL 反馈记录
+#[derive(Serialize)]
+struct FeedbackLine<'a> {
+    timestamp: u64,
+    request_id: &'a str,
+    engine: &'a str,
+    rating: u8,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    corrected_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    char_error_rate: Option<f64>,
+}
+
+/// 反馈存储
+pub struct FeedbackStore {
+    recent: VecDeque<RatableResult>,
+    totals: HashMap<String, EngineTotals>,
+}
+
+impl FeedbackStore {
+    pub fn new() -> Self {
+        Self {
+            recent: VecDeque::new(),
+            totals: HashMap::new(),
+        }
+    }
+
+    /// 登记一条可评分的结果，返回分配的 request_id
+    pub fn register(&mut self, result: &TranscriptionResult) -> String {
+        let request_id = resume::generate_session_id();
+        while self.recent.len() >= MAX_RATABLE_RESULTS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(RatableResult {
+            request_id: request_id.clone(),
+            engine: result.engine.clone(),
+            text: result.text.clone(),
+        });
+        request_id
+    }
+
+    /// 记录评分，每条结果只接受一次评分
+    ///
+    /// 配置了 `log_path` 时追加一行脱敏后的 JSONL 记录
+    pub fn rate(
+        &mut self,
+        request_id: &str,
+        rating: i64,
+        corrected_text: Option<&str>,
+        log_path: Option<&str>,
+    ) -> Result<RatingOutcome, FeedbackError> {
+        let rating = u8::try_from(rating)
+            .ok()
+            .filter(|r| RATING_RANGE.contains(r))
+            .ok_or(FeedbackError::InvalidRating(rating))?;
+        let index = self.recent.iter()
+            .position(|r| r.request_id == request_id)
+            .ok_or_else(|| FeedbackError::UnknownRequest(request_id.to_string()))?;
+        let result = self.recent.remove(index).expect("索引有效");
+
+        let totals = self.totals.entry(result.engine.clone()).or_default();
+        totals.ratings += 1;
+        totals.rating_sum += rating as u64;
+
+        let corrected_text = corrected_text.map(str::trim).filter(|t| !t.is_empty());
+        let char_error_rate = corrected_text.map(|corrected| {
+            let distance = edit_distance(&result.text, corrected) as u64;
+            let reference = corrected.chars().count() as u64;
+            totals.corrections += 1;
+            totals.edit_distance += distance;
+            totals.reference_chars += reference;
+            distance as f64 / reference as f64
+        });
+
+        if let Some(path) = log_path {
+            transcript_log::append_json(path, &FeedbackLine {
+                timestamp: transcript_log::now_millis(),
+                request_id,
+                engine: &result.engine,
+                rating,
+                text: redact(&result.text),
+                corrected_text: corrected_text.map(redact),
+                char_error_rate,
+            });
+        }
+
+        Ok(RatingOutcome {
+            engine: result.engine,
+            char_error_rate,
+        })
+    }
+
+    /// 各引擎的反馈统计
+    pub fn stats(&self) -> HashMap<String, EngineFeedback> {
+        self.totals.iter()
+            .map(|(engine, totals)| (engine.clone(), totals.snapshot()))
+            .collect()
+    }
+}
+
+impl Default for FeedbackStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 进程级反馈存储 (跨连接共享)
+pub fn global() -> &'static Mutex<FeedbackStore> {
+    static STORE: OnceLock<Mutex<FeedbackStore>> = OnceLock::new();
+    STORE.get_or_init(|| Mutex::new(FeedbackStore::new()))
+}
+
+/// 按字符计算的编辑距离 (Levenshtein)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
+
+/// 脱敏: 邮箱替换为 `[EMAIL]`，连续 6 位及以上的数字 (允许空格、短横线分隔) 替换为 `[NUMBER]`
+pub fn redact(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some(end) = email_end(&chars, i) {
+            output.push_str("[EMAIL]");
+            i = end;
+        } else if let Some(end) = number_end(&chars, i) {
+            output.push_str("[NUMBER]");
+            i = end;
+        } else {
+            output.push(chars[i]);
+            i += 1;
+        }
+    }
+    output
+}
+
+fn is_email_local(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '%' | '+' | '-')
+}
+
+fn is_email_domain(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '-')
+}
+
+/// 从 start 开始是邮箱时返回其结束位置 (start 须为本地部分的首字符)
+fn email_end(chars: &[char], start: usize) -> Option<usize> {
+    if start > 0 && is_email_local(chars[start - 1]) {
+        return None;
+    }
+    let at = start + chars[start..].iter().take_while(|&&c| is_email_local(c)).count();
+    if at == start || chars.get(at) != Some(&'@') {
+        return None;
+    }
+    let domain = &chars[at + 1..];
+    let mut len = domain.iter().take_while(|&&c| is_email_domain(c)).count();
+    // 句末的点不属于域名
+    while len > 0 && domain[len - 1] == '.' {
+        len -= 1;
+    }
+    let dot = domain[..len].iter().position(|&c| c == '.')?;
+    (dot > 0 && dot + 1 < len).then_some(at + 1 + len)
+}
+
+/// 从 start 开始是敏感数字串时返回其结束位置
+fn number_end(chars: &[char], start: usize) -> Option<usize> {
+    if !chars[start].is_ascii_digit() || (start > 0 && chars[start - 1].is_ascii_digit()) {
+        return None;
+    }
+    let mut digits = 0;
+    let mut end = start;
+    let mut i = start;
+    while i < chars.len() {
+        if chars[i].is_ascii_digit() {
+            digits += 1;
+            i += 1;
+            end = i;
+        } else if matches!(chars[i], ' ' | '-') && chars.get(i + 1).is_some_and(char::is_ascii_digit) {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+    (digits >= MIN_SENSITIVE_DIGITS).then_some(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_emails_and_numbers() {
+        assert_eq!(
+            redact("邮箱是 zhang.san@example.com。电话 138-1234-5678，房间 302"),
+            "邮箱是 [EMAIL]。电话 [NUMBER]，房间 302"
+        );
+        assert_eq!(redact("发到a@b.cn."), "发到[EMAIL].");
+        assert_eq!(redact("not@email 和 2024 年"), "not@email 和 2024 年");
+        assert_eq!(redact("身份证110101199003071234"), "身份证[NUMBER]");
+    }
+
+    #[test]
+    fn test_rating_updates_engine_stats() {
+        let mut store = FeedbackStore::new();
+        let first = store.register(&TranscriptionResult::new("今天天汽很好".to_string(), "qwen".to_string(), false, 0));
+        let second = store.register(&TranscriptionResult::new("你好".to_string(), "qwen".to_string(), false, 0));
+
+        assert!(matches!(store.rate(&first, 6, None, None), Err(FeedbackError::InvalidRating(6))));
+
+        let outcome = store.rate(&first, 3, Some("今天天气很好"), None).unwrap();
+        assert_eq!(outcome.engine, "qwen");
+        assert!((outcome.char_error_rate.unwrap() - 1.0 / 6.0).abs() < 1e-9);
+        store.rate(&second, 5, None, None).unwrap();
+
+        // 每条结果只接受一次评分
+        assert!(matches!(store.rate(&first, 4, None, None), Err(FeedbackError::UnknownRequest(_))));
+
+        let stats = store.stats();
+        let qwen = &stats["qwen"];
+        assert_eq!(qwen.ratings, 2);
+        assert_eq!(qwen.average_rating, Some(4.0));
+        assert_eq!(qwen.corrections, 1);
+        assert!((qwen.char_error_rate.unwrap() - 1.0 / 6.0).abs() < 1e-9);
+    }
+}
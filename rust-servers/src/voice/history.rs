@@ -0,0 +1,128 @@
+// 转录历史
+// 每个会话保存最近 N 条转录结果，其他连接无法读取；异常断开后随会话快照保存，
+// 客户端通过 resume_session 恢复会话后可用 get_history 取回
+
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::asr::{Timings, TranscriptionResult};
+
+/// 默认保留的历史条数
+pub const DEFAULT_HISTORY_CAPACITY: usize = 20;
+
+/// 单条转录历史
+///
+/// 只记录转录输出本身，不包含配置与凭据
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct HistoryItem {
+    /// 最终输出文本 (已后处理与格式化)
+    pub text: String,
+    /// 输出格式 (text / markdown)
+    pub format: String,
+    pub engine: String,
+    pub used_fallback: bool,
+    pub duration_ms: u64,
+    pub timings: Timings,
+    /// 完成时间 (Unix 毫秒)
+    pub created_at: u64,
+}
+
+impl HistoryItem {
+    pub fn new(result: &TranscriptionResult, text: String, format: &str) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        Self {
+            text,
+            format: format.to_string(),
+            engine: result.engine.clone(),
+            used_fallback: result.used_fallback,
+            duration_ms: result.duration_ms,
+            timings: result.timings,
+            created_at,
+        }
+    }
+}
+
+/// 固定容量的转录历史环形缓冲
+#[derive(Debug)]
+pub struct TranscriptionHistory {
+    items: VecDeque<HistoryItem>,
+    capacity: usize,
+}
+
+impl TranscriptionHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// 追加一条记录，超出容量时丢弃最旧的
+    pub fn push(&mut self, item: HistoryItem) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.items.len() >= self.capacity {
+            self.items.pop_front();
+        }
+        self.items.push_back(item);
+    }
+
+    /// 调整容量，缩小时保留最新的记录
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.items.len() > capacity {
+            self.items.pop_front();
+        }
+    }
+
+    /// 最近的记录 (由新到旧)，limit 为 None 时返回全部
+    pub fn recent(&self, limit: Option<usize>) -> Vec<HistoryItem> {
+        let limit = limit.unwrap_or(self.items.len());
+        self.items.iter().rev().take(limit).cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl Default for TranscriptionHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(text: &str) -> HistoryItem {
+        let result = TranscriptionResult::new(text.to_string(), "qwen".to_string(), false, 100);
+        HistoryItem::new(&result, text.to_string(), "text")
+    }
+
+    #[test]
+    fn test_ring_buffer_keeps_latest() {
+        let mut history = TranscriptionHistory::new(3);
+        for text in ["一", "二", "三", "四"] {
+            history.push(item(text));
+        }
+
+        let texts: Vec<_> = history.recent(None).into_iter().map(|i| i.text).collect();
+        assert_eq!(texts, vec!["四", "三", "二"]);
+        assert_eq!(history.recent(Some(1))[0].text, "四");
+
+        history.set_capacity(1);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history.recent(None)[0].text, "四");
+    }
+}
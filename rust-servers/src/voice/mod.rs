@@ -5,18 +5,35 @@ pub mod audio;
 pub mod asr;
 pub mod beep;
 pub mod config;
+pub mod feedback;
+pub mod history;
+pub mod obsidian;
+pub mod offline_queue;
+pub mod resume;
+pub mod state;
+pub mod stdin;
+pub mod transcript_log;
+pub mod upload;
+pub mod usage;
+pub mod webhook;
 
 use crate::router::{ModuleHandler, ModuleMessage, ModuleType, RouterError, ServerResponse};
+use crate::outbound::{Delivery, OutboundMessage, Payload};
 use crate::server::WsSender;
-use futures_util::SinkExt;
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::{mpsc, oneshot, Mutex as TokioMutex};
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 
-use audio::{AudioRecorder, RecordingMode as AudioRecordingMode, StreamingRecorder, AudioData};
-use asr::{ParallelFallbackStrategy, TranscriptionResult, ASRError, RealtimeTaskResult, RealtimeTranscriptionTask};
-use beep::BeepPlayer;
-use config::{ASRConfig, ASRMode};
+use audio::{AudioRecorder, AudioTee, CaptureRequest, RecordingMode as AudioRecordingMode, StreamingRecorder, AudioData, LevelMonitor, SpeechDetector};
+use audio::utils::LevelStats;
+use asr::{ASREngine, CircuitBreakerEngine, ConcurrencyLimitedEngine, RateLimitedEngine, RetryConfig, ParallelFallbackStrategy, PartialDeltaTracker, Timings, TranscriptionResult, ASRError, RealtimeTaskResult, RealtimeTranscriptionTask};
+use beep::{BeepPlayer, BeepType, SoundKind};
+use config::{ASRConfig, ASRMode, ASRProviderConfig, OutputFormat, ScriptTarget};
+use state::{TransitionError, VoiceEvent, VoicePhase};
+use usage::{UsageMeter, UsageQuota};
 
 /// 日志宏
 macro_rules! log_info {
@@ -25,6 +42,12 @@ macro_rules! log_info {
     };
 }
 
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        eprintln!("[WARN] [Voice] {}", format!($($arg)*));
+    };
+}
+
 macro_rules! log_error {
     ($($arg:tt)*) => {
         eprintln!("[ERROR] [Voice] {}", format!($($arg)*));
@@ -39,6 +62,15 @@ macro_rules! log_debug {
     };
 }
 
+/// 录音统计 (recording_stats) 发送间隔
+const RECORDING_STATS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// 录音中开始判断削波所需的最短音频时长 (毫秒)，过短时个别爆音占比偏高
+const CLIPPING_CHECK_MIN_MS: u64 = 1000;
+
+/// 停止录音后等待已排队的部分结果发完的最长时间
+const PARTIAL_FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
 // ============================================================================
 // 录音模式
 // ============================================================================
@@ -82,6 +114,119 @@ pub enum RecordingState {
 struct AudioLevelData {
     level: f32,
     waveform: Vec<f32>,
+    stats: LevelStats,
+    /// 幅度谱 (配置了 spectrum_bins 时)
+    spectrum: Option<Vec<f32>>,
+    /// 基频估计 (开启 pitch_tracking 时；内层为 None 表示无声或清音)
+    pitch: Option<Option<f32>>,
+}
+
+// ============================================================================
+// 连接专属引擎
+// ============================================================================
+
+/// 连接专属的 ASR 引擎实例
+///
+/// 多个连接共享同一服务进程时，各自持有按自身配置创建的引擎
+#[derive(Clone)]
+struct ConnectionEngines {
+    /// 创建引擎所用的配置
+    config: ASRConfig,
+    /// 主引擎
+    primary: Arc<dyn ASREngine>,
+    /// 主引擎的 HTTP 模式 (短录音与实时失败后的回退)，主引擎本身为 HTTP 模式时与其相同；
+    /// 创建失败时为空，回退时报告该错误
+    primary_http: Result<Arc<dyn ASREngine>, String>,
+    /// 备用引擎
+    fallback: Option<Arc<dyn ASREngine>>,
+    /// 主/备引擎的熔断器 (未启用熔断时为空)
+    breakers: Vec<(&'static str, Arc<CircuitBreakerEngine>)>,
+    /// 主引擎的重试配置 (引擎默认值叠加用户覆盖)，用于兜底策略与实时任务
+    retry: RetryConfig,
+}
+
+impl ConnectionEngines {
+    /// 按配置创建引擎
+    fn build(config: &ASRConfig) -> Result<Self, ASRError> {
+        config.validate()
+            .map_err(|e| ASRError::ConfigError(e.to_string()))?;
+        
+        let mut breakers = Vec::new();
+        let mut wrap = |role: &'static str, provider: &ASRProviderConfig, engine: Box<dyn ASREngine>| -> Arc<dyn ASREngine> {
+            let engine: Arc<dyn ASREngine> = engine.into();
+            asr::warmup::spawn_warmup(Arc::clone(&engine));
+            // 熔断器在外层，排队等待的请求在熔断时直接快速失败
+            let mut engine: Arc<dyn ASREngine> = Arc::new(ConcurrencyLimitedEngine::new(engine, provider.max_concurrency));
+            if let Some(rate_limit) = provider.rate_limit {
+                engine = Arc::new(RateLimitedEngine::new(engine, rate_limit));
+            }
+            if !config.circuit_breaker.enabled {
+                return engine;
+            }
+            let breaker = Arc::new(CircuitBreakerEngine::new(engine, config.circuit_breaker));
+            breakers.push((role, Arc::clone(&breaker)));
+            breaker
+        };
+        
+        let context_prompt = config.context_prompt();
+        let primary = wrap(
+            "primary",
+            &config.primary,
+            asr::create_engine_with_context(&config.primary, context_prompt)?,
+        );
+        // 实时模式的主引擎另建 HTTP 模式引擎，同样经过限流、熔断等包装
+        let primary_http = if config.primary.mode == ASRMode::Http {
+            Ok(Arc::clone(&primary))
+        } else {
+            let mut http_config = config.primary.clone();
+            http_config.mode = ASRMode::Http;
+            asr::create_engine_with_context(&http_config, context_prompt)
+                .map(|engine| wrap("primary_http", &http_config, engine))
+                .map_err(|e| e.to_string())
+        };
+        let fallback = match config.fallback {
+            Some(ref fallback_config) => Some(wrap(
+                "fallback",
+                fallback_config,
+                asr::create_engine_with_context(fallback_config, context_prompt)?,
+            )),
+            None => None,
+        };
+        let retry = primary.default_retry().with_overrides(&config.primary.retry);
+        
+        Ok(Self {
+            config: config.clone(),
+            primary,
+            primary_http,
+            fallback,
+            breakers,
+            retry,
+        })
+    }
+    
+    /// 各引擎的熔断状态
+    fn circuit_status(&self) -> serde_json::Value {
+        let status: serde_json::Map<String, serde_json::Value> = self.breakers.iter()
+            .map(|(role, breaker)| {
+                let value = serde_json::json!({
+                    "engine": breaker.name(),
+                    "circuit": breaker.status(),
+                });
+                (role.to_string(), value)
+            })
+            .collect();
+        serde_json::Value::Object(status)
+    }
+    
+    /// 创建使用这些引擎的兜底策略
+    fn strategy(&self) -> ParallelFallbackStrategy {
+        ParallelFallbackStrategy::from_engines(
+            Arc::clone(&self.primary),
+            self.fallback.clone(),
+            self.config.enable_fallback,
+        )
+        .with_retry_config(self.retry.clone())
+    }
 }
 
 // ============================================================================
@@ -92,8 +237,10 @@ struct AudioLevelData {
 struct ConnectionState {
     /// 当前 ASR 配置
     asr_config: Option<ASRConfig>,
-    /// 是否正在录音
-    is_recording: bool,
+    /// 连接专属引擎
+    engines: Option<ConnectionEngines>,
+    /// 录音阶段
+    phase: VoicePhase,
     /// 录音模式
     recording_mode: Option<RecordingMode>,
     /// 录音开始时间
@@ -104,29 +251,94 @@ struct ConnectionState {
     streaming_recorder: Option<StreamingRecorder>,
     /// 实时转录任务句柄
     realtime_task: Option<JoinHandle<RealtimeTaskResult>>,
+    /// 部分结果转发任务句柄 (Realtime 模式)
+    partial_forwarder: Option<JoinHandle<()>>,
     /// 停止信号发送器 (用于停止实时转录任务)
     stop_signal: Option<oneshot::Sender<()>>,
+    /// 切换识别语言请求发送器 (Realtime 模式录音中)
+    language_tx: Option<mpsc::UnboundedSender<String>>,
     /// 提示音播放器
     beep_player: BeepPlayer,
+    /// 本次录音期间服务器播放的提示音 (回声消除的参考信号)
+    echo_reference: Option<AudioData>,
     /// 音频级别发送器
     audio_level_tx: Option<mpsc::UnboundedSender<AudioLevelData>>,
+    /// 部分转录增量追踪器 (Realtime 模式)
+    delta_tracker: Arc<StdMutex<PartialDeltaTracker>>,
+    /// 连接用量与配额
+    usage: Arc<UsageMeter>,
+    /// 本会话的转录历史 (容量取自本连接的配置)
+    history: Arc<StdMutex<history::TranscriptionHistory>>,
+    /// 本次录音派生任务的取消令牌 (连接令牌的子令牌)
+    recording_token: Option<CancellationToken>,
+    /// 录音序号 (每次开始录音递增，用于丢弃过期的设备断开通知)
+    recording_id: u64,
 }
 
 impl ConnectionState {
     fn new() -> Self {
         Self {
             asr_config: None,
-            is_recording: false,
+            engines: None,
+            phase: VoicePhase::Idle,
             recording_mode: None,
             recording_start_time: None,
             recorder: None,
             streaming_recorder: None,
             realtime_task: None,
+            partial_forwarder: None,
             stop_signal: None,
+            language_tx: None,
             beep_player: BeepPlayer::new(),
+            echo_reference: None,
             audio_level_tx: None,
+            delta_tracker: Arc::new(StdMutex::new(PartialDeltaTracker::new())),
+            usage: Arc::new(UsageMeter::default()),
+            history: Arc::new(StdMutex::new(history::TranscriptionHistory::default())),
+            recording_token: None,
+            recording_id: 0,
         }
     }
+    
+    /// 确保连接专属引擎与配置一致，不一致时重建
+    fn ensure_engines(&mut self, config: &ASRConfig) -> Result<(), ASRError> {
+        let up_to_date = self.engines.as_ref().is_some_and(|e| e.config == *config);
+        if !up_to_date {
+            self.engines = Some(ConnectionEngines::build(config)?);
+            log_debug!("已按新配置重建连接专属引擎: primary={}", config.primary.provider);
+        }
+        self.asr_config = Some(config.clone());
+        if let Ok(mut history) = self.history.lock() {
+            history.set_capacity(config.history_capacity);
+        }
+        Ok(())
+    }
+    
+    /// 取消本次录音派生的任务 (电平推送、实时会话、转录)
+    fn cancel_recording_tasks(&mut self) {
+        if let Some(token) = self.recording_token.take() {
+            token.cancel();
+        }
+    }
+    
+    /// 释放残留的实时转录会话
+    fn release_realtime_session(&mut self) {
+        self.cancel_recording_tasks();
+        if let Some(stop_tx) = self.stop_signal.take() {
+            let _ = stop_tx.send(());
+        }
+        self.language_tx = None;
+        if let Some(task_handle) = self.realtime_task.take() {
+            task_handle.abort();
+        }
+        if let Some(forwarder) = self.partial_forwarder.take() {
+            forwarder.abort();
+        }
+        if let Some(ref mut streaming_recorder) = self.streaming_recorder {
+            streaming_recorder.cancel();
+        }
+        self.streaming_recorder = None;
+    }
 }
 
 // ============================================================================
@@ -141,25 +353,63 @@ pub struct VoiceHandler {
     state: TokioMutex<ConnectionState>,
     /// WebSocket 发送器
     ws_sender: TokioMutex<Option<WsSender>>,
+    /// 连接级取消令牌 (连接关闭时触发)
+    connection_token: TokioMutex<CancellationToken>,
+    /// 会话 ID (断线后凭此取回未送达的结果与会话状态，恢复成功后沿用旧会话的 ID)
+    session_id: StdMutex<String>,
+    /// 录音设备断开通知 (携带录音序号，由音频线程发送)
+    device_lost_tx: mpsc::UnboundedSender<u64>,
+    device_lost_rx: TokioMutex<mpsc::UnboundedReceiver<u64>>,
+    /// 离线队列的下次重试时间 (None 表示未安排)
+    offline_retry_at: StdMutex<Option<tokio::time::Instant>>,
+    /// 进行中的离线重试 (开始录音时取消，录音优先占用引擎)
+    offline_retry: StdMutex<Option<CancellationToken>>,
 }
 
 impl VoiceHandler {
     /// 创建新的 Voice 处理器
     pub fn new() -> Self {
+        let (device_lost_tx, device_lost_rx) = mpsc::unbounded_channel();
         Self {
             state: TokioMutex::new(ConnectionState::new()),
             ws_sender: TokioMutex::new(None),
+            connection_token: TokioMutex::new(CancellationToken::new()),
+            session_id: StdMutex::new(resume::generate_session_id()),
+            device_lost_tx,
+            device_lost_rx: TokioMutex::new(device_lost_rx),
+            offline_retry_at: StdMutex::new(None),
+            offline_retry: StdMutex::new(None),
         }
     }
     
+    /// 当前会话 ID
+    fn session_id(&self) -> String {
+        self.session_id.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+    
     /// 设置 WebSocket 发送器
     pub async fn set_ws_sender(&self, sender: WsSender) {
         let mut ws_sender = self.ws_sender.lock().await;
         *ws_sender = Some(sender);
     }
     
+    /// 设置连接级取消令牌
+    pub async fn set_connection_token(&self, token: CancellationToken) {
+        *self.connection_token.lock().await = token;
+    }
+    
+    /// 设置连接配额 (连接建立时调用，用量从零开始统计)
+    pub async fn set_usage_quota(&self, quota: UsageQuota) {
+        self.state.lock().await.usage = Arc::new(UsageMeter::new(quota));
+    }
+    
     /// 发送消息给客户端
     async fn send_message(&self, msg_type: &str, payload: serde_json::Value) -> Result<(), RouterError> {
+        self.try_send_message(msg_type, payload).await.map(|_| ())
+    }
+    
+    /// 发送消息给客户端，返回消息是否已写出 (连接已关闭时为 false)
+    async fn try_send_message(&self, msg_type: &str, payload: serde_json::Value) -> Result<bool, RouterError> {
         let ws_sender = self.ws_sender.lock().await;
         if let Some(ref sender) = *ws_sender {
             let response = serde_json::json!({
@@ -175,43 +425,89 @@ impl VoiceHandler {
                 }
             }
             
-            let json = serde_json::to_string(&response)
-                .map_err(|e| RouterError::ModuleError(format!("JSON 序列化失败: {}", e)))?;
-            
-            let mut sender = sender.lock().await;
-            sender.send(tokio_tungstenite::tungstenite::Message::Text(json.into())).await
+            let connection_token = self.connection_token.lock().await.clone();
+            if connection_token.is_cancelled() {
+                log_debug!("连接已关闭，丢弃消息: {}", msg_type);
+                return Ok(false);
+            }
+            let message = OutboundMessage {
+                payload: Payload::Value(serde_json::Value::Object(response)),
+                delivery: delivery_for(msg_type),
+            };
+            sender.enqueue(message).await
                 .map_err(|e| RouterError::ModuleError(format!("发送消息失败: {}", e)))?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// 配置启用时发送 play_sound，失败只记录日志 (提示音不影响录音流程)
+    async fn send_play_sound(&self, asr_config: &ASRConfig, sound: SoundKind) {
+        if !asr_config.play_sound_events {
+            return;
+        }
+        if let Err(e) = self.send_message("play_sound", serde_json::json!({ "sound": sound })).await {
+            log_error!("发送 play_sound 失败: {}", e);
         }
-        Ok(())
     }
 
     /// 处理开始录音命令
     async fn handle_start_recording(
         &self,
         mode: RecordingMode,
-        asr_config: ASRConfig,
+        asr_config: Option<ASRConfig>,
+        capture: CaptureRequest,
     ) -> Result<Option<ServerResponse>, RouterError> {
         log_info!("收到开始录音命令，模式: {:?}", mode);
         
         let mut state = self.state.lock().await;
         
-        // 检查是否已在录音
-        if state.is_recording {
-            return Err(RouterError::ModuleError("已在录音中".to_string()));
+        // 检查状态转换是否合法 (录音中或转录中均拒绝)
+        let next_phase = state.phase.transition(VoiceEvent::Start)?;
+        
+        // 后台的离线重试让出引擎，录音放回队首，之后再试
+        if let Some(retry_token) = self.offline_retry.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            log_info!("开始录音，中止进行中的离线重试");
+            retry_token.cancel();
         }
         
+        // 未携带配置时使用 update_config 设置的连接配置
+        let asr_config = asr_config
+            .or_else(|| state.asr_config.clone())
+            .ok_or_else(|| RouterError::ModuleError("ASR 配置未设置".to_string()))?;
+        state.ensure_engines(&asr_config)
+            .map_err(|e| RouterError::ModuleError(format!("创建 ASR 引擎失败: {}", e)))?;
+        let (primary_engine, primary_retry) = state.engines.as_ref()
+            .map(|e| (Arc::clone(&e.primary), e.retry.clone()))
+            .ok_or_else(|| RouterError::ModuleError("ASR 引擎未初始化".to_string()))?;
+        
+        // 超过连接配额时拒绝新的转录；录音器启动成功前只是预留，失败时归还
+        let usage_reservation = UsageMeter::reserve(&state.usage)?;
+        
+        // 本次录音派生的任务均监听此令牌，连接关闭或取消录音时一并停止
+        let recording_token = self.connection_token.lock().await.child_token();
+        state.recording_token = Some(recording_token.clone());
+        
         // 更新状态
-        state.asr_config = Some(asr_config.clone());
-        state.is_recording = true;
         state.recording_mode = Some(mode.clone());
         state.recording_start_time = Some(Instant::now());
+        state.recording_id += 1;
+        let device_lost_tx = self.device_lost_tx.clone();
+        let recording_id = state.recording_id;
+        let on_device_lost: audio::DeviceLostCallback = Arc::new(move || {
+            let _ = device_lost_tx.send(recording_id);
+        });
         
         // 创建音频级别 channel
         let (audio_level_tx, mut audio_level_rx) = mpsc::unbounded_channel::<AudioLevelData>();
         state.audio_level_tx = Some(audio_level_tx.clone());
+        let spectrum_bins = asr_config.spectrum_bins.filter(|&bins| bins > 0);
+        let pitch_tracking = asr_config.pitch_tracking;
         
         // 根据 ASR 模式选择录音器
         let is_realtime_mode = asr_config.primary.mode == ASRMode::Realtime;
+        let capture_params;
+        let recording_diagnostics;
         
         if is_realtime_mode {
             log_info!("使用 Realtime 模式，启动流式录音器");
@@ -222,36 +518,57 @@ impl VoiceHandler {
             
             // 设置音频级别回调
             let tx = audio_level_tx.clone();
-            streaming_recorder.set_level_callback(move |level, waveform| {
-                let _ = tx.send(AudioLevelData { level, waveform });
+            streaming_recorder.set_level_callback(move |level, waveform, stats, samples, sample_rate| {
+                let spectrum = spectrum_bins.map(|bins| audio::utils::compute_spectrum(samples, bins));
+                let pitch = pitch_tracking.then(|| audio::utils::estimate_pitch(samples, sample_rate));
+                let _ = tx.send(AudioLevelData { level, waveform, stats, spectrum, pitch });
             });
             
             // 启动流式录音，获取音频块接收通道
+            streaming_recorder.set_capture_request(capture);
+            streaming_recorder.set_downmix(asr_config.downmix);
+            streaming_recorder.set_wave_scale(asr_config.wave_scale);
+            streaming_recorder.set_device_lost_callback(on_device_lost);
             let chunk_rx = streaming_recorder.start_streaming(mode.clone().into())
                 .map_err(|e| RouterError::ModuleError(format!("启动流式录音失败: {}", e)))?;
+            capture_params = streaming_recorder.capture_params();
+            recording_diagnostics = streaming_recorder.diagnostics();
             
             // 创建实时转录任务
             let primary_config = asr_config.primary.clone();
             let ws_sender = self.ws_sender.lock().await.clone();
             
-            // 创建部分结果回调
-            let partial_callback: Option<Box<dyn Fn(&str) + Send + 'static>> = if let Some(sender) = ws_sender.clone() {
-                Some(Box::new(move |text: &str| {
-                    let text_owned = text.to_string();
-                    let sender = sender.clone();
-                    tokio::spawn(async move {
-                        let msg = serde_json::json!({
-                            "module": "voice",
-                            "type": "transcription_progress",
-                            "partial_text": text_owned,
-                        });
-                        let json = serde_json::to_string(&msg).unwrap();
-                        let mut s = sender.lock().await;
-                        let _ = s.send(tokio_tungstenite::tungstenite::Message::Text(json.into())).await;
-                    });
-                }))
+            // 配置了旁路时录音块先复制到旁路再送入转录
+            let chunk_rx = match start_audio_tee(&asr_config, ws_sender.clone(), &recording_token) {
+                Some(tee) => tee.tee_chunks(chunk_rx),
+                None => chunk_rx,
+            };
+            
+            // 噪声门在旁路之后，旁路仍收到完整录音
+            let noise_gate = asr_config.effective_noise_gate();
+            let chunk_rx = if noise_gate.enabled {
+                audio::NoiseGate::new(noise_gate).gate_chunks(chunk_rx)
             } else {
-                None
+                chunk_rx
+            };
+            
+            // 重置增量追踪器
+            if let Ok(mut tracker) = state.delta_tracker.lock() {
+                tracker.reset();
+            }
+            // 创建部分结果回调，部分结果经单个转发任务按序发送
+            let (partial_callback, partial_forwarder) = match ws_sender.clone() {
+                Some(sender) => {
+                    let stabilizer = asr::PartialStabilizer::new(asr_config.partial_stability);
+                    let (callback, forwarder) = partial_forwarder(
+                        sender,
+                        recording_token.clone(),
+                        Arc::clone(&state.delta_tracker),
+                        stabilizer,
+                    );
+                    (Some(callback), Some(forwarder))
+                }
+                None => (None, None),
             };
             
             // 创建实时转录任务
@@ -260,6 +577,14 @@ impl VoiceHandler {
                 chunk_rx,
                 partial_callback,
             );
+            let (language_tx, language_rx) = mpsc::unbounded_channel();
+            let task = task
+                .with_engine(primary_engine)
+                .with_retry_config(primary_retry)
+                .with_cancel_token(recording_token.clone())
+                .with_language_receiver(language_rx)
+                .with_keepalive(asr_config.keepalive)
+                .with_adaptive_mode(asr_config.adaptive_mode);
             
             // 启动实时转录任务
             let task_handle = tokio::spawn(async move {
@@ -268,7 +593,9 @@ impl VoiceHandler {
             
             state.streaming_recorder = Some(streaming_recorder);
             state.realtime_task = Some(task_handle);
+            state.partial_forwarder = partial_forwarder;
             state.stop_signal = Some(stop_tx);
+            state.language_tx = Some(language_tx);
             
         } else {
             log_info!("使用 HTTP 模式，启动普通录音器");
@@ -279,46 +606,197 @@ impl VoiceHandler {
             
             // 设置音频级别回调
             let tx = audio_level_tx.clone();
-            recorder.set_level_callback(move |level, waveform| {
-                let _ = tx.send(AudioLevelData { level, waveform });
+            recorder.set_level_callback(move |level, waveform, stats, samples, sample_rate| {
+                let spectrum = spectrum_bins.map(|bins| audio::utils::compute_spectrum(samples, bins));
+                let pitch = pitch_tracking.then(|| audio::utils::estimate_pitch(samples, sample_rate));
+                let _ = tx.send(AudioLevelData { level, waveform, stats, spectrum, pitch });
             });
             
+            // 边录边写 WAV，顺带恢复上次崩溃遗留的文件
+            if let Some(ref dir) = asr_config.recording_dir {
+                recorder.set_spool_path(prepare_recording_path(&crate::utils::temp_dir::resolve(dir)));
+            }
+            
+            let ws_sender = self.ws_sender.lock().await.clone();
+            recorder.set_tee(start_audio_tee(&asr_config, ws_sender, &recording_token));
+            
             // 启动录音
+            recorder.set_capture_request(capture);
+            recorder.set_downmix(asr_config.downmix);
+            recorder.set_wave_scale(asr_config.wave_scale);
+            recorder.set_device_lost_callback(on_device_lost);
             recorder.start(mode.clone().into())
                 .map_err(|e| RouterError::ModuleError(format!("启动录音失败: {}", e)))?;
+            capture_params = recorder.capture_params();
+            recording_diagnostics = recorder.diagnostics();
             
             state.recorder = Some(recorder);
         }
         
+        // 录音器启动成功后才进入录音阶段，并计入请求次数
+        state.phase = next_phase;
+        usage_reservation.commit();
+        
         // 播放开始提示音
         state.beep_player.play_start();
+        state.echo_reference = (asr_config.echo_cancel && state.beep_player.is_enabled())
+            .then(|| beep::render(BeepType::RecordingStart, state.beep_player.volume()));
+        
+        // 录音统计：字数仅实时模式可从 partial 估计
+        let recording_start = state.recording_start_time.unwrap_or_else(Instant::now);
+        let stats_tracker = is_realtime_mode.then(|| Arc::clone(&state.delta_tracker));
+        let mut level_monitor = LevelMonitor::new(asr_config.level_alert);
+        let mut speech_detector = SpeechDetector::new(asr_config.barge_in);
+        // 峰值保持随本次录音的转发任务创建，录音停止即丢弃，下次录音从零开始
+        let mut peak_hold = audio::utils::PeakHold::new(asr_config.peak_decay_per_sec);
+        let clipping_threshold = asr_config.clipping_threshold;
         
         drop(state);
         
-        // 启动音频级别转发任务
+        // 启动音频级别转发任务 (顺带周期性发送录音统计与电平告警)
         let ws_sender = self.ws_sender.lock().await.clone();
         if let Some(sender) = ws_sender {
+            let level_token = recording_token.clone();
             tokio::spawn(async move {
-                while let Some(data) = audio_level_rx.recv().await {
-                    let msg = serde_json::json!({
+                let mut last_stats: Option<Instant> = None;
+                // 采样率与削波警告每次录音各发送一次
+                let mut rate_warned = false;
+                let mut clipping_warned = false;
+                loop {
+                    let data = tokio::select! {
+                        _ = level_token.cancelled() => break,
+                        data = audio_level_rx.recv() => match data {
+                            Some(data) => data,
+                            None => break,
+                        },
+                    };
+                    let elapsed_ms = recording_start.elapsed().as_millis() as u64;
+                    let peak_levels = peak_hold.update(&data.waveform, elapsed_ms);
+                    let mut messages = vec![serde_json::json!({
                         "module": "voice",
                         "type": "audio_level",
                         "level": data.level,
                         "waveform": data.waveform,
-                    });
-                    let json = serde_json::to_string(&msg).unwrap();
-                    let mut s = sender.lock().await;
-                    if s.send(tokio_tungstenite::tungstenite::Message::Text(json.into())).await.is_err() {
-                        break;
+                        "peak_levels": peak_levels,
+                    })];
+                    if let Some(pitch) = data.pitch {
+                        messages[0]["pitch"] = serde_json::json!(pitch);
+                    }
+                    if let Some(spectrum) = data.spectrum {
+                        messages.push(serde_json::json!({
+                            "module": "voice",
+                            "type": "spectrum",
+                            "bins": spectrum,
+                        }));
+                    }
+                    
+                    if speech_detector.update(data.stats, elapsed_ms) {
+                        log_debug!("检测到用户开口: {}ms", elapsed_ms);
+                        messages.push(serde_json::json!({
+                            "module": "voice",
+                            "type": "speech_detected",
+                            "elapsed_ms": elapsed_ms,
+                        }));
+                    }
+                    if let Some(alert) = level_monitor.update(data.stats, elapsed_ms) {
+                        log_info!("输入电平告警: {}", alert.code());
+                        messages.push(serde_json::json!({
+                            "module": "voice",
+                            "type": "warning",
+                            "code": alert.code(),
+                            "message": alert.message(),
+                        }));
+                    }
+                    
+                    let (suspected_rate, clipping_ratio) = {
+                        let diagnostics = recording_diagnostics.lock().unwrap();
+                        let clipping = (diagnostics.duration_ms() >= CLIPPING_CHECK_MIN_MS)
+                            .then(|| diagnostics.clipping_ratio());
+                        (diagnostics.sample_rate_mismatch(), clipping)
+                    };
+                    if let Some(suspected) = suspected_rate.filter(|_| !rate_warned) {
+                        rate_warned = true;
+                        log_warn!(
+                            "疑似采样率不符: 声明 {}Hz, 推断 {}Hz",
+                            capture_params.sample_rate, suspected
+                        );
+                        messages.push(serde_json::json!({
+                            "module": "voice",
+                            "type": "warning",
+                            "code": "SAMPLE_RATE_MISMATCH",
+                            "message": format!(
+                                "音频采样率疑似不符: 声明 {}Hz，实际更像 {}Hz，识别结果可能不准确",
+                                capture_params.sample_rate, suspected
+                            ),
+                            "declared_sample_rate": capture_params.sample_rate,
+                            "suspected_sample_rate": suspected,
+                        }));
+                    }
+                    if let Some(ratio) = clipping_ratio.filter(|&ratio| !clipping_warned && ratio > clipping_threshold) {
+                        clipping_warned = true;
+                        log_warn!(
+                            "检测到削波: {:.2}% 超过阈值 {:.2}%",
+                            ratio * 100.0,
+                            clipping_threshold * 100.0
+                        );
+                        messages.push(serde_json::json!({
+                            "module": "voice",
+                            "type": "warning",
+                            "code": "CLIPPING",
+                            "message": "录音音量过大出现削波，可能影响识别效果，请调低麦克风音量",
+                            "clipping_ratio": ratio,
+                        }));
+                    }
+                    
+                    if last_stats.is_none_or(|t| t.elapsed() >= RECORDING_STATS_INTERVAL) {
+                        last_stats = Some(Instant::now());
+                        let mut stats = serde_json::json!({
+                            "module": "voice",
+                            "type": "recording_stats",
+                            "elapsed_ms": elapsed_ms,
+                        });
+                        if let Some(chars) = stats_tracker.as_ref()
+                            .and_then(|tracker| tracker.lock().ok().map(|t| t.estimated_chars()))
+                        {
+                            stats["estimated_chars"] = serde_json::json!(chars);
+                        }
+                        messages.push(stats);
+                    }
+                    
+                    for msg in messages {
+                        if !send_json(&sender, &level_token, msg).await {
+                            return;
+                        }
                     }
                 }
             });
         }
         
-        // 发送录音开始状态
+        // 设备不支持请求的参数时已选用最接近的配置
+        if !capture.is_satisfied_by(capture_params) {
+            log_info!("请求的采集参数 {:?} 不受支持，实际使用 {:?}", capture, capture_params);
+            self.send_message("warning", serde_json::json!({
+                "code": "CAPTURE_PARAMS_ADJUSTED",
+                "message": format!(
+                    "录音设备不支持请求的参数，已使用 {}Hz / {} 声道",
+                    capture_params.sample_rate, capture_params.channels
+                ),
+                "requested": {
+                    "sample_rate": capture.sample_rate,
+                    "channels": capture.channels,
+                },
+                "actual": capture_params,
+            })).await?;
+        }
+        
+        // 发送录音开始状态 (附带设备实际使用的音频参数)
         self.send_message("recording_state", serde_json::json!({
-            "state": "started"
+            "state": "started",
+            "sample_rate": capture_params.sample_rate,
+            "channels": capture_params.channels,
+            "session_id": self.session_id(),
         })).await?;
+        self.send_play_sound(&asr_config, SoundKind::Start).await;
         
         Ok(None)
     }
@@ -327,22 +805,268 @@ impl VoiceHandler {
     async fn handle_stop_recording(&self) -> Result<Option<ServerResponse>, RouterError> {
         log_info!("收到停止录音命令");
         
+        {
+            let mut state = self.state.lock().await;
+            state.phase = state.phase.transition(VoiceEvent::Stop)?;
+        }
+        
+        let result = self.stop_and_transcribe().await;
+        
+        // 无论转录成功与否都回到空闲 (连接清理可能已提前重置)
+        let mut state = self.state.lock().await;
+        if let Ok(next_phase) = state.phase.transition(VoiceEvent::Finish) {
+            state.phase = next_phase;
+        }
+        
+        result
+    }
+
+    /// 等待录音设备断开通知，返回断开时的录音序号
+    ///
+    /// 由连接的消息循环与客户端消息一起监听 (可安全取消)
+    pub async fn device_lost(&self) -> u64 {
+        match self.device_lost_rx.lock().await.recv().await {
+            Some(recording_id) => recording_id,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// 处理录音设备断开：停止录音、转录已录制的音频并发送 DEVICE_LOST 错误
+    ///
+    /// 下次录音会重新打开系统默认输入设备，错误中附带当前可用的默认设备名
+    pub async fn handle_device_lost(&self, recording_id: u64) {
+        {
+            let mut state = self.state.lock().await;
+            if state.recording_id != recording_id || !state.phase.is_recording() {
+                return;
+            }
+            match state.phase.transition(VoiceEvent::Stop) {
+                Ok(next_phase) => state.phase = next_phase,
+                Err(_) => return,
+            }
+        }
+        
+        let fallback_device = audio::default_input_device_name();
+        log_error!("录音设备已断开，停止录音 (默认设备: {:?})", fallback_device);
+        if let Err(e) = self.send_message("error", serde_json::json!({
+            "code": "DEVICE_LOST",
+            "message": "录音设备已断开，已停止录音并保留已录制的音频",
+            "fallback_device": fallback_device,
+        })).await {
+            log_error!("发送 DEVICE_LOST 失败: {}", e);
+        }
+        
+        if let Err(e) = self.stop_and_transcribe().await {
+            log_error!("设备断开后处理已录音频失败: {}", e);
+        }
+        
         let mut state = self.state.lock().await;
+        if let Ok(next_phase) = state.phase.transition(VoiceEvent::Finish) {
+            state.phase = next_phase;
+        }
+    }
+
+    /// 等待离线队列的下次重试时间 (未安排重试时一直挂起)
+    ///
+    /// 由连接的消息循环与客户端消息一起监听 (可安全取消)，到期时清除安排，
+    /// 后台重试尚未开始时不会重复触发
+    pub async fn offline_retry_due(&self) {
+        let deadline = *self.offline_retry_at.lock().unwrap_or_else(|e| e.into_inner());
+        match deadline {
+            Some(deadline) => tokio::time::sleep_until(deadline).await,
+            None => std::future::pending().await,
+        }
+        *self.offline_retry_at.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// 队列中有待转录的录音时，在 delay 后安排重试 (已安排更早的重试时保持不变)
+    fn schedule_offline_retry(&self, asr_config: &ASRConfig, delay: Duration) {
+        let has_pending = offline_queue::for_config(&asr_config.offline_queue)
+            .is_some_and(|queue| queue.lock().is_ok_and(|queue| queue.has_pending()));
+        if !has_pending {
+            return;
+        }
+        let deadline = tokio::time::Instant::now() + delay;
+        let mut retry_at = self.offline_retry_at.lock().unwrap_or_else(|e| e.into_inner());
+        if retry_at.is_none_or(|current| deadline < current) {
+            *retry_at = Some(deadline);
+        }
+    }
+
+    /// 重新转录离线队列中最早的一条录音
+    ///
+    /// 成功时发送带 `offline_id` 的延迟 transcription_complete 并立即处理下一条；
+    /// 网络仍不可用时放回队首，按 retry_interval_ms 再试；其它错误时丢弃该条并发送 error。
+    /// 耗时取决于网络，由连接在后台任务中调用；进行中时开始录音会中止本次重试
+    pub async fn retry_offline_queue(&self) {
+        *self.offline_retry_at.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        
+        let connection_token = self.connection_token.lock().await.clone();
+        let (asr_config, engines, retry_token) = {
+            let state = self.state.lock().await;
+            let (Some(asr_config), Some(engines)) = (state.asr_config.clone(), state.engines.clone()) else {
+                return;
+            };
+            // 录音或转录中不占用引擎
+            if state.phase != VoicePhase::Idle {
+                let retry_interval = Duration::from_millis(asr_config.offline_queue.retry_interval_ms);
+                self.schedule_offline_retry(&asr_config, retry_interval);
+                return;
+            }
+            // 持有状态锁时登记，开始录音必然能看到并取消本次重试
+            let mut running = self.offline_retry.lock().unwrap_or_else(|e| e.into_inner());
+            if running.is_some() {
+                return;
+            }
+            let retry_token = connection_token.child_token();
+            *running = Some(retry_token.clone());
+            (asr_config, engines, retry_token)
+        };
+        
+        self.retry_next_offline(&asr_config, &engines, &retry_token, &connection_token).await;
+        
+        // 被录音中止时登记已由开始录音取走
+        if !retry_token.is_cancelled() {
+            *self.offline_retry.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        }
+    }
+    
+    async fn retry_next_offline(
+        &self,
+        asr_config: &ASRConfig,
+        engines: &ConnectionEngines,
+        retry_token: &CancellationToken,
+        connection_token: &CancellationToken,
+    ) {
+        let Some(queue) = offline_queue::for_config(&asr_config.offline_queue) else {
+            return;
+        };
+        let retry_interval = Duration::from_millis(asr_config.offline_queue.retry_interval_ms);
+        let Some(item) = queue.lock().ok().and_then(|mut queue| queue.claim()) else {
+            return;
+        };
+        
+        let audio_data = match audio::read_wav(&item.path) {
+            Ok(audio_data) => audio_data,
+            Err(e) => {
+                log_error!("离线队列录音无法读取，已丢弃: {}: {}", item.path.display(), e);
+                if let Ok(mut queue) = queue.lock() {
+                    queue.complete(&item);
+                }
+                self.schedule_offline_retry(asr_config, Duration::ZERO);
+                return;
+            }
+        };
+        
+        log_info!("重新转录离线录音: id={}, 时长 {}ms", item.id, audio_data.duration_ms);
+        let encoding_ms = encode_ahead(&audio_data);
+        match until_cancelled(retry_token, perform_transcription(&audio_data, engines, None)).await {
+            Ok(result) => {
+                if let Ok(mut queue) = queue.lock() {
+                    queue.complete(&item);
+                }
+                let timings = Timings {
+                    recording_ms: audio_data.duration_ms,
+                    encoding_ms,
+                    network_ms: result.timings.network_ms,
+                    ..Timings::default()
+                };
+                if let Err(e) = self.deliver_transcription(&result, timings, asr_config, Some(&item)).await {
+                    log_error!("发送离线转录结果失败: {}", e);
+                }
+                self.schedule_offline_retry(asr_config, Duration::ZERO);
+            }
+            Err(e) if e.is_network() || matches!(e, ASRError::Cancelled) => {
+                log_info!("离线重试未完成 (网络不可用或被录音中止)，{}ms 后再试: {}", retry_interval.as_millis(), e);
+                if let Ok(mut queue) = queue.lock() {
+                    queue.release(item);
+                }
+                if !connection_token.is_cancelled() {
+                    self.schedule_offline_retry(asr_config, retry_interval);
+                }
+            }
+            Err(e) => {
+                log_error!("离线录音转录失败，已丢弃: id={}: {}", item.id, e);
+                if let Ok(mut queue) = queue.lock() {
+                    queue.complete(&item);
+                }
+                let mut error = transcription_error(e.to_string(), &e);
+                error["offline_id"] = serde_json::json!(item.id);
+                if let Err(e) = self.send_message("error", error).await {
+                    log_error!("发送离线转录错误失败: {}", e);
+                }
+                self.schedule_offline_retry(asr_config, Duration::ZERO);
+            }
+        }
+    }
+
+    /// 网络不可用导致转录失败时，把录音放入离线队列并发送 QUEUED_OFFLINE 警告
+    ///
+    /// 返回是否已入队 (未启用队列、非网络错误或入队失败时返回 false，由调用方照常报错)
+    async fn queue_offline(&self, audio_data: &AudioData, asr_config: &ASRConfig, error: &ASRError) -> bool {
+        if !error.is_network() || audio_data.is_empty() {
+            return false;
+        }
+        let Some(queue) = offline_queue::for_config(&asr_config.offline_queue) else {
+            return false;
+        };
+        let queued = queue.lock()
+            .map_err(|_| "离线队列不可用".to_string())
+            .and_then(|mut queue| {
+                let item = queue.enqueue(audio_data).map_err(|e| e.to_string())?;
+                Ok((item, queue.len()))
+            });
         
-        // 检查是否在录音
-        if !state.is_recording {
-            return Err(RouterError::ModuleError("未在录音中".to_string()));
+        match queued {
+            Ok((item, pending)) => {
+                log_info!("网络不可用，录音已加入离线队列: id={}, 队列 {} 条", item.id, pending);
+                if let Err(e) = self.send_message("warning", serde_json::json!({
+                    "code": "QUEUED_OFFLINE",
+                    "message": "网络不可用，录音已保存到离线队列，网络恢复后自动转录",
+                    "offline_id": item.id,
+                    "pending": pending,
+                })).await {
+                    log_error!("发送 QUEUED_OFFLINE 失败: {}", e);
+                }
+                let retry_interval = Duration::from_millis(asr_config.offline_queue.retry_interval_ms);
+                self.schedule_offline_retry(asr_config, retry_interval);
+                true
+            }
+            Err(e) => {
+                log_error!("录音加入离线队列失败: {}", e);
+                if let Err(e) = self.send_message("warning", serde_json::json!({
+                    "code": "OFFLINE_QUEUE_FAILED",
+                    "message": format!("录音未能加入离线队列: {}", e),
+                })).await {
+                    log_error!("发送 OFFLINE_QUEUE_FAILED 失败: {}", e);
+                }
+                false
+            }
         }
+    }
+
+    /// 停止录音并执行转录 (调用方负责阶段转换)
+    async fn stop_and_transcribe(&self) -> Result<Option<ServerResponse>, RouterError> {
+        let mut state = self.state.lock().await;
         
         // 播放结束提示音
         state.beep_player.play_stop();
+        let echo_reference = state.echo_reference.take();
         
         // 关闭音频级别 channel
         state.audio_level_tx = None;
         
-        // 获取 ASR 配置
+        // 实际录音时长 (用于校验采样率与耗时统计)
+        let wall_clock_ms = state.recording_start_time.take()
+            .map(|t| t.elapsed().as_millis() as u64);
+        let recording_ms = wall_clock_ms.unwrap_or(0);
+        
+        // 获取 ASR 配置和连接专属引擎
         let asr_config = state.asr_config.clone()
             .ok_or_else(|| RouterError::ModuleError("ASR 配置未设置".to_string()))?;
+        let engines = state.engines.clone()
+            .ok_or_else(|| RouterError::ModuleError("ASR 引擎未初始化".to_string()))?;
+        let cancel_token = state.recording_token.clone().unwrap_or_default();
         
         // 检查是否是 realtime 模式
         let is_realtime_mode = state.streaming_recorder.is_some();
@@ -355,20 +1079,22 @@ impl VoiceHandler {
             if let Some(stop_tx) = state.stop_signal.take() {
                 let _ = stop_tx.send(());
             }
+            state.language_tx = None;
             
             // 停止流式录音并获取完整音频数据 (用于回退)
-            let audio_data = if let Some(ref mut streaming_recorder) = state.streaming_recorder {
-                streaming_recorder.stop_streaming()
-                    .map_err(|e| RouterError::ModuleError(format!("停止流式录音失败: {}", e)))?
+            let (audio_data, device_clock) = if let Some(ref mut streaming_recorder) = state.streaming_recorder {
+                let audio_data = streaming_recorder.stop_streaming()
+                    .map_err(|e| RouterError::ModuleError(format!("停止流式录音失败: {}", e)))?;
+                (audio_data, streaming_recorder.device_clock())
             } else {
                 return Err(RouterError::ModuleError("流式录音器未初始化".to_string()));
             };
             
-            // 获取实时转录任务句柄
+            // 获取实时转录任务与部分结果转发任务句柄
             let realtime_task = state.realtime_task.take();
+            let partial_forwarder = state.partial_forwarder.take();
             
             // 更新状态
-            state.is_recording = false;
             state.recording_mode = None;
             state.streaming_recorder = None;
             drop(state);
@@ -377,8 +1103,15 @@ impl VoiceHandler {
             self.send_message("recording_state", serde_json::json!({
                 "state": "stopped"
             })).await?;
+            self.send_play_sound(&asr_config, SoundKind::Stop).await;
+            
+            // 整段音频诊断 (针对原始录音)，之后再做预处理
+            log_diagnostics(&audio_data, wall_clock_ms, device_clock);
+            let audio_data = cancel_echo(audio_data, echo_reference.as_ref());
+            let audio_data = preprocess_audio(audio_data, &asr_config);
             
-            // 等待实时转录任务完成
+            // 等待实时转录任务完成 (停止后到最终结果的耗时计入网络与推理)
+            let wait_start = Instant::now();
             let realtime_result = if let Some(task_handle) = realtime_task {
                 log_info!("等待实时转录任务完成...");
                 match task_handle.await {
@@ -393,6 +1126,21 @@ impl VoiceHandler {
                 None
             };
             
+            // 任务结束后回调随之释放，等已排队的部分结果发完，保证其在最终结果之前
+            if let Some(forwarder) = partial_forwarder {
+                if tokio::time::timeout(PARTIAL_FLUSH_TIMEOUT, forwarder).await.is_err() {
+                    log_error!("部分结果转发未在 {}ms 内结束", PARTIAL_FLUSH_TIMEOUT.as_millis());
+                }
+            }
+            
+            let wait_ms = wait_start.elapsed().as_millis() as u64;
+            
+            // 连接已关闭时不再回退转录
+            if cancel_token.is_cancelled() {
+                log_info!("录音任务已取消，放弃转录结果");
+                return Ok(None);
+            }
+            
             // 处理实时转录结果
             match realtime_result {
                 Some(RealtimeTaskResult::Success(result)) => {
@@ -403,18 +1151,72 @@ impl VoiceHandler {
                         &result.text
                     );
                     
-                    self.send_message("transcription_complete", serde_json::json!({
-                        "text": result.text,
-                        "engine": result.engine,
-                        "used_fallback": false,
-                        "duration_ms": result.duration_ms,
+                    let timings = Timings {
+                        recording_ms,
+                        network_ms: wait_ms,
+                        ..Timings::default()
+                    };
+                    self.send_transcription_complete(&result, timings, &asr_config).await?;
+                }
+                Some(RealtimeTaskResult::Partial { result, timeout_ms }) => {
+                    log_info!(
+                        "实时转录最终结果超时 ({}ms)，使用部分结果: engine={}, text={}",
+                        timeout_ms,
+                        result.engine,
+                        &result.text
+                    );
+                    
+                    self.send_message("warning", serde_json::json!({
+                        "code": "FINAL_RESULT_TIMEOUT",
+                        "message": format!("等待最终识别结果超时 ({}ms)，已返回部分结果", timeout_ms),
+                        "timeout_ms": timeout_ms,
                     })).await?;
+                    
+                    let timings = Timings {
+                        recording_ms,
+                        network_ms: wait_ms,
+                        ..Timings::default()
+                    };
+                    self.send_transcription_complete(&result, timings, &asr_config).await?;
+                }
+                Some(RealtimeTaskResult::ShortAudio) => {
+                    log_info!("短录音未建立实时会话，使用 HTTP 模式转录: {}ms", audio_data.duration_ms);
+                    
+                    let encoding_ms = encode_ahead(&audio_data);
+                    let short_result = until_cancelled(
+                        &cancel_token,
+                        perform_short_transcription(&audio_data, &asr_config, &engines),
+                    ).await;
+                    
+                    match short_result {
+                        Ok(result) => {
+                            let timings = Timings {
+                                recording_ms,
+                                encoding_ms,
+                                network_ms: wait_ms + result.timings.network_ms,
+                                ..Timings::default()
+                            };
+                            self.send_transcription_complete(&result, timings, &asr_config).await?;
+                        }
+                        Err(e) => {
+                            log_error!("HTTP 模式转录失败: {}", e);
+                            
+                            if !self.queue_offline(&audio_data, &asr_config, &e).await {
+                                self.send_message("error", transcription_error(e.to_string(), &e)).await?;
+                                self.send_play_sound(&asr_config, SoundKind::Error).await;
+                            }
+                        }
+                    }
                 }
                 Some(RealtimeTaskResult::Failed { error, engine_name, .. }) => {
                     log_error!("实时转录失败 ({}): {}，尝试回退到 HTTP 模式", engine_name, error);
                     
                     // 回退到 HTTP 模式
-                    let fallback_result = perform_fallback_transcription(&audio_data, &asr_config).await;
+                    let encoding_ms = encode_ahead(&audio_data);
+                    let fallback_result = until_cancelled(
+                        &cancel_token,
+                        perform_fallback_transcription(&audio_data, &asr_config, &engines),
+                    ).await;
                     
                     match fallback_result {
                         Ok(result) => {
@@ -425,23 +1227,24 @@ impl VoiceHandler {
                                 &result.text
                             );
                             
-                            self.send_message("transcription_complete", serde_json::json!({
-                                "text": result.text,
-                                "engine": result.engine,
-                                "used_fallback": true,
-                                "duration_ms": result.duration_ms,
-                            })).await?;
+                            let timings = Timings {
+                                recording_ms,
+                                encoding_ms,
+                                network_ms: wait_ms + result.timings.network_ms,
+                                ..Timings::default()
+                            };
+                            self.send_transcription_complete(&result, timings, &asr_config).await?;
                         }
                         Err(fallback_error) => {
                             log_error!("HTTP 回退也失败: {}", fallback_error);
                             
-                            self.send_message("error", serde_json::json!({
-                                "code": "TRANSCRIPTION_FAILED",
-                                "message": format!(
-                                    "实时转录失败: {}; HTTP 回退也失败: {}",
-                                    error, fallback_error
-                                ),
-                            })).await?;
+                            if !self.queue_offline(&audio_data, &asr_config, &fallback_error).await {
+                                self.send_message("error", transcription_error(
+                                    format!("实时转录失败: {}; HTTP 回退也失败: {}", error, fallback_error),
+                                    &fallback_error,
+                                )).await?;
+                                self.send_play_sound(&asr_config, SoundKind::Error).await;
+                            }
                         }
                     }
                 }
@@ -449,7 +1252,11 @@ impl VoiceHandler {
                     log_error!("实时转录任务异常，尝试回退到 HTTP 模式");
                     
                     // 回退到 HTTP 模式
-                    let fallback_result = perform_fallback_transcription(&audio_data, &asr_config).await;
+                    let encoding_ms = encode_ahead(&audio_data);
+                    let fallback_result = until_cancelled(
+                        &cancel_token,
+                        perform_fallback_transcription(&audio_data, &asr_config, &engines),
+                    ).await;
                     
                     match fallback_result {
                         Ok(result) => {
@@ -460,23 +1267,24 @@ impl VoiceHandler {
                                 &result.text
                             );
                             
-                            self.send_message("transcription_complete", serde_json::json!({
-                                "text": result.text,
-                                "engine": result.engine,
-                                "used_fallback": true,
-                                "duration_ms": result.duration_ms,
-                            })).await?;
+                            let timings = Timings {
+                                recording_ms,
+                                encoding_ms,
+                                network_ms: wait_ms + result.timings.network_ms,
+                                ..Timings::default()
+                            };
+                            self.send_transcription_complete(&result, timings, &asr_config).await?;
                         }
                         Err(fallback_error) => {
                             log_error!("HTTP 回退也失败: {}", fallback_error);
                             
-                            self.send_message("error", serde_json::json!({
-                                "code": "TRANSCRIPTION_FAILED",
-                                "message": format!(
-                                    "实时转录任务异常; HTTP 回退也失败: {}",
-                                    fallback_error
-                                ),
-                            })).await?;
+                            if !self.queue_offline(&audio_data, &asr_config, &fallback_error).await {
+                                self.send_message("error", transcription_error(
+                                    format!("实时转录任务异常; HTTP 回退也失败: {}", fallback_error),
+                                    &fallback_error,
+                                )).await?;
+                                self.send_play_sound(&asr_config, SoundKind::Error).await;
+                            }
                         }
                     }
                 }
@@ -486,14 +1294,14 @@ impl VoiceHandler {
             log_info!("停止 HTTP 模式录音");
             
             // 停止录音并获取音频数据
-            let audio_data = if let Some(ref mut recorder) = state.recorder {
-                recorder.stop().map_err(|e| RouterError::ModuleError(format!("停止录音失败: {}", e)))?
+            let (audio_data, device_clock) = if let Some(ref mut recorder) = state.recorder {
+                let audio_data = recorder.stop().map_err(|e| RouterError::ModuleError(format!("停止录音失败: {}", e)))?;
+                (audio_data, recorder.device_clock())
             } else {
                 return Err(RouterError::ModuleError("录音器未初始化".to_string()));
             };
             
             // 更新状态
-            state.is_recording = false;
             state.recording_mode = None;
             state.recorder = None;
             drop(state);
@@ -502,23 +1310,41 @@ impl VoiceHandler {
             self.send_message("recording_state", serde_json::json!({
                 "state": "stopped"
             })).await?;
+            self.send_play_sound(&asr_config, SoundKind::Stop).await;
+            
+            // 整段音频诊断 (针对原始录音)，之后再做预处理
+            log_diagnostics(&audio_data, wall_clock_ms, device_clock);
+            let audio_data = cancel_echo(audio_data, echo_reference.as_ref());
+            let audio_data = preprocess_audio(audio_data, &asr_config);
             
             // 检查音频数据是否为空
             if audio_data.is_empty() {
                 log_info!("录音数据为空，跳过转录");
-                self.send_message("transcription_complete", serde_json::json!({
-                    "text": "",
-                    "engine": "none",
-                    "used_fallback": false,
-                    "duration_ms": 0,
-                })).await?;
+                let empty = TranscriptionResult::new(String::new(), "none".to_string(), false, 0);
+                let timings = Timings {
+                    recording_ms,
+                    ..Timings::default()
+                };
+                self.send_transcription_complete(&empty, timings, &asr_config).await?;
                 return Ok(None);
             }
             
             log_info!("开始 ASR 转录，音频时长: {}ms", audio_data.duration_ms);
             
-            // 执行 ASR 转录
-            let transcription_result = perform_transcription(&audio_data, &asr_config).await;
+            // 执行 ASR 转录 (先编码，引擎复用编码结果)
+            let encoding_ms = encode_ahead(&audio_data);
+            let (progress, forwarder) = match self.ws_sender.lock().await.clone() {
+                Some(sender) => {
+                    let (progress, forwarder) = progress_forwarder(sender, cancel_token.clone());
+                    (Some(progress), Some(forwarder))
+                }
+                None => (None, None),
+            };
+            let transcription_result = until_cancelled(&cancel_token, perform_transcription(&audio_data, &engines, progress)).await;
+            // 转录结束后回调已释放，等待剩余进度发出，保证先于完成消息
+            if let Some(forwarder) = forwarder {
+                let _ = forwarder.await;
+            }
             
             match transcription_result {
                 Ok(result) => {
@@ -530,20 +1356,21 @@ impl VoiceHandler {
                         &result.text
                     );
                     
-                    self.send_message("transcription_complete", serde_json::json!({
-                        "text": result.text,
-                        "engine": result.engine,
-                        "used_fallback": result.used_fallback,
-                        "duration_ms": result.duration_ms,
-                    })).await?;
+                    let timings = Timings {
+                        recording_ms,
+                        encoding_ms,
+                        network_ms: result.timings.network_ms,
+                        ..Timings::default()
+                    };
+                    self.send_transcription_complete(&result, timings, &asr_config).await?;
                 }
                 Err(e) => {
-                    log_error!("转录失败: {}", e);
+                    log_error!("转录失败: {}", e.detailed());
                     
-                    self.send_message("error", serde_json::json!({
-                        "code": "TRANSCRIPTION_FAILED",
-                        "message": e.to_string(),
-                    })).await?;
+                    if !self.queue_offline(&audio_data, &asr_config, &e).await {
+                        self.send_message("error", transcription_error(e.to_string(), &e)).await?;
+                        self.send_play_sound(&asr_config, SoundKind::Error).await;
+                    }
                 }
             }
         }
@@ -551,19 +1378,102 @@ impl VoiceHandler {
         Ok(None)
     }
 
+    /// 发送转录完成消息
+    ///
+    /// 按配置格式化输出文本，并附带相对最后一次 partial 的增量与各阶段耗时
+    async fn send_transcription_complete(
+        &self,
+        result: &TranscriptionResult,
+        timings: Timings,
+        asr_config: &ASRConfig,
+    ) -> Result<(), RouterError> {
+        let sent = self.deliver_transcription(result, timings, asr_config, None).await;
+        
+        // 转录成功说明网络可用，补发离线队列中的录音
+        if !result.text.is_empty() {
+            self.schedule_offline_retry(asr_config, Duration::ZERO);
+        }
+        sent
+    }
+
+    /// 发送转录完成消息，离线队列补发的结果附带 `offline_id`、`queued_at` 与 `deferred`
+    async fn deliver_transcription(
+        &self,
+        result: &TranscriptionResult,
+        timings: Timings,
+        asr_config: &ASRConfig,
+        queued: Option<&offline_queue::QueuedItem>,
+    ) -> Result<(), RouterError> {
+        let (result, text, format) = finalize_result(result, timings, asr_config).await;
+        
+        let (delta_tracker, usage, history) = {
+            let state = self.state.lock().await;
+            (Arc::clone(&state.delta_tracker), Arc::clone(&state.usage), Arc::clone(&state.history))
+        };
+        
+        // 计费按录音时长与输出字数统计
+        usage.record(result.timings.recording_ms, asr::delta::estimate_chars(&result.text) as u64);
+        
+        let mut message = completion_payload(&result, text.clone(), format, asr_config);
+        let command = message.get("command").cloned();
+        
+        // 最终文本相对最后一次 partial 的增量，None 表示需整段替换
+        let final_text = message["text"].as_str().unwrap_or_default();
+        let delta = delta_tracker.lock()
+            .ok()
+            .and_then(|mut tracker| tracker.finish(final_text));
+        message["delta"] = serde_json::json!(delta);
+        if let Some(item) = queued {
+            message["offline_id"] = serde_json::json!(item.id);
+            message["queued_at"] = serde_json::json!(item.queued_at);
+            message["deferred"] = serde_json::json!(true);
+        }
+        
+        if !text.is_empty() && command.is_none() {
+            // 客户端凭 request_id 回传评分与修正
+            if let Ok(mut feedback) = feedback::global().lock() {
+                message["request_id"] = serde_json::json!(feedback.register(&result));
+            }
+            transcript_log::append(asr_config, &result);
+            if let Ok(mut history) = history.lock() {
+                history.push(history::HistoryItem::new(&result, text, format));
+            }
+        }
+        
+        // 语音命令先于转录结果发送，前端据此执行编辑动作；
+        // 未送达时随 transcription_complete 的 command 字段一并暂存
+        if let Some(command) = command {
+            log_info!("识别到语音命令: {}", command);
+            if let Err(e) = self.send_message("command", command).await {
+                log_error!("发送语音命令失败: {}", e);
+            }
+        }
+        
+        webhook::notify(asr_config, &message);
+        obsidian::insert(asr_config, &message);
+        
+        // 连接已断开时暂存结果，客户端重连后可通过 resume_session 取回
+        let sent = self.try_send_message("transcription_complete", message.clone()).await;
+        if !matches!(sent, Ok(true)) {
+            let session_id = self.session_id();
+            log_info!("转录结果未送达，已暂存: session_id={}", session_id);
+            resume::stash(&session_id, message);
+        }
+        sent.map(|_| ())
+    }
+
     /// 处理取消录音命令
     async fn handle_cancel_recording(&self) -> Result<Option<ServerResponse>, RouterError> {
         log_info!("收到取消录音命令");
         
         let mut state = self.state.lock().await;
         
-        // 检查是否在录音
-        if !state.is_recording {
-            return Err(RouterError::ModuleError("未在录音中".to_string()));
-        }
+        // 检查状态转换是否合法
+        let next_phase = state.phase.transition(VoiceEvent::Cancel)?;
         
-        // 关闭音频级别 channel
+        // 关闭音频级别 channel，并停止本次录音派生的任务
         state.audio_level_tx = None;
+        state.cancel_recording_tasks();
         
         // 检查是否是 realtime 模式
         let is_realtime_mode = state.streaming_recorder.is_some();
@@ -583,11 +1493,15 @@ impl VoiceHandler {
             if let Some(task_handle) = state.realtime_task.take() {
                 task_handle.abort();
             }
+            if let Some(forwarder) = state.partial_forwarder.take() {
+                forwarder.abort();
+            }
             
             // 更新状态
             state.streaming_recorder = None;
             state.realtime_task = None;
             state.stop_signal = None;
+            state.language_tx = None;
         } else {
             // 取消普通录音
             if let Some(ref mut recorder) = state.recorder {
@@ -597,14 +1511,36 @@ impl VoiceHandler {
         }
         
         // 更新状态
-        state.is_recording = false;
+        state.phase = next_phase;
         state.recording_mode = None;
+        let asr_config = state.asr_config.clone();
         drop(state);
         
         // 发送录音取消状态
         self.send_message("recording_state", serde_json::json!({
             "state": "cancelled"
         })).await?;
+        if let Some(ref asr_config) = asr_config {
+            self.send_play_sound(asr_config, SoundKind::Cancel).await;
+        }
+        
+        Ok(None)
+    }
+    
+    /// 处理切换识别语言命令 (仅 Realtime 模式录音中)
+    ///
+    /// 支持的引擎立即更新会话配置，其余引擎在下一分句边界重建会话，已说内容保留
+    async fn handle_set_language(&self, language: String) -> Result<Option<ServerResponse>, RouterError> {
+        log_info!("收到切换识别语言命令: {}", language);
+        
+        let state = self.state.lock().await;
+        if !state.phase.is_recording() {
+            return Err(TransitionError::NotRecording.into());
+        }
+        let language_tx = state.language_tx.as_ref()
+            .ok_or_else(|| RouterError::ModuleError("仅 Realtime 模式支持录音中切换语言".to_string()))?;
+        language_tx.send(language)
+            .map_err(|_| RouterError::ModuleError("实时转录任务已结束".to_string()))?;
         
         Ok(None)
     }
@@ -614,9 +1550,148 @@ impl VoiceHandler {
         log_info!("收到更新配置命令");
         
         let mut state = self.state.lock().await;
-        state.asr_config = Some(asr_config);
+        
+        // 录音或转录中不允许切换引擎
+        state.phase.transition(VoiceEvent::UpdateConfig)?;
+        
+        // 先释放旧的实时会话，再替换引擎实例
+        state.release_realtime_session();
+        state.ensure_engines(&asr_config)
+            .map_err(|e| RouterError::ModuleError(format!("创建 ASR 引擎失败: {}", e)))?;
+        drop(state);
         
         log_debug!("ASR 配置已更新");
+        // 恢复上次未完成的离线录音
+        self.schedule_offline_retry(&asr_config, Duration::ZERO);
+        
+        Ok(None)
+    }
+    
+    /// 处理查询转录历史命令
+    async fn handle_get_history(&self, limit: Option<usize>) -> Result<Option<ServerResponse>, RouterError> {
+        let history = Arc::clone(&self.state.lock().await.history);
+        let items = history.lock()
+            .map(|history| history.recent(limit))
+            .unwrap_or_default();
+        log_debug!("返回转录历史: {} 条", items.len());
+        
+        self.send_message("history", serde_json::json!({
+            "items": items,
+        })).await?;
+        
+        Ok(None)
+    }
+    
+    /// 处理查询用量命令
+    async fn handle_get_usage(&self) -> Result<Option<ServerResponse>, RouterError> {
+        let usage = Arc::clone(&self.state.lock().await.usage);
+        
+        self.send_message("usage", serde_json::json!({
+            "usage": usage.snapshot(),
+            "quota": usage.quota(),
+        })).await?;
+        
+        Ok(None)
+    }
+    
+    /// 处理重连后恢复会话的命令
+    ///
+    /// 取回断线期间未送达的结果；异常断开时保存的快照中的 ASR 配置仅在新连接尚未配置时恢复
+    async fn handle_resume_session(&self, session_id: &str) -> Result<Option<ServerResponse>, RouterError> {
+        let resumed = resume::global().lock()
+            .ok()
+            .and_then(|mut cache| cache.take(session_id))
+            .ok_or_else(|| RouterError::Coded {
+                code: "SESSION_NOT_FOUND",
+                message: format!("会话不存在或已过期: {}", session_id),
+            })?;
+        
+        let mut restored_config = false;
+        if let Some(snapshot) = resumed.snapshot {
+            let mut state = self.state.lock().await;
+            if let Some(ref asr_config) = snapshot.asr_config {
+                if state.asr_config.is_none() && state.phase == VoicePhase::Idle {
+                    match state.ensure_engines(asr_config) {
+                        Ok(()) => {
+                            restored_config = true;
+                            self.schedule_offline_retry(asr_config, Duration::ZERO);
+                        }
+                        Err(e) => { log_error!("恢复 ASR 配置失败: {}", e); }
+                    }
+                }
+            }
+            state.usage = snapshot.usage;
+            // 沿用旧会话的历史，容量按当前配置
+            if let Ok(mut history) = snapshot.history.lock() {
+                let capacity = state.asr_config.as_ref().map_or(history::DEFAULT_HISTORY_CAPACITY, |c| c.history_capacity);
+                history.set_capacity(capacity);
+            }
+            state.history = snapshot.history;
+        }
+        *self.session_id.lock().unwrap_or_else(|e| e.into_inner()) = session_id.to_string();
+        log_info!(
+            "恢复会话 {}: {} 条暂存结果, 恢复配置: {}",
+            session_id,
+            resumed.results.len(),
+            restored_config
+        );
+        
+        self.send_message("session_resumed", serde_json::json!({
+            "session_id": session_id,
+            "results": resumed.results,
+            "restored_config": restored_config,
+        })).await?;
+        
+        Ok(None)
+    }
+    
+    /// 处理查询引擎状态命令
+    async fn handle_get_engine_status(&self) -> Result<Option<ServerResponse>, RouterError> {
+        let engines = self.state.lock().await.engines.as_ref()
+            .map(|engines| engines.circuit_status())
+            .unwrap_or_else(|| serde_json::json!({}));
+        
+        let feedback = feedback::global().lock()
+            .map(|feedback| feedback.stats())
+            .unwrap_or_default();
+        
+        self.send_message("engine_status", serde_json::json!({
+            "engines": engines,
+            "feedback": feedback,
+        })).await?;
+        
+        Ok(None)
+    }
+    
+    /// 处理转录结果评分命令
+    async fn handle_rate_transcription(
+        &self,
+        request_id: &str,
+        rating: i64,
+        corrected_text: Option<String>,
+    ) -> Result<Option<ServerResponse>, RouterError> {
+        let log_path = self.state.lock().await.asr_config.as_ref()
+            .and_then(|config| config.feedback_log.clone());
+        let outcome = feedback::global().lock()
+            .map_err(|_| RouterError::ModuleError("反馈存储不可用".to_string()))?
+            .rate(request_id, rating, corrected_text.as_deref(), log_path.as_deref())
+            .map_err(|e| RouterError::Coded { code: e.code(), message: e.to_string() })?;
+        log_info!("收到转录评分: request_id={}, engine={}, rating={}", request_id, outcome.engine, rating);
+        
+        self.send_message("rating_recorded", serde_json::json!({
+            "request_id": request_id,
+            "engine": outcome.engine,
+            "char_error_rate": outcome.char_error_rate,
+        })).await?;
+        
+        Ok(None)
+    }
+    
+    /// 处理比较两次转录结果的命令
+    async fn handle_diff_transcripts(&self, original: &str, revised: &str) -> Result<Option<ServerResponse>, RouterError> {
+        self.send_message("transcript_diff", serde_json::json!({
+            "ops": asr::diff_transcripts(original, revised),
+        })).await?;
         
         Ok(None)
     }
@@ -624,38 +1699,50 @@ impl VoiceHandler {
     /// 检查是否正在录音
     pub async fn is_recording(&self) -> bool {
         let state = self.state.lock().await;
-        state.is_recording
+        state.phase.is_recording()
+    }
+    
+    /// 结束会话 (连接关闭、清理资源前调用)
+    ///
+    /// 客户端正常关闭时清理该会话的暂存内容；异常断开时保存会话快照，供重连后恢复
+    pub async fn end_session(&self, closed_by_client: bool) {
+        let session_id = self.session_id();
+        if closed_by_client {
+            if let Ok(mut cache) = resume::global().lock() {
+                cache.discard(&session_id);
+            }
+            return;
+        }
+        let state = self.state.lock().await;
+        resume::suspend(&session_id, resume::SessionSnapshot {
+            asr_config: state.asr_config.clone(),
+            usage: Arc::clone(&state.usage),
+            history: Arc::clone(&state.history),
+        });
+        log_debug!("连接异常断开，已保存会话快照: session_id={}", session_id);
     }
     
     /// 清理资源
     pub async fn cleanup(&self) {
         let mut state = self.state.lock().await;
         
-        if state.is_recording {
-            state.is_recording = false;
+        if state.phase.is_recording() {
             state.recording_mode = None;
             log_info!("连接关闭，取消录音");
         }
+        state.phase = VoicePhase::Idle;
         
-        // 取消实时转录任务
-        if let Some(stop_tx) = state.stop_signal.take() {
-            let _ = stop_tx.send(());
-        }
-        if let Some(task_handle) = state.realtime_task.take() {
-            task_handle.abort();
-        }
+        // 取消实时转录任务和流式录音
+        state.release_realtime_session();
         
         // 取消录音
-        if let Some(ref mut streaming_recorder) = state.streaming_recorder {
-            streaming_recorder.cancel();
-        }
         if let Some(ref mut recorder) = state.recorder {
             recorder.cancel();
         }
         
-        state.streaming_recorder = None;
         state.recorder = None;
         state.audio_level_tx = None;
+        state.engines = None;
     }
 }
 
@@ -678,10 +1765,13 @@ impl ModuleHandler for VoiceHandler {
             "start_recording" => {
                 let mode: RecordingMode = msg.get_field("mode")
                     .ok_or_else(|| RouterError::ModuleError("缺少 mode 字段".to_string()))?;
-                let asr_config: ASRConfig = msg.get_field("asr_config")
-                    .ok_or_else(|| RouterError::ModuleError("缺少 asr_config 字段".to_string()))?;
+                let asr_config: Option<ASRConfig> = msg.get_field("asr_config");
+                let capture = CaptureRequest {
+                    sample_rate: msg.get_field("sample_rate"),
+                    channels: msg.get_field("channels"),
+                };
                 
-                self.handle_start_recording(mode, asr_config).await
+                self.handle_start_recording(mode, asr_config, capture).await
             }
             "stop_recording" => {
                 self.handle_stop_recording().await
@@ -689,12 +1779,48 @@ impl ModuleHandler for VoiceHandler {
             "cancel_recording" => {
                 self.handle_cancel_recording().await
             }
+            "set_language" => {
+                let language: String = msg.get_field("language")
+                    .ok_or_else(|| RouterError::ModuleError("缺少 language 字段".to_string()))?;
+                
+                self.handle_set_language(language).await
+            }
             "update_config" => {
                 let asr_config: ASRConfig = msg.get_field("asr_config")
                     .ok_or_else(|| RouterError::ModuleError("缺少 asr_config 字段".to_string()))?;
                 
                 self.handle_update_config(asr_config).await
             }
+            "get_history" => {
+                let limit: Option<usize> = msg.get_field("limit");
+                self.handle_get_history(limit).await
+            }
+            "get_usage" => {
+                self.handle_get_usage().await
+            }
+            "get_engine_status" => {
+                self.handle_get_engine_status().await
+            }
+            "resume_session" => {
+                let session_id: String = msg.get_field("session_id")
+                    .ok_or_else(|| RouterError::ModuleError("缺少 session_id 字段".to_string()))?;
+                self.handle_resume_session(&session_id).await
+            }
+            "rate_transcription" => {
+                let request_id: String = msg.get_field("request_id")
+                    .ok_or_else(|| RouterError::ModuleError("缺少 request_id 字段".to_string()))?;
+                let rating: i64 = msg.get_field("rating")
+                    .ok_or_else(|| RouterError::ModuleError("缺少 rating 字段".to_string()))?;
+                let corrected_text: Option<String> = msg.get_field("corrected_text");
+                self.handle_rate_transcription(&request_id, rating, corrected_text).await
+            }
+            "diff_transcripts" => {
+                let original: String = msg.get_field("original")
+                    .ok_or_else(|| RouterError::ModuleError("缺少 original 字段".to_string()))?;
+                let revised: String = msg.get_field("revised")
+                    .ok_or_else(|| RouterError::ModuleError("缺少 revised 字段".to_string()))?;
+                self.handle_diff_transcripts(&original, &revised).await
+            }
             _ => {
                 log_debug!("未知的 Voice 消息类型: {}", msg.msg_type);
                 Err(RouterError::ModuleError(format!("未知的 Voice 消息类型: {}", msg.msg_type)))
@@ -707,17 +1833,130 @@ impl ModuleHandler for VoiceHandler {
 // 辅助函数
 // ============================================================================
 
-/// 执行 ASR 转录
+/// 消息类型对应的投递方式：音量、频谱等高频状态只需最新值，发送队列满时可丢弃
+fn delivery_for(msg_type: &str) -> Delivery {
+    match msg_type {
+        "audio_level" | "spectrum" => Delivery::Droppable,
+        _ => Delivery::Critical,
+    }
+}
+
+/// 向客户端发送 JSON 消息，令牌已取消或发送队列已关闭时返回 false
+async fn send_json(sender: &WsSender, token: &CancellationToken, msg: serde_json::Value) -> bool {
+    if token.is_cancelled() {
+        return false;
+    }
+    let delivery = delivery_for(msg.get("type").and_then(|t| t.as_str()).unwrap_or_default());
+    sender
+        .enqueue(OutboundMessage {
+            payload: Payload::Value(msg),
+            delivery,
+        })
+        .await
+        .is_ok()
+}
+
+/// 创建实时部分结果回调：增量与稳定前缀在回调内按到达顺序计算，
+/// 消息经单个转发任务按序发送，回调释放后任务结束
+fn partial_forwarder(
+    sender: WsSender,
+    token: CancellationToken,
+    delta_tracker: Arc<StdMutex<PartialDeltaTracker>>,
+    stabilizer: asr::PartialStabilizer,
+) -> (asr::AlternativesCallback, JoinHandle<()>) {
+    let (partial_tx, mut partial_rx) = mpsc::unbounded_channel::<serde_json::Value>();
+    let forwarder = tokio::spawn(async move {
+        while let Some(msg) = partial_rx.recv().await {
+            send_json(&sender, &token, msg).await;
+        }
+    });
+    let stabilizer = StdMutex::new(stabilizer);
+    let callback: asr::AlternativesCallback = Box::new(move |text: &str, alternatives: &[String]| {
+        let delta = delta_tracker.lock().ok().and_then(|mut tracker| tracker.update(text));
+        let mut msg = serde_json::json!({
+            "module": "voice",
+            "type": "transcription_progress",
+            "partial_text": text,
+            "delta": delta,
+        });
+        if let Ok(mut stabilizer) = stabilizer.lock() {
+            let stable = stabilizer.update(text);
+            msg["stable_text"] = serde_json::json!(stable.stable);
+            msg["unstable_text"] = serde_json::json!(stable.unstable);
+        }
+        // 只有引擎返回多个候选时才附带候选列表
+        if alternatives.len() > 1 {
+            msg["alternatives"] = serde_json::json!(alternatives);
+        }
+        let _ = partial_tx.send(msg);
+    });
+    (callback, forwarder)
+}
+
+/// 创建转录进度回调：进度消息经单个转发任务按序发送，回调全部释放后任务结束
+fn progress_forwarder(sender: WsSender, token: CancellationToken) -> (asr::ProgressCallback, JoinHandle<()>) {
+    let (progress_tx, mut progress_rx) = mpsc::unbounded_channel::<serde_json::Value>();
+    let forwarder = tokio::spawn(async move {
+        while let Some(msg) = progress_rx.recv().await {
+            send_json(&sender, &token, msg).await;
+        }
+    });
+    let progress: asr::ProgressCallback = Arc::new(move |text: &str, percent: u8| {
+        let _ = progress_tx.send(serde_json::json!({
+            "module": "voice",
+            "type": "transcription_progress",
+            "partial_text": text,
+            "percent": percent,
+        }));
+    });
+    (progress, forwarder)
+}
+
+/// 按配置启动录音旁路转发，旁路出错时仅向客户端发送 warning
+fn start_audio_tee(
+    asr_config: &ASRConfig,
+    sender: Option<WsSender>,
+    token: &CancellationToken,
+) -> Option<AudioTee> {
+    let config = asr_config.audio_tee.clone()?;
+    let warning_token = token.clone();
+    let on_warning: audio::tee::TeeWarningCallback = Box::new(move |message: &str| {
+        let Some(sender) = sender.clone() else {
+            return;
+        };
+        let token = warning_token.clone();
+        let msg = serde_json::json!({
+            "module": "voice",
+            "type": "warning",
+            "code": "AUDIO_TEE_FAILED",
+            "message": message,
+        });
+        tokio::spawn(async move {
+            send_json(&sender, &token, msg).await;
+        });
+    });
+    Some(AudioTee::start(config, token.child_token(), on_warning))
+}
+
+/// 令牌取消时提前结束转录
+async fn until_cancelled<T>(
+    token: &CancellationToken,
+    future: impl std::future::Future<Output = Result<T, ASRError>>,
+) -> Result<T, ASRError> {
+    tokio::select! {
+        result = future => result,
+        _ = token.cancelled() => Err(ASRError::Cancelled),
+    }
+}
+
+/// 执行 ASR 转录，长音频的转录进度经 `progress` 上报
 async fn perform_transcription(
     audio_data: &AudioData,
-    asr_config: &ASRConfig,
+    engines: &ConnectionEngines,
+    progress: Option<asr::ProgressCallback>,
 ) -> Result<TranscriptionResult, ASRError> {
-    // 验证配置
-    asr_config.validate()
-        .map_err(|e| ASRError::ConfigError(e.to_string()))?;
-    
-    // 创建并行兜底策略
-    let strategy = ParallelFallbackStrategy::from_config(asr_config.clone());
+    // 使用连接专属引擎创建并行兜底策略
+    let strategy = engines.strategy().with_progress(progress);
     
     log_info!(
         "使用 ASR 引擎: primary={}, fallback={:?}, enable_fallback={}",
@@ -730,10 +1969,265 @@ async fn perform_transcription(
     strategy.transcribe(audio_data).await
 }
 
+/// 准备本次录音的 WAV 文件路径
+///
+/// 先恢复目录中上次崩溃遗留的未完成录音；目录无法创建时返回 None (仅内存录音)
+fn prepare_recording_path(dir: &Path) -> Option<PathBuf> {
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        log_error!("创建录音目录失败: {}: {}", dir.display(), e);
+        return None;
+    }
+
+    let recovered = audio::recorder::recover_spool_dir(dir);
+    if !recovered.is_empty() {
+        log_info!("已恢复 {} 个未完成的录音文件", recovered.len());
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    Some(dir.join(format!("recording-{}.wav", timestamp)))
+}
+
+/// 构造转录失败的 error 消息，附带由错误种类推导的可重试性与建议
+fn transcription_error(message: String, error: &ASRError) -> serde_json::Value {
+    serde_json::json!({
+        "code": "TRANSCRIPTION_FAILED",
+        "message": message,
+        "retryable": error.is_retryable(),
+        "suggestion": error.suggestion(),
+    })
+}
+
+/// 输出整段录音的诊断结果 (削波与采样率警告已在录音过程中发送)
+fn log_diagnostics(audio_data: &AudioData, wall_clock_ms: Option<u64>, device_clock: Option<audio::DeviceClock>) {
+    let mut diagnostics = audio::diagnose(audio_data);
+    diagnostics.device_clock = device_clock;
+    log_debug!(
+        "音频诊断: peak={:.3}, rms={:.4}, loudness={:.1}LUFS, clipping={:.2}%, silence={:.2}%, duration={}ms, wall_clock={:?}ms, device_rate={:?}Hz, suspected_rate={:?}Hz",
+        diagnostics.peak,
+        diagnostics.rms,
+        diagnostics.loudness_lufs,
+        diagnostics.clipping_ratio * 100.0,
+        diagnostics.silence_ratio * 100.0,
+        diagnostics.duration_ms,
+        wall_clock_ms,
+        diagnostics.device_clock.map(|clock| clock.measured_rate),
+        wall_clock_ms.and_then(|ms| audio::infer_sample_rate_mismatch(audio_data, ms))
+    );
+}
+
+/// 已知播放的参考信号时消除其回声
+fn cancel_echo(audio_data: AudioData, reference: Option<&AudioData>) -> AudioData {
+    match reference {
+        Some(reference) if !audio_data.is_empty() => {
+            let start = Instant::now();
+            let cleaned = audio::acoustic_echo_cancel(&audio_data, reference);
+            log_debug!("回声消除完成，耗时 {}ms", start.elapsed().as_millis());
+            cleaned
+        }
+        _ => audio_data,
+    }
+}
+
+/// 按配置的预处理管线处理音频
+///
+/// 配置已在创建引擎时校验，这里遇到非法阶段名时退回默认管线
+fn preprocess_audio(audio_data: AudioData, asr_config: &ASRConfig) -> AudioData {
+    let pipeline = asr_config.audio_pipeline().unwrap_or_else(|e| {
+        log_error!("音频预处理管线无效，使用默认管线: {}", e);
+        audio::Pipeline::default()
+    });
+    
+    let original_ms = audio_data.duration_ms;
+    // 先压缩长停顿，之后的裁剪与归一化只作用于保留的音频
+    let audio_data = match asr_config.effective_compress_silence_ms() {
+        Some(max_silence_ms) => audio::utils::compress_silence(&audio_data, max_silence_ms),
+        None => audio_data,
+    };
+    let audio_data = pipeline.process(audio_data);
+    log_debug!(
+        "音频预处理 {:?}: {}ms -> {}ms",
+        pipeline.stages(),
+        original_ms,
+        audio_data.duration_ms
+    );
+    audio_data
+}
+
+/// 预先编码音频，返回编码耗时 (毫秒)
+///
+/// 编码结果缓存在 AudioData 中，后续引擎直接复用；编码失败时由引擎报告错误
+fn encode_ahead(audio_data: &AudioData) -> u64 {
+    let start = Instant::now();
+    if let Err(e) = audio_data.to_wav() {
+        log_error!("音频编码失败: {}", e);
+    }
+    start.elapsed().as_millis() as u64
+}
+
+/// 后处理转录结果并按输出格式生成最终文本，返回 (结果, 文本, 格式)
+async fn finalize_result(
+    result: &TranscriptionResult,
+    timings: Timings,
+    asr_config: &ASRConfig,
+) -> (TranscriptionResult, String, &'static str) {
+    // 后处理 (标点恢复等)
+    let post_process_start = Instant::now();
+    let mut result = result.clone();
+    result.text = post_process_text(&result.text, asr_config).await;
+    
+    let (text, format) = match asr_config.output_format {
+        OutputFormat::Markdown if !result.text.is_empty() => {
+            let template = asr_config.markdown_template.as_deref()
+                .unwrap_or(asr::DEFAULT_MARKDOWN_TEMPLATE);
+            (asr::to_markdown(&result, template), "markdown")
+        }
+        _ => (result.text.clone(), "text"),
+    };
+    // 分句只用于拼装文稿：从整段后处理的结果中切出，标点恢复 (可能是 LLM) 只对整段做一次
+    if format == "text" && !result.segments.is_empty() {
+        let rule_texts: Vec<String> = result.segments.iter()
+            .map(|segment| apply_text_rules(&segment.text, asr_config))
+            .collect();
+        let rule_refs: Vec<&str> = rule_texts.iter().map(String::as_str).collect();
+        match asr::split_like_segments(&result.text, &rule_refs) {
+            Some(parts) => {
+                for (segment, part) in result.segments.iter_mut().zip(parts) {
+                    segment.text = part.to_string();
+                }
+            }
+            None => {
+                log_debug!("分句与后处理后的整段文本对不上，分句仅做规则处理");
+                for (segment, text) in result.segments.iter_mut().zip(rule_texts) {
+                    segment.text = text;
+                }
+            }
+        }
+    }
+    result.timings = Timings {
+        post_process_ms: post_process_start.elapsed().as_millis() as u64,
+        ..timings
+    };
+    log_debug!("转录耗时分解: {:?}", result.timings);
+    
+    (result, text, format)
+}
+
+/// 转录完成消息的公共字段 (WebSocket 与 SSE 共用)
+fn completion_payload(
+    result: &TranscriptionResult,
+    text: String,
+    format: &str,
+    asr_config: &ASRConfig,
+) -> serde_json::Value {
+    let mut message = serde_json::json!({
+        "text": text,
+        "format": format,
+        "engine": result.engine,
+        "used_fallback": result.used_fallback,
+        "duration_ms": result.duration_ms,
+        "timings": result.timings,
+    });
+    if let Some(ref language) = result.language {
+        message["language"] = serde_json::json!(language);
+    }
+    
+    // 整句为语音命令时不写入文字，由前端执行编辑动作
+    if asr_config.voice_commands.enabled {
+        let language = result.language.as_deref().unwrap_or_default();
+        if let Some(command) = asr::commands::parse_command(&result.text, language, &asr_config.voice_commands) {
+            message["text"] = serde_json::json!("");
+            message["command"] = serde_json::json!(command);
+            return message;
+        }
+    }
+    
+    // 长文本按停顿、语义与长度分段，仅在确实分出多段或段内换行时附带整篇文稿
+    if format == "text" {
+        let segments = match result.segments.as_slice() {
+            [] => std::slice::from_ref(result),
+            segments => segments,
+        };
+        let document = asr::assemble_document_with(segments, &asr_config.document);
+        if document.contains('\n') {
+            message["document"] = serde_json::json!(document);
+        }
+        if asr_config.sentences {
+            let language = result.language.as_deref().unwrap_or_default();
+            message["sentences"] = serde_json::json!(asr::split_sentences(&text, language));
+        }
+        if asr_config.pinyin && asr::pinyin::has_han(&text) {
+            message["pinyin"] = serde_json::json!(asr::pinyin::annotate_pinyin(&text));
+        }
+    }
+    
+    message
+}
+
+/// 转录文本后处理
+///
+/// 在引擎自身的文本清理之后执行，失败时保留原文
+async fn post_process_text(text: &str, asr_config: &ASRConfig) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+    
+    let mut text = text.to_string();
+    
+    if asr_config.itn {
+        text = asr::itn::normalize(&text);
+    }
+    
+    if let Some(punctuator) = asr::create_punctuator(asr_config) {
+        match punctuator.punctuate(&text).await {
+            Ok(punctuated) => {
+                log_debug!("标点恢复 ({}): {}", punctuator.name(), punctuated);
+                text = punctuated;
+            }
+            Err(e) => {
+                log_error!("标点恢复失败，保留原文: {}", e);
+            }
+        }
+    }
+    
+    convert_and_replace(text, asr_config)
+}
+
+/// 后处理中的规则步骤 (ITN、简繁转换、术语替换)，不做标点恢复
+fn apply_text_rules(text: &str, asr_config: &ASRConfig) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+    let text = if asr_config.itn { asr::itn::normalize(text) } else { text.to_string() };
+    convert_and_replace(text, asr_config)
+}
+
+/// 简繁转换与术语替换 (标点恢复之后的步骤)
+fn convert_and_replace(mut text: String, asr_config: &ASRConfig) -> String {
+    if asr_config.script != ScriptTarget::default() {
+        text = asr::convert_script(&text, asr_config.script);
+    }
+    
+    // 术语替换最后应用，保证替换结果不被简繁或大小写规整改写
+    let replacements = &asr_config.replacements;
+    if !replacements.terms.is_empty() {
+        let options = asr::ReplaceOptions {
+            ignore_case: replacements.ignore_case,
+            whole_word: replacements.whole_word,
+        };
+        text = asr::apply_replacements_with(&text, &replacements.terms, options);
+    }
+    
+    text
+}
+
 /// 执行回退 ASR 转录
 async fn perform_fallback_transcription(
     audio_data: &AudioData,
     asr_config: &ASRConfig,
+    engines: &ConnectionEngines,
 ) -> Result<TranscriptionResult, ASRError> {
     // 检查音频数据是否为空
     if audio_data.is_empty() {
@@ -750,43 +2244,64 @@ async fn perform_fallback_transcription(
     
     // 如果配置了 fallback 引擎且启用了 fallback，优先使用 fallback 引擎
     if asr_config.enable_fallback {
-        if let Some(ref fallback_config) = asr_config.fallback {
-            log_info!("使用配置的 fallback 引擎: {}", fallback_config.provider);
-            
-            // 创建 fallback 引擎
-            let engine = asr::create_engine(fallback_config)?;
+        if let Some(ref engine) = engines.fallback {
+            log_info!("使用配置的 fallback 引擎: {}", engine.name());
             
             let start_time = std::time::Instant::now();
-            let text = engine.transcribe(audio_data).await?;
+            let transcript = asr::transcribe_conformed(engine.as_ref(), audio_data).await?;
             let duration_ms = start_time.elapsed().as_millis() as u64;
             
             return Ok(TranscriptionResult::new(
-                text,
+                transcript.text,
                 engine.name().to_string(),
                 true,
                 duration_ms,
-            ));
+            ).with_language(transcript.language));
         }
     }
     
     // 没有配置 fallback 引擎，使用 primary 引擎的 HTTP 模式
     log_info!("使用 primary 引擎的 HTTP 模式进行回退");
+    transcribe_primary_http(audio_data, engines, true).await
+}
+
+/// 自适应模式下的短录音转录：使用 primary 引擎的 HTTP 模式，失败时按配置改用 fallback 引擎
+async fn perform_short_transcription(
+    audio_data: &AudioData,
+    asr_config: &ASRConfig,
+    engines: &ConnectionEngines,
+) -> Result<TranscriptionResult, ASRError> {
+    if audio_data.is_empty() {
+        log_info!("短录音转录：音频数据为空");
+        return Ok(TranscriptionResult::new(String::new(), "none".to_string(), false, 0));
+    }
     
-    // 创建 HTTP 模式的配置
-    let mut http_config = asr_config.primary.clone();
-    http_config.mode = ASRMode::Http;
-    
-    // 创建 HTTP 引擎
-    let engine = asr::create_engine(&http_config)?;
+    match transcribe_primary_http(audio_data, engines, false).await {
+        Err(e) if asr_config.enable_fallback && engines.fallback.is_some() => {
+            log_error!("primary 引擎 HTTP 模式转录失败: {}，尝试 fallback 引擎", e);
+            perform_fallback_transcription(audio_data, asr_config, engines).await
+        }
+        result => result,
+    }
+}
+
+/// 使用 primary 引擎的 HTTP 模式转录
+async fn transcribe_primary_http(
+    audio_data: &AudioData,
+    engines: &ConnectionEngines,
+    used_fallback: bool,
+) -> Result<TranscriptionResult, ASRError> {
+    let engine = engines.primary_http.as_ref()
+        .map_err(|e| ASRError::ConfigError(format!("无法创建 primary 引擎的 HTTP 模式: {}", e)))?;
     
     let start_time = std::time::Instant::now();
-    let text = engine.transcribe(audio_data).await?;
+    let transcript = asr::transcribe_conformed(engine.as_ref(), audio_data).await?;
     let duration_ms = start_time.elapsed().as_millis() as u64;
     
     Ok(TranscriptionResult::new(
-        text,
+        transcript.text,
         format!("{}-http", engine.name()),
-        true,
+        used_fallback,
         duration_ms,
-    ))
+    ).with_language(transcript.language))
 }
@@ -0,0 +1,142 @@
+// Obsidian Local REST API 集成
+// 转录完成后把文本插入 Obsidian 当前打开的笔记 (需安装 Local REST API 插件)：
+// 未指定标题时追加到笔记末尾，指定标题时追加到该标题下；在独立任务中执行，失败只记录日志
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use super::config::{ASRConfig, ObsidianRestConfig};
+
+/// 单次请求超时
+const OBSIDIAN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 配置了 Obsidian REST 时，异步把转录文本插入当前笔记
+pub fn insert(asr_config: &ASRConfig, payload: &serde_json::Value) {
+    let Some(config) = asr_config.obsidian_rest.clone() else {
+        return;
+    };
+    // 空结果与语音命令不写入笔记
+    let text = payload["text"].as_str().unwrap_or_default();
+    if text.trim().is_empty() || payload.get("command").is_some() {
+        return;
+    }
+    let text = text.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = deliver(client(), &config, &text).await {
+            eprintln!("[WARN] [Voice] 写入 Obsidian 失败: {}", e);
+        }
+    });
+}
+
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        // Local REST API 的 HTTPS 使用自签名证书，且只连接本机
+        reqwest::Client::builder()
+            .timeout(OBSIDIAN_TIMEOUT)
+            .danger_accept_invalid_certs(true)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// 调用 Local REST API 写入文本
+async fn deliver(client: &reqwest::Client, config: &ObsidianRestConfig, text: &str) -> Result<(), String> {
+    let url = format!("{}/active/", config.base_url());
+    let body = format!("{}\n", text);
+    let request = match config.heading {
+        // 追加到指定标题下
+        Some(ref heading) => client
+            .patch(&url)
+            .header("Operation", "append")
+            .header("Target-Type", "heading")
+            .header("Target", percent_encode(heading)),
+        // 追加到笔记末尾
+        None => client.post(&url),
+    };
+
+    let response = request
+        .bearer_auth(&config.token)
+        .header(reqwest::header::CONTENT_TYPE, "text/markdown")
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", response.status()))
+    }
+}
+
+/// 标题按 URL 编码传入 header (插件要求，以支持非 ASCII 标题)
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// 接收一个请求并返回指定状态，返回收到的请求原文
+    async fn serve_once(listener: &TcpListener, status: &str) -> String {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut data = Vec::new();
+        let mut buf = [0u8; 1024];
+        loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            data.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&data).to_string();
+            let Some(header_end) = text.find("\r\n\r\n") else { continue };
+            let length = text[..header_end]
+                .lines()
+                .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(0);
+            if data.len() >= header_end + 4 + length || n == 0 {
+                break;
+            }
+        }
+        let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+        stream.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&data).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_append_and_insert_under_heading() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = ObsidianRestConfig {
+            host: "127.0.0.1".to_string(),
+            port: listener.local_addr().unwrap().port(),
+            https: false,
+            token: "secret".to_string(),
+            heading: None,
+        };
+        let client = reqwest::Client::new();
+
+        let (request, result) = tokio::join!(serve_once(&listener, "204 No Content"), deliver(&client, &config, "你好"));
+        assert!(result.is_ok());
+        assert!(request.starts_with("POST /active/ HTTP/1.1"), "{}", request);
+        assert!(request.to_ascii_lowercase().contains("authorization: bearer secret"));
+        assert!(request.ends_with("\r\n\r\n你好\n"));
+
+        config.heading = Some("语音 笔记".to_string());
+        let (request, result) = tokio::join!(serve_once(&listener, "200 OK"), deliver(&client, &config, "第二段"));
+        assert!(result.is_ok());
+        let lower = request.to_ascii_lowercase();
+        assert!(request.starts_with("PATCH /active/"));
+        assert!(lower.contains("operation: append") && lower.contains("target-type: heading"));
+        assert!(request.contains("%E8%AF%AD%E9%9F%B3%20%E7%AC%94%E8%AE%B0"), "{}", request);
+
+        let (_, result) = tokio::join!(serve_once(&listener, "404 Not Found"), deliver(&client, &config, "x"));
+        assert_eq!(result.unwrap_err(), "HTTP 404 Not Found");
+    }
+}
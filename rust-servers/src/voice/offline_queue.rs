@@ -0,0 +1,231 @@
+// 离线转录队列
+// 网络不可用 (主备引擎的每次尝试都以网络类错误失败) 时把录音存为 WAV 放入磁盘队列，
+// 网络恢复后由连接按入队顺序重新转录，并以延迟的 transcription_complete 回传。
+// 队列按目录在进程内共享、总大小受限；进程重启后首次打开目录时恢复上次未完成的条目
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use super::audio::{encode_to_wav, AudioData};
+use super::config::OfflineQueueConfig;
+use super::resume;
+use super::transcript_log;
+
+/// 写入中的临时文件后缀 (写完后改名，崩溃遗留的在打开队列时删除)
+const PARTIAL_SUFFIX: &str = ".part";
+
+/// 离线队列错误
+#[derive(Debug, thiserror::Error)]
+pub enum QueueError {
+    #[error("离线队列已满 (已用 {used} 字节，上限 {limit} 字节)")]
+    Full { used: u64, limit: u64 },
+
+    #[error("录音编码失败: {0}")]
+    Encoding(String),
+
+    #[error("写入离线队列失败: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// 队列条目 (文件名为 `{queued_at}-{id}.wav`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedItem {
+    pub id: String,
+    /// 入队时间 (Unix 毫秒)
+    pub queued_at: u64,
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+impl QueuedItem {
+    fn from_path(path: PathBuf) -> Option<Self> {
+        let stem = path.file_name()?.to_str()?.strip_suffix(".wav")?;
+        let (queued_at, id) = stem.split_once('-')?;
+        let queued_at = queued_at.parse().ok()?;
+        let bytes = std::fs::metadata(&path).ok()?.len();
+        Some(Self {
+            id: id.to_string(),
+            queued_at,
+            path,
+            bytes,
+        })
+    }
+}
+
+/// 磁盘队列
+#[derive(Debug)]
+pub struct OfflineQueue {
+    dir: PathBuf,
+    max_bytes: u64,
+    pending: VecDeque<QueuedItem>,
+    /// 已取出、正在转录的条目 (仍计入总大小)
+    in_flight: Vec<QueuedItem>,
+}
+
+impl OfflineQueue {
+    /// 打开队列目录 (不存在时创建)，按入队时间加载未完成的条目
+    pub fn open(dir: impl Into<PathBuf>, max_bytes: u64) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+
+        let mut pending = Vec::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.to_string_lossy().ends_with(PARTIAL_SUFFIX) {
+                let _ = std::fs::remove_file(&path);
+            } else if let Some(item) = QueuedItem::from_path(path) {
+                pending.push(item);
+            }
+        }
+        pending.sort_by(|a, b| (a.queued_at, &a.id).cmp(&(b.queued_at, &b.id)));
+
+        Ok(Self {
+            dir,
+            max_bytes,
+            pending: pending.into(),
+            in_flight: Vec::new(),
+        })
+    }
+
+    pub fn set_max_bytes(&mut self, max_bytes: u64) {
+        self.max_bytes = max_bytes;
+    }
+
+    /// 条目数 (含正在转录的)
+    pub fn len(&self) -> usize {
+        self.pending.len() + self.in_flight.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 是否有可取出的条目
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// 队列文件总大小 (字节)
+    pub fn total_bytes(&self) -> u64 {
+        self.pending.iter().chain(&self.in_flight).map(|item| item.bytes).sum()
+    }
+
+    /// 把录音写入队列，超出总大小上限时拒绝
+    pub fn enqueue(&mut self, audio: &AudioData) -> Result<QueuedItem, QueueError> {
+        let wav = encode_to_wav(audio).map_err(|e| QueueError::Encoding(e.to_string()))?;
+        let used = self.total_bytes();
+        if used + wav.len() as u64 > self.max_bytes {
+            return Err(QueueError::Full { used, limit: self.max_bytes });
+        }
+
+        let id = resume::generate_session_id();
+        let queued_at = transcript_log::now_millis();
+        let path = self.dir.join(format!("{}-{}.wav", queued_at, id));
+        let partial = self.dir.join(format!("{}-{}.wav{}", queued_at, id, PARTIAL_SUFFIX));
+        std::fs::write(&partial, &wav)?;
+        std::fs::rename(&partial, &path)?;
+
+        let item = QueuedItem {
+            id,
+            queued_at,
+            path,
+            bytes: wav.len() as u64,
+        };
+        self.pending.push_back(item.clone());
+        Ok(item)
+    }
+
+    /// 取出最早的条目，转录结束后须调用 `complete` 或 `release`
+    pub fn claim(&mut self) -> Option<QueuedItem> {
+        let item = self.pending.pop_front()?;
+        self.in_flight.push(item.clone());
+        Some(item)
+    }
+
+    /// 转录已完成 (或音频无法转录)，删除条目
+    pub fn complete(&mut self, item: &QueuedItem) {
+        self.in_flight.retain(|i| i.id != item.id);
+        if let Err(e) = std::fs::remove_file(&item.path) {
+            eprintln!("[WARN] [Voice] 删除离线队列文件失败: {}: {}", item.path.display(), e);
+        }
+    }
+
+    /// 网络仍不可用，放回队首等待重试
+    pub fn release(&mut self, item: QueuedItem) {
+        self.in_flight.retain(|i| i.id != item.id);
+        self.pending.push_front(item);
+    }
+}
+
+/// 进程级队列 (同一目录共享一个实例)
+pub fn open_shared(dir: &Path, max_bytes: u64) -> std::io::Result<Arc<Mutex<OfflineQueue>>> {
+    static QUEUES: OnceLock<Mutex<HashMap<PathBuf, Arc<Mutex<OfflineQueue>>>>> = OnceLock::new();
+    let mut queues = QUEUES.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(queue) = queues.get(dir) {
+        queue.lock().unwrap_or_else(|e| e.into_inner()).set_max_bytes(max_bytes);
+        return Ok(Arc::clone(queue));
+    }
+
+    let queue = OfflineQueue::open(dir, max_bytes)?;
+    if !queue.is_empty() {
+        eprintln!("[INFO] [Voice] 离线队列恢复 {} 条未完成的录音: {}", queue.len(), dir.display());
+    }
+    let queue = Arc::new(Mutex::new(queue));
+    queues.insert(dir.to_path_buf(), Arc::clone(&queue));
+    Ok(queue)
+}
+
+/// 按配置打开队列，未启用或目录不可用时返回 None
+pub fn for_config(config: &OfflineQueueConfig) -> Option<Arc<Mutex<OfflineQueue>>> {
+    if !config.enabled {
+        return None;
+    }
+    let dir = crate::utils::temp_dir::resolve(config.dir.trim());
+    match open_shared(&dir, config.max_total_mb.saturating_mul(1024 * 1024)) {
+        Ok(queue) => Some(queue),
+        Err(e) => {
+            eprintln!("[ERROR] [Voice] 打开离线队列失败: {}: {}", dir.display(), e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_order_limit_and_recovery() {
+        let dir = std::env::temp_dir().join(format!("sw-offline-queue-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let audio = AudioData::from_i16(vec![100; 1600], 16000, 1);
+        let wav_bytes = encode_to_wav(&audio).unwrap().len() as u64;
+
+        let mut queue = OfflineQueue::open(&dir, wav_bytes * 2).unwrap();
+        let first = queue.enqueue(&audio).unwrap();
+        let second = queue.enqueue(&audio).unwrap();
+        assert!(matches!(queue.enqueue(&audio), Err(QueueError::Full { .. })));
+        assert_eq!(queue.total_bytes(), wav_bytes * 2);
+
+        // 取出后放回仍在队首
+        let claimed = queue.claim().unwrap();
+        assert_eq!(claimed, first);
+        queue.release(claimed);
+        assert_eq!(queue.claim().unwrap(), first);
+        queue.complete(&first);
+        assert!(!first.path.exists());
+
+        // 重新打开时恢复未完成的条目，并清理写入中断的临时文件
+        std::fs::write(dir.join(format!("1-x.wav{}", PARTIAL_SUFFIX)), b"RIFF").unwrap();
+        let mut reopened = OfflineQueue::open(&dir, wav_bytes * 2).unwrap();
+        assert_eq!(reopened.len(), 1);
+        let restored = reopened.claim().unwrap();
+        assert_eq!(restored.id, second.id);
+        assert_eq!(super::super::audio::read_wav(&restored.path).unwrap().duration_ms, 100);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
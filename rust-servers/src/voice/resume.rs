@@ -0,0 +1,226 @@
+// 断线结果暂存
+// 转录完成但连接已断开、结果发送失败时，按连接的 session_id 暂存结果；
+// 连接异常断开时另外保存会话快照 (ASR 配置与用量)，客户端重连后通过 resume_session 一并取回；
+// 超过 TTL 未取回的结果与快照被清理，客户端正常关闭连接时立即清理
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use super::config::ASRConfig;
+use super::history::TranscriptionHistory;
+use super::usage::UsageMeter;
+
+/// 暂存结果的保留时长
+pub const RESUME_TTL: Duration = Duration::from_secs(120);
+
+/// 会话快照的保留时长 (覆盖休眠唤醒等较长的断线)
+pub const SNAPSHOT_TTL: Duration = Duration::from_secs(600);
+
+/// 单个会话最多暂存的结果数
+const MAX_RESULTS_PER_SESSION: usize = 8;
+
+#[derive(Debug)]
+struct PendingResults {
+    results: Vec<serde_json::Value>,
+    expires_at: Instant,
+}
+
+/// 异常断开时保存的会话状态
+#[derive(Debug, Clone)]
+pub struct SessionSnapshot {
+    /// 断开前使用的 ASR 配置
+    pub asr_config: Option<ASRConfig>,
+    /// 连接用量 (重连后继续累计，配额不因重连重置)
+    pub usage: Arc<UsageMeter>,
+    /// 会话的转录历史
+    pub history: Arc<Mutex<TranscriptionHistory>>,
+}
+
+/// 重连时取回的会话
+#[derive(Debug)]
+pub struct ResumedSession {
+    /// 未送达的结果 (按完成顺序)
+    pub results: Vec<serde_json::Value>,
+    pub snapshot: Option<SessionSnapshot>,
+}
+
+/// 按 session_id 索引的未送达结果与会话快照
+#[derive(Debug)]
+pub struct ResumeCache {
+    sessions: HashMap<String, PendingResults>,
+    snapshots: HashMap<String, (SessionSnapshot, Instant)>,
+    ttl: Duration,
+    snapshot_ttl: Duration,
+}
+
+impl ResumeCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            sessions: HashMap::new(),
+            snapshots: HashMap::new(),
+            ttl,
+            snapshot_ttl: SNAPSHOT_TTL,
+        }
+    }
+
+    /// 设置会话快照的保留时长
+    pub fn with_snapshot_ttl(mut self, ttl: Duration) -> Self {
+        self.snapshot_ttl = ttl;
+        self
+    }
+
+    /// 暂存一条未送达的结果 (同一会话再次暂存时刷新 TTL)
+    pub fn store(&mut self, session_id: &str, result: serde_json::Value) {
+        self.purge_expired();
+        let expires_at = Instant::now() + self.ttl;
+        let pending = self.sessions.entry(session_id.to_string()).or_insert_with(|| PendingResults {
+            results: Vec::new(),
+            expires_at,
+        });
+        if pending.results.len() >= MAX_RESULTS_PER_SESSION {
+            pending.results.remove(0);
+        }
+        pending.results.push(result);
+        pending.expires_at = expires_at;
+    }
+
+    /// 保存会话快照 (覆盖同一会话之前的快照)
+    pub fn suspend(&mut self, session_id: &str, snapshot: SessionSnapshot) {
+        self.purge_expired();
+        let expires_at = Instant::now() + self.snapshot_ttl;
+        self.snapshots.insert(session_id.to_string(), (snapshot, expires_at));
+    }
+
+    /// 取回并移除会话的暂存结果与快照，两者都不存在或已过期时返回 None
+    pub fn take(&mut self, session_id: &str) -> Option<ResumedSession> {
+        self.purge_expired();
+        let results = self.sessions.remove(session_id).map(|pending| pending.results);
+        let snapshot = self.snapshots.remove(session_id).map(|(snapshot, _)| snapshot);
+        if results.is_none() && snapshot.is_none() {
+            return None;
+        }
+        Some(ResumedSession {
+            results: results.unwrap_or_default(),
+            snapshot,
+        })
+    }
+
+    /// 移除会话的全部暂存内容 (客户端正常关闭连接时调用)
+    pub fn discard(&mut self, session_id: &str) {
+        self.sessions.remove(session_id);
+        self.snapshots.remove(session_id);
+    }
+
+    /// 清理过期的会话
+    pub fn purge_expired(&mut self) {
+        let now = Instant::now();
+        self.sessions.retain(|_, pending| pending.expires_at > now);
+        self.snapshots.retain(|_, (_, expires_at)| *expires_at > now);
+    }
+}
+
+/// 进程级暂存 (跨连接共享)
+pub fn global() -> &'static Mutex<ResumeCache> {
+    static CACHE: OnceLock<Mutex<ResumeCache>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(ResumeCache::new(RESUME_TTL)))
+}
+
+/// 暂存到进程级缓存，并在 TTL 到期后清理
+pub fn stash(session_id: &str, result: serde_json::Value) {
+    if let Ok(mut cache) = global().lock() {
+        cache.store(session_id, result);
+    }
+    tokio::spawn(async {
+        tokio::time::sleep(RESUME_TTL).await;
+        if let Ok(mut cache) = global().lock() {
+            cache.purge_expired();
+        }
+    });
+}
+
+/// 保存会话快照到进程级缓存，并在 TTL 到期后清理
+pub fn suspend(session_id: &str, snapshot: SessionSnapshot) {
+    if let Ok(mut cache) = global().lock() {
+        cache.suspend(session_id, snapshot);
+    }
+    tokio::spawn(async {
+        tokio::time::sleep(SNAPSHOT_TTL).await;
+        if let Ok(mut cache) = global().lock() {
+            cache.purge_expired();
+        }
+    });
+}
+
+/// 生成不可猜测的会话 ID (128 位随机数的十六进制)
+pub fn generate_session_id() -> String {
+    use ring::rand::SecureRandom;
+
+    let mut bytes = [0u8; 16];
+    if ring::rand::SystemRandom::new().fill(&mut bytes).is_err() {
+        // 系统随机源不可用时退化为时间戳
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        bytes = nanos.to_le_bytes();
+    }
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_take_and_expire() {
+        let mut cache = ResumeCache::new(Duration::from_millis(30));
+        cache.store("a", serde_json::json!({ "text": "第一段" }));
+        cache.store("a", serde_json::json!({ "text": "第二段" }));
+        cache.store("b", serde_json::json!({ "text": "其他连接" }));
+
+        let results = cache.take("a").unwrap().results;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1]["text"], "第二段");
+        // 取回后即移除
+        assert!(cache.take("a").is_none());
+
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(cache.take("b").is_none());
+        assert!(cache.sessions.is_empty());
+
+        let id = generate_session_id();
+        assert_eq!(id.len(), 32);
+        assert_ne!(id, generate_session_id());
+    }
+
+    #[test]
+    fn test_snapshot_resume_and_discard() {
+        let mut cache = ResumeCache::new(Duration::from_millis(30)).with_snapshot_ttl(Duration::from_millis(60));
+        let usage = Arc::new(UsageMeter::default());
+        usage.record(1000, 10);
+        let history: Arc<Mutex<TranscriptionHistory>> = Default::default();
+        let result = crate::voice::asr::TranscriptionResult::new("旧会话".to_string(), "qwen".to_string(), false, 100);
+        history.lock().unwrap().push(crate::voice::history::HistoryItem::new(&result, "旧会话".to_string(), "text"));
+        cache.suspend("a", SessionSnapshot { asr_config: None, usage: Arc::clone(&usage), history });
+        cache.store("a", serde_json::json!({ "text": "未送达" }));
+
+        // 结果过期后快照仍可取回，转录历史随快照恢复
+        std::thread::sleep(Duration::from_millis(40));
+        let resumed = cache.take("a").unwrap();
+        assert!(resumed.results.is_empty());
+        let snapshot = resumed.snapshot.unwrap();
+        assert_eq!(snapshot.usage.snapshot(), usage.snapshot());
+        assert_eq!(snapshot.history.lock().unwrap().recent(None)[0].text, "旧会话");
+        assert!(cache.take("a").is_none());
+
+        // 正常关闭时立即清理
+        cache.suspend("b", SessionSnapshot { asr_config: None, usage: Arc::clone(&usage), history: Default::default() });
+        cache.discard("b");
+        assert!(cache.take("b").is_none());
+
+        cache.suspend("c", SessionSnapshot { asr_config: None, usage, history: Default::default() });
+        std::thread::sleep(Duration::from_millis(70));
+        assert!(cache.take("c").is_none());
+    }
+}
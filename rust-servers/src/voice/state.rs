@@ -0,0 +1,189 @@
+// 录音状态机
+// 显式管理 Idle/Recording/Transcribing 三个阶段，非法转换返回带稳定错误码的错误
+
+use thiserror::Error;
+
+use crate::router::RouterError;
+
+// ============================================================================
+// 状态与事件
+// ============================================================================
+
+/// 语音会话阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VoicePhase {
+    /// 空闲
+    #[default]
+    Idle,
+    /// 录音中
+    Recording,
+    /// 录音已停止，转录进行中
+    Transcribing,
+}
+
+/// 触发状态转换的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceEvent {
+    /// 开始录音
+    Start,
+    /// 停止录音并开始转录
+    Stop,
+    /// 取消录音
+    Cancel,
+    /// 转录结束 (成功或失败)
+    Finish,
+    /// 更新连接配置
+    UpdateConfig,
+}
+
+/// 非法状态转换错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum TransitionError {
+    #[error("已在录音中")]
+    AlreadyRecording,
+
+    #[error("未在录音中")]
+    NotRecording,
+
+    #[error("转录进行中，请等待当前转录完成")]
+    BusyTranscribing,
+
+    #[error("当前没有进行中的转录")]
+    NotTranscribing,
+}
+
+impl TransitionError {
+    /// 稳定错误码 (客户端据此区分错误类型)
+    pub fn code(&self) -> &'static str {
+        match self {
+            TransitionError::AlreadyRecording => "ALREADY_RECORDING",
+            TransitionError::NotRecording => "NOT_RECORDING",
+            TransitionError::BusyTranscribing => "BUSY_TRANSCRIBING",
+            TransitionError::NotTranscribing => "NOT_TRANSCRIBING",
+        }
+    }
+}
+
+impl From<TransitionError> for RouterError {
+    fn from(error: TransitionError) -> Self {
+        RouterError::Coded {
+            code: error.code(),
+            message: error.to_string(),
+        }
+    }
+}
+
+// ============================================================================
+// 状态转换
+// ============================================================================
+
+impl VoicePhase {
+    /// 计算事件触发后的下一阶段
+    ///
+    /// 转录进行中收到开始录音直接拒绝，避免两次转录结果交错
+    pub fn transition(self, event: VoiceEvent) -> Result<VoicePhase, TransitionError> {
+        use VoiceEvent::*;
+        use VoicePhase::*;
+
+        match (self, event) {
+            (Idle, Start) => Ok(Recording),
+            (Recording, Start) => Err(TransitionError::AlreadyRecording),
+            (Transcribing, Start) => Err(TransitionError::BusyTranscribing),
+
+            (Recording, Stop) => Ok(Transcribing),
+            (Idle, Stop) => Err(TransitionError::NotRecording),
+            (Transcribing, Stop) => Err(TransitionError::BusyTranscribing),
+
+            (Recording, Cancel) => Ok(Idle),
+            (Idle, Cancel) => Err(TransitionError::NotRecording),
+            (Transcribing, Cancel) => Err(TransitionError::BusyTranscribing),
+
+            (Transcribing, Finish) => Ok(Idle),
+            (Idle | Recording, Finish) => Err(TransitionError::NotTranscribing),
+
+            (Idle, UpdateConfig) => Ok(Idle),
+            (Recording, UpdateConfig) => Err(TransitionError::AlreadyRecording),
+            (Transcribing, UpdateConfig) => Err(TransitionError::BusyTranscribing),
+        }
+    }
+
+    /// 是否处于录音中
+    pub fn is_recording(self) -> bool {
+        self == VoicePhase::Recording
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHASES: [VoicePhase; 3] = [VoicePhase::Idle, VoicePhase::Recording, VoicePhase::Transcribing];
+    const EVENTS: [VoiceEvent; 5] = [
+        VoiceEvent::Start,
+        VoiceEvent::Stop,
+        VoiceEvent::Cancel,
+        VoiceEvent::Finish,
+        VoiceEvent::UpdateConfig,
+    ];
+
+    /// 期望的转换表
+    fn expected(phase: VoicePhase, event: VoiceEvent) -> Result<VoicePhase, &'static str> {
+        use VoiceEvent::*;
+        use VoicePhase::*;
+
+        match (phase, event) {
+            (Idle, Start) => Ok(Recording),
+            (Idle, Stop) => Err("NOT_RECORDING"),
+            (Idle, Cancel) => Err("NOT_RECORDING"),
+            (Idle, Finish) => Err("NOT_TRANSCRIBING"),
+            (Idle, UpdateConfig) => Ok(Idle),
+            (Recording, Start) => Err("ALREADY_RECORDING"),
+            (Recording, Stop) => Ok(Transcribing),
+            (Recording, Cancel) => Ok(Idle),
+            (Recording, Finish) => Err("NOT_TRANSCRIBING"),
+            (Recording, UpdateConfig) => Err("ALREADY_RECORDING"),
+            (Transcribing, Start) => Err("BUSY_TRANSCRIBING"),
+            (Transcribing, Stop) => Err("BUSY_TRANSCRIBING"),
+            (Transcribing, Cancel) => Err("BUSY_TRANSCRIBING"),
+            (Transcribing, Finish) => Ok(Idle),
+            (Transcribing, UpdateConfig) => Err("BUSY_TRANSCRIBING"),
+        }
+    }
+
+    #[test]
+    fn test_all_transitions() {
+        for phase in PHASES {
+            for event in EVENTS {
+                let actual = phase.transition(event).map_err(|e| e.code());
+                assert_eq!(actual, expected(phase, event), "{:?} + {:?}", phase, event);
+            }
+        }
+    }
+
+    #[test]
+    fn test_full_cycle() {
+        let phase = VoicePhase::default();
+        let phase = phase.transition(VoiceEvent::Start).unwrap();
+        assert!(phase.is_recording());
+        let phase = phase.transition(VoiceEvent::Stop).unwrap();
+        assert_eq!(phase, VoicePhase::Transcribing);
+        assert!(!phase.is_recording());
+        let phase = phase.transition(VoiceEvent::Finish).unwrap();
+        assert_eq!(phase, VoicePhase::Idle);
+    }
+
+    #[test]
+    fn test_error_codes_are_distinct() {
+        let errors = [
+            TransitionError::AlreadyRecording,
+            TransitionError::NotRecording,
+            TransitionError::BusyTranscribing,
+            TransitionError::NotTranscribing,
+        ];
+        for (i, a) in errors.iter().enumerate() {
+            for b in &errors[i + 1..] {
+                assert_ne!(a.code(), b.code());
+            }
+        }
+    }
+}
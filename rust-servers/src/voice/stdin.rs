@@ -0,0 +1,539 @@
+// 标准输入音频转录
+// 从 stdin 读取裸 PCM (小端有符号整数，采样率/声道/位深由命令行指定) 持续转录，
+// 便于把 ffmpeg 等工具的管道直接接入。主引擎为实时模式时边读边推送，读到 EOF 即结束并等待最终结果；
+// 结果以 JSON Lines 写到 stdout，或广播给所有已连接的 WebSocket 客户端
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Instant;
+use tokio::sync::{broadcast, mpsc};
+use tokio_util::sync::CancellationToken;
+
+use super::asr::{ASRError, RealtimeTaskResult, Timings, TranscriptionResult};
+use super::audio::recorder::to_mono;
+use super::audio::streaming::{AudioChunkData, ChunkAccumulator, CHUNK_CHANNEL_BUFFER, CHUNK_SAMPLES};
+use super::audio::{read_wav, AudioData, IncrementalWavWriter, StreamResampler, TARGET_SAMPLE_RATE};
+use super::config::{ASRConfig, ASRMode};
+use super::upload::{self, UploadEvent};
+use super::{preprocess_audio, ConnectionEngines};
+use crate::outbound::WsSender;
+
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        eprintln!("[INFO] [stdin] {}", format!($($arg)*));
+    };
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        eprintln!("[WARN] [stdin] {}", format!($($arg)*));
+    };
+}
+
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        eprintln!("[ERROR] [stdin] {}", format!($($arg)*));
+    };
+}
+
+/// 单次读取 stdin 的字节数
+const READ_BUFFER_BYTES: usize = 8192;
+
+/// 解码后采样块的通道容量
+const BLOCK_CHANNEL_BUFFER: usize = 64;
+
+/// 广播给 WebSocket 客户端的消息缓冲
+const BROADCAST_BUFFER: usize = 256;
+
+// ============================================================================
+// PCM 格式与解码
+// ============================================================================
+
+/// 裸 PCM 格式 (小端有符号整数)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PcmFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// 位深，支持 16、24、32
+    pub bits: u16,
+}
+
+impl PcmFormat {
+    pub fn new(sample_rate: u32, channels: u16, bits: u16) -> Result<Self, String> {
+        if sample_rate == 0 {
+            return Err("采样率必须大于 0".to_string());
+        }
+        if channels == 0 {
+            return Err("声道数必须大于 0".to_string());
+        }
+        if !matches!(bits, 16 | 24 | 32) {
+            return Err(format!("不支持的位深: {} (支持 16、24、32)", bits));
+        }
+        Ok(Self { sample_rate, channels, bits })
+    }
+
+    fn frame_bytes(&self) -> usize {
+        self.bits as usize / 8 * self.channels as usize
+    }
+}
+
+impl Default for PcmFormat {
+    fn default() -> Self {
+        Self { sample_rate: TARGET_SAMPLE_RATE, channels: 1, bits: 16 }
+    }
+}
+
+/// 把任意切分的字节流解码为 f32 采样，每次只输出完整的帧，不完整的帧留到下次
+#[derive(Debug)]
+pub struct PcmDecoder {
+    format: PcmFormat,
+    remainder: Vec<u8>,
+}
+
+impl PcmDecoder {
+    pub fn new(format: PcmFormat) -> Self {
+        Self { format, remainder: Vec::new() }
+    }
+
+    /// 解码一段字节，返回交错的 f32 采样 (范围 -1.0 到 1.0)
+    pub fn push(&mut self, data: &[u8]) -> Vec<f32> {
+        self.remainder.extend_from_slice(data);
+        let frame_bytes = self.format.frame_bytes();
+        let complete = self.remainder.len() / frame_bytes * frame_bytes;
+        let sample_bytes = self.format.bits as usize / 8;
+        let samples = self.remainder[..complete]
+            .chunks_exact(sample_bytes)
+            .map(|bytes| match bytes {
+                [b0, b1] => i16::from_le_bytes([*b0, *b1]) as f32 / 32768.0,
+                // 24 位放到 i32 高位后算术右移，完成符号扩展
+                [b0, b1, b2] => (i32::from_le_bytes([0, *b0, *b1, *b2]) >> 8) as f32 / 8_388_608.0,
+                [b0, b1, b2, b3] => i32::from_le_bytes([*b0, *b1, *b2, *b3]) as f32 / 2_147_483_648.0,
+                _ => unreachable!("位深已在 PcmFormat::new 中校验"),
+            })
+            .collect();
+        self.remainder.drain(..complete);
+        samples
+    }
+
+    /// 尚未组成完整帧的字节数
+    pub fn remainder(&self) -> usize {
+        self.remainder.len()
+    }
+}
+
+/// 在后台线程读取 `reader` 并解码，读到 EOF 或出错时关闭返回的通道
+pub fn spawn_reader<R: Read + Send + 'static>(mut reader: R, format: PcmFormat) -> mpsc::Receiver<Vec<f32>> {
+    let (block_tx, block_rx) = mpsc::channel(BLOCK_CHANNEL_BUFFER);
+    std::thread::spawn(move || {
+        let mut decoder = PcmDecoder::new(format);
+        let mut buffer = vec![0u8; READ_BUFFER_BYTES];
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    log_error!("读取标准输入失败: {}", e);
+                    break;
+                }
+            };
+            let samples = decoder.push(&buffer[..read]);
+            if !samples.is_empty() && block_tx.blocking_send(samples).is_err() {
+                return;
+            }
+        }
+        if decoder.remainder() > 0 {
+            log_warn!("标准输入末尾有 {} 字节不足一帧，已丢弃", decoder.remainder());
+        }
+    });
+    block_rx
+}
+
+// ============================================================================
+// 转录
+// ============================================================================
+
+/// 把输入格式的交错采样转换为实时会话使用的 16kHz 单声道块
+struct ChunkConverter {
+    channels: u16,
+    resampler: StreamResampler,
+    accumulator: ChunkAccumulator,
+}
+
+impl ChunkConverter {
+    fn new(format: PcmFormat) -> Self {
+        Self {
+            channels: format.channels,
+            resampler: StreamResampler::new(format.sample_rate, TARGET_SAMPLE_RATE, 1),
+            accumulator: ChunkAccumulator::new(),
+        }
+    }
+
+    fn push(&mut self, block: &[f32]) -> Vec<Vec<i16>> {
+        let resampled = self.resampler.process(&to_mono(block, self.channels));
+        self.accumulator.push_f32(&resampled);
+        std::iter::from_fn(|| self.accumulator.pop_chunk()).collect()
+    }
+
+    /// 输入结束：输出重采样尾部与不足一块的剩余采样
+    fn finish(&mut self) -> Vec<Vec<i16>> {
+        let tail = self.resampler.flush();
+        self.accumulator.push_f32(&tail);
+        let mut chunks: Vec<Vec<i16>> = std::iter::from_fn(|| self.accumulator.pop_chunk()).collect();
+        chunks.extend(self.accumulator.take_rest());
+        chunks
+    }
+}
+
+/// 实时模式下保留的整段输入，供实时转录失败时回退 HTTP 模式
+///
+/// 写入临时目录的 WAV 文件而不留在内存，长时间的管道输入不会持续占用内存；
+/// 无法创建或写入文件时放弃回退
+struct InputSpool {
+    writer: Option<IncrementalWavWriter>,
+    path: PathBuf,
+    format: PcmFormat,
+}
+
+impl InputSpool {
+    fn create(path: PathBuf, format: PcmFormat) -> Self {
+        let writer = match IncrementalWavWriter::create(&path, format.sample_rate, format.channels) {
+            Ok(writer) => Some(writer),
+            Err(e) => {
+                log_warn!("创建输入缓存文件失败，实时转录失败时无法回退: {}", e);
+                None
+            }
+        };
+        Self { writer, path, format }
+    }
+
+    fn write(&mut self, block: &[f32]) {
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+        if let Err(e) = writer.write_samples(block) {
+            log_warn!("写入输入缓存文件失败，实时转录失败时无法回退: {}", e);
+            self.writer = None;
+        }
+    }
+
+    /// 已缓存的输入时长
+    fn duration_ms(&self) -> u64 {
+        let frames = self.writer.as_ref().map_or(0, |writer| writer.len() as u64) / self.format.channels as u64;
+        frames * 1000 / self.format.sample_rate as u64
+    }
+
+    /// 读回整段输入
+    fn load(&mut self) -> Option<AudioData> {
+        let writer = self.writer.take()?;
+        match writer.finalize().and_then(read_wav) {
+            Ok(audio) => Some(audio),
+            Err(e) => {
+                log_warn!("读取输入缓存文件失败: {}", e);
+                None
+            }
+        }
+    }
+}
+
+impl Drop for InputSpool {
+    fn drop(&mut self) {
+        drop(self.writer.take());
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// 持续转录 `blocks` (解码后的交错采样，通道关闭即输入结束)，进度与结果通过 `events` 发送
+pub async fn transcribe_stream(
+    blocks: mpsc::Receiver<Vec<f32>>,
+    format: PcmFormat,
+    asr_config: ASRConfig,
+    events: mpsc::UnboundedSender<UploadEvent>,
+    cancel_token: CancellationToken,
+) {
+    let result = run(blocks, format, &asr_config, &events, &cancel_token).await;
+    if let Err(ref e) = result {
+        log_error!("标准输入音频转录失败: {}", e);
+    }
+    upload::emit_result(result, &asr_config, &events, &cancel_token).await;
+}
+
+async fn run(
+    mut blocks: mpsc::Receiver<Vec<f32>>,
+    format: PcmFormat,
+    asr_config: &ASRConfig,
+    events: &mpsc::UnboundedSender<UploadEvent>,
+    cancel_token: &CancellationToken,
+) -> Result<(TranscriptionResult, Timings), ASRError> {
+    if asr_config.primary.mode != ASRMode::Realtime {
+        // HTTP 模式读到 EOF 后整段转录
+        let mut samples = Vec::new();
+        loop {
+            tokio::select! {
+                _ = cancel_token.cancelled() => return Err(ASRError::Cancelled),
+                block = blocks.recv() => match block {
+                    Some(block) => samples.extend_from_slice(&block),
+                    None => break,
+                },
+            }
+        }
+        let audio_data = AudioData::new(samples, format.sample_rate, format.channels);
+        return upload::run(audio_data, asr_config, events, cancel_token).await;
+    }
+
+    let engines = ConnectionEngines::build(asr_config)?;
+    let start = Instant::now();
+    let (chunk_tx, chunk_rx) = mpsc::channel::<AudioChunkData>(CHUNK_CHANNEL_BUFFER);
+    let task = upload::realtime_task(chunk_rx, asr_config, &engines, events, cancel_token);
+
+    let feed = async move {
+        let mut converter = ChunkConverter::new(format);
+        let mut spool = InputSpool::create(std::env::temp_dir().join(format!("sw-stdin-{}.wav", std::process::id())), format);
+        let mut index = 0usize;
+        let mut stamp = |samples: Vec<i16>| {
+            let timestamp_ms = (index * CHUNK_SAMPLES) as u64 * 1000 / TARGET_SAMPLE_RATE as u64;
+            index += 1;
+            AudioChunkData { samples, timestamp_ms }
+        };
+        let mut sending = true;
+        loop {
+            let block = tokio::select! {
+                _ = cancel_token.cancelled() => break,
+                block = blocks.recv() => match block {
+                    Some(block) => block,
+                    None => break,
+                },
+            };
+            spool.write(&block);
+            for samples in converter.push(&block) {
+                // 任务已结束 (如会话失败) 时继续读到 EOF，供回退使用
+                if sending && chunk_tx.send(stamp(samples)).await.is_err() {
+                    sending = false;
+                }
+            }
+        }
+        for samples in converter.finish() {
+            if sending && chunk_tx.send(stamp(samples)).await.is_err() {
+                sending = false;
+            }
+        }
+        // 发送完毕后关闭通道，任务随即等待最终结果
+        drop(chunk_tx);
+        spool
+    };
+
+    let (result, mut spool) = tokio::join!(task.run_with_details(), feed);
+    let recording_ms = spool.duration_ms();
+    // 只有需要回退时才读回整段输入
+    let needs_fallback = match &result {
+        RealtimeTaskResult::ShortAudio => true,
+        RealtimeTaskResult::Failed { error, .. } => !matches!(error, ASRError::Cancelled),
+        _ => false,
+    };
+    let audio_data = needs_fallback
+        .then(|| spool.load())
+        .flatten()
+        .map(|audio| preprocess_audio(audio, asr_config));
+    upload::realtime_outcome(result, recording_ms, audio_data.as_ref(), asr_config, &engines, cancel_token, start).await
+}
+
+// ============================================================================
+// 输出
+// ============================================================================
+
+/// 转录结果的输出方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StdinOutput {
+    /// 以 JSON Lines 写到 stdout (不启动服务器)
+    #[default]
+    Stdout,
+    /// 广播给所有已连接的 WebSocket 客户端
+    WebSocket,
+}
+
+/// 从 JSON 文件读取 ASR 配置
+pub fn load_config(path: &Path) -> Result<ASRConfig, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("读取 {} 失败: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("{} 不是合法的 ASR 配置: {}", path.display(), e))
+}
+
+/// stdout 输出的一行：`event` 为事件名，其余字段同对应的 WebSocket 消息
+fn event_line(event: &UploadEvent) -> String {
+    let mut line = serde_json::json!({ "event": event.name() });
+    if let (Some(line), Some(data)) = (line.as_object_mut(), event.data().as_object()) {
+        line.extend(data.clone());
+    }
+    line.to_string()
+}
+
+/// 广播给 WebSocket 客户端的消息 (与录音转录的消息类型一致)
+fn ws_message(event: &UploadEvent) -> serde_json::Value {
+    let msg_type = match event {
+        UploadEvent::Partial(_) => "transcription_progress",
+        UploadEvent::Final(_) => "transcription_complete",
+        UploadEvent::Error(_) => "error",
+    };
+    let mut message = serde_json::json!({ "module": "voice", "type": msg_type });
+    if let (Some(message), Some(data)) = (message.as_object_mut(), event.data().as_object()) {
+        message.extend(data.clone());
+    }
+    message
+}
+
+/// 转录 stdin 并把事件写到 stdout，返回是否得到最终结果 (Ctrl+C 中止时为 false)
+pub async fn run_to_stdout(format: PcmFormat, asr_config: ASRConfig) -> bool {
+    let cancel_token = CancellationToken::new();
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    let blocks = spawn_reader(std::io::stdin(), format);
+    tokio::spawn(transcribe_stream(blocks, format, asr_config, event_tx, cancel_token.clone()));
+
+    let mut completed = false;
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                cancel_token.cancel();
+                return false;
+            }
+            event = event_rx.recv() => match event {
+                Some(event) => {
+                    completed = matches!(event, UploadEvent::Final(_));
+                    println!("{}", event_line(&event));
+                }
+                None => return completed,
+            },
+        }
+    }
+}
+
+fn broadcaster() -> &'static OnceLock<broadcast::Sender<serde_json::Value>> {
+    static BROADCASTER: OnceLock<broadcast::Sender<serde_json::Value>> = OnceLock::new();
+    &BROADCASTER
+}
+
+/// 在后台转录 stdin，事件广播给已连接的 WebSocket 客户端 (服务器启动后调用)
+pub fn start_broadcast(format: PcmFormat, asr_config: ASRConfig) {
+    let sender = broadcaster().get_or_init(|| broadcast::channel(BROADCAST_BUFFER).0).clone();
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    let blocks = spawn_reader(std::io::stdin(), format);
+    tokio::spawn(transcribe_stream(blocks, format, asr_config, event_tx, CancellationToken::new()));
+    tokio::spawn(async move {
+        while let Some(event) = event_rx.recv().await {
+            // 没有客户端连接时丢弃
+            let _ = sender.send(ws_message(&event));
+        }
+        log_info!("标准输入转录结束");
+    });
+}
+
+/// 把 stdin 转录的广播转发给一个连接，连接关闭时停止；未启用广播时立即返回
+pub async fn forward_broadcast(sender: WsSender, token: CancellationToken) {
+    let Some(mut receiver) = broadcaster().get().map(|sender| sender.subscribe()) else {
+        return;
+    };
+    loop {
+        let message = tokio::select! {
+            _ = token.cancelled() => break,
+            message = receiver.recv() => message,
+        };
+        match message {
+            Ok(message) => {
+                if !super::send_json(&sender, &token, message).await {
+                    break;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                log_warn!("连接读取过慢，跳过 {} 条标准输入转录消息", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_pcm_across_reads() {
+        // 16 位单声道：半个采样留到下次
+        let mut decoder = PcmDecoder::new(PcmFormat::default());
+        assert_eq!(decoder.push(&[0x00, 0x40, 0x00]), vec![0.5]);
+        assert_eq!(decoder.remainder(), 1);
+        assert_eq!(decoder.push(&[0x80]), vec![-1.0]);
+
+        // 24 位立体声：负数符号扩展，按整帧输出
+        let mut decoder = PcmDecoder::new(PcmFormat::new(48000, 2, 24).unwrap());
+        assert!(decoder.push(&[0x00, 0x00, 0x40, 0x00, 0x00]).is_empty());
+        assert_eq!(decoder.push(&[0xC0]), vec![0.5, -0.5]);
+
+        // 32 位
+        let mut decoder = PcmDecoder::new(PcmFormat::new(16000, 1, 32).unwrap());
+        assert_eq!(decoder.push(&i32::MIN.to_le_bytes()), vec![-1.0]);
+
+        assert!(PcmFormat::new(16000, 1, 8).is_err());
+        assert!(PcmFormat::new(0, 1, 16).is_err());
+        assert!(PcmFormat::new(16000, 0, 16).is_err());
+    }
+
+    #[test]
+    fn test_stdin_chunks_and_event_output() {
+        // 48kHz 立体声分块输入，转换为 16kHz 单声道块，尾部不足一块也发送
+        let mut converter = ChunkConverter::new(PcmFormat::new(48000, 2, 16).unwrap());
+        let block = [0.25f32, 0.75].repeat(4800);
+        let mut chunks = Vec::new();
+        for _ in 0..3 {
+            chunks.extend(converter.push(&block));
+        }
+        chunks.extend(converter.finish());
+        let total: usize = chunks.iter().map(|chunk| chunk.len()).sum();
+        assert_eq!(total, 4800);
+        assert!(chunks[..chunks.len() - 1].iter().all(|chunk| chunk.len() == CHUNK_SAMPLES));
+        assert!(chunks.iter().flatten().all(|&s| (s as i32 - 16384).abs() <= 1));
+
+        let partial = UploadEvent::Partial(serde_json::json!({ "partial_text": "你好" }));
+        assert_eq!(event_line(&partial), r#"{"event":"partial","partial_text":"你好"}"#);
+        let error = UploadEvent::Error(serde_json::json!({ "code": "TRANSCRIPTION_FAILED" }));
+        assert_eq!(
+            ws_message(&error),
+            serde_json::json!({ "module": "voice", "type": "error", "code": "TRANSCRIPTION_FAILED" })
+        );
+        assert_eq!(ws_message(&UploadEvent::Final(serde_json::json!({})))["type"], "transcription_complete");
+    }
+
+    #[test]
+    fn test_input_spool_round_trip() {
+        let path = std::env::temp_dir().join(format!("sw-stdin-test-{}.wav", std::process::id()));
+        let format = PcmFormat::new(16000, 2, 16).unwrap();
+        let mut spool = InputSpool::create(path.clone(), format);
+        for _ in 0..10 {
+            spool.write(&[0.5, -0.5].repeat(160));
+        }
+        assert_eq!(spool.duration_ms(), 100);
+
+        // 输入写在磁盘上而不是内存中，读回后与写入一致
+        assert!(path.exists());
+        let audio = spool.load().unwrap();
+        assert_eq!((audio.sample_rate, audio.channels, audio.samples.len()), (16000, 2, 3200));
+        assert!(audio.samples.iter().all(|&s| (s.abs() - 0.5).abs() < 1e-3));
+        assert!(spool.load().is_none());
+
+        // 丢弃时删除缓存文件
+        drop(spool);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_http_mode_transcribes_after_eof() {
+        let config = ASRConfig::primary_only(super::super::config::ASRProviderConfig::qwen(ASRMode::Http, String::new()));
+        let blocks = spawn_reader(std::io::Cursor::new(vec![0u8; 3200]), PcmFormat::default());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        transcribe_stream(blocks, PcmFormat::default(), config, tx, CancellationToken::new()).await;
+
+        // 缺少 API Key 时报错，EOF 后只发送一个结果事件
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.name(), "error");
+        assert!(rx.recv().await.is_none());
+    }
+}
@@ -0,0 +1,148 @@
+// 转录结果 JSON Lines 日志
+// 每条转录结果 (含时间戳、引擎、时长) 序列化为一行 JSON 追加到 ASRConfig.transcript_log 指定的文件；
+// 所有连接的写入都交给同一个写入线程串行执行，避免并发写入交错损坏行
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::OnceLock;
+use std::thread::JoinHandle;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::asr::TranscriptionResult;
+use super::config::ASRConfig;
+
+/// 单行日志
+#[derive(serde::Serialize)]
+struct LogLine<'a> {
+    /// 写入时间 (Unix 毫秒)
+    timestamp: u64,
+    #[serde(flatten)]
+    result: &'a TranscriptionResult,
+}
+
+/// 配置了日志文件时，追加一行转录结果
+pub fn append(asr_config: &ASRConfig, result: &TranscriptionResult) {
+    let Some(ref path) = asr_config.transcript_log else {
+        return;
+    };
+    append_json(path, &LogLine { timestamp: now_millis(), result });
+}
+
+/// 将任意可序列化的记录作为一行追加到指定文件 (与转录日志共用写入线程)
+pub fn append_json(path: &str, record: &impl serde::Serialize) {
+    match serde_json::to_string(record) {
+        Ok(line) => global().append(PathBuf::from(path), line),
+        Err(e) => eprintln!("[WARN] [Voice] 序列化日志行失败: {}", e),
+    }
+}
+
+/// 当前 Unix 毫秒时间戳
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn global() -> &'static JsonlWriter {
+    static WRITER: OnceLock<JsonlWriter> = OnceLock::new();
+    WRITER.get_or_init(JsonlWriter::spawn)
+}
+
+/// JSON Lines 写入器
+///
+/// 写入请求经通道交给后台线程按到达顺序逐行写入，每个文件只打开一次
+pub struct JsonlWriter {
+    tx: mpsc::Sender<(PathBuf, String)>,
+    thread: JoinHandle<()>,
+}
+
+impl JsonlWriter {
+    /// 启动写入线程
+    pub fn spawn() -> Self {
+        let (tx, rx) = mpsc::channel::<(PathBuf, String)>();
+        let thread = std::thread::spawn(move || {
+            let mut files: HashMap<PathBuf, File> = HashMap::new();
+            for (path, line) in rx {
+                if let Err(e) = write_line(&mut files, &path, &line) {
+                    eprintln!("[WARN] [Voice] 写入转录日志失败: {}: {}", path.display(), e);
+                    // 下次写入时重新打开 (文件可能被移动或删除)
+                    files.remove(&path);
+                }
+            }
+        });
+        Self { tx, thread }
+    }
+
+    /// 追加一行 (不含换行符)
+    pub fn append(&self, path: PathBuf, line: String) {
+        let _ = self.tx.send((path, line));
+    }
+
+    /// 写完已提交的所有行后结束写入线程
+    #[allow(dead_code)]
+    pub fn close(self) {
+        drop(self.tx);
+        let _ = self.thread.join();
+    }
+}
+
+fn write_line(files: &mut HashMap<PathBuf, File>, path: &Path, line: &str) -> std::io::Result<()> {
+    if !files.contains_key(path) {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        files.insert(path.to_path_buf(), file);
+    }
+    let file = files.get_mut(path).expect("文件已打开");
+    // 整行一次写入，行内容不会与其他写入交错
+    file.write_all(format!("{}\n", line).as_bytes())?;
+    file.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_concurrent_appends_produce_whole_lines() {
+        let dir = std::env::temp_dir().join(format!("transcript-log-{}", std::process::id()));
+        let path = dir.join("nested").join("log.jsonl");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let writer = Arc::new(JsonlWriter::spawn());
+        let threads: Vec<_> = (0..4)
+            .map(|t| {
+                let writer = Arc::clone(&writer);
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for i in 0..50 {
+                        let result = TranscriptionResult::new("文本".repeat(100), format!("engine-{}", t), false, i);
+                        let line = serde_json::to_string(&LogLine { timestamp: 1, result: &result }).unwrap();
+                        writer.append(path.clone(), line);
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        Arc::try_unwrap(writer).ok().unwrap().close();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 200);
+        for line in lines {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(value["timestamp"], 1);
+            assert!(value["engine"].as_str().unwrap().starts_with("engine-"));
+            assert!(value["duration_ms"].is_u64());
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,313 @@
+// 上传音频转录
+// 供 HTTP SSE 接口使用：转录一段完整的上传音频，复用连接转录的引擎与后处理，
+// 主引擎为实时模式时按块推送音频，过程中产生部分结果
+
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use super::audio::recorder::{f32_to_i16, resample, to_mono};
+use super::audio::streaming::{AudioChunkData, CHUNK_CHANNEL_BUFFER, CHUNK_SAMPLES};
+use super::audio::{AudioData, TARGET_SAMPLE_RATE};
+use super::asr::{rank_nbest, ASRError, PartialDeltaTracker, PartialStabilizer, RealtimeTaskResult, RealtimeTranscriptionTask, Timings, TranscriptionResult};
+use super::config::{ASRConfig, ASRMode};
+use super::{completion_payload, encode_ahead, finalize_result, perform_fallback_transcription, perform_short_transcription, perform_transcription, preprocess_audio, transcription_error, until_cancelled, ConnectionEngines};
+
+/// 转录进度事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum UploadEvent {
+    /// 部分结果 (字段与 transcription_progress 消息一致)
+    Partial(serde_json::Value),
+    /// 最终结果 (字段与 transcription_complete 消息一致)
+    Final(serde_json::Value),
+    /// 转录失败 (字段与 error 消息一致)
+    Error(serde_json::Value),
+}
+
+impl UploadEvent {
+    /// SSE 事件名
+    pub fn name(&self) -> &'static str {
+        match self {
+            UploadEvent::Partial(_) => "partial",
+            UploadEvent::Final(_) => "final",
+            UploadEvent::Error(_) => "error",
+        }
+    }
+
+    pub fn data(&self) -> &serde_json::Value {
+        match self {
+            UploadEvent::Partial(data) | UploadEvent::Final(data) | UploadEvent::Error(data) => data,
+        }
+    }
+}
+
+/// 转录上传的音频，进度与结果通过 `events` 发送
+///
+/// 令牌取消 (客户端断开) 时立即停止且不再发送事件
+pub async fn transcribe_upload(
+    audio_data: AudioData,
+    asr_config: ASRConfig,
+    events: mpsc::UnboundedSender<UploadEvent>,
+    cancel_token: CancellationToken,
+) {
+    let result = run(audio_data, &asr_config, &events, &cancel_token).await;
+    if let Err(ref e) = result {
+        eprintln!("[ERROR] [Voice] 上传音频转录失败: {}", e);
+    }
+    emit_result(result, &asr_config, &events, &cancel_token).await;
+}
+
+/// 转录上传的音频，返回主引擎给出的最多 `n` 个候选结果 (文本, 分数)，按分数降序
+///
+/// 不做后处理与备用引擎回退；超过引擎单次时长上限的音频无法合并分段候选，直接报错
+pub async fn transcribe_nbest(
+    audio_data: AudioData,
+    asr_config: &ASRConfig,
+    n: usize,
+) -> Result<Vec<(String, f32)>, ASRError> {
+    let engines = ConnectionEngines::build(asr_config)?;
+    let audio_data = preprocess_audio(audio_data, asr_config);
+    if audio_data.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut segments = engines.primary.audio_requirements().conform(audio_data);
+    if segments.len() != 1 {
+        return Err(ASRError::InvalidAudio(format!(
+            "音频超过 {} 的单次时长上限，n-best 转录不支持分段",
+            engines.primary.name()
+        )));
+    }
+    let candidates = engines.primary.transcribe_nbest(&segments.remove(0), n).await?;
+    Ok(rank_nbest(candidates, n))
+}
+
+/// 后处理转录结果并作为 final 事件发送，失败时发送 error 事件 (令牌已取消时不发送)
+pub(super) async fn emit_result(
+    result: Result<(TranscriptionResult, Timings), ASRError>,
+    asr_config: &ASRConfig,
+    events: &mpsc::UnboundedSender<UploadEvent>,
+    cancel_token: &CancellationToken,
+) {
+    if cancel_token.is_cancelled() {
+        return;
+    }
+
+    let event = match result {
+        Ok((result, timings)) => {
+            let (result, text, format) = finalize_result(&result, timings, asr_config).await;
+            let payload = completion_payload(&result, text, format, asr_config);
+            super::webhook::notify(asr_config, &payload);
+            super::obsidian::insert(asr_config, &payload);
+            UploadEvent::Final(payload)
+        }
+        Err(e) => UploadEvent::Error(transcription_error(e.to_string(), &e)),
+    };
+    let _ = events.send(event);
+}
+
+/// 转录一段完整音频 (主引擎为实时模式时按块推送，失败回退 HTTP 模式)
+pub(super) async fn run(
+    audio_data: AudioData,
+    asr_config: &ASRConfig,
+    events: &mpsc::UnboundedSender<UploadEvent>,
+    cancel_token: &CancellationToken,
+) -> Result<(TranscriptionResult, Timings), ASRError> {
+    let engines = ConnectionEngines::build(asr_config)?;
+    let audio_data = preprocess_audio(audio_data, asr_config);
+    let recording_ms = audio_data.duration_ms;
+
+    if audio_data.is_empty() {
+        let empty = TranscriptionResult::new(String::new(), "none".to_string(), false, 0);
+        return Ok((empty, Timings::default()));
+    }
+
+    if asr_config.primary.mode == ASRMode::Realtime {
+        let start = Instant::now();
+        let result = transcribe_realtime(&audio_data, asr_config, &engines, events, cancel_token).await;
+        return realtime_outcome(result, recording_ms, Some(&audio_data), asr_config, &engines, cancel_token, start).await;
+    }
+
+    let encoding_ms = encode_ahead(&audio_data);
+    let progress_events = events.clone();
+    let progress: super::asr::ProgressCallback = Arc::new(move |text: &str, percent: u8| {
+        let _ = progress_events.send(UploadEvent::Partial(serde_json::json!({
+            "partial_text": text,
+            "percent": percent,
+        })));
+    });
+    let result = until_cancelled(cancel_token, perform_transcription(&audio_data, &engines, Some(progress))).await?;
+    let timings = Timings {
+        recording_ms,
+        encoding_ms,
+        network_ms: result.timings.network_ms,
+        ..Timings::default()
+    };
+    Ok((result, timings))
+}
+
+/// 整理实时转录的结果，失败时按 HTTP 模式回退转录 `audio_data` (已预处理的整段音频，为空时不回退)
+pub(super) async fn realtime_outcome(
+    result: RealtimeTaskResult,
+    recording_ms: u64,
+    audio_data: Option<&AudioData>,
+    asr_config: &ASRConfig,
+    engines: &ConnectionEngines,
+    cancel_token: &CancellationToken,
+    start: Instant,
+) -> Result<(TranscriptionResult, Timings), ASRError> {
+    match result {
+        RealtimeTaskResult::Success(result) | RealtimeTaskResult::Partial { result, .. } => {
+            let timings = Timings {
+                recording_ms,
+                network_ms: start.elapsed().as_millis() as u64,
+                ..Timings::default()
+            };
+            Ok((result, timings))
+        }
+        RealtimeTaskResult::Failed { error: ASRError::Cancelled, .. } => Err(ASRError::Cancelled),
+        RealtimeTaskResult::ShortAudio => {
+            let Some(audio_data) = audio_data else {
+                return Err(ASRError::UnsupportedOperation("短录音未建立实时会话，且没有可回退转录的录音".to_string()));
+            };
+            let encoding_ms = encode_ahead(audio_data);
+            let result = until_cancelled(
+                cancel_token,
+                perform_short_transcription(audio_data, asr_config, engines),
+            ).await?;
+            let timings = Timings {
+                recording_ms,
+                encoding_ms,
+                network_ms: start.elapsed().as_millis() as u64,
+                ..Timings::default()
+            };
+            Ok((result, timings))
+        }
+        RealtimeTaskResult::Failed { error, engine_name, .. } => {
+            let Some(audio_data) = audio_data else {
+                eprintln!("[WARN] [Voice] 实时转录失败 ({}): {}，没有可回退转录的录音", engine_name, error);
+                return Err(error);
+            };
+            eprintln!("[WARN] [Voice] 实时转录失败 ({}): {}，回退到 HTTP 模式", engine_name, error);
+            let encoding_ms = encode_ahead(audio_data);
+            let result = until_cancelled(
+                cancel_token,
+                perform_fallback_transcription(audio_data, asr_config, engines),
+            ).await?;
+            let timings = Timings {
+                recording_ms,
+                encoding_ms,
+                network_ms: start.elapsed().as_millis() as u64,
+                ..Timings::default()
+            };
+            Ok((result, timings))
+        }
+    }
+}
+
+/// 按块推送音频到实时会话，部分结果作为 partial 事件发送
+async fn transcribe_realtime(
+    audio_data: &AudioData,
+    asr_config: &ASRConfig,
+    engines: &ConnectionEngines,
+    events: &mpsc::UnboundedSender<UploadEvent>,
+    cancel_token: &CancellationToken,
+) -> RealtimeTaskResult {
+    let (chunk_tx, chunk_rx) = mpsc::channel::<AudioChunkData>(CHUNK_CHANNEL_BUFFER);
+    let task = realtime_task(chunk_rx, asr_config, engines, events, cancel_token);
+
+    let pcm = realtime_pcm(audio_data);
+    let feed = async move {
+        for (index, samples) in pcm.chunks(CHUNK_SAMPLES).enumerate() {
+            let chunk = AudioChunkData {
+                samples: samples.to_vec(),
+                timestamp_ms: (index * CHUNK_SAMPLES) as u64 * 1000 / TARGET_SAMPLE_RATE as u64,
+            };
+            if chunk_tx.send(chunk).await.is_err() {
+                break;
+            }
+        }
+        // 发送完毕后关闭通道，任务随即等待最终结果
+    };
+
+    let (result, ()) = tokio::join!(task.run_with_details(), feed);
+    result
+}
+
+/// 创建读取 `chunk_rx` 的实时转录任务，部分结果作为 partial 事件发送
+pub(super) fn realtime_task(
+    chunk_rx: mpsc::Receiver<AudioChunkData>,
+    asr_config: &ASRConfig,
+    engines: &ConnectionEngines,
+    events: &mpsc::UnboundedSender<UploadEvent>,
+    cancel_token: &CancellationToken,
+) -> RealtimeTranscriptionTask {
+    let tracker = Arc::new(StdMutex::new(PartialDeltaTracker::new()));
+    let stabilizer = StdMutex::new(PartialStabilizer::new(asr_config.partial_stability));
+    let partial_events = events.clone();
+    let partial_callback: super::asr::AlternativesCallback = Box::new(move |text: &str, alternatives: &[String]| {
+        let delta = tracker.lock().ok().and_then(|mut tracker| tracker.update(text));
+        let mut partial = serde_json::json!({
+            "partial_text": text,
+            "delta": delta,
+        });
+        if let Ok(mut stabilizer) = stabilizer.lock() {
+            let stable = stabilizer.update(text);
+            partial["stable_text"] = serde_json::json!(stable.stable);
+            partial["unstable_text"] = serde_json::json!(stable.unstable);
+        }
+        if alternatives.len() > 1 {
+            partial["alternatives"] = serde_json::json!(alternatives);
+        }
+        let _ = partial_events.send(UploadEvent::Partial(partial));
+    });
+
+    let (task, _stop_tx) = RealtimeTranscriptionTask::new(asr_config.primary.clone(), chunk_rx, Some(partial_callback));
+    task.with_engine(Arc::clone(&engines.primary))
+        .with_cancel_token(cancel_token.clone())
+        .with_keepalive(asr_config.keepalive)
+}
+
+/// 转换为实时会话需要的 16kHz 单声道 i16 采样
+fn realtime_pcm(audio_data: &AudioData) -> Vec<i16> {
+    if audio_data.sample_rate == TARGET_SAMPLE_RATE && audio_data.channels == 1 {
+        if let Some(pcm) = audio_data.pcm_i16() {
+            return pcm.to_vec();
+        }
+    }
+    let mono = to_mono(&audio_data.samples, audio_data.channels);
+    resample(&mono, audio_data.sample_rate, TARGET_SAMPLE_RATE)
+        .into_iter()
+        .map(f32_to_i16)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_realtime_pcm_downmixes_and_resamples() {
+        let stereo = AudioData::from_i16([1000, 3000].repeat(32000), 32000, 2);
+        let pcm = realtime_pcm(&stereo);
+        assert_eq!(pcm.len(), 16000);
+        assert!(pcm.iter().all(|&s| (s - 2000).abs() <= 1));
+
+        let native = AudioData::from_i16(vec![7; 320], TARGET_SAMPLE_RATE, 1);
+        assert_eq!(realtime_pcm(&native), vec![7; 320]);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_config_reports_error_event() {
+        let config = ASRConfig::primary_only(super::super::config::ASRProviderConfig::qwen(ASRMode::Http, String::new()));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        transcribe_upload(AudioData::from_i16(vec![0; 1600], TARGET_SAMPLE_RATE, 1), config, tx, CancellationToken::new()).await;
+
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.name(), "error");
+        assert_eq!(event.data()["code"], "TRANSCRIPTION_FAILED");
+        assert!(rx.recv().await.is_none());
+    }
+}
@@ -0,0 +1,207 @@
+// 用量统计与配额
+// 按连接累计转录音频时长、请求次数与字符数，超过配额时拒绝新的转录
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::router::RouterError;
+
+/// 配额 (None 表示不限制)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct UsageQuota {
+    /// 转录音频总时长上限 (毫秒)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_audio_ms: Option<u64>,
+    /// 转录请求次数上限
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_requests: Option<u64>,
+    /// 输出字符数上限
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_chars: Option<u64>,
+}
+
+impl UsageQuota {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_audio_ms.is_none() && self.max_requests.is_none() && self.max_chars.is_none()
+    }
+}
+
+/// 用量快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct UsageSnapshot {
+    pub audio_ms: u64,
+    pub requests: u64,
+    pub chars: u64,
+}
+
+/// 连接用量计数器
+///
+/// 计数均为原子操作，转录任务可在不持有连接状态锁的情况下记录用量
+#[derive(Debug, Default)]
+pub struct UsageMeter {
+    quota: UsageQuota,
+    audio_ms: AtomicU64,
+    requests: AtomicU64,
+    chars: AtomicU64,
+}
+
+impl UsageMeter {
+    pub fn new(quota: UsageQuota) -> Self {
+        Self {
+            quota,
+            ..Default::default()
+        }
+    }
+
+    pub fn quota(&self) -> UsageQuota {
+        self.quota
+    }
+
+    /// 开始一次转录：检查配额并占用一次请求计数
+    pub fn try_start(&self) -> Result<(), RouterError> {
+        if let Some(max) = self.quota.max_audio_ms {
+            if self.audio_ms.load(Ordering::SeqCst) >= max {
+                return Err(quota_exceeded(format!("转录时长已达上限 ({} 秒)", max / 1000)));
+            }
+        }
+        if let Some(max) = self.quota.max_chars {
+            if self.chars.load(Ordering::SeqCst) >= max {
+                return Err(quota_exceeded(format!("转录字数已达上限 ({} 字)", max)));
+            }
+        }
+
+        // 比较并递增，并发开始的请求不会超出上限
+        self.requests
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                match self.quota.max_requests {
+                    Some(max) if count >= max => None,
+                    _ => Some(count + 1),
+                }
+            })
+            .map(|_| ())
+            .map_err(|count| quota_exceeded(format!("转录次数已达上限 ({} 次)", count)))
+    }
+
+    /// 撤销 `try_start` 占用的请求计数 (转录未能开始时调用)
+    pub fn cancel_start(&self) {
+        let _ = self.requests.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| count.checked_sub(1));
+    }
+
+    /// 检查配额并预留一次请求计数，预留在 `commit` 之前被丢弃时归还
+    pub fn reserve(meter: &Arc<Self>) -> Result<UsageReservation, RouterError> {
+        meter.try_start()?;
+        Ok(UsageReservation { meter: Some(Arc::clone(meter)) })
+    }
+
+    /// 记录一次转录的音频时长与输出字符数
+    pub fn record(&self, audio_ms: u64, chars: u64) {
+        self.audio_ms.fetch_add(audio_ms, Ordering::SeqCst);
+        self.chars.fetch_add(chars, Ordering::SeqCst);
+    }
+
+    pub fn snapshot(&self) -> UsageSnapshot {
+        UsageSnapshot {
+            audio_ms: self.audio_ms.load(Ordering::SeqCst),
+            requests: self.requests.load(Ordering::SeqCst),
+            chars: self.chars.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// 已预留的请求计数 (开始录音失败时随错误返回而丢弃，计数随之归还)
+#[derive(Debug)]
+pub struct UsageReservation {
+    meter: Option<Arc<UsageMeter>>,
+}
+
+impl UsageReservation {
+    /// 转录已开始，保留这次计数
+    pub fn commit(mut self) {
+        self.meter = None;
+    }
+}
+
+impl Drop for UsageReservation {
+    fn drop(&mut self) {
+        if let Some(meter) = self.meter.take() {
+            meter.cancel_start();
+        }
+    }
+}
+
+fn quota_exceeded(message: String) -> RouterError {
+    RouterError::Coded {
+        code: "QUOTA_EXCEEDED",
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_quota_is_exact_under_concurrency() {
+        let meter = Arc::new(UsageMeter::new(UsageQuota {
+            max_requests: Some(50),
+            ..Default::default()
+        }));
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let meter = Arc::clone(&meter);
+                std::thread::spawn(move || (0..20).filter(|_| meter.try_start().is_ok()).count())
+            })
+            .collect();
+        let started: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+
+        assert_eq!(started, 50);
+        assert_eq!(meter.snapshot().requests, 50);
+    }
+
+    #[test]
+    fn test_audio_and_char_quota() {
+        let meter = UsageMeter::new(UsageQuota {
+            max_audio_ms: Some(60_000),
+            max_chars: Some(100),
+            ..Default::default()
+        });
+
+        assert!(meter.try_start().is_ok());
+        meter.record(30_000, 40);
+        assert!(meter.try_start().is_ok());
+        meter.record(30_000, 40);
+
+        let err = meter.try_start().unwrap_err();
+        assert!(matches!(err, RouterError::Coded { code: "QUOTA_EXCEEDED", .. }));
+        assert_eq!(meter.snapshot(), UsageSnapshot { audio_ms: 60_000, requests: 2, chars: 80 });
+
+        assert!(UsageMeter::default().quota().is_unlimited());
+    }
+
+    #[test]
+    fn test_failed_start_returns_reserved_request() {
+        let meter = Arc::new(UsageMeter::new(UsageQuota {
+            max_requests: Some(1),
+            ..Default::default()
+        }));
+
+        // 录音器启动失败：预留随错误返回丢弃，不占用配额
+        let start = |fail: bool| -> Result<(), RouterError> {
+            let reservation = UsageMeter::reserve(&meter)?;
+            if fail {
+                return Err(RouterError::ModuleError("启动录音失败".to_string()));
+            }
+            reservation.commit();
+            Ok(())
+        };
+        assert!(start(true).is_err());
+        assert!(start(true).is_err());
+        assert_eq!(meter.snapshot().requests, 0);
+
+        assert!(start(false).is_ok());
+        assert_eq!(meter.snapshot().requests, 1);
+        assert!(matches!(start(false), Err(RouterError::Coded { code: "QUOTA_EXCEEDED", .. })));
+        assert_eq!(meter.snapshot().requests, 1);
+    }
+}
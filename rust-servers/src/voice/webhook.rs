@@ -0,0 +1,161 @@
+// 转录完成回调 (webhook)
+// 转录完成后把结果 JSON POST 到 ASRConfig.webhook_url，在独立任务中执行，
+// 失败按退避重试有限次数，最终失败只记录日志，不影响返回给客户端的结果
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use super::config::ASRConfig;
+
+/// 单次回调请求超时
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 最多尝试次数 (含首次)
+const WEBHOOK_MAX_ATTEMPTS: u32 = 3;
+
+/// 首次重试前的等待时间，之后每次翻倍
+const WEBHOOK_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// 签名 header，值为 `sha256=<请求体 HMAC-SHA256 的十六进制>`
+pub const SIGNATURE_HEADER: &str = "X-Smart-Workflow-Signature";
+
+/// 配置了回调地址时，异步投递转录结果
+pub fn notify(asr_config: &ASRConfig, payload: &serde_json::Value) {
+    let Some(url) = asr_config.webhook_url.clone() else {
+        return;
+    };
+    let secret = asr_config.webhook_secret.clone();
+    let body = payload.to_string();
+    tokio::spawn(async move {
+        deliver(client(), &url, secret.as_deref(), body, WEBHOOK_RETRY_BACKOFF).await;
+    });
+}
+
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// 投递回调，返回是否成功
+async fn deliver(
+    client: &reqwest::Client,
+    url: &str,
+    secret: Option<&str>,
+    body: String,
+    backoff: Duration,
+) -> bool {
+    let signature = secret.map(|secret| sign(secret, body.as_bytes()));
+    let mut delay = backoff;
+
+    for attempt in 1..=WEBHOOK_MAX_ATTEMPTS {
+        let mut request = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone());
+        if let Some(ref signature) = signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+
+        let (error, retryable) = match request.send().await {
+            Ok(response) if response.status().is_success() => return true,
+            Ok(response) => {
+                let status = response.status();
+                (format!("HTTP {}", status), status.is_server_error() || status.as_u16() == 429)
+            }
+            Err(e) => (e.to_string(), true),
+        };
+
+        if !retryable || attempt == WEBHOOK_MAX_ATTEMPTS {
+            eprintln!("[WARN] [Voice] webhook 回调失败 (第 {} 次，放弃): {}: {}", attempt, url, error);
+            return false;
+        }
+        eprintln!("[WARN] [Voice] webhook 回调失败 (第 {} 次，{}ms 后重试): {}: {}", attempt, delay.as_millis(), url, error);
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+    }
+    false
+}
+
+/// 请求体签名
+fn sign(secret: &str, body: &[u8]) -> String {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = ring::hmac::sign(&key, body);
+    let hex: String = tag.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// 读取一个完整的 HTTP 请求 (头部 + Content-Length 指定的请求体)
+    async fn read_request(stream: &mut tokio::net::TcpStream) -> String {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 1024];
+        loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            data.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&data).to_string();
+            if let Some(header_end) = text.find("\r\n\r\n") {
+                let length = text[..header_end]
+                    .lines()
+                    .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().to_string()))
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or(0);
+                if data.len() >= header_end + 4 + length || n == 0 {
+                    return text;
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deliver_retries_and_signs() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        // 第一次返回 500，第二次返回 200
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for status in ["500 Internal Server Error", "200 OK"] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                requests.push(read_request(&mut stream).await);
+                let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+
+        let body = r#"{"text":"你好"}"#.to_string();
+        let client = reqwest::Client::new();
+        assert!(deliver(&client, &url, Some("secret"), body.clone(), Duration::from_millis(10)).await);
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 2);
+        let signature = sign("secret", body.as_bytes());
+        assert!(requests[1].to_ascii_lowercase().contains(&format!("{}: {}", SIGNATURE_HEADER.to_ascii_lowercase(), signature)));
+        assert!(requests[1].ends_with(&body));
+    }
+
+    #[tokio::test]
+    async fn test_deliver_gives_up_on_client_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            read_request(&mut stream).await;
+            stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await.unwrap();
+        });
+
+        let client = reqwest::Client::new();
+        assert!(!deliver(&client, &url, None, "{}".to_string(), Duration::from_millis(10)).await);
+        server.await.unwrap();
+    }
+}
@@ -776,6 +776,8 @@ export const en: TranslationKeys = {
     permissionDenied: 'Microphone permission denied. Please allow access in system settings',
     deviceError: 'Audio device error',
     alreadyRecording: 'Already recording',
+    notRecording: 'Not recording',
+    busyTranscribing: 'Transcription in progress. Please wait for it to finish',
     // ASR errors
     asrNetworkError: 'ASR network error. Please check your network connection',
     asrAuthFailed: 'ASR authentication failed. Please check your API Key',
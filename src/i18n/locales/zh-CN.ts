@@ -776,6 +776,8 @@ export const zhCN: TranslationKeys = {
     permissionDenied: '麦克风权限被拒绝，请在系统设置中允许访问',
     deviceError: '音频设备错误',
     alreadyRecording: '已在录音中',
+    notRecording: '未在录音中',
+    busyTranscribing: '转录进行中，请等待当前转录完成',
     // ASR 错误
     asrNetworkError: 'ASR 网络错误，请检查网络连接',
     asrAuthFailed: 'ASR 认证失败，请检查 API Key 配置',
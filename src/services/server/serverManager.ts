@@ -27,6 +27,11 @@ import { VoiceClient } from './voiceClient';
 import { LLMClient } from './llmClient';
 import { UtilsClient } from './utilsClient';
 
+/**
+ * 客户端支持的协议版本 (Sec-WebSocket-Protocol，服务器选择其中最高的版本)
+ */
+const PROTOCOL_VERSIONS = ['sw-voice.v1'];
+
 /**
  * 事件监听器类型
  */
@@ -501,7 +506,7 @@ export class ServerManager {
       const wsUrl = `ws://127.0.0.1:${this.port}`;
       debugLog('[ServerManager] 连接 WebSocket:', wsUrl);
       
-      this.ws = new WebSocket(wsUrl);
+      this.ws = new WebSocket(wsUrl, PROTOCOL_VERSIONS);
       
       const timeout = setTimeout(() => {
         this.wsConnectPromise = null;
@@ -513,7 +518,7 @@ export class ServerManager {
 
       this.ws.onopen = () => {
         clearTimeout(timeout);
-        debugLog('[ServerManager] WebSocket 已连接');
+        debugLog('[ServerManager] WebSocket 已连接，协议版本:', this.ws?.protocol);
         
         // 重置重连计数
         this.wsReconnectAttempts = 0;
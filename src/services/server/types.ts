@@ -160,10 +160,16 @@ export type {
   RecordingMode,
   RecordingStateMessage,
   AudioLevelMessage,
+  RecordingStatsMessage,
   TranscriptionProgressMessage,
   TranscriptionCompleteMessage,
+  TranscriptionHistoryItem,
+  VoiceUsage,
+  VoiceQuota,
 } from '../voice/types';
 
+import type { TranscriptionHistoryItem, VoiceUsage, VoiceQuota } from '../voice/types';
+
 /**
  * Voice 事件映射
  */
@@ -172,12 +178,18 @@ export interface VoiceEvents {
   'recording-state': (state: 'started' | 'stopped' | 'cancelled') => void;
   /** 音频级别 */
   'audio-level': (level: number, waveform: number[]) => void;
+  /** 录音统计 (时长与估算字数) */
+  'recording-stats': (elapsedMs: number, estimatedChars?: number) => void;
   /** 转录进度 */
   'transcription-progress': (text: string) => void;
   /** 转录完成 */
   'transcription-complete': (text: string, engine: string, usedFallback: boolean, durationMs: number) => void;
+  /** 转录历史 (由新到旧) */
+  'history': (items: TranscriptionHistoryItem[]) => void;
+  /** 连接用量与配额 */
+  'usage': (usage: VoiceUsage, quota: VoiceQuota) => void;
   /** 错误 */
-  'error': (code: string, message: string) => void;
+  'error': (code: string, message: string, retryable?: boolean, suggestion?: string) => void;
 }
 
 // ============================================================================
@@ -5,7 +5,7 @@
  */
 
 import { ModuleClient } from './moduleClient';
-import { VoiceEvents, ServerMessage, ASRConfig, RecordingMode } from './types';
+import { VoiceEvents, ServerMessage, ASRConfig, RecordingMode, TranscriptionHistoryItem, VoiceUsage, VoiceQuota } from './types';
 import { debugLog } from '../../utils/logger';
 
 /**
@@ -57,6 +57,22 @@ export class VoiceClient extends ModuleClient {
     });
   }
 
+  /**
+   * 查询最近的转录历史，结果通过 history 事件返回
+   * 
+   * @param limit 最多返回条数，缺省时返回全部
+   */
+  getHistory(limit?: number): void {
+    this.send('get_history', limit === undefined ? {} : { limit });
+  }
+
+  /**
+   * 查询本连接的用量与配额，结果通过 usage 事件返回
+   */
+  getUsage(): void {
+    this.send('get_usage', {});
+  }
+
   /**
    * 注册录音状态处理器
    */
@@ -71,6 +87,13 @@ export class VoiceClient extends ModuleClient {
     return this.on('audio-level', handler);
   }
 
+  /**
+   * 注册录音统计处理器
+   */
+  onRecordingStats(handler: VoiceEvents['recording-stats']): () => void {
+    return this.on('recording-stats', handler);
+  }
+
   /**
    * 注册转录进度处理器
    */
@@ -85,6 +108,20 @@ export class VoiceClient extends ModuleClient {
     return this.on('transcription-complete', handler);
   }
 
+  /**
+   * 注册转录历史处理器
+   */
+  onHistory(handler: VoiceEvents['history']): () => void {
+    return this.on('history', handler);
+  }
+
+  /**
+   * 注册用量处理器
+   */
+  onUsage(handler: VoiceEvents['usage']): () => void {
+    return this.on('usage', handler);
+  }
+
   /**
    * 注册错误处理器
    */
@@ -141,6 +178,10 @@ export class VoiceClient extends ModuleClient {
         this.emit('audio-level', msg.level as number, msg.waveform as number[]);
         break;
         
+      case 'recording_stats':
+        this.emit('recording-stats', msg.elapsed_ms as number, msg.estimated_chars as number | undefined);
+        break;
+        
       case 'transcription_progress':
         this.emit('transcription-progress', msg.partial_text as string);
         break;
@@ -155,8 +196,22 @@ export class VoiceClient extends ModuleClient {
         );
         break;
         
+      case 'history':
+        this.emit('history', msg.items as TranscriptionHistoryItem[]);
+        break;
+        
+      case 'usage':
+        this.emit('usage', msg.usage as VoiceUsage, msg.quota as VoiceQuota);
+        break;
+        
       case 'error':
-        this.emit('error', msg.code as string, msg.message as string);
+        this.emit(
+          'error',
+          msg.code as string,
+          msg.message as string,
+          msg.retryable as boolean | undefined,
+          msg.suggestion as string | undefined
+        );
         break;
     }
   }
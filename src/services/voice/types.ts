@@ -108,6 +108,22 @@ export interface UpdateConfigMessage {
   asr_config: ASRConfig;
 }
 
+/**
+ * 查询转录历史消息
+ */
+export interface GetHistoryMessage {
+  type: 'get_history';
+  /** 最多返回条数，缺省时返回全部 */
+  limit?: number;
+}
+
+/**
+ * 查询连接用量消息
+ */
+export interface GetUsageMessage {
+  type: 'get_usage';
+}
+
 /**
  * 客户端发送的消息联合类型
  */
@@ -115,7 +131,9 @@ export type ClientMessage =
   | StartRecordingMessage 
   | StopRecordingMessage 
   | CancelRecordingMessage 
-  | UpdateConfigMessage;
+  | UpdateConfigMessage
+  | GetHistoryMessage
+  | GetUsageMessage;
 
 // ============================================================================
 // WebSocket 消息类型 (服务器 → 客户端)
@@ -140,12 +158,25 @@ export interface AudioLevelMessage {
   waveform: number[];
 }
 
+/**
+ * 录音统计消息 (录音期间周期性发送)
+ */
+export interface RecordingStatsMessage {
+  type: 'recording_stats';
+  /** 已录音时长 (ms) */
+  elapsed_ms: number;
+  /** 按实时 partial 估算的字数，非实时模式时缺失 */
+  estimated_chars?: number;
+}
+
 /**
  * 转录进度消息 (实时模式)
  */
 export interface TranscriptionProgressMessage {
   type: 'transcription_progress';
   partial_text: string;
+  /** 相对上次 partial 新增的文本，为 null 时需用 partial_text 整段替换 */
+  delta?: string | null;
 }
 
 /**
@@ -157,6 +188,76 @@ export interface TranscriptionCompleteMessage {
   engine: string;
   used_fallback: boolean;
   duration_ms: number;
+  /** 相对最后一次 partial 新增的文本，为 null 或缺失时需用 text 整段替换 */
+  delta?: string | null;
+  /** 各阶段耗时分解 */
+  timings?: TranscriptionTimings;
+  /** 按段落拼装的整篇文稿 (长文本分出多个段落时才有) */
+  document?: string;
+}
+
+/**
+ * 转录各阶段耗时 (ms)
+ */
+export interface TranscriptionTimings {
+  /** 录音时长 */
+  recording_ms: number;
+  /** 音频编码耗时 */
+  encoding_ms: number;
+  /** 网络与推理耗时 */
+  network_ms: number;
+  /** 后处理耗时 */
+  post_process_ms: number;
+}
+
+/**
+ * 单条转录历史
+ */
+export interface TranscriptionHistoryItem {
+  text: string;
+  format: 'text' | 'markdown';
+  engine: string;
+  used_fallback: boolean;
+  duration_ms: number;
+  timings: TranscriptionTimings;
+  /** 完成时间 (Unix ms) */
+  created_at: number;
+}
+
+/**
+ * 转录历史消息 (由新到旧)
+ */
+export interface HistoryMessage {
+  type: 'history';
+  items: TranscriptionHistoryItem[];
+}
+
+/**
+ * 连接用量统计
+ */
+export interface VoiceUsage {
+  /** 已转录音频时长 (ms) */
+  audio_ms: number;
+  requests: number;
+  chars: number;
+}
+
+/**
+ * 连接配额 (缺省字段表示不限制)
+ */
+export interface VoiceQuota {
+  max_audio_ms?: number;
+  max_requests?: number;
+  max_chars?: number;
+}
+
+/**
+ * 用量消息
+ */
+export interface UsageMessage {
+  type: 'usage';
+  usage: VoiceUsage;
+  quota: VoiceQuota;
 }
 
 /**
@@ -166,6 +267,20 @@ export interface VoiceErrorMessage {
   type: 'error';
   code: string;
   message: string;
+  /** 原样重试是否可能成功 (仅转录错误携带) */
+  retryable?: boolean;
+  /** 建议用户采取的动作 */
+  suggestion?: string;
+}
+
+/**
+ * 警告消息 (不中断录音/转录)
+ */
+export interface VoiceWarningMessage {
+  type: 'warning';
+  code: string;
+  message: string;
+  [key: string]: unknown;
 }
 
 /**
@@ -174,9 +289,13 @@ export interface VoiceErrorMessage {
 export type ServerMessage = 
   | RecordingStateMessage 
   | AudioLevelMessage 
+  | RecordingStatsMessage
   | TranscriptionProgressMessage 
   | TranscriptionCompleteMessage 
-  | VoiceErrorMessage;
+  | HistoryMessage
+  | UsageMessage
+  | VoiceErrorMessage
+  | VoiceWarningMessage;
 
 // ============================================================================
 // 悬浮窗状态类型
@@ -243,6 +362,8 @@ export enum VoiceErrorCode {
   PERMISSION_DENIED = 'PERMISSION_DENIED',
   DEVICE_ERROR = 'DEVICE_ERROR',
   ALREADY_RECORDING = 'ALREADY_RECORDING',
+  NOT_RECORDING = 'NOT_RECORDING',
+  BUSY_TRANSCRIBING = 'BUSY_TRANSCRIBING',
   
   // ASR 错误
   ASR_NETWORK_ERROR = 'ASR_NETWORK_ERROR',
@@ -332,6 +332,10 @@ export class VoiceErrorHandler {
         return t('voiceError.deviceError') || '音频设备错误';
       case VoiceErrorCode.ALREADY_RECORDING:
         return t('voiceError.alreadyRecording') || '已在录音中';
+      case VoiceErrorCode.NOT_RECORDING:
+        return t('voiceError.notRecording') || '未在录音中';
+      case VoiceErrorCode.BUSY_TRANSCRIBING:
+        return t('voiceError.busyTranscribing') || '转录进行中，请等待当前转录完成';
       case VoiceErrorCode.ASR_NETWORK_ERROR:
         return t('voiceError.asrNetworkError') || 'ASR 网络错误，请检查网络连接';
       case VoiceErrorCode.ASR_AUTH_FAILED: